@@ -19,6 +19,18 @@ pub trait API: Sync + Send {
     /// Provides a list of models available in the current environment
     async fn models(&self) -> Result<Vec<Model>>;
 
+    /// Provides a table of discovered local models with their provider,
+    /// availability and health, or `None` when none were discovered
+    async fn model_summary(&self) -> Result<Option<String>>;
+
+    /// Provides the full health state of every monitored local provider as
+    /// JSON, or `None` when local model discovery is unavailable
+    async fn health_dump(&self) -> Result<Option<String>>;
+
+    /// Saves the performance metrics gathered by this process; called on
+    /// clean exit
+    async fn flush_performance_metrics(&self) -> Result<()>;
+
     /// Executes a chat request and returns a stream of responses
     async fn chat(&self, chat: ChatRequest) -> Result<MpscStream<Result<ChatResponse>>>;
 
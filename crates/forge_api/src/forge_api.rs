@@ -52,6 +52,20 @@ impl<A: Services, F: CommandInfra> API for ForgeAPI<A, F> {
         Ok(self.services.models(provider, app_config).await?)
     }
 
+    async fn model_summary(&self) -> Result<Option<String>> {
+        let app_config = self.app_config().await.unwrap_or_default();
+        self.services.model_summary(app_config).await
+    }
+
+    async fn health_dump(&self) -> Result<Option<String>> {
+        let app_config = self.app_config().await.unwrap_or_default();
+        self.services.health_dump(app_config).await
+    }
+
+    async fn flush_performance_metrics(&self) -> Result<()> {
+        self.services.flush_performance_metrics().await
+    }
+
     async fn chat(
         &self,
         chat: ChatRequest,
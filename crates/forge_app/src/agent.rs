@@ -50,8 +50,10 @@ impl<T: Services> AgentService for T {
         context: Context,
     ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
         let config = self.read_app_config().await.unwrap_or_default();
-        let provider = self.get_provider(config).await?;
-        self.chat(id, context, provider).await
+        let (provider, model) = self
+            .get_provider_for(config, id, context.token_count())
+            .await?;
+        self.chat(&model, context, provider).await
     }
 
     async fn call(
@@ -379,10 +379,14 @@ impl<S: AgentService> Orchestrator<S> {
             self.conversation.context = Some(context.clone());
             self.services.update(self.conversation.clone()).await?;
 
+            // Every retry of this request carries the same idempotency key, so
+            // providers can deduplicate an attempt that already succeeded
+            let request_context = context.clone().with_new_idempotency_key();
+
             // Run the main chat request and compaction check in parallel
             let main_request = crate::retry::retry_with_config(
                 &self.environment.retry_config,
-                || self.execute_chat_turn(&model_id, context.clone(), tool_supported, reasoning_supported),
+                || self.execute_chat_turn(&model_id, request_context.clone(), tool_supported, reasoning_supported),
                 self.sender.as_ref().map(|sender| {
                     let sender = sender.clone();
                     let agent_id = agent.id.clone();
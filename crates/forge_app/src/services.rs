@@ -94,6 +94,15 @@ pub trait ProviderService: Send + Sync {
     ) -> ResultStream<ChatCompletionMessage, anyhow::Error>;
     async fn models(&self, provider: Provider, app_config: AppConfig)
     -> anyhow::Result<Vec<Model>>;
+    /// Table of discovered local models with their provider, availability
+    /// and health, or `None` when no local models were discovered
+    async fn model_summary(&self, app_config: AppConfig) -> anyhow::Result<Option<String>>;
+    /// Health state of every monitored local provider as JSON, or `None`
+    /// when local model discovery is unavailable
+    async fn health_dump(&self, app_config: AppConfig) -> anyhow::Result<Option<String>>;
+    /// Save the performance metrics gathered by this process so later runs
+    /// start from them
+    async fn flush_performance_metrics(&self) -> anyhow::Result<()>;
 }
 
 #[async_trait::async_trait]
@@ -292,6 +301,19 @@ pub trait AuthService: Send + Sync {
 #[async_trait::async_trait]
 pub trait ProviderRegistry: Send + Sync {
     async fn get_provider(&self, config: AppConfig) -> anyhow::Result<Provider>;
+
+    /// Provider to send a request for `model` with a prompt of about
+    /// `prompt_tokens` tokens to, and the model to ask it for, which differs
+    /// from `model` when selection substitutes another one. Defaults to
+    /// [`Self::get_provider`] and `model`.
+    async fn get_provider_for(
+        &self,
+        config: AppConfig,
+        model: &ModelId,
+        _prompt_tokens: usize,
+    ) -> anyhow::Result<(Provider, ModelId)> {
+        Ok((self.get_provider(config).await?, model.clone()))
+    }
 }
 
 /// Core app trait providing access to services and repositories.
@@ -384,6 +406,18 @@ impl<I: Services> ProviderService for I {
     ) -> anyhow::Result<Vec<Model>> {
         self.provider_service().models(provider, app_config).await
     }
+
+    async fn model_summary(&self, app_config: AppConfig) -> anyhow::Result<Option<String>> {
+        self.provider_service().model_summary(app_config).await
+    }
+
+    async fn health_dump(&self, app_config: AppConfig) -> anyhow::Result<Option<String>> {
+        self.provider_service().health_dump(app_config).await
+    }
+
+    async fn flush_performance_metrics(&self) -> anyhow::Result<()> {
+        self.provider_service().flush_performance_metrics().await
+    }
 }
 
 #[async_trait::async_trait]
@@ -579,6 +613,17 @@ impl<I: Services> ProviderRegistry for I {
     async fn get_provider(&self, config: AppConfig) -> anyhow::Result<Provider> {
         self.provider_registry().get_provider(config).await
     }
+
+    async fn get_provider_for(
+        &self,
+        config: AppConfig,
+        model: &ModelId,
+        prompt_tokens: usize,
+    ) -> anyhow::Result<(Provider, ModelId)> {
+        self.provider_registry()
+            .get_provider_for(config, model, prompt_tokens)
+            .await
+    }
 }
 
 #[async_trait::async_trait]
@@ -7,7 +7,10 @@ use super::{ToolCallFull, ToolResult};
 use crate::temperature::Temperature;
 use crate::top_k::TopK;
 use crate::top_p::TopP;
-use crate::{ConversationId, Image, ModelId, ReasoningFull, ToolChoice, ToolDefinition, ToolValue};
+use crate::{
+    ConversationId, IdempotencyKey, Image, ModelId, ReasoningFull, ToolChoice, ToolDefinition,
+    ToolValue,
+};
 
 /// Represents a message being sent to the LLM provider
 /// NOTE: ToolResults message are part of the larger Request object and not part
@@ -253,9 +256,19 @@ pub struct Context {
     pub top_k: Option<TopK>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub reasoning: Option<crate::agent::ReasoningConfig>,
+    /// Key identifying this logical request across retries, so providers that
+    /// support it can deduplicate a retried request instead of charging twice
+    #[serde(skip)]
+    pub idempotency_key: Option<IdempotencyKey>,
 }
 
 impl Context {
+    /// Mark this context as a new logical request with a fresh idempotency
+    /// key. Retries should resend the returned context unchanged.
+    pub fn with_new_idempotency_key(self) -> Self {
+        self.idempotency_key(IdempotencyKey::generate())
+    }
+
     pub fn add_base64_url(mut self, image: Image) -> Self {
         self.messages.push(ContextMessage::Image(image));
         self
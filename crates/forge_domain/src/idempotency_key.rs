@@ -0,0 +1,48 @@
+use std::fmt;
+
+/// Key identifying one logical request across retries.
+///
+/// A fresh random key is generated for each logical request and reused for
+/// every retry of it, so the upstream provider can deduplicate a retry instead
+/// of charging twice. Two separate requests with the same body, such as a
+/// regenerated answer, get different keys.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IdempotencyKey(String);
+
+impl IdempotencyKey {
+    /// Generate the key for a new logical request
+    pub fn generate() -> Self {
+        Self(format!("forge-{}", uuid::Uuid::new_v4().simple()))
+    }
+
+    /// Create a key from an existing value, e.g. one supplied by the caller
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for IdempotencyKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_ne;
+
+    use super::*;
+
+    #[test]
+    fn test_each_logical_request_gets_its_own_key() {
+        let actual = IdempotencyKey::generate();
+
+        let expected = IdempotencyKey::generate();
+        assert_ne!(actual, expected);
+        assert!(actual.as_str().starts_with("forge-"));
+    }
+}
@@ -13,6 +13,7 @@ mod error;
 mod event;
 mod file;
 mod http_config;
+mod idempotency_key;
 mod image;
 mod max_tokens;
 mod mcp;
@@ -61,6 +62,7 @@ pub use error::*;
 pub use event::*;
 pub use file::*;
 pub use http_config::*;
+pub use idempotency_key::*;
 pub use image::*;
 pub use max_tokens::*;
 pub use mcp::*;
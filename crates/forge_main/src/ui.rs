@@ -313,7 +313,10 @@ impl<A: API + 'static, F: Fn() -> A> UI<A, F> {
                 self.handle_local_model_list().await?;
                 Ok(false)
             }
-            Command::Exit => Ok(true),
+            Command::Exit => {
+                self.on_exit().await;
+                Ok(true)
+            }
             _ => {
                 if offline_mode {
                     self.writeln(TitleFormat::error(
@@ -513,6 +516,7 @@ impl<A: API + 'static, F: Fn() -> A> UI<A, F> {
                 on_update(self.api.clone(), None).await;
             }
             Command::Exit => {
+                self.on_exit().await;
                 return Ok(true);
             }
 
@@ -923,59 +927,31 @@ impl<A: API + 'static, F: Fn() -> A> UI<A, F> {
         Ok(())
     }
 
-    /// Handle model health command
-    async fn on_model_health(&mut self) -> Result<()> {
-        self.writeln(TitleFormat::action(
-            "Checking health status of all providers...",
-        ))?;
-
-        // TODO: Integrate with ModelDiscoveryService health monitoring
-        // For now, we'll provide basic health information
-        let models = self.get_models().await?;
-
-        if models.is_empty() {
-            self.writeln(TitleFormat::error(
-                "No providers available for health check",
-            ))?;
-            self.writeln("Run '/model discover' to find available providers")?;
-            return Ok(());
+    /// Flush state that should outlive the process before a clean exit
+    async fn on_exit(&self) {
+        if let Err(error) = self.api.flush_performance_metrics().await {
+            tracing::warn!(error = ?error, "Failed to save performance metrics");
         }
+    }
 
-        self.writeln(TitleFormat::action("Provider Health Status:"))?;
-
-        // Basic health check by trying to fetch models
-        let providers = ["OpenAI", "Anthropic", "Ollama"];
-
-        for provider in providers {
-            let provider_models: Vec<_> = models
-                .iter()
-                .filter(|model| match provider {
-                    "OpenAI" => {
-                        model.id.as_str().contains("gpt") || model.id.as_str().contains("openai")
-                    }
-                    "Anthropic" => {
-                        model.id.as_str().contains("claude")
-                            || model.id.as_str().contains("anthropic")
-                    }
-                    "Ollama" => {
-                        model.id.as_str().contains("ollama") || model.id.as_str().contains("llama")
-                    }
-                    _ => false,
-                })
-                .collect();
+    /// Handle model health command
+    async fn on_model_health(&mut self) -> Result<()> {
+        self.writeln(TitleFormat::action("Provider Health Status"))?;
 
-            if !provider_models.is_empty() {
-                self.writeln(format!(
-                    "✅ {}: Healthy ({} models available)",
-                    provider,
-                    provider_models.len()
+        match self.api.health_dump().await {
+            Ok(Some(dump)) => self.writeln(dump)?,
+            Ok(None) => {
+                self.writeln(TitleFormat::info(
+                    "Local model discovery is unavailable, so no provider health was recorded",
                 ))?;
-            } else {
-                self.writeln(format!("❌ {provider}: No models available"))?;
+            }
+            Err(e) => {
+                self.writeln(TitleFormat::error(format!(
+                    "Failed to check provider health: {e}"
+                )))?;
             }
         }
 
-        self.writeln(format!("\nTotal models available: {}", models.len()))?;
         self.writeln("Use '/model refresh' to update health status")?;
         Ok(())
     }
@@ -1362,50 +1338,23 @@ impl<A: API + 'static, F: Fn() -> A> UI<A, F> {
     }
 
     async fn handle_local_model_list(&mut self) -> Result<()> {
-        self.writeln(TitleFormat::title("Local AI Models"))?;
-        
-        if let Some(provider) = &self.state.provider {
-            let app_config = AppConfig::default(); // Use default config for local operations
-            match self.api.models(provider.clone(), app_config).await {
-                Ok(models) => {
-                    if models.is_empty() {
-                        self.writeln(TitleFormat::warning(
-                            "No local models found. Make sure Ollama is running and has models installed.\n\
-                            Visit https://ollama.ai for installation instructions."
-                        ))?;
-                    } else {
-                        // Filter to show only local models
-                        let local_models: Vec<_> = models.iter()
-                            .filter(|m| m.description.as_ref().map_or(false, |d| d.contains("Local")))
-                            .collect();
-                            
-                        if local_models.is_empty() {
-                            self.writeln(TitleFormat::info(
-                                "No local models detected. Available models are from cloud providers."
-                            ))?;
-                        } else {
-                            for model in local_models {
-                                self.writeln(format!(
-                                    "• {} - {}",
-                                    TitleFormat::title(&model.name),
-                                    model.description.as_deref().unwrap_or("No description")
-                                ))?;
-                            }
-                            self.writeln(format!("\nFound {} local model(s)", local_models.len()))?;
-                        }
-                    }
-                }
-                Err(e) => {
-                    self.writeln(TitleFormat::error(&format!(
-                        "Failed to list local models: {}. Make sure Ollama is running on http://localhost:11434",
-                        e
-                    )))?;
-                }
+        self.writeln(TitleFormat::action("Local AI Models"))?;
+
+        match self.api.model_summary().await {
+            Ok(Some(summary)) => self.writeln(summary)?,
+            Ok(None) => {
+                self.writeln(TitleFormat::info(
+                    "No local models found. Make sure Ollama is running and has models installed.\n\
+                    Visit https://ollama.ai for installation instructions."
+                ))?;
+            }
+            Err(e) => {
+                self.writeln(TitleFormat::error(format!(
+                    "Failed to list local models: {e}. Make sure Ollama is running on http://localhost:11434"
+                )))?;
             }
-        } else {
-            self.writeln(TitleFormat::error("Local provider not initialized"))?;
         }
-        
+
         Ok(())
     }
 }
@@ -0,0 +1,230 @@
+//! Circuit breaker for provider calls
+//!
+//! A breaker opens after a run of consecutive failures and refuses calls
+//! until a cooldown has elapsed. It then lets a single probe through in the
+//! half-open state: a successful probe closes the breaker, a failed one opens
+//! it again. A probe whose outcome is never recorded, for example because the
+//! selected provider was not called after all, is released after a timeout.
+
+use std::time::{Duration, Instant};
+
+use derive_setters::Setters;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Configuration for a [`CircuitBreaker`]
+#[derive(Debug, Clone, Serialize, Deserialize, Setters)]
+#[setters(strip_option, into)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the breaker
+    pub failure_threshold: u32,
+    /// How long the breaker stays open before allowing a probe
+    pub cooldown: Duration,
+    /// How long a half-open probe may go without an outcome before another
+    /// probe is allowed
+    #[serde(default = "default_probe_timeout")]
+    pub probe_timeout: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(30),
+            probe_timeout: default_probe_timeout(),
+        }
+    }
+}
+
+fn default_probe_timeout() -> Duration {
+    Duration::from_secs(60)
+}
+
+/// State of a circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls flow normally
+    Closed,
+    /// Calls are refused until the cooldown elapses
+    Open,
+    /// A single probe call is allowed to test recovery
+    HalfOpen,
+}
+
+/// Tracks consecutive failures for one provider and decides whether calls
+/// may proceed
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// When the current half-open probe was granted
+    probe_started_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+            probe_started_at: None,
+        }
+    }
+
+    /// Current state, treating an open breaker whose cooldown has elapsed as
+    /// half-open
+    pub fn state_at(&self, now: Instant) -> CircuitState {
+        match (self.state, self.opened_at) {
+            (CircuitState::Open, Some(opened_at))
+                if now.saturating_duration_since(opened_at) >= self.config.cooldown =>
+            {
+                CircuitState::HalfOpen
+            }
+            (state, _) => state,
+        }
+    }
+
+    /// Current state
+    pub fn state(&self) -> CircuitState {
+        self.state_at(Instant::now())
+    }
+
+    /// Whether a call would be allowed at `now`, without reserving the probe
+    pub fn is_available_at(&self, now: Instant) -> bool {
+        match self.state_at(now) {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen => !self.probe_in_flight_at(now),
+        }
+    }
+
+    /// Whether a granted probe is still awaiting its outcome at `now`
+    fn probe_in_flight_at(&self, now: Instant) -> bool {
+        self.probe_started_at.is_some_and(|started_at| {
+            now.saturating_duration_since(started_at) < self.config.probe_timeout
+        })
+    }
+
+    /// Reserve permission for a call at `now`. In the half-open state only
+    /// one probe is granted until its outcome is recorded or it times out.
+    pub fn try_acquire_at(&mut self, now: Instant) -> bool {
+        match self.state_at(now) {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen if self.probe_in_flight_at(now) => false,
+            CircuitState::HalfOpen => {
+                self.state = CircuitState::HalfOpen;
+                self.probe_started_at = Some(now);
+                true
+            }
+        }
+    }
+
+    /// Record a successful call, closing the breaker
+    pub fn record_success(&mut self) {
+        if self.state != CircuitState::Closed {
+            info!("Circuit breaker closed after successful probe");
+        }
+        self.state = CircuitState::Closed;
+        self.consecutive_failures = 0;
+        self.opened_at = None;
+        self.probe_started_at = None;
+    }
+
+    /// Record a failed call at `now`, opening the breaker once the failure
+    /// threshold is reached or when a half-open probe fails
+    pub fn record_failure_at(&mut self, now: Instant) {
+        self.consecutive_failures += 1;
+        self.probe_started_at = None;
+
+        let reopen = self.state_at(now) == CircuitState::HalfOpen;
+        if reopen || self.consecutive_failures >= self.config.failure_threshold {
+            if self.state == CircuitState::Closed {
+                warn!(
+                    failures = self.consecutive_failures,
+                    cooldown_ms = self.config.cooldown.as_millis() as u64,
+                    "Circuit breaker opened"
+                );
+            }
+            self.state = CircuitState::Open;
+            self.opened_at = Some(now);
+        }
+    }
+
+    /// Consecutive failures recorded since the last success
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fixture() -> CircuitBreaker {
+        CircuitBreaker::new(
+            CircuitBreakerConfig::default()
+                .failure_threshold(2u32)
+                .cooldown(Duration::from_secs(10))
+                .probe_timeout(Duration::from_secs(20)),
+        )
+    }
+
+    #[test]
+    fn test_opens_after_threshold_and_probes_after_cooldown() {
+        let mut fixture = fixture();
+        let start = Instant::now();
+
+        fixture.record_failure_at(start);
+        assert_eq!(fixture.state_at(start), CircuitState::Closed);
+        fixture.record_failure_at(start);
+        assert_eq!(fixture.state_at(start), CircuitState::Open);
+        assert!(!fixture.try_acquire_at(start + Duration::from_secs(5)));
+
+        let later = start + Duration::from_secs(10);
+        assert_eq!(fixture.state_at(later), CircuitState::HalfOpen);
+        assert!(fixture.try_acquire_at(later));
+        assert!(!fixture.try_acquire_at(later));
+
+        fixture.record_success();
+        assert_eq!(fixture.state_at(later), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_failed_probe_reopens() {
+        let mut fixture = fixture();
+        let start = Instant::now();
+        fixture.record_failure_at(start);
+        fixture.record_failure_at(start);
+
+        let later = start + Duration::from_secs(10);
+        assert!(fixture.try_acquire_at(later));
+        fixture.record_failure_at(later);
+
+        assert_eq!(fixture.state_at(later), CircuitState::Open);
+        assert!(!fixture.is_available_at(later + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_unreported_probe_is_released_after_timeout() {
+        let mut fixture = fixture();
+        let start = Instant::now();
+        fixture.record_failure_at(start);
+        fixture.record_failure_at(start);
+        let probe_at = start + Duration::from_secs(10);
+        assert!(fixture.try_acquire_at(probe_at));
+
+        let before_timeout = probe_at + Duration::from_secs(19);
+        assert!(!fixture.is_available_at(before_timeout));
+        assert!(!fixture.try_acquire_at(before_timeout));
+
+        let after_timeout = probe_at + Duration::from_secs(20);
+        assert!(fixture.is_available_at(after_timeout));
+        assert!(fixture.try_acquire_at(after_timeout));
+    }
+}
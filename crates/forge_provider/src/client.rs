@@ -5,22 +5,81 @@ use std::sync::Arc;
 
 use anyhow::{Context as _, Result};
 use forge_app::domain::{
-    ChatCompletionMessage, Context, HttpConfig, Model, ModelId, Provider, ResultStream, RetryConfig,
+    BoxStream, ChatCompletionMessage, Context, FinishReason, HttpConfig, Model, ModelId, Provider,
+    ResultStream, RetryConfig, Usage,
 };
 use reqwest::redirect::Policy;
 use tokio::sync::RwLock;
-use tokio_stream::StreamExt;
+use tokio_stream::{Stream, StreamExt};
+use tokio_util::sync::CancellationToken;
 
 use crate::anthropic::Anthropic;
+use crate::continuation::StreamContinuation;
+use crate::error::Error;
 use crate::forge_provider::ForgeProvider;
+use crate::idempotency::IdempotencyKey;
 use crate::ollama::Ollama;
-use crate::retry::into_retry;
+use crate::performance::{
+    AdmissionController, IdleEvictionPolicy, ModelLoadingOptimizer, PerformanceMeasurement,
+    PerformanceMonitor, RequestType, WarmStandby, WarmStandbyConfig,
+};
+use crate::retry::{into_retry, is_retryable, retry_with, RetryPolicy};
+use crate::selection::ConcurrencyLimits;
+use crate::timing::{ConnectionTimer, RequestTiming, TimedRequest};
 
 #[derive(Clone)]
 pub struct Client {
     retry_config: Arc<RetryConfig>,
+    /// Backoff for individual requests such as listing models. Chat
+    /// requests are retried as a whole by the caller according to
+    /// `retry_config`, so they are only classified here.
+    retry_policy: RetryPolicy,
     inner: Arc<InnerClient>,
     models_cache: Arc<RwLock<HashMap<ModelId, Model>>>,
+    http: reqwest::Client,
+    provider: Provider,
+    /// Admission control for chat requests, with the name this client's
+    /// load is tracked under
+    admission: Option<(Arc<AdmissionController>, String)>,
+    /// Concurrency limits chat requests take a slot from, with the provider
+    /// name this client's requests count against
+    concurrency: Option<(ConcurrencyLimits, String)>,
+    /// Monitor chat requests are recorded in, with their network timing,
+    /// under the given provider name
+    performance: Option<(Arc<PerformanceMonitor>, String)>,
+    /// Optimizer chat requests count model usage in, under the given
+    /// provider name
+    model_usage: Option<(Arc<ModelLoadingOptimizer>, String)>,
+    /// Idle eviction policy chat requests keep their model loaded in, under
+    /// the given provider name
+    idle_eviction: Option<(Arc<IdleEvictionPolicy>, String)>,
+    /// Continuation for chat streams that fail part-way, with the client and
+    /// model the rest of the response is requested from
+    continuation: Option<(StreamContinuation, Arc<Client>, ModelId)>,
+}
+
+/// An incremental piece of a streamed chat response
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatChunk {
+    /// Text generated since the previous chunk
+    pub delta: String,
+    /// Why generation stopped, set on the final chunk
+    pub finish_reason: Option<FinishReason>,
+    /// Token usage, when the provider reports it
+    pub usage: Option<Usage>,
+}
+
+impl From<ChatCompletionMessage> for ChatChunk {
+    fn from(message: ChatCompletionMessage) -> Self {
+        Self {
+            delta: message
+                .content
+                .map(|content| content.as_str().to_string())
+                .unwrap_or_default(),
+            finish_reason: message.finish_reason,
+            usage: message.usage,
+        }
+    }
 }
 
 enum InnerClient {
@@ -36,7 +95,8 @@ impl Client {
         version: impl ToString,
         timeout_config: &HttpConfig,
     ) -> Result<Self> {
-        let client = reqwest::Client::builder()
+        let client = ConnectionTimer::new()
+            .instrument(reqwest::Client::builder())
             .connect_timeout(std::time::Duration::from_secs(
                 timeout_config.connect_timeout,
             ))
@@ -51,7 +111,7 @@ impl Client {
         let inner = match &provider {
             Provider::OpenAI { url, .. } => InnerClient::OpenAICompat(
                 ForgeProvider::builder()
-                    .client(client)
+                    .client(client.clone())
                     .provider(provider.clone())
                     .version(version.to_string())
                     .build()
@@ -60,7 +120,7 @@ impl Client {
 
             Provider::Anthropic { url, key } => InnerClient::Anthropic(
                 Anthropic::builder()
-                    .client(client)
+                    .client(client.clone())
                     .api_key(key.to_string())
                     .base_url(url.clone())
                     .anthropic_version("2023-06-01".to_string())
@@ -72,7 +132,7 @@ impl Client {
 
             Provider::Ollama { url } => InnerClient::Ollama(
                 Ollama::builder()
-                    .client(client)
+                    .client(client.clone())
                     .base_url(url.clone())
                     .build()
                     .with_context(|| {
@@ -84,21 +144,151 @@ impl Client {
         Ok(Self {
             inner: Arc::new(inner),
             retry_config,
+            retry_policy: RetryPolicy::default(),
             models_cache: Arc::new(RwLock::new(HashMap::new())),
+            http: client,
+            provider,
+            admission: None,
+            concurrency: None,
+            performance: None,
+            model_usage: None,
+            idle_eviction: None,
+            continuation: None,
         })
     }
 
+    /// Create a warm standby that keeps this client's pooled connection to a
+    /// cloud provider alive. Returns `None` for local providers, which do not
+    /// pay a TLS handshake on reconnect.
+    pub fn warm_standby(&self, config: WarmStandbyConfig) -> Option<WarmStandby> {
+        match self.inner.as_ref() {
+            InnerClient::Ollama(_) => None,
+            InnerClient::OpenAICompat(_) | InnerClient::Anthropic(_) => Some(
+                WarmStandby::new(self.http.clone(), config)
+                    .with_target(self.provider.to_base_url()),
+            ),
+        }
+    }
+
+    /// Responses that did not match the expected protocol, a sign the server
+    /// was upgraded to an incompatible API version
+    pub fn protocol_mismatch_count(&self) -> u64 {
+        match self.inner.as_ref() {
+            InnerClient::OpenAICompat(provider) => provider.protocol_mismatch_count(),
+            InnerClient::Ollama(provider) => provider.protocol_mismatch_count(),
+            InnerClient::Anthropic(_) => 0,
+        }
+    }
+
+    /// Set the backoff used when retrying individual requests
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Admit chat requests through `controller`, tracking their load under
+    /// `provider_name`. A request rejected as overloaded fails before it is
+    /// sent; an admitted one holds its slot until the response stream is
+    /// dropped.
+    pub fn with_admission(
+        mut self,
+        controller: Arc<AdmissionController>,
+        provider_name: impl Into<String>,
+    ) -> Self {
+        self.admission = Some((controller, provider_name.into()));
+        self
+    }
+
+    /// Take a slot from `limits` for every chat request, counted against
+    /// `provider_name`. A saturated provider is waited on or rejected
+    /// according to its saturation policy; a request that gets a slot holds
+    /// it until the response stream is dropped.
+    pub fn with_concurrency_limits(
+        mut self,
+        limits: ConcurrencyLimits,
+        provider_name: impl Into<String>,
+    ) -> Self {
+        self.concurrency = Some((limits, provider_name.into()));
+        self
+    }
+
+    /// Record every chat request in `monitor` under `provider_name`, with its
+    /// DNS, connect, TLS and time-to-first-byte breakdown
+    pub fn with_performance_monitor(
+        mut self,
+        monitor: Arc<PerformanceMonitor>,
+        provider_name: impl Into<String>,
+    ) -> Self {
+        self.performance = Some((monitor, provider_name.into()));
+        self
+    }
+
+    /// Count every chat request towards its model's usage in `optimizer`
+    /// under `provider_name`, so the most used models are the ones preloaded
+    pub fn with_model_optimizer(
+        mut self,
+        optimizer: Arc<ModelLoadingOptimizer>,
+        provider_name: impl Into<String>,
+    ) -> Self {
+        self.model_usage = Some((optimizer, provider_name.into()));
+        self
+    }
+
+    /// Record every chat request as activity of its model on `provider_name`
+    /// in `policy`, so only models left idle are unloaded
+    pub fn with_idle_eviction(
+        mut self,
+        policy: Arc<IdleEvictionPolicy>,
+        provider_name: impl Into<String>,
+    ) -> Self {
+        self.idle_eviction = Some((policy, provider_name.into()));
+        self
+    }
+
+    /// Continue OpenAI-compatible and Ollama chat streams that fail part-way
+    /// on `fallback` with `model`, according to `continuation`. The fallback
+    /// is prompted with the partial output, so the stitched stream reads as
+    /// one response.
+    pub fn with_stream_continuation(
+        mut self,
+        continuation: StreamContinuation,
+        fallback: Client,
+        model: ModelId,
+    ) -> Self {
+        self.continuation = Some((continuation, Arc::new(fallback), model));
+        self
+    }
+
+    /// Record a finished chat request in the performance monitor, if any
+    fn record_chat(&self, request: &TimedRequest, timing: &RequestTiming, success: bool) {
+        let Some((monitor, provider_name)) = self.performance.clone() else {
+            return;
+        };
+        let mut measurement =
+            PerformanceMeasurement::new(provider_name, RequestType::Inference).with_timing(timing);
+        measurement.start_time = request.started_at();
+        let measurement = if success {
+            measurement.complete_success()
+        } else {
+            measurement.complete_failure()
+        };
+        monitor.record_in_background(measurement);
+    }
+
     fn retry<A>(&self, result: anyhow::Result<A>) -> anyhow::Result<A> {
         let retry_config = &self.retry_config;
         result.map_err(move |e| into_retry(e, retry_config))
     }
 
     pub async fn refresh_models(&self) -> anyhow::Result<Vec<Model>> {
-        let models = self.clone().retry(match self.inner.as_ref() {
-            InnerClient::OpenAICompat(provider) => provider.models().await,
-            InnerClient::Anthropic(provider) => provider.models().await,
-            InnerClient::Ollama(provider) => provider.models().await,
-        })?;
+        let models = retry_with(&self.retry_policy, is_retryable, || async {
+            self.retry(match self.inner.as_ref() {
+                InnerClient::OpenAICompat(provider) => provider.models().await,
+                InnerClient::Anthropic(provider) => provider.models().await,
+                InnerClient::Ollama(provider) => provider.models().await,
+            })
+        })
+        .await?;
 
         // Update the cache with all fetched models
         {
@@ -119,16 +309,165 @@ impl Client {
         model: &ModelId,
         context: Context,
     ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
-        let chat_stream = self.clone().retry(match self.inner.as_ref() {
-            InnerClient::OpenAICompat(provider) => provider.chat(model, context).await,
-            InnerClient::Anthropic(provider) => provider.chat(model, context).await,
-            InnerClient::Ollama(provider) => provider.chat(model.clone(), context).await,
-        })?;
+        let idempotency_key = context
+            .idempotency_key
+            .clone()
+            .unwrap_or_else(IdempotencyKey::generate);
+        self.chat_with_key(model, context, &idempotency_key).await
+    }
+
+    /// Send a chat request carrying `idempotency_key`, for callers that retry
+    /// a logical request and want the provider to deduplicate the retries.
+    /// Only OpenAI-compatible providers send the key; the others ignore it.
+    pub async fn chat_with_key(
+        &self,
+        model: &ModelId,
+        context: Context,
+        idempotency_key: &IdempotencyKey,
+    ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+        // Anthropic streams are not continued, so only the others keep the
+        // context to replay
+        let replay = match (&self.continuation, self.inner.as_ref()) {
+            (Some(_), InnerClient::OpenAICompat(_) | InnerClient::Ollama(_)) => {
+                Some(context.clone())
+            }
+            _ => None,
+        };
+        let chat_stream = self.send_chat(model, context, idempotency_key).await?;
 
+        match (replay, &self.continuation) {
+            (Some(context), Some((continuation, fallback, fallback_model))) => {
+                let fallback = fallback.clone();
+                let fallback_model = fallback_model.clone();
+                Ok(
+                    continuation.stitch(chat_stream, context, move |context| async move {
+                        // The continuation is a new request, and is not continued
+                        // itself if it fails
+                        fallback
+                            .send_chat(&fallback_model, context, &IdempotencyKey::generate())
+                            .await
+                    }),
+                )
+            }
+            _ => Ok(chat_stream),
+        }
+    }
+
+    /// Send a single chat request, recording it and holding its admission
+    /// and concurrency slots until the response stream is dropped
+    async fn send_chat(
+        &self,
+        model: &ModelId,
+        context: Context,
+        idempotency_key: &IdempotencyKey,
+    ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
+        let permit = self
+            .admission
+            .as_ref()
+            .map(|(controller, provider_name)| controller.try_acquire(provider_name))
+            .transpose()?;
+        let slot = match &self.concurrency {
+            Some((limits, provider_name)) => Some(limits.acquire(provider_name).await?),
+            None => None,
+        };
+
+        // Some providers only send the request once the stream is polled, so
+        // both run with this request's timing state
+        let request = TimedRequest::start();
+        let chat_stream = request
+            .scope(async {
+                match self.inner.as_ref() {
+                    InnerClient::OpenAICompat(provider) => {
+                        provider.chat(model, context, idempotency_key).await
+                    }
+                    InnerClient::Anthropic(provider) => provider.chat(model, context).await,
+                    InnerClient::Ollama(provider) => provider.chat(model.clone(), context).await,
+                }
+            })
+            .await;
+        let chat_stream = match chat_stream {
+            Ok(chat_stream) => chat_stream,
+            Err(error) => {
+                self.record_chat(&request, &request.timing(), false);
+                return Err(into_retry(error, &self.retry_config));
+            }
+        };
+        if let Some((optimizer, provider_name)) = &self.model_usage {
+            optimizer
+                .record_model_usage(provider_name, model.as_str())
+                .await;
+        }
+        if let Some((policy, provider_name)) = &self.idle_eviction {
+            policy.record_request(provider_name, model).await;
+        }
+
+        let this = self.clone();
+        let mut chat_stream = request.stream(chat_stream);
+        let mut first_byte: Option<RequestTiming> = None;
+        let mut recorded = false;
+        Ok(Box::pin(futures::stream::poll_fn(move |cx| {
+            let _held = (&permit, &slot);
+            let item = std::task::ready!(futures::StreamExt::poll_next_unpin(&mut chat_stream, cx));
+            let timing = first_byte.get_or_insert_with(|| request.timing());
+            let finished = !matches!(item, Some(Ok(_)));
+            if finished && !recorded {
+                recorded = true;
+                this.record_chat(&request, timing, item.is_none());
+            }
+            std::task::Poll::Ready(item.map(|item| this.clone().retry(item)))
+        })))
+    }
+
+    /// Stream a chat response as incremental chunks. Failing to connect and
+    /// errors partway through the response both surface as `Err` items, after
+    /// which the stream ends.
+    pub fn chat_stream(
+        &self,
+        model: &ModelId,
+        context: Context,
+    ) -> impl Stream<Item = anyhow::Result<ChatChunk>> + Send {
         let this = self.clone();
-        Ok(Box::pin(
-            chat_stream.map(move |item| this.clone().retry(item)),
-        ))
+        let model = model.clone();
+        let stream = futures::StreamExt::flat_map(
+            futures::stream::once(async move { this.chat(&model, context).await }),
+            |result| -> BoxStream<ChatCompletionMessage, anyhow::Error> {
+                match result {
+                    Ok(stream) => stream,
+                    Err(error) => Box::pin(futures::stream::iter([Err(error)])),
+                }
+            },
+        );
+
+        let mut failed = false;
+        stream.map_while(move |item| {
+            if failed {
+                return None;
+            }
+            failed = item.is_err();
+            Some(item.map(ChatChunk::from))
+        })
+    }
+
+    /// Stream a chat response like [`Client::chat_stream`] until `token` is
+    /// cancelled. Cancelling drops the in-flight HTTP request or response,
+    /// closing its connection, which also stops Ollama generating. The stream
+    /// then ends with an [`Error::Cancelled`] item, classified as
+    /// [`FailureKind::Cancelled`](crate::retry::FailureKind::Cancelled).
+    pub fn chat_cancellable(
+        &self,
+        model: &ModelId,
+        context: Context,
+        token: CancellationToken,
+    ) -> impl Stream<Item = anyhow::Result<ChatChunk>> + Send {
+        let chunks = Box::pin(self.chat_stream(model, context));
+        futures::stream::unfold(Some((chunks, token)), |state| async move {
+            let (mut chunks, token) = state?;
+            tokio::select! {
+                biased;
+                _ = token.cancelled() => Some((Err(Error::Cancelled.into()), None)),
+                chunk = chunks.next() => chunk.map(|chunk| (chunk, Some((chunks, token)))),
+            }
+        })
     }
 
     pub async fn models(&self) -> anyhow::Result<Vec<Model>> {
@@ -153,10 +492,429 @@ impl Client {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use forge_app::domain::Provider;
+    use pretty_assertions::assert_eq;
     use reqwest::Url;
 
     use super::*;
+    use crate::config::local_ai::{LocalAiConfig, LocalProviderConfig};
+    use crate::mock_server::{MockOllamaServer, MockServer, ScriptedResponse};
+    use crate::performance::{AdmissionError, PerformanceConfig};
+    use crate::selection::Saturated;
+
+    fn client(provider: Provider) -> Client {
+        Client::new(
+            provider,
+            Arc::new(RetryConfig::default()),
+            "dev",
+            &HttpConfig::default(),
+        )
+        .unwrap()
+    }
+
+    async fn collect_text(
+        stream: impl Stream<Item = anyhow::Result<ChatChunk>>,
+    ) -> anyhow::Result<String> {
+        let chunks: Vec<_> = stream.collect::<anyhow::Result<Vec<_>>>().await?;
+        Ok(chunks.into_iter().map(|chunk| chunk.delta).collect())
+    }
+
+    fn openai_chunk(content: &str, finish_reason: Option<&str>) -> serde_json::Value {
+        serde_json::json!({
+            "id": "chatcmpl-1",
+            "model": "gpt-4o",
+            "created": 0,
+            "object": "chat.completion.chunk",
+            "choices": [{"delta": {"content": content}, "finish_reason": finish_reason}]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_concatenates_ollama_chunks() {
+        let server = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/api/chat",
+                ScriptedResponse::chat_stream("llama3.2", &["Hel", "lo", " world"]),
+            )
+            .start()
+            .await;
+        let fixture =
+            client(Provider::Ollama { url: Url::parse(&format!("{}/", server.url())).unwrap() });
+
+        let actual =
+            collect_text(fixture.chat_stream(&ModelId::new("llama3.2"), Context::default()))
+                .await
+                .unwrap();
+
+        assert_eq!(actual, "Hello world");
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_concatenates_openai_sse_chunks() {
+        let server = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/v1/chat/completions",
+                ScriptedResponse::sse(vec![
+                    openai_chunk("Hel", None),
+                    openai_chunk("lo", None),
+                    openai_chunk(" world", Some("stop")),
+                ]),
+            )
+            .start()
+            .await;
+        let fixture = client(Provider::OpenAI {
+            url: Url::parse(&format!("{}/v1/", server.url())).unwrap(),
+            key: None,
+        });
+
+        let chunks: Vec<_> = fixture
+            .chat_stream(&ModelId::new("gpt-4o"), Context::default())
+            .collect::<anyhow::Result<Vec<_>>>()
+            .await
+            .unwrap();
+
+        let actual: String = chunks.iter().map(|chunk| chunk.delta.as_str()).collect();
+        assert_eq!(actual, "Hello world");
+        assert_eq!(
+            chunks.last().unwrap().finish_reason,
+            Some(FinishReason::Stop)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_surfaces_mid_stream_error() {
+        let server = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/api/chat",
+                ScriptedResponse::chat_stream("llama3.2", &["Hel", "lo", " world"]).drop_after(2),
+            )
+            .start()
+            .await;
+        let fixture =
+            client(Provider::Ollama { url: Url::parse(&format!("{}/", server.url())).unwrap() });
+
+        let items: Vec<_> = fixture
+            .chat_stream(&ModelId::new("llama3.2"), Context::default())
+            .collect()
+            .await;
+
+        let actual: Vec<_> = items.iter().map(|item| item.is_ok()).collect();
+        let expected = vec![true, true, false];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_continues_mid_stream_error_on_fallback() {
+        let local = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/api/chat",
+                ScriptedResponse::chat_stream("llama3.2", &["Hel", "lo", " world"]).drop_after(2),
+            )
+            .start()
+            .await;
+        let mut cloud = MockServer::new().await;
+        let continuation = StreamContinuation::default().enabled(true);
+        let mock = cloud
+            .mock_chat_completion_stream(
+                serde_json::json!({
+                    "messages": [
+                        {"role": "assistant", "content": "Hello"},
+                        {"role": "user", "content": continuation.instruction},
+                    ]
+                }),
+                vec![openai_chunk(" world", Some("stop"))],
+            )
+            .await;
+        let fallback = client(Provider::OpenAI {
+            url: Url::parse(&format!("{}/", cloud.url())).unwrap(),
+            key: None,
+        });
+        let fixture =
+            client(Provider::Ollama { url: Url::parse(&format!("{}/", local.url())).unwrap() })
+                .with_stream_continuation(continuation, fallback, ModelId::new("gpt-4o"));
+
+        let actual =
+            collect_text(fixture.chat_stream(&ModelId::new("llama3.2"), Context::default()))
+                .await
+                .unwrap();
+
+        assert_eq!(actual, "Hello world");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_surfaces_connection_error() {
+        let fixture = client(Provider::Ollama { url: Url::parse("http://127.0.0.1:1/").unwrap() });
+
+        let items: Vec<_> = fixture
+            .chat_stream(&ModelId::new("llama3.2"), Context::default())
+            .collect()
+            .await;
+
+        assert_eq!(items.len(), 1);
+        assert!(items[0].is_err());
+    }
+
+    #[tokio::test]
+    async fn test_chat_holds_admission_until_stream_dropped() {
+        let server = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/api/chat",
+                ScriptedResponse::chat_stream("llama3.2", &["Hel", "lo"]),
+            )
+            .start()
+            .await;
+        let admission = Arc::new(AdmissionController::new(Default::default()).unwrap());
+        admission.register_provider("ollama", 1).unwrap();
+        let fixture =
+            client(Provider::Ollama { url: Url::parse(&format!("{}/", server.url())).unwrap() })
+                .with_admission(admission.clone(), "ollama");
+        let model = ModelId::new("llama3.2");
+
+        let first = fixture.chat(&model, Context::default()).await.unwrap();
+        let rejected = fixture
+            .chat(&model, Context::default())
+            .await
+            .err()
+            .unwrap();
+        drop(first);
+        let admitted = fixture.chat(&model, Context::default()).await.is_ok();
+
+        let actual = match rejected.downcast_ref::<AdmissionError>() {
+            Some(AdmissionError::Overloaded(overloaded)) => Some(overloaded.capacity),
+            _ => None,
+        };
+        assert_eq!(actual, Some(1));
+        assert!(admitted);
+        assert_eq!(admission.load(), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn test_chat_waits_for_concurrency_slot_up_to_queue_timeout() {
+        let server = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/api/chat",
+                ScriptedResponse::chat_stream("llama3.2", &["Hel", "lo"]),
+            )
+            .start()
+            .await;
+        let mut local_config = LocalAiConfig::new();
+        local_config.providers.insert(
+            "ollama".to_string(),
+            LocalProviderConfig::default()
+                .max_concurrent_requests(1usize)
+                .queue_timeout_ms(100u64),
+        );
+        let limits = ConcurrencyLimits::new(&local_config);
+        let fixture =
+            client(Provider::Ollama { url: Url::parse(&format!("{}/", server.url())).unwrap() })
+                .with_concurrency_limits(limits.clone(), "ollama");
+        let model = ModelId::new("llama3.2");
+
+        let first = fixture.chat(&model, Context::default()).await.unwrap();
+        let started = std::time::Instant::now();
+        let rejected = fixture
+            .chat(&model, Context::default())
+            .await
+            .err()
+            .unwrap();
+        let waited = started.elapsed();
+        drop(first);
+        let admitted = fixture.chat(&model, Context::default()).await.is_ok();
+
+        let actual = rejected.downcast_ref::<Saturated>().map(|e| e.capacity);
+        assert_eq!(actual, Some(1));
+        assert!(waited >= Duration::from_millis(100));
+        assert!(admitted);
+        assert_eq!(limits.in_flight("ollama"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_chat_counts_model_usage_for_preloading() {
+        let server = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/api/chat",
+                ScriptedResponse::chat_stream("llama3.2", &["Hel", "lo"]),
+            )
+            .on(
+                "POST",
+                "/api/generate",
+                ScriptedResponse::json(200, serde_json::json!({})),
+            )
+            .start()
+            .await;
+        let optimizer = Arc::new(ModelLoadingOptimizer::new(Default::default()));
+        let fixture =
+            client(Provider::Ollama { url: Url::parse(&format!("{}/", server.url())).unwrap() })
+                .with_model_optimizer(optimizer.clone(), "ollama");
+
+        let _response = fixture
+            .chat(&ModelId::new("llama3.2"), Context::default())
+            .await
+            .unwrap();
+
+        let ollama = crate::ollama::OllamaConfig::new()
+            .with_base_url(server.url())
+            .create_provider()
+            .unwrap();
+        let actual = optimizer.preload("ollama", &ollama).await;
+        let expected = vec![ModelId::new("llama3.2")];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_models_retries_timed_out_request() {
+        let models = serde_json::json!({
+            "data": [{ "id": "gpt-4o", "object": "model", "owned_by": "openai" }]
+        });
+        let server = MockOllamaServer::builder()
+            .on(
+                "GET",
+                "/v1/models",
+                ScriptedResponse::json(200, models.clone()).with_delay(Duration::from_millis(1500)),
+            )
+            .on("GET", "/v1/models", ScriptedResponse::json(200, models))
+            .start()
+            .await;
+        let fixture = Client::new(
+            Provider::OpenAI {
+                url: Url::parse(&format!("{}/v1/", server.url())).unwrap(),
+                key: None,
+            },
+            Arc::new(RetryConfig::default()),
+            "dev",
+            &HttpConfig { read_timeout: 1, ..HttpConfig::default() },
+        )
+        .unwrap()
+        .with_retry_policy(RetryPolicy::default().base_delay(Duration::from_millis(10)));
+
+        let actual: Vec<_> = fixture
+            .refresh_models()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|model| model.id)
+            .collect();
+
+        assert_eq!(actual, vec![ModelId::new("gpt-4o")]);
+        assert_eq!(server.hits("GET", "/v1/models"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_models_does_not_retry_fatal_error() {
+        let server = MockOllamaServer::builder()
+            .on(
+                "GET",
+                "/v1/models",
+                ScriptedResponse::json(401, serde_json::json!({ "error": "unauthorized" })),
+            )
+            .start()
+            .await;
+        let fixture = client(Provider::OpenAI {
+            url: Url::parse(&format!("{}/v1/", server.url())).unwrap(),
+            key: None,
+        });
+
+        let actual = fixture.refresh_models().await;
+
+        assert!(actual.is_err());
+        assert_eq!(server.hits("GET", "/v1/models"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_chat_attempts_reuse_context_idempotency_key() {
+        let mut server = MockServer::new().await;
+        let fixture = client(Provider::OpenAI {
+            url: Url::parse(&server.url()).unwrap(),
+            key: Some("test-api-key".to_string()),
+        });
+        let context = Context::default().with_new_idempotency_key();
+        let expected = context.idempotency_key.clone().unwrap();
+        let mock = server
+            .mock_chat_completions("Idempotency-Key", expected.as_str(), 2)
+            .await;
+
+        // Two attempts of the same logical request, as the orchestrator
+        // retries it
+        for _ in 0..2 {
+            let actual: Vec<_> = fixture
+                .chat(&ModelId::new("gpt-4o"), context.clone())
+                .await
+                .unwrap()
+                .collect()
+                .await;
+            assert!(actual.is_empty());
+        }
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_chat_records_network_timing() {
+        let mut server = MockServer::new().await;
+        let monitor = Arc::new(PerformanceMonitor::new(PerformanceConfig::default()));
+        let fixture = client(Provider::OpenAI {
+            url: Url::parse(&server.url()).unwrap(),
+            key: Some("test-api-key".to_string()),
+        })
+        .with_performance_monitor(monitor.clone(), "openai");
+        let context = Context::default().with_new_idempotency_key();
+        let key = context.idempotency_key.clone().unwrap();
+        let _mock = server
+            .mock_chat_completions("Idempotency-Key", key.as_str(), 1)
+            .await;
+
+        let _: Vec<_> = fixture
+            .chat(&ModelId::new("gpt-4o"), context)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        // The measurement is recorded in the background
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let actual = monitor.get_provider_metrics("openai").await.unwrap();
+        assert_eq!(actual.total_requests, 1);
+        assert_eq!(actual.network_timing.samples, 1);
+        assert_eq!(actual.network_timing.new_connections, 1);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_right_after_chat_keeps_measurement() {
+        let mut server = MockServer::new().await;
+        let monitor = Arc::new(PerformanceMonitor::new(PerformanceConfig::default()));
+        let fixture = client(Provider::OpenAI {
+            url: Url::parse(&server.url()).unwrap(),
+            key: Some("test-api-key".to_string()),
+        })
+        .with_performance_monitor(monitor.clone(), "openai");
+        let context = Context::default().with_new_idempotency_key();
+        let key = context.idempotency_key.clone().unwrap();
+        let _mock = server
+            .mock_chat_completions("Idempotency-Key", key.as_str(), 1)
+            .await;
+
+        let _: Vec<_> = fixture
+            .chat(&ModelId::new("gpt-4o"), context)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        monitor.shutdown().await.unwrap();
+
+        let actual = monitor.get_provider_metrics("openai").await.unwrap();
+        assert_eq!(actual.total_requests, 1);
+        assert!(!monitor.is_running());
+    }
 
     #[tokio::test]
     async fn test_cache_initialization() {
@@ -179,22 +937,22 @@ mod tests {
 
     #[tokio::test]
     async fn test_refresh_models_method_exists() {
-        let provider = Provider::OpenAI {
-            url: Url::parse("https://api.openai.com/v1/").unwrap(),
+        let mut server = MockServer::new().await;
+        let mock = server
+            .mock_models(
+                serde_json::json!({"error": {"message": "Invalid API key", "code": 401}}),
+                401,
+            )
+            .await;
+        let client = client(Provider::OpenAI {
+            url: Url::parse(&server.url()).unwrap(),
             key: Some("test-key".to_string()),
-        };
-        let client = Client::new(
-            provider,
-            Arc::new(RetryConfig::default()),
-            "dev",
-            &HttpConfig::default(),
-        )
-        .unwrap();
+        });
 
-        // Verify refresh_models method is available (it will fail due to no actual API,
-        // but that's expected)
         let result = client.refresh_models().await;
-        assert!(result.is_err()); // Expected to fail since we're not hitting a
-                                  // real API
+
+        // A rejected key is not retried
+        mock.assert_async().await;
+        assert!(result.is_err());
     }
 }
@@ -0,0 +1,243 @@
+//! Resolution of user-typed model names
+//!
+//! Users type `llama3` while a provider serves `llama3.2:latest`. Rather than
+//! every module applying its own leniency, names are compared through a
+//! [`ModelAliasResolver`]: explicit aliases from
+//! [`LocalAiConfig::model_aliases`](super::LocalAiConfig) are applied first,
+//! then a trailing `:latest` is dropped, and a name matches every model of
+//! the family it is a prefix of up to a `:`, `-` or `.` separator.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+/// Errors raised while resolving a model name
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AliasError {
+    #[error("Model '{requested}' is ambiguous. Candidates: {candidates:?}")]
+    Ambiguous {
+        requested: String,
+        candidates: Vec<String>,
+    },
+
+    #[error("Model '{requested}' does not match any available model")]
+    NotFound { requested: String },
+}
+
+/// Maps requested model names onto the model ids providers serve
+#[derive(Debug, Clone, Default)]
+pub struct ModelAliasResolver {
+    aliases: HashMap<String, String>,
+}
+
+impl ModelAliasResolver {
+    /// Create a resolver from explicit `alias -> model` mappings
+    pub fn new(aliases: HashMap<String, String>) -> Self {
+        Self { aliases }
+    }
+
+    /// Canonical form of `model`: its explicit alias target if it has one,
+    /// without a trailing `:latest`
+    pub fn normalize(&self, model: &str) -> String {
+        let target = self
+            .aliases
+            .get(model)
+            .or_else(|| self.aliases.get(strip_latest(model)))
+            .map_or(model, String::as_str);
+        strip_latest(target).to_string()
+    }
+
+    /// Whether `requested` names `model`: they normalize to the same name or
+    /// one is a prefix of the other ending at a separator, so `llama3`
+    /// matches `llama3.2:latest` and `llama3.2:3b` matches a `llama3.2`
+    /// entry, but `llama` matches neither. Empty names match nothing.
+    pub fn matches(&self, requested: &str, model: &str) -> bool {
+        let requested = self.normalize(requested);
+        let model = self.normalize(model);
+        if requested.is_empty() || model.is_empty() {
+            return false;
+        }
+        is_family_prefix(&requested, &model) || is_family_prefix(&model, &requested)
+    }
+
+    /// Every model in `available` that `requested` names
+    pub fn candidates<'a>(
+        &self,
+        requested: &str,
+        available: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<String> {
+        let mut candidates: Vec<String> = available
+            .into_iter()
+            .filter(|model| self.matches(requested, model))
+            .map(str::to_string)
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+        candidates
+    }
+
+    /// The single model in `available` that `requested` names. A model equal
+    /// to the requested name once normalized wins over family matches;
+    /// otherwise several matches are ambiguous.
+    pub fn resolve<'a>(
+        &self,
+        requested: &str,
+        available: impl IntoIterator<Item = &'a str>,
+    ) -> Result<String, AliasError> {
+        let normalized = self.normalize(requested);
+        let mut candidates = self.candidates(requested, available);
+        if let Some(exact) = candidates
+            .iter()
+            .position(|model| self.normalize(model) == normalized)
+        {
+            return Ok(candidates.swap_remove(exact));
+        }
+        match candidates.len() {
+            0 => Err(AliasError::NotFound { requested: requested.to_string() }),
+            1 => Ok(candidates.remove(0)),
+            _ => Err(AliasError::Ambiguous { requested: requested.to_string(), candidates }),
+        }
+    }
+}
+
+fn strip_latest(model: &str) -> &str {
+    model.strip_suffix(":latest").unwrap_or(model)
+}
+
+/// Whether `name` equals `prefix` or continues it past a separator
+fn is_family_prefix(prefix: &str, name: &str) -> bool {
+    name.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with([':', '-', '.']))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fixture() -> ModelAliasResolver {
+        ModelAliasResolver::new(HashMap::from([
+            ("coder".to_string(), "qwen2.5-coder:7b".to_string()),
+            ("llama".to_string(), "llama3.2:latest".to_string()),
+        ]))
+    }
+
+    const AVAILABLE: [&str; 4] = [
+        "llama3.1:latest",
+        "llama3.2:latest",
+        "qwen2.5-coder:7b",
+        "mistral:latest",
+    ];
+
+    #[test]
+    fn test_explicit_alias_resolves_to_target() {
+        let fixture = fixture();
+
+        let actual = (
+            fixture.resolve("coder", AVAILABLE),
+            fixture.resolve("llama", AVAILABLE),
+        );
+
+        let expected = (
+            Ok("qwen2.5-coder:7b".to_string()),
+            Ok("llama3.2:latest".to_string()),
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_latest_is_normalized_away() {
+        let fixture = fixture();
+
+        let actual = (
+            fixture.resolve("mistral", AVAILABLE),
+            fixture.resolve("llama3.2", AVAILABLE),
+            fixture.normalize("mistral:latest"),
+        );
+
+        let expected = (
+            Ok("mistral:latest".to_string()),
+            Ok("llama3.2:latest".to_string()),
+            "mistral".to_string(),
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_family_name_matches_its_only_model() {
+        let fixture = fixture();
+
+        let actual = fixture.resolve("llama3", ["llama3.2:latest", "mistral:latest"]);
+
+        let expected = Ok("llama3.2:latest".to_string());
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_ambiguous_name_returns_all_candidates() {
+        let fixture = fixture();
+
+        let actual = fixture.resolve("llama3", AVAILABLE);
+
+        let expected = Err(AliasError::Ambiguous {
+            requested: "llama3".to_string(),
+            candidates: vec!["llama3.1:latest".to_string(), "llama3.2:latest".to_string()],
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_unknown_name_is_not_found() {
+        let fixture = fixture();
+
+        let actual = fixture.resolve("gemma", AVAILABLE);
+
+        let expected = Err(AliasError::NotFound { requested: "gemma".to_string() });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_family_prefix_must_end_at_separator() {
+        let fixture = ModelAliasResolver::default();
+
+        let actual = (
+            fixture.matches("llama", "llama3.2:latest"),
+            fixture.matches("qwen2", "qwen2.5-coder:7b"),
+            fixture.matches("qwen2.5", "qwen2.5-coder:7b"),
+            fixture.matches("phi", "phi4:latest"),
+            fixture.matches("llama3.2:3b", "llama3.2"),
+        );
+
+        let expected = (false, true, true, false, true);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_empty_name_matches_nothing() {
+        let fixture = fixture();
+
+        let actual = (
+            fixture.matches("", "llama3.2:latest"),
+            fixture.matches("llama3.2", ""),
+            fixture.resolve("", AVAILABLE),
+        );
+
+        let expected = (
+            false,
+            false,
+            Err(AliasError::NotFound { requested: String::new() }),
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_unrelated_name_sharing_a_prefix_is_not_found() {
+        let fixture = fixture();
+
+        let actual = fixture.resolve("mist", AVAILABLE);
+
+        let expected = Err(AliasError::NotFound { requested: "mist".to_string() });
+        assert_eq!(actual, expected);
+    }
+}
@@ -5,14 +5,19 @@
 //! experience improvements, and advanced decision logic.
 
 use std::collections::HashMap;
+use std::io;
+use std::path::Path;
 use std::time::{Duration, Instant};
 
+use anyhow::Context;
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, Timelike};
 use derive_setters::Setters;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-use crate::config::fallback::{FallbackConfig, FallbackContext, FallbackDecision};
+use crate::config::fallback::{FallbackConfig, FallbackContext, FallbackDecision, FallbackEngine};
 use crate::config::local_ai::{LocalAiConfig, ProviderHealthStatus};
+use crate::idempotency::ChargedRequests;
 
 /// Enhanced fallback configuration with intelligent features
 #[derive(Debug, Clone, Serialize, Deserialize, Setters)]
@@ -30,6 +35,49 @@ pub struct EnhancedFallbackConfig {
     pub pattern_learning: PatternLearning,
     /// Cost optimization settings
     pub cost_optimization: CostOptimization,
+    /// How health, success rate and latency combine into performance scores
+    #[serde(default)]
+    pub scoring_weights: ScoringWeights,
+}
+
+/// Weights of the linear combination behind provider performance scores.
+/// Each component lies between 0.0 and 1.0 and the weighted average of them
+/// is the score before outcome feedback is applied.
+#[derive(Debug, Clone, Serialize, Deserialize, Setters)]
+#[setters(strip_option, into)]
+pub struct ScoringWeights {
+    /// Weight of the health status: healthy 1.0, degraded 0.6, unhealthy 0.1
+    pub health_weight: f64,
+    /// Weight of the recorded success rate
+    pub success_weight: f64,
+    /// Weight of the normalized latency
+    pub latency_weight: f64,
+    /// Response time at which the latency component is 0.5; faster providers
+    /// approach 1.0 and slower ones 0.0
+    pub latency_reference: Duration,
+}
+
+impl ScoringWeights {
+    /// Latency component for an average `response_time`
+    pub fn latency_score(&self, response_time: Duration) -> f64 {
+        let reference = self.latency_reference.as_secs_f64();
+        if reference <= 0.0 {
+            return 0.0;
+        }
+        reference / (reference + response_time.as_secs_f64())
+    }
+
+    /// Weighted average of the health, success and latency components
+    pub fn combine(&self, health: f64, success: f64, latency: f64) -> f64 {
+        let total = self.health_weight + self.success_weight + self.latency_weight;
+        if total <= 0.0 {
+            return 0.0;
+        }
+        (self.health_weight * health
+            + self.success_weight * success
+            + self.latency_weight * latency)
+            / total
+    }
 }
 
 /// User experience optimization settings
@@ -78,6 +126,65 @@ pub struct CostOptimization {
     pub budget_aware_switching: bool,
     /// Daily budget limit in USD
     pub daily_budget_limit: Option<f64>,
+    /// Cheaper model on the same provider to use for each model when
+    /// approaching the daily budget limit
+    #[serde(default)]
+    pub downgrade_map: HashMap<String, String>,
+    /// Token prices keyed by `provider/model`, or by provider alone to price
+    /// every model of that provider
+    #[serde(default)]
+    pub token_prices: HashMap<String, PricePerMillionTokens>,
+}
+
+/// Price of a model in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PricePerMillionTokens {
+    pub prompt: f64,
+    pub completion: f64,
+}
+
+impl PricePerMillionTokens {
+    /// Cost in USD of a request with the given token usage
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.prompt_tokens as f64 * self.prompt
+            + usage.completion_tokens as f64 * self.completion)
+            / 1_000_000.0
+    }
+}
+
+/// Tokens consumed by a single request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+/// Observed result of a single request
+#[derive(Debug, Clone, Copy, PartialEq, Setters)]
+#[setters(strip_option)]
+pub struct RequestOutcome {
+    /// Whether the request succeeded
+    pub success: bool,
+    /// How long the request took
+    pub response_time: Duration,
+    /// Tokens the provider reported, and therefore billed, for the request
+    pub token_usage: Option<TokenUsage>,
+    /// Explicit quality assessment (0.0 to 1.0)
+    pub quality_score: Option<f64>,
+    /// User satisfaction score (0.0 to 1.0)
+    pub user_satisfaction: Option<f64>,
+}
+
+impl RequestOutcome {
+    pub fn new(success: bool, response_time: Duration) -> Self {
+        Self {
+            success,
+            response_time,
+            token_usage: None,
+            quality_score: None,
+            user_satisfaction: None,
+        }
+    }
 }
 
 /// Enhanced fallback decision with additional context
@@ -95,6 +202,8 @@ pub struct EnhancedFallbackDecision {
     pub cost_impact: Option<CostImpact>,
     /// Performance prediction
     pub performance_prediction: Option<PerformancePrediction>,
+    /// Cheaper model substituted for the requested one under budget pressure
+    pub model_override: Option<String>,
 }
 
 /// Alternative option that was considered
@@ -146,14 +255,18 @@ pub struct PerformancePrediction {
 /// Enhanced fallback engine with intelligent features
 pub struct EnhancedFallbackEngine {
     config: EnhancedFallbackConfig,
-    local_config: LocalAiConfig,
     usage_patterns: UsagePatterns,
     performance_history: PerformanceHistory,
     cost_tracker: CostTracker,
+    /// Engine making the base decision, kept across decisions so its cloud
+    /// circuit breakers and selection state persist
+    base_engine: FallbackEngine,
+    /// Current local wall-clock time, used for time-based preferences
+    clock: fn() -> NaiveDateTime,
 }
 
 /// Usage patterns tracking
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsagePatterns {
     /// Time-based patterns
     pub time_patterns: HashMap<String, TimePattern>,
@@ -164,7 +277,7 @@ pub struct UsagePatterns {
 }
 
 /// Time-based usage pattern
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimePattern {
     /// Hour of day preferences
     pub hourly_preferences: HashMap<u8, ProviderPreference>,
@@ -175,7 +288,7 @@ pub struct TimePattern {
 }
 
 /// Model usage pattern
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelPattern {
     /// Preferred providers for this model
     pub preferred_providers: Vec<String>,
@@ -186,7 +299,7 @@ pub struct ModelPattern {
 }
 
 /// Workload pattern
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkloadPattern {
     /// Streaming vs non-streaming preferences
     pub streaming_preference: Option<String>,
@@ -197,7 +310,7 @@ pub struct WorkloadPattern {
 }
 
 /// Provider preference data
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderPreference {
     /// Provider name
     pub provider: String,
@@ -210,7 +323,7 @@ pub struct ProviderPreference {
 }
 
 /// Time range for patterns
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimeRange {
     /// Start hour (0-23)
     pub start_hour: u8,
@@ -245,7 +358,7 @@ pub struct ProviderPerformanceMetrics {
 }
 
 /// Performance trend analysis
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PerformanceTrend {
     /// Trend direction
     pub direction: TrendDirection,
@@ -258,7 +371,7 @@ pub struct PerformanceTrend {
 }
 
 /// Trend direction
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TrendDirection {
     /// Performance improving
     Improving,
@@ -286,7 +399,7 @@ pub struct PerformanceAnomaly {
 }
 
 /// Type of performance anomaly
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AnomalyType {
     /// Sudden response time spike
     ResponseTimeSpike,
@@ -307,10 +420,14 @@ pub struct CostTracker {
     pub daily_costs: HashMap<String, f64>,
     /// Monthly costs by provider
     pub monthly_costs: HashMap<String, f64>,
-    /// Cost per request by provider
+    /// Average cost per charged request by provider
     pub cost_per_request: HashMap<String, f64>,
+    /// Number of charged requests by provider
+    pub charged_counts: HashMap<String, u64>,
     /// Budget status
     pub budget_status: BudgetStatus,
+    /// Idempotency keys of requests that have already been charged
+    pub charged_requests: ChargedRequests,
 }
 
 /// Budget status tracking
@@ -326,6 +443,18 @@ pub struct BudgetStatus {
     pub monthly_limit: Option<f64>,
     /// Budget alerts
     pub alerts: Vec<BudgetAlert>,
+    /// Day on which each alert type was last raised
+    pub alerted_on: HashMap<BudgetAlertType, NaiveDate>,
+    /// Day the daily and monthly spend were last rolled over to
+    pub tracked_on: Option<NaiveDate>,
+}
+
+impl BudgetStatus {
+    /// Whether spend has reached the daily limit
+    pub fn daily_exceeded(&self) -> bool {
+        self.daily_limit
+            .is_some_and(|limit| self.daily_used >= limit)
+    }
 }
 
 /// Budget alert
@@ -342,7 +471,7 @@ pub struct BudgetAlert {
 }
 
 /// Budget alert types
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum BudgetAlertType {
     /// Approaching daily limit
     DailyApproaching,
@@ -363,6 +492,18 @@ impl Default for EnhancedFallbackConfig {
             ux_optimizations: UxOptimizations::default(),
             pattern_learning: PatternLearning::default(),
             cost_optimization: CostOptimization::default(),
+            scoring_weights: ScoringWeights::default(),
+        }
+    }
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        Self {
+            health_weight: 1.0,
+            success_weight: 1.0,
+            latency_weight: 0.25,
+            latency_reference: Duration::from_secs(10),
         }
     }
 }
@@ -399,6 +540,8 @@ impl Default for CostOptimization {
             cloud_cost_ranking: vec!["openai".to_string(), "anthropic".to_string()],
             budget_aware_switching: false,
             daily_budget_limit: None,
+            downgrade_map: HashMap::new(),
+            token_prices: HashMap::new(),
         }
     }
 }
@@ -406,15 +549,25 @@ impl Default for CostOptimization {
 impl EnhancedFallbackEngine {
     /// Create a new enhanced fallback engine
     pub fn new(config: EnhancedFallbackConfig, local_config: LocalAiConfig) -> Self {
+        let mut cost_tracker = CostTracker::new();
+        cost_tracker.budget_status.daily_limit = config.cost_optimization.daily_budget_limit;
+
         Self {
+            base_engine: FallbackEngine::new(config.base_config.clone(), local_config),
             config,
-            local_config,
             usage_patterns: UsagePatterns::new(),
             performance_history: PerformanceHistory::new(),
-            cost_tracker: CostTracker::new(),
+            cost_tracker,
+            clock: || Local::now().naive_local(),
         }
     }
 
+    /// Read the current local time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: fn() -> NaiveDateTime) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Make an enhanced fallback decision
     pub async fn decide_provider_enhanced(
         &mut self,
@@ -426,17 +579,28 @@ impl EnhancedFallbackEngine {
             adaptive = self.config.adaptive_strategy,
             "Making enhanced fallback decision"
         );
-
-        // Start with base decision
-        let base_engine = crate::config::fallback::FallbackEngine::new(
-            self.config.base_config.clone(),
-            self.local_config.clone(),
-        );
-
-        let base_decision = base_engine.decide_provider(context, local_health).await;
+        self.cost_tracker.roll_over((self.clock)().date());
+
+        // Start with base decision, offering cloud providers cheapest first
+        let cloud_providers = self.cost_ranked_cloud_providers().await;
+        self.base_engine.set_cloud_providers(cloud_providers);
+        let mut base_decision = self
+            .base_engine
+            .decide_provider(context, local_health)
+            .await;
 
         // Apply enhancements
         let mut reasoning = vec!["Base fallback decision made".to_string()];
+
+        // Stay off paid providers once the daily budget is spent
+        if let Some(local) = self.over_budget_local(&base_decision, local_health) {
+            reasoning.push(format!("Daily budget exceeded: preferring local {local}"));
+            base_decision = FallbackDecision::UseLocal {
+                provider_name: local,
+                reason: "Daily budget exceeded".to_string(),
+                model_override: None,
+            };
+        }
         let mut confidence: f64 = 0.7; // Base confidence
         let mut alternatives = Vec::new();
 
@@ -496,6 +660,15 @@ impl EnhancedFallbackEngine {
             None
         };
 
+        // Downgrade to a cheaper model before the budget forces a block
+        let model_override = self.budget_downgrade(context, &base_decision, cost_impact.as_ref());
+        if let Some(ref downgraded) = model_override {
+            reasoning.push(format!(
+                "Budget pressure: downgraded {} to {downgraded}",
+                context.model_id
+            ));
+        }
+
         // Performance prediction
         let performance_prediction = self.predict_performance(&base_decision, context).await;
         if performance_prediction.is_some() {
@@ -513,9 +686,112 @@ impl EnhancedFallbackEngine {
             alternatives,
             cost_impact,
             performance_prediction,
+            model_override,
         }
     }
 
+    /// Configured cloud providers ordered by `cloud_cost_ranking` when cost
+    /// optimization is enabled. Unranked providers follow the ranked ones and
+    /// ties keep their configured order. Providers whose next request would
+    /// exceed the daily budget are dropped, unless that would leave none.
+    async fn cost_ranked_cloud_providers(&self) -> Vec<String> {
+        let mut cloud_providers = self.config.base_config.cloud_providers.clone();
+        let cost_optimization = &self.config.cost_optimization;
+        if !cost_optimization.enabled {
+            return cloud_providers;
+        }
+
+        cloud_providers.sort_by_key(|provider| {
+            cost_optimization
+                .cloud_cost_ranking
+                .iter()
+                .position(|ranked| ranked == provider)
+                .unwrap_or(usize::MAX)
+        });
+
+        let mut affordable = Vec::new();
+        for provider in &cloud_providers {
+            let cost_per_request = self.cloud_cost_per_request(provider);
+            match self.assess_budget_impact(cost_per_request).await {
+                BudgetImpact::ExceedsBudget { overage_amount } => {
+                    debug!(
+                        provider = %provider,
+                        cost_per_request,
+                        overage_amount,
+                        "Skipping cloud provider that would exceed the daily budget"
+                    );
+                }
+                _ => affordable.push(provider.clone()),
+            }
+        }
+        if affordable.is_empty() {
+            cloud_providers
+        } else {
+            affordable
+        }
+    }
+
+    /// Average charged cost of a request to cloud provider `provider`, which
+    /// usage is recorded under as `cloud:<provider>`
+    fn cloud_cost_per_request(&self, provider: &str) -> f64 {
+        let cost_per_request = &self.cost_tracker.cost_per_request;
+        cost_per_request
+            .get(&format!("cloud:{provider}"))
+            .or_else(|| cost_per_request.get(provider))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Usable local provider to switch a cloud decision to when budget-aware
+    /// switching is enabled and the daily budget is exceeded
+    fn over_budget_local(
+        &self,
+        decision: &FallbackDecision,
+        local_health: &[(String, ProviderHealthStatus)],
+    ) -> Option<String> {
+        if !self.config.cost_optimization.budget_aware_switching
+            || !matches!(decision, FallbackDecision::UseCloud { .. })
+            || !self.cost_tracker.budget_status.daily_exceeded()
+        {
+            return None;
+        }
+        local_health
+            .iter()
+            .find(|(_, status)| status.is_usable())
+            .map(|(name, _)| name.clone())
+    }
+
+    /// Cheaper model to substitute for the requested one when a cloud
+    /// decision is approaching the daily budget limit
+    fn budget_downgrade(
+        &self,
+        context: &FallbackContext,
+        decision: &FallbackDecision,
+        cost_impact: Option<&CostImpact>,
+    ) -> Option<String> {
+        if !matches!(decision, FallbackDecision::UseCloud { .. }) {
+            return None;
+        }
+        let BudgetImpact::ApproachingLimit { remaining_percentage } = cost_impact?.budget_impact
+        else {
+            return None;
+        };
+        let downgraded = self
+            .config
+            .cost_optimization
+            .downgrade_map
+            .get(&context.model_id)?;
+
+        info!(
+            provider = decision.provider_name().unwrap_or_default(),
+            requested_model = %context.model_id,
+            downgraded_model = %downgraded,
+            remaining_percentage,
+            "Approaching daily budget limit, downgrading to cheaper model"
+        );
+        Some(downgraded.clone())
+    }
+
     /// Analyze usage patterns for decision enhancement
     async fn analyze_usage_patterns(&self, context: &FallbackContext) -> Option<String> {
         if !self.config.pattern_learning.enabled {
@@ -550,25 +826,83 @@ impl EnhancedFallbackEngine {
         &self,
         local_health: &[(String, ProviderHealthStatus)],
     ) -> HashMap<String, f64> {
-        let mut scores = HashMap::new();
-
-        for (provider_name, health_status) in local_health {
-            let mut score = match health_status {
-                ProviderHealthStatus::Healthy { .. } => 1.0,
-                ProviderHealthStatus::Degraded { .. } => 0.6,
-                ProviderHealthStatus::Unhealthy { .. } => 0.1,
-            };
+        local_health
+            .iter()
+            .map(|(provider_name, health_status)| {
+                (
+                    provider_name.clone(),
+                    self.provider_score(provider_name, Some(health_status)),
+                )
+            })
+            .collect()
+    }
 
-            // Apply historical performance data
-            if let Some(metrics) = self.performance_history.provider_metrics.get(provider_name) {
-                let avg_success_rate = self.calculate_average_success_rate(metrics);
-                score *= avg_success_rate;
+    /// Performance score of a provider from its health and recorded history,
+    /// combined according to the configured [`ScoringWeights`]. Providers
+    /// without a health status, such as cloud providers, count as healthy,
+    /// and components without any data count in full.
+    pub fn provider_score(
+        &self,
+        provider_name: &str,
+        health_status: Option<&ProviderHealthStatus>,
+    ) -> f64 {
+        let weights = &self.config.scoring_weights;
+        let health = match health_status {
+            Some(ProviderHealthStatus::Healthy { .. }) | None => 1.0,
+            Some(ProviderHealthStatus::Degraded { .. }) => 0.6,
+            Some(ProviderHealthStatus::Unhealthy { .. }) => 0.1,
+        };
+        let metrics = self.performance_history.provider_metrics.get(provider_name);
+        let success = metrics.map_or(1.0, |metrics| self.calculate_average_success_rate(metrics));
+
+        // Recorded response times, or the health check's when there are none
+        let response_time = metrics
+            .filter(|metrics| !metrics.response_times.is_empty())
+            .map(|metrics| self.calculate_average_response_time(metrics))
+            .or_else(|| health_status.map(ProviderHealthStatus::response_time));
+        let latency = response_time.map_or(1.0, |time| weights.latency_score(time));
+
+        let mut score = weights.combine(health, success, latency);
+
+        // Outcome feedback only influences the score once it has been observed
+        if let Some(metrics) = metrics {
+            if !metrics.quality_scores.is_empty() {
+                score *= self.calculate_average_quality_score(metrics);
+            }
+            if !metrics.reliability_scores.is_empty() {
+                score *= self.calculate_reliability_score(metrics);
             }
-
-            scores.insert(provider_name.clone(), score);
         }
 
-        scores
+        score
+    }
+
+    /// Rank providers by performance score, best first
+    pub async fn rank_providers(
+        &self,
+        local_health: &[(String, ProviderHealthStatus)],
+    ) -> Vec<(String, f64)> {
+        let mut ranking: Vec<_> = self
+            .calculate_performance_scores(local_health)
+            .await
+            .into_iter()
+            .collect();
+        ranking.sort_by(|(name_a, a), (name_b, b)| {
+            b.partial_cmp(a)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| name_a.cmp(name_b))
+        });
+        ranking
+    }
+
+    /// Anomalies detected in recorded usage, oldest first
+    pub fn get_recent_anomalies(&self) -> &[PerformanceAnomaly] {
+        &self.performance_history.anomalies
+    }
+
+    /// Cloud cost charged so far today
+    pub fn daily_cost(&self) -> f64 {
+        self.cost_tracker.budget_status.daily_used
     }
 
     /// Check for preemptive fallback conditions
@@ -646,11 +980,33 @@ impl EnhancedFallbackEngine {
         }
     }
 
-    /// Get time-based preference
+    /// Get time-based preference for the current local time
     async fn get_time_based_preference(&self) -> Option<String> {
-        // Implementation would analyze current time and return preferred provider
-        // For now, return a placeholder
-        Some("local preferred during business hours".to_string())
+        self.get_time_based_preference_at((self.clock)())
+    }
+
+    /// Highest-scoring provider among the hourly and daily preferences of
+    /// every time pattern that apply at `now`
+    fn get_time_based_preference_at(&self, now: NaiveDateTime) -> Option<String> {
+        let hour = now.hour() as u8;
+        let day = now.weekday().num_days_from_sunday() as u8;
+
+        self.usage_patterns
+            .time_patterns
+            .values()
+            .flat_map(|pattern| {
+                [
+                    pattern.hourly_preferences.get(&hour),
+                    pattern.daily_preferences.get(&day),
+                ]
+            })
+            .flatten()
+            .max_by(|a, b| {
+                a.score
+                    .partial_cmp(&b.score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map(|preference| preference.provider.clone())
     }
 
     /// Get model-specific preference
@@ -732,14 +1088,28 @@ impl EnhancedFallbackEngine {
         total / metrics.reliability_scores.len() as f64
     }
 
-    /// Record usage for pattern learning
+    /// Record usage for pattern learning. When `token_usage` is known and the
+    /// model has a configured token price, the request is charged for its
+    /// tokens; otherwise a flat per-request estimate is used. A failed request
+    /// is only charged when the provider billed it, which it signals by
+    /// reporting token usage. Retries sharing the context's idempotency key
+    /// still feed performance history, but the cost is only counted once.
     pub async fn record_usage(
         &mut self,
         provider_name: &str,
         context: &FallbackContext,
         success: bool,
         response_time: Duration,
+        token_usage: Option<TokenUsage>,
     ) {
+        if let Some(cloud_provider) = provider_name.strip_prefix("cloud:") {
+            if success {
+                self.base_engine.record_cloud_success(cloud_provider);
+            } else {
+                self.base_engine.record_cloud_failure(cloud_provider);
+            }
+        }
+
         if !self.config.pattern_learning.enabled {
             return;
         }
@@ -755,13 +1125,81 @@ impl EnhancedFallbackEngine {
         // Update performance history
         self.update_performance_history(provider_name, success, response_time)
             .await;
+        self.performance_history.recompute_trends();
+        self.performance_history
+            .detect_anomalies(provider_name, Instant::now());
 
         // Update usage patterns
         self.update_usage_patterns(provider_name, context).await;
 
-        // Update cost tracking if this is a cloud provider
-        if provider_name.starts_with("cloud:") {
-            self.update_cost_tracking(provider_name).await;
+        // Update cost tracking if this is a cloud provider that billed the request
+        if provider_name.starts_with("cloud:") && (success || token_usage.is_some()) {
+            let first_charge = context
+                .idempotency_key
+                .as_ref()
+                .is_none_or(|key| self.cost_tracker.charged_requests.first_charge(key));
+            if first_charge {
+                self.update_cost_tracking(provider_name, &context.model_id, token_usage)
+                    .await;
+            } else {
+                debug!(
+                    provider = provider_name,
+                    "Skipping cost for retried idempotent request"
+                );
+            }
+        }
+    }
+
+    /// Record the observed outcome of a request so that latency, quality and
+    /// reliability feed into future performance scores
+    pub async fn record_outcome(
+        &mut self,
+        provider_name: &str,
+        context: &FallbackContext,
+        outcome: RequestOutcome,
+    ) {
+        let RequestOutcome {
+            success,
+            response_time,
+            token_usage,
+            quality_score,
+            user_satisfaction,
+        } = outcome;
+        self.record_usage(provider_name, context, success, response_time, token_usage)
+            .await;
+
+        if !self.config.pattern_learning.enabled {
+            return;
+        }
+
+        let Some(metrics) = self
+            .performance_history
+            .provider_metrics
+            .get_mut(provider_name)
+        else {
+            return;
+        };
+
+        let now = Instant::now();
+
+        // Explicit quality assessments win over general satisfaction
+        if let Some(quality) = quality_score.or(user_satisfaction) {
+            metrics.quality_scores.push((now, quality.clamp(0.0, 1.0)));
+        }
+
+        // A successful request the user was unhappy with is only partially reliable
+        let reliability = if success {
+            user_satisfaction.unwrap_or(1.0).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        metrics.reliability_scores.push((now, reliability));
+
+        if metrics.quality_scores.len() > 1000 {
+            metrics.quality_scores.remove(0);
+        }
+        if metrics.reliability_scores.len() > 1000 {
+            metrics.reliability_scores.remove(0);
         }
     }
 
@@ -823,15 +1261,35 @@ impl EnhancedFallbackEngine {
         }
     }
 
+    /// Configured token price for `model_id` on `provider_name`, preferring a
+    /// model-specific entry over the provider-wide one
+    fn token_price(&self, provider_name: &str, model_id: &str) -> Option<PricePerMillionTokens> {
+        let prices = &self.config.cost_optimization.token_prices;
+        prices
+            .get(&format!("{provider_name}/{model_id}"))
+            .or_else(|| prices.get(provider_name))
+            .copied()
+    }
+
     /// Update cost tracking
-    async fn update_cost_tracking(&mut self, provider_name: &str) {
-        // Simplified cost tracking - in real implementation, this would integrate with
-        // billing APIs
-        let cost = match provider_name {
-            "cloud:openai" => 0.002, // Example cost per request
+    async fn update_cost_tracking(
+        &mut self,
+        provider_name: &str,
+        model_id: &str,
+        token_usage: Option<TokenUsage>,
+    ) {
+        let priced = token_usage.and_then(|usage| {
+            self.token_price(provider_name, model_id)
+                .map(|price| price.cost(&usage))
+        });
+        // Flat estimate for requests without token usage or a known price
+        let estimate = match provider_name {
+            "cloud:openai" => 0.002,
             "cloud:anthropic" => 0.003,
             _ => 0.001,
         };
+        let cost = priced.unwrap_or(estimate);
+        self.cost_tracker.roll_over((self.clock)().date());
 
         *self
             .cost_tracker
@@ -843,12 +1301,59 @@ impl EnhancedFallbackEngine {
             .monthly_costs
             .entry(provider_name.to_string())
             .or_insert(0.0) += cost;
-        self.cost_tracker
+        let count = self
+            .cost_tracker
+            .charged_counts
+            .entry(provider_name.to_string())
+            .or_insert(0);
+        *count += 1;
+        let count = *count as f64;
+        let average = self
+            .cost_tracker
             .cost_per_request
-            .insert(provider_name.to_string(), cost);
+            .entry(provider_name.to_string())
+            .or_insert(0.0);
+        *average += (cost - *average) / count;
 
         self.cost_tracker.budget_status.daily_used += cost;
         self.cost_tracker.budget_status.monthly_used += cost;
+        self.check_budget_alerts();
+    }
+
+    /// Raise daily budget alerts for thresholds crossed by the current spend.
+    /// Each alert type fires at most once per day.
+    fn check_budget_alerts(&mut self) {
+        let today = (self.clock)().date();
+        self.cost_tracker.roll_over(today);
+        let budget = &self.cost_tracker.budget_status;
+        let Some(daily_limit) = budget.daily_limit.filter(|limit| *limit > 0.0) else {
+            return;
+        };
+        let used = budget.daily_used / daily_limit;
+
+        for (alert_type, threshold) in [
+            (BudgetAlertType::DailyApproaching, BUDGET_ALERT_THRESHOLD),
+            (BudgetAlertType::DailyExceeded, 1.0),
+        ] {
+            let budget = &mut self.cost_tracker.budget_status;
+            if used < threshold || budget.alerted_on.get(&alert_type) == Some(&today) {
+                continue;
+            }
+            budget.alerted_on.insert(alert_type, today);
+
+            let message = format!(
+                "Daily spend ${:.2} has reached {:.0}% of the ${daily_limit:.2} budget",
+                budget.daily_used,
+                used * 100.0
+            );
+            warn!(alert = ?alert_type, daily_used = budget.daily_used, daily_limit, "{message}");
+            budget.alerts.push(BudgetAlert {
+                alert_type,
+                threshold: threshold * 100.0,
+                timestamp: Instant::now(),
+                message,
+            });
+        }
     }
 }
 
@@ -862,6 +1367,18 @@ impl UsagePatterns {
     }
 }
 
+/// Fraction of the daily budget at which a `DailyApproaching` alert is raised
+const BUDGET_ALERT_THRESHOLD: f64 = 0.8;
+
+/// Most recent samples considered when computing a trend
+const TREND_WINDOW: usize = 20;
+
+/// Fewest samples needed before a trend is reported
+const TREND_MIN_SAMPLES: usize = 5;
+
+/// Strength below which a trend is considered stable
+const TREND_STABLE_THRESHOLD: f64 = 0.1;
+
 impl PerformanceHistory {
     fn new() -> Self {
         Self {
@@ -870,6 +1387,384 @@ impl PerformanceHistory {
             anomalies: Vec::new(),
         }
     }
+
+    /// Recompute each provider's trend from the linear regression of its
+    /// recent response times and success rates. Rising response times and
+    /// falling success rates both count as degrading.
+    pub fn recompute_trends(&mut self) {
+        for (provider_name, metrics) in &self.provider_metrics {
+            let response_times = recent(&metrics.response_times);
+            let success_rates = recent(&metrics.success_rates);
+
+            let trend = if response_times.len() < TREND_MIN_SAMPLES {
+                PerformanceTrend {
+                    direction: TrendDirection::Unknown,
+                    strength: 0.0,
+                    confidence: 0.0,
+                    time_window: Duration::ZERO,
+                }
+            } else {
+                let latencies: Vec<f64> = response_times
+                    .iter()
+                    .map(|(_, time)| time.as_secs_f64())
+                    .collect();
+                let rates: Vec<f64> = success_rates.iter().map(|(_, rate)| *rate).collect();
+
+                let latency = linear_fit(&latencies);
+                let mean_latency = latencies.iter().sum::<f64>() / latencies.len() as f64;
+                // Change across the window relative to the mean latency
+                let latency_change = if mean_latency > 0.0 {
+                    latency.slope * (latencies.len() - 1) as f64 / mean_latency
+                } else {
+                    0.0
+                };
+                let rate_change = if rates.len() >= TREND_MIN_SAMPLES {
+                    linear_fit(&rates).slope * (rates.len() - 1) as f64
+                } else {
+                    0.0
+                };
+
+                let signal = latency_change.clamp(-1.0, 1.0) - rate_change.clamp(-1.0, 1.0);
+                let strength = signal.abs().min(1.0);
+                let direction = if strength < TREND_STABLE_THRESHOLD {
+                    TrendDirection::Stable
+                } else if signal > 0.0 {
+                    TrendDirection::Degrading
+                } else {
+                    TrendDirection::Improving
+                };
+                let coverage = response_times.len() as f64 / TREND_WINDOW as f64;
+
+                PerformanceTrend {
+                    direction,
+                    strength,
+                    confidence: latency.r_squared * coverage,
+                    time_window: response_times[response_times.len() - 1]
+                        .0
+                        .duration_since(response_times[0].0),
+                }
+            };
+
+            debug!(
+                provider = %provider_name,
+                direction = ?trend.direction,
+                strength = trend.strength,
+                confidence = trend.confidence,
+                "Recomputed performance trend"
+            );
+            self.trends.insert(provider_name.clone(), trend);
+        }
+    }
+}
+
+/// A response time this many times the rolling mean is a spike
+const SPIKE_FACTOR: f64 = 3.0;
+
+/// Samples in the rolling success rate
+const SUCCESS_RATE_WINDOW: usize = 10;
+
+/// Rolling success rate below which an anomaly is recorded
+const SUCCESS_RATE_THRESHOLD: f64 = 0.5;
+
+/// Most anomalies kept, dropping the oldest first
+const MAX_ANOMALIES: usize = 100;
+
+impl PerformanceHistory {
+    /// Check `provider_name`'s latest sample for a response time spike
+    /// against the rolling mean, and its rolling success rate for a drop
+    /// below [`SUCCESS_RATE_THRESHOLD`]
+    fn detect_anomalies(&mut self, provider_name: &str, now: Instant) {
+        let Some(metrics) = self.provider_metrics.get(provider_name) else {
+            return;
+        };
+        let mut detected = Vec::new();
+
+        if let Some(((_, latest), previous)) = recent(&metrics.response_times).split_last() {
+            if previous.len() >= TREND_MIN_SAMPLES {
+                let mean = previous
+                    .iter()
+                    .map(|(_, time)| time.as_secs_f64())
+                    .sum::<f64>()
+                    / previous.len() as f64;
+                let ratio = latest.as_secs_f64() / mean;
+                if mean > 0.0 && ratio > SPIKE_FACTOR {
+                    detected.push((
+                        AnomalyType::ResponseTimeSpike,
+                        (ratio / (2.0 * SPIKE_FACTOR)).min(1.0),
+                        format!(
+                            "Response time {}ms is {ratio:.1}x the rolling mean of {}ms",
+                            latest.as_millis(),
+                            (mean * 1000.0) as u64
+                        ),
+                    ));
+                }
+            }
+        }
+
+        // Only report the moment the rate crosses the threshold, not every
+        // sample while it stays below
+        let rates = &metrics.success_rates;
+        if rates.len() >= TREND_MIN_SAMPLES {
+            let current = success_rate(&rates[rates.len().saturating_sub(SUCCESS_RATE_WINDOW)..]);
+            let before = &rates[..rates.len() - 1];
+            let previous =
+                success_rate(&before[before.len().saturating_sub(SUCCESS_RATE_WINDOW)..]);
+            if current < SUCCESS_RATE_THRESHOLD && previous >= SUCCESS_RATE_THRESHOLD {
+                detected.push((
+                    AnomalyType::SuccessRateDrop,
+                    (SUCCESS_RATE_THRESHOLD - current) / SUCCESS_RATE_THRESHOLD,
+                    format!(
+                        "Success rate dropped to {:.0}% over the last {} requests",
+                        current * 100.0,
+                        SUCCESS_RATE_WINDOW.min(rates.len())
+                    ),
+                ));
+            }
+        }
+
+        for (anomaly_type, severity, description) in detected {
+            info!(
+                provider = provider_name,
+                anomaly = ?anomaly_type,
+                severity = severity,
+                "{}",
+                description
+            );
+            self.anomalies.push(PerformanceAnomaly {
+                provider: provider_name.to_string(),
+                anomaly_type,
+                severity,
+                timestamp: now,
+                description,
+            });
+        }
+        if self.anomalies.len() > MAX_ANOMALIES {
+            let excess = self.anomalies.len() - MAX_ANOMALIES;
+            self.anomalies.drain(..excess);
+        }
+    }
+}
+
+impl EnhancedFallbackEngine {
+    /// Write the learned usage patterns and performance history to `path` as
+    /// JSON
+    pub async fn save_state(&self, path: &Path) -> anyhow::Result<()> {
+        let state = LearnedState::capture(
+            &self.usage_patterns,
+            &self.performance_history,
+            Instant::now(),
+        );
+        let json = serde_json::to_string_pretty(&state)?;
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, json)
+            .await
+            .with_context(|| format!("Failed to save learned state to {}", path.display()))?;
+
+        debug!(path = %path.display(), "Saved learned fallback state");
+        Ok(())
+    }
+
+    /// Replace the learned usage patterns and performance history with those
+    /// saved at `path`, returning whether anything was loaded. A missing file
+    /// leaves the current state untouched.
+    pub async fn load_state(&mut self, path: &Path) -> anyhow::Result<bool> {
+        let json = match tokio::fs::read_to_string(path).await {
+            Ok(json) => json,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                debug!(path = %path.display(), "No saved learned fallback state");
+                return Ok(false);
+            }
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("Failed to read learned state from {}", path.display())
+                })
+            }
+        };
+        let state: LearnedState = serde_json::from_str(&json)
+            .with_context(|| format!("Invalid learned state in {}", path.display()))?;
+
+        let (usage_patterns, performance_history) = state.restore(Instant::now());
+        info!(
+            path = %path.display(),
+            models = usage_patterns.model_patterns.len(),
+            providers = performance_history.provider_metrics.len(),
+            "Loaded learned fallback state"
+        );
+        self.usage_patterns = usage_patterns;
+        self.performance_history = performance_history;
+        Ok(true)
+    }
+}
+
+/// On-disk form of the engine's learned state. An `Instant` only means
+/// something within the process that took it, so every timestamp is stored
+/// as its age at save time and rebased onto the loading process's clock.
+#[derive(Debug, Serialize, Deserialize)]
+struct LearnedState {
+    usage_patterns: UsagePatterns,
+    provider_metrics: HashMap<String, MetricsSnapshot>,
+    trends: HashMap<String, PerformanceTrend>,
+    anomalies: Vec<AnomalySnapshot>,
+}
+
+/// [`ProviderPerformanceMetrics`] with sample ages in place of instants
+#[derive(Debug, Serialize, Deserialize)]
+struct MetricsSnapshot {
+    response_times: Vec<(Duration, Duration)>,
+    success_rates: Vec<(Duration, f64)>,
+    quality_scores: Vec<(Duration, f64)>,
+    reliability_scores: Vec<(Duration, f64)>,
+}
+
+/// [`PerformanceAnomaly`] with its age in place of an instant
+#[derive(Debug, Serialize, Deserialize)]
+struct AnomalySnapshot {
+    provider: String,
+    anomaly_type: AnomalyType,
+    severity: f64,
+    age: Duration,
+    description: String,
+}
+
+impl LearnedState {
+    fn capture(usage_patterns: &UsagePatterns, history: &PerformanceHistory, now: Instant) -> Self {
+        let provider_metrics = history
+            .provider_metrics
+            .iter()
+            .map(|(provider_name, metrics)| {
+                let snapshot = MetricsSnapshot {
+                    response_times: to_ages(&metrics.response_times, now),
+                    success_rates: to_ages(&metrics.success_rates, now),
+                    quality_scores: to_ages(&metrics.quality_scores, now),
+                    reliability_scores: to_ages(&metrics.reliability_scores, now),
+                };
+                (provider_name.clone(), snapshot)
+            })
+            .collect();
+        let anomalies = history
+            .anomalies
+            .iter()
+            .map(|anomaly| AnomalySnapshot {
+                provider: anomaly.provider.clone(),
+                anomaly_type: anomaly.anomaly_type.clone(),
+                severity: anomaly.severity,
+                age: now.saturating_duration_since(anomaly.timestamp),
+                description: anomaly.description.clone(),
+            })
+            .collect();
+
+        Self {
+            usage_patterns: usage_patterns.clone(),
+            provider_metrics,
+            trends: history.trends.clone(),
+            anomalies,
+        }
+    }
+
+    fn restore(self, now: Instant) -> (UsagePatterns, PerformanceHistory) {
+        let provider_metrics = self
+            .provider_metrics
+            .into_iter()
+            .map(|(provider_name, snapshot)| {
+                let metrics = ProviderPerformanceMetrics {
+                    response_times: from_ages(snapshot.response_times, now),
+                    success_rates: from_ages(snapshot.success_rates, now),
+                    quality_scores: from_ages(snapshot.quality_scores, now),
+                    reliability_scores: from_ages(snapshot.reliability_scores, now),
+                };
+                (provider_name, metrics)
+            })
+            .collect();
+        let anomalies = self
+            .anomalies
+            .into_iter()
+            .map(|anomaly| PerformanceAnomaly {
+                provider: anomaly.provider,
+                anomaly_type: anomaly.anomaly_type,
+                severity: anomaly.severity,
+                timestamp: instant_before(now, anomaly.age),
+                description: anomaly.description,
+            })
+            .collect();
+
+        let history = PerformanceHistory { provider_metrics, trends: self.trends, anomalies };
+        (self.usage_patterns, history)
+    }
+}
+
+/// Ages of each sample in a time series, as of `now`
+fn to_ages<T: Copy>(samples: &[(Instant, T)], now: Instant) -> Vec<(Duration, T)> {
+    samples
+        .iter()
+        .map(|&(at, value)| (now.saturating_duration_since(at), value))
+        .collect()
+}
+
+/// Rebuild a time series from sample ages relative to `now`
+fn from_ages<T>(samples: Vec<(Duration, T)>, now: Instant) -> Vec<(Instant, T)> {
+    samples
+        .into_iter()
+        .map(|(age, value)| (instant_before(now, age), value))
+        .collect()
+}
+
+/// The instant `age` before `now`, clamped to `now` if that predates the
+/// platform's clock
+fn instant_before(now: Instant, age: Duration) -> Instant {
+    now.checked_sub(age).unwrap_or(now)
+}
+
+/// Mean of a success-rate series, treating an empty series as fully
+/// successful
+fn success_rate(samples: &[(Instant, f64)]) -> f64 {
+    if samples.is_empty() {
+        return 1.0;
+    }
+    samples.iter().map(|(_, rate)| rate).sum::<f64>() / samples.len() as f64
+}
+
+/// The last [`TREND_WINDOW`] samples of a time series
+fn recent<T>(samples: &[(Instant, T)]) -> &[(Instant, T)] {
+    &samples[samples.len().saturating_sub(TREND_WINDOW)..]
+}
+
+/// Least-squares line through `values` plotted against their index
+struct LinearFit {
+    slope: f64,
+    /// Share of the variance explained by the line, 0.0 to 1.0
+    r_squared: f64,
+}
+
+fn linear_fit(values: &[f64]) -> LinearFit {
+    let n = values.len() as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = values.iter().sum::<f64>() / n;
+
+    let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+    for (x, y) in values.iter().enumerate() {
+        let dx = x as f64 - mean_x;
+        let dy = y - mean_y;
+        sxx += dx * dx;
+        sxy += dx * dy;
+        syy += dy * dy;
+    }
+
+    if sxx == 0.0 {
+        return LinearFit { slope: 0.0, r_squared: 0.0 };
+    }
+    let slope = sxy / sxx;
+    // A flat series is fully explained by a flat line
+    let r_squared = if syy == 0.0 {
+        1.0
+    } else {
+        (sxy * sxy) / (sxx * syy)
+    };
+    LinearFit { slope, r_squared }
 }
 
 impl CostTracker {
@@ -878,13 +1773,36 @@ impl CostTracker {
             daily_costs: HashMap::new(),
             monthly_costs: HashMap::new(),
             cost_per_request: HashMap::new(),
+            charged_counts: HashMap::new(),
             budget_status: BudgetStatus {
                 daily_used: 0.0,
                 daily_limit: None,
                 monthly_used: 0.0,
                 monthly_limit: None,
                 alerts: Vec::new(),
+                alerted_on: HashMap::new(),
+                tracked_on: None,
             },
+            charged_requests: ChargedRequests::default(),
+        }
+    }
+
+    /// Start a new daily spend when `today` is a later day than the one last
+    /// tracked, and a new monthly spend when it is in a later month
+    fn roll_over(&mut self, today: NaiveDate) {
+        let Some(tracked_on) = self.budget_status.tracked_on.replace(today) else {
+            return;
+        };
+        if today <= tracked_on {
+            return;
+        }
+        debug!(%tracked_on, %today, "Rolling over daily budget spend");
+        self.daily_costs.clear();
+        self.budget_status.daily_used = 0.0;
+        if (today.year(), today.month()) != (tracked_on.year(), tracked_on.month()) {
+            debug!(%tracked_on, %today, "Rolling over monthly budget spend");
+            self.monthly_costs.clear();
+            self.budget_status.monthly_used = 0.0;
         }
     }
 }
@@ -894,7 +1812,10 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::circuit_breaker::CircuitBreakerConfig;
+    use crate::config::fallback::{CloudSelectionStrategy, FallbackStrategy};
     use crate::config::local_ai::LocalAiConfig;
+    use crate::idempotency::IdempotencyKey;
 
     #[test]
     fn test_enhanced_fallback_config_default() {
@@ -945,4 +1866,788 @@ mod tests {
         assert_eq!(scores.len(), 2);
         assert!(scores.get("ollama").unwrap() > scores.get("local_ai").unwrap());
     }
+
+    fn healthy(response_time_ms: u64) -> ProviderHealthStatus {
+        ProviderHealthStatus::Healthy {
+            response_time: Duration::from_millis(response_time_ms),
+            models_available: 1,
+            additional_info: None,
+        }
+    }
+
+    async fn record_series(fixture: &mut EnhancedFallbackEngine, millis: &[u64]) {
+        let context = FallbackContext::new("llama3.2".to_string());
+        for millis in millis {
+            fixture
+                .record_usage(
+                    "ollama",
+                    &context,
+                    true,
+                    Duration::from_millis(*millis),
+                    None,
+                )
+                .await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rising_response_times_are_a_degrading_trend() {
+        let mut fixture =
+            EnhancedFallbackEngine::new(EnhancedFallbackConfig::default(), LocalAiConfig::new());
+
+        record_series(
+            &mut fixture,
+            &[200, 260, 310, 400, 480, 600, 720, 900, 1100, 1400],
+        )
+        .await;
+
+        let actual = &fixture.performance_history.trends["ollama"];
+        assert_eq!(actual.direction, TrendDirection::Degrading);
+        assert!(actual.strength > 0.7, "strength {}", actual.strength);
+        assert!(actual.confidence > 0.4, "confidence {}", actual.confidence);
+
+        let local_health = vec![(
+            "ollama".to_string(),
+            ProviderHealthStatus::Degraded {
+                reason: "Slow responses".to_string(),
+                response_time: Duration::from_millis(1400),
+                models_available: 1,
+            },
+        )];
+        let context = FallbackContext::new("llama3.2".to_string());
+        assert!(fixture
+            .check_preemptive_fallback(&context, &local_health)
+            .await
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_trend_direction_follows_series_shape() {
+        let mut stable =
+            EnhancedFallbackEngine::new(EnhancedFallbackConfig::default(), LocalAiConfig::new());
+        let mut improving =
+            EnhancedFallbackEngine::new(EnhancedFallbackConfig::default(), LocalAiConfig::new());
+        let mut sparse =
+            EnhancedFallbackEngine::new(EnhancedFallbackConfig::default(), LocalAiConfig::new());
+
+        record_series(&mut stable, &[300, 310, 295, 305, 300, 298, 302, 301]).await;
+        record_series(&mut improving, &[1200, 1000, 850, 700, 520, 400, 300, 250]).await;
+        record_series(&mut sparse, &[100, 900]).await;
+
+        let actual = [&stable, &improving, &sparse].map(|engine| {
+            engine.performance_history.trends["ollama"]
+                .direction
+                .clone()
+        });
+        let expected = [
+            TrendDirection::Stable,
+            TrendDirection::Improving,
+            TrendDirection::Unknown,
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_response_time_spike_is_recorded_as_anomaly() {
+        let mut fixture =
+            EnhancedFallbackEngine::new(EnhancedFallbackConfig::default(), LocalAiConfig::new());
+
+        record_series(&mut fixture, &[200, 210, 190, 200, 200, 200, 900]).await;
+
+        let actual: Vec<_> = fixture
+            .get_recent_anomalies()
+            .iter()
+            .map(|anomaly| (anomaly.provider.as_str(), anomaly.anomaly_type.clone()))
+            .collect();
+        let expected = vec![("ollama", AnomalyType::ResponseTimeSpike)];
+        assert_eq!(actual, expected);
+        let severity = fixture.get_recent_anomalies()[0].severity;
+        assert!((severity - 0.75).abs() < 1e-9, "severity {severity}");
+    }
+
+    #[tokio::test]
+    async fn test_failure_burst_is_recorded_once_as_success_rate_drop() {
+        let mut fixture =
+            EnhancedFallbackEngine::new(EnhancedFallbackConfig::default(), LocalAiConfig::new());
+        let context = FallbackContext::new("llama3.2".to_string());
+
+        for success in [true; 6].into_iter().chain([false; 8]) {
+            fixture
+                .record_usage(
+                    "ollama",
+                    &context,
+                    success,
+                    Duration::from_millis(200),
+                    None,
+                )
+                .await;
+        }
+
+        let actual: Vec<_> = fixture
+            .get_recent_anomalies()
+            .iter()
+            .map(|anomaly| (anomaly.anomaly_type.clone(), anomaly.severity))
+            .collect();
+        // The rolling rate first falls below 50% at 4 successes in 10
+        let expected = vec![(AnomalyType::SuccessRateDrop, (0.5 - 0.4) / 0.5)];
+        assert_eq!(actual, expected);
+    }
+
+    fn preference(provider: &str, score: f64) -> ProviderPreference {
+        ProviderPreference {
+            provider: provider.to_string(),
+            score,
+            usage_count: 10,
+            success_rate: 1.0,
+        }
+    }
+
+    fn time_pattern_engine() -> EnhancedFallbackEngine {
+        let mut engine =
+            EnhancedFallbackEngine::new(EnhancedFallbackConfig::default(), LocalAiConfig::new());
+        engine.usage_patterns.time_patterns.insert(
+            "peak_hours".to_string(),
+            TimePattern {
+                hourly_preferences: HashMap::from([
+                    (14, preference("cloud:openai", 0.9)),
+                    (3, preference("ollama", 0.8)),
+                ]),
+                // Tuesdays
+                daily_preferences: HashMap::from([(2, preference("ollama", 0.6))]),
+                peak_times: vec![TimeRange {
+                    start_hour: 9,
+                    end_hour: 17,
+                    days_of_week: vec![1, 2, 3, 4, 5],
+                }],
+            },
+        );
+        engine
+    }
+
+    #[tokio::test]
+    async fn test_time_preference_follows_injected_clock() {
+        // Tuesday 14:00
+        let fixture = time_pattern_engine().with_clock(|| {
+            chrono::NaiveDate::from_ymd_opt(2026, 10, 13)
+                .unwrap()
+                .and_hms_opt(14, 0, 0)
+                .unwrap()
+        });
+
+        let actual = fixture.get_time_based_preference().await;
+
+        let expected = Some("cloud:openai".to_string());
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_time_preference_outside_configured_times() {
+        let fixture = time_pattern_engine();
+        let at = |day: u32, hour: u32| {
+            chrono::NaiveDate::from_ymd_opt(2026, 10, day)
+                .unwrap()
+                .and_hms_opt(hour, 30, 0)
+                .unwrap()
+        };
+
+        // Tuesday 10:30, Saturday 10:30
+        let actual = (
+            fixture.get_time_based_preference_at(at(13, 10)),
+            fixture.get_time_based_preference_at(at(17, 10)),
+        );
+
+        let expected = (Some("ollama".to_string()), None);
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_poor_outcomes_lower_provider_ranking() {
+        let config = EnhancedFallbackConfig::default();
+        let mut fixture = EnhancedFallbackEngine::new(config, LocalAiConfig::new());
+        let context = FallbackContext::new("llama3.2".to_string());
+        let local_health = vec![
+            ("ollama-a".to_string(), healthy(100)),
+            ("ollama-b".to_string(), healthy(100)),
+        ];
+
+        for _ in 0..5 {
+            fixture
+                .record_outcome(
+                    "ollama-a",
+                    &context,
+                    RequestOutcome::new(true, Duration::from_secs(8))
+                        .quality_score(0.2)
+                        .user_satisfaction(0.3),
+                )
+                .await;
+            fixture
+                .record_outcome(
+                    "ollama-b",
+                    &context,
+                    RequestOutcome::new(true, Duration::from_millis(300))
+                        .quality_score(0.9)
+                        .user_satisfaction(0.95),
+                )
+                .await;
+        }
+
+        let actual = fixture.rank_providers(&local_health).await;
+
+        let expected = vec!["ollama-b".to_string(), "ollama-a".to_string()];
+        assert_eq!(
+            actual
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert!(actual[0].1 > actual[1].1);
+    }
+
+    fn degraded(response_time_ms: u64) -> ProviderHealthStatus {
+        ProviderHealthStatus::Degraded {
+            reason: "High load".to_string(),
+            response_time: Duration::from_millis(response_time_ms),
+            models_available: 1,
+        }
+    }
+
+    async fn ranking(weights: ScoringWeights) -> Vec<String> {
+        let config = EnhancedFallbackConfig::default().scoring_weights(weights);
+        let fixture = EnhancedFallbackEngine::new(config, LocalAiConfig::new());
+        let local_health = vec![
+            ("fast-degraded".to_string(), degraded(50)),
+            ("slow-healthy".to_string(), healthy(8000)),
+        ];
+        fixture
+            .rank_providers(&local_health)
+            .await
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_default_weights_prefer_healthy_provider() {
+        let actual = ranking(ScoringWeights::default()).await;
+
+        let expected = vec!["slow-healthy".to_string(), "fast-degraded".to_string()];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_latency_weight_flips_ranking() {
+        let actual = ranking(ScoringWeights::default().latency_weight(5.0)).await;
+
+        let expected = vec!["fast-degraded".to_string(), "slow-healthy".to_string()];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_latency_score_is_half_at_reference() {
+        let fixture = ScoringWeights::default().latency_reference(Duration::from_secs(2));
+
+        let actual = (
+            fixture.latency_score(Duration::ZERO),
+            fixture.latency_score(Duration::from_secs(2)),
+        );
+
+        let expected = (1.0, 0.5);
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_failed_outcomes_reduce_score() {
+        let config = EnhancedFallbackConfig::default();
+        let mut fixture = EnhancedFallbackEngine::new(config, LocalAiConfig::new());
+        let context = FallbackContext::new("llama3.2".to_string());
+        let local_health = vec![("ollama".to_string(), healthy(100))];
+
+        fixture
+            .record_outcome(
+                "ollama",
+                &context,
+                RequestOutcome::new(true, Duration::from_millis(500)),
+            )
+            .await;
+        let before = fixture.calculate_performance_scores(&local_health).await["ollama"];
+
+        for _ in 0..3 {
+            fixture
+                .record_outcome(
+                    "ollama",
+                    &context,
+                    RequestOutcome::new(false, Duration::from_secs(30)),
+                )
+                .await;
+        }
+        let after = fixture.calculate_performance_scores(&local_health).await["ollama"];
+
+        assert!(after < before);
+    }
+
+    #[tokio::test]
+    async fn test_retried_idempotent_request_charged_once() {
+        let mut fixture =
+            EnhancedFallbackEngine::new(EnhancedFallbackConfig::default(), LocalAiConfig::new());
+        let context = FallbackContext::new("gpt-4o".to_string())
+            .with_idempotency_key(IdempotencyKey::new("forge-retry"));
+
+        for _ in 0..2 {
+            fixture
+                .record_outcome(
+                    "cloud:openai",
+                    &context,
+                    RequestOutcome::new(true, Duration::from_secs(1)),
+                )
+                .await;
+        }
+
+        let actual = fixture.cost_tracker.budget_status.daily_used;
+        let expected = 0.002;
+        assert_eq!(actual, expected);
+        assert_eq!(fixture.cost_tracker.daily_costs["cloud:openai"], expected);
+    }
+
+    #[tokio::test]
+    async fn test_failed_request_without_usage_not_charged() {
+        let mut fixture =
+            EnhancedFallbackEngine::new(EnhancedFallbackConfig::default(), LocalAiConfig::new());
+        let context = FallbackContext::new("gpt-4o".to_string());
+
+        fixture
+            .record_outcome(
+                "cloud:openai",
+                &context,
+                RequestOutcome::new(false, Duration::from_secs(1)),
+            )
+            .await;
+
+        let actual = fixture.daily_cost();
+        let expected = 0.0;
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_failed_request_with_usage_charged() {
+        let mut fixture = priced_fixture();
+        let context = FallbackContext::new("gpt-4o".to_string());
+
+        fixture
+            .record_outcome(
+                "cloud:openai",
+                &context,
+                RequestOutcome::new(false, Duration::from_secs(1))
+                    .token_usage(TokenUsage { prompt_tokens: 1_000_000, completion_tokens: 0 }),
+            )
+            .await;
+
+        let actual = fixture.daily_cost();
+        let expected = 2.5;
+        assert_eq!(actual, expected);
+    }
+
+    fn priced_fixture() -> EnhancedFallbackEngine {
+        let mut token_prices = HashMap::new();
+        token_prices.insert(
+            "cloud:openai".to_string(),
+            PricePerMillionTokens { prompt: 2.5, completion: 10.0 },
+        );
+        token_prices.insert(
+            "cloud:openai/gpt-4o-mini".to_string(),
+            PricePerMillionTokens { prompt: 0.15, completion: 0.6 },
+        );
+        let config = EnhancedFallbackConfig::default()
+            .cost_optimization(CostOptimization::default().token_prices(token_prices));
+        EnhancedFallbackEngine::new(config, LocalAiConfig::new())
+    }
+
+    #[tokio::test]
+    async fn test_cost_computed_from_token_usage() {
+        let mut fixture = priced_fixture();
+        let usage = TokenUsage { prompt_tokens: 1_000, completion_tokens: 500 };
+
+        fixture
+            .record_usage(
+                "cloud:openai",
+                &FallbackContext::new("gpt-4o".to_string()),
+                true,
+                Duration::from_secs(1),
+                Some(usage),
+            )
+            .await;
+        let provider_wide = fixture.cost_tracker.daily_costs["cloud:openai"];
+        fixture
+            .record_usage(
+                "cloud:openai",
+                &FallbackContext::new("gpt-4o-mini".to_string()),
+                true,
+                Duration::from_secs(1),
+                Some(usage),
+            )
+            .await;
+
+        // 1000 * 2.5 / 1M + 500 * 10 / 1M, then 1000 * 0.15 / 1M + 500 * 0.6 / 1M
+        let expected = (0.0075, 0.0075 + 0.00045);
+        let actual = (
+            provider_wide,
+            fixture.cost_tracker.daily_costs["cloud:openai"],
+        );
+        assert!((actual.0 - expected.0).abs() < 1e-12);
+        assert!((actual.1 - expected.1).abs() < 1e-12);
+        let average = fixture.cost_tracker.cost_per_request["cloud:openai"];
+        assert!((average - expected.1 / 2.0).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_budget_totals_accumulate() {
+        let mut fixture = priced_fixture();
+        let context = FallbackContext::new("gpt-4o".to_string());
+        let usage = TokenUsage { prompt_tokens: 400_000, completion_tokens: 100_000 };
+
+        for _ in 0..3 {
+            fixture
+                .record_usage(
+                    "cloud:openai",
+                    &context,
+                    true,
+                    Duration::from_secs(1),
+                    Some(usage),
+                )
+                .await;
+        }
+        // Without token usage the flat estimate is charged
+        fixture
+            .record_usage(
+                "cloud:anthropic",
+                &context,
+                true,
+                Duration::from_secs(1),
+                None,
+            )
+            .await;
+
+        let budget = &fixture.cost_tracker.budget_status;
+        let actual = (
+            fixture.cost_tracker.daily_costs["cloud:openai"],
+            fixture.cost_tracker.monthly_costs["cloud:openai"],
+            fixture.cost_tracker.cost_per_request["cloud:openai"],
+            fixture.cost_tracker.charged_counts["cloud:openai"],
+            budget.daily_used,
+            budget.monthly_used,
+        );
+        let expected = (6.0, 6.0, 2.0, 3, 6.003, 6.003);
+        assert_eq!(actual, expected);
+    }
+
+    fn alert_fixture(clock: fn() -> NaiveDateTime) -> EnhancedFallbackEngine {
+        let mut token_prices = HashMap::new();
+        token_prices.insert(
+            "cloud:openai".to_string(),
+            PricePerMillionTokens { prompt: 1.0, completion: 0.0 },
+        );
+        let config = EnhancedFallbackConfig::default().cost_optimization(
+            CostOptimization::default()
+                .daily_budget_limit(1.0)
+                .budget_aware_switching(true)
+                .token_prices(token_prices),
+        );
+        EnhancedFallbackEngine::new(config, LocalAiConfig::new()).with_clock(clock)
+    }
+
+    /// Spend `usd` on a single priced request
+    async fn spend(engine: &mut EnhancedFallbackEngine, usd: f64) {
+        let usage = TokenUsage {
+            prompt_tokens: (usd * 1_000_000.0) as u64,
+            completion_tokens: 0,
+        };
+        engine
+            .record_usage(
+                "cloud:openai",
+                &FallbackContext::new("gpt-4o".to_string()),
+                true,
+                Duration::from_secs(1),
+                Some(usage),
+            )
+            .await;
+    }
+
+    fn alert_types(engine: &EnhancedFallbackEngine) -> Vec<BudgetAlertType> {
+        engine
+            .cost_tracker
+            .budget_status
+            .alerts
+            .iter()
+            .map(|alert| alert.alert_type)
+            .collect()
+    }
+
+    fn day_one() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    fn day_two() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, 2)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_budget_alerts_fire_once_per_threshold() {
+        let mut fixture = alert_fixture(day_one);
+
+        spend(&mut fixture, 0.5).await;
+        assert_eq!(alert_types(&fixture), vec![]);
+
+        spend(&mut fixture, 0.35).await;
+        spend(&mut fixture, 0.05).await;
+        assert_eq!(
+            alert_types(&fixture),
+            vec![BudgetAlertType::DailyApproaching]
+        );
+
+        spend(&mut fixture, 0.2).await;
+        spend(&mut fixture, 0.2).await;
+
+        let actual = alert_types(&fixture);
+        let expected = vec![
+            BudgetAlertType::DailyApproaching,
+            BudgetAlertType::DailyExceeded,
+        ];
+        assert_eq!(actual, expected);
+        assert_eq!(
+            fixture.cost_tracker.budget_status.alerts[1].threshold,
+            100.0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_budget_alert_fires_again_on_a_new_day() {
+        let mut fixture = alert_fixture(day_one);
+        spend(&mut fixture, 0.9).await;
+
+        fixture = fixture.with_clock(day_two);
+        spend(&mut fixture, 0.9).await;
+
+        let actual = alert_types(&fixture);
+        let expected = vec![
+            BudgetAlertType::DailyApproaching,
+            BudgetAlertType::DailyApproaching,
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_monthly_spend_rolls_over_in_a_new_month() {
+        let mut fixture = alert_fixture(day_one);
+        spend(&mut fixture, 0.5).await;
+        fixture = fixture.with_clock(day_two);
+        spend(&mut fixture, 0.25).await;
+        fixture = fixture.with_clock(|| {
+            NaiveDate::from_ymd_opt(2024, 2, 1)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap()
+        });
+        spend(&mut fixture, 0.125).await;
+
+        let budget = &fixture.cost_tracker.budget_status;
+        let actual = (
+            budget.daily_used,
+            budget.monthly_used,
+            fixture.cost_tracker.monthly_costs["cloud:openai"],
+        );
+        let expected = (0.125, 0.125, 0.125);
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_over_budget_prefers_local() {
+        let mut fixture = alert_fixture(day_one);
+        spend(&mut fixture, 1.5).await;
+        let mut context = FallbackContext::new("gpt-4o".to_string());
+        context.consecutive_failures = 3;
+        let local_health = vec![(
+            "ollama".to_string(),
+            ProviderHealthStatus::Degraded {
+                response_time: Duration::from_secs(4),
+                reason: "slow".to_string(),
+                models_available: 1,
+            },
+        )];
+
+        let actual = fixture
+            .decide_provider_enhanced(&context, &local_health)
+            .await;
+
+        assert_eq!(actual.decision.provider_name(), Some("ollama"));
+        assert!(matches!(actual.decision, FallbackDecision::UseLocal { .. }));
+    }
+
+    fn budget_fixture(daily_used: f64) -> EnhancedFallbackEngine {
+        let mut downgrade_map = HashMap::new();
+        downgrade_map.insert("gpt-4".to_string(), "gpt-4o-mini".to_string());
+        let config = EnhancedFallbackConfig::default().cost_optimization(
+            CostOptimization::default()
+                .daily_budget_limit(1.0)
+                .downgrade_map(downgrade_map),
+        );
+        let mut engine = EnhancedFallbackEngine::new(config, LocalAiConfig::new());
+        engine.cost_tracker.budget_status.daily_used = daily_used;
+        engine
+    }
+
+    #[tokio::test]
+    async fn test_expensive_model_downgraded_near_budget_limit() {
+        let mut fixture = budget_fixture(0.9);
+        let context = FallbackContext::new("gpt-4".to_string());
+
+        let actual = fixture.decide_provider_enhanced(&context, &[]).await;
+
+        assert!(matches!(actual.decision, FallbackDecision::UseCloud { .. }));
+        assert_eq!(actual.model_override, Some("gpt-4o-mini".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_model_not_downgraded_within_budget() {
+        let mut fixture = budget_fixture(0.1);
+        let context = FallbackContext::new("gpt-4".to_string());
+
+        let actual = fixture.decide_provider_enhanced(&context, &[]).await;
+
+        assert!(matches!(actual.decision, FallbackDecision::UseCloud { .. }));
+        assert_eq!(actual.model_override, None);
+    }
+
+    #[tokio::test]
+    async fn test_learned_state_survives_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("learned.json");
+        let mut fixture =
+            EnhancedFallbackEngine::new(EnhancedFallbackConfig::default(), LocalAiConfig::new());
+        record_series(&mut fixture, &[100, 200, 300, 400, 500, 600]).await;
+        fixture.save_state(&path).await.unwrap();
+
+        let mut actual =
+            EnhancedFallbackEngine::new(EnhancedFallbackConfig::default(), LocalAiConfig::new());
+        let loaded = actual.load_state(&path).await.unwrap();
+
+        assert_eq!(loaded, true);
+        let pattern = &actual.usage_patterns.model_patterns["llama3.2"];
+        assert_eq!(pattern.preferred_providers, vec!["ollama".to_string()]);
+        assert_eq!(pattern.usage_frequency, 6.0);
+        let response_times: Vec<_> = actual.performance_history.provider_metrics["ollama"]
+            .response_times
+            .iter()
+            .map(|(_, response_time)| response_time.as_millis())
+            .collect();
+        assert_eq!(response_times, vec![100, 200, 300, 400, 500, 600]);
+        assert_eq!(
+            actual.performance_history.trends["ollama"].direction,
+            TrendDirection::Degrading
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_state_from_missing_file_keeps_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut fixture =
+            EnhancedFallbackEngine::new(EnhancedFallbackConfig::default(), LocalAiConfig::new());
+        record_series(&mut fixture, &[100]).await;
+
+        let actual = fixture
+            .load_state(&dir.path().join("missing.json"))
+            .await
+            .unwrap();
+
+        assert_eq!(actual, false);
+        assert_eq!(fixture.usage_patterns.model_patterns.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cloud_providers_tried_cheapest_first() {
+        let config = EnhancedFallbackConfig::default().cost_optimization(
+            CostOptimization::default()
+                .cloud_cost_ranking(vec!["anthropic".to_string(), "openai".to_string()]),
+        );
+        let mut fixture = EnhancedFallbackEngine::new(config, LocalAiConfig::new());
+        let context = FallbackContext::new("gpt-4".to_string());
+
+        let actual = fixture.decide_provider_enhanced(&context, &[]).await;
+
+        assert!(matches!(actual.decision, FallbackDecision::UseCloud { .. }));
+        assert_eq!(actual.decision.provider_name(), Some("anthropic"));
+    }
+
+    #[tokio::test]
+    async fn test_cloud_provider_over_budget_is_skipped() {
+        let config = EnhancedFallbackConfig::default().cost_optimization(
+            CostOptimization::default()
+                .cloud_cost_ranking(vec!["anthropic".to_string(), "openai".to_string()])
+                .daily_budget_limit(1.0),
+        );
+        let mut fixture = EnhancedFallbackEngine::new(config, LocalAiConfig::new());
+        fixture.cost_tracker.budget_status.daily_used = 0.5;
+        fixture
+            .cost_tracker
+            .cost_per_request
+            .insert("cloud:anthropic".to_string(), 0.8);
+        fixture
+            .cost_tracker
+            .cost_per_request
+            .insert("cloud:openai".to_string(), 0.1);
+        let context = FallbackContext::new("gpt-4".to_string());
+
+        let actual = fixture.decide_provider_enhanced(&context, &[]).await;
+
+        assert_eq!(actual.decision.provider_name(), Some("openai"));
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_rotates_across_decisions() {
+        let config = EnhancedFallbackConfig::default().base_config(
+            FallbackConfig::default()
+                .strategy(FallbackStrategy::Immediate)
+                .cloud_selection(CloudSelectionStrategy::RoundRobin),
+        );
+        let mut fixture = EnhancedFallbackEngine::new(config, LocalAiConfig::new());
+        let context = FallbackContext::new("gpt-4".to_string());
+
+        let mut actual = Vec::new();
+        for _ in 0..4 {
+            let decision = fixture.decide_provider_enhanced(&context, &[]).await;
+            actual.push(decision.decision.provider_name().unwrap().to_string());
+        }
+
+        let expected = vec!["openai", "anthropic", "openai", "anthropic"];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_recorded_cloud_failures_open_the_breaker() {
+        let config = EnhancedFallbackConfig::default().base_config(
+            FallbackConfig::default()
+                .strategy(FallbackStrategy::Immediate)
+                .cloud_breaker(CircuitBreakerConfig::default().failure_threshold(1u32)),
+        );
+        let mut fixture = EnhancedFallbackEngine::new(config, LocalAiConfig::new());
+        let context = FallbackContext::new("gpt-4".to_string());
+        fixture
+            .record_usage(
+                "cloud:openai",
+                &context,
+                false,
+                Duration::from_secs(1),
+                None,
+            )
+            .await;
+
+        let actual = fixture.decide_provider_enhanced(&context, &[]).await;
+
+        assert_eq!(actual.decision.provider_name(), Some("anthropic"));
+    }
 }
@@ -0,0 +1,273 @@
+//! Environment variable overrides for local AI and fallback configuration
+//!
+//! Recognised variables (all prefixed with `TRUSTAI_`):
+//!
+//! - `TRUSTAI_LOCAL_ENABLED` - enable or disable local AI
+//! - `TRUSTAI_PROVIDER_<NAME>_URL` - endpoint for local provider `<name>`
+//! - `TRUSTAI_PROVIDER_<NAME>_ENABLED` - enable or disable provider `<name>`
+//! - `TRUSTAI_PROVIDER_<NAME>_MODELS` - comma-separated preferred models
+//! - `TRUSTAI_FALLBACK_STRATEGY` - `graceful`, `immediate`, `manual` or `none`
+//! - `TRUSTAI_FALLBACK_CLOUD` - comma-separated cloud fallback providers
+//! - `TRUSTAI_DAILY_BUDGET` - daily cloud budget limit in USD
+
+use std::str::FromStr;
+
+use tracing::{debug, warn};
+
+use crate::config::enhanced::EnhancedFallbackConfig;
+use crate::config::fallback::FallbackStrategy;
+use crate::config::local_ai::LocalAiConfig;
+
+/// Prefix shared by all recognised environment variables
+pub const ENV_PREFIX: &str = "TRUSTAI_";
+
+/// Configuration after environment overrides have been applied
+#[derive(Debug, Clone)]
+pub struct EnvConfigResult {
+    /// Local AI configuration
+    pub local: LocalAiConfig,
+    /// Fallback configuration
+    pub fallback: EnhancedFallbackConfig,
+    /// Variables with the `TRUSTAI_` prefix that were not recognised
+    pub unknown_vars: Vec<String>,
+}
+
+/// Builds configuration from `TRUSTAI_*` environment variables, layered on top
+/// of file-based configuration so that environment values take precedence
+#[derive(Debug, Clone, Default)]
+pub struct EnvConfigLoader {
+    vars: Vec<(String, String)>,
+}
+
+impl EnvConfigLoader {
+    /// Capture all `TRUSTAI_*` variables from the process environment
+    pub fn from_env() -> Self {
+        Self::from_vars(std::env::vars())
+    }
+
+    /// Use an explicit set of variables instead of the process environment,
+    /// for example from an injected environment source or in tests
+    pub fn from_vars<I, K, V>(vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let mut vars: Vec<(String, String)> = vars
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .filter(|(key, _)| key.starts_with(ENV_PREFIX))
+            .collect();
+        vars.sort();
+        Self { vars }
+    }
+
+    /// Build configuration from defaults and environment variables only
+    pub fn load(&self) -> anyhow::Result<EnvConfigResult> {
+        self.apply(LocalAiConfig::default(), EnhancedFallbackConfig::default())
+    }
+
+    /// Apply environment overrides on top of file configuration. Every
+    /// malformed variable is reported in the returned error; unrecognised
+    /// variables are collected in [`EnvConfigResult::unknown_vars`].
+    pub fn apply(
+        &self,
+        mut local: LocalAiConfig,
+        mut fallback: EnhancedFallbackConfig,
+    ) -> anyhow::Result<EnvConfigResult> {
+        let mut errors = Vec::new();
+        let mut unknown_vars = Vec::new();
+
+        for (key, value) in &self.vars {
+            let name = &key[ENV_PREFIX.len()..];
+            let result = match name {
+                "LOCAL_ENABLED" => parse::<bool>(key, value).map(|enabled| {
+                    local.enabled = enabled;
+                    true
+                }),
+                "FALLBACK_STRATEGY" => parse_strategy(key, value).map(|strategy| {
+                    fallback.base_config.strategy = strategy;
+                    true
+                }),
+                "FALLBACK_CLOUD" => {
+                    fallback.base_config.cloud_providers = parse_list(value);
+                    Ok(true)
+                }
+                "DAILY_BUDGET" => parse::<f64>(key, value).and_then(|budget| {
+                    if budget < 0.0 {
+                        Err(format!("{key}: budget cannot be negative, got '{value}'"))
+                    } else {
+                        fallback.cost_optimization.daily_budget_limit = Some(budget);
+                        Ok(true)
+                    }
+                }),
+                _ => match name.strip_prefix("PROVIDER_") {
+                    Some(provider) => apply_provider(&mut local, key, provider, value),
+                    None => Ok(false),
+                },
+            };
+
+            match result {
+                Ok(true) => debug!(var = %key, "Applied environment override"),
+                Ok(false) => unknown_vars.push(key.clone()),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        for key in &unknown_vars {
+            warn!(var = %key, "Ignoring unrecognised environment variable");
+        }
+
+        if !errors.is_empty() {
+            anyhow::bail!("Invalid environment configuration: {}", errors.join("; "));
+        }
+
+        local.validate()?;
+        fallback.base_config.validate()?;
+
+        Ok(EnvConfigResult { local, fallback, unknown_vars })
+    }
+}
+
+/// Apply a `TRUSTAI_PROVIDER_<NAME>_<FIELD>` variable, creating the provider
+/// with default settings when it is not already configured. Returns `false`
+/// when the variable is not recognised.
+fn apply_provider(
+    local: &mut LocalAiConfig,
+    key: &str,
+    provider: &str,
+    value: &str,
+) -> Result<bool, String> {
+    let Some((name, field)) = provider
+        .rsplit_once('_')
+        .filter(|(name, _)| !name.is_empty())
+    else {
+        return Ok(false);
+    };
+
+    if !matches!(field, "URL" | "ENABLED" | "MODELS") {
+        return Ok(false);
+    }
+
+    let provider = local.providers.entry(name.to_lowercase()).or_default();
+
+    match field {
+        "URL" => {
+            reqwest::Url::parse(value).map_err(|e| format!("{key}: invalid URL '{value}': {e}"))?;
+            provider.endpoint = value.to_string();
+        }
+        "ENABLED" => provider.enabled = parse::<bool>(key, value)?,
+        _ => provider.preferred_models = parse_list(value),
+    }
+
+    Ok(true)
+}
+
+/// Parse a scalar value, describing the variable on failure
+fn parse<T: FromStr>(key: &str, value: &str) -> Result<T, String>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|e| format!("{key}: invalid value '{value}': {e}"))
+}
+
+/// Parse a fallback strategy name
+fn parse_strategy(key: &str, value: &str) -> Result<FallbackStrategy, String> {
+    match value.trim().to_lowercase().as_str() {
+        "graceful" => Ok(FallbackStrategy::Graceful),
+        "immediate" => Ok(FallbackStrategy::Immediate),
+        "manual" => Ok(FallbackStrategy::Manual),
+        "none" => Ok(FallbackStrategy::None),
+        _ => Err(format!("{key}: unknown fallback strategy '{value}'")),
+    }
+}
+
+/// Split a comma-separated list, dropping empty entries
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::config::local_ai::LocalProviderConfig;
+
+    #[test]
+    fn test_env_builds_config() {
+        let fixture = EnvConfigLoader::from_vars([
+            ("TRUSTAI_PROVIDER_OLLAMA_URL", "http://gpu-box:11434"),
+            ("TRUSTAI_PROVIDER_OLLAMA_MODELS", "llama3.2, qwen2.5"),
+            ("TRUSTAI_FALLBACK_CLOUD", "openai,anthropic"),
+            ("TRUSTAI_FALLBACK_STRATEGY", "immediate"),
+            ("TRUSTAI_DAILY_BUDGET", "10.0"),
+            ("HOME", "/root"),
+        ]);
+
+        let actual = fixture.load().unwrap();
+
+        let ollama = &actual.local.providers["ollama"];
+        assert_eq!(ollama.endpoint, "http://gpu-box:11434");
+        assert_eq!(ollama.preferred_models, vec!["llama3.2", "qwen2.5"]);
+        assert_eq!(
+            actual.fallback.base_config.cloud_providers,
+            vec!["openai", "anthropic"]
+        );
+        assert_eq!(
+            actual.fallback.base_config.strategy,
+            FallbackStrategy::Immediate
+        );
+        assert_eq!(
+            actual.fallback.cost_optimization.daily_budget_limit,
+            Some(10.0)
+        );
+        assert!(actual.unknown_vars.is_empty());
+    }
+
+    #[test]
+    fn test_env_overrides_file_value() {
+        let fixture =
+            EnvConfigLoader::from_vars([("TRUSTAI_PROVIDER_ENVTEST_URL", "http://from-env:11434")]);
+        let file = LocalAiConfig::default().add_provider(
+            "envtest".to_string(),
+            LocalProviderConfig::default().endpoint("http://from-file:11434"),
+        );
+
+        let actual = fixture
+            .apply(file, EnhancedFallbackConfig::default())
+            .unwrap();
+
+        assert_eq!(
+            actual.local.providers["envtest"].endpoint,
+            "http://from-env:11434"
+        );
+    }
+
+    #[test]
+    fn test_env_reports_unknown_and_malformed_vars() {
+        let unknown = EnvConfigLoader::from_vars([("TRUSTAI_SOMETHING_ELSE", "1")])
+            .load()
+            .unwrap();
+        assert_eq!(unknown.unknown_vars, vec!["TRUSTAI_SOMETHING_ELSE"]);
+
+        let actual = EnvConfigLoader::from_vars([
+            ("TRUSTAI_DAILY_BUDGET", "ten"),
+            ("TRUSTAI_PROVIDER_OLLAMA_URL", "not a url"),
+        ])
+        .load()
+        .unwrap_err()
+        .to_string();
+
+        assert!(actual.contains("TRUSTAI_DAILY_BUDGET"));
+        assert!(actual.contains("TRUSTAI_PROVIDER_OLLAMA_URL"));
+    }
+}
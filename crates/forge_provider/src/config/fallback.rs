@@ -1,10 +1,22 @@
-use std::time::Duration;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use derive_setters::Setters;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
+use super::aliases::ModelAliasResolver;
 use super::local_ai::{LocalAiConfig, ProviderHealthStatus};
+use super::routing::{RoutingRule, RoutingTable};
+use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
+use crate::idempotency::IdempotencyKey;
+use crate::retry::{random_seed, retry_scaled, splitmix64, FailureKind, RetryPolicy};
+
+/// How much longer to wait before retrying a rate-limited provider than the
+/// regular backoff
+const RATE_LIMIT_BACKOFF_MULTIPLIER: u32 = 4;
 
 /// Configuration for provider fallback behavior
 #[derive(Debug, Clone, Serialize, Deserialize, Setters)]
@@ -18,14 +30,102 @@ pub struct FallbackConfig {
     pub notify_user: bool,
     /// Maximum retry attempts before fallback
     pub max_retries: u32,
-    /// Delay between retry attempts in milliseconds
+    /// Delay before the first retry in milliseconds, doubled for each
+    /// further retry
     pub retry_delay_ms: u64,
+    /// Upper bound on the delay between retries in milliseconds
+    #[serde(default = "default_max_retry_delay_ms")]
+    pub max_retry_delay_ms: u64,
     /// Timeout for fallback decision in seconds
     pub decision_timeout_seconds: u64,
     /// Whether to automatically return to local when available
     pub auto_return_to_local: bool,
     /// Minimum time to wait before returning to local in seconds
     pub local_recovery_delay_seconds: u64,
+    /// Return an informative degraded-mode response instead of an error when
+    /// no provider is available
+    #[serde(default)]
+    pub degraded_mode_response: bool,
+    /// Attach the full per-provider diagnostic trail to the error returned
+    /// when every provider fails
+    #[serde(default = "default_explain_on_error")]
+    pub explain_on_error: bool,
+    /// Route simple requests to a small local model before falling back to
+    /// cloud
+    #[serde(default)]
+    pub tiny_model: TinyModelFallback,
+    /// Per-model routing rules, evaluated in order before the default
+    /// selection logic
+    #[serde(default)]
+    pub routing_rules: Vec<RoutingRule>,
+    /// Circuit breaker applied to each cloud provider, so a provider that
+    /// keeps failing is skipped until its cooldown elapses
+    #[serde(default)]
+    pub cloud_breaker: CircuitBreakerConfig,
+    /// How a cloud provider is chosen among those available
+    #[serde(default)]
+    pub cloud_selection: CloudSelectionStrategy,
+    /// Cloud providers to fall back to per model, keyed by model id prefix
+    /// (a trailing `:latest` is ignored). Consulted before `cloud_providers`;
+    /// the longest matching prefix wins.
+    #[serde(default)]
+    pub model_routing: HashMap<String, Vec<String>>,
+}
+
+fn default_explain_on_error() -> bool {
+    true
+}
+
+fn default_max_retry_delay_ms() -> u64 {
+    30_000
+}
+
+/// Complexity of a request, used to decide whether a tiny local model can
+/// serve it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestComplexity {
+    /// Short prompt without tools
+    Simple,
+    /// Long prompt, tool use, or unknown prompt size
+    Complex,
+}
+
+/// Configuration for falling back to a tiny local model for simple requests
+#[derive(Debug, Clone, Serialize, Deserialize, Setters)]
+#[setters(strip_option, into)]
+pub struct TinyModelFallback {
+    /// Whether simple requests may use the tiny model
+    pub enabled: bool,
+    /// Local provider serving the tiny model
+    pub provider: String,
+    /// Tiny model to use
+    pub model: String,
+    /// Longest prompt, in characters, still considered simple
+    pub max_prompt_chars: usize,
+}
+
+impl Default for TinyModelFallback {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: "ollama".to_string(),
+            model: "llama3.2:1b".to_string(),
+            max_prompt_chars: 2000,
+        }
+    }
+}
+
+impl TinyModelFallback {
+    /// Classify a request. Requests with tools or an unknown prompt size are
+    /// treated as complex.
+    pub fn classify(&self, context: &FallbackContext) -> RequestComplexity {
+        match context.prompt_chars {
+            Some(chars) if !context.requires_tools && chars <= self.max_prompt_chars => {
+                RequestComplexity::Simple
+            }
+            _ => RequestComplexity::Complex,
+        }
+    }
 }
 
 /// Fallback strategy options
@@ -42,6 +142,21 @@ pub enum FallbackStrategy {
     None,
 }
 
+/// How the fallback engine spreads requests across cloud providers
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CloudSelectionStrategy {
+    /// Always use the first available provider in configured order
+    #[default]
+    FirstAvailable,
+    /// Rotate through the available providers
+    RoundRobin,
+    /// Pick available providers at random in proportion to their weight.
+    /// Providers without a weight are only used when no weighted provider is
+    /// available.
+    Weighted(HashMap<String, u32>),
+}
+
 /// Result of a fallback decision
 #[derive(Debug, Clone)]
 pub enum FallbackDecision {
@@ -49,6 +164,8 @@ pub enum FallbackDecision {
     UseLocal {
         provider_name: String,
         reason: String,
+        /// Model to use instead of the requested one
+        model_override: Option<String>,
     },
     /// Fallback to cloud provider
     UseCloud {
@@ -68,6 +185,17 @@ pub enum FallbackDecision {
     },
 }
 
+/// Outcome of [`FallbackEngine::execute_with_retry`]
+#[derive(Debug)]
+pub enum RetryOutcome<T> {
+    /// The local provider eventually succeeded
+    Completed(T),
+    /// Every attempt failed; carries where to go next
+    Fallback(FallbackDecision),
+    /// The caller cancelled the request, so there is nothing to fall back to
+    Cancelled(anyhow::Error),
+}
+
 /// Context for fallback decisions
 #[derive(Debug, Clone)]
 pub struct FallbackContext {
@@ -83,6 +211,14 @@ pub struct FallbackContext {
     pub consecutive_failures: u32,
     /// Time since last successful request
     pub time_since_last_success: Option<Duration>,
+    /// Prompt length in characters, if known
+    pub prompt_chars: Option<usize>,
+    /// Cloud providers that must not be selected, such as those already
+    /// attempted for this request
+    pub excluded_cloud_providers: Vec<String>,
+    /// Key shared by every retry of this request, so its cost is only
+    /// charged once
+    pub idempotency_key: Option<IdempotencyKey>,
 }
 
 impl Default for FallbackConfig {
@@ -93,9 +229,17 @@ impl Default for FallbackConfig {
             notify_user: true,
             max_retries: 3,
             retry_delay_ms: 1000,
+            max_retry_delay_ms: default_max_retry_delay_ms(),
             decision_timeout_seconds: 10,
             auto_return_to_local: true,
             local_recovery_delay_seconds: 60,
+            degraded_mode_response: false,
+            explain_on_error: true,
+            tiny_model: TinyModelFallback::default(),
+            routing_rules: Vec::new(),
+            cloud_breaker: CircuitBreakerConfig::default(),
+            cloud_selection: CloudSelectionStrategy::default(),
+            model_routing: HashMap::new(),
         }
     }
 }
@@ -131,6 +275,8 @@ impl FallbackConfig {
             warn!("No cloud providers configured for fallback");
         }
 
+        RoutingTable::new(&self.routing_rules)?;
+
         debug!("Fallback configuration validated successfully");
         Ok(())
     }
@@ -140,6 +286,18 @@ impl FallbackConfig {
         Duration::from_millis(self.retry_delay_ms)
     }
 
+    /// Backoff for retrying a local provider: `max_retries` retries starting
+    /// at `retry_delay`, doubled per retry and capped at `max_retry_delay_ms`,
+    /// each randomized to between half and all of its length
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::default()
+            .max_attempts(self.max_retries.saturating_add(1))
+            .base_delay(self.retry_delay())
+            .multiplier(2.0)
+            .max_delay(Duration::from_millis(self.max_retry_delay_ms))
+            .jitter(0.5)
+    }
+
     /// Get decision timeout as Duration
     pub fn decision_timeout(&self) -> Duration {
         Duration::from_secs(self.decision_timeout_seconds)
@@ -155,12 +313,63 @@ impl FallbackConfig {
 pub struct FallbackEngine {
     config: FallbackConfig,
     local_config: LocalAiConfig,
+    aliases: ModelAliasResolver,
+    /// Circuit breakers for cloud providers, created on first use
+    cloud_breakers: Mutex<HashMap<String, CircuitBreaker>>,
+    /// Round-robin cursor and random state for cloud selection
+    cloud_selection: Mutex<CloudSelectionState>,
+}
+
+/// Mutable state behind [`CloudSelectionStrategy`]
+#[derive(Debug, Clone)]
+struct CloudSelectionState {
+    cursor: usize,
+    rng: u64,
+}
+
+impl CloudSelectionState {
+    /// Advance the random state
+    fn next_random(&mut self) -> u64 {
+        self.rng = splitmix64(self.rng);
+        self.rng
+    }
 }
 
 impl FallbackEngine {
     /// Create a new fallback engine
     pub fn new(config: FallbackConfig, local_config: LocalAiConfig) -> Self {
-        Self { config, local_config }
+        let seed = random_seed();
+        Self {
+            config,
+            aliases: local_config.alias_resolver(),
+            local_config,
+            cloud_breakers: Mutex::new(HashMap::new()),
+            cloud_selection: Mutex::new(CloudSelectionState { cursor: 0, rng: seed }),
+        }
+    }
+
+    /// Seed weighted cloud selection, making it reproducible
+    pub fn with_selection_seed(self, seed: u64) -> Self {
+        self.cloud_selection.lock().unwrap().rng = seed;
+        self
+    }
+
+    /// Offer `providers` as the cloud providers, in order of preference,
+    /// keeping circuit breaker and selection state
+    pub fn set_cloud_providers(&mut self, providers: Vec<String>) {
+        self.config.cloud_providers = providers;
+    }
+
+    /// Copy of this engine with its current circuit breaker and selection
+    /// state, for decisions that must not affect this engine
+    pub fn snapshot(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            local_config: self.local_config.clone(),
+            aliases: self.aliases.clone(),
+            cloud_breakers: Mutex::new(self.cloud_breakers.lock().unwrap().clone()),
+            cloud_selection: Mutex::new(self.cloud_selection.lock().unwrap().clone()),
+        }
     }
 
     /// Make a fallback decision based on current context and provider health
@@ -168,6 +377,18 @@ impl FallbackEngine {
         &self,
         context: &FallbackContext,
         local_health: &[(String, ProviderHealthStatus)],
+    ) -> FallbackDecision {
+        self.decide_provider_at(context, local_health, Instant::now())
+            .await
+    }
+
+    /// Make a fallback decision at `now`, skipping cloud providers whose
+    /// circuit breaker is open
+    pub async fn decide_provider_at(
+        &self,
+        context: &FallbackContext,
+        local_health: &[(String, ProviderHealthStatus)],
+        now: Instant,
     ) -> FallbackDecision {
         info!(
             strategy = ?self.config.strategy,
@@ -179,8 +400,8 @@ impl FallbackEngine {
         match self.config.strategy {
             FallbackStrategy::None => self.decide_local_only(context, local_health).await,
             FallbackStrategy::Manual => self.decide_manual(context, local_health).await,
-            FallbackStrategy::Immediate => self.decide_immediate(context, local_health).await,
-            FallbackStrategy::Graceful => self.decide_graceful(context, local_health).await,
+            FallbackStrategy::Immediate => self.decide_immediate(context, local_health, now).await,
+            FallbackStrategy::Graceful => self.decide_graceful(context, local_health, now).await,
         }
     }
 
@@ -194,6 +415,7 @@ impl FallbackEngine {
             FallbackDecision::UseLocal {
                 provider_name: name.clone(),
                 reason: "Local provider available and healthy".to_string(),
+                model_override: None,
             }
         } else {
             FallbackDecision::NoProvider {
@@ -213,6 +435,7 @@ impl FallbackEngine {
             FallbackDecision::UseLocal {
                 provider_name: name.clone(),
                 reason: "Local provider available".to_string(),
+                model_override: None,
             }
         } else {
             let mut options = Vec::new();
@@ -241,13 +464,17 @@ impl FallbackEngine {
         &self,
         context: &FallbackContext,
         local_health: &[(String, ProviderHealthStatus)],
+        now: Instant,
     ) -> FallbackDecision {
         if let Some((name, _)) = self.find_healthy_local_provider(context, local_health) {
             FallbackDecision::UseLocal {
                 provider_name: name.clone(),
                 reason: "Local provider available and healthy".to_string(),
+                model_override: None,
             }
-        } else if let Some(cloud_provider) = self.select_cloud_provider(context) {
+        } else if let Some(tiny) = self.decide_tiny_model(context, local_health) {
+            tiny
+        } else if let Some(cloud_provider) = self.select_cloud_provider(context, now) {
             let local_status = local_health.first().map(|(_, status)| status.clone());
             FallbackDecision::UseCloud {
                 provider_name: cloud_provider,
@@ -267,6 +494,7 @@ impl FallbackEngine {
         &self,
         context: &FallbackContext,
         local_health: &[(String, ProviderHealthStatus)],
+        now: Instant,
     ) -> FallbackDecision {
         // Check if we should retry local providers
         if context.consecutive_failures < self.config.max_retries {
@@ -283,12 +511,21 @@ impl FallbackEngine {
                     _ => "Local provider status unknown".to_string(),
                 };
 
-                return FallbackDecision::UseLocal { provider_name: name.clone(), reason };
+                return FallbackDecision::UseLocal {
+                    provider_name: name.clone(),
+                    reason,
+                    model_override: None,
+                };
             }
         }
 
+        // Simple requests can be served by a tiny local model before cloud
+        if let Some(tiny) = self.decide_tiny_model(context, local_health) {
+            return tiny;
+        }
+
         // Fallback to cloud if retries exhausted
-        if let Some(cloud_provider) = self.select_cloud_provider(context) {
+        if let Some(cloud_provider) = self.select_cloud_provider(context, now) {
             let local_status = local_health.first().map(|(_, status)| status.clone());
             FallbackDecision::UseCloud {
                 provider_name: cloud_provider,
@@ -306,6 +543,114 @@ impl FallbackEngine {
         }
     }
 
+    /// Run `operation` against local provider `provider_name`, retrying up to
+    /// `max_retries` times with capped, jittered exponential backoff. Rate
+    /// limited attempts wait longer, and failures retrying cannot fix (bad
+    /// credentials, an unknown model) are not retried. When every attempt
+    /// fails, returns the configured strategy's decision for an unusable
+    /// local provider. A cancelled attempt ends the operation without falling
+    /// back.
+    pub async fn execute_with_retry<T, F, Fut>(
+        &self,
+        context: &FallbackContext,
+        provider_name: &str,
+        mut operation: F,
+    ) -> RetryOutcome<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let mut attempts = 0;
+        let result = retry_scaled(
+            &self.config.retry_policy(),
+            |error: &anyhow::Error| match FailureKind::classify(error) {
+                FailureKind::RateLimited => Some(RATE_LIMIT_BACKOFF_MULTIPLIER),
+                kind if kind.is_retryable() => Some(1),
+                _ => None,
+            },
+            || {
+                attempts += 1;
+                operation()
+            },
+        )
+        .await;
+        let error = match result {
+            Ok(value) => return RetryOutcome::Completed(value),
+            Err(error) => error,
+        };
+        let kind = FailureKind::classify(&error);
+        if kind == FailureKind::Cancelled {
+            return RetryOutcome::Cancelled(error);
+        }
+        let retry = attempts - 1;
+        warn!(
+            provider = provider_name,
+            attempts,
+            ?kind,
+            error = %error,
+            "Local provider failed"
+        );
+
+        let failure = if kind.is_retryable() {
+            format!("Local provider '{provider_name}' failed after {retry} retries: {error}")
+        } else {
+            format!("Local provider '{provider_name}' failed without retrying ({kind:?}): {error}")
+        };
+
+        // Decide as for any unusable local provider, so the configured
+        // strategy still applies and local-only setups never go to cloud
+        let local_health = [(
+            provider_name.to_string(),
+            ProviderHealthStatus::Unhealthy {
+                reason: failure.clone(),
+                response_time: Duration::ZERO,
+            },
+        )];
+        let context = context
+            .clone()
+            .with_consecutive_failures(context.consecutive_failures.max(self.config.max_retries));
+        let decision = self
+            .decide_provider_at(&context, &local_health, Instant::now())
+            .await;
+        let reason = format!("{failure}. {}", decision.reason());
+        RetryOutcome::Fallback(decision.with_reason(reason))
+    }
+
+    /// Route a simple request to the configured tiny model when its provider
+    /// is usable
+    fn decide_tiny_model(
+        &self,
+        context: &FallbackContext,
+        local_health: &[(String, ProviderHealthStatus)],
+    ) -> Option<FallbackDecision> {
+        let tiny = &self.config.tiny_model;
+        if !tiny.enabled || tiny.classify(context) != RequestComplexity::Simple {
+            return None;
+        }
+
+        let usable = local_health
+            .iter()
+            .any(|(name, status)| name == &tiny.provider && status.is_usable());
+        if !usable || !self.provider_supports_model(&tiny.provider, &tiny.model) {
+            return None;
+        }
+
+        debug!(
+            provider = %tiny.provider,
+            model = %tiny.model,
+            requested_model = %context.model_id,
+            "Routing simple request to tiny local model"
+        );
+        Some(FallbackDecision::UseLocal {
+            provider_name: tiny.provider.clone(),
+            reason: format!(
+                "Requested model unavailable locally, simple request routed to tiny model {}",
+                tiny.model
+            ),
+            model_override: Some(tiny.model.clone()),
+        })
+    }
+
     /// Find a healthy local provider that supports the requested model
     fn find_healthy_local_provider<'a>(
         &self,
@@ -338,46 +683,192 @@ impl FallbackEngine {
                 return true;
             }
 
-            // Check if model is in preferred list or matches pattern
-            provider_config.preferred_models.iter().any(|preferred| {
-                preferred == model_id || model_id.starts_with(&preferred.replace(":latest", ""))
-            })
+            // Check if the model resolves to one in the preferred list
+            provider_config
+                .preferred_models
+                .iter()
+                .any(|preferred| self.aliases.matches(model_id, preferred))
         } else {
             false
         }
     }
 
-    /// Select a cloud provider based on context and availability
-    fn select_cloud_provider(&self, context: &FallbackContext) -> Option<String> {
-        // For now, simple round-robin selection
-        // In the future, this could be more sophisticated based on:
-        // - Provider capabilities (streaming, tools, etc.)
-        // - Model availability
-        // - Performance metrics
-        // - User preferences
-
-        if self.config.cloud_providers.is_empty() {
-            return None;
+    /// Select a cloud provider based on context and availability, skipping
+    /// providers whose circuit breaker refuses calls at `now`. A half-open
+    /// breaker grants its single probe to the provider selected here.
+    fn select_cloud_provider(&self, context: &FallbackContext, now: Instant) -> Option<String> {
+        let mut breakers = self.cloud_breakers.lock().unwrap();
+
+        // Use the model's routed providers when any of them is available,
+        // otherwise the global list
+        let mut candidates = self
+            .routed_cloud_providers(&context.model_id)
+            .map(|providers| self.available_cloud_providers(providers, context, now, &mut breakers))
+            .filter(|candidates| !candidates.is_empty())
+            .unwrap_or_else(|| {
+                self.available_cloud_providers(
+                    &self.config.cloud_providers,
+                    context,
+                    now,
+                    &mut breakers,
+                )
+            });
+
+        // A provider whose breaker refuses the call is skipped for the next
+        // candidate
+        while let Some(selected) = self.pick_cloud_provider(&candidates).cloned() {
+            if breakers
+                .get_mut(&selected)
+                .is_some_and(|breaker| breaker.try_acquire_at(now))
+            {
+                return Some(selected);
+            }
+            debug!(provider = %selected, "Skipping cloud provider refused by its circuit breaker");
+            candidates.retain(|candidate| **candidate != selected);
         }
+        None
+    }
 
-        // Prefer providers that support the required features
-        let suitable_providers: Vec<_> = self
-            .config
-            .cloud_providers
+    /// Whether a cloud provider not excluded by `context` would accept a call
+    /// at `now`, without taking a half-open breaker's probe
+    pub fn has_available_cloud_provider_at(&self, context: &FallbackContext, now: Instant) -> bool {
+        let mut breakers = self.cloud_breakers.lock().unwrap();
+        let routed = self
+            .routed_cloud_providers(&context.model_id)
+            .unwrap_or_default();
+        [routed, self.config.cloud_providers.as_slice()]
             .iter()
-            .filter(|provider| self.cloud_provider_supports_features(provider, context))
-            .collect();
+            .any(|providers| {
+                !self
+                    .available_cloud_providers(providers, context, now, &mut breakers)
+                    .is_empty()
+            })
+    }
+
+    /// Cloud providers routed to `model_id` by the longest matching
+    /// `model_routing` pattern
+    fn routed_cloud_providers(&self, model_id: &str) -> Option<&[String]> {
+        self.config
+            .model_routing
+            .iter()
+            .filter(|(pattern, _)| self.model_matches(pattern, model_id))
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map(|(_, providers)| providers.as_slice())
+    }
 
-        if !suitable_providers.is_empty() {
-            Some(suitable_providers[0].clone())
+    /// Whether `model_id` starts with `pattern` once both are normalized
+    /// through the configured model aliases
+    fn model_matches(&self, pattern: &str, model_id: &str) -> bool {
+        self.aliases
+            .normalize(model_id)
+            .starts_with(&self.aliases.normalize(pattern))
+    }
+
+    /// Providers from `providers` not excluded by `context` whose circuit
+    /// breaker allows a call at `now`, preferring those that support the
+    /// required features and falling back to the rest when none of those are
+    /// available
+    fn available_cloud_providers<'a>(
+        &self,
+        providers: &'a [String],
+        context: &FallbackContext,
+        now: Instant,
+        breakers: &mut HashMap<String, CircuitBreaker>,
+    ) -> Vec<&'a String> {
+        let (suitable, others): (Vec<_>, Vec<_>) = providers
+            .iter()
+            .filter(|provider| !context.excluded_cloud_providers.contains(provider))
+            .filter(|provider| {
+                let available = breakers
+                    .entry(provider.to_string())
+                    .or_insert_with(|| CircuitBreaker::new(self.config.cloud_breaker.clone()))
+                    .is_available_at(now);
+                if !available {
+                    debug!(provider = %provider, "Skipping cloud provider with open circuit");
+                }
+                available
+            })
+            .partition(|provider| self.cloud_provider_supports_features(provider, context));
+        if suitable.is_empty() {
+            others
         } else {
-            // Fallback to first available provider
-            Some(self.config.cloud_providers[0].clone())
+            suitable
         }
     }
 
+    /// Choose among available `candidates` according to the configured
+    /// [`CloudSelectionStrategy`]
+    fn pick_cloud_provider<'a>(&self, candidates: &[&'a String]) -> Option<&'a String> {
+        if candidates.is_empty() {
+            return None;
+        }
+        let mut state = self.cloud_selection.lock().unwrap();
+        match &self.config.cloud_selection {
+            CloudSelectionStrategy::FirstAvailable => Some(candidates[0]),
+            CloudSelectionStrategy::RoundRobin => {
+                let selected = candidates[state.cursor % candidates.len()];
+                state.cursor = state.cursor.wrapping_add(1);
+                Some(selected)
+            }
+            CloudSelectionStrategy::Weighted(weights) => {
+                let weight = |provider: &String| weights.get(provider).copied().unwrap_or(0) as u64;
+                let total: u64 = candidates.iter().map(|provider| weight(provider)).sum();
+                if total == 0 {
+                    return Some(candidates[0]);
+                }
+                let mut point = state.next_random() % total;
+                candidates.iter().copied().find(|provider| {
+                    let weight = weight(provider);
+                    if point < weight {
+                        true
+                    } else {
+                        point -= weight;
+                        false
+                    }
+                })
+            }
+        }
+    }
+
+    /// Record a successful call to `provider`, closing its circuit breaker
+    pub fn record_cloud_success(&self, provider: &str) {
+        if let Some(breaker) = self.cloud_breakers.lock().unwrap().get_mut(provider) {
+            breaker.record_success();
+        }
+    }
+
+    /// Record a failed call to `provider`
+    pub fn record_cloud_failure(&self, provider: &str) {
+        self.record_cloud_failure_at(provider, Instant::now());
+    }
+
+    /// Record a failed call to `provider` at `now`, opening its circuit
+    /// breaker after enough consecutive failures
+    pub fn record_cloud_failure_at(&self, provider: &str, now: Instant) {
+        self.cloud_breakers
+            .lock()
+            .unwrap()
+            .entry(provider.to_string())
+            .or_insert_with(|| CircuitBreaker::new(self.config.cloud_breaker.clone()))
+            .record_failure_at(now);
+    }
+
+    /// Circuit breaker state of `provider` at `now`
+    pub fn cloud_breaker_state_at(&self, provider: &str, now: Instant) -> CircuitState {
+        self.cloud_breakers
+            .lock()
+            .unwrap()
+            .get(provider)
+            .map(|breaker| breaker.state_at(now))
+            .unwrap_or(CircuitState::Closed)
+    }
+
     /// Check if a cloud provider supports the required features
-    fn cloud_provider_supports_features(&self, provider: &str, context: &FallbackContext) -> bool {
+    pub(crate) fn cloud_provider_supports_features(
+        &self,
+        provider: &str,
+        context: &FallbackContext,
+    ) -> bool {
         match provider {
             "openai" => {
                 // OpenAI supports streaming and tools
@@ -451,6 +942,14 @@ impl FallbackDecision {
         }
     }
 
+    /// Get the model to use instead of the requested one, if any
+    pub fn model_override(&self) -> Option<&str> {
+        match self {
+            FallbackDecision::UseLocal { model_override, .. } => model_override.as_deref(),
+            _ => None,
+        }
+    }
+
     /// Get the reason for this decision
     pub fn reason(&self) -> &str {
         match self {
@@ -460,6 +959,17 @@ impl FallbackDecision {
             | FallbackDecision::NoProvider { reason, .. } => reason,
         }
     }
+
+    /// Replace the reason for this decision
+    fn with_reason(mut self, new_reason: String) -> Self {
+        match &mut self {
+            FallbackDecision::UseLocal { reason, .. }
+            | FallbackDecision::UseCloud { reason, .. }
+            | FallbackDecision::RequireManual { reason, .. }
+            | FallbackDecision::NoProvider { reason, .. } => *reason = new_reason,
+        }
+        self
+    }
 }
 
 impl FallbackContext {
@@ -472,6 +982,9 @@ impl FallbackContext {
             previous_provider: None,
             consecutive_failures: 0,
             time_since_last_success: None,
+            prompt_chars: None,
+            excluded_cloud_providers: Vec::new(),
+            idempotency_key: None,
         }
     }
 
@@ -504,16 +1017,35 @@ impl FallbackContext {
         self.time_since_last_success = Some(time);
         self
     }
+
+    /// Set prompt length in characters
+    pub fn with_prompt_chars(mut self, chars: usize) -> Self {
+        self.prompt_chars = Some(chars);
+        self
+    }
+
+    /// Set cloud providers that must not be selected
+    pub fn with_excluded_cloud_providers(mut self, providers: Vec<String>) -> Self {
+        self.excluded_cloud_providers = providers;
+        self
+    }
+
+    /// Set the idempotency key of the request
+    pub fn with_idempotency_key(mut self, key: IdempotencyKey) -> Self {
+        self.idempotency_key = Some(key);
+        self
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::time::Duration;
 
     use pretty_assertions::assert_eq;
 
     use super::*;
-    use crate::config::local_ai::LocalAiConfig;
+    use crate::config::local_ai::{LocalAiConfig, LocalProviderConfig};
 
     fn create_test_local_config() -> LocalAiConfig {
         LocalAiConfig::with_default_ollama()
@@ -548,6 +1080,14 @@ mod tests {
         assert!(actual.is_err());
     }
 
+    #[test]
+    fn test_fallback_config_validation_invalid_routing_pattern() {
+        let fixture =
+            FallbackConfig::default().routing_rules(vec![RoutingRule::regex("*-70b", "gpu-pool")]);
+        let actual = fixture.validate();
+        assert!(actual.is_err());
+    }
+
     #[tokio::test]
     async fn test_fallback_engine_local_only_healthy() {
         let config = FallbackConfig::default().strategy(FallbackStrategy::None);
@@ -562,6 +1102,28 @@ mod tests {
         assert_eq!(actual.provider_name(), Some("ollama"));
     }
 
+    #[tokio::test]
+    async fn test_fallback_engine_resolves_model_aliases() {
+        let config = FallbackConfig::default().strategy(FallbackStrategy::None);
+        let local_config = create_test_local_config().model_aliases(HashMap::from([(
+            "coder".to_string(),
+            "codellama:latest".to_string(),
+        )]));
+        let engine = FallbackEngine::new(config, local_config);
+        let health = vec![("ollama".to_string(), create_healthy_status())];
+
+        let mut actual = Vec::new();
+        for model in ["llama3", "coder", "mistral"] {
+            let decision = engine
+                .decide_provider(&FallbackContext::new(model.to_string()), &health)
+                .await;
+            actual.push(decision.is_local());
+        }
+
+        let expected = vec![true, true, false];
+        assert_eq!(actual, expected);
+    }
+
     #[tokio::test]
     async fn test_fallback_engine_local_only_unhealthy() {
         let config = FallbackConfig::default().strategy(FallbackStrategy::None);
@@ -628,11 +1190,477 @@ mod tests {
         assert!(actual.is_cloud());
     }
 
+    fn breaker_engine() -> FallbackEngine {
+        let config = FallbackConfig::default()
+            .strategy(FallbackStrategy::Immediate)
+            .cloud_breaker(
+                CircuitBreakerConfig::default()
+                    .failure_threshold(2u32)
+                    .cooldown(Duration::from_secs(30))
+                    .probe_timeout(Duration::from_secs(60)),
+            );
+        FallbackEngine::new(config, create_test_local_config())
+    }
+
+    #[tokio::test]
+    async fn test_repeated_cloud_failures_open_breaker_and_skip_provider() {
+        let fixture = breaker_engine();
+        let start = Instant::now();
+        let context = FallbackContext::new("gpt-4".to_string());
+        let health = vec![("ollama".to_string(), create_unhealthy_status())];
+
+        fixture.record_cloud_failure_at("openai", start);
+        let after_one = fixture.decide_provider_at(&context, &health, start).await;
+        fixture.record_cloud_failure_at("openai", start);
+        let after_two = fixture.decide_provider_at(&context, &health, start).await;
+
+        let actual = (
+            after_one.provider_name().map(str::to_string),
+            after_two.provider_name().map(str::to_string),
+            fixture.cloud_breaker_state_at("openai", start),
+        );
+        let expected = (
+            Some("openai".to_string()),
+            Some("anthropic".to_string()),
+            CircuitState::Open,
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_cloud_breaker_allows_single_probe_after_cooldown() {
+        let fixture = breaker_engine();
+        let start = Instant::now();
+        let later = start + Duration::from_secs(30);
+        let context = FallbackContext::new("gpt-4".to_string());
+        let health = vec![("ollama".to_string(), create_unhealthy_status())];
+        for provider in ["openai", "anthropic"] {
+            fixture.record_cloud_failure_at(provider, start);
+            fixture.record_cloud_failure_at(provider, start);
+        }
+
+        let during_cooldown = fixture.decide_provider_at(&context, &health, start).await;
+        let state = fixture.cloud_breaker_state_at("openai", later);
+        let probe = fixture.decide_provider_at(&context, &health, later).await;
+        let second = fixture.decide_provider_at(&context, &health, later).await;
+        let third = fixture.decide_provider_at(&context, &health, later).await;
+        fixture.record_cloud_success("openai");
+        let recovered = fixture.decide_provider_at(&context, &health, later).await;
+
+        assert!(during_cooldown.no_provider());
+        assert_eq!(state, CircuitState::HalfOpen);
+        let actual = [&probe, &second, &recovered].map(|decision| decision.provider_name());
+        assert_eq!(actual, [Some("openai"), Some("anthropic"), Some("openai")]);
+        assert!(third.no_provider());
+    }
+
+    #[tokio::test]
+    async fn test_unused_cloud_probe_is_released_after_timeout() {
+        let fixture = breaker_engine();
+        let start = Instant::now();
+        let probe_at = start + Duration::from_secs(30);
+        let context = FallbackContext::new("gpt-4".to_string());
+        let health = vec![("ollama".to_string(), create_unhealthy_status())];
+        for provider in ["openai", "anthropic"] {
+            fixture.record_cloud_failure_at(provider, start);
+            fixture.record_cloud_failure_at(provider, start);
+        }
+
+        // Both probes are taken by decisions whose requests are never sent
+        fixture
+            .decide_provider_at(&context, &health, probe_at)
+            .await;
+        fixture
+            .decide_provider_at(&context, &health, probe_at)
+            .await;
+        let blocked = fixture
+            .decide_provider_at(&context, &health, probe_at + Duration::from_secs(59))
+            .await;
+        let released = fixture
+            .decide_provider_at(&context, &health, probe_at + Duration::from_secs(60))
+            .await;
+
+        assert!(blocked.no_provider());
+        assert_eq!(released.provider_name(), Some("openai"));
+    }
+
+    async fn cloud_selections(fixture: &FallbackEngine, calls: usize) -> HashMap<String, usize> {
+        let context = FallbackContext::new("gpt-4".to_string());
+        let health = vec![("ollama".to_string(), create_unhealthy_status())];
+        let mut counts = HashMap::new();
+        for _ in 0..calls {
+            let decision = fixture.decide_provider(&context, &health).await;
+            *counts
+                .entry(decision.provider_name().unwrap().to_string())
+                .or_default() += 1;
+        }
+        counts
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_spreads_cloud_requests_evenly() {
+        let config = FallbackConfig::default()
+            .strategy(FallbackStrategy::Immediate)
+            .cloud_selection(CloudSelectionStrategy::RoundRobin);
+        let fixture = FallbackEngine::new(config, create_test_local_config());
+
+        let actual = cloud_selections(&fixture, 100).await;
+
+        let expected = HashMap::from([("openai".to_string(), 50), ("anthropic".to_string(), 50)]);
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_weighted_selection_is_proportional_and_seeded() {
+        let weights = HashMap::from([("openai".to_string(), 3), ("anthropic".to_string(), 1)]);
+        let fixture = || {
+            let config = FallbackConfig::default()
+                .strategy(FallbackStrategy::Immediate)
+                .cloud_selection(CloudSelectionStrategy::Weighted(weights.clone()));
+            FallbackEngine::new(config, create_test_local_config()).with_selection_seed(42)
+        };
+
+        let actual = cloud_selections(&fixture(), 1000).await;
+        let repeated = cloud_selections(&fixture(), 1000).await;
+
+        assert_eq!(actual, repeated);
+        assert_eq!(actual.values().sum::<usize>(), 1000);
+        let openai = actual["openai"];
+        assert!(
+            (700..=800).contains(&openai),
+            "openai selected {openai} times"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_model_routing_overrides_default_cloud_providers() {
+        let config = FallbackConfig::default()
+            .strategy(FallbackStrategy::Immediate)
+            .cloud_providers(vec!["openrouter".to_string()])
+            .model_routing(HashMap::from([
+                ("gpt-4".to_string(), vec!["openai".to_string()]),
+                ("claude".to_string(), vec!["anthropic".to_string()]),
+            ]));
+        let fixture = FallbackEngine::new(config, create_test_local_config());
+        let health = vec![("ollama".to_string(), create_unhealthy_status())];
+
+        let mut actual = Vec::new();
+        for model in ["gpt-4-turbo", "claude-3-5-sonnet", "mistral-large"] {
+            let context = FallbackContext::new(model.to_string());
+            let decision = fixture.decide_provider(&context, &health).await;
+            actual.push(decision.provider_name().map(str::to_string));
+        }
+
+        let expected = vec![
+            Some("openai".to_string()),
+            Some("anthropic".to_string()),
+            Some("openrouter".to_string()),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_model_routing_falls_back_when_routed_providers_are_open() {
+        let config = FallbackConfig::default()
+            .strategy(FallbackStrategy::Immediate)
+            .cloud_breaker(CircuitBreakerConfig::default().failure_threshold(1u32))
+            .model_routing(HashMap::from([(
+                "gpt-4".to_string(),
+                vec!["azure".to_string()],
+            )]));
+        let fixture = FallbackEngine::new(config, create_test_local_config());
+        let health = vec![("ollama".to_string(), create_unhealthy_status())];
+        fixture.record_cloud_failure("azure");
+
+        let context = FallbackContext::new("gpt-4".to_string());
+        let actual = fixture.decide_provider(&context, &health).await;
+
+        assert_eq!(actual.provider_name(), Some("openai"));
+    }
+
+    #[tokio::test]
+    async fn test_model_routing_matches_through_aliases() {
+        let config = FallbackConfig::default()
+            .strategy(FallbackStrategy::Immediate)
+            .model_routing(HashMap::from([
+                ("claude-3-5".to_string(), vec!["anthropic".to_string()]),
+                ("llama3:latest".to_string(), vec!["groq".to_string()]),
+            ]));
+        let local_config = create_test_local_config().model_aliases(HashMap::from([(
+            "sonnet".to_string(),
+            "claude-3-5-sonnet".to_string(),
+        )]));
+        let fixture = FallbackEngine::new(config, local_config);
+        let health = vec![("ollama".to_string(), create_unhealthy_status())];
+
+        let mut actual = Vec::new();
+        for model in ["sonnet:latest", "llama3.2:latest"] {
+            let context = FallbackContext::new(model.to_string());
+            let decision = fixture.decide_provider(&context, &health).await;
+            actual.push(decision.provider_name().map(str::to_string));
+        }
+
+        let expected = vec![Some("anthropic".to_string()), Some("groq".to_string())];
+        assert_eq!(actual, expected);
+    }
+
+    fn retry_engine() -> FallbackEngine {
+        let config = FallbackConfig::default()
+            .max_retries(3u32)
+            .retry_delay_ms(100u64)
+            .max_retry_delay_ms(150u64);
+        FallbackEngine::new(config, create_test_local_config()).with_selection_seed(7)
+    }
+
+    #[test]
+    fn test_retry_backoff_doubles_up_to_cap() {
+        let fixture = FallbackConfig::default()
+            .retry_delay_ms(100u64)
+            .max_retry_delay_ms(1000u64)
+            .retry_policy();
+
+        let actual: Vec<_> = (0..6).map(|retry| fixture.backoff(retry)).collect();
+
+        let expected = [100, 200, 400, 800, 1000, 1000].map(Duration::from_millis);
+        assert_eq!(actual, expected);
+        assert_eq!(fixture.backoff(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_execute_with_retry_backs_off_until_success() {
+        let fixture = retry_engine();
+        let context = FallbackContext::new("llama3.2:latest".to_string());
+        let mut attempts = 0;
+        let start = tokio::time::Instant::now();
+
+        let actual = fixture
+            .execute_with_retry(&context, "ollama", || {
+                attempts += 1;
+                let attempt = attempts;
+                async move {
+                    if attempt <= 2 {
+                        anyhow::bail!("connection refused")
+                    }
+                    Ok(attempt)
+                }
+            })
+            .await;
+
+        // Two jittered delays: 50-100ms then 100-150ms (capped)
+        let elapsed = start.elapsed();
+        assert!(matches!(actual, RetryOutcome::Completed(3)));
+        assert!(
+            (Duration::from_millis(150)..=Duration::from_millis(250)).contains(&elapsed),
+            "elapsed {elapsed:?}"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_execute_with_retry_falls_back_to_cloud_when_exhausted() {
+        let fixture = retry_engine();
+        let context = FallbackContext::new("llama3.2:latest".to_string());
+        let mut attempts = 0;
+        let start = tokio::time::Instant::now();
+
+        let actual = fixture
+            .execute_with_retry(&context, "ollama", || {
+                attempts += 1;
+                async { Err::<(), _>(anyhow::anyhow!("connection refused")) }
+            })
+            .await;
+
+        let RetryOutcome::Fallback(decision) = actual else {
+            panic!("expected a fallback decision");
+        };
+        assert_eq!(attempts, 4);
+        assert_eq!(decision.provider_name(), Some("openai"));
+        assert!(decision.reason().contains("failed after 3 retries"));
+        // Three jittered delays of at most 100ms, 150ms and 150ms
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        assert!(start.elapsed() <= Duration::from_millis(400));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_execute_with_retry_follows_configured_strategy() {
+        let context = FallbackContext::new("llama3.2:latest".to_string());
+
+        let mut actual = Vec::new();
+        for strategy in [FallbackStrategy::None, FallbackStrategy::Manual] {
+            let fixture = FallbackEngine::new(
+                FallbackConfig::default()
+                    .strategy(strategy)
+                    .max_retries(1u32)
+                    .retry_delay_ms(10u64),
+                create_test_local_config(),
+            );
+            let outcome = fixture
+                .execute_with_retry(&context, "ollama", || async {
+                    Err::<(), _>(anyhow::anyhow!("connection refused"))
+                })
+                .await;
+            let RetryOutcome::Fallback(decision) = outcome else {
+                panic!("expected a fallback decision");
+            };
+            assert!(decision.reason().contains("failed after 1 retries"));
+            actual.push((decision.no_provider(), decision.requires_manual()));
+        }
+
+        // Local-only fails outright, manual asks the user instead of going to
+        // cloud
+        assert_eq!(actual, vec![(true, false), (false, true)]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_execute_with_retry_skips_permanent_failures() {
+        let fixture = retry_engine();
+        let context = FallbackContext::new("llama3.2:latest".to_string());
+
+        for (error, kind) in [
+            (
+                crate::ollama::OllamaError::AuthenticationFailed { message: "bad key".to_string() },
+                "AuthFailure",
+            ),
+            (
+                crate::ollama::OllamaError::model_not_found("llama3.2:latest".to_string()),
+                "ModelNotFound",
+            ),
+        ] {
+            let mut attempts = 0;
+            let mut error = Some(error);
+            let start = tokio::time::Instant::now();
+
+            let actual = fixture
+                .execute_with_retry(&context, "ollama", || {
+                    attempts += 1;
+                    let error = error.take().map(anyhow::Error::from);
+                    async move { Err::<(), _>(error.unwrap()) }
+                })
+                .await;
+
+            let RetryOutcome::Fallback(decision) = actual else {
+                panic!("expected a fallback decision");
+            };
+            assert_eq!((attempts, start.elapsed()), (1, Duration::ZERO));
+            assert_eq!(decision.provider_name(), Some("openai"));
+            assert!(decision.reason().contains(kind), "{}", decision.reason());
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_execute_with_retry_backs_off_longer_when_rate_limited() {
+        let fixture = retry_engine();
+        let context = FallbackContext::new("llama3.2:latest".to_string());
+        let mut attempts = 0;
+        let start = tokio::time::Instant::now();
+
+        let actual = fixture
+            .execute_with_retry(&context, "ollama", || {
+                attempts += 1;
+                let attempt = attempts;
+                async move {
+                    if attempt == 1 {
+                        return Err(crate::ollama::OllamaError::RateLimitExceeded.into());
+                    }
+                    Ok(attempt)
+                }
+            })
+            .await;
+
+        // One jittered delay of 200-400ms instead of 50-100ms
+        let elapsed = start.elapsed();
+        assert!(matches!(actual, RetryOutcome::Completed(2)));
+        assert!(
+            (Duration::from_millis(200)..=Duration::from_millis(400)).contains(&elapsed),
+            "elapsed {elapsed:?}"
+        );
+    }
+
+    fn tiny_model_engine() -> FallbackEngine {
+        let config = FallbackConfig::default().tiny_model(
+            TinyModelFallback::default()
+                .enabled(true)
+                .provider("ollama-tiny")
+                .model("qwen2.5:0.5b"),
+        );
+        let mut local_config = create_test_local_config();
+        local_config
+            .providers
+            .get_mut("ollama")
+            .unwrap()
+            .preferred_models = vec!["llama3.2:latest".to_string()];
+        local_config.providers.insert(
+            "ollama-tiny".to_string(),
+            LocalProviderConfig::default().preferred_models(vec!["qwen2.5:0.5b".to_string()]),
+        );
+        FallbackEngine::new(config, local_config)
+    }
+
+    #[tokio::test]
+    async fn test_simple_request_falls_back_to_tiny_model() {
+        let engine = tiny_model_engine();
+        let context = FallbackContext::new("llama3.2:latest".to_string())
+            .with_prompt_chars(40)
+            .with_consecutive_failures(3);
+        let health = vec![
+            ("ollama".to_string(), create_unhealthy_status()),
+            ("ollama-tiny".to_string(), create_healthy_status()),
+        ];
+
+        let actual = engine.decide_provider(&context, &health).await;
+
+        assert!(actual.is_local());
+        assert_eq!(actual.provider_name(), Some("ollama-tiny"));
+        assert_eq!(actual.model_override(), Some("qwen2.5:0.5b"));
+    }
+
+    #[tokio::test]
+    async fn test_complex_request_escalates_to_cloud() {
+        let engine = tiny_model_engine();
+        let health = vec![
+            ("ollama".to_string(), create_unhealthy_status()),
+            ("ollama-tiny".to_string(), create_healthy_status()),
+        ];
+        let with_tools = FallbackContext::new("llama3.2:latest".to_string())
+            .with_prompt_chars(40)
+            .with_tools(true);
+        let long_prompt =
+            FallbackContext::new("llama3.2:latest".to_string()).with_prompt_chars(10_000);
+
+        let actual = vec![
+            engine.decide_provider(&with_tools, &health).await,
+            engine.decide_provider(&long_prompt, &health).await,
+        ];
+
+        for decision in actual {
+            assert!(decision.is_cloud());
+            assert_eq!(decision.provider_name(), Some("openai"));
+        }
+    }
+
+    #[test]
+    fn test_tiny_model_classification() {
+        let fixture = TinyModelFallback::default().max_prompt_chars(100usize);
+
+        let actual = vec![
+            fixture.classify(&FallbackContext::new("m".to_string()).with_prompt_chars(100)),
+            fixture.classify(&FallbackContext::new("m".to_string()).with_prompt_chars(101)),
+            fixture.classify(&FallbackContext::new("m".to_string())),
+        ];
+
+        let expected = vec![
+            RequestComplexity::Simple,
+            RequestComplexity::Complex,
+            RequestComplexity::Complex,
+        ];
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_fallback_decision_properties() {
         let local_decision = FallbackDecision::UseLocal {
             provider_name: "ollama".to_string(),
             reason: "Healthy".to_string(),
+            model_override: None,
         };
         assert!(local_decision.is_local());
         assert!(!local_decision.is_cloud());
@@ -3,10 +3,15 @@ use std::time::Duration;
 
 use anyhow::Context as _;
 use derive_setters::Setters;
+use forge_app::domain::Provider;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
 
+use crate::config::aliases::ModelAliasResolver;
+use crate::forge_provider::ForgeProvider;
+use crate::health::HealthCheckerFactory;
 use crate::ollama::{HealthStatus, OllamaConfig, OllamaHealthCheck};
+use crate::performance::IdleEvictionConfig;
 
 /// Configuration for local AI providers
 #[derive(Debug, Clone, Serialize, Deserialize, Setters)]
@@ -18,6 +23,10 @@ pub struct LocalAiConfig {
     pub providers: HashMap<String, LocalProviderConfig>,
     /// Global settings for local AI
     pub settings: LocalAiSettings,
+    /// Model names users may type in place of the model ids providers serve,
+    /// such as `coder -> qwen2.5-coder:7b`
+    #[serde(default)]
+    pub model_aliases: HashMap<String, String>,
 }
 
 /// Configuration for a specific local provider
@@ -36,6 +45,34 @@ pub struct LocalProviderConfig {
     pub config: ProviderSpecificConfig,
     /// Health check settings
     pub health_check: HealthCheckConfig,
+    /// Maximum requests dispatched to this provider at once; 0 means no
+    /// limit
+    #[serde(default)]
+    pub max_concurrent_requests: usize,
+    /// What happens to a request while the provider is at
+    /// `max_concurrent_requests`
+    #[serde(default)]
+    pub saturation_policy: SaturationPolicy,
+    /// Longest a queued request waits for a slot before it moves on to the
+    /// next provider, in milliseconds
+    #[serde(default = "default_queue_timeout_ms")]
+    pub queue_timeout_ms: u64,
+    /// Labels such as a team name. Requests that require tags only select
+    /// providers carrying at least one of them.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// How requests are handled once a provider reaches its concurrency limit
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SaturationPolicy {
+    /// Wait up to `queue_timeout_ms` for a request in flight to finish
+    #[default]
+    Queue,
+    /// Move on to the next provider, for latency-sensitive workloads such as
+    /// streaming where waiting in line would stall the first token
+    Fallback,
 }
 
 /// Provider-specific configuration
@@ -50,6 +87,30 @@ pub enum ProviderSpecificConfig {
         connection_pooling: bool,
         user_agent: Option<String>,
     },
+    /// Any server exposing an OpenAI-compatible API, such as vLLM, LM Studio
+    /// or text-generation-webui
+    #[serde(rename = "openai_compatible")]
+    OpenAiCompatible {
+        /// API root, e.g. `http://localhost:8000/v1`
+        base_url: String,
+        /// Sent as a bearer token when set
+        api_key: Option<String>,
+        /// Models endpoint relative to `base_url`
+        #[serde(default = "default_models_path")]
+        models_path: String,
+    },
+    /// A provider kind whose health checker is registered with a
+    /// [`HealthCheckerFactory`] under the provider's `provider_type`
+    #[serde(rename = "custom")]
+    Custom {
+        /// Settings interpreted by the registered checker
+        #[serde(default)]
+        options: HashMap<String, serde_json::Value>,
+    },
+}
+
+fn default_models_path() -> String {
+    "models".to_string()
 }
 
 /// Health check configuration
@@ -64,6 +125,38 @@ pub struct HealthCheckConfig {
     pub failure_threshold: u32,
     /// Number of consecutive successes before marking healthy
     pub success_threshold: u32,
+    /// Ceiling for the check interval while backing off from a failing
+    /// provider
+    #[serde(default = "default_max_backoff_seconds")]
+    pub max_backoff_seconds: u64,
+    /// Randomly stretch or shrink each check interval by up to this fraction
+    /// of itself, so providers sharing an interval are not all checked at
+    /// the same instant
+    #[serde(default)]
+    pub jitter_fraction: Option<f64>,
+    /// How thoroughly each check exercises the provider
+    #[serde(default)]
+    pub depth: HealthCheckDepth,
+    /// Report a provider that passes its check but takes longer than this
+    /// many milliseconds to respond as degraded, whatever its checker says
+    #[serde(default)]
+    pub degraded_response_time_threshold_ms: Option<u64>,
+}
+
+/// How thoroughly a health check exercises a provider
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthCheckDepth {
+    /// List the provider's models
+    #[default]
+    Shallow,
+    /// List models, then generate a single token with the first preferred
+    /// model, catching servers that respond but cannot generate
+    Deep,
+}
+
+fn default_max_backoff_seconds() -> u64 {
+    300
 }
 
 /// Global settings for local AI
@@ -75,6 +168,9 @@ pub struct LocalAiSettings {
     pub discovery: DiscoveryConfig,
     /// Performance monitoring settings
     pub monitoring: MonitoringConfig,
+    /// Unloading of models left idle on local providers
+    #[serde(default)]
+    pub idle_eviction: IdleEvictionConfig,
 }
 
 /// Service discovery configuration
@@ -84,11 +180,42 @@ pub struct DiscoveryConfig {
     /// Whether to enable automatic service discovery
     pub enabled: bool,
     /// Ports to scan for services
+    #[serde(alias = "candidate_ports")]
     pub scan_ports: Vec<u16>,
     /// Hosts to scan
+    #[serde(alias = "candidate_hosts")]
     pub scan_hosts: Vec<String>,
+    /// Longest a single scan probe may take, in milliseconds
+    #[serde(default = "default_probe_timeout_ms")]
+    pub probe_timeout_ms: u64,
+    /// Number of scan probes run at once
+    #[serde(default = "default_probe_concurrency")]
+    pub concurrency: usize,
     /// Discovery interval in seconds
     pub interval_seconds: u64,
+    /// How long models discovered from a healthy provider are served from
+    /// cache before the provider is probed again
+    #[serde(default = "default_cache_ttl_seconds")]
+    pub cache_ttl_seconds: u64,
+    /// Per-provider overrides of `cache_ttl_seconds`
+    #[serde(default)]
+    pub provider_cache_ttl_seconds: HashMap<String, u64>,
+}
+
+fn default_cache_ttl_seconds() -> u64 {
+    300
+}
+
+fn default_probe_timeout_ms() -> u64 {
+    3000
+}
+
+fn default_probe_concurrency() -> usize {
+    8
+}
+
+fn default_queue_timeout_ms() -> u64 {
+    30_000
 }
 
 /// Performance monitoring configuration
@@ -109,6 +236,7 @@ impl Default for LocalAiConfig {
             enabled: true,
             providers: HashMap::new(),
             settings: LocalAiSettings::default(),
+            model_aliases: HashMap::new(),
         }
     }
 }
@@ -131,6 +259,10 @@ impl Default for LocalProviderConfig {
                 user_agent: Some("forge-ai/1.0".to_string()),
             },
             health_check: HealthCheckConfig::default(),
+            max_concurrent_requests: 0,
+            saturation_policy: SaturationPolicy::default(),
+            queue_timeout_ms: default_queue_timeout_ms(),
+            tags: Vec::new(),
         }
     }
 }
@@ -142,6 +274,10 @@ impl Default for HealthCheckConfig {
             timeout_seconds: 5,
             failure_threshold: 3,
             success_threshold: 2,
+            max_backoff_seconds: default_max_backoff_seconds(),
+            jitter_fraction: None,
+            depth: HealthCheckDepth::default(),
+            degraded_response_time_threshold_ms: None,
         }
     }
 }
@@ -152,11 +288,32 @@ impl Default for DiscoveryConfig {
             enabled: true,
             scan_ports: vec![11434, 11435, 11436],
             scan_hosts: vec!["localhost".to_string(), "127.0.0.1".to_string()],
+            probe_timeout_ms: default_probe_timeout_ms(),
+            concurrency: default_probe_concurrency(),
             interval_seconds: 300, // 5 minutes
+            cache_ttl_seconds: default_cache_ttl_seconds(),
+            provider_cache_ttl_seconds: HashMap::new(),
         }
     }
 }
 
+impl DiscoveryConfig {
+    /// Longest a single scan probe may take
+    pub fn probe_timeout(&self) -> Duration {
+        Duration::from_millis(self.probe_timeout_ms)
+    }
+
+    /// Discovery cache TTL for `provider_name`
+    pub fn cache_ttl_for(&self, provider_name: &str) -> Duration {
+        let seconds = self
+            .provider_cache_ttl_seconds
+            .get(provider_name)
+            .copied()
+            .unwrap_or(self.cache_ttl_seconds);
+        Duration::from_secs(seconds)
+    }
+}
+
 impl Default for MonitoringConfig {
     fn default() -> Self {
         Self {
@@ -173,6 +330,11 @@ impl LocalAiConfig {
         Self::default()
     }
 
+    /// Resolver applying the configured model aliases
+    pub fn alias_resolver(&self) -> ModelAliasResolver {
+        ModelAliasResolver::new(self.model_aliases.clone())
+    }
+
     /// Add a provider configuration
     pub fn add_provider(mut self, name: String, config: LocalProviderConfig) -> Self {
         self.providers.insert(name, config);
@@ -194,6 +356,16 @@ impl LocalAiConfig {
         self.providers.iter().filter(|(_, config)| config.enabled)
     }
 
+    /// Name of the enabled local provider whose chat requests go to
+    /// `provider`, if any
+    pub fn provider_name_for(&self, provider: &Provider) -> Option<&str> {
+        self.providers
+            .iter()
+            .filter(|(_, config)| config.enabled)
+            .find(|(_, config)| config.chat_provider().is_ok_and(|chat| chat == *provider))
+            .map(|(name, _)| name.as_str())
+    }
+
     /// Validate the configuration
     pub fn validate(&self) -> anyhow::Result<()> {
         if !self.enabled {
@@ -238,6 +410,9 @@ impl LocalProviderConfig {
 
         // Validate health check configuration
         self.health_check.validate()?;
+        if self.health_check.depth == HealthCheckDepth::Deep && self.preferred_models.is_empty() {
+            anyhow::bail!("Deep health checks need a preferred model to generate with");
+        }
 
         // Validate provider-specific configuration
         match &self.config {
@@ -252,6 +427,14 @@ impl LocalProviderConfig {
                     warn!("Max retries of {} is very high", max_retries);
                 }
             }
+            ProviderSpecificConfig::OpenAiCompatible { base_url, models_path, .. } => {
+                reqwest::Url::parse(base_url)
+                    .with_context(|| format!("Invalid base URL: {base_url}"))?;
+                if models_path.contains("://") || models_path.contains("..") {
+                    anyhow::bail!("Invalid models path: {models_path}");
+                }
+            }
+            ProviderSpecificConfig::Custom { .. } => {}
         }
 
         debug!(
@@ -295,11 +478,68 @@ impl LocalProviderConfig {
                 debug!("Successfully created OllamaConfig");
                 Ok(config)
             }
+            ProviderSpecificConfig::OpenAiCompatible { .. }
+            | ProviderSpecificConfig::Custom { .. } => {
+                anyhow::bail!("Provider {} is not an Ollama provider", self.provider_type)
+            }
+        }
+    }
+
+    /// The provider chat requests to this local server are sent through:
+    /// Ollama's API at `endpoint`, or the OpenAI-compatible API at
+    /// `base_url`
+    pub fn chat_provider(&self) -> anyhow::Result<Provider> {
+        match &self.config {
+            ProviderSpecificConfig::Ollama { .. } => {
+                reqwest::Url::parse(&self.endpoint)
+                    .with_context(|| format!("Invalid endpoint URL: {}", self.endpoint))?;
+                Ok(Provider::ollama(&self.endpoint))
+            }
+            ProviderSpecificConfig::OpenAiCompatible { base_url, api_key, .. } => {
+                Ok(Provider::OpenAI { url: api_root(base_url)?, key: api_key.clone() })
+            }
+            ProviderSpecificConfig::Custom { .. } => {
+                anyhow::bail!("Provider {} cannot serve chat requests", self.provider_type)
+            }
+        }
+    }
+
+    /// Create an OpenAI-compatible provider for this configuration
+    pub(crate) fn create_provider(&self) -> anyhow::Result<ForgeProvider> {
+        match &self.config {
+            ProviderSpecificConfig::OpenAiCompatible { base_url, api_key, .. } => {
+                let client = reqwest::Client::builder()
+                    .connect_timeout(self.health_check.timeout_duration())
+                    .build()?;
+                let provider = Provider::OpenAI { url: api_root(base_url)?, key: api_key.clone() };
+                ForgeProvider::builder()
+                    .client(client)
+                    .provider(provider)
+                    .version(env!("CARGO_PKG_VERSION").to_string())
+                    .build()
+                    .with_context(|| format!("Failed to initialize provider at {base_url}"))
+            }
+            ProviderSpecificConfig::Ollama { .. } => {
+                anyhow::bail!(
+                    "Provider {} is not OpenAI-compatible; use to_ollama_config",
+                    self.provider_type
+                )
+            }
+            ProviderSpecificConfig::Custom { .. } => {
+                anyhow::bail!("Provider {} is not OpenAI-compatible", self.provider_type)
+            }
         }
     }
 
-    /// Create a health checker for this provider
+    /// Create a health checker for this provider through the default
+    /// [`HealthCheckerFactory`]
     pub fn create_health_checker(&self) -> anyhow::Result<Box<dyn ProviderHealthChecker>> {
+        HealthCheckerFactory::default().create(self)
+    }
+
+    /// Create the checker built into this crate for the provider's config
+    /// variant
+    pub(crate) fn builtin_health_checker(&self) -> anyhow::Result<Box<dyn ProviderHealthChecker>> {
         debug!(
             "Creating health checker for provider type: {}",
             self.provider_type
@@ -312,10 +552,64 @@ impl LocalProviderConfig {
                 debug!(
                     "Successfully converted to OllamaConfig, creating OllamaProviderHealthChecker"
                 );
-                Ok(Box::new(OllamaProviderHealthChecker::new(ollama_config)))
+                Ok(Box::new(
+                    OllamaProviderHealthChecker::new(ollama_config)
+                        .with_deep_probe(self.deep_probe_model()),
+                ))
             }
+            ProviderSpecificConfig::OpenAiCompatible { base_url, api_key, models_path } => {
+                debug!("Creating OpenAI-compatible health checker");
+                let models_url = api_root(base_url)?
+                    .join(models_path.trim_start_matches('/'))
+                    .with_context(|| format!("Invalid models path: {models_path}"))?;
+                let checker = OpenAiCompatibleHealthChecker::new(
+                    models_url,
+                    api_key.clone(),
+                    self.health_check.timeout_duration(),
+                );
+                let checker = match self.deep_probe_model() {
+                    Some(model) => {
+                        let chat_url = api_root(base_url)?
+                            .join("chat/completions")
+                            .with_context(|| format!("Invalid base URL: {base_url}"))?;
+                        checker.with_deep_probe(chat_url, model)
+                    }
+                    None => checker,
+                };
+                Ok(Box::new(checker))
+            }
+            ProviderSpecificConfig::Custom { .. } => anyhow::bail!(
+                "No health checker registered for provider type '{}'",
+                self.provider_type
+            ),
+        }
+    }
+
+    /// Model generated with by deep health checks, or `None` for shallow
+    /// checks
+    fn deep_probe_model(&self) -> Option<String> {
+        match self.health_check.depth {
+            HealthCheckDepth::Shallow => None,
+            HealthCheckDepth::Deep => self.preferred_models.first().cloned(),
         }
     }
+
+    /// Whether the provider carries any of the `required` tags. Requiring no
+    /// tags matches every provider.
+    pub fn matches_tags(&self, required: &[String]) -> bool {
+        required.is_empty() || required.iter().any(|tag| self.tags.contains(tag))
+    }
+}
+
+/// Parse `base_url` as an API root, adding the trailing slash relative paths
+/// need to resolve beneath it
+fn api_root(base_url: &str) -> anyhow::Result<reqwest::Url> {
+    let url = if base_url.ends_with('/') {
+        base_url.to_string()
+    } else {
+        format!("{base_url}/")
+    };
+    reqwest::Url::parse(&url).with_context(|| format!("Invalid base URL: {base_url}"))
 }
 
 impl HealthCheckConfig {
@@ -339,6 +633,12 @@ impl HealthCheckConfig {
         if self.success_threshold == 0 {
             anyhow::bail!("Success threshold cannot be zero");
         }
+        if self
+            .jitter_fraction
+            .is_some_and(|jitter| !(0.0..=1.0).contains(&jitter))
+        {
+            anyhow::bail!("Health check jitter fraction must be between 0 and 1");
+        }
         Ok(())
     }
 
@@ -351,6 +651,58 @@ impl HealthCheckConfig {
     pub fn interval_duration(&self) -> Duration {
         Duration::from_secs(self.interval_seconds)
     }
+
+    /// Interval until the next check after `consecutive_failures` failed
+    /// checks: the base interval, doubled for every failure from
+    /// `failure_threshold` onwards up to `max_backoff_seconds`
+    pub fn backoff_interval(&self, consecutive_failures: u32) -> Duration {
+        let base = self.interval_duration();
+        if consecutive_failures < self.failure_threshold {
+            return base;
+        }
+        let ceiling = Duration::from_secs(self.max_backoff_seconds).max(base);
+        let doublings = (consecutive_failures - self.failure_threshold + 1).min(31);
+        base.checked_mul(1 << doublings)
+            .map_or(ceiling, |interval| interval.min(ceiling))
+    }
+
+    /// `interval` moved by up to `jitter_fraction` of itself in either
+    /// direction, using `random` as the source of randomness
+    pub fn jittered_interval(&self, interval: Duration, random: u64) -> Duration {
+        let Some(jitter) = self.jitter_fraction else {
+            return interval;
+        };
+        let spread = interval.mul_f64(jitter.clamp(0.0, 1.0));
+        // The top 53 bits give a uniform fraction in [0, 1)
+        let fraction = (random >> 11) as f64 / (1u64 << 53) as f64;
+        interval - spread + spread.mul_f64(2.0 * fraction)
+    }
+
+    /// Get the degraded response time threshold as Duration
+    pub fn degraded_response_time_threshold(&self) -> Option<Duration> {
+        self.degraded_response_time_threshold_ms
+            .map(Duration::from_millis)
+    }
+
+    /// `status` downgraded to degraded when a healthy provider responded
+    /// slower than the degraded response time threshold
+    pub fn apply_degraded_threshold(&self, status: ProviderHealthStatus) -> ProviderHealthStatus {
+        match (status, self.degraded_response_time_threshold()) {
+            (
+                ProviderHealthStatus::Healthy { response_time, models_available, .. },
+                Some(threshold),
+            ) if response_time > threshold => ProviderHealthStatus::Degraded {
+                reason: format!(
+                    "Response time {}ms exceeds degraded threshold of {}ms",
+                    response_time.as_millis(),
+                    threshold.as_millis()
+                ),
+                response_time,
+                models_available,
+            },
+            (status, _) => status,
+        }
+    }
 }
 
 /// Trait for provider-specific health checking
@@ -359,12 +711,26 @@ pub trait ProviderHealthChecker: Send + Sync {
     /// Check the health of the provider
     async fn check_health(&self) -> anyhow::Result<ProviderHealthStatus>;
 
+    /// Check the health of the provider along with any load metrics the
+    /// server reported. Checkers that cannot read load report `None`.
+    async fn check_health_with_load(
+        &self,
+    ) -> anyhow::Result<(ProviderHealthStatus, Option<ServerLoad>)> {
+        Ok((self.check_health().await?, None))
+    }
+
+    /// Models currently loaded into memory on the provider. Checkers that
+    /// cannot tell report `None`.
+    async fn loaded_models(&self) -> anyhow::Result<Option<Vec<String>>> {
+        Ok(None)
+    }
+
     /// Get the provider type
     fn provider_type(&self) -> &str;
 }
 
 /// Health status of a provider
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum ProviderHealthStatus {
     /// Provider is healthy and responsive
     Healthy {
@@ -403,6 +769,15 @@ impl ProviderHealthStatus {
         }
     }
 
+    /// Short label describing the status
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProviderHealthStatus::Healthy { .. } => "healthy",
+            ProviderHealthStatus::Degraded { .. } => "degraded",
+            ProviderHealthStatus::Unhealthy { .. } => "unhealthy",
+        }
+    }
+
     /// Get number of available models
     pub fn models_available(&self) -> usize {
         match self {
@@ -413,21 +788,94 @@ impl ProviderHealthStatus {
     }
 }
 
+/// Load metrics reported by a provider server in its response headers
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerLoad {
+    /// Requests waiting in the server's queue (`x-queue-depth`)
+    pub queue_depth: Option<usize>,
+    /// Requests the server can process concurrently (`x-max-concurrency`)
+    pub max_concurrency: Option<usize>,
+    /// Requests currently being processed (`x-active-requests`)
+    pub active_requests: Option<usize>,
+}
+
+impl ServerLoad {
+    /// Header carrying the server's queue depth
+    pub const QUEUE_DEPTH_HEADER: &'static str = "x-queue-depth";
+    /// Header carrying the server's concurrency limit
+    pub const MAX_CONCURRENCY_HEADER: &'static str = "x-max-concurrency";
+    /// Header carrying the number of requests in progress
+    pub const ACTIVE_REQUESTS_HEADER: &'static str = "x-active-requests";
+
+    /// Read load metrics from response headers, returning `None` when the
+    /// server reported none
+    pub fn from_headers(headers: &reqwest::header::HeaderMap) -> Option<Self> {
+        let read = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok())
+        };
+        let load = Self {
+            queue_depth: read(Self::QUEUE_DEPTH_HEADER),
+            max_concurrency: read(Self::MAX_CONCURRENCY_HEADER),
+            active_requests: read(Self::ACTIVE_REQUESTS_HEADER),
+        };
+        (load != Self::default()).then_some(load)
+    }
+}
+
+/// Downgrade a healthy `status` to degraded when generating with `model`
+/// failed, since the provider answers but cannot serve requests
+fn apply_generation_probe(
+    status: ProviderHealthStatus,
+    model: &str,
+    probe: anyhow::Result<()>,
+) -> ProviderHealthStatus {
+    match (status, probe) {
+        (ProviderHealthStatus::Healthy { response_time, models_available, .. }, Err(error)) => {
+            warn!(model = %model, error = %error, "Provider lists models but cannot generate");
+            ProviderHealthStatus::Degraded {
+                reason: format!("Models listed but generation with '{model}' failed: {error}"),
+                response_time,
+                models_available,
+            }
+        }
+        (status, _) => status,
+    }
+}
+
 /// Ollama-specific health checker implementation
 pub struct OllamaProviderHealthChecker {
     health_check: OllamaHealthCheck,
+    deep_probe_model: Option<String>,
 }
 
 impl OllamaProviderHealthChecker {
     pub fn new(config: OllamaConfig) -> Self {
-        Self { health_check: OllamaHealthCheck::new(config) }
+        Self {
+            health_check: OllamaHealthCheck::new(config),
+            deep_probe_model: None,
+        }
+    }
+
+    /// Also generate a token with `model` on each check, when set
+    pub fn with_deep_probe(mut self, model: Option<String>) -> Self {
+        self.deep_probe_model = model;
+        self
     }
 }
 
 #[async_trait::async_trait]
 impl ProviderHealthChecker for OllamaProviderHealthChecker {
     async fn check_health(&self) -> anyhow::Result<ProviderHealthStatus> {
-        let status = self.health_check.check_health().await?;
+        Ok(self.check_health_with_load().await?.0)
+    }
+
+    async fn check_health_with_load(
+        &self,
+    ) -> anyhow::Result<(ProviderHealthStatus, Option<ServerLoad>)> {
+        let (status, load) = self.health_check.check_health_with_load().await?;
 
         let provider_status = match status {
             HealthStatus::Healthy { response_time, models_available } => {
@@ -449,7 +897,19 @@ impl ProviderHealthChecker for OllamaProviderHealthChecker {
             }
         };
 
-        Ok(provider_status)
+        let provider_status = match &self.deep_probe_model {
+            Some(model) if provider_status.is_usable() => {
+                let probe = self.health_check.check_generation(model).await;
+                apply_generation_probe(provider_status, model, probe.map_err(Into::into))
+            }
+            _ => provider_status,
+        };
+
+        Ok((provider_status, load))
+    }
+
+    async fn loaded_models(&self) -> anyhow::Result<Option<Vec<String>>> {
+        Ok(Some(self.health_check.loaded_models().await?))
     }
 
     fn provider_type(&self) -> &str {
@@ -457,12 +917,161 @@ impl ProviderHealthChecker for OllamaProviderHealthChecker {
     }
 }
 
+/// Health checker for OpenAI-compatible servers, probing the models endpoint
+pub struct OpenAiCompatibleHealthChecker {
+    client: reqwest::Client,
+    models_url: reqwest::Url,
+    api_key: Option<String>,
+    timeout: Duration,
+    deep_probe: Option<(reqwest::Url, String)>,
+}
+
+impl OpenAiCompatibleHealthChecker {
+    pub fn new(models_url: reqwest::Url, api_key: Option<String>, timeout: Duration) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            models_url,
+            api_key,
+            timeout,
+            deep_probe: None,
+        }
+    }
+
+    /// Also generate a token with `model` through `chat_url` on each check
+    pub fn with_deep_probe(mut self, chat_url: reqwest::Url, model: impl Into<String>) -> Self {
+        self.deep_probe = Some((chat_url, model.into()));
+        self
+    }
+
+    /// Request a single-token chat completion from `model`
+    async fn check_generation(&self, chat_url: &reqwest::Url, model: &str) -> anyhow::Result<()> {
+        let body = serde_json::json!({
+            "model": model,
+            "messages": [{ "role": "user", "content": "ping" }],
+            "max_tokens": 1,
+        });
+        let mut request = self
+            .client
+            .post(chat_url.clone())
+            .timeout(self.timeout)
+            .json(&body);
+        if let Some(ref api_key) = self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("HTTP {status}: {body}");
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ProviderHealthChecker for OpenAiCompatibleHealthChecker {
+    async fn check_health(&self) -> anyhow::Result<ProviderHealthStatus> {
+        Ok(self.check_health_with_load().await?.0)
+    }
+
+    async fn check_health_with_load(
+        &self,
+    ) -> anyhow::Result<(ProviderHealthStatus, Option<ServerLoad>)> {
+        debug!(url = %self.models_url, "Checking OpenAI-compatible provider health");
+
+        let mut request = self
+            .client
+            .get(self.models_url.clone())
+            .timeout(self.timeout);
+        if let Some(ref api_key) = self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let start = std::time::Instant::now();
+        let response = request.send().await?;
+        let response_time = start.elapsed();
+        let load = ServerLoad::from_headers(response.headers());
+
+        let status = response.status();
+        if !status.is_success() {
+            let reason = format!(
+                "HTTP {}: {}",
+                status,
+                status.canonical_reason().unwrap_or("Unknown")
+            );
+            return Ok((
+                ProviderHealthStatus::Unhealthy { reason, response_time },
+                load,
+            ));
+        }
+
+        let models = response
+            .json::<serde_json::Value>()
+            .await
+            .ok()
+            .and_then(|json| json.get("data")?.as_array().map(Vec::len));
+        let provider_status = match models {
+            Some(models_available) => ProviderHealthStatus::Healthy {
+                response_time,
+                models_available,
+                additional_info: None,
+            },
+            None => ProviderHealthStatus::Degraded {
+                reason: "Invalid models response format".to_string(),
+                response_time,
+                models_available: 0,
+            },
+        };
+
+        let provider_status = match &self.deep_probe {
+            Some((chat_url, model)) => {
+                let probe = self.check_generation(chat_url, model).await;
+                apply_generation_probe(provider_status, model, probe)
+            }
+            None => provider_status,
+        };
+
+        Ok((provider_status, load))
+    }
+
+    fn provider_type(&self) -> &str {
+        "openai_compatible"
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
 
+    #[test]
+    fn test_provider_name_for_chat_provider() {
+        let lmstudio = LocalProviderConfig::default()
+            .provider_type("lmstudio")
+            .config(ProviderSpecificConfig::OpenAiCompatible {
+                base_url: "http://localhost:1234/v1".to_string(),
+                api_key: None,
+                models_path: "models".to_string(),
+            });
+        let fixture =
+            LocalAiConfig::with_default_ollama().add_provider("lmstudio".to_string(), lmstudio);
+
+        let actual = [
+            Provider::ollama("http://localhost:11434"),
+            Provider::OpenAI {
+                url: reqwest::Url::parse("http://localhost:1234/v1/").unwrap(),
+                key: None,
+            },
+            Provider::openai("key"),
+        ]
+        .map(|provider| fixture.provider_name_for(&provider));
+
+        let expected = [Some("ollama"), Some("lmstudio"), None];
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_default_local_ai_config() {
         let fixture = LocalAiConfig::default();
@@ -513,6 +1122,21 @@ mod tests {
         assert!(actual.is_err());
     }
 
+    #[test]
+    fn test_health_check_backoff_doubles_up_to_ceiling() {
+        let fixture = HealthCheckConfig::default()
+            .interval_seconds(10u64)
+            .failure_threshold(2u32)
+            .max_backoff_seconds(60u64);
+
+        let actual: Vec<_> = (0..7)
+            .map(|failures| fixture.backoff_interval(failures).as_secs())
+            .collect();
+
+        let expected = vec![10, 10, 20, 40, 60, 60, 60];
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_provider_health_status_usability() {
         let healthy = ProviderHealthStatus::Healthy {
@@ -563,4 +1187,165 @@ mod tests {
         assert_eq!(enabled.len(), 1);
         assert_eq!(enabled[0].0, "enabled");
     }
+
+    fn openai_compatible_fixture(base_url: String) -> LocalProviderConfig {
+        LocalProviderConfig::default()
+            .provider_type("openai_compatible")
+            .endpoint(base_url.clone())
+            .config(ProviderSpecificConfig::OpenAiCompatible {
+                base_url,
+                api_key: Some("secret".to_string()),
+                models_path: "models".to_string(),
+            })
+    }
+
+    #[test]
+    fn test_openai_compatible_models_path_defaults() {
+        let fixture = r#"{"type": "openai_compatible", "base_url": "http://localhost:1234/v1"}"#;
+
+        let actual: ProviderSpecificConfig = serde_json::from_str(fixture).unwrap();
+
+        let ProviderSpecificConfig::OpenAiCompatible { models_path, api_key, .. } = actual else {
+            panic!("expected an OpenAI-compatible config");
+        };
+        assert_eq!((models_path.as_str(), api_key), ("models", None));
+    }
+
+    #[test]
+    fn test_openai_compatible_is_not_an_ollama_provider() {
+        let fixture = openai_compatible_fixture("http://localhost:8000/v1".to_string());
+
+        assert!(fixture.validate().is_ok());
+        assert!(fixture.to_ollama_config().is_err());
+        assert!(fixture.create_provider().is_ok());
+        assert!(LocalProviderConfig::default().create_provider().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_openai_compatible_health_checker_counts_models() {
+        let server = crate::mock_server::MockOllamaServer::builder()
+            .on(
+                "GET",
+                "/v1/models",
+                crate::mock_server::ScriptedResponse::json(
+                    200,
+                    serde_json::json!({ "data": [{ "id": "a" }, { "id": "b" }] }),
+                ),
+            )
+            .on(
+                "GET",
+                "/v1/models",
+                crate::mock_server::ScriptedResponse::json(503, serde_json::json!({})),
+            )
+            .start()
+            .await;
+        let fixture = openai_compatible_fixture(format!("{}/v1", server.url()))
+            .create_health_checker()
+            .unwrap();
+
+        let healthy = fixture.check_health().await.unwrap();
+        let unavailable = fixture.check_health().await.unwrap();
+
+        let actual = (
+            healthy.label(),
+            healthy.models_available(),
+            unavailable.label(),
+            fixture.provider_type(),
+        );
+        let expected = ("healthy", 2, "unhealthy", "openai_compatible");
+        assert_eq!(actual, expected);
+    }
+
+    /// Ollama server that lists models but fails every generation
+    async fn listing_only_ollama() -> crate::mock_server::MockOllamaServer {
+        crate::mock_server::MockOllamaServer::builder()
+            .tags(&["llama3.2:latest"])
+            .on(
+                "POST",
+                "/api/generate",
+                crate::mock_server::ScriptedResponse::json(
+                    500,
+                    serde_json::json!({ "error": "model failed to load" }),
+                ),
+            )
+            .start()
+            .await
+    }
+
+    async fn check_listing_only_ollama(depth: HealthCheckDepth) -> ProviderHealthStatus {
+        let server = listing_only_ollama().await;
+        LocalProviderConfig::default()
+            .endpoint(server.url())
+            .health_check(HealthCheckConfig::default().depth(depth))
+            .create_health_checker()
+            .unwrap()
+            .check_health()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_shallow_health_check_ignores_generation() {
+        let actual = check_listing_only_ollama(HealthCheckDepth::Shallow).await;
+
+        assert_eq!((actual.label(), actual.models_available()), ("healthy", 1));
+    }
+
+    #[tokio::test]
+    async fn test_deep_health_check_degrades_when_generation_fails() {
+        let actual = check_listing_only_ollama(HealthCheckDepth::Deep).await;
+
+        assert_eq!((actual.label(), actual.models_available()), ("degraded", 1));
+        let ProviderHealthStatus::Degraded { reason, .. } = actual else {
+            panic!("expected a degraded status");
+        };
+        assert!(reason.contains("generation with 'llama3.2:latest' failed"));
+    }
+
+    #[tokio::test]
+    async fn test_deep_health_check_generates_with_openai_compatible_server() {
+        let server = crate::mock_server::MockOllamaServer::builder()
+            .on(
+                "GET",
+                "/v1/models",
+                crate::mock_server::ScriptedResponse::json(
+                    200,
+                    serde_json::json!({ "data": [{ "id": "llama3.2:latest" }] }),
+                ),
+            )
+            .on(
+                "POST",
+                "/v1/chat/completions",
+                crate::mock_server::ScriptedResponse::json(200, serde_json::json!({})),
+            )
+            .on(
+                "POST",
+                "/v1/chat/completions",
+                crate::mock_server::ScriptedResponse::json(503, serde_json::json!({})),
+            )
+            .start()
+            .await;
+        let fixture = openai_compatible_fixture(format!("{}/v1", server.url()))
+            .health_check(HealthCheckConfig::default().depth(HealthCheckDepth::Deep))
+            .create_health_checker()
+            .unwrap();
+
+        let generating = fixture.check_health().await.unwrap();
+        let failing = fixture.check_health().await.unwrap();
+
+        let actual = (generating.label(), failing.label());
+        let expected = ("healthy", "degraded");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_deep_health_check_requires_preferred_model() {
+        let fixture = LocalProviderConfig::default()
+            .preferred_models(Vec::<String>::new())
+            .health_check(HealthCheckConfig::default().depth(HealthCheckDepth::Deep));
+
+        let actual = fixture.validate();
+
+        assert!(actual.is_err());
+    }
 }
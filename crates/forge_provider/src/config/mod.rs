@@ -1,9 +1,15 @@
 //! Configuration system for local AI providers and fallback logic
 
+pub mod aliases;
 pub mod enhanced;
+pub mod env;
 pub mod fallback;
 pub mod local_ai;
+pub mod routing;
 
+pub use aliases::{AliasError, ModelAliasResolver};
 pub use enhanced::{EnhancedFallbackConfig, EnhancedFallbackEngine};
-pub use fallback::{FallbackConfig, FallbackStrategy};
+pub use env::EnvConfigLoader;
+pub use fallback::{FallbackConfig, FallbackStrategy, TinyModelFallback};
 pub use local_ai::{LocalAiConfig, LocalProviderConfig};
+pub use routing::{ModelPattern, RoutingRule, RoutingTable};
@@ -0,0 +1,155 @@
+//! Per-model routing rules
+//!
+//! Rules map model ids to a target provider, e.g. sending every `*-70b` model
+//! to the GPU host and every `*-embed` model to the embeddings provider. Rules
+//! are evaluated in order before the default selection logic, and the first
+//! matching rule wins.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Pattern matched against the full model id
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelPattern {
+    /// Shell-style glob where `*` matches any run of characters and `?` a
+    /// single character
+    Glob(String),
+    /// Regular expression, anchored to the whole model id
+    Regex(String),
+}
+
+impl ModelPattern {
+    /// Compile the pattern into an anchored regular expression
+    pub fn compile(&self) -> anyhow::Result<Regex> {
+        let source = match self {
+            ModelPattern::Glob(glob) => glob_to_regex(glob),
+            ModelPattern::Regex(regex) => format!("^(?:{regex})$"),
+        };
+        Regex::new(&source).map_err(|e| anyhow::anyhow!("Invalid model pattern {self}: {e}"))
+    }
+}
+
+impl std::fmt::Display for ModelPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModelPattern::Glob(glob) => write!(f, "glob '{glob}'"),
+            ModelPattern::Regex(regex) => write!(f, "regex '{regex}'"),
+        }
+    }
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut source = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => source.push_str(".*"),
+            '?' => source.push('.'),
+            c => source.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    source.push('$');
+    source
+}
+
+/// Route models matching `pattern` to `target`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoutingRule {
+    /// Pattern matched against the requested model id
+    pub pattern: ModelPattern,
+    /// Local provider name, or `cloud:<name>` for a cloud provider
+    pub target: String,
+}
+
+impl RoutingRule {
+    /// Rule matching model ids against a glob
+    pub fn glob(pattern: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            pattern: ModelPattern::Glob(pattern.into()),
+            target: target.into(),
+        }
+    }
+
+    /// Rule matching model ids against a regular expression
+    pub fn regex(pattern: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            pattern: ModelPattern::Regex(pattern.into()),
+            target: target.into(),
+        }
+    }
+}
+
+/// Routing rules with their patterns compiled, ready for matching
+#[derive(Debug, Clone, Default)]
+pub struct RoutingTable {
+    rules: Vec<(Regex, RoutingRule)>,
+}
+
+impl RoutingTable {
+    /// Compile `rules`, failing on the first invalid pattern
+    pub fn new(rules: &[RoutingRule]) -> anyhow::Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| Ok((rule.pattern.compile()?, rule.clone())))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// First rule matching `model_id`
+    pub fn route(&self, model_id: &str) -> Option<&RoutingRule> {
+        self.rules
+            .iter()
+            .find(|(regex, _)| regex.is_match(model_id))
+            .map(|(_, rule)| rule)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let fixture = RoutingTable::new(&[
+            RoutingRule::glob("*-70b", "gpu-pool"),
+            RoutingRule::regex(r".*-embed(:.*)?", "embeddings"),
+            RoutingRule::glob("*", "cloud:openai"),
+        ])
+        .unwrap();
+
+        let actual = vec![
+            fixture
+                .route("llama3.1-70b")
+                .map(|rule| rule.target.as_str()),
+            fixture
+                .route("nomic-embed:latest")
+                .map(|rule| rule.target.as_str()),
+            fixture.route("qwen2.5").map(|rule| rule.target.as_str()),
+        ];
+
+        let expected = vec![Some("gpu-pool"), Some("embeddings"), Some("cloud:openai")];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_glob_is_anchored_and_escaped() {
+        let fixture = RoutingTable::new(&[RoutingRule::glob("llama3.?-70b", "gpu-pool")]).unwrap();
+
+        let actual = vec![
+            fixture.route("llama3.1-70b").is_some(),
+            fixture.route("llama3x1-70b-instruct").is_some(),
+        ];
+
+        let expected = vec![true, false];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_invalid_regex_is_rejected() {
+        let actual = RoutingTable::new(&[RoutingRule::regex("llama(", "gpu-pool")]);
+
+        assert!(actual.is_err());
+    }
+}
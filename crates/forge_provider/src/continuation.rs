@@ -0,0 +1,211 @@
+use std::future::Future;
+
+use derive_setters::Setters;
+use forge_app::domain::{BoxStream, ChatCompletionMessage, Context, ContextMessage, ResultStream};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tracing::{info, warn};
+
+/// Continues a streaming response on another provider when the original
+/// stream fails part-way through.
+///
+/// The fallback provider is prompted with the original conversation, the
+/// partial answer as an assistant turn and an instruction to carry on, so the
+/// combined output reads as a single response.
+#[derive(Debug, Clone, Serialize, Deserialize, Setters)]
+#[setters(strip_option, into)]
+pub struct StreamContinuation {
+    /// Continue failed streams instead of surfacing the error
+    pub enabled: bool,
+    /// Instruction sent after the partial answer
+    pub instruction: String,
+}
+
+impl Default for StreamContinuation {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            instruction: "Your previous response was interrupted. Continue it exactly where it \
+                          stopped, without repeating anything already written."
+                .to_string(),
+        }
+    }
+}
+
+impl StreamContinuation {
+    /// Build the context used to ask the fallback provider to continue
+    /// `partial`. With no partial output the original context is replayed.
+    pub fn continuation_context(&self, context: &Context, partial: &str) -> Context {
+        if partial.is_empty() {
+            return context.clone();
+        }
+        context
+            .clone()
+            .add_message(ContextMessage::assistant(partial, None, None))
+            .add_message(ContextMessage::user(&self.instruction, None))
+    }
+
+    /// Forward `primary`, and if it fails, stitch on the stream returned by
+    /// `continue_with` for the continuation context. Failures after tool
+    /// calls were emitted are surfaced unchanged, since a half-finished tool
+    /// call cannot be resumed.
+    pub fn stitch<F, Fut>(
+        &self,
+        mut primary: BoxStream<ChatCompletionMessage, anyhow::Error>,
+        context: Context,
+        continue_with: F,
+    ) -> BoxStream<ChatCompletionMessage, anyhow::Error>
+    where
+        F: FnOnce(Context) -> Fut + Send + 'static,
+        Fut: Future<Output = ResultStream<ChatCompletionMessage, anyhow::Error>> + Send + 'static,
+    {
+        if !self.enabled {
+            return primary;
+        }
+
+        let this = self.clone();
+        let (tx, rx) = mpsc::channel(32);
+
+        tokio::spawn(async move {
+            let mut partial = String::new();
+            let mut emitted_tool_calls = false;
+
+            let error = loop {
+                match primary.next().await {
+                    Some(Ok(message)) => {
+                        if let Some(content) = &message.content {
+                            partial.push_str(content.as_str());
+                        }
+                        emitted_tool_calls |= !message.tool_calls.is_empty();
+                        if tx.send(Ok(message)).await.is_err() {
+                            return;
+                        }
+                    }
+                    Some(Err(error)) => break error,
+                    None => return,
+                }
+            };
+
+            if emitted_tool_calls {
+                let _ = tx.send(Err(error)).await;
+                return;
+            }
+            // Release whatever the failed request holds, such as a local
+            // generation slot, before the continuation runs
+            drop(primary);
+
+            warn!(
+                error = %error,
+                partial_chars = partial.chars().count(),
+                "Stream failed, continuing on fallback provider"
+            );
+            let mut continuation =
+                match continue_with(this.continuation_context(&context, &partial)).await {
+                    Ok(stream) => stream,
+                    Err(continuation_error) => {
+                        let _ = tx
+                            .send(Err(continuation_error
+                                .context(format!("Continuation failed after: {error}"))))
+                            .await;
+                        return;
+                    }
+                };
+
+            info!(
+                partial_chars = partial.chars().count(),
+                "Stitching continuation stream"
+            );
+            while let Some(item) = continuation.next().await {
+                if tx.send(item).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        Box::pin(ReceiverStream::new(rx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use forge_app::domain::FinishReason;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn stream_of(
+        items: Vec<anyhow::Result<ChatCompletionMessage>>,
+    ) -> BoxStream<ChatCompletionMessage, anyhow::Error> {
+        Box::pin(tokio_stream::iter(items))
+    }
+
+    fn fixture() -> Context {
+        Context::default().add_message(ContextMessage::user("Count to five", None))
+    }
+
+    async fn collect_text(
+        mut stream: BoxStream<ChatCompletionMessage, anyhow::Error>,
+    ) -> anyhow::Result<String> {
+        let mut text = String::new();
+        while let Some(message) = stream.next().await {
+            if let Some(content) = message?.content {
+                text.push_str(content.as_str());
+            }
+        }
+        Ok(text)
+    }
+
+    #[tokio::test]
+    async fn test_failed_stream_continues_with_emitted_tokens() {
+        let local = stream_of(vec![
+            Ok(ChatCompletionMessage::default().content_part("one, ")),
+            Ok(ChatCompletionMessage::default().content_part("two, ")),
+            Err(anyhow::anyhow!("connection reset")),
+        ]);
+        let prompted = Arc::new(Mutex::new(None));
+        let captured = prompted.clone();
+
+        let stitched = StreamContinuation::default().enabled(true).stitch(
+            local,
+            fixture(),
+            move |context| async move {
+                *captured.lock().unwrap() = Some(context);
+                Ok(stream_of(vec![
+                    Ok(ChatCompletionMessage::default().content_part("three, four, five")),
+                    Ok(ChatCompletionMessage::default()
+                        .finish_reason_opt(Some(FinishReason::Stop))),
+                ]))
+            },
+        );
+
+        let actual = collect_text(stitched).await.unwrap();
+
+        assert_eq!(actual, "one, two, three, four, five");
+        let context = prompted.lock().unwrap().clone().unwrap();
+        let expected = vec![
+            ContextMessage::user("Count to five", None),
+            ContextMessage::assistant("one, two, ", None, None),
+            ContextMessage::user(StreamContinuation::default().instruction, None),
+        ];
+        assert_eq!(context.messages, expected);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_continuation_surfaces_error() {
+        let local = stream_of(vec![
+            Ok(ChatCompletionMessage::default().content_part("one, ")),
+            Err(anyhow::anyhow!("connection reset")),
+        ]);
+
+        let stitched = StreamContinuation::default().stitch(local, fixture(), |_| async move {
+            panic!("continuation should not run")
+        });
+
+        let actual = collect_text(stitched).await.unwrap_err();
+        assert_eq!(actual.to_string(), "connection reset");
+    }
+}
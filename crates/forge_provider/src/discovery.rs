@@ -4,18 +4,35 @@
 //! simple model listing to include automatic detection, health monitoring,
 //! and availability reporting for local AI services.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{Context, Result};
+use derive_setters::Setters;
 use forge_app::domain::{Model, ModelId};
+use futures::stream::{self, StreamExt};
 use tracing::{debug, error, info, warn};
 
+use crate::config::aliases::{AliasError, ModelAliasResolver};
 use crate::config::local_ai::{
-    LocalAiConfig, LocalProviderConfig, ProviderHealthStatus, ProviderSpecificConfig,
+    HealthCheckConfig, LocalAiConfig, LocalProviderConfig, ProviderHealthStatus,
+    ProviderSpecificConfig,
 };
-use crate::health::HealthMonitor;
-use crate::ollama::{OllamaConfig, OllamaHealthCheck};
+use crate::health::{HealthCheckerFactory, HealthMonitor, HealthSnapshot};
+use crate::ollama::{Ollama, OllamaConfig, OllamaHealthCheck};
+use crate::performance::{
+    AdmissionController, IdleEvictionPolicy, ModelLoadingOptimizer, ModelUnloaders,
+};
+use crate::readiness::ReadinessGate;
+use crate::selection::ContextLengths;
+
+/// Where LM Studio serves its OpenAI-compatible API by default
+const LMSTUDIO_DEFAULT_URL: &str = "http://localhost:1234/v1";
+
+/// Number of Ollama `/api/show` requests in flight at once during discovery
+const OLLAMA_SHOW_CONCURRENCY: usize = 4;
 
 /// Enhanced model discovery service with automatic detection and health
 /// monitoring
@@ -24,8 +41,26 @@ pub struct ModelDiscoveryService {
     health_monitor: HealthMonitor,
     /// Local AI configuration
     local_config: LocalAiConfig,
-    /// Cached discovered models with their health status
-    discovered_models: HashMap<String, DiscoveredModel>,
+    /// Cached discovered models with their health status, one entry per
+    /// model and provider serving it, keyed and ordered by model id and then
+    /// provider
+    discovered_models: BTreeMap<(String, String), DiscoveredModel>,
+    /// Opened once initial health checks and discovery complete
+    readiness: ReadinessGate,
+    /// Longest [`ModelDiscoveryService::ready`] waits for startup
+    ready_timeout: Duration,
+    /// API root probed for an LM Studio server that is not configured
+    lmstudio_url: String,
+    /// Optimizer whose popular models are preloaded on start
+    optimizer: Option<Arc<ModelLoadingOptimizer>>,
+    /// Resolves requested model names onto discovered model ids
+    aliases: ModelAliasResolver,
+    /// Context windows of discovered models, shared with provider selection
+    context_lengths: Option<ContextLengths>,
+    /// Policy unloading models left idle on the configured Ollama providers
+    idle_eviction: Arc<IdleEvictionPolicy>,
+    /// Task evicting idle models, once started
+    idle_eviction_task: Option<tokio::task::JoinHandle<()>>,
 }
 
 /// Information about a discovered model including its health and availability
@@ -45,6 +80,99 @@ pub struct DiscoveredModel {
     pub response_time: Option<Duration>,
 }
 
+/// What a caller wants from a model when it does not name one exactly
+#[derive(Debug, Clone, Default, Setters)]
+#[setters(strip_option, into)]
+pub struct ModelHint {
+    /// The model must support tool calls
+    pub requires_tools: bool,
+    /// The model must support reasoning
+    pub requires_reasoning: bool,
+    /// The model's context window must hold at least this many tokens
+    pub min_context: Option<u32>,
+    /// Case-insensitive substring the model id or name must contain
+    pub name_contains: Option<String>,
+}
+
+impl ModelHint {
+    fn matches_name(&self, model: &Model) -> bool {
+        let Some(needle) = &self.name_contains else {
+            return true;
+        };
+        let needle = needle.to_lowercase();
+        model.id.as_str().to_lowercase().contains(&needle)
+            || model
+                .name
+                .as_ref()
+                .is_some_and(|name| name.to_lowercase().contains(&needle))
+    }
+}
+
+/// One row of the combined model listing: a discovered model alongside its
+/// provider's health
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelSummaryRow {
+    /// The model's id
+    pub model_id: ModelId,
+    /// Provider serving the model
+    pub provider: String,
+    /// Whether the model is currently available for use
+    pub available: bool,
+    /// Short label for the provider's health
+    pub health: &'static str,
+    /// Response time of the provider's last health check
+    pub response_time: Option<Duration>,
+}
+
+/// Render `rows` as a compact, column-aligned table with a header line
+pub fn render_model_summary(rows: &[ModelSummaryRow]) -> String {
+    let header = ["MODEL", "PROVIDER", "STATUS", "HEALTH", "LATENCY"];
+    let cells: Vec<[String; 5]> = rows
+        .iter()
+        .map(|row| {
+            let status = if row.available {
+                "available"
+            } else {
+                "unavailable"
+            };
+            let latency = row
+                .response_time
+                .map_or("-".to_string(), |time| format!("{}ms", time.as_millis()));
+            [
+                row.model_id.to_string(),
+                row.provider.clone(),
+                status.to_string(),
+                row.health.to_string(),
+                latency,
+            ]
+        })
+        .collect();
+
+    let mut widths = header.map(str::len);
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let format_line = |line: [&str; 5]| {
+        let padded: Vec<_> = line
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        padded.join("  ").trim_end().to_string()
+    };
+    std::iter::once(format_line(header))
+        .chain(
+            cells
+                .iter()
+                .map(|row| format_line(row.each_ref().map(String::as_str))),
+        )
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Result of model discovery operation
 #[derive(Debug)]
 pub struct ModelDiscoveryResult {
@@ -63,47 +191,199 @@ pub struct ModelDiscoveryResult {
 impl ModelDiscoveryService {
     /// Create a new model discovery service
     pub async fn new(local_config: LocalAiConfig) -> Result<Self> {
+        Self::new_with_factory(local_config, &HealthCheckerFactory::default()).await
+    }
+
+    /// Create a new model discovery service whose health checkers are built
+    /// by `factory`
+    pub async fn new_with_factory(
+        local_config: LocalAiConfig,
+        factory: &HealthCheckerFactory,
+    ) -> Result<Self> {
         debug!(
             "Creating ModelDiscoveryService with config: {:?}",
             local_config
         );
 
-        let health_monitor = match HealthMonitor::new(local_config.clone()).await {
-            Ok(monitor) => {
-                debug!("HealthMonitor created successfully");
-                monitor
-            }
-            Err(e) => {
-                error!("Failed to create HealthMonitor: {}", e);
-                error!("This might be due to provider configuration issues");
+        let health_monitor =
+            match HealthMonitor::new_with_factory(local_config.clone(), factory).await {
+                Ok(monitor) => {
+                    debug!("HealthMonitor created successfully");
+                    monitor
+                }
+                Err(e) => {
+                    error!("Failed to create HealthMonitor: {}", e);
+                    error!("This might be due to provider configuration issues");
 
-                // Create a minimal health monitor with no checkers as fallback
-                HealthMonitor::new_fallback(local_config.clone())
-            }
-        };
+                    // Create a minimal health monitor with no checkers as fallback
+                    HealthMonitor::new_fallback(local_config.clone())
+                }
+            };
 
         debug!("ModelDiscoveryService created successfully");
         Ok(Self {
             health_monitor,
+            idle_eviction: Arc::new(IdleEvictionPolicy::new(
+                local_config.settings.idle_eviction.clone(),
+            )),
+            idle_eviction_task: None,
+            aliases: local_config.alias_resolver(),
             local_config,
-            discovered_models: HashMap::new(),
+            discovered_models: BTreeMap::new(),
+            readiness: ReadinessGate::new(),
+            ready_timeout: Duration::from_secs(30),
+            lmstudio_url: LMSTUDIO_DEFAULT_URL.to_string(),
+            optimizer: None,
+            context_lengths: None,
         })
     }
 
+    /// Set how long [`ModelDiscoveryService::ready`] waits for startup
+    pub fn with_ready_timeout(mut self, timeout: Duration) -> Self {
+        self.ready_timeout = timeout;
+        self
+    }
+
+    /// Set the API root probed by automatic LM Studio discovery
+    pub fn with_lmstudio_url(mut self, url: impl Into<String>) -> Self {
+        self.lmstudio_url = url.into();
+        self
+    }
+
+    /// Preload `optimizer`'s most used models on start
+    pub fn with_model_optimizer(mut self, optimizer: Arc<ModelLoadingOptimizer>) -> Self {
+        self.optimizer = Some(optimizer);
+        self
+    }
+
+    /// Report the context window of every discovered model to
+    /// `context_lengths`, such as those of a
+    /// [`ProviderSelector`](crate::selection::ProviderSelector)
+    pub fn with_context_lengths(mut self, context_lengths: ContextLengths) -> Self {
+        self.context_lengths = Some(context_lengths);
+        self
+    }
+
+    /// Evict idle models according to `policy` rather than a policy of its
+    /// own, so clients serving requests can report activity to it
+    pub fn with_idle_eviction(mut self, policy: Arc<IdleEvictionPolicy>) -> Self {
+        self.idle_eviction = policy;
+        self
+    }
+
+    /// Adapt provider capacities in `controller` to the load servers report
+    /// on each health check
+    pub fn with_admission(mut self, controller: Arc<AdmissionController>) -> Self {
+        self.health_monitor.set_admission(controller);
+        self
+    }
+
+    /// Policy that unloads idle models once [`Self::start_idle_eviction`]
+    /// has run
+    pub fn idle_eviction(&self) -> Arc<IdleEvictionPolicy> {
+        self.idle_eviction.clone()
+    }
+
+    /// Future that resolves once initial health checks and model discovery
+    /// have completed, or after the ready timeout. The future does not borrow
+    /// the service, so callers can await it while [`Self::start`] runs.
+    pub fn ready(&self) -> impl Future<Output = ()> + Send + 'static {
+        self.readiness.wait(self.ready_timeout)
+    }
+
+    /// Readiness gate opened when startup completes
+    pub fn readiness(&self) -> &ReadinessGate {
+        &self.readiness
+    }
+
     /// Start the discovery service with automatic monitoring
     pub async fn start(&mut self) -> Result<()> {
         info!("Starting model discovery service");
+        // Fails the readiness gate on every path that does not mark it ready
+        let _readiness = self.readiness.guard();
 
         // Start health monitoring
         self.health_monitor.start().await?;
 
         // Perform initial discovery
         self.discover_all_models().await?;
+        self.readiness.mark_ready();
+        self.preload_popular_models().await;
+        self.start_idle_eviction();
 
         info!("Model discovery service started successfully");
         Ok(())
     }
 
+    /// Warm the most used models of every usable Ollama provider in the
+    /// background, so loading them does not hold up startup
+    async fn preload_popular_models(&self) {
+        let Some(optimizer) = self.optimizer.clone() else {
+            return;
+        };
+        let health_status = self.health_monitor.get_health_status().await;
+
+        let mut providers = Vec::new();
+        for (provider_name, provider_config) in self.local_config.enabled_providers() {
+            if !matches!(
+                provider_config.config,
+                ProviderSpecificConfig::Ollama { .. }
+            ) || !health_status
+                .get(provider_name)
+                .is_some_and(ProviderHealthStatus::is_usable)
+            {
+                continue;
+            }
+            let ollama = provider_config
+                .to_ollama_config()
+                .and_then(|config| Ok(config.create_provider()?));
+            match ollama {
+                Ok(ollama) => providers.push((provider_name.clone(), ollama)),
+                Err(e) => {
+                    warn!(provider = %provider_name, error = %e, "Skipping model preload")
+                }
+            }
+        }
+
+        tokio::spawn(async move {
+            for (provider_name, ollama) in providers {
+                optimizer.preload(&provider_name, &ollama).await;
+            }
+        });
+    }
+
+    /// Periodically unload models left idle on the enabled Ollama providers,
+    /// when idle eviction is enabled. Does nothing if the task is running.
+    pub fn start_idle_eviction(&mut self) {
+        if self.idle_eviction_task.is_some() || !self.idle_eviction.is_enabled() {
+            return;
+        }
+
+        let mut unloaders = ModelUnloaders::new();
+        for (provider_name, provider_config) in self.local_config.enabled_providers() {
+            if !matches!(
+                provider_config.config,
+                ProviderSpecificConfig::Ollama { .. }
+            ) {
+                continue;
+            }
+            let ollama = provider_config
+                .to_ollama_config()
+                .and_then(|config| Ok(config.create_provider()?));
+            match ollama {
+                Ok(ollama) => {
+                    unloaders.insert(provider_name.clone(), Arc::new(ollama));
+                }
+                Err(e) => {
+                    warn!(provider = %provider_name, error = %e, "Skipping idle model eviction")
+                }
+            }
+        }
+
+        info!(providers = unloaders.len(), "Started idle model eviction");
+        self.idle_eviction_task = self.idle_eviction.spawn(unloaders);
+    }
+
     /// Discover all available models from all configured providers
     pub async fn discover_all_models(&mut self) -> Result<ModelDiscoveryResult> {
         let start_time = std::time::Instant::now();
@@ -111,12 +391,25 @@ impl ModelDiscoveryService {
 
         info!("Starting comprehensive model discovery");
 
-        // Clear previous discoveries
-        self.discovered_models.clear();
-
-        // Discover from each provider
+        // Drop models from providers that are no longer configured, including
+        // previous automatic discoveries
         let providers = self.local_config.providers.clone();
+        self.discovered_models
+            .retain(|_, model| providers.contains_key(&model.provider));
+
+        // Discover from each provider, serving fresh cached models where possible
         for (provider_name, provider_config) in providers {
+            if let Some(count) = self.cached_model_count(&provider_name).await {
+                debug!(
+                    provider = %provider_name,
+                    models = count,
+                    "Serving discovered models from cache"
+                );
+                continue;
+            }
+            self.discovered_models
+                .retain(|_, model| model.provider != provider_name);
+
             match self
                 .discover_provider_models(&provider_name, &provider_config)
                 .await
@@ -151,6 +444,22 @@ impl ModelDiscoveryService {
             }
         }
 
+        // Automatic LM Studio discovery if not explicitly configured
+        if !self.local_config.providers.contains_key("lmstudio") {
+            match self.discover_lmstudio_automatically().await {
+                Ok(count) => {
+                    if count > 0 {
+                        info!("Automatically discovered {} LM Studio models", count);
+                    }
+                }
+                Err(e) => {
+                    let warning = format!("Automatic LM Studio discovery failed: {e}");
+                    debug!("{}", warning);
+                    warnings.push(warning);
+                }
+            }
+        }
+
         let discovery_duration = start_time.elapsed();
 
         // Get health status
@@ -160,14 +469,10 @@ impl ModelDiscoveryService {
             .filter(|status| matches!(status, ProviderHealthStatus::Healthy { .. }))
             .count();
 
-        let available_models = self
-            .discovered_models
-            .values()
-            .filter(|model| model.available)
-            .count();
+        let available_models = self.get_available_models().len();
 
         let result = ModelDiscoveryResult {
-            total_models: self.discovered_models.len(),
+            total_models: self.distinct_model_count(),
             healthy_providers,
             available_models,
             discovery_duration,
@@ -185,6 +490,38 @@ impl ModelDiscoveryService {
         Ok(result)
     }
 
+    /// Number of cached models for `provider_name` if they can be served
+    /// without probing: the provider was healthy when checked, is still
+    /// healthy, and was checked within its cache TTL
+    async fn cached_model_count(&self, provider_name: &str) -> Option<usize> {
+        let cached: Vec<_> = self
+            .discovered_models
+            .values()
+            .filter(|model| model.provider == provider_name)
+            .collect();
+        if cached.is_empty() {
+            return None;
+        }
+
+        let current_health = self
+            .health_monitor
+            .get_provider_health(provider_name)
+            .await?;
+        let ttl = self
+            .local_config
+            .settings
+            .discovery
+            .cache_ttl_for(provider_name);
+
+        let fresh = cached.iter().all(|model| {
+            matches!(model.provider_health, ProviderHealthStatus::Healthy { .. })
+                && model.provider_health.label() == current_health.label()
+                && model.last_checked.elapsed() < ttl
+        });
+
+        fresh.then_some(cached.len())
+    }
+
     /// Discover models from a specific provider
     async fn discover_provider_models(
         &mut self,
@@ -217,6 +554,25 @@ impl ModelDiscoveryService {
                 self.discover_ollama_models(provider_name, &ollama_config, provider_health)
                     .await
             }
+            ProviderSpecificConfig::OpenAiCompatible { models_path, .. } => {
+                let provider = provider_config.create_provider()?;
+                let models = provider.models_at(models_path).await.with_context(|| {
+                    format!(
+                        "Failed to fetch models from OpenAI-compatible provider '{provider_name}'"
+                    )
+                })?;
+                Ok(self
+                    .record_models(provider_name, models, provider_health)
+                    .await)
+            }
+            ProviderSpecificConfig::Custom { .. } => {
+                debug!(
+                    provider = provider_name,
+                    provider_type = %provider_config.provider_type,
+                    "No model discovery for custom provider type"
+                );
+                Ok(0)
+            }
         }
     }
 
@@ -234,7 +590,25 @@ impl ModelDiscoveryService {
         let models = ollama.models().await.with_context(|| {
             format!("Failed to fetch models from Ollama provider '{provider_name}'")
         })?;
+        let models = stream::iter(models)
+            .map(|model| with_ollama_details(&ollama, model))
+            .buffered(OLLAMA_SHOW_CONCURRENCY)
+            .collect()
+            .await;
+
+        Ok(self
+            .record_models(provider_name, models, provider_health)
+            .await)
+    }
 
+    /// Cache `models` as served by `provider_name` and report their context
+    /// windows, returning how many were recorded
+    async fn record_models(
+        &mut self,
+        provider_name: &str,
+        models: Vec<Model>,
+        provider_health: ProviderHealthStatus,
+    ) -> usize {
         let now = std::time::Instant::now();
         let response_time = Some(provider_health.response_time());
 
@@ -250,12 +624,17 @@ impl ModelDiscoveryService {
                 response_time,
             };
 
-            // Use model ID as key to avoid duplicates
-            self.discovered_models
-                .insert(model.id.as_str().to_string(), discovered_model);
+            // Keep every provider's offering of a model that several serve
+            self.discovered_models.insert(
+                (model.id.as_str().to_string(), provider_name.to_string()),
+                discovered_model,
+            );
+        }
+        if let Some(context_lengths) = &self.context_lengths {
+            context_lengths.set_models(provider_name, &models).await;
         }
 
-        Ok(models.len())
+        models.len()
     }
 
     /// Automatically discover Ollama installations on common ports
@@ -310,61 +689,194 @@ impl ModelDiscoveryService {
             }
         }
 
-        // If default doesn't work, try discovery
-        let discovered_services = health_check.discover_services().await;
-
-        for service_url in discovered_services {
-            let config = OllamaConfig::new().with_base_url(service_url.clone());
-            let health_check = OllamaHealthCheck::new(config.clone());
+        // If default doesn't work, scan the configured hosts and ports
+        let discovery = &self.local_config.settings.discovery;
+        if !discovery.enabled {
+            return Ok(0);
+        }
+        let discovered_services = health_check.discover_services(discovery).await;
+
+        for (service_url, health_status) in discovered_services {
+            info!("Auto-discovered Ollama service at: {}", service_url);
+            let config = OllamaConfig::new().with_base_url(service_url);
+
+            let provider_health = match health_status {
+                crate::ollama::HealthStatus::Healthy { response_time, models_available } => {
+                    ProviderHealthStatus::Healthy {
+                        response_time,
+                        models_available,
+                        additional_info: None,
+                    }
+                }
+                crate::ollama::HealthStatus::Degraded { reason, response_time } => {
+                    ProviderHealthStatus::Degraded { reason, response_time, models_available: 0 }
+                }
+                crate::ollama::HealthStatus::Unhealthy { reason, response_time } => {
+                    ProviderHealthStatus::Unhealthy { reason, response_time }
+                }
+            };
 
-            if let Ok(health_status) = health_check.check_health().await {
-                if health_status.is_usable() {
-                    info!("Auto-discovered Ollama service at: {}", service_url);
+            self.discover_ollama_models("ollama-discovered", &config, provider_health)
+                .await?;
+        }
 
-                    let provider_health = match health_status {
-                        crate::ollama::HealthStatus::Healthy {
-                            response_time,
-                            models_available,
-                        } => ProviderHealthStatus::Healthy {
-                            response_time,
-                            models_available,
-                            additional_info: None,
-                        },
-                        crate::ollama::HealthStatus::Degraded { reason, response_time } => {
-                            ProviderHealthStatus::Degraded {
-                                reason,
-                                response_time,
-                                models_available: 0,
-                            }
-                        }
-                        crate::ollama::HealthStatus::Unhealthy { reason, response_time } => {
-                            ProviderHealthStatus::Unhealthy { reason, response_time }
-                        }
-                    };
+        // Hosts may resolve to the same service, so count distinct models
+        Ok(self.get_provider_models("ollama-discovered").len())
+    }
 
-                    return self
-                        .discover_ollama_models("ollama-discovered", &config, provider_health)
-                        .await;
-                }
-            }
+    /// Probe LM Studio's OpenAI-compatible API at its default location
+    async fn discover_lmstudio_automatically(&mut self) -> Result<usize> {
+        debug!(url = %self.lmstudio_url, "Attempting automatic LM Studio discovery");
+
+        let config = LocalProviderConfig::default()
+            .provider_type("lmstudio")
+            .endpoint(self.lmstudio_url.clone())
+            .config(ProviderSpecificConfig::OpenAiCompatible {
+                base_url: self.lmstudio_url.clone(),
+                api_key: None,
+                models_path: "models".to_string(),
+            })
+            .health_check(HealthCheckConfig::default().timeout_seconds(3u64));
+
+        let provider_health = config.create_health_checker()?.check_health().await?;
+        if !provider_health.is_usable() {
+            anyhow::bail!("LM Studio at {} is not usable", self.lmstudio_url);
         }
+        info!("Found LM Studio service at: {}", self.lmstudio_url);
 
-        Ok(0)
+        let models = config.create_provider()?.models_at("models").await?;
+        Ok(self
+            .record_models("lmstudio-auto", models, provider_health)
+            .await)
     }
 
-    /// Get all discovered models
+    /// Get all discovered models, with one entry for each provider serving
+    /// a model
     pub fn get_discovered_models(&self) -> Vec<&DiscoveredModel> {
         self.discovered_models.values().collect()
     }
 
-    /// Get available models only
+    /// Get available models only, one per model id from the best provider
+    /// serving it
     pub fn get_available_models(&self) -> Vec<&DiscoveredModel> {
-        self.discovered_models
+        let mut available: Vec<_> = self
+            .discovered_models
             .values()
             .filter(|model| model.available)
+            .collect();
+        available.sort_by_key(|model| (model.model.id.as_str(), offering_rank(model)));
+        available.dedup_by(|a, b| a.model.id == b.model.id);
+        available
+    }
+
+    /// Available models served by configured providers, one per model id
+    /// from the best provider serving it. Servers found by automatic
+    /// discovery are left out: provider selection only routes requests to
+    /// configured providers.
+    pub fn get_servable_models(&self) -> Vec<&DiscoveredModel> {
+        let mut servable: Vec<_> = self
+            .discovered_models
+            .values()
+            .filter(|model| {
+                model.available && self.local_config.providers.contains_key(&model.provider)
+            })
+            .collect();
+        servable.sort_by_key(|model| (model.model.id.as_str(), offering_rank(model)));
+        servable.dedup_by(|a, b| a.model.id == b.model.id);
+        servable
+    }
+
+    /// Every provider's offering of the model `model_id` names, best first:
+    /// available offerings, then usable providers, then the fastest to
+    /// respond. Names are resolved as by [`Self::resolve_model_id`], falling
+    /// back to the exact id when no available model matches.
+    pub fn get_model_offerings(&self, model_id: &ModelId) -> Vec<&DiscoveredModel> {
+        let model_id = self
+            .resolve_model_id(model_id)
+            .unwrap_or_else(|_| model_id.clone());
+        let mut offerings: Vec<_> = self
+            .discovered_models
+            .values()
+            .filter(|model| model.model.id == model_id)
+            .collect();
+        offerings.sort_by_key(|model| offering_rank(model));
+        offerings
+    }
+
+    /// The best available offering of the model `model_id` names, if any
+    /// provider can serve it
+    pub fn get_best_provider(&self, model_id: &ModelId) -> Option<&DiscoveredModel> {
+        self.get_model_offerings(model_id)
+            .into_iter()
+            .find(|model| model.available)
+    }
+
+    /// Get available models that have the required capabilities. A required
+    /// capability the provider did not report counts as missing.
+    pub fn get_models_with_capabilities(
+        &self,
+        requires_tools: bool,
+        requires_reasoning: bool,
+        min_context: Option<u32>,
+    ) -> Vec<&DiscoveredModel> {
+        self.discovered_models
+            .values()
+            .filter(|discovered| {
+                let model = &discovered.model;
+                discovered.available
+                    && (!requires_tools || model.tools_supported == Some(true))
+                    && (!requires_reasoning || model.supports_reasoning == Some(true))
+                    && min_context.is_none_or(|min_context| {
+                        model
+                            .context_length
+                            .is_some_and(|context_length| context_length >= u64::from(min_context))
+                    })
+            })
             .collect()
     }
 
+    /// Every discovered model with its provider's health, available models
+    /// first and then ordered by provider and model id
+    pub fn summarize(&self) -> Vec<ModelSummaryRow> {
+        let mut rows: Vec<_> = self
+            .discovered_models
+            .values()
+            .map(|model| ModelSummaryRow {
+                model_id: model.model.id.clone(),
+                provider: model.provider.clone(),
+                available: model.available,
+                health: model.provider_health.label(),
+                response_time: model.response_time,
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            (!a.available, &a.provider, a.model_id.as_str()).cmp(&(
+                !b.available,
+                &b.provider,
+                b.model_id.as_str(),
+            ))
+        });
+        rows
+    }
+
+    /// The best available model matching `hint`: offerings from healthier,
+    /// faster providers first, then the largest context window
+    pub fn resolve_model(&self, hint: ModelHint) -> Option<&DiscoveredModel> {
+        self.get_models_with_capabilities(
+            hint.requires_tools,
+            hint.requires_reasoning,
+            hint.min_context,
+        )
+        .into_iter()
+        .filter(|discovered| hint.matches_name(&discovered.model))
+        .min_by_key(|discovered| {
+            (
+                offering_rank(discovered),
+                std::cmp::Reverse(discovered.model.context_length),
+            )
+        })
+    }
+
     /// Get models from a specific provider
     pub fn get_provider_models(&self, provider_name: &str) -> Vec<&DiscoveredModel> {
         self.discovered_models
@@ -373,12 +885,33 @@ impl ModelDiscoveryService {
             .collect()
     }
 
-    /// Check if a specific model is available
+    /// Check if any provider can serve a specific model, resolving the name
+    /// as [`ModelDiscoveryService::resolve_model_id`] does
     pub fn is_model_available(&self, model_id: &ModelId) -> bool {
+        self.resolve_model_id(model_id).is_ok()
+    }
+
+    /// The available model `model_id` names, through configured aliases,
+    /// `:latest` normalization and model families. A name matching several
+    /// models fails with every candidate.
+    pub fn resolve_model_id(&self, model_id: &ModelId) -> Result<ModelId, AliasError> {
+        let available = self
+            .discovered_models
+            .values()
+            .filter(|model| model.available)
+            .map(|model| model.model.id.as_str());
+        self.aliases
+            .resolve(model_id.as_str(), available)
+            .map(ModelId::new)
+    }
+
+    /// Number of distinct model ids discovered across providers
+    fn distinct_model_count(&self) -> usize {
         self.discovered_models
-            .get(model_id.as_str())
-            .map(|model| model.available)
-            .unwrap_or(false)
+            .keys()
+            .map(|(model_id, _)| model_id)
+            .collect::<std::collections::BTreeSet<_>>()
+            .len()
     }
 
     /// Get health status for all providers
@@ -386,6 +919,11 @@ impl ModelDiscoveryService {
         self.health_monitor.get_health_status().await
     }
 
+    /// Full health state of every provider checked so far, for diagnostics
+    pub async fn health_snapshot(&self) -> HealthSnapshot {
+        self.health_monitor.export_snapshot().await
+    }
+
     /// Force refresh of model discovery
     pub async fn refresh_discovery(&mut self) -> Result<ModelDiscoveryResult> {
         info!("Refreshing model discovery");
@@ -397,14 +935,33 @@ impl ModelDiscoveryService {
         self.discover_all_models().await
     }
 
+    /// Re-check one configured provider's health and rediscover only its
+    /// models, leaving other providers' entries untouched. Models the
+    /// provider no longer serves are dropped. Returns the number of models
+    /// discovered.
+    pub async fn refresh_provider(&mut self, provider_name: &str) -> Result<usize> {
+        let provider_config = self
+            .local_config
+            .providers
+            .get(provider_name)
+            .cloned()
+            .with_context(|| format!("Unknown provider: {provider_name}"))?;
+        info!(provider = %provider_name, "Refreshing model discovery for provider");
+
+        if let Err(e) = self.health_monitor.force_check(provider_name).await {
+            warn!(provider = %provider_name, error = %e, "Failed to re-check provider health");
+        }
+
+        self.discovered_models
+            .retain(|_, model| model.provider != provider_name);
+        self.discover_provider_models(provider_name, &provider_config)
+            .await
+    }
+
     /// Get discovery statistics
     pub fn get_discovery_stats(&self) -> DiscoveryStats {
-        let total_models = self.discovered_models.len();
-        let available_models = self
-            .discovered_models
-            .values()
-            .filter(|model| model.available)
-            .count();
+        let total_models = self.distinct_model_count();
+        let available_models = self.get_available_models().len();
 
         let providers: std::collections::HashSet<_> = self
             .discovered_models
@@ -425,6 +982,14 @@ impl ModelDiscoveryService {
     }
 }
 
+impl Drop for ModelDiscoveryService {
+    fn drop(&mut self) {
+        if let Some(task) = self.idle_eviction_task.take() {
+            task.abort();
+        }
+    }
+}
+
 /// Statistics about model discovery
 #[derive(Debug)]
 pub struct DiscoveryStats {
@@ -438,6 +1003,31 @@ pub struct DiscoveryStats {
     pub last_discovery: Option<std::time::Instant>,
 }
 
+/// Sort key ranking one provider's offering of a model against another's
+fn offering_rank(model: &DiscoveredModel) -> (bool, bool, Duration) {
+    (
+        !model.available,
+        !model.provider_health.is_usable(),
+        model.response_time.unwrap_or(Duration::MAX),
+    )
+}
+
+/// Fill `model`'s capability metadata from Ollama's `/api/show`, keeping
+/// what `/api/tags` reported when the details cannot be fetched
+async fn with_ollama_details(ollama: &Ollama, mut model: Model) -> Model {
+    match ollama.show_model(&model.id).await {
+        Ok(details) => {
+            model.context_length = details.context_length().or(model.context_length);
+            model.tools_supported = details.tools_supported().or(model.tools_supported);
+            model.supports_reasoning = details.supports_reasoning().or(model.supports_reasoning);
+        }
+        Err(e) => {
+            debug!(model = %model.id, error = %e, "Failed to fetch Ollama model details");
+        }
+    }
+    model
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -445,6 +1035,8 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::performance::IdleEvictionConfig;
+    use crate::readiness::Readiness;
 
     fn create_test_model(id: &str, name: &str) -> Model {
         Model {
@@ -471,7 +1063,6 @@ mod tests {
             reason: "High response time".to_string(),
             response_time: Duration::from_millis(2000),
             models_available: 2,
-            additional_info: None,
         }
     }
 
@@ -499,13 +1090,13 @@ mod tests {
     #[tokio::test]
     async fn test_model_discovery_service_empty_config() {
         let config = LocalAiConfig::new();
-        let service = ModelDiscoveryService::new(config).await.unwrap();
-        let result = service.discover_models().await.unwrap();
+        let mut service = ModelDiscoveryService::new(config).await.unwrap();
+        let result = service.discover_all_models().await.unwrap();
 
-        assert_eq!(result.discovered_models.len(), 0);
-        assert_eq!(result.stats.total_models, 0);
-        assert_eq!(result.stats.available_models, 0);
-        assert_eq!(result.stats.total_providers, 0);
+        assert_eq!(result.total_models, 0);
+        assert_eq!(result.available_models, 0);
+        assert_eq!(result.healthy_providers, 0);
+        assert_eq!(service.get_discovery_stats().total_providers, 0);
     }
 
     #[test]
@@ -627,18 +1218,16 @@ mod tests {
     #[test]
     fn test_model_discovery_result_empty() {
         let fixture = ModelDiscoveryResult {
-            discovered_models: vec![],
-            stats: DiscoveryStats {
-                total_models: 0,
-                available_models: 0,
-                total_providers: 0,
-                last_discovery: Some(std::time::Instant::now()),
-            },
+            total_models: 0,
+            healthy_providers: 0,
+            available_models: 0,
+            discovery_duration: Duration::ZERO,
+            warnings: vec![],
         };
 
-        assert!(fixture.discovered_models.is_empty());
-        assert_eq!(fixture.stats.total_models, 0);
-        assert_eq!(fixture.stats.available_models, 0);
+        assert!(fixture.warnings.is_empty());
+        assert_eq!(fixture.total_models, 0);
+        assert_eq!(fixture.available_models, 0);
     }
 
     #[test]
@@ -664,27 +1253,24 @@ mod tests {
             response_time: Some(Duration::from_millis(2000)),
         };
 
+        let discovered_models = [discovered_model1, discovered_model2];
         let fixture = ModelDiscoveryResult {
-            discovered_models: vec![discovered_model1, discovered_model2],
-            stats: DiscoveryStats {
-                total_models: 2,
-                available_models: 2,
-                total_providers: 1,
-                last_discovery: Some(std::time::Instant::now()),
-            },
+            total_models: 2,
+            healthy_providers: 1,
+            available_models: 2,
+            discovery_duration: Duration::from_millis(50),
+            warnings: vec![],
         };
 
-        assert_eq!(fixture.discovered_models.len(), 2);
-        assert_eq!(fixture.stats.total_models, 2);
-        assert_eq!(fixture.stats.available_models, 2);
-        assert_eq!(fixture.stats.total_providers, 1);
+        assert_eq!(discovered_models.len(), fixture.total_models);
+        assert_eq!(fixture.available_models, 2);
+        assert_eq!(fixture.healthy_providers, 1);
 
         // Verify both models are available
-        assert!(fixture.discovered_models.iter().all(|m| m.available));
+        assert!(discovered_models.iter().all(|m| m.available));
 
         // Verify different health statuses
-        let health_statuses: Vec<_> = fixture
-            .discovered_models
+        let health_statuses: Vec<_> = discovered_models
             .iter()
             .map(|m| &m.provider_health)
             .collect();
@@ -696,13 +1282,839 @@ mod tests {
             .any(|s| matches!(s, ProviderHealthStatus::Degraded { .. })));
     }
 
+    #[tokio::test]
+    async fn test_discovery_serves_fresh_healthy_provider_from_cache() {
+        let fresh = crate::mock_server::MockOllamaServer::builder()
+            .tags(&["llama3.2:latest"])
+            .start()
+            .await;
+        let stale = crate::mock_server::MockOllamaServer::builder()
+            .tags(&["qwen2.5:latest"])
+            .start()
+            .await;
+
+        let mut config = LocalAiConfig::new();
+        config.providers.insert(
+            "fresh".to_string(),
+            LocalProviderConfig::default().endpoint(fresh.url()),
+        );
+        config.providers.insert(
+            "stale".to_string(),
+            LocalProviderConfig::default().endpoint(stale.url()),
+        );
+        config
+            .settings
+            .discovery
+            .provider_cache_ttl_seconds
+            .insert("stale".to_string(), 0);
+
+        let mut fixture = ModelDiscoveryService::new(config).await.unwrap();
+        for name in ["fresh", "stale"] {
+            fixture
+                .health_monitor
+                .set_provider_status(name, create_healthy_status())
+                .await;
+        }
+
+        for _ in 0..3 {
+            fixture.discover_all_models().await.unwrap();
+        }
+
+        let actual = (
+            fresh.hits("GET", "/api/tags"),
+            stale.hits("GET", "/api/tags"),
+        );
+        let expected = (1, 3);
+        assert_eq!(actual, expected);
+        assert_eq!(fixture.get_provider_models("fresh").len(), 1);
+        assert_eq!(fixture.get_provider_models("stale").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ready_resolves_after_initial_checks() {
+        let server = crate::mock_server::MockOllamaServer::builder()
+            .tags(&["llama3.2:latest"])
+            .start()
+            .await;
+        let mut config = LocalAiConfig::new();
+        config.providers.insert(
+            "ollama".to_string(),
+            LocalProviderConfig::default().endpoint(server.url()),
+        );
+        let mut fixture = ModelDiscoveryService::new(config).await.unwrap();
+        let ready = fixture.ready();
+        assert!(!fixture.readiness().is_ready());
+
+        fixture.start().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(1), ready)
+            .await
+            .unwrap();
+
+        let health = fixture.get_provider_health_status().await;
+        assert!(health["ollama"].is_usable());
+        assert_eq!(fixture.get_provider_models("ollama").len(), 1);
+        assert!(fixture.readiness().is_ready());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_start_fails_readiness() {
+        // Accepts connections but never answers, so startup cannot finish
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = LocalAiConfig::new();
+        config.providers.insert(
+            "ollama".to_string(),
+            LocalProviderConfig::default()
+                .endpoint(format!("http://{}", listener.local_addr().unwrap())),
+        );
+        let mut fixture = ModelDiscoveryService::new(config).await.unwrap();
+        let ready = fixture.ready();
+
+        let started = tokio::time::timeout(Duration::from_millis(100), fixture.start()).await;
+        tokio::time::timeout(Duration::from_secs(1), ready)
+            .await
+            .unwrap();
+
+        assert!(started.is_err());
+        assert_eq!(fixture.readiness().state(), Readiness::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_openai_compatible_provider_models_are_discovered() {
+        let server = crate::mock_server::MockOllamaServer::builder()
+            .on(
+                "GET",
+                "/v1/models",
+                crate::mock_server::ScriptedResponse::json(
+                    200,
+                    serde_json::json!({
+                        "object": "list",
+                        "data": [
+                            {
+                                "id": "meta-llama/Llama-3.1-8B-Instruct",
+                                "object": "model",
+                                "owned_by": "vllm",
+                                "context_length": 8192,
+                                "supported_parameters": ["tools"]
+                            },
+                            { "id": "qwen2.5-coder", "object": "model", "owned_by": "vllm" }
+                        ]
+                    }),
+                ),
+            )
+            .start()
+            .await;
+        let mut config = LocalAiConfig::new();
+        config.providers.insert(
+            "vllm".to_string(),
+            LocalProviderConfig::default()
+                .provider_type("openai_compatible")
+                .endpoint(server.url())
+                .config(ProviderSpecificConfig::OpenAiCompatible {
+                    base_url: format!("{}/v1", server.url()),
+                    api_key: None,
+                    models_path: "models".to_string(),
+                }),
+        );
+        let mut fixture = ModelDiscoveryService::new(config).await.unwrap();
+
+        fixture.start().await.unwrap();
+
+        let health = fixture.get_provider_health_status().await;
+        let actual: Vec<_> = fixture
+            .get_provider_models("vllm")
+            .into_iter()
+            .map(|discovered| serde_json::to_value(&discovered.model).unwrap())
+            .collect();
+        let expected: Vec<_> = [
+            Model {
+                id: ModelId::new("meta-llama/Llama-3.1-8B-Instruct"),
+                name: None,
+                description: None,
+                context_length: Some(8192),
+                tools_supported: Some(true),
+                supports_parallel_tool_calls: Some(false),
+                supports_reasoning: Some(false),
+            },
+            Model {
+                id: ModelId::new("qwen2.5-coder"),
+                name: None,
+                description: None,
+                context_length: None,
+                tools_supported: Some(false),
+                supports_parallel_tool_calls: Some(false),
+                supports_reasoning: Some(false),
+            },
+        ]
+        .iter()
+        .map(|model| serde_json::to_value(model).unwrap())
+        .collect();
+        assert_eq!(actual, expected);
+        assert_eq!(health["vllm"].models_available(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_models_filtered_by_capability() {
+        let model = |id: &str, tools, reasoning, context_length| Model {
+            id: ModelId::new(id),
+            name: None,
+            description: None,
+            context_length,
+            tools_supported: tools,
+            supports_parallel_tool_calls: None,
+            supports_reasoning: reasoning,
+        };
+        let mut fixture = ModelDiscoveryService::new(LocalAiConfig::new())
+            .await
+            .unwrap();
+        fixture
+            .record_models(
+                "ollama",
+                vec![
+                    model("tools-long", Some(true), Some(false), Some(131072)),
+                    model("reasoning-short", Some(false), Some(true), Some(4096)),
+                    model("both", Some(true), Some(true), Some(32768)),
+                    model("unknown", None, None, None),
+                ],
+                create_healthy_status(),
+            )
+            .await;
+        fixture
+            .record_models(
+                "lmstudio",
+                vec![model("unavailable", Some(true), Some(true), Some(131072))],
+                create_degraded_status(),
+            )
+            .await;
+
+        let ids = |models: Vec<&DiscoveredModel>| -> Vec<String> {
+            models
+                .into_iter()
+                .map(|discovered| discovered.model.id.as_str().to_string())
+                .collect()
+        };
+        let actual = vec![
+            ids(fixture.get_models_with_capabilities(false, false, None)),
+            ids(fixture.get_models_with_capabilities(true, false, None)),
+            ids(fixture.get_models_with_capabilities(false, true, None)),
+            ids(fixture.get_models_with_capabilities(false, false, Some(32768))),
+            ids(fixture.get_models_with_capabilities(true, true, Some(65536))),
+        ];
+        let expected: Vec<Vec<String>> = vec![
+            vec!["both", "reasoning-short", "tools-long", "unknown"],
+            vec!["both", "tools-long"],
+            vec!["both", "reasoning-short"],
+            vec!["both", "tools-long"],
+            vec![],
+        ]
+        .into_iter()
+        .map(|ids| ids.into_iter().map(String::from).collect())
+        .collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_provider_only_changes_that_provider() {
+        let changed = crate::mock_server::MockOllamaServer::builder()
+            .tags(&["llama3.2:latest", "qwen2.5:latest"])
+            .tags(&["llama3.2:latest", "phi4:latest"])
+            .start()
+            .await;
+        let untouched = crate::mock_server::MockOllamaServer::builder()
+            .tags(&["mistral:latest"])
+            .start()
+            .await;
+        let mut config = LocalAiConfig::new();
+        config.providers.insert(
+            "changed".to_string(),
+            LocalProviderConfig::default().endpoint(changed.url()),
+        );
+        config.providers.insert(
+            "untouched".to_string(),
+            LocalProviderConfig::default().endpoint(untouched.url()),
+        );
+        let mut fixture = ModelDiscoveryService::new(config).await.unwrap();
+        for name in ["changed", "untouched"] {
+            fixture
+                .health_monitor
+                .set_provider_status(name, create_healthy_status())
+                .await;
+        }
+        fixture.discover_all_models().await.unwrap();
+        let untouched_hits = untouched.hits("GET", "/api/tags");
+
+        let count = fixture.refresh_provider("changed").await.unwrap();
+
+        let ids = |provider_name: &str| -> Vec<String> {
+            fixture
+                .get_provider_models(provider_name)
+                .into_iter()
+                .map(|discovered| discovered.model.id.as_str().to_string())
+                .collect()
+        };
+        let actual = (count, ids("changed"), ids("untouched"));
+        let expected = (
+            2,
+            vec!["llama3.2:latest".to_string(), "phi4:latest".to_string()],
+            vec!["mistral:latest".to_string()],
+        );
+        assert_eq!(actual, expected);
+        assert_eq!(untouched.hits("GET", "/api/tags"), untouched_hits);
+        assert!(fixture.refresh_provider("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shared_model_keeps_every_offering() {
+        let healthy = crate::mock_server::MockOllamaServer::builder()
+            .tags(&["llama3.2:latest", "qwen2.5:latest"])
+            .start()
+            .await;
+        let degraded = crate::mock_server::MockOllamaServer::builder()
+            .tags(&["llama3.2:latest"])
+            .start()
+            .await;
+        let mut config = LocalAiConfig::new();
+        config.providers.insert(
+            "gpu-healthy".to_string(),
+            LocalProviderConfig::default().endpoint(healthy.url()),
+        );
+        config.providers.insert(
+            "gpu-degraded".to_string(),
+            LocalProviderConfig::default().endpoint(degraded.url()),
+        );
+        let mut fixture = ModelDiscoveryService::new(config).await.unwrap();
+        fixture
+            .health_monitor
+            .set_provider_status("gpu-healthy", create_healthy_status())
+            .await;
+        fixture
+            .health_monitor
+            .set_provider_status("gpu-degraded", create_degraded_status())
+            .await;
+
+        fixture.discover_all_models().await.unwrap();
+
+        let shared = ModelId::new("llama3.2:latest");
+        let offerings: Vec<_> = fixture
+            .get_model_offerings(&shared)
+            .into_iter()
+            .map(|offering| (offering.provider.as_str(), offering.available))
+            .collect();
+        assert_eq!(
+            offerings,
+            vec![("gpu-healthy", true), ("gpu-degraded", false)]
+        );
+
+        let available: Vec<_> = fixture
+            .get_available_models()
+            .into_iter()
+            .map(|model| (model.model.id.as_str(), model.provider.as_str()))
+            .collect();
+        assert_eq!(
+            available,
+            vec![
+                ("llama3.2:latest", "gpu-healthy"),
+                ("qwen2.5:latest", "gpu-healthy")
+            ]
+        );
+        assert_eq!(
+            fixture
+                .get_best_provider(&shared)
+                .map(|model| model.provider.as_str()),
+            Some("gpu-healthy")
+        );
+        assert_eq!(fixture.get_discovery_stats().total_models, 2);
+    }
+
+    #[tokio::test]
+    async fn test_best_provider_prefers_faster_healthy_offering() {
+        let model = create_test_model("llama3.2:latest", "Llama 3.2");
+        let mut fixture = ModelDiscoveryService::new(LocalAiConfig::new())
+            .await
+            .unwrap();
+        for (provider_name, millis) in [("gpu-slow", 900), ("gpu-fast", 150)] {
+            fixture
+                .record_models(
+                    provider_name,
+                    vec![model.clone()],
+                    ProviderHealthStatus::Healthy {
+                        response_time: Duration::from_millis(millis),
+                        models_available: 1,
+                        additional_info: None,
+                    },
+                )
+                .await;
+        }
+
+        let actual = fixture
+            .get_best_provider(&model.id)
+            .map(|model| model.provider.clone());
+
+        assert_eq!(actual, Some("gpu-fast".to_string()));
+        assert_eq!(fixture.get_model_offerings(&model.id).len(), 2);
+        assert!(fixture.is_model_available(&model.id));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_model_id_through_aliases() {
+        let config = LocalAiConfig::new().model_aliases(HashMap::from([(
+            "coder".to_string(),
+            "qwen2.5-coder:7b".to_string(),
+        )]));
+        let mut fixture = ModelDiscoveryService::new(config).await.unwrap();
+        fixture
+            .record_models(
+                "ollama",
+                vec![
+                    create_test_model("llama3.1:latest", "Llama 3.1"),
+                    create_test_model("llama3.2:latest", "Llama 3.2"),
+                    create_test_model("qwen2.5-coder:7b", "Qwen 2.5 Coder"),
+                ],
+                create_healthy_status(),
+            )
+            .await;
+        let resolve = |name: &str| {
+            fixture
+                .resolve_model_id(&ModelId::new(name))
+                .map(|model_id| model_id.as_str().to_string())
+        };
+
+        let actual = vec![resolve("coder"), resolve("llama3.2"), resolve("llama3")];
+
+        let expected = vec![
+            Ok("qwen2.5-coder:7b".to_string()),
+            Ok("llama3.2:latest".to_string()),
+            Err(AliasError::Ambiguous {
+                requested: "llama3".to_string(),
+                candidates: vec!["llama3.1:latest".to_string(), "llama3.2:latest".to_string()],
+            }),
+        ];
+        assert_eq!(actual, expected);
+        assert!(fixture.is_model_available(&ModelId::new("coder")));
+        assert!(!fixture.is_model_available(&ModelId::new("llama3")));
+        assert_eq!(
+            fixture
+                .get_best_provider(&ModelId::new("coder"))
+                .map(|offering| offering.model.id.as_str()),
+            Some("qwen2.5-coder:7b")
+        );
+        assert_eq!(
+            fixture.get_model_offerings(&ModelId::new("llama3.2")).len(),
+            1
+        );
+    }
+
+    async fn hint_fixture() -> ModelDiscoveryService {
+        let model = |id: &str, name: &str, reasoning: bool, context_length: u64| Model {
+            supports_reasoning: Some(reasoning),
+            context_length: Some(context_length),
+            ..create_test_model(id, name)
+        };
+        let mut fixture = ModelDiscoveryService::new(LocalAiConfig::new())
+            .await
+            .unwrap();
+        fixture
+            .record_models(
+                "gpu-healthy",
+                vec![
+                    model("qwen2.5-coder:7b", "Qwen 2.5 Coder", false, 32_768),
+                    model("deepseek-r1:8b", "DeepSeek R1", true, 65_536),
+                    model("phi4-reasoning:14b", "Phi 4 Reasoning", true, 16_384),
+                ],
+                create_healthy_status(),
+            )
+            .await;
+        fixture
+            .record_models(
+                "gpu-degraded",
+                vec![model("qwq:32b", "QwQ", true, 131_072)],
+                create_degraded_status(),
+            )
+            .await;
+        fixture
+    }
+
+    #[tokio::test]
+    async fn test_resolve_model_picks_best_matching_model() {
+        let fixture = hint_fixture().await;
+        let resolve = |hint: ModelHint| {
+            fixture
+                .resolve_model(hint)
+                .map(|model| model.model.id.as_str().to_string())
+        };
+
+        let actual = vec![
+            resolve(ModelHint::default().requires_reasoning(true)),
+            resolve(ModelHint::default().name_contains("CODER")),
+            resolve(
+                ModelHint::default()
+                    .requires_reasoning(true)
+                    .min_context(100_000u32),
+            ),
+            resolve(ModelHint::default().name_contains("llava")),
+        ];
+
+        let expected = vec![
+            Some("deepseek-r1:8b".to_string()),
+            Some("qwen2.5-coder:7b".to_string()),
+            None,
+            None,
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_summary_lists_unavailable_models_last() {
+        let mut fixture = ModelDiscoveryService::new(LocalAiConfig::new())
+            .await
+            .unwrap();
+        fixture
+            .record_models(
+                "a-down",
+                vec![create_test_model("llama3.2:latest", "Llama 3.2")],
+                create_unhealthy_status(),
+            )
+            .await;
+        fixture
+            .record_models(
+                "b-up",
+                vec![
+                    create_test_model("qwen2.5:latest", "Qwen 2.5"),
+                    create_test_model("llama3.2:latest", "Llama 3.2"),
+                ],
+                create_healthy_status(),
+            )
+            .await;
+
+        let summary = fixture.summarize();
+
+        let actual: Vec<_> = summary
+            .iter()
+            .map(|row| (row.model_id.as_str(), row.provider.as_str(), row.available))
+            .collect();
+        let expected = vec![
+            ("llama3.2:latest", "b-up", true),
+            ("qwen2.5:latest", "b-up", true),
+            ("llama3.2:latest", "a-down", false),
+        ];
+        assert_eq!(actual, expected);
+        let recorded =
+            fixture.get_provider_models("a-down").len() + fixture.get_provider_models("b-up").len();
+        assert_eq!(summary.len(), recorded);
+
+        let actual = render_model_summary(&summary);
+        let expected = [
+            "MODEL            PROVIDER  STATUS       HEALTH     LATENCY",
+            "llama3.2:latest  b-up      available    healthy    100ms",
+            "qwen2.5:latest   b-up      available    healthy    100ms",
+            "llama3.2:latest  a-down    unavailable  unhealthy  5000ms",
+        ]
+        .join("\n");
+        assert_eq!(actual, expected);
+    }
+
+    async fn ollama_fixture(server_url: String) -> ModelDiscoveryService {
+        let mut config = LocalAiConfig::new();
+        config.providers.insert(
+            "ollama".to_string(),
+            LocalProviderConfig::default().endpoint(server_url),
+        );
+        let fixture = ModelDiscoveryService::new(config).await.unwrap();
+        fixture
+            .health_monitor
+            .set_provider_status("ollama", create_healthy_status())
+            .await;
+        fixture
+    }
+
+    #[tokio::test]
+    async fn test_ollama_models_carry_capabilities_from_show() {
+        let server = crate::mock_server::MockOllamaServer::builder()
+            .tags(&["llama3.2:latest", "qwen3:8b"])
+            .on(
+                "POST",
+                "/api/show",
+                crate::mock_server::ScriptedResponse::json(
+                    200,
+                    serde_json::json!({
+                        "parameters": "num_ctx 8192\nstop \"<|eot_id|>\"",
+                        "template": "{{ if .Tools }}{{ .Tools }}{{ end }}",
+                        "model_info": {
+                            "general.architecture": "llama",
+                            "llama.context_length": 131072
+                        },
+                        "capabilities": ["completion", "tools"]
+                    }),
+                ),
+            )
+            .start()
+            .await;
+        let mut fixture = ollama_fixture(server.url()).await;
+
+        fixture.discover_all_models().await.unwrap();
+
+        let actual: Vec<_> = fixture
+            .get_provider_models("ollama")
+            .into_iter()
+            .map(|discovered| {
+                let model = &discovered.model;
+                (
+                    model.id.as_str().to_string(),
+                    model.context_length,
+                    model.tools_supported,
+                    model.supports_reasoning,
+                )
+            })
+            .collect();
+        let expected = vec![
+            (
+                "llama3.2:latest".to_string(),
+                Some(8192),
+                Some(true),
+                Some(false),
+            ),
+            ("qwen3:8b".to_string(), Some(8192), Some(true), Some(false)),
+        ];
+        assert_eq!(actual, expected);
+        assert_eq!(server.hits("POST", "/api/show"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_idle_eviction_unloads_idle_ollama_models() {
+        let mut server = crate::mock_server::MockServer::new().await;
+        let unload = server
+            .mock_ollama_generate(
+                serde_json::json!({"model": "llama3.2:latest", "keep_alive": "0"}),
+                200,
+            )
+            .await;
+        let mut config = LocalAiConfig::new();
+        config.providers.insert(
+            "ollama".to_string(),
+            LocalProviderConfig::default().endpoint(server.url()),
+        );
+        config.settings.idle_eviction = IdleEvictionConfig::default()
+            .enabled(true)
+            .idle_threshold(Duration::ZERO)
+            .check_interval(Duration::from_millis(10));
+        let mut fixture = ModelDiscoveryService::new(config).await.unwrap();
+        fixture
+            .idle_eviction()
+            .record_request("ollama", &ModelId::new("llama3.2:latest"))
+            .await;
+
+        fixture.start_idle_eviction();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        unload.assert_async().await;
+        assert_eq!(fixture.idle_eviction().tracked_models().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_idle_eviction_is_not_started_unless_enabled() {
+        let mut fixture = ModelDiscoveryService::new(LocalAiConfig::with_default_ollama())
+            .await
+            .unwrap();
+
+        fixture.start_idle_eviction();
+
+        assert!(fixture.idle_eviction_task.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ollama_show_failure_keeps_model() {
+        let server = crate::mock_server::MockOllamaServer::builder()
+            .tags(&["llama3.2:latest"])
+            .start()
+            .await;
+        let mut fixture = ollama_fixture(server.url()).await;
+
+        fixture.discover_all_models().await.unwrap();
+
+        let models = fixture.get_provider_models("ollama");
+        let actual = (
+            models.len(),
+            models[0].model.context_length,
+            models[0].model.supports_reasoning,
+        );
+        assert_eq!(actual, (1, None, None));
+        assert_eq!(server.hits("POST", "/api/show"), 1);
+    }
+
+    async fn lmstudio_fixture(server_url: &str, config: LocalAiConfig) -> ModelDiscoveryService {
+        ModelDiscoveryService::new(config)
+            .await
+            .unwrap()
+            .with_lmstudio_url(format!("{server_url}/v1"))
+    }
+
+    fn lmstudio_models_server() -> crate::mock_server::MockOllamaServerBuilder {
+        crate::mock_server::MockOllamaServer::builder().on(
+            "GET",
+            "/v1/models",
+            crate::mock_server::ScriptedResponse::json(
+                200,
+                serde_json::json!({
+                    "object": "list",
+                    "data": [
+                        { "id": "qwen2.5-7b-instruct", "object": "model", "owned_by": "organization_owner" },
+                        { "id": "text-embedding-nomic-embed-text-v1.5", "object": "model", "owned_by": "organization_owner" }
+                    ]
+                }),
+            ),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_lmstudio_is_discovered_automatically() {
+        let server = lmstudio_models_server().start().await;
+        let mut fixture = lmstudio_fixture(&server.url(), LocalAiConfig::new()).await;
+
+        let result = fixture.discover_all_models().await.unwrap();
+
+        let actual: Vec<_> = fixture
+            .get_provider_models("lmstudio-auto")
+            .into_iter()
+            .map(|model| (model.model.id.as_str().to_string(), model.available))
+            .collect();
+        let expected = vec![
+            ("qwen2.5-7b-instruct".to_string(), true),
+            ("text-embedding-nomic-embed-text-v1.5".to_string(), true),
+        ];
+        assert_eq!(actual, expected);
+        assert!(!result.warnings.iter().any(|w| w.contains("LM Studio")));
+    }
+
+    #[tokio::test]
+    async fn test_automatically_discovered_models_are_not_servable() {
+        let server = lmstudio_models_server().start().await;
+        let mut fixture = lmstudio_fixture(&server.url(), LocalAiConfig::new()).await;
+
+        fixture.discover_all_models().await.unwrap();
+
+        assert_eq!(fixture.get_available_models().len(), 2);
+        assert!(fixture.get_servable_models().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_lmstudio_probe_failure_is_a_warning() {
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", closed.local_addr().unwrap());
+        drop(closed);
+        let mut fixture = lmstudio_fixture(&url, LocalAiConfig::new()).await;
+
+        let result = fixture.discover_all_models().await.unwrap();
+
+        assert!(fixture.get_provider_models("lmstudio-auto").is_empty());
+        assert!(result
+            .warnings
+            .iter()
+            .any(|w| w.starts_with("Automatic LM Studio discovery failed")));
+    }
+
+    #[tokio::test]
+    async fn test_configured_lmstudio_skips_automatic_probe() {
+        let server = lmstudio_models_server().start().await;
+        let mut config = LocalAiConfig::new();
+        config.providers.insert(
+            "lmstudio".to_string(),
+            LocalProviderConfig::default().enabled(false),
+        );
+        let mut fixture = lmstudio_fixture(&server.url(), config).await;
+
+        fixture.discover_all_models().await.unwrap();
+
+        assert_eq!(server.hits("GET", "/v1/models"), 0);
+        assert!(fixture.get_provider_models("lmstudio-auto").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_scan_registers_only_the_healthy_candidate() {
+        let healthy = crate::mock_server::MockOllamaServer::builder()
+            .tags(&["llama3.2:latest"])
+            .start()
+            .await;
+        let mut ports = Vec::new();
+        for _ in 0..3 {
+            let hanging = crate::mock_server::spawn_delayed_server(
+                Duration::from_secs(30),
+                "application/json",
+                "{}".to_string(),
+            )
+            .await;
+            ports.push(reqwest::Url::parse(&hanging).unwrap().port().unwrap());
+        }
+        for _ in 0..3 {
+            let refused = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            ports.push(refused.local_addr().unwrap().port());
+        }
+        ports.push(reqwest::Url::parse(&healthy.url()).unwrap().port().unwrap());
+
+        let probe_timeout = Duration::from_millis(500);
+        let mut config = LocalAiConfig::new();
+        config.settings.discovery = config
+            .settings
+            .discovery
+            .scan_hosts(vec!["127.0.0.1".to_string()])
+            .scan_ports(ports.clone())
+            .probe_timeout_ms(probe_timeout.as_millis() as u64);
+        let mut fixture = ModelDiscoveryService::new(config).await.unwrap();
+
+        let start = std::time::Instant::now();
+        let actual = fixture.discover_ollama_automatically().await.unwrap();
+        let elapsed = start.elapsed();
+
+        assert_eq!(actual, 1);
+        assert_eq!(fixture.get_provider_models("ollama-discovered").len(), 1);
+        assert!(
+            elapsed < probe_timeout * 3,
+            "scan took {elapsed:?}, sequential bound is {:?}",
+            probe_timeout * 3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_discovery_reprobes_provider_whose_status_changed() {
+        let server = crate::mock_server::MockOllamaServer::builder()
+            .tags(&["llama3.2:latest"])
+            .start()
+            .await;
+        let mut config = LocalAiConfig::new();
+        config.providers.insert(
+            "ollama".to_string(),
+            LocalProviderConfig::default().endpoint(server.url()),
+        );
+        let mut fixture = ModelDiscoveryService::new(config).await.unwrap();
+        fixture
+            .health_monitor
+            .set_provider_status("ollama", create_healthy_status())
+            .await;
+        fixture.discover_all_models().await.unwrap();
+
+        fixture
+            .health_monitor
+            .set_provider_status(
+                "ollama",
+                ProviderHealthStatus::Degraded {
+                    reason: "High response time".to_string(),
+                    response_time: Duration::from_millis(2000),
+                    models_available: 1,
+                },
+            )
+            .await;
+        fixture.discover_all_models().await.unwrap();
+
+        let actual = server.hits("GET", "/api/tags");
+        let expected = 2;
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_model_discovery_result_mixed_availability() {
         let model1 = create_test_model("llama3.2:latest", "Llama 3.2");
         let model2 = create_test_model("qwen2.5:latest", "Qwen 2.5");
         let model3 = create_test_model("deepseek-r1:latest", "DeepSeek R1");
 
-        let discovered_models = vec![
+        let discovered_models = [
             DiscoveredModel {
                 model: model1,
                 provider: "ollama".to_string(),
@@ -730,40 +2142,26 @@ mod tests {
         ];
 
         let fixture = ModelDiscoveryResult {
-            discovered_models: discovered_models.clone(),
-            stats: DiscoveryStats {
-                total_models: 3,
-                available_models: 2,
-                total_providers: 2,
-                last_discovery: Some(std::time::Instant::now()),
-            },
+            total_models: 3,
+            healthy_providers: 1,
+            available_models: 2,
+            discovery_duration: Duration::from_millis(50),
+            warnings: vec!["ollama-backup: Connection timeout".to_string()],
         };
 
-        assert_eq!(fixture.discovered_models.len(), 3);
-        assert_eq!(fixture.stats.total_models, 3);
-        assert_eq!(fixture.stats.available_models, 2);
-        assert_eq!(fixture.stats.total_providers, 2);
+        assert_eq!(discovered_models.len(), fixture.total_models);
+        assert_eq!(fixture.available_models, 2);
+        assert_eq!(fixture.healthy_providers, 1);
 
         // Verify availability counts
-        let available_count = fixture
-            .discovered_models
-            .iter()
-            .filter(|m| m.available)
-            .count();
-        let unavailable_count = fixture
-            .discovered_models
-            .iter()
-            .filter(|m| !m.available)
-            .count();
+        let available_count = discovered_models.iter().filter(|m| m.available).count();
+        let unavailable_count = discovered_models.iter().filter(|m| !m.available).count();
         assert_eq!(available_count, 2);
         assert_eq!(unavailable_count, 1);
 
         // Verify provider distribution
-        let providers: std::collections::HashSet<_> = fixture
-            .discovered_models
-            .iter()
-            .map(|m| &m.provider)
-            .collect();
+        let providers: std::collections::HashSet<_> =
+            discovered_models.iter().map(|m| &m.provider).collect();
         assert_eq!(providers.len(), 2);
         assert!(providers.contains(&"ollama".to_string()));
         assert!(providers.contains(&"ollama-backup".to_string()));
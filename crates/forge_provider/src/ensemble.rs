@@ -0,0 +1,450 @@
+//! Ensemble dispatch across multiple providers
+//!
+//! For critical decisions a request can be sent to several providers at once
+//! and their answers combined into a single response, either by taking the
+//! first answer, a majority vote, or the highest-scoring answer. Failing
+//! members are tolerated as long as a quorum of providers responds.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+
+use derive_setters::Setters;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::task::JoinSet;
+use tracing::{debug, info, warn};
+
+use crate::performance::{PerformanceMeasurement, PerformanceMonitor, RequestType};
+use crate::retry::FailureKind;
+
+/// How member responses are combined into one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnsembleAggregation {
+    /// The first member to respond successfully. The remaining members
+    /// are cancelled as soon as it does.
+    First,
+    /// The answer given by the most members
+    Majority,
+    /// The answer with the highest score
+    BestByScore,
+}
+
+/// Ensemble dispatch configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Setters)]
+#[setters(strip_option, into)]
+pub struct EnsembleConfig {
+    /// Providers the request is dispatched to
+    pub providers: Vec<String>,
+    /// Aggregation applied to the member responses
+    pub aggregation: EnsembleAggregation,
+    /// Successful responses required to produce a result. First
+    /// aggregation only ever needs one.
+    pub quorum: usize,
+}
+
+impl Default for EnsembleConfig {
+    fn default() -> Self {
+        Self {
+            providers: Vec::new(),
+            aggregation: EnsembleAggregation::Majority,
+            quorum: 2,
+        }
+    }
+}
+
+/// A response that can take part in ensemble aggregation
+pub trait EnsembleVote {
+    /// Key under which equivalent answers are grouped for majority voting
+    fn vote(&self) -> String;
+
+    /// Confidence used by [`EnsembleAggregation::BestByScore`]
+    fn score(&self) -> f64 {
+        0.0
+    }
+}
+
+impl EnsembleVote for String {
+    fn vote(&self) -> String {
+        self.trim().to_lowercase()
+    }
+}
+
+/// Outcome of one ensemble member
+#[derive(Debug, Clone)]
+pub struct EnsembleMember {
+    /// Provider that served the member request
+    pub provider_name: String,
+    /// Error message if the member failed
+    pub error: Option<String>,
+}
+
+/// Aggregated ensemble response
+#[derive(Debug, Clone)]
+pub struct EnsembleResponse<T> {
+    /// The chosen response
+    pub response: T,
+    /// Provider whose response was chosen
+    pub provider_name: String,
+    /// Members that gave the same answer as the chosen response
+    pub agreement: usize,
+    /// Every member, in the order they completed
+    pub members: Vec<EnsembleMember>,
+}
+
+/// Errors raised by ensemble dispatch
+#[derive(Debug, Error)]
+pub enum EnsembleError {
+    #[error("Ensemble has no providers configured")]
+    NoProviders,
+
+    #[error("Ensemble quorum not met: {responded} of {required} required responses")]
+    QuorumNotMet {
+        responded: usize,
+        required: usize,
+        errors: Vec<String>,
+    },
+}
+
+/// Dispatches a request to several providers and aggregates their responses
+pub struct Ensemble {
+    config: EnsembleConfig,
+    performance_monitor: Option<Arc<PerformanceMonitor>>,
+}
+
+impl Ensemble {
+    pub fn new(config: EnsembleConfig) -> Self {
+        Self { config, performance_monitor: None }
+    }
+
+    /// Record a measurement for every member request
+    pub fn with_performance_monitor(mut self, monitor: Arc<PerformanceMonitor>) -> Self {
+        self.performance_monitor = Some(monitor);
+        self
+    }
+
+    /// Send the request to every configured provider concurrently via `call`
+    /// and aggregate the successful responses
+    pub async fn dispatch<T, F, Fut>(&self, call: F) -> Result<EnsembleResponse<T>, EnsembleError>
+    where
+        T: EnsembleVote + Send + 'static,
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>> + Send + 'static,
+    {
+        if self.config.providers.is_empty() {
+            return Err(EnsembleError::NoProviders);
+        }
+
+        info!(
+            providers = ?self.config.providers,
+            aggregation = ?self.config.aggregation,
+            quorum = self.config.quorum,
+            "Dispatching ensemble request"
+        );
+
+        let mut tasks = JoinSet::new();
+        for provider_name in &self.config.providers {
+            let request = call(provider_name.clone());
+            let provider_name = provider_name.clone();
+            tasks.spawn(async move {
+                let start_time = Instant::now();
+                let result = request.await;
+                (provider_name, start_time, Instant::now(), result)
+            });
+        }
+
+        let required = match self.config.aggregation {
+            EnsembleAggregation::First => 1,
+            _ => self.config.quorum.max(1),
+        };
+        let mut members = Vec::new();
+        let mut responses = Vec::new();
+        let mut errors = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            let (provider_name, start_time, end_time, result) = match joined {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    warn!(panicked = e.is_panic(), "Ensemble member task failed");
+                    errors.push(e.to_string());
+                    continue;
+                }
+            };
+
+            self.record_member(&provider_name, start_time, end_time, result.is_ok())
+                .await;
+
+            match result {
+                Ok(response) => {
+                    // Only the size of the answer is logged, never its content
+                    debug!(
+                        provider = %provider_name,
+                        vote_chars = response.vote().chars().count(),
+                        "Ensemble member responded"
+                    );
+                    members
+                        .push(EnsembleMember { provider_name: provider_name.clone(), error: None });
+                    responses.push((provider_name, response));
+                }
+                Err(e) => {
+                    warn!(
+                        provider = %provider_name,
+                        kind = ?FailureKind::classify(&e),
+                        "Ensemble member failed"
+                    );
+                    let error = format!("{provider_name}: {e}");
+                    members.push(EnsembleMember { provider_name, error: Some(error.clone()) });
+                    errors.push(error);
+                }
+            }
+
+            if self.config.aggregation == EnsembleAggregation::First && !responses.is_empty() {
+                break;
+            }
+        }
+
+        // Dropping the join set cancels members still in flight
+        drop(tasks);
+
+        if responses.len() < required {
+            return Err(EnsembleError::QuorumNotMet {
+                responded: responses.len(),
+                required,
+                errors,
+            });
+        }
+
+        let chosen = aggregate(&responses, self.config.aggregation);
+        let agreement = responses
+            .iter()
+            .filter(|(_, response)| response.vote() == responses[chosen].1.vote())
+            .count();
+        let (provider_name, response) = responses.swap_remove(chosen);
+
+        info!(
+            provider = %provider_name,
+            agreement,
+            responded = members.iter().filter(|member| member.error.is_none()).count(),
+            "Ensemble response aggregated"
+        );
+
+        Ok(EnsembleResponse { response, provider_name, agreement, members })
+    }
+
+    /// Record a member request that ran from `start_time` to `end_time`
+    async fn record_member(
+        &self,
+        provider_name: &str,
+        start_time: Instant,
+        end_time: Instant,
+        success: bool,
+    ) {
+        let Some(monitor) = &self.performance_monitor else {
+            return;
+        };
+
+        let measurement =
+            PerformanceMeasurement::new(provider_name.to_string(), RequestType::Inference);
+        let mut measurement = if success {
+            measurement.complete_success()
+        } else {
+            measurement.complete_failure()
+        };
+        measurement.start_time = start_time;
+        measurement.end_time = end_time;
+        monitor.record_measurement(measurement).await;
+    }
+}
+
+/// Index of the response chosen by `aggregation`. `responses` is in
+/// completion order, which also breaks ties.
+fn aggregate<T: EnsembleVote>(
+    responses: &[(String, T)],
+    aggregation: EnsembleAggregation,
+) -> usize {
+    match aggregation {
+        EnsembleAggregation::First => 0,
+        EnsembleAggregation::Majority => {
+            let votes: Vec<String> = responses
+                .iter()
+                .map(|(_, response)| response.vote())
+                .collect();
+            let mut best = 0;
+            let mut best_count = 0;
+            for (index, vote) in votes.iter().enumerate() {
+                let count = votes.iter().filter(|other| *other == vote).count();
+                if count > best_count {
+                    best = index;
+                    best_count = count;
+                }
+            }
+            best
+        }
+        EnsembleAggregation::BestByScore => {
+            let mut best = 0;
+            for (index, (_, response)) in responses.iter().enumerate() {
+                if response.score() > responses[best].1.score() {
+                    best = index;
+                }
+            }
+            best
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::performance::PerformanceConfig;
+
+    #[derive(Debug, Clone)]
+    struct Scored {
+        answer: String,
+        score: f64,
+    }
+
+    impl EnsembleVote for Scored {
+        fn vote(&self) -> String {
+            self.answer.clone()
+        }
+
+        fn score(&self) -> f64 {
+            self.score
+        }
+    }
+
+    fn fixture(aggregation: EnsembleAggregation) -> Ensemble {
+        Ensemble::new(
+            EnsembleConfig::default()
+                .providers(vec![
+                    "gpu-a".to_string(),
+                    "gpu-b".to_string(),
+                    "gpu-c".to_string(),
+                ])
+                .aggregation(aggregation)
+                .quorum(2usize),
+        )
+    }
+
+    /// Mock providers: gpu-a answers first with the minority answer
+    async fn answer(provider_name: String) -> anyhow::Result<String> {
+        match provider_name.as_str() {
+            "gpu-a" => Ok("Spam".to_string()),
+            "gpu-b" => {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                Ok("not spam".to_string())
+            }
+            _ => {
+                tokio::time::sleep(Duration::from_millis(40)).await;
+                Ok("Not Spam ".to_string())
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_majority_vote_picks_most_common_answer() {
+        let fixture = fixture(EnsembleAggregation::Majority);
+
+        let actual = fixture.dispatch(answer).await.unwrap();
+
+        assert_eq!(actual.response.vote(), "not spam");
+        assert_eq!(actual.agreement, 2);
+        assert_eq!(actual.members.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_majority_vote_tolerates_one_failure() {
+        let monitor = Arc::new(PerformanceMonitor::new(PerformanceConfig::default()));
+        let fixture =
+            fixture(EnsembleAggregation::Majority).with_performance_monitor(monitor.clone());
+
+        let actual = fixture
+            .dispatch(|provider_name| async move {
+                if provider_name == "gpu-a" {
+                    anyhow::bail!("connection refused");
+                }
+                answer(provider_name).await
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(actual.response.vote(), "not spam");
+        assert_eq!(
+            actual
+                .members
+                .iter()
+                .filter(|member| member.error.is_some())
+                .count(),
+            1
+        );
+        let metrics = monitor.get_all_metrics().await;
+        assert_eq!(metrics["gpu-a"].failed_requests, 1);
+        assert_eq!(metrics["gpu-b"].successful_requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_quorum_not_met_returns_error() {
+        let fixture = fixture(EnsembleAggregation::Majority);
+
+        let actual = fixture
+            .dispatch(|provider_name| async move {
+                if provider_name != "gpu-c" {
+                    anyhow::bail!("model not loaded");
+                }
+                answer(provider_name).await
+            })
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            actual,
+            EnsembleError::QuorumNotMet { responded: 1, required: 2, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_first_returns_without_waiting_for_other_members() {
+        let fixture = fixture(EnsembleAggregation::First);
+
+        let actual = tokio::time::timeout(
+            Duration::from_secs(1),
+            fixture.dispatch(|provider_name| async move {
+                match provider_name.as_str() {
+                    "gpu-a" => anyhow::bail!("connection refused"),
+                    "gpu-b" => {
+                        tokio::time::sleep(Duration::from_millis(20)).await;
+                        Ok("not spam".to_string())
+                    }
+                    _ => std::future::pending().await,
+                }
+            }),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(actual.provider_name, "gpu-b");
+        assert_eq!(actual.members.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_first_and_best_by_score_aggregation() {
+        let actual = fixture(EnsembleAggregation::First)
+            .dispatch(answer)
+            .await
+            .unwrap();
+        assert_eq!(actual.provider_name, "gpu-a");
+
+        let actual = fixture(EnsembleAggregation::BestByScore)
+            .dispatch(|provider_name| async move {
+                let score = if provider_name == "gpu-b" { 0.9 } else { 0.4 };
+                Ok(Scored { answer: provider_name, score })
+            })
+            .await
+            .unwrap();
+        assert_eq!(actual.provider_name, "gpu-b");
+    }
+}
@@ -24,6 +24,14 @@ pub enum Error {
 
     #[error("Invalid Status Code: {0}")]
     InvalidStatusCode(u16),
+
+    #[error("Request cancelled")]
+    Cancelled,
+
+    /// The response was well-formed but shaped differently than expected,
+    /// usually because the server runs an incompatible API version
+    #[error("Protocol mismatch with {provider}: {detail}")]
+    ProtocolMismatch { provider: String, detail: String },
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
@@ -1,3 +1,6 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
 use anyhow::{Context as _, Result};
 use derive_builder::Builder;
 use forge_app::domain::{
@@ -7,13 +10,14 @@ use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
 use reqwest::{Client, Url};
 use reqwest_eventsource::{Event, RequestBuilderExt};
 use tokio_stream::StreamExt;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use super::model::{ListModelResponse, Model};
 use super::request::Request;
 use super::response::Response;
 use crate::error::Error;
 use crate::forge_provider::transformers::{ProviderPipeline, Transformer};
+use crate::idempotency::{IdempotencyKey, IDEMPOTENCY_KEY_HEADER};
 use crate::utils::{format_http_context, sanitize_headers};
 
 #[derive(Clone, Builder)]
@@ -21,6 +25,8 @@ pub struct ForgeProvider {
     client: Client,
     provider: Provider,
     version: String,
+    #[builder(default)]
+    protocol_mismatches: Arc<AtomicU64>,
 }
 
 impl ForgeProvider {
@@ -28,6 +34,11 @@ impl ForgeProvider {
         ForgeProviderBuilder::default()
     }
 
+    /// Number of responses that did not match the expected protocol
+    pub fn protocol_mismatch_count(&self) -> u64 {
+        self.protocol_mismatches.load(Ordering::Relaxed)
+    }
+
     fn url(&self, path: &str) -> anyhow::Result<Url> {
         // Validate the path doesn't contain certain patterns
         if path.contains("://") || path.contains("..") {
@@ -79,13 +90,18 @@ impl ForgeProvider {
         &self,
         model: &ModelId,
         context: ChatContext,
+        idempotency_key: &IdempotencyKey,
     ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
         let mut request = Request::from(context).model(model.clone()).stream(true);
         let mut pipeline = ProviderPipeline::new(&self.provider);
         request = pipeline.transform(request);
 
         let url = self.url("chat/completions")?;
-        let headers = self.headers();
+        let mut headers = self.headers();
+
+        if let Ok(value) = HeaderValue::from_str(idempotency_key.as_str()) {
+            headers.insert(IDEMPOTENCY_KEY_HEADER, value);
+        }
 
         info!(
             url = %url,
@@ -104,6 +120,8 @@ impl ForgeProvider {
             .eventsource()
             .with_context(|| format_http_context(None, "POST", &url))?;
 
+        let protocol_mismatches = self.protocol_mismatches.clone();
+        let provider = self.provider.clone();
         let stream = es
             .take_while(|message| !matches!(message, Err(reqwest_eventsource::Error::StreamEnded)))
             .then(|event| async {
@@ -163,15 +181,20 @@ impl ForgeProvider {
                 }
             })
             .filter_map(move |response| {
-                response
-                    .map(|result| result.with_context(|| format_http_context(None, "POST", &url)))
+                response.map(|result| {
+                    result
+                        .map_err(|error| {
+                            detect_protocol_mismatch(&protocol_mismatches, &provider, error)
+                        })
+                        .with_context(|| format_http_context(None, "POST", &url))
+                })
             });
 
         Ok(Box::pin(stream))
     }
 
-    async fn inner_models(&self) -> Result<Vec<forge_app::domain::Model>> {
-        let url = self.url("models")?;
+    async fn inner_models(&self, path: &str) -> Result<Vec<forge_app::domain::Model>> {
+        let url = self.url(path)?;
         debug!(url = %url, "Fetching models");
         match self.fetch_models(url.clone()).await {
             Err(error) => {
@@ -180,6 +203,13 @@ impl ForgeProvider {
             }
             Ok(response) => {
                 let data: ListModelResponse = serde_json::from_str(&response)
+                    .map_err(|error| {
+                        detect_protocol_mismatch(
+                            &self.protocol_mismatches,
+                            &self.provider,
+                            error.into(),
+                        )
+                    })
                     .with_context(|| format_http_context(None, "GET", &url))
                     .with_context(|| "Failed to deserialize models response")?;
                 Ok(data.data.into_iter().map(Into::into).collect())
@@ -219,16 +249,26 @@ impl ForgeProvider {
 }
 
 impl ForgeProvider {
+    /// Send a chat request carrying `idempotency_key`. Retries of the same
+    /// logical request must reuse its key so the provider can deduplicate a
+    /// request that already succeeded upstream.
     pub async fn chat(
         &self,
         model: &ModelId,
         context: ChatContext,
+        idempotency_key: &IdempotencyKey,
     ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
-        self.inner_chat(model, context).await
+        self.inner_chat(model, context, idempotency_key).await
     }
 
     pub async fn models(&self) -> Result<Vec<forge_app::domain::Model>> {
-        self.inner_models().await
+        self.inner_models("models").await
+    }
+
+    /// List models from a server whose models endpoint is at `path` instead
+    /// of the default `models`
+    pub async fn models_at(&self, path: &str) -> Result<Vec<forge_app::domain::Model>> {
+        self.inner_models(path).await
     }
 }
 
@@ -262,13 +302,36 @@ impl From<Model> for forge_app::domain::Model {
     }
 }
 
+/// Report a response that failed to deserialize despite being valid JSON (a
+/// moved field or changed type) as a protocol mismatch, counting it. Other
+/// errors are returned unchanged.
+fn detect_protocol_mismatch(
+    protocol_mismatches: &AtomicU64,
+    provider: &Provider,
+    error: anyhow::Error,
+) -> anyhow::Error {
+    let Some(parse_error) = error
+        .downcast_ref::<serde_json::Error>()
+        .filter(|parse_error| parse_error.classify() == serde_json::error::Category::Data)
+    else {
+        return error;
+    };
+    protocol_mismatches.fetch_add(1, Ordering::Relaxed);
+    let provider = provider.to_base_url().to_string();
+    let detail = parse_error.to_string();
+    // The detail can quote response content, so it is not logged
+    warn!(provider = %provider, "Response does not match the expected protocol");
+    let message = error.to_string();
+    anyhow::Error::from(Error::ProtocolMismatch { provider, detail }).context(message)
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Context;
     use reqwest::Client;
 
     use super::*;
-    use crate::mock_server::{normalize_ports, MockServer};
+    use crate::mock_server::{normalize_ports, MockOllamaServer, MockServer, ScriptedResponse};
 
     fn create_provider(base_url: &str) -> anyhow::Result<ForgeProvider> {
         let provider = Provider::OpenAI {
@@ -381,6 +444,79 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_chat_retries_share_idempotency_key() -> anyhow::Result<()> {
+        let mut fixture = MockServer::new().await;
+        let model = ModelId::new("gpt-4o");
+        let provider = create_provider(&fixture.url())?;
+        let expected = IdempotencyKey::generate();
+        let mock = fixture
+            .mock_chat_completions(IDEMPOTENCY_KEY_HEADER, expected.as_str(), 2)
+            .await;
+
+        // Simulate a retry of the same logical request
+        for _ in 0..2 {
+            let actual: Vec<_> = provider
+                .chat(&model, ChatContext::default(), &expected)
+                .await?
+                .collect()
+                .await;
+            assert!(actual.is_empty());
+        }
+
+        mock.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_models_protocol_mismatch_is_counted() -> anyhow::Result<()> {
+        let mut fixture = MockServer::new().await;
+        // A newer protocol that nests the list under `models`
+        let _mock = fixture
+            .mock_models(serde_json::json!({"data": {"models": []}}), 200)
+            .await;
+        let provider = create_provider(&fixture.url())?;
+
+        let actual = provider.models().await.unwrap_err();
+
+        let error = actual.downcast_ref::<Error>();
+        assert!(matches!(error, Some(Error::ProtocolMismatch { .. })));
+        assert_eq!(provider.protocol_mismatch_count(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chat_protocol_mismatch_is_counted() -> anyhow::Result<()> {
+        let server = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/chat/completions",
+                // An older protocol where `choices` was a single object
+                ScriptedResponse::sse(vec![serde_json::json!({
+                    "id": "chatcmpl-1",
+                    "choices": {"delta": {"content": "Hi"}}
+                })]),
+            )
+            .start()
+            .await;
+        let provider = create_provider(&server.url())?;
+
+        let actual: Vec<_> = provider
+            .chat(
+                &ModelId::new("gpt-4o"),
+                ChatContext::default(),
+                &IdempotencyKey::generate(),
+            )
+            .await?
+            .collect()
+            .await;
+
+        let error = actual[0].as_ref().unwrap_err().downcast_ref::<Error>();
+        assert!(matches!(error, Some(Error::ProtocolMismatch { .. })));
+        assert_eq!(provider.protocol_mismatch_count(), 1);
+        Ok(())
+    }
+
     #[test]
     fn test_error_deserialization() -> Result<()> {
         let content = serde_json::to_string(&serde_json::json!({
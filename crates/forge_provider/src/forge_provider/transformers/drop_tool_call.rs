@@ -74,6 +74,7 @@ mod tests {
             top_p: None,
             top_k: None,
             reasoning: None,
+            idempotency_key: None,
         };
 
         let request = Request::from(context);
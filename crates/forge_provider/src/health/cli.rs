@@ -0,0 +1,103 @@
+//! CLI integration for provider health monitoring
+
+use std::sync::Arc;
+
+use tracing::info;
+
+use super::{HealthMonitor, HealthSnapshot};
+
+/// Health CLI handler reporting on a running [`HealthMonitor`]
+pub struct HealthCli {
+    monitor: Arc<HealthMonitor>,
+}
+
+/// Health command variants
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthCommand {
+    /// Show the status of every provider
+    Status,
+    /// Dump the full health state of every provider as JSON
+    Dump,
+}
+
+/// Health CLI output
+#[derive(Debug, Clone)]
+pub struct HealthOutput {
+    pub command: HealthCommand,
+    pub message: String,
+    pub snapshot: HealthSnapshot,
+}
+
+impl HealthCli {
+    /// Create a health CLI handler for `monitor`
+    pub fn new(monitor: Arc<HealthMonitor>) -> Self {
+        Self { monitor }
+    }
+
+    /// Execute a health command
+    pub async fn execute_command(&self, command: HealthCommand) -> anyhow::Result<HealthOutput> {
+        info!(?command, "Executing health command");
+
+        let snapshot = self.monitor.export_snapshot().await;
+        let message = match command {
+            HealthCommand::Status => {
+                let mut message = String::from("Provider Health Status:\n");
+                for (name, provider) in &snapshot.providers {
+                    message.push_str(&format!(
+                        "  {}: {} ({:?}, checked {}s ago)\n",
+                        name,
+                        provider.status.label(),
+                        provider.status.response_time(),
+                        provider.last_checked_age.as_secs()
+                    ));
+                }
+                message
+            }
+            HealthCommand::Dump => snapshot.to_json()?,
+        };
+
+        Ok(HealthOutput { command, message, snapshot })
+    }
+}
+
+/// Parse health command from CLI input
+pub fn parse_health_command(input: &str) -> anyhow::Result<HealthCommand> {
+    let parts: Vec<&str> = input.split_whitespace().collect();
+
+    match parts.first() {
+        None | Some(&"status") => Ok(HealthCommand::Status),
+        Some(&"dump") => Ok(HealthCommand::Dump),
+        Some(other) => anyhow::bail!("Unknown health command: {other}"),
+    }
+}
+
+/// Format health command output for display
+pub fn format_health_output(output: &HealthOutput) -> String {
+    match output.command {
+        HealthCommand::Dump => output.message.clone(),
+        HealthCommand::Status => format!("✅ Health Command: Status\n\n{}", output.message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_health_command() {
+        let actual = ["", "status", "dump"]
+            .into_iter()
+            .map(|input| parse_health_command(input).unwrap())
+            .collect::<Vec<_>>();
+
+        let expected = vec![
+            HealthCommand::Status,
+            HealthCommand::Status,
+            HealthCommand::Dump,
+        ];
+        assert_eq!(actual, expected);
+        assert!(parse_health_command("bogus").is_err());
+    }
+}
@@ -5,16 +5,63 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use anyhow::Context as _;
+use futures::stream::{self, StreamExt};
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 
-use crate::config::local_ai::{LocalAiConfig, ProviderHealthChecker, ProviderHealthStatus};
+use crate::config::local_ai::{
+    HealthCheckConfig, LocalAiConfig, ProviderHealthChecker, ProviderHealthStatus, ServerLoad,
+};
+use crate::performance::AdmissionController;
+use crate::retry::{random_seed, splitmix64};
+
+mod cli;
+mod registry;
+mod snapshot;
+
+pub use cli::{format_health_output, parse_health_command, HealthCli, HealthCommand, HealthOutput};
+pub use registry::{HealthCheckerConstructor, HealthCheckerFactory};
+pub use snapshot::{HealthCheckSnapshot, HealthSnapshot, ProviderHealthSnapshot};
+
+/// Number of providers checked at once during the initial health check pass
+const INITIAL_CHECK_CONCURRENCY: usize = 8;
 
 /// Health monitoring service for local AI providers
 pub struct HealthMonitor {
     config: LocalAiConfig,
     health_status: Arc<RwLock<HashMap<String, ProviderHealthInfo>>>,
-    checkers: HashMap<String, Box<dyn ProviderHealthChecker>>,
+    checkers: HashMap<String, Arc<dyn ProviderHealthChecker>>,
+    monitoring_tasks: std::sync::Mutex<HashMap<String, JoinHandle<()>>>,
+    /// Seed for the per-provider interval jitter sequences
+    jitter_seed: u64,
+    /// Controller whose provider capacities follow the load servers report
+    /// on each check
+    admission: Option<Arc<AdmissionController>>,
+}
+
+/// Seedable source of the random offsets applied to one provider's check
+/// intervals
+#[derive(Debug, Clone)]
+struct IntervalJitter {
+    state: u64,
+}
+
+impl IntervalJitter {
+    /// Start a sequence for `provider_name`, so providers sharing a seed
+    /// still drift apart
+    fn new(seed: u64, provider_name: &str) -> Self {
+        let state = provider_name
+            .bytes()
+            .fold(seed, |state, byte| splitmix64(state ^ u64::from(byte)));
+        Self { state }
+    }
+
+    /// `interval` with the next jitter offset applied
+    fn next_delay(&mut self, interval: Duration, health_check: &HealthCheckConfig) -> Duration {
+        self.state = splitmix64(self.state);
+        health_check.jittered_interval(interval, self.state)
+    }
 }
 
 /// Health information for a provider
@@ -32,6 +79,19 @@ pub struct ProviderHealthInfo {
     pub avg_response_time: Duration,
     /// Check history (last 10 results)
     pub check_history: Vec<HealthCheckResult>,
+    /// Load metrics reported by the server on the last check, if any
+    pub server_load: Option<ServerLoad>,
+    /// Models the provider had loaded into memory on the last check, if it
+    /// reports them
+    pub loaded_models: Option<Vec<String>>,
+    /// Delay before the next periodic check, backed off while the provider
+    /// keeps failing
+    pub current_interval: Duration,
+    /// Whether the provider failed a check and has not yet passed
+    /// `health_config.success_threshold` consecutive checks since
+    pub recovering: bool,
+    /// Health check settings the provider is checked with
+    pub health_config: HealthCheckConfig,
 }
 
 /// Result of a health check
@@ -77,16 +137,24 @@ pub enum HealthEvent {
 impl HealthMonitor {
     /// Create a new health monitor
     pub async fn new(config: LocalAiConfig) -> anyhow::Result<Self> {
+        Self::new_with_factory(config, &HealthCheckerFactory::default()).await
+    }
+
+    /// Create a new health monitor whose checkers are built by `factory`
+    pub async fn new_with_factory(
+        config: LocalAiConfig,
+        factory: &HealthCheckerFactory,
+    ) -> anyhow::Result<Self> {
         debug!("Creating HealthMonitor with config: {:?}", config);
         let mut checkers = HashMap::new();
 
         // Create health checkers for enabled providers
         for (name, provider_config) in config.enabled_providers() {
             debug!("Creating health checker for provider: {}", name);
-            match provider_config.create_health_checker() {
+            match factory.create(provider_config) {
                 Ok(checker) => {
                     debug!("Successfully created health checker for provider: {}", name);
-                    checkers.insert(name.clone(), checker);
+                    checkers.insert(name.clone(), Arc::from(checker));
                 }
                 Err(e) => {
                     error!(
@@ -110,6 +178,9 @@ impl HealthMonitor {
             config,
             health_status: Arc::new(RwLock::new(HashMap::new())),
             checkers,
+            monitoring_tasks: std::sync::Mutex::new(HashMap::new()),
+            jitter_seed: random_seed(),
+            admission: None,
         })
     }
 
@@ -120,9 +191,36 @@ impl HealthMonitor {
             config,
             health_status: Arc::new(RwLock::new(HashMap::new())),
             checkers: HashMap::new(),
+            monitoring_tasks: std::sync::Mutex::new(HashMap::new()),
+            jitter_seed: random_seed(),
+            admission: None,
         }
     }
 
+    /// Check `provider_name` with `checker` instead of the checker created
+    /// from its configuration
+    pub fn with_health_checker(
+        mut self,
+        provider_name: impl Into<String>,
+        checker: Arc<dyn ProviderHealthChecker>,
+    ) -> Self {
+        self.checkers.insert(provider_name.into(), checker);
+        self
+    }
+
+    /// Seed the interval jitter with `seed` instead of the clock, making the
+    /// check schedule reproducible
+    pub fn with_jitter_seed(mut self, seed: u64) -> Self {
+        self.jitter_seed = seed;
+        self
+    }
+
+    /// Adapt each provider's capacity in `controller` to the load its server
+    /// reports, after every health check that reports one
+    pub fn set_admission(&mut self, controller: Arc<AdmissionController>) {
+        self.admission = Some(controller);
+    }
+
     /// Start the health monitoring service
     pub async fn start(&self) -> anyhow::Result<()> {
         info!(
@@ -131,7 +229,7 @@ impl HealthMonitor {
         );
 
         // Perform initial health checks
-        self.perform_initial_checks().await?;
+        self.perform_initial_checks().await;
 
         // Start periodic health checks for each provider
         for provider_name in self.checkers.keys() {
@@ -141,51 +239,33 @@ impl HealthMonitor {
         Ok(())
     }
 
-    /// Perform initial health checks for all providers
-    async fn perform_initial_checks(&self) -> anyhow::Result<()> {
+    /// Perform initial health checks for all providers concurrently, recording
+    /// each result as soon as its check completes. Each provider's entry is
+    /// written independently, so completion order does not matter. A failed
+    /// or timed out check is recorded as an unhealthy status.
+    async fn perform_initial_checks(&self) {
         info!("Performing initial health checks");
 
-        for (provider_name, checker) in &self.checkers {
-            match self.check_provider_health(provider_name, checker).await {
-                Ok(info) => {
-                    let mut status = self.health_status.write().await;
-                    status.insert(provider_name.clone(), info);
-                    info!(
-                        "Initial health check completed for {}: {:?}",
-                        provider_name,
-                        status
-                            .get(provider_name)
-                            .map(|info| &info.status)
-                            .unwrap_or(&ProviderHealthStatus::Unhealthy {
-                                reason: "Status not available".to_string(),
-                                response_time: Duration::from_millis(0),
-                            })
-                    );
-                }
-                Err(e) => {
-                    error!("Initial health check failed for {}: {}", provider_name, e);
-                    // Insert unhealthy status
-                    let unhealthy_info = ProviderHealthInfo {
-                        status: ProviderHealthStatus::Unhealthy {
-                            reason: format!("Initial check failed: {e}"),
-                            response_time: Duration::from_millis(0),
-                        },
-                        last_checked: Instant::now(),
-                        consecutive_failures: 1,
-                        consecutive_successes: 0,
-                        avg_response_time: Duration::from_millis(0),
-                        check_history: vec![],
-                    };
-                    let mut status = self.health_status.write().await;
-                    status.insert(provider_name.clone(), unhealthy_info);
-                }
-            }
+        let mut checks = stream::iter(&self.checkers)
+            .map(|(provider_name, checker)| async move {
+                (
+                    provider_name,
+                    self.check_provider_health(provider_name, checker).await,
+                )
+            })
+            .buffer_unordered(INITIAL_CHECK_CONCURRENCY);
+        while let Some((provider_name, info)) = checks.next().await {
+            info!(
+                "Initial health check completed for {}: {:?}",
+                provider_name, info.status
+            );
+            let mut status = self.health_status.write().await;
+            status.insert(provider_name.clone(), info);
         }
-
-        Ok(())
     }
 
-    /// Start monitoring for a specific provider
+    /// Spawn a task that re-checks a provider every health check interval,
+    /// replacing any task already monitoring it
     async fn start_provider_monitoring(&self, provider_name: String) {
         let provider_config = match self.config.providers.get(&provider_name) {
             Some(config) => config,
@@ -195,148 +275,138 @@ impl HealthMonitor {
             }
         };
 
-        let interval_duration = provider_config.health_check.interval_duration();
-        let _health_status = Arc::clone(&self.health_status);
-        let _checker = match self.checkers.get(&provider_name) {
-            Some(checker) => checker,
+        let health_check = provider_config.health_check.clone();
+        let interval_duration = health_check.interval_duration();
+        let health_status = Arc::clone(&self.health_status);
+        let checker = match self.checkers.get(&provider_name) {
+            Some(checker) => Arc::clone(checker),
             None => {
                 error!("Health checker not found for provider: {}", provider_name);
                 return;
             }
         };
 
-        let provider_name_clone = provider_name.clone();
+        let mut jitter = IntervalJitter::new(self.jitter_seed, &provider_name);
+        let admission = self.admission.clone();
+        let task_provider_name = provider_name.clone();
+        let task = tokio::spawn(async move {
+            // The initial check already ran, so start by waiting for the next
+            loop {
+                let interval = health_status
+                    .read()
+                    .await
+                    .get(&task_provider_name)
+                    .map_or(interval_duration, |info| info.current_interval);
+                tokio::time::sleep(jitter.next_delay(interval, &health_check)).await;
+
+                let info = check_provider(
+                    &task_provider_name,
+                    checker.as_ref(),
+                    &health_check,
+                    &health_status,
+                    admission.as_deref(),
+                )
+                .await;
+                if info.current_interval > interval_duration {
+                    debug!(
+                        provider = %task_provider_name,
+                        consecutive_failures = info.consecutive_failures,
+                        next_check_ms = info.current_interval.as_millis() as u64,
+                        "Backing off health checks"
+                    );
+                }
+                health_status
+                    .write()
+                    .await
+                    .insert(task_provider_name.clone(), info);
+            }
+        });
 
-        // Note: In a real implementation, we would spawn this as a background task
-        // For now, we'll just log that monitoring would start
+        let previous = self
+            .monitoring_tasks
+            .lock()
+            .unwrap()
+            .insert(provider_name.clone(), task);
+        if let Some(previous) = previous {
+            previous.abort();
+        }
         info!(
-            "Would start health monitoring for {} with interval {:?}",
-            provider_name_clone, interval_duration
+            provider = %provider_name,
+            interval_ms = interval_duration.as_millis() as u64,
+            "Started health monitoring"
         );
     }
 
+    /// Stop all periodic health checks and wait for their tasks to finish
+    pub async fn stop(&self) {
+        let tasks: Vec<_> = self.monitoring_tasks.lock().unwrap().drain().collect();
+        for (provider_name, task) in tasks {
+            task.abort();
+            let _ = task.await;
+            debug!(provider = %provider_name, "Stopped health monitoring");
+        }
+    }
+
+    /// Providers with a running periodic health check
+    pub fn monitored_providers(&self) -> Vec<String> {
+        let tasks = self.monitoring_tasks.lock().unwrap();
+        let mut providers: Vec<_> = tasks
+            .iter()
+            .filter(|(_, task)| !task.is_finished())
+            .map(|(name, _)| name.clone())
+            .collect();
+        providers.sort();
+        providers
+    }
+
     /// Check health of a specific provider
     async fn check_provider_health(
         &self,
         provider_name: &str,
-        checker: &Box<dyn ProviderHealthChecker>,
-    ) -> anyhow::Result<ProviderHealthInfo> {
-        let start_time = Instant::now();
-
-        debug!("Checking health for provider: {}", provider_name);
-
-        match checker.check_health().await {
-            Ok(status) => {
-                let response_time = start_time.elapsed();
-                let check_result = HealthCheckResult {
-                    timestamp: start_time,
-                    success: status.is_usable(),
-                    response_time,
-                    error: None,
-                };
-
-                // Get current info or create new
-                let current_info = {
-                    let health_status = self.health_status.read().await;
-                    health_status.get(provider_name).cloned()
-                };
-
-                let info = self.update_health_info(current_info, status, check_result);
-
-                debug!(
-                    "Health check completed for {}: {:?} ({}ms)",
-                    provider_name,
-                    info.status,
-                    response_time.as_millis()
-                );
-
-                Ok(info)
-            }
-            Err(e) => {
-                let response_time = start_time.elapsed();
-                let error_msg = format!("Health check failed: {e}");
-
-                let check_result = HealthCheckResult {
-                    timestamp: start_time,
-                    success: false,
-                    response_time,
-                    error: Some(error_msg.clone()),
-                };
-
-                let unhealthy_status =
-                    ProviderHealthStatus::Unhealthy { reason: error_msg, response_time };
-
-                let current_info = {
-                    let health_status = self.health_status.read().await;
-                    health_status.get(provider_name).cloned()
-                };
-
-                let info = self.update_health_info(current_info, unhealthy_status, check_result);
-
-                warn!(
-                    "Health check failed for {}: {} ({}ms)",
-                    provider_name,
-                    e,
-                    response_time.as_millis()
-                );
-
-                Ok(info)
-            }
-        }
+        checker: &Arc<dyn ProviderHealthChecker>,
+    ) -> ProviderHealthInfo {
+        let health_check = self.health_check_config(provider_name);
+        check_provider(
+            provider_name,
+            checker.as_ref(),
+            &health_check,
+            &self.health_status,
+            self.admission.as_deref(),
+        )
+        .await
     }
 
-    /// Update health information with new check result
-    fn update_health_info(
-        &self,
-        current_info: Option<ProviderHealthInfo>,
-        new_status: ProviderHealthStatus,
-        check_result: HealthCheckResult,
-    ) -> ProviderHealthInfo {
-        let now = Instant::now();
-
-        match current_info {
-            Some(mut info) => {
-                // Update status
-                info.status = new_status.clone();
-                info.last_checked = now;
-
-                // Update failure/success counters
-                if check_result.success {
-                    info.consecutive_successes += 1;
-                    info.consecutive_failures = 0;
-                } else {
-                    info.consecutive_failures += 1;
-                    info.consecutive_successes = 0;
-                }
+    fn health_check_config(&self, provider_name: &str) -> HealthCheckConfig {
+        self.config
+            .providers
+            .get(provider_name)
+            .map(|config| config.health_check.clone())
+            .unwrap_or_default()
+    }
 
-                // Update check history (keep last 10)
-                info.check_history.push(check_result);
-                if info.check_history.len() > 10 {
-                    info.check_history.remove(0);
-                }
+    /// Delay before the periodic task next checks `provider_name`, including
+    /// any backoff applied while the provider is failing
+    pub async fn next_check_delay(&self, provider_name: &str) -> Option<Duration> {
+        let health_status = self.health_status.read().await;
+        health_status
+            .get(provider_name)
+            .map(|info| info.current_interval)
+    }
 
-                // Update average response time
-                let total_time: Duration = info
-                    .check_history
-                    .iter()
-                    .map(|result| result.response_time)
-                    .sum();
-                info.avg_response_time = total_time / info.check_history.len() as u32;
+    /// Get the load metrics the provider reported on its last health check
+    pub async fn get_server_load(&self, provider_name: &str) -> Option<ServerLoad> {
+        let health_status = self.health_status.read().await;
+        health_status
+            .get(provider_name)
+            .and_then(|info| info.server_load.clone())
+    }
 
-                info
-            }
-            None => {
-                // Create new info
-                ProviderHealthInfo {
-                    status: new_status,
-                    last_checked: now,
-                    consecutive_failures: if check_result.success { 0 } else { 1 },
-                    consecutive_successes: if check_result.success { 1 } else { 0 },
-                    avg_response_time: check_result.response_time,
-                    check_history: vec![check_result],
-                }
-            }
-        }
+    /// Get the models the provider had loaded on its last health check
+    pub async fn get_loaded_models(&self, provider_name: &str) -> Option<Vec<String>> {
+        let health_status = self.health_status.read().await;
+        health_status
+            .get(provider_name)
+            .and_then(|info| info.loaded_models.clone())
     }
 
     /// Get current health status for all providers
@@ -344,7 +414,7 @@ impl HealthMonitor {
         let health_status = self.health_status.read().await;
         health_status
             .iter()
-            .map(|(name, info)| (name.clone(), info.status.clone()))
+            .map(|(name, info)| (name.clone(), info.effective_status()))
             .collect()
     }
 
@@ -359,7 +429,7 @@ impl HealthMonitor {
         let health_status = self.health_status.read().await;
         health_status
             .get(provider_name)
-            .map(|info| info.status.clone())
+            .map(|info| info.effective_status())
     }
 
     /// Check if a provider is healthy
@@ -387,7 +457,7 @@ impl HealthMonitor {
             .get(provider_name)
             .with_context(|| format!("No health checker found for provider: {provider_name}"))?;
 
-        let info = self.check_provider_health(provider_name, checker).await?;
+        let info = self.check_provider_health(provider_name, checker).await;
 
         // Update stored status
         {
@@ -423,13 +493,37 @@ impl HealthMonitor {
         Ok(results)
     }
 
+    /// Seed the stored status for a provider without running its checker
+    #[cfg(test)]
+    pub(crate) async fn set_provider_status(
+        &self,
+        provider_name: &str,
+        status: ProviderHealthStatus,
+    ) {
+        let check_result = HealthCheckResult {
+            timestamp: Instant::now(),
+            success: status.is_usable(),
+            response_time: status.response_time(),
+            error: None,
+        };
+        let mut health_status = self.health_status.write().await;
+        let current = health_status.remove(provider_name);
+        let info = update_health_info(
+            current,
+            status,
+            check_result,
+            &self.health_check_config(provider_name),
+        );
+        health_status.insert(provider_name.to_string(), info);
+    }
+
     /// Get providers sorted by health (healthy first, then degraded, then
     /// unhealthy)
     pub async fn get_providers_by_health(&self) -> Vec<(String, ProviderHealthStatus)> {
         let health_status = self.health_status.read().await;
         let mut providers: Vec<_> = health_status
             .iter()
-            .map(|(name, info)| (name.clone(), info.status.clone()))
+            .map(|(name, info)| (name.clone(), info.effective_status()))
             .collect();
 
         // Sort by health status priority
@@ -451,7 +545,224 @@ impl HealthMonitor {
     }
 }
 
+/// Run `checker` for `provider_name` and fold the result into the provider's
+/// stored health information, applying any load the server reports to
+/// `admission`. A check that outlasts the configured timeout counts as a
+/// failure.
+async fn check_provider(
+    provider_name: &str,
+    checker: &dyn ProviderHealthChecker,
+    health_check: &HealthCheckConfig,
+    health_status: &RwLock<HashMap<String, ProviderHealthInfo>>,
+    admission: Option<&AdmissionController>,
+) -> ProviderHealthInfo {
+    let start_time = Instant::now();
+
+    debug!("Checking health for provider: {}", provider_name);
+
+    let timeout = health_check.timeout_duration();
+    let result = tokio::time::timeout(timeout, checker.check_health_with_load())
+        .await
+        .unwrap_or_else(|_| {
+            Err(anyhow::anyhow!(
+                "no response within {}ms timeout",
+                timeout.as_millis()
+            ))
+        });
+
+    match result {
+        Ok((status, server_load)) => {
+            let response_time = start_time.elapsed();
+            let status = health_check.apply_degraded_threshold(status);
+            let check_result = HealthCheckResult {
+                timestamp: start_time,
+                success: status.is_usable(),
+                response_time,
+                error: None,
+            };
+
+            // Get current info or create new
+            let current_info = {
+                let health_status = health_status.read().await;
+                health_status.get(provider_name).cloned()
+            };
+
+            let loaded_models = if status.is_usable() {
+                read_loaded_models(provider_name, checker, timeout).await
+            } else {
+                None
+            };
+            if let (Some(admission), Some(load)) = (admission, &server_load) {
+                admission.apply_server_load(provider_name, load);
+            }
+            let mut info = update_health_info(current_info, status, check_result, health_check);
+            info.server_load = server_load;
+            info.loaded_models = loaded_models;
+
+            debug!(
+                "Health check completed for {}: {:?} ({}ms)",
+                provider_name,
+                info.status,
+                response_time.as_millis()
+            );
+
+            info
+        }
+        Err(e) => {
+            let response_time = start_time.elapsed();
+            let error_msg = format!("Health check failed: {e}");
+
+            let check_result = HealthCheckResult {
+                timestamp: start_time,
+                success: false,
+                response_time,
+                error: Some(error_msg.clone()),
+            };
+
+            let unhealthy_status =
+                ProviderHealthStatus::Unhealthy { reason: error_msg, response_time };
+
+            let current_info = {
+                let health_status = health_status.read().await;
+                health_status.get(provider_name).cloned()
+            };
+
+            let mut info =
+                update_health_info(current_info, unhealthy_status, check_result, health_check);
+            // Load reported before the failure is no longer meaningful
+            info.server_load = None;
+            info.loaded_models = None;
+
+            warn!(
+                "Health check failed for {}: {} ({}ms)",
+                provider_name,
+                e,
+                response_time.as_millis()
+            );
+
+            info
+        }
+    }
+}
+
+/// Models `checker` reports as loaded, or `None` when it cannot tell or the
+/// request fails
+async fn read_loaded_models(
+    provider_name: &str,
+    checker: &dyn ProviderHealthChecker,
+    timeout: Duration,
+) -> Option<Vec<String>> {
+    match tokio::time::timeout(timeout, checker.loaded_models()).await {
+        Ok(Ok(models)) => models,
+        Ok(Err(e)) => {
+            debug!(provider = %provider_name, error = %e, "Failed to read loaded models");
+            None
+        }
+        Err(_) => {
+            debug!(provider = %provider_name, "Timed out reading loaded models");
+            None
+        }
+    }
+}
+
+/// Update health information with new check result, backing off the check
+/// interval while the provider keeps failing
+fn update_health_info(
+    current_info: Option<ProviderHealthInfo>,
+    new_status: ProviderHealthStatus,
+    check_result: HealthCheckResult,
+    health_check: &HealthCheckConfig,
+) -> ProviderHealthInfo {
+    let now = Instant::now();
+
+    match current_info {
+        Some(mut info) => {
+            // Update status
+            info.status = new_status.clone();
+            info.last_checked = now;
+
+            // Update failure/success counters
+            if check_result.success {
+                info.consecutive_successes += 1;
+                info.consecutive_failures = 0;
+                if info.consecutive_successes >= health_check.success_threshold {
+                    info.recovering = false;
+                }
+            } else {
+                info.consecutive_failures += 1;
+                info.consecutive_successes = 0;
+                info.recovering = true;
+            }
+
+            // Update check history (keep last 10)
+            info.check_history.push(check_result);
+            if info.check_history.len() > 10 {
+                info.check_history.remove(0);
+            }
+
+            // Update average response time
+            let total_time: Duration = info
+                .check_history
+                .iter()
+                .map(|result| result.response_time)
+                .sum();
+            info.avg_response_time = total_time / info.check_history.len() as u32;
+            info.current_interval = health_check.backoff_interval(info.consecutive_failures);
+            info.health_config = health_check.clone();
+
+            info
+        }
+        None => {
+            // Create new info
+            let check_result_success = check_result.success;
+            let consecutive_failures = if check_result_success { 0 } else { 1 };
+            ProviderHealthInfo {
+                status: new_status,
+                last_checked: now,
+                consecutive_failures,
+                consecutive_successes: if check_result.success { 1 } else { 0 },
+                avg_response_time: check_result.response_time,
+                check_history: vec![check_result],
+                server_load: None,
+                loaded_models: None,
+                current_interval: health_check.backoff_interval(consecutive_failures),
+                recovering: !check_result_success,
+                health_config: health_check.clone(),
+            }
+        }
+    }
+}
+
+impl Drop for HealthMonitor {
+    fn drop(&mut self) {
+        for task in self.monitoring_tasks.get_mut().unwrap().values() {
+            task.abort();
+        }
+    }
+}
+
 impl ProviderHealthInfo {
+    /// Status as seen by provider selection: a recovering provider whose
+    /// checks pass is reported as degraded until it has passed
+    /// `success_threshold` consecutive checks
+    pub fn effective_status(&self) -> ProviderHealthStatus {
+        match &self.status {
+            ProviderHealthStatus::Healthy { response_time, models_available, .. }
+                if self.recovering =>
+            {
+                ProviderHealthStatus::Degraded {
+                    reason: format!(
+                        "Recovering: {} of {} consecutive successful checks",
+                        self.consecutive_successes, self.health_config.success_threshold
+                    ),
+                    response_time: *response_time,
+                    models_available: *models_available,
+                }
+            }
+            status => status.clone(),
+        }
+    }
+
     /// Check if the provider has been consistently failing
     pub fn is_consistently_failing(&self, threshold: u32) -> bool {
         self.consecutive_failures >= threshold
@@ -485,12 +796,39 @@ impl ProviderHealthInfo {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
     use std::time::Duration;
 
     use pretty_assertions::assert_eq;
 
     use super::*;
-    use crate::config::local_ai::LocalAiConfig;
+    use crate::config::local_ai::{HealthCheckConfig, LocalAiConfig, LocalProviderConfig};
+
+    /// Healthy for the first `healthy_checks` checks, unhealthy afterwards
+    struct FlippingChecker {
+        checks: AtomicU32,
+        healthy_checks: u32,
+    }
+
+    #[async_trait::async_trait]
+    impl ProviderHealthChecker for FlippingChecker {
+        async fn check_health(&self) -> anyhow::Result<ProviderHealthStatus> {
+            let check = self.checks.fetch_add(1, Ordering::SeqCst) + 1;
+            if check <= self.healthy_checks {
+                Ok(ProviderHealthStatus::Healthy {
+                    response_time: Duration::from_millis(10),
+                    models_available: 1,
+                    additional_info: None,
+                })
+            } else {
+                anyhow::bail!("connection refused")
+            }
+        }
+
+        fn provider_type(&self) -> &str {
+            "mock"
+        }
+    }
 
     #[tokio::test]
     async fn test_health_monitor_creation() {
@@ -509,9 +847,252 @@ mod tests {
         assert_eq!(health_status.len(), 0);
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_periodic_checks_update_stored_status() {
+        let mut config = LocalAiConfig::new();
+        config.providers.insert(
+            "ollama".to_string(),
+            LocalProviderConfig::default()
+                .health_check(HealthCheckConfig::default().interval_seconds(10u64)),
+        );
+        let checker = Arc::new(FlippingChecker { checks: AtomicU32::new(0), healthy_checks: 2 });
+        let fixture = HealthMonitor::new(config)
+            .await
+            .unwrap()
+            .with_health_checker("ollama", checker.clone());
+
+        fixture.start().await.unwrap();
+        tokio::time::sleep(Duration::from_secs(15)).await;
+        assert!(fixture.is_provider_healthy("ollama").await);
+
+        // The third check, at 20s, fails
+        tokio::time::sleep(Duration::from_secs(20)).await;
+        let actual = fixture.get_detailed_health_info().await["ollama"].clone();
+        assert!(!actual.status.is_usable());
+        assert_eq!(actual.consecutive_failures, 2);
+        assert_eq!(fixture.monitored_providers(), vec!["ollama".to_string()]);
+
+        fixture.stop().await;
+        let checks = checker.checks.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert_eq!(checker.checks.load(Ordering::SeqCst), checks);
+        assert_eq!(fixture.monitored_providers(), Vec::<String>::new());
+    }
+
+    /// Fails while `failing` is set
+    struct SwitchableChecker {
+        failing: std::sync::atomic::AtomicBool,
+    }
+
+    #[async_trait::async_trait]
+    impl ProviderHealthChecker for SwitchableChecker {
+        async fn check_health(&self) -> anyhow::Result<ProviderHealthStatus> {
+            if self.failing.load(Ordering::SeqCst) {
+                anyhow::bail!("connection refused");
+            }
+            Ok(ProviderHealthStatus::Healthy {
+                response_time: Duration::from_millis(10),
+                models_available: 1,
+                additional_info: None,
+            })
+        }
+
+        fn provider_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failing_provider_backs_off_and_resets_on_recovery() {
+        let mut config = LocalAiConfig::new();
+        config.providers.insert(
+            "ollama".to_string(),
+            LocalProviderConfig::default().health_check(
+                HealthCheckConfig::default()
+                    .interval_seconds(10u64)
+                    .failure_threshold(3u32)
+                    .max_backoff_seconds(60u64),
+            ),
+        );
+        let checker =
+            Arc::new(SwitchableChecker { failing: std::sync::atomic::AtomicBool::new(true) });
+        let fixture = HealthMonitor::new(config)
+            .await
+            .unwrap()
+            .with_health_checker("ollama", checker.clone());
+        fixture.start().await.unwrap();
+
+        // Failed checks at 0s, 10s, 20s, 40s and 80s
+        let mut actual = Vec::new();
+        for wait in [5, 10, 10, 20, 40] {
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+            actual.push(fixture.next_check_delay("ollama").await.unwrap().as_secs());
+        }
+        assert_eq!(actual, vec![10, 10, 20, 40, 60]);
+
+        // The check at 140s succeeds and restores the base interval
+        checker.failing.store(false, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        let actual = fixture.next_check_delay("ollama").await.unwrap();
+        assert_eq!(actual, Duration::from_secs(10));
+        assert!(fixture.is_provider_usable("ollama").await);
+    }
+
+    /// Healthy, reporting a fixed server load
+    struct LoadedChecker {
+        load: ServerLoad,
+    }
+
+    #[async_trait::async_trait]
+    impl ProviderHealthChecker for LoadedChecker {
+        async fn check_health(&self) -> anyhow::Result<ProviderHealthStatus> {
+            Ok(healthy())
+        }
+
+        async fn check_health_with_load(
+            &self,
+        ) -> anyhow::Result<(ProviderHealthStatus, Option<ServerLoad>)> {
+            Ok((healthy(), Some(self.load.clone())))
+        }
+
+        fn provider_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_checks_adapt_admission_capacity() {
+        let admission = Arc::new(AdmissionController::default());
+        admission.register_provider("ollama", 8).unwrap();
+        let checker = Arc::new(LoadedChecker {
+            load: ServerLoad { max_concurrency: Some(2), ..Default::default() },
+        });
+        let mut fixture = HealthMonitor::new(LocalAiConfig::with_default_ollama())
+            .await
+            .unwrap()
+            .with_health_checker("ollama", checker);
+        fixture.set_admission(admission.clone());
+
+        fixture.force_check("ollama").await.unwrap();
+        let first = admission.capacity("ollama");
+        fixture.force_check("ollama").await.unwrap();
+        let second = admission.capacity("ollama");
+
+        assert_eq!((first, second), (Some(5), Some(3)));
+    }
+
+    fn healthy() -> ProviderHealthStatus {
+        ProviderHealthStatus::Healthy {
+            response_time: Duration::from_millis(10),
+            models_available: 2,
+            additional_info: None,
+        }
+    }
+
+    async fn recovering_fixture() -> HealthMonitor {
+        let mut config = LocalAiConfig::new();
+        config.providers.insert(
+            "ollama".to_string(),
+            LocalProviderConfig::default()
+                .health_check(HealthCheckConfig::default().success_threshold(3u32)),
+        );
+        let monitor = HealthMonitor::new(config).await.unwrap();
+        monitor.set_provider_status("ollama", healthy()).await;
+        monitor
+            .set_provider_status(
+                "ollama",
+                ProviderHealthStatus::Unhealthy {
+                    reason: "connection refused".to_string(),
+                    response_time: Duration::from_millis(0),
+                },
+            )
+            .await;
+        monitor
+    }
+
+    #[tokio::test]
+    async fn test_one_off_success_stays_degraded() {
+        let fixture = recovering_fixture().await;
+
+        fixture.set_provider_status("ollama", healthy()).await;
+
+        let actual = fixture.get_providers_by_health().await;
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].1.label(), "degraded");
+        assert!(actual[0].1.is_usable());
+        assert!(!fixture.is_provider_healthy("ollama").await);
+    }
+
+    #[tokio::test]
+    async fn test_sustained_successes_promote_to_healthy() {
+        let fixture = recovering_fixture().await;
+
+        for _ in 0..3 {
+            fixture.set_provider_status("ollama", healthy()).await;
+        }
+
+        let actual = fixture.get_provider_health("ollama").await.unwrap();
+        assert_eq!(actual.label(), "healthy");
+        assert!(!fixture.get_detailed_health_info().await["ollama"].recovering);
+    }
+
+    /// Reports healthy with a fixed response time
+    struct SlowChecker(Duration);
+
+    #[async_trait::async_trait]
+    impl ProviderHealthChecker for SlowChecker {
+        async fn check_health(&self) -> anyhow::Result<ProviderHealthStatus> {
+            Ok(ProviderHealthStatus::Healthy {
+                response_time: self.0,
+                models_available: 2,
+                additional_info: None,
+            })
+        }
+
+        fn provider_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_healthy_check_is_degraded_past_threshold() {
+        let mut config = LocalAiConfig::new();
+        for name in ["slow", "fast"] {
+            config.providers.insert(
+                name.to_string(),
+                LocalProviderConfig::default().health_check(
+                    HealthCheckConfig::default().degraded_response_time_threshold_ms(1000u64),
+                ),
+            );
+        }
+        let fixture = HealthMonitor::new(config)
+            .await
+            .unwrap()
+            .with_health_checker("slow", Arc::new(SlowChecker(Duration::from_secs(3))))
+            .with_health_checker("fast", Arc::new(SlowChecker(Duration::from_millis(200))));
+
+        fixture.force_check_all().await.unwrap();
+
+        let actual = (
+            fixture.get_provider_health("slow").await.unwrap(),
+            fixture.get_provider_health("fast").await.unwrap().label(),
+        );
+        let expected = (
+            ProviderHealthStatus::Degraded {
+                reason: "Response time 3000ms exceeds degraded threshold of 1000ms".to_string(),
+                response_time: Duration::from_secs(3),
+                models_available: 2,
+            },
+            "healthy",
+        );
+        assert_eq!(actual, expected);
+        assert!(fixture.is_provider_usable("slow").await);
+        assert!(!fixture.is_provider_healthy("slow").await);
+    }
+
     #[test]
     fn test_provider_health_info_success_rate() {
-        let mut fixture = ProviderHealthInfo {
+        let fixture = ProviderHealthInfo {
             status: ProviderHealthStatus::Healthy {
                 response_time: Duration::from_millis(100),
                 models_available: 5,
@@ -541,6 +1122,11 @@ mod tests {
                     error: None,
                 },
             ],
+            server_load: None,
+            loaded_models: None,
+            current_interval: Duration::from_secs(30),
+            recovering: false,
+            health_config: HealthCheckConfig::default(),
         };
 
         let actual = fixture.success_rate();
@@ -560,6 +1146,11 @@ mod tests {
             consecutive_successes: 0,
             avg_response_time: Duration::from_millis(0),
             check_history: vec![],
+            server_load: None,
+            loaded_models: None,
+            current_interval: Duration::from_secs(30),
+            recovering: false,
+            health_config: HealthCheckConfig::default(),
         };
 
         assert!(fixture.is_consistently_failing(3));
@@ -585,6 +1176,11 @@ mod tests {
                 response_time: Duration::from_millis(200),
                 error: None,
             }],
+            server_load: None,
+            loaded_models: None,
+            current_interval: Duration::from_secs(30),
+            recovering: false,
+            health_config: HealthCheckConfig::default(),
         };
 
         // Should perform well with lenient thresholds
@@ -593,4 +1189,141 @@ mod tests {
         // Should not perform well with strict thresholds
         assert!(!fixture.is_performing_well(Duration::from_millis(100), 0.8));
     }
+
+    /// Never answers within any reasonable timeout
+    struct HangingChecker;
+
+    #[async_trait::async_trait]
+    impl ProviderHealthChecker for HangingChecker {
+        async fn check_health(&self) -> anyhow::Result<ProviderHealthStatus> {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+            anyhow::bail!("unreachable")
+        }
+
+        fn provider_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    async fn hanging_fixture(timeouts: &[(&str, u64)]) -> HealthMonitor {
+        let mut config = LocalAiConfig::new();
+        for &(name, timeout_seconds) in timeouts {
+            config.providers.insert(
+                name.to_string(),
+                LocalProviderConfig::default()
+                    .health_check(HealthCheckConfig::default().timeout_seconds(timeout_seconds)),
+            );
+        }
+        let mut monitor = HealthMonitor::new(config).await.unwrap();
+        for &(name, _) in timeouts {
+            monitor = monitor.with_health_checker(name, Arc::new(HangingChecker));
+        }
+        monitor
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timed_out_check_is_unhealthy() {
+        let fixture = hanging_fixture(&[("slow", 2)]).await;
+
+        let started = tokio::time::Instant::now();
+        let actual = fixture.force_check("slow").await.unwrap();
+
+        assert_eq!(started.elapsed(), Duration::from_secs(2));
+        let expected = ProviderHealthStatus::Unhealthy {
+            reason: "Health check failed: no response within 2000ms timeout".to_string(),
+            response_time: actual.response_time(),
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_initial_check_records_timeout_as_unhealthy() {
+        let fixture = hanging_fixture(&[("slow", 2)]).await;
+
+        let started = tokio::time::Instant::now();
+        fixture.perform_initial_checks().await;
+
+        assert_eq!(started.elapsed(), Duration::from_secs(2));
+        let actual = fixture.get_health_status().await["slow"].clone();
+        let expected = ProviderHealthStatus::Unhealthy {
+            reason: "Health check failed: no response within 2000ms timeout".to_string(),
+            response_time: actual.response_time(),
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_jittered_intervals_stay_in_band_and_differ_by_provider() {
+        let health_check = HealthCheckConfig::default().jitter_fraction(0.2);
+        let interval = Duration::from_secs(30);
+        let delays = |provider_name: &str| -> Vec<Duration> {
+            let mut jitter = IntervalJitter::new(42, provider_name);
+            (0..5)
+                .map(|_| jitter.next_delay(interval, &health_check))
+                .collect()
+        };
+
+        let first = delays("ollama-a");
+        let second = delays("ollama-b");
+
+        let band = Duration::from_secs(24)..=Duration::from_secs(36);
+        assert!(first
+            .iter()
+            .chain(&second)
+            .all(|delay| band.contains(delay)));
+        assert_ne!(first, second);
+        assert_eq!(first, delays("ollama-a"));
+
+        let mut unjittered = IntervalJitter::new(42, "ollama-a");
+        let actual = unjittered.next_delay(interval, &HealthCheckConfig::default());
+        assert_eq!(actual, interval);
+    }
+
+    /// Healthy after `delay`
+    struct DelayedChecker {
+        delay: Duration,
+    }
+
+    #[async_trait::async_trait]
+    impl ProviderHealthChecker for DelayedChecker {
+        async fn check_health(&self) -> anyhow::Result<ProviderHealthStatus> {
+            tokio::time::sleep(self.delay).await;
+            Ok(ProviderHealthStatus::Healthy {
+                response_time: self.delay,
+                models_available: 1,
+                additional_info: None,
+            })
+        }
+
+        fn provider_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_initial_checks_run_concurrently() {
+        let names = ["local-a", "local-b", "local-c"];
+        let mut config = LocalAiConfig::new();
+        for name in names {
+            config
+                .providers
+                .insert(name.to_string(), LocalProviderConfig::default());
+        }
+        let mut fixture = HealthMonitor::new(config).await.unwrap();
+        for name in names {
+            let checker = DelayedChecker { delay: Duration::from_millis(200) };
+            fixture = fixture.with_health_checker(name, Arc::new(checker));
+        }
+
+        let started = tokio::time::Instant::now();
+        fixture.perform_initial_checks().await;
+
+        assert_eq!(started.elapsed(), Duration::from_millis(200));
+        let health = fixture.get_health_status().await;
+        let actual: Vec<_> = names
+            .into_iter()
+            .map(|name| health[name].is_usable())
+            .collect();
+        assert_eq!(actual, vec![true, true, true]);
+    }
 }
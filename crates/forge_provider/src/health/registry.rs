@@ -0,0 +1,209 @@
+//! Registry of health checker constructors by provider type
+//!
+//! Provider kinds built into this crate are checked according to their
+//! [`ProviderSpecificConfig`](crate::config::local_ai::ProviderSpecificConfig)
+//! variant. Other kinds register a constructor under their `provider_type`
+//! and are configured with the `custom` variant, so a new kind of provider
+//! can be monitored without changes to this crate. A registration also
+//! replaces the built-in checker for a provider type that has one.
+//!
+//! The factory is passed to `new_with_factory` on
+//! [`HealthMonitor`](super::HealthMonitor),
+//! [`ProviderSelector`](crate::selection::ProviderSelector) or
+//! [`ModelDiscoveryService`](crate::discovery::ModelDiscoveryService).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use tracing::debug;
+
+use crate::config::local_ai::{LocalProviderConfig, ProviderHealthChecker};
+
+/// Builds a health checker from a provider's configuration
+pub type HealthCheckerConstructor = Arc<
+    dyn Fn(&LocalProviderConfig) -> anyhow::Result<Box<dyn ProviderHealthChecker>> + Send + Sync,
+>;
+
+/// Health checker constructors keyed by provider type
+#[derive(Clone, Default)]
+pub struct HealthCheckerFactory {
+    constructors: HashMap<String, HealthCheckerConstructor>,
+}
+
+impl fmt::Debug for HealthCheckerFactory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut provider_types: Vec<_> = self.constructors.keys().collect();
+        provider_types.sort();
+        f.debug_struct("HealthCheckerFactory")
+            .field("provider_types", &provider_types)
+            .finish()
+    }
+}
+
+impl HealthCheckerFactory {
+    /// Check providers of `provider_type` with checkers built by
+    /// `constructor`
+    pub fn with_checker_type<F>(mut self, provider_type: impl Into<String>, constructor: F) -> Self
+    where
+        F: Fn(&LocalProviderConfig) -> anyhow::Result<Box<dyn ProviderHealthChecker>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.constructors
+            .insert(provider_type.into(), Arc::new(constructor));
+        self
+    }
+
+    /// Whether a constructor is registered for `provider_type`
+    pub fn is_registered(&self, provider_type: &str) -> bool {
+        self.constructors.contains_key(provider_type)
+    }
+
+    /// Create the health checker for `config`: the constructor registered
+    /// for its provider type, or the built-in checker for its config variant
+    pub fn create(
+        &self,
+        config: &LocalProviderConfig,
+    ) -> anyhow::Result<Box<dyn ProviderHealthChecker>> {
+        match self.constructors.get(&config.provider_type) {
+            Some(constructor) => {
+                debug!(
+                    provider_type = %config.provider_type,
+                    "Creating registered health checker"
+                );
+                constructor(config)
+            }
+            None => config.builtin_health_checker(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::config::fallback::FallbackConfig;
+    use crate::config::local_ai::{LocalAiConfig, ProviderHealthStatus, ProviderSpecificConfig};
+    use crate::discovery::ModelDiscoveryService;
+    use crate::health::HealthMonitor;
+    use crate::selection::{ProviderSelector, ProviderType, SelectionContext};
+
+    /// Reports the model count configured in its options
+    struct StaticChecker {
+        models_available: usize,
+    }
+
+    #[async_trait::async_trait]
+    impl ProviderHealthChecker for StaticChecker {
+        async fn check_health(&self) -> anyhow::Result<ProviderHealthStatus> {
+            Ok(ProviderHealthStatus::Healthy {
+                response_time: Duration::from_millis(5),
+                models_available: self.models_available,
+                additional_info: Some("static".to_string()),
+            })
+        }
+
+        fn provider_type(&self) -> &str {
+            "static"
+        }
+    }
+
+    fn fixture() -> HealthCheckerFactory {
+        HealthCheckerFactory::default().with_checker_type("static", |config| {
+            let ProviderSpecificConfig::Custom { options } = &config.config else {
+                anyhow::bail!("static providers use the custom config");
+            };
+            let models_available = options
+                .get("models")
+                .and_then(serde_json::Value::as_u64)
+                .unwrap_or_default() as usize;
+            Ok(Box::new(StaticChecker { models_available }))
+        })
+    }
+
+    fn static_provider() -> LocalProviderConfig {
+        LocalProviderConfig::default()
+            .provider_type("static")
+            .config(ProviderSpecificConfig::Custom {
+                options: HashMap::from([("models".to_string(), serde_json::json!(7))]),
+            })
+    }
+
+    #[tokio::test]
+    async fn test_monitor_uses_registered_checker() {
+        let config = LocalAiConfig::new().add_provider("edge".to_string(), static_provider());
+        let monitor = HealthMonitor::new_with_factory(config, &fixture())
+            .await
+            .unwrap();
+
+        let actual = monitor.force_check("edge").await.unwrap();
+
+        let expected = ProviderHealthStatus::Healthy {
+            response_time: Duration::from_millis(5),
+            models_available: 7,
+            additional_info: Some("static".to_string()),
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_selector_uses_registered_checker() {
+        let config = LocalAiConfig::new().add_provider("edge".to_string(), static_provider());
+        let mut selector =
+            ProviderSelector::new_with_factory(config, FallbackConfig::default(), &fixture())
+                .await
+                .unwrap();
+        selector.refresh_health().await.unwrap();
+
+        let actual = selector
+            .select_provider(SelectionContext::new("llama3.2".to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (actual.provider_name.as_str(), actual.provider_type),
+            ("edge", ProviderType::Local)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_discovery_uses_registered_checker() {
+        let config = LocalAiConfig::new().add_provider("edge".to_string(), static_provider());
+        let mut discovery = ModelDiscoveryService::new_with_factory(config, &fixture())
+            .await
+            .unwrap();
+        discovery.refresh_provider("edge").await.unwrap();
+
+        let actual = discovery.get_provider_health_status().await;
+
+        let expected = ProviderHealthStatus::Healthy {
+            response_time: Duration::from_millis(5),
+            models_available: 7,
+            additional_info: Some("static".to_string()),
+        };
+        assert_eq!(actual["edge"], expected);
+    }
+
+    #[test]
+    fn test_unregistered_custom_type_is_rejected() {
+        let actual = HealthCheckerFactory::default().create(&static_provider());
+
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn test_builtin_types_need_no_registration() {
+        let fixture = fixture();
+
+        let actual = fixture.create(&LocalProviderConfig::default()).unwrap();
+
+        assert_eq!(actual.provider_type(), "ollama");
+        assert!(!fixture.is_registered("ollama"));
+    }
+}
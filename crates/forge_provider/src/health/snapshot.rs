@@ -0,0 +1,187 @@
+//! Point-in-time export of the health monitor's state
+//!
+//! [`ProviderHealthInfo`] records when checks happened as [`Instant`]s, which
+//! mean nothing outside the process. A [`HealthSnapshot`] renders them as ages
+//! relative to the moment it was taken, so it can be attached to bug reports
+//! as JSON. Ages and durations are written in whole milliseconds.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use anyhow::Context as _;
+use serde::Serialize;
+
+use super::{HealthCheckResult, HealthMonitor, ProviderHealthInfo};
+use crate::config::local_ai::{ProviderHealthStatus, ServerLoad};
+
+/// Health of every monitored provider at one moment
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthSnapshot {
+    /// Per-provider health, by provider name
+    pub providers: BTreeMap<String, ProviderHealthSnapshot>,
+}
+
+/// One provider's health as of the snapshot
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderHealthSnapshot {
+    /// Status as seen by provider selection
+    pub status: ProviderHealthStatus,
+    /// Time since the provider was last checked
+    #[serde(rename = "last_checked_age_ms", serialize_with = "millis")]
+    pub last_checked_age: Duration,
+    pub consecutive_failures: u32,
+    pub consecutive_successes: u32,
+    /// Average response time over the check history
+    #[serde(rename = "avg_response_time_ms", serialize_with = "millis")]
+    pub avg_response_time: Duration,
+    /// Share of successful checks in the history, between 0.0 and 1.0
+    pub success_rate: f64,
+    /// Recent checks, oldest first
+    pub check_history: Vec<HealthCheckSnapshot>,
+    pub server_load: Option<ServerLoad>,
+    #[serde(rename = "current_interval_ms", serialize_with = "millis")]
+    pub current_interval: Duration,
+    pub recovering: bool,
+}
+
+/// A single health check as of the snapshot
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthCheckSnapshot {
+    /// Time since the check ran
+    #[serde(rename = "age_ms", serialize_with = "millis")]
+    pub age: Duration,
+    pub success: bool,
+    #[serde(rename = "response_time_ms", serialize_with = "millis")]
+    pub response_time: Duration,
+    pub error: Option<String>,
+}
+
+fn millis<S: serde::Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis().try_into().unwrap_or(u64::MAX))
+}
+
+impl HealthSnapshot {
+    /// Render the snapshot as pretty-printed JSON
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize health snapshot")
+    }
+}
+
+impl ProviderHealthSnapshot {
+    fn at(info: &ProviderHealthInfo, now: Instant) -> Self {
+        Self {
+            status: info.effective_status(),
+            last_checked_age: now.saturating_duration_since(info.last_checked),
+            consecutive_failures: info.consecutive_failures,
+            consecutive_successes: info.consecutive_successes,
+            avg_response_time: info.avg_response_time,
+            success_rate: info.success_rate(),
+            check_history: info
+                .check_history
+                .iter()
+                .map(|result| HealthCheckSnapshot::at(result, now))
+                .collect(),
+            server_load: info.server_load.clone(),
+            current_interval: info.current_interval,
+            recovering: info.recovering,
+        }
+    }
+}
+
+impl HealthCheckSnapshot {
+    fn at(result: &HealthCheckResult, now: Instant) -> Self {
+        Self {
+            age: now.saturating_duration_since(result.timestamp),
+            success: result.success,
+            response_time: result.response_time,
+            error: result.error.clone(),
+        }
+    }
+}
+
+impl HealthMonitor {
+    /// Capture the health of every provider checked so far
+    pub async fn export_snapshot(&self) -> HealthSnapshot {
+        self.export_snapshot_at(Instant::now()).await
+    }
+
+    /// Capture the health of every provider checked so far, with ages
+    /// measured from `now`
+    pub async fn export_snapshot_at(&self, now: Instant) -> HealthSnapshot {
+        let health_status = self.health_status.read().await;
+        let providers = health_status
+            .iter()
+            .map(|(name, info)| (name.clone(), ProviderHealthSnapshot::at(info, now)))
+            .collect();
+        HealthSnapshot { providers }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::config::local_ai::{LocalAiConfig, ProviderHealthChecker};
+
+    struct FixedChecker(bool);
+
+    #[async_trait::async_trait]
+    impl ProviderHealthChecker for FixedChecker {
+        async fn check_health(&self) -> anyhow::Result<ProviderHealthStatus> {
+            if self.0 {
+                Ok(ProviderHealthStatus::Healthy {
+                    response_time: Duration::from_millis(15),
+                    models_available: 2,
+                    additional_info: None,
+                })
+            } else {
+                anyhow::bail!("connection refused")
+            }
+        }
+
+        fn provider_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_covers_every_provider_with_relative_ages() {
+        let fixture = HealthMonitor::new_fallback(LocalAiConfig::new())
+            .with_health_checker("up", Arc::new(FixedChecker(true)))
+            .with_health_checker("down", Arc::new(FixedChecker(false)));
+        fixture.force_check_all().await.unwrap();
+        fixture.force_check("up").await.unwrap();
+
+        let snapshot = fixture.export_snapshot().await;
+
+        let actual: Vec<_> = snapshot
+            .providers
+            .iter()
+            .map(|(name, provider)| {
+                (
+                    name.clone(),
+                    provider.status.label(),
+                    provider.check_history.len(),
+                )
+            })
+            .collect();
+        let expected = vec![
+            ("down".to_string(), "unhealthy", 1),
+            ("up".to_string(), "healthy", 2),
+        ];
+        assert_eq!(actual, expected);
+        for provider in snapshot.providers.values() {
+            assert!(provider.last_checked_age >= Duration::ZERO);
+            assert!(provider
+                .check_history
+                .iter()
+                .all(|check| check.age >= Duration::ZERO));
+        }
+        let json: serde_json::Value = serde_json::from_str(&snapshot.to_json().unwrap()).unwrap();
+        assert!(json["providers"]["down"]["last_checked_age_ms"].is_u64());
+        assert!(json["providers"]["up"]["check_history"][0]["age_ms"].is_u64());
+    }
+}
@@ -0,0 +1,118 @@
+//! Idempotency keys and charged-request tracking
+//!
+//! Every logical request gets an [`IdempotencyKey`] that all of its retries
+//! reuse, and OpenAI-compatible providers receive it in the
+//! `Idempotency-Key` header so they can deduplicate retries. Cost tracking
+//! remembers the keys it already charged for in [`ChargedRequests`], so a
+//! retried request is only billed once.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+pub use forge_app::domain::IdempotencyKey;
+
+/// Header used by OpenAI-compatible APIs to deduplicate retried requests
+pub(crate) const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// How long a charged request is remembered for deduplicating its retries
+const CHARGED_REQUEST_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Most charged requests remembered at once
+const MAX_CHARGED_REQUESTS: usize = 10_000;
+
+/// Idempotency keys of recently charged requests.
+///
+/// Keys are forgotten once they are older than an hour, long after any retry
+/// of the request, and the oldest are dropped beyond a fixed capacity so the
+/// set stays bounded in long-running sessions.
+#[derive(Debug, Clone, Default)]
+pub struct ChargedRequests {
+    charged_at: HashMap<IdempotencyKey, Instant>,
+    order: VecDeque<(IdempotencyKey, Instant)>,
+}
+
+impl ChargedRequests {
+    /// Record a charge for `key` now, returning whether it is the first
+    pub fn first_charge(&mut self, key: &IdempotencyKey) -> bool {
+        self.first_charge_at(key, Instant::now())
+    }
+
+    /// Record a charge for `key` at `now`, returning whether it is the first
+    /// within the retention window
+    pub fn first_charge_at(&mut self, key: &IdempotencyKey, now: Instant) -> bool {
+        self.expire(now);
+        if self.charged_at.contains_key(key) {
+            return false;
+        }
+
+        while self.order.len() >= MAX_CHARGED_REQUESTS {
+            self.pop_oldest();
+        }
+        self.charged_at.insert(key.clone(), now);
+        self.order.push_back((key.clone(), now));
+        true
+    }
+
+    /// Number of remembered charges
+    pub fn len(&self) -> usize {
+        self.charged_at.len()
+    }
+
+    /// Whether no charges are remembered
+    pub fn is_empty(&self) -> bool {
+        self.charged_at.is_empty()
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while self
+            .order
+            .front()
+            .is_some_and(|(_, at)| now.saturating_duration_since(*at) > CHARGED_REQUEST_TTL)
+        {
+            self.pop_oldest();
+        }
+    }
+
+    fn pop_oldest(&mut self) {
+        if let Some((key, _)) = self.order.pop_front() {
+            self.charged_at.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_charged_request_is_counted_once_until_it_expires() {
+        let mut fixture = ChargedRequests::default();
+        let key = IdempotencyKey::new("forge-retry");
+        let base = Instant::now();
+
+        let actual = [
+            fixture.first_charge_at(&key, base),
+            fixture.first_charge_at(&key, base + Duration::from_secs(30)),
+            fixture.first_charge_at(&key, base + CHARGED_REQUEST_TTL + Duration::from_secs(1)),
+        ];
+
+        let expected = [true, false, true];
+        assert_eq!(actual, expected);
+        assert_eq!(fixture.len(), 1);
+    }
+
+    #[test]
+    fn test_charged_requests_are_bounded() {
+        let mut fixture = ChargedRequests::default();
+        let base = Instant::now();
+
+        for i in 0..MAX_CHARGED_REQUESTS + 5 {
+            fixture.first_charge_at(&IdempotencyKey::new(format!("forge-{i}")), base);
+        }
+
+        assert_eq!(fixture.len(), MAX_CHARGED_REQUESTS);
+        assert!(fixture.first_charge_at(&IdempotencyKey::new("forge-0"), base));
+    }
+}
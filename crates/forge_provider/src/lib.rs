@@ -1,7 +1,9 @@
 mod anthropic;
 mod client;
+mod continuation;
 mod error;
 mod forge_provider;
+mod idempotency;
 #[cfg(test)]
 mod mock_server;
 mod ollama;
@@ -10,12 +12,20 @@ mod retry;
 mod utils;
 
 // Re-export from builder.rs
-pub use client::Client;
+pub use client::{ChatChunk, Client};
+pub use continuation::StreamContinuation;
+pub use idempotency::{ChargedRequests, IdempotencyKey};
+pub use retry::{retry_scaled, retry_with, FailureKind, RetryPolicy};
 
+pub mod circuit_breaker;
 pub mod config;
 pub mod discovery;
+pub mod ensemble;
 pub mod health;
 pub mod performance;
+pub mod readiness;
+pub mod redaction;
 pub mod selection;
-#[cfg(test)]
+#[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils;
+pub mod timing;
@@ -1,4 +1,11 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
 use mockito::{Mock, Server, ServerGuard};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
 
 pub struct MockServer {
     server: ServerGuard,
@@ -29,11 +36,511 @@ impl MockServer {
             .await
     }
 
+    /// Mock the Ollama tags endpoint, adding extra response `headers`
+    pub async fn mock_ollama_models_with_headers(
+        &mut self,
+        body: serde_json::Value,
+        headers: &[(&str, &str)],
+    ) -> Mock {
+        let mut mock = self
+            .server
+            .mock("GET", "/api/tags")
+            .with_status(200)
+            .with_header("content-type", "application/json");
+        for &(name, value) in headers {
+            mock = mock.with_header(name, value);
+        }
+        mock.with_body(body.to_string()).create_async().await
+    }
+
+    pub async fn mock_ollama_generate(&mut self, body: serde_json::Value, status: usize) -> Mock {
+        self.server
+            .mock("POST", "/api/generate")
+            .match_body(mockito::Matcher::PartialJson(body))
+            .with_status(status)
+            .with_header("content-type", "application/json")
+            .with_body("{}")
+            .create_async()
+            .await
+    }
+
+    /// Mock the Ollama embeddings endpoint for requests whose prompt is
+    /// `prompt`
+    pub async fn mock_ollama_embeddings(
+        &mut self,
+        prompt: &str,
+        body: serde_json::Value,
+        status: usize,
+    ) -> Mock {
+        self.server
+            .mock("POST", "/api/embeddings")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({ "prompt": prompt }),
+            ))
+            .with_status(status)
+            .with_header("content-type", "application/json")
+            .with_body(body.to_string())
+            .create_async()
+            .await
+    }
+
+    /// Mock a streaming chat completion that only matches when `header` has
+    /// `value`, expecting exactly `hits` requests
+    pub async fn mock_chat_completions(&mut self, header: &str, value: &str, hits: usize) -> Mock {
+        self.server
+            .mock("POST", "/chat/completions")
+            .match_header(header, value)
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body("data: [DONE]\n\n")
+            .expect(hits)
+            .create_async()
+            .await
+    }
+
+    /// Mock a streaming chat completion for requests containing `request`,
+    /// streaming each of `events` back as a server-sent event
+    pub async fn mock_chat_completion_stream(
+        &mut self,
+        request: serde_json::Value,
+        events: Vec<serde_json::Value>,
+    ) -> Mock {
+        let body: String = events
+            .iter()
+            .map(|event| format!("data: {event}\n\n"))
+            .chain(["data: [DONE]\n\n".to_string()])
+            .collect();
+        self.server
+            .mock("POST", "/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(request))
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(body)
+            .create_async()
+            .await
+    }
+
+    /// Mock the Anthropic Messages endpoint for requests containing
+    /// `request`, streaming each of `events` back as a server-sent event
+    pub async fn mock_anthropic_messages(
+        &mut self,
+        request: serde_json::Value,
+        events: Vec<serde_json::Value>,
+    ) -> Mock {
+        let body: String = events
+            .iter()
+            .map(|event| {
+                format!(
+                    "event: {}\ndata: {event}\n\n",
+                    event["type"].as_str().unwrap()
+                )
+            })
+            .collect();
+        self.server
+            .mock("POST", "/messages")
+            .match_header("anthropic-version", "2023-06-01")
+            .match_body(mockito::Matcher::PartialJson(request))
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(body)
+            .create_async()
+            .await
+    }
+
     pub fn url(&self) -> String {
         self.server.url()
     }
 }
 
+/// Spawn a bare HTTP server that waits `delay` before answering every request
+/// with `body`, for exercising client-side timeouts. Returns the base URL.
+pub async fn spawn_delayed_server(delay: Duration, content_type: &str, body: String) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let content_type = content_type.to_string();
+
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let content_type = content_type.clone();
+            let body = body.clone();
+            tokio::spawn(async move {
+                let mut buf = [0u8; 8192];
+                let _ = socket.read(&mut buf).await;
+                tokio::time::sleep(delay).await;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: {content_type}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+
+    format!("http://{addr}")
+}
+
+/// Spawn a keep-alive HTTP server that answers every request with an empty
+/// JSON body and counts accepted connections, so tests can tell whether a
+/// client reused a pooled connection. Returns the base URL and the counter.
+pub async fn spawn_connection_counting_server() -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let connections = Arc::new(AtomicUsize::new(0));
+    let counter = connections.clone();
+
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(async move {
+                let mut buf = [0u8; 8192];
+                // Requests carry no body, so every read is one request
+                while let Ok(read) = socket.read(&mut buf).await {
+                    if read == 0 {
+                        break;
+                    }
+                    let response =
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: 2\r\n\r\n{}";
+                    if socket.write_all(response.as_bytes()).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    (format!("http://{addr}"), connections)
+}
+
+/// Body of a scripted response
+#[derive(Debug, Clone)]
+enum ScriptedBody {
+    /// Send the whole body at once
+    Full(String),
+    /// Send the body as chunks, optionally closing the connection before
+    /// chunk `drop_after` is written
+    Chunks {
+        chunks: Vec<String>,
+        interval: Duration,
+        drop_after: Option<usize>,
+    },
+    /// Close the connection without answering
+    Drop,
+}
+
+/// One scripted answer for a [`MockOllamaServer`] route
+#[derive(Debug, Clone)]
+pub struct ScriptedResponse {
+    status: u16,
+    content_type: String,
+    headers: Vec<(String, String)>,
+    delay: Duration,
+    body: ScriptedBody,
+}
+
+impl ScriptedResponse {
+    /// Answer with a JSON body
+    pub fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json".to_string(),
+            headers: Vec::new(),
+            delay: Duration::ZERO,
+            body: ScriptedBody::Full(body.to_string()),
+        }
+    }
+
+    /// Stream each value as a server-sent event
+    pub fn sse(events: Vec<serde_json::Value>) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/event-stream".to_string(),
+            headers: Vec::new(),
+            delay: Duration::ZERO,
+            body: ScriptedBody::Chunks {
+                chunks: events
+                    .into_iter()
+                    .map(|event| format!("data: {event}\n\n"))
+                    .collect(),
+                interval: Duration::ZERO,
+                drop_after: None,
+            },
+        }
+    }
+
+    /// Stream each value as a line of newline-delimited JSON
+    pub fn ndjson(lines: Vec<serde_json::Value>) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/x-ndjson".to_string(),
+            headers: Vec::new(),
+            delay: Duration::ZERO,
+            body: ScriptedBody::Chunks {
+                chunks: lines.into_iter().map(|line| format!("{line}\n")).collect(),
+                interval: Duration::ZERO,
+                drop_after: None,
+            },
+        }
+    }
+
+    /// Stream an Ollama chat response that emits `tokens` in order, followed
+    /// by a final `done` event
+    pub fn chat_stream(model: &str, tokens: &[&str]) -> Self {
+        let event = |content: &str, done: bool| {
+            serde_json::json!({
+                "model": model,
+                "created_at": "2025-05-04T17:37:44Z",
+                "message": {"role": "assistant", "content": content},
+                "done": done
+            })
+        };
+        let mut events: Vec<_> = tokens.iter().map(|token| event(token, false)).collect();
+        events.push(event("", true));
+        Self::sse(events)
+    }
+
+    /// Close the connection without sending a response
+    pub fn connection_drop() -> Self {
+        Self {
+            body: ScriptedBody::Drop,
+            ..Self::json(200, serde_json::Value::Null)
+        }
+    }
+
+    /// Wait before sending the response headers
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Add a response header
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Wait between streamed chunks
+    pub fn with_chunk_interval(mut self, duration: Duration) -> Self {
+        if let ScriptedBody::Chunks { interval, .. } = &mut self.body {
+            *interval = duration;
+        }
+        self
+    }
+
+    /// Drop the connection after `count` chunks have been streamed
+    pub fn drop_after(mut self, count: usize) -> Self {
+        if let ScriptedBody::Chunks { drop_after, .. } = &mut self.body {
+            *drop_after = Some(count);
+        }
+        self
+    }
+}
+
+type Route = (String, String);
+
+/// Builder for a [`MockOllamaServer`]
+#[derive(Debug, Default)]
+pub struct MockOllamaServerBuilder {
+    routes: HashMap<Route, Vec<ScriptedResponse>>,
+}
+
+impl MockOllamaServerBuilder {
+    /// Append `response` to the script for `method` and `path`. Requests
+    /// consume the script in order and the last response repeats.
+    pub fn on(mut self, method: &str, path: &str, response: ScriptedResponse) -> Self {
+        self.routes
+            .entry((method.to_uppercase(), path.to_string()))
+            .or_default()
+            .push(response);
+        self
+    }
+
+    /// Answer `GET /api/tags` with the given model names
+    pub fn tags(self, models: &[&str]) -> Self {
+        let models: Vec<_> = models
+            .iter()
+            .map(|name| {
+                serde_json::json!({
+                    "name": name,
+                    "model": name,
+                    "modified_at": "2025-05-04T17:37:44Z",
+                    "size": 0,
+                    "digest": "",
+                    "details": {
+                        "parent_model": "",
+                        "format": "gguf",
+                        "family": "llama",
+                        "families": ["llama"],
+                        "parameter_size": "",
+                        "quantization_level": ""
+                    }
+                })
+            })
+            .collect();
+        self.on(
+            "GET",
+            "/api/tags",
+            ScriptedResponse::json(200, serde_json::json!({ "models": models })),
+        )
+    }
+
+    /// Answer `GET /api/ps` with the given models loaded into memory
+    pub fn ps(self, models: &[&str]) -> Self {
+        let models: Vec<_> = models
+            .iter()
+            .map(|name| serde_json::json!({ "name": name, "model": name, "size_vram": 0 }))
+            .collect();
+        self.on(
+            "GET",
+            "/api/ps",
+            ScriptedResponse::json(200, serde_json::json!({ "models": models })),
+        )
+    }
+
+    /// Bind to a local port and start serving the script
+    pub async fn start(self) -> MockOllamaServer {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let routes = Arc::new(Mutex::new(self.routes));
+        let hits: Arc<Mutex<HashMap<Route, usize>>> = Arc::default();
+
+        let served = hits.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let routes = routes.clone();
+                let hits = served.clone();
+                tokio::spawn(async move {
+                    let _ = serve_scripted(socket, routes, hits).await;
+                });
+            }
+        });
+
+        MockOllamaServer { url: format!("http://{addr}"), hits }
+    }
+}
+
+/// Raw HTTP server that answers Ollama endpoints from a per-route script,
+/// supporting delays, failures, streamed bodies and dropped connections
+pub struct MockOllamaServer {
+    url: String,
+    hits: Arc<Mutex<HashMap<Route, usize>>>,
+}
+
+impl MockOllamaServer {
+    pub fn builder() -> MockOllamaServerBuilder {
+        MockOllamaServerBuilder::default()
+    }
+
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// Number of requests received for `method` and `path`
+    pub fn hits(&self, method: &str, path: &str) -> usize {
+        self.hits
+            .lock()
+            .unwrap()
+            .get(&(method.to_uppercase(), path.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// Read one request from `socket` and answer it from the script
+async fn serve_scripted(
+    mut socket: tokio::net::TcpStream,
+    routes: Arc<Mutex<HashMap<Route, Vec<ScriptedResponse>>>>,
+    hits: Arc<Mutex<HashMap<Route, usize>>>,
+) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 8192];
+    let header_end = loop {
+        let read = socket.read(&mut buf).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        request.extend_from_slice(&buf[..read]);
+        if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&request[..header_end]).to_string();
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_uppercase();
+    let path = request_line
+        .next()
+        .unwrap_or_default()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or_default();
+    while request.len() < header_end + content_length {
+        let read = socket.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read]);
+    }
+
+    let route = (method, path);
+    *hits.lock().unwrap().entry(route.clone()).or_default() += 1;
+    let response = {
+        let mut routes = routes.lock().unwrap();
+        match routes.get_mut(&route) {
+            Some(script) if script.len() > 1 => Some(script.remove(0)),
+            Some(script) => script.first().cloned(),
+            None => None,
+        }
+    }
+    .unwrap_or_else(|| {
+        ScriptedResponse::json(404, serde_json::json!({ "error": "no scripted response" }))
+    });
+
+    tokio::time::sleep(response.delay).await;
+
+    let reason = reqwest::StatusCode::from_u16(response.status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or_default();
+    let mut head = format!(
+        "HTTP/1.1 {} {reason}\r\ncontent-type: {}\r\nconnection: close\r\n",
+        response.status, response.content_type
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+
+    match response.body {
+        ScriptedBody::Drop => Ok(()),
+        ScriptedBody::Full(body) => {
+            head.push_str(&format!("content-length: {}\r\n\r\n", body.len()));
+            socket.write_all(head.as_bytes()).await?;
+            socket.write_all(body.as_bytes()).await
+        }
+        ScriptedBody::Chunks { chunks, interval, drop_after } => {
+            head.push_str("transfer-encoding: chunked\r\n\r\n");
+            socket.write_all(head.as_bytes()).await?;
+            for (index, chunk) in chunks.iter().enumerate() {
+                if drop_after == Some(index) {
+                    return socket.shutdown().await;
+                }
+                socket
+                    .write_all(format!("{:x}\r\n{chunk}\r\n", chunk.len()).as_bytes())
+                    .await?;
+                socket.flush().await?;
+                tokio::time::sleep(interval).await;
+            }
+            socket.write_all(b"0\r\n\r\n").await
+        }
+    }
+}
+
 /// Normalize dynamic addresses in messages for testing/logging.
 pub fn normalize_ports(input: String) -> String {
     use regex::Regex;
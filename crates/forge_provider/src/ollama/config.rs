@@ -1,19 +1,32 @@
+use std::collections::HashMap;
 use std::time::Duration;
 
+use derive_setters::Setters;
+use futures::future::{AbortHandle, Abortable};
+use futures::stream::{self, StreamExt};
 use reqwest::{Client, Url};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
 use super::error::OllamaError;
 use super::Ollama;
+use crate::config::local_ai::{DiscoveryConfig, ServerLoad};
+use crate::performance::RequestType;
 
 /// Configuration for Ollama provider with validation and defaults
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OllamaConfig {
     /// Base URL for Ollama service
     pub base_url: String,
-    /// Request timeout in seconds
+    /// Maximum time to wait for data on an open connection, in seconds
     pub timeout_seconds: u64,
+    /// Overall bound on requests without a per-type timeout in
+    /// `request_timeouts`, so a wedged server cannot hang inference forever
+    #[serde(default = "default_request_timeout")]
+    pub request_timeout: Duration,
+    /// Maximum time to establish a connection
+    #[serde(default = "default_connect_timeout")]
+    pub connect_timeout: Duration,
     /// Maximum number of retry attempts
     pub max_retries: u32,
     /// Retry delay in milliseconds
@@ -22,6 +35,39 @@ pub struct OllamaConfig {
     pub connection_pooling: bool,
     /// User agent string
     pub user_agent: Option<String>,
+    /// Overall timeouts applied per request type
+    #[serde(default)]
+    pub request_timeouts: RequestTimeouts,
+    /// How long Ollama keeps a model loaded after a chat request, e.g. "30m"
+    /// or "-1" to keep it resident. `None` leaves Ollama's own idle timeout in
+    /// place.
+    #[serde(default)]
+    pub keep_alive: Option<String>,
+}
+
+/// Overall request timeouts for each kind of request sent to a provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Setters)]
+#[setters(strip_option, into)]
+pub struct RequestTimeouts {
+    /// Timeout for health check probes in milliseconds
+    pub health_check_ms: u64,
+    /// Timeout for model discovery calls in milliseconds
+    pub discovery_ms: u64,
+    /// Overall timeout for inference in milliseconds. `None` leaves streaming
+    /// generations unbounded, relying on `inter_token_ms` instead.
+    pub inference_ms: Option<u64>,
+    /// Maximum gap between streamed events in milliseconds
+    pub inter_token_ms: Option<u64>,
+    /// Timeout for loading or unloading a model in milliseconds
+    pub model_loading_ms: u64,
+}
+
+fn default_request_timeout() -> Duration {
+    Duration::from_secs(600)
+}
+
+fn default_connect_timeout() -> Duration {
+    Duration::from_secs(5)
 }
 
 impl Default for OllamaConfig {
@@ -29,14 +75,47 @@ impl Default for OllamaConfig {
         Self {
             base_url: "http://localhost:11434".to_string(),
             timeout_seconds: 30,
+            request_timeout: default_request_timeout(),
+            connect_timeout: default_connect_timeout(),
             max_retries: 3,
             retry_delay_ms: 1000,
             connection_pooling: true,
             user_agent: Some("forge-ai/1.0".to_string()),
+            request_timeouts: RequestTimeouts::default(),
+            keep_alive: None,
+        }
+    }
+}
+
+impl Default for RequestTimeouts {
+    fn default() -> Self {
+        Self {
+            health_check_ms: 5_000,
+            discovery_ms: 15_000,
+            inference_ms: None,
+            inter_token_ms: Some(60_000),
+            model_loading_ms: 300_000,
         }
     }
 }
 
+impl RequestTimeouts {
+    /// Overall timeout for a request of the given type, if any
+    pub fn timeout_for(&self, request_type: RequestType) -> Option<Duration> {
+        match request_type {
+            RequestType::HealthCheck => Some(Duration::from_millis(self.health_check_ms)),
+            RequestType::Discovery => Some(Duration::from_millis(self.discovery_ms)),
+            RequestType::Inference => self.inference_ms.map(Duration::from_millis),
+            RequestType::ModelLoading => Some(Duration::from_millis(self.model_loading_ms)),
+        }
+    }
+
+    /// Maximum gap allowed between streamed inference events
+    pub fn inter_token_timeout(&self) -> Option<Duration> {
+        self.inter_token_ms.map(Duration::from_millis)
+    }
+}
+
 impl OllamaConfig {
     /// Create a new configuration with default values
     pub fn new() -> Self {
@@ -55,6 +134,18 @@ impl OllamaConfig {
         self
     }
 
+    /// Set the overall timeout for requests without a per-type timeout
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// Set the connection timeout
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
     /// Set the maximum retry attempts
     pub fn with_max_retries(mut self, max_retries: u32) -> Self {
         self.max_retries = max_retries;
@@ -79,6 +170,18 @@ impl OllamaConfig {
         self
     }
 
+    /// Set the per-request-type timeouts
+    pub fn with_request_timeouts(mut self, request_timeouts: RequestTimeouts) -> Self {
+        self.request_timeouts = request_timeouts;
+        self
+    }
+
+    /// Set how long Ollama keeps models loaded after chat requests
+    pub fn with_keep_alive(mut self, keep_alive: String) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+
     /// Validate the configuration
     pub fn validate(&self) -> Result<(), OllamaError> {
         // Validate base URL
@@ -92,6 +195,12 @@ impl OllamaConfig {
             });
         }
 
+        if self.request_timeout.is_zero() || self.connect_timeout.is_zero() {
+            return Err(OllamaError::InvalidConfiguration {
+                message: "Request and connect timeouts cannot be zero".to_string(),
+            });
+        }
+
         if self.timeout_seconds > 300 {
             warn!("Timeout of {} seconds is very high", self.timeout_seconds);
         }
@@ -111,9 +220,11 @@ impl OllamaConfig {
 
     /// Create an HTTP client based on this configuration
     pub fn create_client(&self) -> Result<Client, OllamaError> {
+        // The request timeout is applied per chat request rather than here, so
+        // model pulls are not cut off however long the download takes
         let mut builder = Client::builder()
-            .timeout(Duration::from_secs(self.timeout_seconds))
-            .connect_timeout(Duration::from_secs(5)) // Add connection timeout
+            .read_timeout(Duration::from_secs(self.timeout_seconds))
+            .connect_timeout(self.connect_timeout)
             .pool_idle_timeout(Duration::from_secs(30))
             .pool_max_idle_per_host(if self.connection_pooling { 10 } else { 0 });
 
@@ -139,6 +250,9 @@ impl OllamaConfig {
         Ok(Ollama::builder()
             .client(client)
             .base_url(base_url)
+            .timeouts(self.request_timeouts.clone())
+            .request_timeout(Some(self.request_timeout))
+            .keep_alive(self.keep_alive.clone())
             .build()
             .unwrap())
     }
@@ -147,17 +261,34 @@ impl OllamaConfig {
 /// Health check and service discovery utilities
 pub struct OllamaHealthCheck {
     config: OllamaConfig,
+    client: tokio::sync::OnceCell<Client>,
 }
 
 impl OllamaHealthCheck {
     /// Create a new health check instance
     pub fn new(config: OllamaConfig) -> Self {
-        Self { config }
+        Self { config, client: tokio::sync::OnceCell::new() }
+    }
+
+    /// HTTP client shared by every probe, so repeated checks reuse pooled
+    /// connections instead of opening new ones
+    async fn client(&self) -> Result<&Client, OllamaError> {
+        self.client
+            .get_or_try_init(|| async { self.config.create_client() })
+            .await
     }
 
     /// Check if Ollama service is available and healthy
     pub async fn check_health(&self) -> Result<HealthStatus, OllamaError> {
-        let client = self.config.create_client()?;
+        Ok(self.check_health_with_load().await?.0)
+    }
+
+    /// Check service health and capture any load metrics reported in the
+    /// response headers
+    pub async fn check_health_with_load(
+        &self,
+    ) -> Result<(HealthStatus, Option<ServerLoad>), OllamaError> {
+        let client = self.client().await?;
         let base_url = Url::parse(&self.config.base_url)
             .map_err(|_| OllamaError::InvalidBaseUrl { url: self.config.base_url.clone() })?;
 
@@ -169,8 +300,17 @@ impl OllamaHealthCheck {
             .map_err(|_| OllamaError::InvalidBaseUrl { url: self.config.base_url.clone() })?;
 
         let start = std::time::Instant::now();
-        let response = client.get(models_url).send().await?;
+        let mut request = client.get(models_url);
+        if let Some(timeout) = self
+            .config
+            .request_timeouts
+            .timeout_for(RequestType::HealthCheck)
+        {
+            request = request.timeout(timeout);
+        }
+        let response = request.send().await?;
         let duration = start.elapsed();
+        let load = ServerLoad::from_headers(response.headers());
 
         let status = if response.status().is_success() {
             let body = response.text().await.unwrap_or_default();
@@ -205,30 +345,143 @@ impl OllamaHealthCheck {
         };
 
         info!("Ollama health check completed: {:?}", status);
-        Ok(status)
+        Ok((status, load))
+    }
+
+    /// Models currently loaded into memory, as listed by `/api/ps`
+    pub async fn loaded_models(&self) -> Result<Vec<String>, OllamaError> {
+        let client = self.client().await?;
+        let ps_url = Url::parse(&self.config.base_url)
+            .and_then(|base_url| base_url.join("api/ps"))
+            .map_err(|_| OllamaError::InvalidBaseUrl { url: self.config.base_url.clone() })?;
+
+        let mut request = client.get(ps_url);
+        if let Some(timeout) = self
+            .config
+            .request_timeouts
+            .timeout_for(RequestType::HealthCheck)
+        {
+            request = request.timeout(timeout);
+        }
+        let response = request.send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            return Err(OllamaError::HttpError { status: status.as_u16(), message });
+        }
+        let json = response
+            .json::<serde_json::Value>()
+            .await
+            .unwrap_or_default();
+        let models = json
+            .get("models")
+            .and_then(|models| models.as_array())
+            .map(|models| {
+                models
+                    .iter()
+                    .filter_map(|model| model.get("name").and_then(|name| name.as_str()))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(models)
     }
 
-    /// Discover available Ollama services on common ports
-    pub async fn discover_services(&self) -> Vec<String> {
-        let ports = vec![11434, 11435, 11436]; // Common Ollama ports
-        let hosts = vec!["localhost", "127.0.0.1"];
-        let mut discovered = Vec::new();
+    /// Generate a single token with `model`, failing when the service lists
+    /// models but cannot actually serve them. The model is unloaded right
+    /// after, so probing does not keep it resident in memory.
+    pub async fn check_generation(&self, model: &str) -> Result<(), OllamaError> {
+        let client = self.client().await?;
+        let generate_url = Url::parse(&self.config.base_url)
+            .and_then(|base_url| base_url.join("api/generate"))
+            .map_err(|_| OllamaError::InvalidBaseUrl { url: self.config.base_url.clone() })?;
+
+        debug!(model = %model, "Probing Ollama generation");
+
+        let body = serde_json::json!({
+            "model": model,
+            "prompt": "ping",
+            "stream": false,
+            "options": { "num_predict": 1 },
+            "keep_alive": 0,
+        });
+        let mut request = client.post(generate_url).json(&body);
+        if let Some(timeout) = self
+            .config
+            .request_timeouts
+            .timeout_for(RequestType::HealthCheck)
+        {
+            request = request.timeout(timeout);
+        }
+        let response = request.send().await?;
 
-        for host in hosts {
-            for port in &ports {
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            return Err(OllamaError::HttpError { status: status.as_u16(), message });
+        }
+        Ok(())
+    }
+
+    /// Probe every configured host and port for Ollama services, returning
+    /// the first healthy service found on each host in host order. Probes run
+    /// concurrently; once a host has a healthy service, its remaining probes
+    /// are cancelled.
+    pub async fn discover_services(
+        &self,
+        discovery: &DiscoveryConfig,
+    ) -> Vec<(String, HealthStatus)> {
+        let mut abort_handles: HashMap<&str, Vec<AbortHandle>> = HashMap::new();
+        let mut probes = Vec::new();
+        for host in &discovery.scan_hosts {
+            for port in &discovery.scan_ports {
                 let url = format!("http://{host}:{port}");
-                let config = OllamaConfig::new()
-                    .with_base_url(url.clone())
-                    .with_timeout(3);
-                let health_check = OllamaHealthCheck::new(config);
+                let health_check =
+                    OllamaHealthCheck::new(self.config.clone().with_base_url(url.clone()));
+                let (handle, registration) = AbortHandle::new_pair();
+                abort_handles.entry(host.as_str()).or_default().push(handle);
+
+                let timeout = discovery.probe_timeout();
+                probes.push(Abortable::new(
+                    async move {
+                        match tokio::time::timeout(timeout, health_check.check_health()).await {
+                            Ok(Ok(status @ HealthStatus::Healthy { .. })) => {
+                                Some((host.as_str(), url, status))
+                            }
+                            Ok(_) => None,
+                            Err(_) => {
+                                debug!(url = %url, "Discovery probe timed out");
+                                None
+                            }
+                        }
+                    },
+                    registration,
+                ));
+            }
+        }
 
-                if let Ok(HealthStatus::Healthy { .. }) = health_check.check_health().await {
-                    discovered.push(url);
-                }
+        let mut discovered: HashMap<&str, (String, HealthStatus)> = HashMap::new();
+        let mut results = stream::iter(probes).buffer_unordered(discovery.concurrency.max(1));
+        while let Some(result) = results.next().await {
+            let Ok(Some((host, url, status))) = result else {
+                continue;
+            };
+            if discovered.contains_key(host) {
+                continue;
             }
+            for handle in &abort_handles[host] {
+                handle.abort();
+            }
+            info!("Discovered Ollama service at {}", url);
+            discovered.insert(host, (url, status));
         }
 
-        discovered
+        discovery
+            .scan_hosts
+            .iter()
+            .filter_map(|host| discovered.remove(host.as_str()))
+            .collect()
     }
 }
 
@@ -260,19 +513,11 @@ impl HealthStatus {
             HealthStatus::Healthy { .. } | HealthStatus::Degraded { .. }
         )
     }
-
-    /// Get response time
-    pub fn response_time(&self) -> Duration {
-        match self {
-            HealthStatus::Healthy { response_time, .. }
-            | HealthStatus::Degraded { response_time, .. }
-            | HealthStatus::Unhealthy { response_time, .. } => *response_time,
-        }
-    }
 }
 
 #[cfg(test)]
 mod tests {
+    use forge_app::domain::{Context, ModelId};
     use pretty_assertions::assert_eq;
 
     use super::*;
@@ -349,7 +594,143 @@ mod tests {
     async fn test_health_check_creation() {
         let config = OllamaConfig::default();
         let fixture = OllamaHealthCheck::new(config);
-        // Just test that it can be created
-        assert!(true);
+        // The HTTP client is built lazily on the first probe
+        assert!(fixture.client.get().is_none());
+    }
+
+    #[test]
+    fn test_request_timeouts_per_type() {
+        let fixture = RequestTimeouts::default();
+
+        assert_eq!(
+            fixture.timeout_for(RequestType::HealthCheck),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            fixture.timeout_for(RequestType::Discovery),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(fixture.timeout_for(RequestType::Inference), None);
+        assert_eq!(fixture.inter_token_timeout(), Some(Duration::from_secs(60)));
+    }
+
+    #[tokio::test]
+    async fn test_health_check_uses_short_timeout() {
+        let url = crate::mock_server::spawn_delayed_server(
+            Duration::from_millis(300),
+            "application/json",
+            serde_json::json!({"models": []}).to_string(),
+        )
+        .await;
+        let timeouts = RequestTimeouts::default()
+            .health_check_ms(100u64)
+            .inference_ms(5_000u64);
+        let fixture = OllamaHealthCheck::new(
+            OllamaConfig::new()
+                .with_base_url(url)
+                .with_request_timeouts(timeouts),
+        );
+
+        let actual = fixture.check_health().await;
+
+        assert!(matches!(actual, Err(OllamaError::RequestTimeout { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_inference_is_bounded_by_request_timeout() -> anyhow::Result<()> {
+        let url = crate::mock_server::spawn_delayed_server(
+            Duration::from_secs(5),
+            "text/event-stream",
+            String::new(),
+        )
+        .await;
+        let fixture = OllamaConfig::new()
+            .with_base_url(url)
+            .with_request_timeout(Duration::from_millis(100))
+            .create_provider()?;
+
+        let started = std::time::Instant::now();
+        let error = fixture
+            .chat(ModelId::new("llama3.2"), Context::default())
+            .await?
+            .next()
+            .await
+            .unwrap()
+            .unwrap_err();
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(matches!(
+            error.downcast_ref::<OllamaError>(),
+            Some(OllamaError::RequestTimeout { timeout_ms: 100 })
+        ));
+        assert_eq!(
+            crate::retry::FailureKind::classify(&error),
+            crate::retry::FailureKind::Transient
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pull_is_not_bounded_by_request_timeout() -> anyhow::Result<()> {
+        let server = crate::mock_server::MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/api/pull",
+                crate::mock_server::ScriptedResponse::ndjson(vec![
+                    serde_json::json!({"status": "success"}),
+                ])
+                .with_delay(Duration::from_millis(300)),
+            )
+            .start()
+            .await;
+        let fixture = OllamaConfig::new()
+            .with_base_url(server.url())
+            .with_request_timeout(Duration::from_millis(100))
+            .create_provider()?;
+
+        let actual: Vec<_> =
+            futures::TryStreamExt::try_collect(fixture.pull_model("llama3.2:latest")).await?;
+
+        assert_eq!(actual.last().map(|update| update.is_success()), Some(true));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_health_check_reports_scripted_latency_and_load() {
+        let server = crate::mock_server::MockOllamaServer::builder()
+            .on(
+                "GET",
+                "/api/tags",
+                crate::mock_server::ScriptedResponse::json(200, serde_json::json!({"models": []}))
+                    .with_delay(Duration::from_millis(50))
+                    .with_header("x-queue-depth", "2"),
+            )
+            .start()
+            .await;
+        let fixture = OllamaHealthCheck::new(OllamaConfig::new().with_base_url(server.url()));
+
+        let (status, load) = fixture.check_health_with_load().await.unwrap();
+
+        assert!(matches!(
+            status,
+            HealthStatus::Healthy { response_time, .. } if response_time >= Duration::from_millis(50)
+        ));
+        assert_eq!(load.and_then(|load| load.queue_depth), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_check_generation_unloads_probed_model() {
+        let mut server = crate::mock_server::MockServer::new().await;
+        let generate = server
+            .mock_ollama_generate(
+                serde_json::json!({ "model": "llama3.2", "keep_alive": 0 }),
+                200,
+            )
+            .await;
+        let fixture = OllamaHealthCheck::new(OllamaConfig::new().with_base_url(server.url()));
+
+        fixture.check_generation("llama3.2").await.unwrap();
+
+        generate.assert_async().await;
     }
 }
@@ -1,6 +1,5 @@
 use anyhow::Context as _;
 use forge_app::domain::{Context, ContextMessage, ModelId};
-use tokio_stream::StreamExt;
 use tracing::info;
 
 use crate::ollama::error::OllamaError;
@@ -56,10 +55,8 @@ async fn test_ollama_error_handling() -> anyhow::Result<()> {
     assert!(user_message.contains("ollama pull"));
 
     // Test connection failed error
-    let reqwest_error = reqwest::Error::from(reqwest::Error::from(std::io::Error::new(
-        std::io::ErrorKind::ConnectionRefused,
-        "Connection refused",
-    )));
+    // Nothing listens on port 1, so the connection is refused
+    let reqwest_error = reqwest::get("http://127.0.0.1:1").await.unwrap_err();
     let error = OllamaError::connection_failed("http://localhost:11434".to_string(), reqwest_error);
     assert!(error.is_service_unavailable());
     assert!(error.is_retryable());
@@ -113,6 +110,8 @@ async fn test_ollama_conversation_compatibility() -> anyhow::Result<()> {
         ))
         .add_message(ContextMessage::assistant(
             "I'm doing well, thank you! How can I help you today?",
+            None,
+            None,
         ))
         .add_message(ContextMessage::user(
             "What's the weather like?",
@@ -123,17 +122,22 @@ async fn test_ollama_conversation_compatibility() -> anyhow::Result<()> {
     let request = crate::ollama::request::ChatRequest::try_from(context)?
         .model("llama3.2".to_string())
         .stream(true);
+    let request = serde_json::to_value(&request)?;
 
     // Validate request structure
-    assert_eq!(request.model, "llama3.2");
-    assert_eq!(request.stream, Some(true));
-    assert!(!request.messages.is_empty());
+    assert_eq!(request["model"], "llama3.2");
+    assert_eq!(request["stream"], true);
 
     // Validate message conversion
-    let messages = &request.messages;
-    assert!(messages.iter().any(|m| m.role == "system"));
-    assert!(messages.iter().any(|m| m.role == "user"));
-    assert!(messages.iter().any(|m| m.role == "assistant"));
+    let roles: Vec<_> = request["messages"]
+        .as_array()
+        .context("messages should be an array")?
+        .iter()
+        .filter_map(|message| message["role"].as_str())
+        .collect();
+    assert!(roles.contains(&"system"));
+    assert!(roles.contains(&"user"));
+    assert!(roles.contains(&"assistant"));
 
     info!("Ollama conversation compatibility test completed successfully");
     Ok(())
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::retry::FailureKind;
+
 /// Comprehensive error types for Ollama provider operations
 #[derive(Debug, Error)]
 pub enum OllamaError {
@@ -28,6 +30,9 @@ pub enum OllamaError {
     #[error("Model '{model}' is currently loading. Please wait and try again")]
     ModelLoading { model: String },
 
+    #[error("Model '{model}' does not support embeddings")]
+    EmbeddingsUnsupported { model: String },
+
     #[error("Model '{model}' failed to load: {reason}")]
     ModelLoadFailed { model: String, reason: String },
 
@@ -38,8 +43,8 @@ pub enum OllamaError {
     #[error("Request payload too large: {size} bytes exceeds maximum allowed")]
     PayloadTooLarge { size: usize },
 
-    #[error("Request timeout after {timeout_seconds} seconds")]
-    RequestTimeout { timeout_seconds: u64 },
+    #[error("Request timeout after {timeout_ms}ms")]
+    RequestTimeout { timeout_ms: u64 },
 
     /// Response parsing errors
     #[error("Failed to parse response from Ollama: {message}")]
@@ -58,6 +63,14 @@ pub enum OllamaError {
     #[error("Stream parsing failed: {message}")]
     StreamParsingFailed { message: String },
 
+    #[error("Stream stalled: no data received for {gap_ms}ms")]
+    StreamStalled { gap_ms: u64 },
+
+    /// The response was well-formed but shaped differently than expected,
+    /// usually because the server runs an incompatible API version
+    #[error("Protocol mismatch with {provider}: {detail}")]
+    ProtocolMismatch { provider: String, detail: String },
+
     /// Resource and system errors
     #[error("Insufficient system resources: {message}")]
     InsufficientResources { message: String },
@@ -102,6 +115,11 @@ impl OllamaError {
         Self::ModelLoading { model }
     }
 
+    /// Create an error for a model that cannot produce embeddings
+    pub fn embeddings_unsupported(model: String) -> Self {
+        Self::EmbeddingsUnsupported { model }
+    }
+
     /// Create a model load failed error
     pub fn model_load_failed(model: String, reason: String) -> Self {
         Self::ModelLoadFailed { model, reason }
@@ -127,6 +145,32 @@ impl OllamaError {
         Self::HttpError { status, message }
     }
 
+    /// Create a protocol mismatch error
+    pub fn protocol_mismatch(provider: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self::ProtocolMismatch { provider: provider.into(), detail: detail.into() }
+    }
+
+    /// Classify a deserialization failure. Valid JSON with an unexpected shape
+    /// (a moved field or changed type) is reported as a protocol mismatch;
+    /// anything else is passed to `otherwise`.
+    pub fn from_deserialize(
+        error: serde_json::Error,
+        provider: &str,
+        otherwise: impl FnOnce(String) -> Self,
+    ) -> Self {
+        match error.classify() {
+            serde_json::error::Category::Data => {
+                Self::protocol_mismatch(provider, error.to_string())
+            }
+            _ => otherwise(error.to_string()),
+        }
+    }
+
+    /// Check if this error indicates a protocol mismatch
+    pub fn is_protocol_mismatch(&self) -> bool {
+        matches!(self, OllamaError::ProtocolMismatch { .. })
+    }
+
     /// Check if this error indicates the service is unavailable
     pub fn is_service_unavailable(&self) -> bool {
         matches!(
@@ -144,12 +188,45 @@ impl OllamaError {
             OllamaError::ServiceUnavailable { .. }
                 | OllamaError::ConnectionFailed { .. }
                 | OllamaError::RequestTimeout { .. }
+                | OllamaError::StreamStalled { .. }
                 | OllamaError::ModelLoading { .. }
                 | OllamaError::RateLimitExceeded
                 | OllamaError::HttpError { status: 429 | 502 | 503 | 504, .. }
         )
     }
 
+    /// Classify this error for retry and fallback decisions
+    pub fn failure_kind(&self) -> FailureKind {
+        match self {
+            OllamaError::ConnectionFailed { .. }
+            | OllamaError::ServiceUnavailable { .. }
+            | OllamaError::ModelLoading { .. }
+            | OllamaError::RequestTimeout { .. }
+            | OllamaError::StreamInterrupted { .. }
+            | OllamaError::StreamStalled { .. }
+            | OllamaError::InsufficientResources { .. }
+            | OllamaError::Unknown { .. } => FailureKind::Transient,
+            OllamaError::RateLimitExceeded => FailureKind::RateLimited,
+            OllamaError::AuthenticationFailed { .. } => FailureKind::AuthFailure,
+            OllamaError::ModelNotFound { .. } | OllamaError::EmbeddingsUnsupported { .. } => {
+                FailureKind::ModelNotFound
+            }
+            OllamaError::HttpError { status, message } => {
+                FailureKind::from_status(*status, message)
+            }
+            OllamaError::ModelLoadFailed { .. }
+            | OllamaError::InvalidRequest { .. }
+            | OllamaError::PayloadTooLarge { .. }
+            | OllamaError::ResponseParsingFailed { .. }
+            | OllamaError::MalformedResponse
+            | OllamaError::UnexpectedResponseFormat
+            | OllamaError::StreamParsingFailed { .. }
+            | OllamaError::ProtocolMismatch { .. }
+            | OllamaError::InvalidConfiguration { .. }
+            | OllamaError::InvalidBaseUrl { .. } => FailureKind::Fatal,
+        }
+    }
+
     /// Check if this error indicates a client-side issue
     pub fn is_client_error(&self) -> bool {
         matches!(
@@ -157,6 +234,7 @@ impl OllamaError {
             OllamaError::InvalidRequest { .. }
                 | OllamaError::PayloadTooLarge { .. }
                 | OllamaError::ModelNotFound { .. }
+                | OllamaError::EmbeddingsUnsupported { .. }
                 | OllamaError::InvalidConfiguration { .. }
                 | OllamaError::InvalidBaseUrl { .. }
                 | OllamaError::HttpError { status: 400..=499, .. }
@@ -181,6 +259,11 @@ impl OllamaError {
                     "Model '{model}' is not available. Use 'ollama list' to see available models or 'ollama pull {model}' to download it"
                 )
             }
+            OllamaError::EmbeddingsUnsupported { model } => {
+                format!(
+                    "Model '{model}' cannot produce embeddings. Pull an embedding model such as 'nomic-embed-text' and use it instead"
+                )
+            }
             OllamaError::ModelLoading { model } => {
                 format!("Model '{model}' is currently loading. Please wait a moment and try again")
             }
@@ -191,9 +274,14 @@ impl OllamaError {
             OllamaError::RateLimitExceeded => {
                 "Too many requests. Please wait a moment before trying again".to_string()
             }
-            OllamaError::RequestTimeout { timeout_seconds } => {
+            OllamaError::RequestTimeout { timeout_ms } => {
+                format!(
+                    "Request timed out after {timeout_ms}ms. The model might be too large or the system is under heavy load"
+                )
+            }
+            OllamaError::ProtocolMismatch { provider, detail } => {
                 format!(
-                    "Request timed out after {timeout_seconds} seconds. The model might be too large or the system is under heavy load"
+                    "{provider} returned a response in an unexpected format ({detail}). The server may have been upgraded to an incompatible API version"
                 )
             }
             OllamaError::InvalidConfiguration { message } => {
@@ -209,7 +297,7 @@ impl From<reqwest::Error> for OllamaError {
     fn from(error: reqwest::Error) -> Self {
         if error.is_timeout() {
             OllamaError::RequestTimeout {
-                timeout_seconds: 30, // Default timeout assumption
+                timeout_ms: 30_000, // Default timeout assumption
             }
         } else if error.is_connect() {
             OllamaError::ConnectionFailed {
@@ -227,6 +315,7 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::ollama::response::ListModelsResponse;
 
     #[test]
     fn test_service_unavailable_detection() {
@@ -268,6 +357,14 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_embeddings_unsupported_is_model_not_found() {
+        let fixture = OllamaError::embeddings_unsupported("llama3.2".to_string());
+        let actual = (fixture.failure_kind(), fixture.is_retryable());
+        let expected = (FailureKind::ModelNotFound, false);
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_http_error_status_categorization() {
         let fixture = OllamaError::http_error(404, "Not Found".to_string());
@@ -280,4 +377,28 @@ mod tests {
         let expected = true;
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_deserialize_shape_error_is_protocol_mismatch() {
+        let fixture =
+            serde_json::from_str::<ListModelsResponse>(r#"{"models": {"name": "llama3.2"}}"#)
+                .unwrap_err();
+
+        let actual = OllamaError::from_deserialize(fixture, "ollama", |message| {
+            OllamaError::response_parsing_failed(message)
+        });
+
+        assert!(actual.is_protocol_mismatch());
+    }
+
+    #[test]
+    fn test_deserialize_syntax_error_is_not_protocol_mismatch() {
+        let fixture = serde_json::from_str::<serde_json::Value>("{not json").unwrap_err();
+
+        let actual = OllamaError::from_deserialize(fixture, "ollama", |message| {
+            OllamaError::response_parsing_failed(message)
+        });
+
+        assert!(!actual.is_protocol_mismatch());
+    }
 }
@@ -8,7 +8,8 @@ mod provider;
 mod request;
 mod response;
 
-pub use config::{HealthStatus, OllamaConfig, OllamaHealthCheck};
+pub use config::{HealthStatus, OllamaConfig, OllamaHealthCheck, RequestTimeouts};
+pub use error::OllamaError;
 #[cfg(test)]
 pub use integration_tests::OllamaIntegrationTest;
 pub use provider::Ollama;
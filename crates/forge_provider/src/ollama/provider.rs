@@ -1,20 +1,46 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
 use anyhow::Context as _;
 use derive_builder::Builder;
 use forge_app::domain::{ChatCompletionMessage, Context, Model, ModelId, ResultStream};
+use futures::Stream;
 use reqwest::{Client, Url};
 use reqwest_eventsource::{Event, RequestBuilderExt};
 use tokio_stream::StreamExt;
-use tracing::debug;
+use tracing::{debug, warn};
 
 use super::error::OllamaError;
-use super::request::ChatRequest;
-use super::response::{ChatResponse, ListModelsResponse};
+use super::request::{ChatRequest, EmbeddingsRequest, GenerateRequest, PullRequest, ShowRequest};
+use super::response::{
+    ChatResponse, EmbeddingsResponse, ListModelsResponse, PullProgress, PullResponse,
+    ShowModelResponse,
+};
+use super::RequestTimeouts;
+use crate::performance::RequestType;
 use crate::utils::format_http_context;
 
+/// Number of `/api/embeddings` requests sent at once, since the endpoint
+/// embeds a single prompt per request
+const EMBEDDINGS_BATCH_SIZE: usize = 8;
+
 #[derive(Clone, Builder)]
 pub struct Ollama {
     client: Client,
     base_url: Url,
+    #[builder(default)]
+    timeouts: RequestTimeouts,
+    /// Timeout for chat requests without a per-type inference timeout
+    #[builder(default)]
+    request_timeout: Option<Duration>,
+    /// Responses that did not match the expected protocol shape
+    #[builder(default)]
+    protocol_mismatches: Arc<AtomicU64>,
+    /// Sent with chat requests so the model stays loaded between them. Only
+    /// inference sets it; discovery and health checks never pin a model.
+    #[builder(default)]
+    keep_alive: Option<String>,
 }
 
 impl Ollama {
@@ -22,7 +48,12 @@ impl Ollama {
         OllamaBuilder::default()
     }
 
-    fn url(&self, path: &str) -> anyhow::Result<Url> {
+    /// Number of responses that did not match the expected protocol shape
+    pub fn protocol_mismatch_count(&self) -> u64 {
+        self.protocol_mismatches.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn url(&self, path: &str) -> anyhow::Result<Url> {
         // Validate the path doesn't contain certain patterns
         if path.contains("://") || path.contains("..") {
             anyhow::bail!("Invalid path: Contains forbidden patterns");
@@ -43,29 +74,34 @@ impl Ollama {
         model: ModelId,
         context: Context,
     ) -> ResultStream<ChatCompletionMessage, anyhow::Error> {
-        // Convert context to Ollama chat request
-        let request = ChatRequest::try_from(context)?
-            .model(model.as_str().to_string())
-            .stream(true);
-
+        let request = self.chat_request(&model, context)?;
         let url = self.url("api/chat")?;
         debug!(url = %url, model = %model, "Connecting to Ollama");
 
-        let es = self
-            .client
-            .post(url.clone())
-            .json(&request)
+        let inference_timeout = self
+            .timeouts
+            .timeout_for(RequestType::Inference)
+            .or(self.request_timeout);
+        let mut request_builder = self.client.post(url.clone()).json(&request);
+        if let Some(timeout) = inference_timeout {
+            request_builder = request_builder.timeout(timeout);
+        }
+
+        let es = request_builder
             .eventsource()
             .with_context(|| format_http_context(None, "POST", &url))?;
 
         let url_clone = url.clone();
         let url_clone2 = url.clone();
         let model_clone = model.clone();
+        let protocol_mismatches = self.protocol_mismatches.clone();
+        let inference_timeout = inference_timeout.unwrap_or_default();
         let stream = es
             .take_while(|message| !matches!(message, Err(reqwest_eventsource::Error::StreamEnded)))
             .then(move |event| {
                 let url_inner = url_clone.clone();
                 let model_inner = model_clone.clone();
+                let protocol_mismatches = protocol_mismatches.clone();
                 async move {
                 match event {
                     Ok(event) => match event {
@@ -76,7 +112,11 @@ impl Ollama {
                         }
                         Event::Message(message) => Some(
                             serde_json::from_str::<ChatResponse>(&message.data)
-                                .map_err(|e| OllamaError::StreamParsingFailed { message: e.to_string() })
+                                .map_err(|e| {
+                                    record_parse_error(&protocol_mismatches, e, |message| {
+                                        OllamaError::StreamParsingFailed { message }
+                                    })
+                                })
                                 .with_context(|| "Failed to parse Ollama event")
                                 .and_then(|event| {
                                     ChatCompletionMessage::try_from(event).with_context(|| {
@@ -116,6 +156,12 @@ impl Ollama {
                             debug!(response = ?response, "Invalid content type");
                             Some(Err(error).with_context(|| format!("Http Status: {status_code}")))
                         }
+                        reqwest_eventsource::Error::Transport(error) if error.is_timeout() => {
+                            debug!(error = ?error, "Ollama chat request timed out");
+                            Some(Err(anyhow::anyhow!(OllamaError::RequestTimeout {
+                                timeout_ms: inference_timeout.as_millis() as u64,
+                            })))
+                        }
                         error => {
                             tracing::error!(error = ?error, "Failed to receive chat completion event");
                             Some(Err(error.into()))
@@ -131,14 +177,306 @@ impl Ollama {
                 _ => response,
             });
 
-        Ok(Box::pin(stream.filter_map(|x| x)))
+        let stream = stream.filter_map(|x| x);
+
+        // Bound the gap between events rather than the whole generation
+        match self.timeouts.inter_token_timeout() {
+            Some(gap) => {
+                let mut elapsed = false;
+                let stream = stream.timeout(gap).map_while(move |item| {
+                    if elapsed {
+                        return None;
+                    }
+                    match item {
+                        Ok(item) => Some(item),
+                        Err(_) => {
+                            elapsed = true;
+                            debug!(gap_ms = gap.as_millis(), "Ollama stream stalled");
+                            Some(Err(anyhow::anyhow!(OllamaError::StreamStalled {
+                                gap_ms: gap.as_millis() as u64
+                            })))
+                        }
+                    }
+                });
+                Ok(Box::pin(stream))
+            }
+            None => Ok(Box::pin(stream)),
+        }
+    }
+
+    /// Convert `context` into a streaming chat request for `model`
+    fn chat_request(&self, model: &ModelId, context: Context) -> anyhow::Result<ChatRequest> {
+        let request = ChatRequest::try_from(context)?
+            .model(model.as_str().to_string())
+            .stream(true);
+        Ok(match &self.keep_alive {
+            Some(keep_alive) => request.keep_alive(keep_alive.clone()),
+            None => request,
+        })
+    }
+
+    /// Asks Ollama to load `model` into memory by issuing a generate request
+    /// without a prompt, keeping it loaded for the configured `keep_alive`.
+    pub async fn load_model(&self, model: &ModelId) -> anyhow::Result<()> {
+        let url = self.url("api/generate")?;
+        debug!(url = %url, model = %model, "Loading model into Ollama");
+
+        let request = GenerateRequest::default()
+            .model(model.as_str())
+            .stream(false);
+        let request = match &self.keep_alive {
+            Some(keep_alive) => request.keep_alive(keep_alive.clone()),
+            None => request,
+        };
+
+        let mut request_builder = self.client.post(url.clone()).json(&request);
+        if let Some(timeout) = self.timeouts.timeout_for(RequestType::ModelLoading) {
+            request_builder = request_builder.timeout(timeout);
+        }
+
+        let response = request_builder
+            .send()
+            .await
+            .map_err(|e| OllamaError::connection_failed(url.to_string(), e))
+            .with_context(|| format_http_context(None, "POST", &url))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let ollama_error = match status.as_u16() {
+                404 => OllamaError::model_not_found(model.as_str().to_string()),
+                _ => OllamaError::http_error(status.as_u16(), body),
+            };
+            return Err(anyhow::anyhow!(ollama_error))
+                .with_context(|| format_http_context(Some(status), "POST", &url))
+                .with_context(|| format!("Failed to load model {model}"));
+        }
+
+        Ok(())
+    }
+
+    /// Asks Ollama to unload `model` from memory by issuing a generate request
+    /// with `keep_alive` set to zero.
+    pub async fn unload_model(&self, model: &ModelId) -> anyhow::Result<()> {
+        let url = self.url("api/generate")?;
+        debug!(url = %url, model = %model, "Unloading model from Ollama");
+
+        let request = GenerateRequest::default()
+            .model(model.as_str())
+            .stream(false)
+            .keep_alive("0");
+
+        let mut request_builder = self.client.post(url.clone()).json(&request);
+        if let Some(timeout) = self.timeouts.timeout_for(RequestType::ModelLoading) {
+            request_builder = request_builder.timeout(timeout);
+        }
+
+        let response = request_builder
+            .send()
+            .await
+            .map_err(|e| OllamaError::connection_failed(url.to_string(), e))
+            .with_context(|| format_http_context(None, "POST", &url))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let ollama_error = match status.as_u16() {
+                404 => OllamaError::model_not_found(model.as_str().to_string()),
+                _ => OllamaError::http_error(status.as_u16(), body),
+            };
+            return Err(anyhow::anyhow!(ollama_error))
+                .with_context(|| format_http_context(Some(status), "POST", &url))
+                .with_context(|| format!("Failed to unload model {model}"));
+        }
+
+        Ok(())
+    }
+
+    /// Fetch `model`'s modelfile parameters, template and capabilities from
+    /// `/api/show`
+    pub async fn show_model(&self, model: &ModelId) -> anyhow::Result<ShowModelResponse> {
+        let url = self.url("api/show")?;
+        debug!(url = %url, model = %model, "Fetching model details from Ollama");
+
+        let request = ShowRequest { model: model.as_str().to_string() };
+        let mut request_builder = self.client.post(url.clone()).json(&request);
+        if let Some(timeout) = self.timeouts.timeout_for(RequestType::Discovery) {
+            request_builder = request_builder.timeout(timeout);
+        }
+
+        let response = request_builder
+            .send()
+            .await
+            .map_err(|e| OllamaError::connection_failed(url.to_string(), e))
+            .with_context(|| format_http_context(None, "POST", &url))?;
+
+        let status = response.status();
+        let ctx_msg = format_http_context(Some(status), "POST", &url);
+        let text = response
+            .text()
+            .await
+            .with_context(|| ctx_msg.clone())
+            .with_context(|| "Failed to decode response into text")?;
+        if !status.is_success() {
+            let ollama_error = match status.as_u16() {
+                404 => OllamaError::model_not_found(model.as_str().to_string()),
+                _ => OllamaError::http_error(status.as_u16(), text),
+            };
+            return Err(anyhow::anyhow!(ollama_error))
+                .with_context(|| ctx_msg)
+                .with_context(|| format!("Failed to fetch details for model {model}"));
+        }
+
+        serde_json::from_str(&text)
+            .map_err(|e| {
+                record_parse_error(
+                    &self.protocol_mismatches,
+                    e,
+                    OllamaError::response_parsing_failed,
+                )
+            })
+            .with_context(|| ctx_msg)
+            .with_context(|| "Failed to deserialize model details response")
+    }
+
+    /// Embed each of `input` with `model`, returning one vector per input in
+    /// the same order
+    pub async fn embeddings(
+        &self,
+        model: &ModelId,
+        input: &[String],
+    ) -> anyhow::Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(input.len());
+        for batch in input.chunks(EMBEDDINGS_BATCH_SIZE) {
+            let batch = batch.iter().map(|prompt| self.embedding(model, prompt));
+            embeddings.extend(futures::future::try_join_all(batch).await?);
+        }
+        Ok(embeddings)
+    }
+
+    async fn embedding(&self, model: &ModelId, prompt: &str) -> anyhow::Result<Vec<f32>> {
+        let url = self.url("api/embeddings")?;
+        debug!(url = %url, model = %model, "Requesting embedding from Ollama");
+
+        let request = EmbeddingsRequest {
+            model: model.as_str().to_string(),
+            prompt: prompt.to_string(),
+        };
+        let mut request_builder = self.client.post(url.clone()).json(&request);
+        if let Some(timeout) = self.timeouts.timeout_for(RequestType::Inference) {
+            request_builder = request_builder.timeout(timeout);
+        }
+
+        let response = request_builder
+            .send()
+            .await
+            .map_err(|e| OllamaError::connection_failed(url.to_string(), e))
+            .with_context(|| format_http_context(None, "POST", &url))?;
+
+        let status = response.status();
+        let ctx_msg = format_http_context(Some(status), "POST", &url);
+        let text = response
+            .text()
+            .await
+            .with_context(|| ctx_msg.clone())
+            .with_context(|| "Failed to decode response into text")?;
+        if !status.is_success() {
+            let ollama_error = match status.as_u16() {
+                404 => OllamaError::model_not_found(model.as_str().to_string()),
+                _ if text.contains("does not support embeddings") => {
+                    OllamaError::embeddings_unsupported(model.as_str().to_string())
+                }
+                _ => OllamaError::http_error(status.as_u16(), text),
+            };
+            return Err(anyhow::anyhow!(ollama_error))
+                .with_context(|| ctx_msg)
+                .with_context(|| format!("Failed to embed input with model {model}"));
+        }
+
+        let response: EmbeddingsResponse = serde_json::from_str(&text)
+            .map_err(|e| {
+                record_parse_error(
+                    &self.protocol_mismatches,
+                    e,
+                    OllamaError::response_parsing_failed,
+                )
+            })
+            .with_context(|| ctx_msg)
+            .with_context(|| "Failed to deserialize embeddings response")?;
+
+        // Older Ollama versions answer with an empty vector instead of an error
+        // when the model has no embedding support
+        if response.embedding.is_empty() {
+            return Err(anyhow::anyhow!(OllamaError::embeddings_unsupported(
+                model.as_str().to_string()
+            )));
+        }
+        Ok(response.embedding)
+    }
+
+    /// Download `name` through `/api/pull`, yielding Ollama's progress updates
+    /// as they arrive.
+    ///
+    /// The stream ends after the final `success` update, at which point the
+    /// model is listed by [`Ollama::models`]. No overall timeout is applied,
+    /// so large downloads are not cut off; a stalled connection still fails
+    /// once the read timeout passes.
+    pub fn pull_model(
+        &self,
+        name: &str,
+    ) -> impl Stream<Item = anyhow::Result<PullProgress>> + Send + 'static {
+        let pull = PullStream {
+            ollama: self.clone(),
+            name: name.to_string(),
+            response: None,
+            buffer: Vec::new(),
+            finished: false,
+        };
+        futures::stream::unfold(Some(pull), |pull| async move {
+            let mut pull = pull?;
+            let item = pull.next().await?;
+            let next = item.is_ok().then_some(pull);
+            Some((item, next))
+        })
+    }
+
+    async fn start_pull(&self, name: &str) -> anyhow::Result<reqwest::Response> {
+        let url = self.url("api/pull")?;
+        debug!(url = %url, model = %name, "Pulling model into Ollama");
+
+        let request = PullRequest { model: name.to_string(), stream: true };
+        let response = self
+            .client
+            .post(url.clone())
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| OllamaError::connection_failed(url.to_string(), e))
+            .with_context(|| format_http_context(None, "POST", &url))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow::anyhow!(OllamaError::http_error(
+                status.as_u16(),
+                body
+            )))
+            .with_context(|| format_http_context(Some(status), "POST", &url))
+            .with_context(|| format!("Failed to pull model {name}"));
+        }
+
+        Ok(response)
     }
 
     pub async fn models(&self) -> anyhow::Result<Vec<Model>> {
         let url = self.url("api/tags")?;
         debug!(url = %url, "Fetching models from Ollama");
 
-        let result = self.client.get(url.clone()).send().await;
+        let mut request_builder = self.client.get(url.clone());
+        if let Some(timeout) = self.timeouts.timeout_for(RequestType::Discovery) {
+            request_builder = request_builder.timeout(timeout);
+        }
+        let result = request_builder.send().await;
 
         match result {
             Err(error) => {
@@ -150,7 +488,13 @@ impl Ollama {
 
                 // Convert to OllamaError for better user experience
                 let ollama_error = if error.is_timeout() {
-                    OllamaError::RequestTimeout { timeout_seconds: 30 }
+                    OllamaError::RequestTimeout {
+                        timeout_ms: self
+                            .timeouts
+                            .timeout_for(RequestType::Discovery)
+                            .map(|timeout| timeout.as_millis() as u64)
+                            .unwrap_or_default(),
+                    }
                 } else if error.is_connect() {
                     OllamaError::connection_failed(url.to_string(), error)
                 } else {
@@ -182,7 +526,13 @@ impl Ollama {
 
                 if status.is_success() {
                     let response: ListModelsResponse = serde_json::from_str(&text)
-                        .map_err(|e| OllamaError::response_parsing_failed(e.to_string()))
+                        .map_err(|e| {
+                            record_parse_error(
+                                &self.protocol_mismatches,
+                                e,
+                                OllamaError::response_parsing_failed,
+                            )
+                        })
                         .with_context(|| ctx_msg)
                         .with_context(|| "Failed to deserialize models response")?;
                     Ok(response.models.into_iter().map(Into::into).collect())
@@ -203,12 +553,108 @@ impl Ollama {
     }
 }
 
+/// State of an in-flight `/api/pull`, which sends the request on first poll
+/// and then splits the newline-delimited JSON body into progress updates
+struct PullStream {
+    ollama: Ollama,
+    name: String,
+    response: Option<reqwest::Response>,
+    buffer: Vec<u8>,
+    finished: bool,
+}
+
+impl PullStream {
+    /// The next progress update, or `None` once the pull has succeeded
+    async fn next(&mut self) -> Option<anyhow::Result<PullProgress>> {
+        let response = match &mut self.response {
+            Some(response) => response,
+            None => match self.ollama.start_pull(&self.name).await {
+                Ok(response) => self.response.insert(response),
+                Err(error) => return Some(Err(error)),
+            },
+        };
+
+        loop {
+            if let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                if line.trim_ascii().is_empty() {
+                    continue;
+                }
+                let item = parse_pull_line(&line, &self.name, &self.ollama.protocol_mismatches);
+                if let Ok(progress) = &item {
+                    self.finished |= progress.is_success();
+                }
+                return Some(item);
+            }
+
+            let reason = match response.chunk().await {
+                Ok(Some(chunk)) => {
+                    self.buffer.extend_from_slice(&chunk);
+                    continue;
+                }
+                Ok(None) if !self.buffer.trim_ascii().is_empty() => {
+                    self.buffer.push(b'\n');
+                    continue;
+                }
+                Ok(None) if self.finished => return None,
+                Ok(None) => "pull ended before reporting success".to_string(),
+                Err(e) => e.to_string(),
+            };
+            return Some(
+                Err(anyhow::anyhow!(OllamaError::stream_interrupted(reason)))
+                    .with_context(|| format!("Failed to pull model {}", self.name)),
+            );
+        }
+    }
+}
+
+/// Parse one line of a pull response, turning a reported error into an `Err`
+fn parse_pull_line(
+    line: &[u8],
+    name: &str,
+    protocol_mismatches: &AtomicU64,
+) -> anyhow::Result<PullProgress> {
+    let response = serde_json::from_slice(line)
+        .map_err(|e| {
+            record_parse_error(protocol_mismatches, e, OllamaError::response_parsing_failed)
+        })
+        .with_context(|| "Failed to deserialize pull progress")?;
+    match response {
+        PullResponse::Progress(progress) => Ok(progress),
+        PullResponse::Error { error } => Err(anyhow::anyhow!(OllamaError::model_load_failed(
+            name.to_string(),
+            error
+        )))
+        .with_context(|| format!("Failed to pull model {name}")),
+    }
+}
+
+/// Convert a deserialization failure into an [`OllamaError`], counting it when
+/// it points to a protocol version mismatch
+fn record_parse_error(
+    protocol_mismatches: &AtomicU64,
+    error: serde_json::Error,
+    otherwise: impl FnOnce(String) -> OllamaError,
+) -> OllamaError {
+    let error = OllamaError::from_deserialize(error, "ollama", otherwise);
+    if let OllamaError::ProtocolMismatch { .. } = &error {
+        protocol_mismatches.fetch_add(1, Ordering::Relaxed);
+        // The detail can quote response content, so it is not logged
+        warn!("Ollama response does not match the expected protocol");
+    }
+    error
+}
+
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use forge_app::domain::ContextMessage;
 
     use super::*;
-    use crate::mock_server::{normalize_ports, MockServer};
+    use crate::mock_server::{
+        normalize_ports, spawn_delayed_server, MockOllamaServer, MockServer, ScriptedResponse,
+    };
 
     fn create_ollama(base_url: &str) -> anyhow::Result<Ollama> {
         Ok(Ollama::builder()
@@ -301,6 +747,30 @@ mod tests {
         insta::assert_snapshot!(serde_json::to_string_pretty(&request).unwrap());
     }
 
+    #[test]
+    fn test_chat_request_keep_alive() -> anyhow::Result<()> {
+        let fixture = |keep_alive: Option<&str>| -> anyhow::Result<serde_json::Value> {
+            let ollama = Ollama::builder()
+                .client(Client::new())
+                .base_url(Url::parse("http://localhost:11434")?)
+                .keep_alive(keep_alive.map(str::to_string))
+                .build()?;
+            let context = Context::default().add_message(ContextMessage::user(
+                "Hello",
+                ModelId::new("llama3.2").into(),
+            ));
+            let request = ollama.chat_request(&ModelId::new("llama3.2"), context)?;
+            Ok(serde_json::to_value(request)?)
+        };
+
+        let configured = fixture(Some("30m"))?;
+        let unset = fixture(None)?;
+
+        assert_eq!(configured["keep_alive"], "30m");
+        assert!(unset.get("keep_alive").is_none());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_fetch_models_success() -> anyhow::Result<()> {
         let mut fixture = MockServer::new().await;
@@ -350,4 +820,412 @@ mod tests {
         assert!(actual.is_empty());
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_unload_model_sends_zero_keep_alive() -> anyhow::Result<()> {
+        let mut fixture = MockServer::new().await;
+        let mock = fixture
+            .mock_ollama_generate(
+                serde_json::json!({"model": "llama3.2:latest", "keep_alive": "0"}),
+                200,
+            )
+            .await;
+
+        let ollama = create_ollama(&fixture.url())?;
+        ollama
+            .unload_model(&ModelId::new("llama3.2:latest"))
+            .await?;
+
+        mock.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_preserve_input_order() -> anyhow::Result<()> {
+        let mut fixture = MockServer::new().await;
+        let input: Vec<String> = (0..10).map(|i| format!("prompt {i}")).collect();
+        let mut mocks = Vec::new();
+        for (i, prompt) in input.iter().enumerate() {
+            let body = serde_json::json!({"embedding": [i as f32, 0.5]});
+            mocks.push(fixture.mock_ollama_embeddings(prompt, body, 200).await);
+        }
+        let ollama = create_ollama(&fixture.url())?;
+
+        let actual = ollama
+            .embeddings(&ModelId::new("nomic-embed-text"), &input)
+            .await?;
+
+        let expected: Vec<Vec<f32>> = (0..10).map(|i| vec![i as f32, 0.5]).collect();
+        assert_eq!(actual, expected);
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_unsupported_model_is_model_not_found() -> anyhow::Result<()> {
+        let mut fixture = MockServer::new().await;
+        let _mock = fixture
+            .mock_ollama_embeddings(
+                "hello",
+                serde_json::json!({"error": "\"llama3.2\" does not support embeddings"}),
+                500,
+            )
+            .await;
+        let ollama = create_ollama(&fixture.url())?;
+
+        let error = ollama
+            .embeddings(&ModelId::new("llama3.2"), &["hello".to_string()])
+            .await
+            .unwrap_err();
+
+        let actual = crate::retry::FailureKind::classify(&error);
+        let expected = crate::retry::FailureKind::ModelNotFound;
+        assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    fn create_ollama_with_timeouts(
+        base_url: &str,
+        timeouts: RequestTimeouts,
+    ) -> anyhow::Result<Ollama> {
+        Ok(Ollama::builder()
+            .client(Client::new())
+            .base_url(Url::parse(base_url)?)
+            .timeouts(timeouts)
+            .build()
+            .unwrap())
+    }
+
+    fn create_chat_event() -> String {
+        let event = serde_json::json!({
+            "model": "llama3.2",
+            "created_at": "2025-05-04T17:37:44Z",
+            "message": {"role": "assistant", "content": "Hello"},
+            "done": true
+        });
+        format!("data: {event}\n\n")
+    }
+
+    #[tokio::test]
+    async fn test_inference_uses_long_timeout() -> anyhow::Result<()> {
+        let url = spawn_delayed_server(
+            Duration::from_millis(300),
+            "text/event-stream",
+            create_chat_event(),
+        )
+        .await;
+        let timeouts = RequestTimeouts::default()
+            .health_check_ms(100u64)
+            .inference_ms(5_000u64);
+        let fixture = create_ollama_with_timeouts(&url, timeouts)?;
+
+        let actual = fixture
+            .chat(ModelId::new("llama3.2"), Context::default())
+            .await?
+            .next()
+            .await;
+
+        assert!(matches!(actual, Some(Ok(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_inference_timeout_is_enforced() -> anyhow::Result<()> {
+        let url = spawn_delayed_server(
+            Duration::from_millis(500),
+            "text/event-stream",
+            create_chat_event(),
+        )
+        .await;
+        let timeouts = RequestTimeouts::default().inference_ms(100u64);
+        let fixture = create_ollama_with_timeouts(&url, timeouts)?;
+
+        let actual = fixture
+            .chat(ModelId::new("llama3.2"), Context::default())
+            .await?
+            .next()
+            .await;
+
+        assert!(matches!(actual, Some(Err(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stalled_stream_reports_gap_in_milliseconds() -> anyhow::Result<()> {
+        let server = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/api/chat",
+                ScriptedResponse::chat_stream("llama3.2", &["Hel", "lo"])
+                    .with_chunk_interval(Duration::from_millis(500)),
+            )
+            .start()
+            .await;
+        let timeouts = RequestTimeouts::default().inter_token_ms(50u64);
+        let fixture = create_ollama_with_timeouts(&server.url(), timeouts)?;
+
+        let actual = fixture
+            .chat(ModelId::new("llama3.2"), Context::default())
+            .await?
+            .filter_map(|item| item.err())
+            .next()
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            actual.downcast_ref::<OllamaError>(),
+            Some(OllamaError::StreamStalled { gap_ms: 50 })
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_discovery_uses_discovery_timeout() -> anyhow::Result<()> {
+        let url = spawn_delayed_server(
+            Duration::from_millis(300),
+            "application/json",
+            create_empty_response().to_string(),
+        )
+        .await;
+
+        let fast =
+            create_ollama_with_timeouts(&url, RequestTimeouts::default().discovery_ms(100u64))?;
+        let slow =
+            create_ollama_with_timeouts(&url, RequestTimeouts::default().discovery_ms(5_000u64))?;
+
+        assert!(fast.models().await.is_err());
+        assert!(slow.models().await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_models_protocol_mismatch() -> anyhow::Result<()> {
+        // A future protocol version that nests models under a different shape
+        let mut fixture = MockServer::new().await;
+        let mock = fixture
+            .mock_ollama_models(
+                serde_json::json!({"models": {"items": [{"name": "llama3.2"}]}}),
+                200,
+            )
+            .await;
+        let ollama = create_ollama(&fixture.url())?;
+
+        let actual = ollama.models().await.unwrap_err();
+
+        mock.assert_async().await;
+        let error = actual.downcast_ref::<OllamaError>().unwrap();
+        assert!(error.is_protocol_mismatch());
+        assert_eq!(ollama.protocol_mismatch_count(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chat_protocol_mismatch_is_counted() -> anyhow::Result<()> {
+        // An older protocol that returned the message as a plain string
+        let event = serde_json::json!({
+            "model": "llama3.2",
+            "created_at": "2025-05-04T17:37:44Z",
+            "message": "Hello",
+            "done": true
+        });
+        let url = spawn_delayed_server(
+            Duration::ZERO,
+            "text/event-stream",
+            format!("data: {event}\n\n"),
+        )
+        .await;
+        let ollama = create_ollama(&url)?;
+
+        let actual = ollama
+            .chat(ModelId::new("llama3.2"), Context::default())
+            .await?
+            .next()
+            .await
+            .unwrap()
+            .unwrap_err();
+
+        let error = actual.downcast_ref::<OllamaError>().unwrap();
+        assert!(error.is_protocol_mismatch());
+        assert_eq!(ollama.protocol_mismatch_count(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_surfaces_mid_stream_drop() -> anyhow::Result<()> {
+        let fixture = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/api/chat",
+                ScriptedResponse::chat_stream("llama3.2", &["Hel", "lo", " world"]).drop_after(2),
+            )
+            .start()
+            .await;
+        let ollama = create_ollama(&fixture.url())?;
+
+        let mut stream = ollama
+            .chat(ModelId::new("llama3.2"), Context::default())
+            .await?;
+        let mut text = String::new();
+        for _ in 0..2 {
+            let message = stream.next().await.unwrap()?;
+            text.push_str(message.content.unwrap().as_str());
+        }
+        let actual = stream.next().await.unwrap();
+
+        assert_eq!(text, "Hello");
+        assert!(actual.is_err());
+        assert_eq!(fixture.hits("POST", "/api/chat"), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_completes_from_script() -> anyhow::Result<()> {
+        let fixture = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/api/chat",
+                ScriptedResponse::chat_stream("llama3.2", &["Hel", "lo"])
+                    .with_chunk_interval(Duration::from_millis(5)),
+            )
+            .start()
+            .await;
+        let ollama = create_ollama(&fixture.url())?;
+
+        let stream = ollama
+            .chat(ModelId::new("llama3.2"), Context::default())
+            .await?;
+        let messages: Vec<_> = stream.collect::<anyhow::Result<Vec<_>>>().await?;
+        let actual: String = messages
+            .iter()
+            .filter_map(|message| message.content.as_ref())
+            .map(|content| content.as_str())
+            .collect();
+
+        assert_eq!(actual, "Hello");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_models_recover_after_scripted_failures() -> anyhow::Result<()> {
+        let fixture = MockOllamaServer::builder()
+            .on("GET", "/api/tags", ScriptedResponse::connection_drop())
+            .on(
+                "GET",
+                "/api/tags",
+                ScriptedResponse::json(503, create_error_response("loading", 503)),
+            )
+            .tags(&["llama3.2:latest"])
+            .start()
+            .await;
+        let ollama = create_ollama(&fixture.url())?;
+
+        let dropped = ollama.models().await;
+        let unavailable = ollama.models().await;
+        let actual = ollama.models().await?;
+
+        assert!(dropped.is_err());
+        assert!(unavailable.is_err());
+        assert_eq!(actual.len(), 1);
+        assert_eq!(fixture.hits("GET", "/api/tags"), 3);
+        Ok(())
+    }
+
+    fn create_pull_progress(status: &str, completed: u64, total: u64) -> serde_json::Value {
+        serde_json::json!({
+            "status": status,
+            "digest": "sha256:dde5aa3fc5ff",
+            "completed": completed,
+            "total": total
+        })
+    }
+
+    #[tokio::test]
+    async fn test_pull_model_streams_progress_until_success() -> anyhow::Result<()> {
+        let fixture = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/api/pull",
+                ScriptedResponse::ndjson(vec![
+                    serde_json::json!({"status": "pulling manifest"}),
+                    create_pull_progress("pulling dde5aa3fc5ff", 1024, 4096),
+                    create_pull_progress("pulling dde5aa3fc5ff", 4096, 4096),
+                    serde_json::json!({"status": "success"}),
+                ]),
+            )
+            .tags(&["llama3.2:latest"])
+            .start()
+            .await;
+        let ollama = create_ollama(&fixture.url())?;
+
+        let progress = ollama
+            .pull_model("llama3.2:latest")
+            .collect::<anyhow::Result<Vec<_>>>()
+            .await?;
+        let models = ollama.models().await?;
+
+        let actual: Vec<_> = progress
+            .iter()
+            .map(|update| (update.status.as_str(), update.completed, update.total))
+            .collect();
+        let expected = vec![
+            ("pulling manifest", None, None),
+            ("pulling dde5aa3fc5ff", Some(1024), Some(4096)),
+            ("pulling dde5aa3fc5ff", Some(4096), Some(4096)),
+            ("success", None, None),
+        ];
+        assert_eq!(actual, expected);
+        assert_eq!(models[0].id.as_str(), "llama3.2:latest");
+        assert_eq!(fixture.hits("POST", "/api/pull"), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pull_model_surfaces_reported_error() -> anyhow::Result<()> {
+        let fixture = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/api/pull",
+                ScriptedResponse::ndjson(vec![
+                    serde_json::json!({"status": "pulling manifest"}),
+                    serde_json::json!({"error": "pull model manifest: file does not exist"}),
+                    serde_json::json!({"status": "success"}),
+                ]),
+            )
+            .start()
+            .await;
+        let ollama = create_ollama(&fixture.url())?;
+
+        let actual: Vec<_> = ollama.pull_model("missing:latest").collect().await;
+
+        assert_eq!(actual.len(), 2);
+        let error = actual[1].as_ref().unwrap_err();
+        assert!(format!("{error:?}").contains("file does not exist"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pull_model_fails_when_stream_ends_early() -> anyhow::Result<()> {
+        let fixture = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/api/pull",
+                ScriptedResponse::ndjson(vec![create_pull_progress(
+                    "pulling dde5aa3fc5ff",
+                    1024,
+                    4096,
+                )]),
+            )
+            .start()
+            .await;
+        let ollama = create_ollama(&fixture.url())?;
+
+        let actual: Vec<_> = ollama.pull_model("llama3.2:latest").collect().await;
+
+        assert_eq!(actual.len(), 2);
+        assert!(actual[0].is_ok());
+        assert!(actual[1].is_err());
+        Ok(())
+    }
 }
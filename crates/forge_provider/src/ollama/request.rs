@@ -58,3 +58,40 @@ impl TryFrom<forge_app::domain::Context> for ChatRequest {
         })
     }
 }
+
+/// Request body for Ollama's `/api/show` endpoint
+#[derive(Serialize)]
+pub struct ShowRequest {
+    pub model: String,
+}
+
+/// Request body for Ollama's `/api/embeddings` endpoint
+#[derive(Serialize)]
+pub struct EmbeddingsRequest {
+    pub model: String,
+    pub prompt: String,
+}
+
+/// Request body for Ollama's `/api/pull` endpoint
+#[derive(Serialize)]
+pub struct PullRequest {
+    pub model: String,
+    pub stream: bool,
+}
+
+/// Request body for Ollama's `/api/generate` endpoint.
+///
+/// Only the fields needed for model lifecycle management are modelled; a
+/// request without a prompt loads or unloads the model depending on
+/// `keep_alive`.
+#[derive(Serialize, Default, Setters)]
+#[setters(into, strip_option)]
+pub struct GenerateRequest {
+    model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<String>,
+}
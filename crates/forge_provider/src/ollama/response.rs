@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use forge_app::domain::{ChatCompletionMessage, Content, Model, ModelId};
 use serde::Deserialize;
 
@@ -41,6 +43,99 @@ impl From<OllamaModel> for Model {
     }
 }
 
+// Response for /api/show endpoint
+#[derive(Deserialize, Debug, Default)]
+pub struct ShowModelResponse {
+    /// Modelfile `PARAMETER` lines, one `name value` pair per line
+    #[serde(default)]
+    pub parameters: Option<String>,
+    /// Prompt template; templates that render `.Tools` accept tool definitions
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Architecture metadata such as `llama.context_length`
+    #[serde(default)]
+    pub model_info: HashMap<String, serde_json::Value>,
+    /// Capabilities reported by newer Ollama versions, e.g. `tools` or
+    /// `thinking`
+    #[serde(default)]
+    pub capabilities: Option<Vec<String>>,
+}
+
+impl ShowModelResponse {
+    /// Context window the model runs with: `num_ctx` from the modelfile
+    /// parameters when set, otherwise the trained context length
+    pub fn context_length(&self) -> Option<u64> {
+        let num_ctx = self.parameters.as_deref().and_then(|parameters| {
+            parameters.lines().find_map(|line| {
+                let mut parts = line.split_whitespace();
+                (parts.next() == Some("num_ctx"))
+                    .then(|| parts.next()?.parse().ok())
+                    .flatten()
+            })
+        });
+        num_ctx.or_else(|| {
+            self.model_info
+                .iter()
+                .find(|(key, _)| key.ends_with(".context_length"))
+                .and_then(|(_, value)| value.as_u64())
+        })
+    }
+
+    /// Whether the model accepts tool definitions
+    pub fn tools_supported(&self) -> Option<bool> {
+        match &self.capabilities {
+            Some(capabilities) => Some(capabilities.iter().any(|c| c == "tools")),
+            None => self
+                .template
+                .as_deref()
+                .map(|template| template.contains(".Tools")),
+        }
+    }
+
+    /// Whether the model can emit reasoning, known only from `capabilities`
+    pub fn supports_reasoning(&self) -> Option<bool> {
+        self.capabilities
+            .as_ref()
+            .map(|capabilities| capabilities.iter().any(|c| c == "thinking"))
+    }
+}
+
+/// Response for the `/api/embeddings` endpoint
+#[derive(Deserialize, Debug)]
+pub struct EmbeddingsResponse {
+    pub embedding: Vec<f32>,
+}
+
+/// One line of the streamed `/api/pull` response
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum PullResponse {
+    Error { error: String },
+    Progress(PullProgress),
+}
+
+/// Progress of a model download reported by `/api/pull`.
+///
+/// `completed` and `total` are byte counts and are only present while a layer
+/// is downloading; the final update has the status `success`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PullProgress {
+    pub status: String,
+    #[serde(default)]
+    pub digest: Option<String>,
+    #[serde(default)]
+    pub completed: Option<u64>,
+    #[serde(default)]
+    pub total: Option<u64>,
+}
+
+impl PullProgress {
+    /// Whether this update reports the pull as finished
+    pub fn is_success(&self) -> bool {
+        self.status == "success"
+    }
+}
+
 // Response for /api/chat endpoint (streaming)
 #[derive(Deserialize, Debug)]
 pub struct ChatResponse {
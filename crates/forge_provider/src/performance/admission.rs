@@ -0,0 +1,445 @@
+//! Backpressure-aware admission control across providers
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use derive_setters::Setters;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::config::local_ai::{LocalAiConfig, ServerLoad};
+
+/// Configuration for rejecting new requests when providers are saturated
+#[derive(Debug, Clone, Serialize, Deserialize, Setters)]
+#[setters(strip_option, into)]
+pub struct AdmissionConfig {
+    /// Enable admission control
+    pub enabled: bool,
+    /// Maximum concurrent requests assumed for local providers registered
+    /// without a `max_concurrent_requests` limit
+    pub default_capacity: usize,
+    /// Fraction of total capacity (0.0-1.0) above which new requests are
+    /// rejected
+    pub reject_threshold: f64,
+    /// Suggested delay before retrying a rejected request
+    pub retry_after: Option<Duration>,
+    /// Adapt provider capacity toward the load reported by the server
+    pub adapt_to_server_load: bool,
+    /// Fraction (0.0-1.0) of the gap to the server-reported capacity closed on
+    /// each update, so one noisy report does not swing the limit
+    pub adaptation_rate: f64,
+    /// Capacity never adapts below this value
+    pub min_capacity: usize,
+}
+
+impl Default for AdmissionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_capacity: 4,
+            reject_threshold: 1.0,
+            retry_after: Some(Duration::from_secs(1)),
+            adapt_to_server_load: true,
+            adaptation_rate: 0.5,
+            min_capacity: 1,
+        }
+    }
+}
+
+impl AdmissionConfig {
+    /// Reject limits that would never admit a request
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.default_capacity == 0 {
+            anyhow::bail!("Default capacity cannot be zero");
+        }
+        if self.min_capacity == 0 {
+            anyhow::bail!("Minimum capacity cannot be zero");
+        }
+        if !(self.reject_threshold > 0.0 && self.reject_threshold <= 1.0) {
+            anyhow::bail!(
+                "Reject threshold must be in (0.0, 1.0], got {}",
+                self.reject_threshold
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Rejection returned when providers are saturated
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Providers overloaded: {in_flight} of {capacity} request slots in use")]
+pub struct Overloaded {
+    /// Aggregate in-flight requests at the time of the check
+    pub in_flight: usize,
+    /// Aggregate capacity across all registered providers
+    pub capacity: usize,
+    /// Suggested delay before retrying
+    pub retry_after: Option<Duration>,
+}
+
+/// Why [`AdmissionController::try_acquire`] turned a request away
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum AdmissionError {
+    /// Every registered provider is at capacity
+    #[error(transparent)]
+    Overloaded(#[from] Overloaded),
+    /// The provider was never registered, so its capacity is unknown
+    #[error("Provider '{0}' is not registered for admission control")]
+    UnknownProvider(String),
+}
+
+/// An admitted request on a provider, released when dropped
+#[derive(Debug)]
+pub struct AdmissionPermit {
+    providers: Arc<Mutex<HashMap<String, ProviderLoad>>>,
+    provider_name: String,
+}
+
+impl Drop for AdmissionPermit {
+    fn drop(&mut self) {
+        let mut providers = self.providers.lock().unwrap();
+        if let Some(load) = providers.get_mut(&self.provider_name) {
+            load.in_flight = load.in_flight.saturating_sub(1);
+        }
+    }
+}
+
+/// Load tracked for a single provider
+#[derive(Debug, Clone, Default)]
+struct ProviderLoad {
+    capacity: usize,
+    in_flight: usize,
+}
+
+/// Compares aggregate in-flight requests against total provider capacity and
+/// rejects new work early instead of letting queues grow unbounded
+#[derive(Debug)]
+pub struct AdmissionController {
+    config: AdmissionConfig,
+    providers: Arc<Mutex<HashMap<String, ProviderLoad>>>,
+}
+
+impl Default for AdmissionController {
+    fn default() -> Self {
+        Self {
+            config: AdmissionConfig::default(),
+            providers: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl AdmissionController {
+    /// Create a new admission controller, rejecting invalid limits
+    pub fn new(config: AdmissionConfig) -> anyhow::Result<Self> {
+        config.validate()?;
+        Ok(Self { config, providers: Arc::new(Mutex::new(HashMap::new())) })
+    }
+
+    /// Register a provider with an explicit concurrent request capacity
+    pub fn register_provider(&self, provider_name: &str, capacity: usize) -> anyhow::Result<()> {
+        if capacity == 0 {
+            anyhow::bail!("Capacity for provider {provider_name} cannot be zero");
+        }
+        let mut providers = self.providers.lock().unwrap();
+        providers
+            .entry(provider_name.to_string())
+            .or_default()
+            .capacity = capacity;
+        Ok(())
+    }
+
+    /// Register every enabled provider in `local_config`, with its
+    /// `max_concurrent_requests` as capacity or `default_capacity` when it
+    /// sets no limit
+    pub fn register_local_providers(&self, local_config: &LocalAiConfig) {
+        for (name, config) in local_config.enabled_providers() {
+            let capacity = match config.max_concurrent_requests {
+                0 => self.config.default_capacity,
+                limit => limit,
+            };
+            // Both capacities are non-zero, so registration cannot fail
+            let _ = self.register_provider(name, capacity);
+        }
+    }
+
+    /// Admit a request on `provider_name` if aggregate load allows it. The
+    /// check and the increment happen under one lock, so concurrent callers
+    /// cannot both be admitted past the limit. The slot is held until the
+    /// returned permit is dropped. Providers that were never registered are
+    /// rejected rather than counted with a guessed capacity.
+    pub fn try_acquire(&self, provider_name: &str) -> Result<AdmissionPermit, AdmissionError> {
+        let mut providers = self.providers.lock().unwrap();
+        if !providers.contains_key(provider_name) {
+            return Err(AdmissionError::UnknownProvider(provider_name.to_string()));
+        }
+
+        if self.config.enabled {
+            let (in_flight, capacity) = Self::aggregate(&providers);
+            let limit = ((capacity as f64 * self.config.reject_threshold) as usize).max(1);
+            if in_flight >= limit {
+                warn!(in_flight, capacity, "Providers overloaded");
+                return Err(Overloaded {
+                    in_flight,
+                    capacity,
+                    retry_after: self.config.retry_after,
+                }
+                .into());
+            }
+        }
+
+        let load = providers.get_mut(provider_name).unwrap();
+        load.in_flight += 1;
+        debug!(
+            provider = provider_name,
+            in_flight = load.in_flight,
+            "Request admitted"
+        );
+        Ok(AdmissionPermit {
+            providers: Arc::clone(&self.providers),
+            provider_name: provider_name.to_string(),
+        })
+    }
+
+    /// Current capacity of a provider, if it is tracked
+    pub fn capacity(&self, provider_name: &str) -> Option<usize> {
+        let providers = self.providers.lock().unwrap();
+        providers.get(provider_name).map(|load| load.capacity)
+    }
+
+    /// Move a provider's capacity toward what the server reports it can take:
+    /// its advertised concurrency (or the current capacity when not
+    /// advertised) minus any queued requests. Returns the new capacity, or
+    /// `None` for a provider that is not registered.
+    pub fn apply_server_load(&self, provider_name: &str, load: &ServerLoad) -> Option<usize> {
+        let mut providers = self.providers.lock().unwrap();
+        let provider = providers.get_mut(provider_name)?;
+
+        if !self.config.adapt_to_server_load {
+            return Some(provider.capacity);
+        }
+
+        let current = provider.capacity;
+        let target = load
+            .max_concurrency
+            .unwrap_or(current)
+            .saturating_sub(load.queue_depth.unwrap_or(0))
+            .max(self.config.min_capacity);
+
+        let gap = target as f64 - current as f64;
+        let mut step = (gap * self.config.adaptation_rate.clamp(0.0, 1.0)).round() as isize;
+        if step == 0 && gap != 0.0 {
+            step = gap.signum() as isize;
+        }
+        provider.capacity = current
+            .saturating_add_signed(step)
+            .max(self.config.min_capacity);
+
+        if provider.capacity != current {
+            info!(
+                provider = provider_name,
+                from = current,
+                to = provider.capacity,
+                target,
+                queue_depth = load.queue_depth,
+                "Adapted concurrency limit to server load"
+            );
+        }
+        Some(provider.capacity)
+    }
+
+    /// Aggregate in-flight requests and capacity across all providers
+    pub fn load(&self) -> (usize, usize) {
+        Self::aggregate(&self.providers.lock().unwrap())
+    }
+
+    fn aggregate(providers: &HashMap<String, ProviderLoad>) -> (usize, usize) {
+        providers
+            .values()
+            .fold((0, 0), |(in_flight, capacity), load| {
+                (in_flight + load.in_flight, capacity + load.capacity)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::config::local_ai::LocalProviderConfig;
+    use crate::health::HealthMonitor;
+    use crate::mock_server::MockServer;
+
+    fn fixture() -> AdmissionController {
+        let controller = AdmissionController::new(AdmissionConfig::default()).unwrap();
+        controller.register_provider("ollama", 2).unwrap();
+        controller.register_provider("openai", 1).unwrap();
+        controller
+    }
+
+    #[test]
+    fn test_rejects_when_all_providers_saturated() {
+        let fixture = fixture();
+        let _held = [
+            fixture.try_acquire("ollama").unwrap(),
+            fixture.try_acquire("ollama").unwrap(),
+            fixture.try_acquire("openai").unwrap(),
+        ];
+
+        let actual = fixture.try_acquire("ollama").unwrap_err();
+
+        let expected = AdmissionError::Overloaded(Overloaded {
+            in_flight: 3,
+            capacity: 3,
+            retry_after: Some(Duration::from_secs(1)),
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_rejects_unregistered_provider() {
+        let fixture = fixture();
+
+        let actual = fixture.try_acquire("lmstudio").unwrap_err();
+
+        let expected = AdmissionError::UnknownProvider("lmstudio".to_string());
+        assert_eq!(actual, expected);
+        assert_eq!(fixture.load(), (0, 3));
+        assert_eq!(
+            fixture.apply_server_load("lmstudio", &ServerLoad::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_registers_enabled_local_providers() {
+        let fixture = AdmissionController::new(AdmissionConfig::default()).unwrap();
+        let local_config = LocalAiConfig::with_default_ollama()
+            .add_provider(
+                "lmstudio".to_string(),
+                LocalProviderConfig::default().max_concurrent_requests(2usize),
+            )
+            .add_provider(
+                "vllm".to_string(),
+                LocalProviderConfig::default().enabled(false),
+            );
+
+        fixture.register_local_providers(&local_config);
+
+        let actual = ["ollama", "lmstudio", "vllm"].map(|name| fixture.capacity(name));
+        let expected = [Some(4), Some(2), None];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_admits_again_once_permit_dropped() {
+        let fixture = fixture();
+        let first = fixture.try_acquire("ollama").unwrap();
+        let _rest = [
+            fixture.try_acquire("ollama").unwrap(),
+            fixture.try_acquire("openai").unwrap(),
+        ];
+        assert!(fixture.try_acquire("openai").is_err());
+
+        drop(first);
+
+        let actual = fixture.try_acquire("ollama");
+        assert!(actual.is_ok());
+        assert_eq!(fixture.load(), (3, 3));
+    }
+
+    #[test]
+    fn test_threshold_rejects_before_full_capacity() {
+        let fixture =
+            AdmissionController::new(AdmissionConfig::default().reject_threshold(0.5)).unwrap();
+        fixture.register_provider("ollama", 4).unwrap();
+        let _held = [
+            fixture.try_acquire("ollama").unwrap(),
+            fixture.try_acquire("ollama").unwrap(),
+        ];
+
+        let actual = fixture.try_acquire("ollama").is_err();
+        assert!(actual);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_callers_never_exceed_capacity() {
+        let fixture = Arc::new(fixture());
+
+        let attempts = (0..16).map(|_| {
+            let controller = Arc::clone(&fixture);
+            tokio::spawn(async move { controller.try_acquire("ollama").ok() })
+        });
+        let permits: Vec<_> = futures::future::join_all(attempts)
+            .await
+            .into_iter()
+            .filter_map(|result| result.unwrap())
+            .collect();
+
+        assert_eq!(permits.len(), 3);
+        assert_eq!(fixture.load(), (3, 3));
+    }
+
+    #[test]
+    fn test_rejects_zero_limits() {
+        let fixture = AdmissionController::new(AdmissionConfig::default()).unwrap();
+
+        let actual = [
+            AdmissionController::new(AdmissionConfig::default().default_capacity(0usize)).is_err(),
+            AdmissionController::new(AdmissionConfig::default().min_capacity(0usize)).is_err(),
+            AdmissionController::new(AdmissionConfig::default().reject_threshold(0.0)).is_err(),
+            fixture.register_provider("ollama", 0).is_err(),
+        ];
+
+        assert_eq!(actual, [true; 4]);
+        assert_eq!(fixture.capacity("ollama"), None);
+    }
+
+    #[tokio::test]
+    async fn test_capacity_adapts_to_reported_queue_depth() {
+        let mut server = MockServer::new().await;
+        let _mock = server
+            .mock_ollama_models_with_headers(
+                serde_json::json!({"models": []}),
+                &[("x-max-concurrency", "8"), ("x-queue-depth", "3")],
+            )
+            .await;
+        let mut local = LocalAiConfig::with_default_ollama();
+        local.providers.get_mut("ollama").unwrap().endpoint = server.url();
+        let monitor = HealthMonitor::new(local).await.unwrap();
+        monitor.force_check("ollama").await.unwrap();
+        let load = monitor.get_server_load("ollama").await.unwrap();
+
+        let fixture = AdmissionController::new(AdmissionConfig::default()).unwrap();
+        fixture.register_provider("ollama", 1).unwrap();
+
+        let mut actual = Vec::new();
+        for _ in 0..4 {
+            actual.push(fixture.apply_server_load("ollama", &load));
+        }
+
+        let expected = vec![Some(3), Some(4), Some(5), Some(5)];
+        assert_eq!(actual, expected);
+        assert_eq!(
+            load,
+            ServerLoad {
+                queue_depth: Some(3),
+                max_concurrency: Some(8),
+                active_requests: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_capacity_shrinks_when_queue_builds() {
+        let fixture = AdmissionController::new(AdmissionConfig::default()).unwrap();
+        fixture.register_provider("ollama", 6).unwrap();
+        let load = ServerLoad { queue_depth: Some(10), ..Default::default() };
+
+        let actual = fixture.apply_server_load("ollama", &load);
+
+        assert_eq!(actual, Some(3));
+        assert_eq!(fixture.capacity("ollama"), Some(3));
+    }
+}
@@ -0,0 +1,319 @@
+//! Push notifications for breached alert thresholds
+//!
+//! [`AlertThresholds`] otherwise only feed recommendations generated on
+//! demand. Callbacks registered through [`PerformanceMonitor::on_alert`] are
+//! invoked as soon as a provider's metrics cross a threshold, and again at
+//! most once per
+//! [`PerformanceConfig::alert_debounce`](super::PerformanceConfig::alert_debounce)
+//! for the same provider and threshold, whether the breach lasts or flaps.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde::Serialize;
+use tracing::warn;
+
+use super::{AlertThresholds, PerformanceMonitor, ProviderMetrics};
+
+/// Threshold an alert was raised for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum AlertKind {
+    /// Average response time above `max_response_time`
+    ResponseTime,
+    /// Success rate below `min_success_rate`
+    SuccessRate,
+    /// Memory usage above `max_memory_usage_mb`
+    MemoryUsage,
+    /// CPU usage above `max_cpu_usage_percent`
+    CpuUsage,
+}
+
+/// A provider metric observed on the wrong side of its threshold. Response
+/// times are in seconds and success rates between 0.0 and 1.0.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Alert {
+    pub provider_name: String,
+    pub kind: AlertKind,
+    pub observed: f64,
+    pub threshold: f64,
+}
+
+/// Async callback notified of alerts
+pub type AlertCallback = Arc<dyn Fn(Alert) -> BoxFuture<'static, ()> + Send + Sync>;
+
+impl AlertThresholds {
+    /// Thresholds breached by `metrics`. Throughput is left out: an idle
+    /// provider is below any minimum without anything being wrong with it.
+    pub fn breaches(&self, metrics: &ProviderMetrics) -> Vec<Alert> {
+        let alert = |kind, observed, threshold| Alert {
+            provider_name: metrics.provider_name.clone(),
+            kind,
+            observed,
+            threshold,
+        };
+        let mut alerts = Vec::new();
+        if metrics.total_requests == 0 {
+            return alerts;
+        }
+
+        if metrics.avg_response_time > self.max_response_time {
+            alerts.push(alert(
+                AlertKind::ResponseTime,
+                metrics.avg_response_time.as_secs_f64(),
+                self.max_response_time.as_secs_f64(),
+            ));
+        }
+        let success_rate = metrics.success_rate() / 100.0;
+        if success_rate < self.min_success_rate {
+            alerts.push(alert(
+                AlertKind::SuccessRate,
+                success_rate,
+                self.min_success_rate,
+            ));
+        }
+        if let Some(memory_usage) = metrics
+            .memory_usage_mb
+            .filter(|usage| *usage > self.max_memory_usage_mb)
+        {
+            alerts.push(alert(
+                AlertKind::MemoryUsage,
+                memory_usage as f64,
+                self.max_memory_usage_mb as f64,
+            ));
+        }
+        if let Some(cpu_usage) = metrics
+            .cpu_usage_percent
+            .filter(|usage| *usage > self.max_cpu_usage_percent)
+        {
+            alerts.push(alert(
+                AlertKind::CpuUsage,
+                cpu_usage,
+                self.max_cpu_usage_percent,
+            ));
+        }
+        alerts
+    }
+}
+
+/// Registered alert callbacks and when each provider and threshold last
+/// notified
+#[derive(Default)]
+pub(super) struct AlertDispatcher {
+    callbacks: RwLock<Vec<AlertCallback>>,
+    notified: Mutex<HashMap<(String, AlertKind), Instant>>,
+}
+
+impl AlertDispatcher {
+    fn register(&self, callback: AlertCallback) {
+        self.callbacks.write().unwrap().push(callback);
+    }
+
+    /// Check `metrics` against `thresholds` at `now`, notifying callbacks of
+    /// breaches that have not notified within `debounce`. A threshold that
+    /// recovers is remembered until its debounce passes, so flapping around
+    /// it does not notify on every crossing. Callbacks run on their own
+    /// tasks and do not hold up the caller.
+    pub(super) async fn evaluate_at(
+        &self,
+        thresholds: &AlertThresholds,
+        metrics: &ProviderMetrics,
+        debounce: Duration,
+        now: Instant,
+    ) {
+        let callbacks = self.callbacks.read().unwrap().clone();
+        if callbacks.is_empty() {
+            return;
+        }
+
+        let breaches = thresholds.breaches(metrics);
+        let due: Vec<Alert> = {
+            let mut notified = self.notified.lock().unwrap();
+            notified.retain(|(provider_name, kind), at| {
+                *provider_name != metrics.provider_name
+                    || breaches.iter().any(|alert| alert.kind == *kind)
+                    || now.saturating_duration_since(*at) < debounce
+            });
+            breaches
+                .into_iter()
+                .filter(|alert| {
+                    let key = (alert.provider_name.clone(), alert.kind);
+                    let due = notified
+                        .get(&key)
+                        .is_none_or(|at| now.saturating_duration_since(*at) >= debounce);
+                    if due {
+                        notified.insert(key, now);
+                    }
+                    due
+                })
+                .collect()
+        };
+
+        for alert in due {
+            warn!(
+                provider = %alert.provider_name,
+                kind = ?alert.kind,
+                observed = alert.observed,
+                threshold = alert.threshold,
+                "Performance alert threshold breached"
+            );
+            for callback in &callbacks {
+                tokio::spawn(callback(alert.clone()));
+            }
+        }
+    }
+}
+
+impl PerformanceMonitor {
+    /// Register a callback invoked whenever a provider breaches one of the
+    /// configured alert thresholds
+    pub fn on_alert<F, Fut>(&self, callback: F)
+    where
+        F: Fn(Alert) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.alerts
+            .register(Arc::new(move |alert| callback(alert).boxed()));
+    }
+
+    /// Check a provider's current metrics against the alert thresholds
+    pub(super) async fn check_alerts(&self, provider_name: &str) {
+        let Some(metrics) = self.get_provider_metrics(provider_name).await else {
+            return;
+        };
+        self.alerts
+            .evaluate_at(
+                &self.config.alert_thresholds,
+                &metrics,
+                self.config.alert_debounce,
+                tokio::time::Instant::now().into_std(),
+            )
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::performance::{PerformanceConfig, PerformanceMeasurement, RequestType};
+
+    fn slow_measurement() -> PerformanceMeasurement {
+        let mut measurement =
+            PerformanceMeasurement::new("ollama".to_string(), RequestType::Inference);
+        measurement.end_time = measurement.start_time + Duration::from_secs(2);
+        measurement.success = true;
+        measurement
+    }
+
+    fn metrics(avg_response_time: Duration) -> ProviderMetrics {
+        let mut metrics = ProviderMetrics::new("ollama");
+        metrics.total_requests = 1;
+        metrics.successful_requests = 1;
+        metrics.avg_response_time = avg_response_time;
+        metrics
+    }
+
+    fn response_time_alert() -> Alert {
+        Alert {
+            provider_name: "ollama".to_string(),
+            kind: AlertKind::ResponseTime,
+            observed: 2.0,
+            threshold: 1.0,
+        }
+    }
+
+    /// Forward every alert `monitor` raises to the returned receiver
+    fn subscribe(monitor: &PerformanceMonitor) -> mpsc::UnboundedReceiver<Alert> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        monitor.on_alert(move |alert| {
+            let tx = tx.clone();
+            async move {
+                let _ = tx.send(alert);
+            }
+        });
+        rx
+    }
+
+    /// Alerts delivered to `rx` once the spawned callbacks have run
+    async fn delivered(rx: &mut mpsc::UnboundedReceiver<Alert>) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        while let Ok(Some(alert)) = tokio::time::timeout(Duration::from_millis(50), rx.recv()).await
+        {
+            alerts.push(alert);
+        }
+        alerts
+    }
+
+    fn config() -> PerformanceConfig {
+        PerformanceConfig::default()
+            .alert_thresholds(AlertThresholds::default().max_response_time(Duration::from_secs(1)))
+            .alert_debounce(Duration::from_secs(300))
+    }
+
+    #[tokio::test]
+    async fn test_alert_fires_once_per_breach_window() {
+        let config = config();
+        let fixture = PerformanceMonitor::new(config.clone());
+        let mut rx = subscribe(&fixture);
+
+        for _ in 0..3 {
+            fixture.record_measurement(slow_measurement()).await;
+        }
+        let within_window = delivered(&mut rx).await;
+
+        // The breach persists past the debounce interval
+        let metrics = fixture.get_provider_metrics("ollama").await.unwrap();
+        fixture
+            .alerts
+            .evaluate_at(
+                &config.alert_thresholds,
+                &metrics,
+                config.alert_debounce,
+                tokio::time::Instant::now().into_std() + Duration::from_secs(301),
+            )
+            .await;
+        let after_window = delivered(&mut rx).await;
+
+        let actual = (within_window, after_window);
+        let expected = (vec![response_time_alert()], vec![response_time_alert()]);
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_flapping_breach_fires_once_per_debounce() {
+        let config = config();
+        let fixture = PerformanceMonitor::new(config.clone());
+        let mut rx = subscribe(&fixture);
+        let start = Instant::now();
+        let steps = [
+            (0, Duration::from_secs(2)),
+            (10, Duration::from_millis(100)),
+            (20, Duration::from_secs(2)),
+            (30, Duration::from_millis(100)),
+            (301, Duration::from_secs(2)),
+        ];
+
+        let mut actual = Vec::new();
+        for (elapsed_secs, avg_response_time) in steps {
+            fixture
+                .alerts
+                .evaluate_at(
+                    &config.alert_thresholds,
+                    &metrics(avg_response_time),
+                    config.alert_debounce,
+                    start + Duration::from_secs(elapsed_secs),
+                )
+                .await;
+            actual.push(delivered(&mut rx).await.len());
+        }
+
+        let expected = vec![1, 0, 0, 0, 1];
+        assert_eq!(actual, expected);
+    }
+}
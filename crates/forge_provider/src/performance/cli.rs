@@ -1,20 +1,31 @@
 //! CLI integration for performance monitoring and optimization
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use anyhow::Context as _;
-use tracing::{info, warn};
+use forge_app::domain::ModelId;
+use tracing::info;
 
+use crate::client::Client;
 use crate::performance::{
-    BenchmarkReport, ModelLoadingOptimizer, OptimizationConfig, OptimizationResult,
-    PerformanceConfig, PerformanceMonitor, PerformanceSummary, ProviderMetrics, ResourceMonitor,
+    BenchmarkReport, ExportFormat, LoadTestReport, ModelLoadingOptimizer, OptimizationConfig,
+    OptimizationResult, PerformanceConfig, PerformanceMonitor, PerformanceSummary,
+    ProviderComparison, ProviderMetrics, ResourceMonitor,
 };
 
+/// Requests sent by a load test when the command does not say
+const DEFAULT_LOAD_TEST_REQUESTS: usize = 20;
+
+/// Requests a load test keeps in flight when the command does not say
+const DEFAULT_LOAD_TEST_CONCURRENCY: usize = 4;
+
 /// Performance CLI handler for managing performance monitoring and optimization
 pub struct PerformanceCli {
     monitor: PerformanceMonitor,
     optimizer: ModelLoadingOptimizer,
     resource_monitor: ResourceMonitor,
+    /// Clients load tests send requests through, by provider name
+    clients: HashMap<String, Client>,
 }
 
 /// Performance command variants
@@ -22,10 +33,22 @@ pub struct PerformanceCli {
 pub enum PerformanceCommand {
     /// Show performance status
     Status,
-    /// Show detailed metrics
-    Metrics { provider_name: Option<String> },
+    /// Show detailed metrics, optionally for a single model of the provider
+    Metrics {
+        provider_name: Option<String>,
+        model_name: Option<String>,
+    },
+    /// Compare metrics side by side for the named providers, or all
+    Compare { providers: Vec<String> },
     /// Run performance benchmark
     Benchmark,
+    /// Send requests to a provider, then benchmark the resulting metrics
+    LoadTest {
+        provider: String,
+        model: String,
+        requests: usize,
+        concurrency: usize,
+    },
     /// Generate optimization recommendations
     Optimize { provider_name: Option<String> },
     /// Show cache statistics
@@ -36,6 +59,10 @@ pub enum PerformanceCommand {
     Start,
     /// Stop performance monitoring
     Stop,
+    /// Export metrics for external monitoring systems
+    Export { format: ExportFormat },
+    /// Clear accumulated metrics, for all providers or a single one
+    Reset { provider_name: Option<String> },
 }
 
 /// Performance CLI output
@@ -51,8 +78,13 @@ pub struct PerformanceOutput {
 #[derive(Debug, Clone)]
 pub enum PerformanceData {
     Summary(PerformanceSummary),
-    Metrics(HashMap<String, ProviderMetrics>),
+    Metrics(BTreeMap<String, ProviderMetrics>),
     BenchmarkReport(BenchmarkReport),
+    Comparison(ProviderComparison),
+    LoadTest {
+        report: LoadTestReport,
+        benchmark: BenchmarkReport,
+    },
     OptimizationResults(Vec<OptimizationResult>),
     CacheStats(crate::performance::optimization::CacheStatistics),
     ResourceUsage(crate::performance::optimization::ResourceUsage),
@@ -68,7 +100,18 @@ impl PerformanceCli {
         let optimizer = ModelLoadingOptimizer::new(optimization_config.clone());
         let resource_monitor = ResourceMonitor::new(optimization_config);
 
-        Ok(Self { monitor, optimizer, resource_monitor })
+        Ok(Self {
+            monitor,
+            optimizer,
+            resource_monitor,
+            clients: HashMap::new(),
+        })
+    }
+
+    /// Send load test requests for `provider_name` through `client`
+    pub fn with_client(mut self, provider_name: impl Into<String>, client: Client) -> Self {
+        self.clients.insert(provider_name.into(), client);
+        self
     }
 
     /// Execute a performance command
@@ -78,10 +121,15 @@ impl PerformanceCli {
     ) -> anyhow::Result<PerformanceOutput> {
         match command.clone() {
             PerformanceCommand::Status => self.handle_status().await,
-            PerformanceCommand::Metrics { provider_name } => {
-                self.handle_metrics(provider_name).await
+            PerformanceCommand::Metrics { provider_name, model_name } => {
+                self.handle_metrics(provider_name, model_name).await
             }
+            PerformanceCommand::Compare { providers } => self.handle_compare(providers).await,
             PerformanceCommand::Benchmark => self.handle_benchmark().await,
+            PerformanceCommand::LoadTest { provider, model, requests, concurrency } => {
+                self.handle_load_test(provider, model, requests, concurrency)
+                    .await
+            }
             PerformanceCommand::Optimize { provider_name } => {
                 self.handle_optimize(provider_name).await
             }
@@ -89,6 +137,8 @@ impl PerformanceCli {
             PerformanceCommand::Resources => self.handle_resources().await,
             PerformanceCommand::Start => self.handle_start().await,
             PerformanceCommand::Stop => self.handle_stop().await,
+            PerformanceCommand::Export { format } => self.handle_export(format).await,
+            PerformanceCommand::Reset { provider_name } => self.handle_reset(provider_name).await,
         }
     }
 
@@ -126,12 +176,25 @@ impl PerformanceCli {
     async fn handle_metrics(
         &self,
         provider_name: Option<String>,
+        model_name: Option<String>,
     ) -> anyhow::Result<PerformanceOutput> {
         match provider_name {
             Some(name) => {
-                info!("Getting metrics for provider: {}", name);
+                let (label, metrics) = match &model_name {
+                    Some(model) => {
+                        info!("Getting metrics for model {} on provider: {}", model, name);
+                        (
+                            format!("{name}/{model}"),
+                            self.monitor.get_model_metrics(&name, model).await,
+                        )
+                    }
+                    None => {
+                        info!("Getting metrics for provider: {}", name);
+                        (name.clone(), self.monitor.get_provider_metrics(&name).await)
+                    }
+                };
 
-                if let Some(metrics) = self.monitor.get_provider_metrics(&name).await {
+                if let Some(metrics) = metrics {
                     let message = format!(
                         "Metrics for {}:\n\
                         • Total Requests: {}\n\
@@ -139,33 +202,42 @@ impl PerformanceCli {
                         • Average Response Time: {:?}\n\
                         • Min/Max Response Time: {:?} / {:?}\n\
                         • Throughput: {:.2} req/s\n\
+                        • Response Throughput: {:.0} bytes/s ({} bytes total)\n\
                         • Memory Usage: {} MB\n\
                         • CPU Usage: {:.1}%",
-                        name,
+                        label,
                         metrics.total_requests,
                         metrics.success_rate(),
                         metrics.avg_response_time,
                         metrics.min_response_time,
                         metrics.max_response_time,
                         metrics.throughput,
+                        metrics.bytes_per_second,
+                        metrics.total_bytes,
                         metrics.memory_usage_mb.unwrap_or(0),
                         metrics.cpu_usage_percent.unwrap_or(0.0)
                     );
 
-                    let mut metrics_map = HashMap::new();
-                    metrics_map.insert(name.clone(), metrics);
+                    let mut metrics_map = BTreeMap::new();
+                    metrics_map.insert(label, metrics);
 
                     Ok(PerformanceOutput {
-                        command: PerformanceCommand::Metrics { provider_name: Some(name) },
+                        command: PerformanceCommand::Metrics {
+                            provider_name: Some(name),
+                            model_name,
+                        },
                         success: true,
                         message,
                         data: Some(PerformanceData::Metrics(metrics_map)),
                     })
                 } else {
                     Ok(PerformanceOutput {
-                        command: PerformanceCommand::Metrics { provider_name: Some(name.clone()) },
+                        command: PerformanceCommand::Metrics {
+                            provider_name: Some(name),
+                            model_name,
+                        },
                         success: false,
-                        message: format!("No metrics found for provider: {name}"),
+                        message: format!("No metrics found for provider: {label}"),
                         data: None,
                     })
                 }
@@ -177,7 +249,7 @@ impl PerformanceCli {
 
                 if all_metrics.is_empty() {
                     Ok(PerformanceOutput {
-                        command: PerformanceCommand::Metrics { provider_name: None },
+                        command: PerformanceCommand::Metrics { provider_name: None, model_name },
                         success: true,
                         message: "No performance metrics available yet".to_string(),
                         data: Some(PerformanceData::Metrics(all_metrics)),
@@ -189,17 +261,18 @@ impl PerformanceCli {
                             "\n{}:\n\
                             • Requests: {} (Success: {:.1}%)\n\
                             • Response Time: {:?} (avg)\n\
-                            • Throughput: {:.2} req/s\n",
+                            • Throughput: {:.2} req/s, {:.0} bytes/s\n",
                             name,
                             metrics.total_requests,
                             metrics.success_rate(),
                             metrics.avg_response_time,
-                            metrics.throughput
+                            metrics.throughput,
+                            metrics.bytes_per_second
                         ));
                     }
 
                     Ok(PerformanceOutput {
-                        command: PerformanceCommand::Metrics { provider_name: None },
+                        command: PerformanceCommand::Metrics { provider_name: None, model_name },
                         success: true,
                         message,
                         data: Some(PerformanceData::Metrics(all_metrics)),
@@ -209,43 +282,115 @@ impl PerformanceCli {
         }
     }
 
+    /// Handle compare command
+    async fn handle_compare(&self, providers: Vec<String>) -> anyhow::Result<PerformanceOutput> {
+        info!(?providers, "Comparing provider metrics");
+
+        let comparison = ProviderComparison::new(self.monitor.get_all_metrics().await, &providers);
+        let message = if comparison.providers.is_empty() && comparison.missing.is_empty() {
+            "No performance metrics available yet".to_string()
+        } else {
+            format!("Provider Comparison:\n\n{}", comparison.render())
+        };
+
+        Ok(PerformanceOutput {
+            command: PerformanceCommand::Compare { providers },
+            success: comparison.missing.is_empty(),
+            message,
+            data: Some(PerformanceData::Comparison(comparison)),
+        })
+    }
+
     /// Handle benchmark command
     async fn handle_benchmark(&self) -> anyhow::Result<PerformanceOutput> {
         info!("Running performance benchmark");
 
         let report = self.monitor.benchmark_against_targets().await;
+        let message = format_benchmark_report(&report);
+
+        Ok(PerformanceOutput {
+            command: PerformanceCommand::Benchmark,
+            success: true,
+            message,
+            data: Some(PerformanceData::BenchmarkReport(report)),
+        })
+    }
+
+    /// Handle load test command
+    async fn handle_load_test(
+        &self,
+        provider: String,
+        model: String,
+        requests: usize,
+        concurrency: usize,
+    ) -> anyhow::Result<PerformanceOutput> {
+        let command = PerformanceCommand::LoadTest {
+            provider: provider.clone(),
+            model: model.clone(),
+            requests,
+            concurrency,
+        };
+        let Some(client) = self.clients.get(&provider) else {
+            return Ok(PerformanceOutput {
+                command,
+                success: false,
+                message: format!("No client configured for provider: {provider}"),
+                data: None,
+            });
+        };
+        if !self.monitor.is_running() {
+            return Ok(PerformanceOutput {
+                command,
+                success: false,
+                message: "Performance monitoring is not running; start it before a load test"
+                    .to_string(),
+                data: None,
+            });
+        }
+
+        let report = self
+            .monitor
+            .run_load_test(
+                client,
+                &provider,
+                &ModelId::new(model),
+                requests,
+                concurrency,
+            )
+            .await;
+        let benchmark = self.monitor.benchmark_against_targets().await;
 
         let mut message = format!(
-            "Performance Benchmark Results:\n\
-            • Overall Performance Score: {:.2}\n\
-            • Benchmark Timestamp: {:?}\n\n",
-            report.overall_performance_score, report.benchmark_timestamp
+            "Load Test Results for {} ({}):\n\
+            • Requests: {} ({} concurrent)\n\
+            • Succeeded / Failed: {} / {}\n\
+            • Latency avg / p95 / max: {:?} / {:?} / {:?}\n\
+            • Throughput: {:.2} req/s\n",
+            report.provider_name,
+            report.model,
+            report.requests,
+            report.concurrency,
+            report.succeeded,
+            report.failed,
+            report.avg_latency,
+            report.p95_latency,
+            report.max_latency,
+            report.throughput()
         );
-
-        for (provider_name, comparison) in &report.provider_comparisons {
-            message.push_str(&format!(
-                "{}:\n\
-                • Response Time vs Target: {:.2}x\n\
-                • Success Rate vs Target: {:.2}x\n\
-                • Throughput vs Target: {:.2}x\n\
-                • Meets All Targets: {}\n\n",
-                provider_name,
-                comparison.response_time_vs_target,
-                comparison.success_rate_vs_target,
-                comparison.throughput_vs_target,
-                if comparison.meets_targets {
-                    "✅"
-                } else {
-                    "❌"
-                }
-            ));
+        if !report.errors.is_empty() {
+            message.push_str("\nErrors:\n");
+            for (error, count) in &report.errors {
+                message.push_str(&format!("• {count}x {error}\n"));
+            }
         }
+        message.push('\n');
+        message.push_str(&format_benchmark_report(&benchmark));
 
         Ok(PerformanceOutput {
-            command: PerformanceCommand::Benchmark,
-            success: true,
+            command,
+            success: report.failed == 0,
             message,
-            data: Some(PerformanceData::BenchmarkReport(report)),
+            data: Some(PerformanceData::LoadTest { report, benchmark }),
         })
     }
 
@@ -334,6 +479,8 @@ impl PerformanceCli {
                                     "Configuration",
                                 crate::performance::RecommendationType::ProviderSelection =>
                                     "Provider Selection",
+                                crate::performance::RecommendationType::Deprecation =>
+                                    "Deprecation",
                             },
                             rec.priority,
                             rec.provider_name,
@@ -433,6 +580,7 @@ impl PerformanceCli {
             .start()
             .await
             .context("Failed to start performance monitoring")?;
+        self.optimizer.start_cache_purge();
 
         Ok(PerformanceOutput {
             command: PerformanceCommand::Start,
@@ -446,13 +594,65 @@ impl PerformanceCli {
     async fn handle_stop(&self) -> anyhow::Result<PerformanceOutput> {
         info!("Stopping performance monitoring");
 
-        // In a real implementation, we would stop background tasks here
-        warn!("Performance monitoring stop not yet implemented");
+        let was_running = self.monitor.stop().await;
+        self.optimizer.stop_cache_purge();
+        let message = if was_running {
+            "Performance monitoring stopped".to_string()
+        } else {
+            "Performance monitoring was not running".to_string()
+        };
 
         Ok(PerformanceOutput {
             command: PerformanceCommand::Stop,
             success: true,
-            message: "Performance monitoring stopped".to_string(),
+            message,
+            data: None,
+        })
+    }
+
+    /// Handle reset command
+    async fn handle_reset(
+        &self,
+        provider_name: Option<String>,
+    ) -> anyhow::Result<PerformanceOutput> {
+        let message = match &provider_name {
+            Some(name) => {
+                info!("Resetting performance metrics for provider: {}", name);
+                if self.monitor.reset_provider(name).await {
+                    format!("Performance metrics reset for provider: {name}")
+                } else {
+                    format!("No metrics found for provider: {name}")
+                }
+            }
+            None => {
+                info!("Resetting performance metrics for all providers");
+                self.monitor.reset().await;
+                "Performance metrics reset".to_string()
+            }
+        };
+
+        Ok(PerformanceOutput {
+            command: PerformanceCommand::Reset { provider_name },
+            success: true,
+            message,
+            data: None,
+        })
+    }
+
+    /// Handle export command
+    async fn handle_export(&self, format: ExportFormat) -> anyhow::Result<PerformanceOutput> {
+        info!(?format, "Exporting performance metrics");
+
+        let message = self
+            .monitor
+            .export(format)
+            .await
+            .context("Failed to export performance metrics")?;
+
+        Ok(PerformanceOutput {
+            command: PerformanceCommand::Export { format },
+            success: true,
+            message,
             data: None,
         })
     }
@@ -469,7 +669,12 @@ impl Default for PerformanceCli {
         let optimizer = ModelLoadingOptimizer::new(optimization_config.clone());
         let resource_monitor = ResourceMonitor::new(optimization_config);
 
-        Self { monitor, optimizer, resource_monitor }
+        Self {
+            monitor,
+            optimizer,
+            resource_monitor,
+            clients: HashMap::new(),
+        }
     }
 }
 
@@ -484,14 +689,32 @@ pub fn parse_performance_command(input: &str) -> anyhow::Result<PerformanceComma
     match parts[0] {
         "status" => Ok(PerformanceCommand::Status),
         "metrics" => {
-            let provider_name = if parts.len() > 1 {
-                Some(parts[1].to_string())
-            } else {
-                None
-            };
-            Ok(PerformanceCommand::Metrics { provider_name })
+            let provider_name = parts.get(1).map(|name| name.to_string());
+            let model_name = parts.get(2).map(|name| name.to_string());
+            Ok(PerformanceCommand::Metrics { provider_name, model_name })
         }
+        "compare" => Ok(PerformanceCommand::Compare {
+            providers: parts[1..].iter().map(|name| name.to_string()).collect(),
+        }),
         "benchmark" => Ok(PerformanceCommand::Benchmark),
+        "loadtest" => {
+            let (Some(provider), Some(model)) = (parts.get(1), parts.get(2)) else {
+                anyhow::bail!("Usage: loadtest <provider> <model> [requests] [concurrency]");
+            };
+            let count = |index: usize, default: usize| -> anyhow::Result<usize> {
+                parts.get(index).map_or(Ok(default), |value| {
+                    value
+                        .parse()
+                        .with_context(|| format!("Invalid load test count: {value}"))
+                })
+            };
+            Ok(PerformanceCommand::LoadTest {
+                provider: provider.to_string(),
+                model: model.to_string(),
+                requests: count(3, DEFAULT_LOAD_TEST_REQUESTS)?,
+                concurrency: count(4, DEFAULT_LOAD_TEST_CONCURRENCY)?,
+            })
+        }
         "optimize" => {
             let provider_name = if parts.len() > 1 {
                 Some(parts[1].to_string())
@@ -504,10 +727,57 @@ pub fn parse_performance_command(input: &str) -> anyhow::Result<PerformanceComma
         "resources" => Ok(PerformanceCommand::Resources),
         "start" => Ok(PerformanceCommand::Start),
         "stop" => Ok(PerformanceCommand::Stop),
+        "export" => {
+            let format = match parts.get(1) {
+                Some(format) => format.parse()?,
+                None => ExportFormat::Prometheus,
+            };
+            Ok(PerformanceCommand::Export { format })
+        }
+        "reset" => {
+            let provider_name = parts.get(1).map(|name| name.to_string());
+            Ok(PerformanceCommand::Reset { provider_name })
+        }
         _ => anyhow::bail!("Unknown performance command: {}", parts[0]),
     }
 }
 
+/// Format a benchmark report for display
+fn format_benchmark_report(report: &BenchmarkReport) -> String {
+    let mut message = format!(
+        "Performance Benchmark Results:\n\
+        • Overall Performance Score: {:.2}\n\
+        • Benchmark Timestamp: {:?}\n\n",
+        report.overall_performance_score, report.benchmark_timestamp
+    );
+
+    for (provider_name, comparison) in &report.provider_comparisons {
+        message.push_str(&format!(
+            "{}:\n\
+            • Response Time vs Target: {:.2}x\n\
+            • Success Rate vs Target: {:.2}x\n\
+            • Throughput vs Target: {:.2}x\n\
+            • Quality Score: {}\n\
+            • Meets All Targets: {}\n\n",
+            provider_name,
+            comparison.response_time_vs_target,
+            comparison.success_rate_vs_target,
+            comparison.throughput_vs_target,
+            comparison
+                .quality_score
+                .map(|score| format!("{score:.2}"))
+                .unwrap_or_else(|| "n/a".to_string()),
+            if comparison.meets_targets {
+                "✅"
+            } else {
+                "❌"
+            }
+        ));
+    }
+
+    message
+}
+
 /// Format performance output for display
 pub fn format_performance_output(output: &PerformanceOutput) -> String {
     let status_indicator = if output.success { "✅" } else { "❌" };
@@ -520,9 +790,15 @@ pub fn format_performance_output(output: &PerformanceOutput) -> String {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use forge_app::domain::{HttpConfig, Provider, RetryConfig};
     use pretty_assertions::assert_eq;
+    use reqwest::Url;
 
     use super::*;
+    use crate::mock_server::{MockOllamaServer, ScriptedResponse};
+    use crate::performance::{PerformanceMeasurement, RequestType};
 
     #[tokio::test]
     async fn test_performance_cli_creation() {
@@ -545,7 +821,7 @@ mod tests {
     async fn test_metrics_command() {
         let cli = PerformanceCli::new().unwrap();
         let result = cli
-            .execute_command(PerformanceCommand::Metrics { provider_name: None })
+            .execute_command(PerformanceCommand::Metrics { provider_name: None, model_name: None })
             .await;
 
         assert!(result.is_ok());
@@ -579,6 +855,116 @@ mod tests {
         assert!(output.message.contains("System Resource Usage"));
     }
 
+    #[tokio::test]
+    async fn test_stop_command_reports_whether_running() {
+        let cli = PerformanceCli::new().unwrap();
+
+        let actual = cli.execute_command(PerformanceCommand::Stop).await.unwrap();
+        assert_eq!(actual.message, "Performance monitoring was not running");
+
+        cli.execute_command(PerformanceCommand::Start)
+            .await
+            .unwrap();
+        let actual = cli.execute_command(PerformanceCommand::Stop).await.unwrap();
+        assert_eq!(actual.message, "Performance monitoring stopped");
+    }
+
+    #[tokio::test]
+    async fn test_reset_command_clears_metrics() {
+        let cli = PerformanceCli::new().unwrap();
+        cli.execute_command(PerformanceCommand::Start)
+            .await
+            .unwrap();
+        for provider_name in ["ollama", "lmstudio"] {
+            cli.monitor
+                .record_measurement(
+                    PerformanceMeasurement::new(provider_name.to_string(), RequestType::Inference)
+                        .complete_success(),
+                )
+                .await;
+        }
+
+        let actual = cli
+            .execute_command(PerformanceCommand::Reset {
+                provider_name: Some("ollama".to_string()),
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            actual.message,
+            "Performance metrics reset for provider: ollama"
+        );
+        let remaining: Vec<_> = cli.monitor.get_all_metrics().await.into_keys().collect();
+        assert_eq!(remaining, vec!["lmstudio".to_string()]);
+
+        cli.execute_command(PerformanceCommand::Reset { provider_name: None })
+            .await
+            .unwrap();
+        assert!(cli.monitor.get_all_metrics().await.is_empty());
+        cli.execute_command(PerformanceCommand::Stop).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_load_test_command_records_every_request() {
+        let server = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/api/chat",
+                ScriptedResponse::chat_stream("llama3.2", &["Hi"]),
+            )
+            .start()
+            .await;
+        let client = Client::new(
+            Provider::Ollama { url: Url::parse(&format!("{}/", server.url())).unwrap() },
+            Arc::new(RetryConfig::default()),
+            "dev",
+            &HttpConfig::default(),
+        )
+        .unwrap();
+        let cli = PerformanceCli::new().unwrap().with_client("ollama", client);
+        cli.execute_command(PerformanceCommand::Start)
+            .await
+            .unwrap();
+
+        let actual = cli
+            .execute_command(PerformanceCommand::LoadTest {
+                provider: "ollama".to_string(),
+                model: "llama3.2".to_string(),
+                requests: 20,
+                concurrency: 20,
+            })
+            .await
+            .unwrap();
+        cli.execute_command(PerformanceCommand::Stop).await.unwrap();
+
+        assert!(actual.success);
+        let Some(PerformanceData::LoadTest { report, benchmark }) = actual.data else {
+            panic!("expected a load test report");
+        };
+        assert_eq!((report.succeeded, report.failed), (20, 0));
+        assert!(benchmark.provider_comparisons.contains_key("ollama"));
+        let metrics = cli.monitor.get_provider_metrics("ollama").await.unwrap();
+        assert_eq!(metrics.total_requests, 20);
+    }
+
+    #[tokio::test]
+    async fn test_load_test_command_requires_client() {
+        let cli = PerformanceCli::new().unwrap();
+
+        let actual = cli
+            .execute_command(PerformanceCommand::LoadTest {
+                provider: "ollama".to_string(),
+                model: "llama3.2".to_string(),
+                requests: 1,
+                concurrency: 1,
+            })
+            .await
+            .unwrap();
+
+        assert!(!actual.success);
+        assert_eq!(actual.message, "No client configured for provider: ollama");
+    }
+
     #[test]
     fn test_parse_performance_command() {
         let result = parse_performance_command("status");
@@ -587,16 +973,44 @@ mod tests {
 
         let result = parse_performance_command("metrics ollama");
         assert!(result.is_ok());
-        if let PerformanceCommand::Metrics { provider_name } = result.unwrap() {
+        if let PerformanceCommand::Metrics { provider_name, model_name } = result.unwrap() {
             assert_eq!(provider_name, Some("ollama".to_string()));
+            assert_eq!(model_name, None);
         } else {
             panic!("Expected Metrics command");
         }
 
+        let result = parse_performance_command("metrics ollama llama3.2");
+        assert!(matches!(
+            result.unwrap(),
+            PerformanceCommand::Metrics { provider_name: Some(provider), model_name: Some(model) }
+                if provider == "ollama" && model == "llama3.2"
+        ));
+
         let result = parse_performance_command("benchmark");
         assert!(result.is_ok());
         assert!(matches!(result.unwrap(), PerformanceCommand::Benchmark));
 
+        let result = parse_performance_command("compare ollama lmstudio");
+        assert!(matches!(
+            result.unwrap(),
+            PerformanceCommand::Compare { providers } if providers == ["ollama", "lmstudio"]
+        ));
+
+        let result = parse_performance_command("loadtest ollama llama3.2 50");
+        assert!(matches!(
+            result.unwrap(),
+            PerformanceCommand::LoadTest { provider, model, requests: 50, concurrency: 4 }
+                if provider == "ollama" && model == "llama3.2"
+        ));
+        assert!(parse_performance_command("loadtest ollama").is_err());
+
+        let result = parse_performance_command("export json");
+        assert!(matches!(
+            result.unwrap(),
+            PerformanceCommand::Export { format: ExportFormat::Json }
+        ));
+
         let result = parse_performance_command("invalid");
         assert!(result.is_err());
     }
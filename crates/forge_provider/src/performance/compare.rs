@@ -0,0 +1,239 @@
+//! Side-by-side comparison of provider metrics
+//!
+//! Used to decide which local model to keep resident: each column marks the
+//! provider that does best on it, and providers asked for without any
+//! recorded metrics are listed rather than dropped.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::ProviderMetrics;
+
+/// Metric compared across providers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ComparisonColumn {
+    /// Highest success rate wins
+    SuccessRate,
+    /// Lowest average response time wins
+    AvgResponseTime,
+    /// Lowest 95th percentile response time wins
+    P95ResponseTime,
+    /// Highest throughput wins
+    Throughput,
+}
+
+impl ComparisonColumn {
+    /// Every column, in display order
+    pub const ALL: [ComparisonColumn; 4] = [
+        ComparisonColumn::SuccessRate,
+        ComparisonColumn::AvgResponseTime,
+        ComparisonColumn::P95ResponseTime,
+        ComparisonColumn::Throughput,
+    ];
+
+    fn header(self) -> &'static str {
+        match self {
+            ComparisonColumn::SuccessRate => "Success",
+            ComparisonColumn::AvgResponseTime => "Avg",
+            ComparisonColumn::P95ResponseTime => "P95",
+            ComparisonColumn::Throughput => "Throughput",
+        }
+    }
+
+    fn value(self, metrics: &ProviderMetrics) -> f64 {
+        match self {
+            ComparisonColumn::SuccessRate => metrics.success_rate(),
+            ComparisonColumn::AvgResponseTime => metrics.avg_response_time.as_secs_f64(),
+            ComparisonColumn::P95ResponseTime => metrics.p95_response_time.as_secs_f64(),
+            ComparisonColumn::Throughput => metrics.throughput,
+        }
+    }
+
+    fn format(self, metrics: &ProviderMetrics) -> String {
+        match self {
+            ComparisonColumn::SuccessRate => format!("{:.1}%", metrics.success_rate()),
+            ComparisonColumn::AvgResponseTime => {
+                format!("{}ms", metrics.avg_response_time.as_millis())
+            }
+            ComparisonColumn::P95ResponseTime => {
+                format!("{}ms", metrics.p95_response_time.as_millis())
+            }
+            ComparisonColumn::Throughput => format!("{:.2} req/s", metrics.throughput),
+        }
+    }
+
+    /// Whether `a` beats `b` on this column
+    fn better(self, a: f64, b: f64) -> bool {
+        let ordering = a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+        match self {
+            ComparisonColumn::SuccessRate | ComparisonColumn::Throughput => {
+                ordering == Ordering::Greater
+            }
+            ComparisonColumn::AvgResponseTime | ComparisonColumn::P95ResponseTime => {
+                ordering == Ordering::Less
+            }
+        }
+    }
+}
+
+/// Metrics of several providers lined up for comparison
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderComparison {
+    /// Compared providers, by name
+    pub providers: BTreeMap<String, ProviderMetrics>,
+    /// Requested providers with no recorded metrics
+    pub missing: Vec<String>,
+}
+
+impl ProviderComparison {
+    /// Compare the `requested` providers out of `all_metrics`, or every
+    /// provider when none are requested
+    pub fn new(mut all_metrics: BTreeMap<String, ProviderMetrics>, requested: &[String]) -> Self {
+        if requested.is_empty() {
+            return Self { providers: all_metrics, missing: Vec::new() };
+        }
+
+        let mut providers = BTreeMap::new();
+        let mut missing = Vec::new();
+        for name in requested {
+            match all_metrics.remove(name) {
+                Some(metrics) => {
+                    providers.insert(name.clone(), metrics);
+                }
+                None if !providers.contains_key(name) => missing.push(name.clone()),
+                None => {}
+            }
+        }
+        Self { providers, missing }
+    }
+
+    /// Provider doing best on `column`; ties go to the first by name
+    pub fn best(&self, column: ComparisonColumn) -> Option<&str> {
+        self.providers
+            .iter()
+            .fold(None, |best: Option<(&String, f64)>, (name, metrics)| {
+                let value = column.value(metrics);
+                match best {
+                    Some((_, best_value)) if !column.better(value, best_value) => best,
+                    _ => Some((name, value)),
+                }
+            })
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Render the comparison as a table, marking the best value in each
+    /// column with `*`
+    pub fn render(&self) -> String {
+        let winners: Vec<Option<&str>> = ComparisonColumn::ALL
+            .iter()
+            .map(|column| self.best(*column))
+            .collect();
+        let name_width = self
+            .providers
+            .keys()
+            .map(String::len)
+            .chain(["Provider".len()])
+            .max()
+            .unwrap_or_default();
+
+        let mut table = format!("{:<name_width$}", "Provider");
+        for column in ComparisonColumn::ALL {
+            table.push_str(&format!("  {:>14}", column.header()));
+        }
+        table.push('\n');
+
+        for (name, metrics) in &self.providers {
+            table.push_str(&format!("{name:<name_width$}"));
+            for (column, winner) in ComparisonColumn::ALL.iter().zip(&winners) {
+                let marker = if *winner == Some(name.as_str()) {
+                    "*"
+                } else {
+                    " "
+                };
+                table.push_str(&format!("  {:>13}{}", column.format(metrics), marker));
+            }
+            table.push('\n');
+        }
+
+        if !self.missing.is_empty() {
+            table.push_str(&format!(
+                "\nNo metrics recorded for: {}\n",
+                self.missing.join(", ")
+            ));
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fixture() -> BTreeMap<String, ProviderMetrics> {
+        let steady = ProviderMetrics::default()
+            .provider_name("steady")
+            .total_requests(10u64)
+            .successful_requests(10u64)
+            .avg_response_time(Duration::from_millis(400))
+            .p95_response_time(Duration::from_millis(500))
+            .throughput(2.0);
+        let quick = ProviderMetrics::default()
+            .provider_name("quick")
+            .total_requests(10u64)
+            .successful_requests(8u64)
+            .failed_requests(2u64)
+            .avg_response_time(Duration::from_millis(150))
+            .p95_response_time(Duration::from_millis(900))
+            .throughput(5.0);
+        BTreeMap::from([("steady".to_string(), steady), ("quick".to_string(), quick)])
+    }
+
+    #[test]
+    fn test_best_provider_per_column() {
+        let fixture = ProviderComparison::new(fixture(), &[]);
+
+        let actual: Vec<_> = ComparisonColumn::ALL
+            .iter()
+            .map(|column| (*column, fixture.best(*column)))
+            .collect();
+
+        let expected = vec![
+            (ComparisonColumn::SuccessRate, Some("steady")),
+            (ComparisonColumn::AvgResponseTime, Some("quick")),
+            (ComparisonColumn::P95ResponseTime, Some("steady")),
+            (ComparisonColumn::Throughput, Some("quick")),
+        ];
+        assert_eq!(actual, expected);
+
+        let table = fixture.render();
+        let row = |name: &str| {
+            table
+                .lines()
+                .find(|line| line.starts_with(name))
+                .unwrap()
+                .to_string()
+        };
+        assert!(row("steady").contains("100.0%*"), "{table}");
+        assert!(row("steady").contains("500ms*"), "{table}");
+        assert!(row("quick").contains("150ms*"), "{table}");
+        assert!(row("quick").contains("5.00 req/s*"), "{table}");
+        assert_eq!(table.matches('*').count(), 4, "{table}");
+    }
+
+    #[test]
+    fn test_missing_providers_are_reported() {
+        let requested = vec!["quick".to_string(), "absent".to_string()];
+
+        let actual = ProviderComparison::new(fixture(), &requested);
+
+        assert_eq!(actual.providers.keys().collect::<Vec<_>>(), vec!["quick"]);
+        assert_eq!(actual.missing, vec!["absent".to_string()]);
+        assert!(actual.render().contains("No metrics recorded for: absent"));
+    }
+}
@@ -0,0 +1,178 @@
+//! End-of-life tracking for provider models
+//!
+//! Cloud providers announce retirement dates for their models. Requests to a
+//! retired model start failing without warning, so configured and active
+//! models are checked against known EOL dates and migration recommendations
+//! are raised ahead of time.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use derive_setters::Setters;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::{OptimizationRecommendation, Priority, RecommendationType};
+
+/// Known end-of-life dates for models
+#[derive(Debug, Clone, Serialize, Deserialize, Setters)]
+#[setters(strip_option, into)]
+pub struct ModelEolConfig {
+    /// EOL date for each model
+    pub model_eol: HashMap<String, NaiveDate>,
+    /// Warn when a model is this many days or fewer from its EOL date
+    pub warning_days: i64,
+}
+
+impl Default for ModelEolConfig {
+    fn default() -> Self {
+        Self { model_eol: HashMap::new(), warning_days: 30 }
+    }
+}
+
+/// Deprecation state of a model on a given day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelDeprecation {
+    /// The model reaches EOL within the warning window
+    Approaching { eol: NaiveDate, days_remaining: i64 },
+    /// The model is past its EOL date
+    Retired { eol: NaiveDate, days_since: i64 },
+}
+
+impl ModelEolConfig {
+    /// Record the EOL date for a model
+    pub fn with_eol(mut self, model: impl Into<String>, eol: NaiveDate) -> Self {
+        self.model_eol.insert(model.into(), eol);
+        self
+    }
+
+    /// Deprecation state of `model` on `today`, or `None` when it has no EOL
+    /// date or is outside the warning window
+    pub fn check(&self, model: &str, today: NaiveDate) -> Option<ModelDeprecation> {
+        let eol = *self.model_eol.get(model)?;
+        let days_remaining = (eol - today).num_days();
+
+        if days_remaining <= 0 {
+            Some(ModelDeprecation::Retired { eol, days_since: -days_remaining })
+        } else if days_remaining <= self.warning_days {
+            Some(ModelDeprecation::Approaching { eol, days_remaining })
+        } else {
+            None
+        }
+    }
+
+    /// Migration recommendations for `(provider, model)` pairs nearing or
+    /// past EOL on `today`
+    pub fn recommendations(
+        &self,
+        models: &[(String, String)],
+        today: NaiveDate,
+    ) -> Vec<OptimizationRecommendation> {
+        models
+            .iter()
+            .filter_map(|(provider_name, model)| {
+                let deprecation = self.check(model, today)?;
+                let (description, priority) = match deprecation {
+                    ModelDeprecation::Retired { eol, days_since } => {
+                        warn!(
+                            provider = %provider_name,
+                            model = %model,
+                            eol = %eol,
+                            days_since,
+                            "Model is past its end-of-life date"
+                        );
+                        (
+                            format!("Model {model} reached end of life on {eol}"),
+                            Priority::High,
+                        )
+                    }
+                    ModelDeprecation::Approaching { eol, days_remaining } => {
+                        warn!(
+                            provider = %provider_name,
+                            model = %model,
+                            eol = %eol,
+                            days_remaining,
+                            "Model is approaching its end-of-life date"
+                        );
+                        (
+                            format!(
+                                "Model {model} reaches end of life on {eol} ({days_remaining} days)"
+                            ),
+                            Priority::Medium,
+                        )
+                    }
+                };
+
+                Some(OptimizationRecommendation {
+                    provider_name: provider_name.clone(),
+                    recommendation_type: RecommendationType::Deprecation,
+                    description,
+                    suggested_action: format!(
+                        "Migrate from {model} to a supported model before requests start failing"
+                    ),
+                    expected_impact: "Avoids failures when the provider retires the model"
+                        .to_string(),
+                    priority,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn fixture() -> ModelEolConfig {
+        ModelEolConfig::default()
+            .with_eol("gpt-4-0314", date(2024, 6, 13))
+            .with_eol("gpt-4o", date(2026, 1, 1))
+    }
+
+    #[test]
+    fn test_model_past_eol_gets_high_priority_recommendation() {
+        let fixture = fixture();
+        let models = vec![("openai".to_string(), "gpt-4-0314".to_string())];
+
+        let actual = fixture.recommendations(&models, date(2025, 1, 1));
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].priority, Priority::High);
+        assert_eq!(actual[0].provider_name, "openai");
+        assert!(actual[0].suggested_action.contains("gpt-4-0314"));
+    }
+
+    #[test]
+    fn test_model_well_before_eol_has_no_warning() {
+        let fixture = fixture();
+        let models = vec![("openai".to_string(), "gpt-4o".to_string())];
+
+        let actual = fixture.recommendations(&models, date(2025, 1, 1));
+
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn test_check_states() {
+        let fixture = fixture();
+
+        let actual = vec![
+            fixture.check("gpt-4o", date(2025, 12, 22)),
+            fixture.check("gpt-4o", date(2026, 1, 3)),
+            fixture.check("llama3.2", date(2026, 1, 3)),
+        ];
+
+        let expected = vec![
+            Some(ModelDeprecation::Approaching { eol: date(2026, 1, 1), days_remaining: 10 }),
+            Some(ModelDeprecation::Retired { eol: date(2026, 1, 1), days_since: 2 }),
+            None,
+        ];
+        assert_eq!(actual, expected);
+    }
+}
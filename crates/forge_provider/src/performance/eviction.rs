@@ -0,0 +1,361 @@
+//! Idle model eviction for a pool of local providers
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use derive_setters::Setters;
+use forge_app::domain::ModelId;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::ollama::Ollama;
+
+/// Configuration for unloading models that have been idle for too long
+#[derive(Debug, Clone, Serialize, Deserialize, Setters)]
+#[setters(strip_option, into)]
+pub struct IdleEvictionConfig {
+    /// Enable automatic eviction of idle models. Off by default so models
+    /// are only unloaded once the user opts in
+    pub enabled: bool,
+    /// How long a model may sit idle before it is unloaded
+    pub idle_threshold: Duration,
+    /// Number of requests after which a model is considered frequently used
+    pub frequent_use_threshold: u64,
+    /// Multiplier applied to `idle_threshold` for frequently used models, so
+    /// they stay warm longer and avoid repeated cold starts
+    pub frequent_use_multiplier: u32,
+    /// How often the eviction task looks for idle models
+    pub check_interval: Duration,
+}
+
+impl Default for IdleEvictionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_threshold: Duration::from_secs(600), // 10 minutes
+            frequent_use_threshold: 20,
+            frequent_use_multiplier: 3,
+            check_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+/// A provider capable of releasing a loaded model from memory
+#[async_trait]
+pub trait ModelUnloader: Send + Sync {
+    /// Unload the given model from the provider
+    async fn unload_model(&self, model: &ModelId) -> anyhow::Result<()>;
+}
+
+#[async_trait]
+impl ModelUnloader for Ollama {
+    async fn unload_model(&self, model: &ModelId) -> anyhow::Result<()> {
+        Ollama::unload_model(self, model).await
+    }
+}
+
+/// Unloaders for the providers of a pool, keyed by provider name
+pub type ModelUnloaders = HashMap<String, Arc<dyn ModelUnloader>>;
+
+/// Request activity tracked for a single model
+#[derive(Debug, Clone)]
+struct ModelActivity {
+    /// When the model last served a request
+    last_request: Instant,
+    /// Number of requests served since the model was last loaded
+    request_count: u64,
+}
+
+/// Tracks activity per provider and model across a pool of local providers
+/// and unloads models idle beyond the configured threshold
+pub struct IdleEvictionPolicy {
+    config: IdleEvictionConfig,
+    activity: Arc<RwLock<HashMap<(String, ModelId), ModelActivity>>>,
+}
+
+impl IdleEvictionPolicy {
+    /// Create a new idle eviction policy
+    pub fn new(config: IdleEvictionConfig) -> Self {
+        Self { config, activity: Arc::new(RwLock::new(HashMap::new())) }
+    }
+
+    /// Whether idle models are unloaded at all
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Record that `provider_name` served a request with `model` just now
+    pub async fn record_request(&self, provider_name: &str, model: &ModelId) {
+        self.record_request_at(provider_name, model, Instant::now())
+            .await;
+    }
+
+    /// Record that `provider_name` served a request with `model` at the
+    /// given instant
+    pub async fn record_request_at(&self, provider_name: &str, model: &ModelId, at: Instant) {
+        let mut activity = self.activity.write().await;
+        let entry = activity
+            .entry((provider_name.to_string(), model.clone()))
+            .or_insert(ModelActivity { last_request: at, request_count: 0 });
+        entry.last_request = entry.last_request.max(at);
+        entry.request_count += 1;
+    }
+
+    /// Effective idle threshold for a model given its usage frequency
+    fn threshold_for(&self, activity: &ModelActivity) -> Duration {
+        if activity.request_count >= self.config.frequent_use_threshold {
+            self.config.idle_threshold * self.config.frequent_use_multiplier.max(1)
+        } else {
+            self.config.idle_threshold
+        }
+    }
+
+    /// Provider and model pairs idle beyond their threshold as of `now`
+    pub async fn idle_models(&self, now: Instant) -> Vec<(String, ModelId)> {
+        let activity = self.activity.read().await;
+        let mut idle: Vec<(String, ModelId)> = activity
+            .iter()
+            .filter(|(_, activity)| {
+                now.saturating_duration_since(activity.last_request) > self.threshold_for(activity)
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        idle.sort_by(|a, b| (a.0.as_str(), a.1.as_str()).cmp(&(b.0.as_str(), b.1.as_str())));
+        idle
+    }
+
+    /// Unload every idle model through its provider's unloader, returning
+    /// the provider and model pairs that were successfully unloaded
+    pub async fn evict_idle(&self, unloaders: &ModelUnloaders) -> Vec<(String, ModelId)> {
+        self.evict_idle_at(unloaders, Instant::now()).await
+    }
+
+    /// Unload models idle as of `now`. Models that fail to unload, or whose
+    /// provider has no unloader, keep their activity record so they are
+    /// retried on the next pass.
+    pub async fn evict_idle_at(
+        &self,
+        unloaders: &ModelUnloaders,
+        now: Instant,
+    ) -> Vec<(String, ModelId)> {
+        if !self.config.enabled {
+            return Vec::new();
+        }
+
+        let mut evicted = Vec::new();
+        for (provider_name, model) in self.idle_models(now).await {
+            let Some(unloader) = unloaders.get(&provider_name) else {
+                debug!(provider = %provider_name, model = %model, "No unloader for idle model");
+                continue;
+            };
+            match unloader.unload_model(&model).await {
+                Ok(()) => {
+                    info!(provider = %provider_name, model = %model, "Unloaded idle model");
+                    self.activity
+                        .write()
+                        .await
+                        .remove(&(provider_name.clone(), model.clone()));
+                    evicted.push((provider_name, model));
+                }
+                Err(e) => {
+                    warn!(provider = %provider_name, model = %model, error = %e, "Failed to unload idle model");
+                }
+            }
+        }
+
+        debug!(evicted = evicted.len(), "Idle eviction pass complete");
+        evicted
+    }
+
+    /// Evict idle models through `unloaders` every
+    /// [`IdleEvictionConfig::check_interval`] until the returned task is
+    /// aborted. Returns `None` when eviction is disabled.
+    pub fn spawn(self: &Arc<Self>, unloaders: ModelUnloaders) -> Option<JoinHandle<()>> {
+        if !self.config.enabled {
+            return None;
+        }
+        let policy = Arc::clone(self);
+        // A zero interval would make the ticker panic
+        let interval = self.config.check_interval.max(Duration::from_millis(1));
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick completes immediately, before any model is idle
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let now = tokio::time::Instant::now().into_std();
+                policy.evict_idle_at(&unloaders, now).await;
+            }
+        }))
+    }
+
+    /// Number of provider and model pairs currently tracked as loaded
+    pub async fn tracked_models(&self) -> usize {
+        self.activity.read().await.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingUnloader {
+        unloaded: Mutex<Vec<ModelId>>,
+    }
+
+    #[async_trait]
+    impl ModelUnloader for RecordingUnloader {
+        async fn unload_model(&self, model: &ModelId) -> anyhow::Result<()> {
+            self.unloaded.lock().unwrap().push(model.clone());
+            Ok(())
+        }
+    }
+
+    fn fixture() -> IdleEvictionPolicy {
+        IdleEvictionPolicy::new(
+            IdleEvictionConfig::default()
+                .enabled(true)
+                .idle_threshold(Duration::from_secs(60))
+                .frequent_use_threshold(5u64)
+                .frequent_use_multiplier(10u32),
+        )
+    }
+
+    fn unloaders(providers: &[(&str, &Arc<RecordingUnloader>)]) -> ModelUnloaders {
+        providers
+            .iter()
+            .map(|(name, unloader)| {
+                let unloader: Arc<dyn ModelUnloader> = Arc::<RecordingUnloader>::clone(unloader);
+                (name.to_string(), unloader)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_idle_model_is_unloaded_and_active_model_is_not() {
+        let fixture = fixture();
+        let base = Instant::now();
+        let now = base + Duration::from_secs(120);
+        let idle = ModelId::new("llama3.2:latest");
+        let active = ModelId::new("qwen2.5:latest");
+        fixture.record_request_at("ollama", &idle, base).await;
+        fixture
+            .record_request_at("ollama", &active, base + Duration::from_secs(115))
+            .await;
+        let unloader = Arc::new(RecordingUnloader::default());
+
+        let actual = fixture
+            .evict_idle_at(&unloaders(&[("ollama", &unloader)]), now)
+            .await;
+
+        let expected = vec![("ollama".to_string(), idle.clone())];
+        assert_eq!(actual, expected);
+        assert_eq!(*unloader.unloaded.lock().unwrap(), vec![idle]);
+        assert_eq!(fixture.tracked_models().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_activity_is_tracked_per_provider() {
+        let fixture = fixture();
+        let base = Instant::now();
+        let now = base + Duration::from_secs(120);
+        let model = ModelId::new("llama3.2:latest");
+        fixture.record_request_at("ollama-a", &model, base).await;
+        fixture
+            .record_request_at("ollama-b", &model, base + Duration::from_secs(115))
+            .await;
+        let a = Arc::new(RecordingUnloader::default());
+        let b = Arc::new(RecordingUnloader::default());
+
+        let actual = fixture
+            .evict_idle_at(&unloaders(&[("ollama-a", &a), ("ollama-b", &b)]), now)
+            .await;
+
+        let expected = vec![("ollama-a".to_string(), model.clone())];
+        assert_eq!(actual, expected);
+        assert_eq!(*a.unloaded.lock().unwrap(), vec![model]);
+        assert!(b.unloaded.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_frequently_used_model_stays_warm_longer() {
+        let fixture = fixture();
+        let base = Instant::now();
+        let now = base + Duration::from_secs(120);
+        let model = ModelId::new("llama3.2:latest");
+        for _ in 0..5 {
+            fixture.record_request_at("ollama", &model, base).await;
+        }
+        let unloader = Arc::new(RecordingUnloader::default());
+
+        let actual = fixture
+            .evict_idle_at(&unloaders(&[("ollama", &unloader)]), now)
+            .await;
+
+        assert!(actual.is_empty());
+        assert!(unloader.unloaded.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_eviction_is_opt_in() {
+        let actual = IdleEvictionConfig::default().enabled;
+
+        assert!(!actual);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_policy_does_not_unload() {
+        let fixture = Arc::new(IdleEvictionPolicy::new(
+            IdleEvictionConfig::default()
+                .enabled(false)
+                .idle_threshold(Duration::from_secs(1)),
+        ));
+        let base = Instant::now();
+        let now = base + Duration::from_secs(60);
+        let model = ModelId::new("llama3.2:latest");
+        fixture.record_request_at("ollama", &model, base).await;
+        let unloader = Arc::new(RecordingUnloader::default());
+
+        let actual = fixture
+            .evict_idle_at(&unloaders(&[("ollama", &unloader)]), now)
+            .await;
+
+        assert!(actual.is_empty());
+        assert!(unloader.unloaded.lock().unwrap().is_empty());
+        assert!(fixture.spawn(unloaders(&[("ollama", &unloader)])).is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_spawned_task_unloads_idle_models() {
+        let fixture = Arc::new(IdleEvictionPolicy::new(
+            IdleEvictionConfig::default()
+                .enabled(true)
+                .idle_threshold(Duration::from_secs(60))
+                .check_interval(Duration::from_secs(30)),
+        ));
+        let model = ModelId::new("llama3.2:latest");
+        fixture
+            .record_request_at("ollama", &model, tokio::time::Instant::now().into_std())
+            .await;
+        let unloader = Arc::new(RecordingUnloader::default());
+
+        let task = fixture.spawn(unloaders(&[("ollama", &unloader)])).unwrap();
+        tokio::time::sleep(Duration::from_secs(45)).await;
+        assert!(unloader.unloaded.lock().unwrap().is_empty());
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        task.abort();
+
+        assert_eq!(*unloader.unloaded.lock().unwrap(), vec![model]);
+        assert_eq!(fixture.tracked_models().await, 0);
+    }
+}
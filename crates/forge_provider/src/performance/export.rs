@@ -0,0 +1,191 @@
+//! Metrics export in external formats
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use serde::{Deserialize, Serialize};
+
+use super::{PerformanceMonitor, ProviderMetrics};
+
+/// Output format for exported metrics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// Prometheus text exposition format
+    Prometheus,
+    /// Per-provider metrics as JSON
+    Json,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "prometheus" => Ok(ExportFormat::Prometheus),
+            "json" => Ok(ExportFormat::Json),
+            _ => anyhow::bail!("Unknown export format: {s}"),
+        }
+    }
+}
+
+/// A Prometheus metric family rendered from [`ProviderMetrics`]
+struct Family {
+    name: &'static str,
+    kind: &'static str,
+    help: &'static str,
+    value: fn(&ProviderMetrics) -> f64,
+}
+
+const FAMILIES: &[Family] = &[
+    Family {
+        name: "trust_ai_requests_total",
+        kind: "counter",
+        help: "Total requests sent to the provider",
+        value: |metrics| metrics.total_requests as f64,
+    },
+    Family {
+        name: "trust_ai_requests_failed_total",
+        kind: "counter",
+        help: "Requests to the provider that failed",
+        value: |metrics| metrics.failed_requests as f64,
+    },
+    Family {
+        name: "trust_ai_avg_response_seconds",
+        kind: "gauge",
+        help: "Average provider response time in seconds",
+        value: |metrics| metrics.avg_response_time.as_secs_f64(),
+    },
+    Family {
+        name: "trust_ai_p95_response_seconds",
+        kind: "gauge",
+        help: "95th percentile provider response time in seconds",
+        value: |metrics| metrics.p95_response_time.as_secs_f64(),
+    },
+    Family {
+        name: "trust_ai_throughput_rps",
+        kind: "gauge",
+        help: "Provider throughput in requests per second",
+        value: |metrics| metrics.throughput,
+    },
+];
+
+impl PerformanceMonitor {
+    /// Render all per-provider metrics in Prometheus text exposition format
+    pub async fn export_prometheus(&self) -> String {
+        render_prometheus(&self.get_all_metrics().await)
+    }
+
+    /// Render all per-provider metrics in `format`
+    pub async fn export(&self, format: ExportFormat) -> anyhow::Result<String> {
+        match format {
+            ExportFormat::Prometheus => Ok(self.export_prometheus().await),
+            ExportFormat::Json => Ok(serde_json::to_string_pretty(&self.get_all_metrics().await)?),
+        }
+    }
+}
+
+fn render_prometheus(metrics: &BTreeMap<String, ProviderMetrics>) -> String {
+    let mut output = String::new();
+    for family in FAMILIES {
+        let _ = writeln!(output, "# HELP {} {}", family.name, family.help);
+        let _ = writeln!(output, "# TYPE {} {}", family.name, family.kind);
+        for (provider_name, provider_metrics) in metrics {
+            let _ = writeln!(
+                output,
+                "{}{{provider=\"{}\"}} {}",
+                family.name,
+                escape_label_value(provider_name),
+                (family.value)(provider_metrics)
+            );
+        }
+    }
+    output
+}
+
+/// Escape a label value as required by the exposition format
+fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::performance::{PerformanceConfig, PerformanceMeasurement, RequestType};
+
+    /// Parse sample lines back into `(metric, provider, value)`
+    fn parse(exposition: &str) -> Vec<(String, String, f64)> {
+        exposition
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(|line| {
+                let (name, rest) = line.split_once("{provider=\"").unwrap();
+                let (label, value) = rest.rsplit_once("\"} ").unwrap();
+                let label = label
+                    .replace("\\n", "\n")
+                    .replace("\\\"", "\"")
+                    .replace("\\\\", "\\");
+                (name.to_string(), label, value.parse().unwrap())
+            })
+            .collect()
+    }
+
+    fn measurement(provider_name: &str, millis: u64, success: bool) -> PerformanceMeasurement {
+        let mut measurement =
+            PerformanceMeasurement::new(provider_name.to_string(), RequestType::Inference);
+        measurement.end_time = measurement.start_time + Duration::from_millis(millis);
+        measurement.success = success;
+        measurement
+    }
+
+    #[tokio::test]
+    async fn test_export_prometheus_round_trips_two_providers() {
+        let fixture = PerformanceMonitor::new(PerformanceConfig::default());
+        fixture
+            .record_measurement(measurement("ollama", 200, true))
+            .await;
+        fixture
+            .record_measurement(measurement("ollama", 400, false))
+            .await;
+        fixture
+            .record_measurement(measurement("gpu \"a\"", 500, true))
+            .await;
+
+        let exposition = fixture.export_prometheus().await;
+
+        let actual: Vec<_> = parse(&exposition)
+            .into_iter()
+            .filter(|(name, _, _)| name != "trust_ai_throughput_rps")
+            .collect();
+        let expected: Vec<_> = [
+            ("trust_ai_requests_total", "gpu \"a\"", 1.0),
+            ("trust_ai_requests_total", "ollama", 2.0),
+            ("trust_ai_requests_failed_total", "gpu \"a\"", 0.0),
+            ("trust_ai_requests_failed_total", "ollama", 1.0),
+            ("trust_ai_avg_response_seconds", "gpu \"a\"", 0.5),
+            ("trust_ai_avg_response_seconds", "ollama", 0.3),
+            ("trust_ai_p95_response_seconds", "gpu \"a\"", 0.5),
+            ("trust_ai_p95_response_seconds", "ollama", 0.4),
+        ]
+        .into_iter()
+        .map(|(name, provider, value)| (name.to_string(), provider.to_string(), value))
+        .collect();
+        assert_eq!(actual, expected);
+        assert!(exposition.contains("# TYPE trust_ai_requests_total counter\n"));
+        assert!(exposition.contains("# HELP trust_ai_throughput_rps "));
+        assert!(exposition.contains("provider=\"gpu \\\"a\\\"\""));
+    }
+}
@@ -0,0 +1,212 @@
+//! Load generation for benchmarks
+//!
+//! Comparing metrics against targets says nothing on a fresh start, when no
+//! requests have been measured yet. A load test issues real chat requests
+//! through a [`Client`], records each one as an inference measurement and
+//! summarizes how the provider held up.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use forge_app::domain::{Context, ContextMessage, ModelId};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use super::{percentile, PerformanceMeasurement, PerformanceMonitor, RequestType};
+use crate::client::Client;
+
+/// Prompt sent by every load test request
+const LOAD_TEST_PROMPT: &str = "Reply with a single word.";
+
+/// Outcome of a load test against one provider
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadTestReport {
+    pub provider_name: String,
+    pub model: String,
+    pub requests: usize,
+    pub concurrency: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Number of failed requests per error message
+    pub errors: BTreeMap<String, usize>,
+    pub avg_latency: Duration,
+    pub p95_latency: Duration,
+    pub max_latency: Duration,
+    /// Wall-clock time for the whole test
+    pub total_duration: Duration,
+}
+
+impl LoadTestReport {
+    /// Completed requests per second over the whole test
+    pub fn throughput(&self) -> f64 {
+        let seconds = self.total_duration.as_secs_f64();
+        if seconds > 0.0 {
+            self.requests as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+}
+
+impl PerformanceMonitor {
+    /// Send `requests` chat requests for `model` through `client`, at most
+    /// `concurrency` at a time, recording each as a measurement for
+    /// `provider_name`
+    pub async fn run_load_test(
+        &self,
+        client: &Client,
+        provider_name: &str,
+        model: &ModelId,
+        requests: usize,
+        concurrency: usize,
+    ) -> LoadTestReport {
+        let concurrency = concurrency.max(1);
+        info!(
+            provider = %provider_name,
+            model = %model,
+            requests,
+            concurrency,
+            "Starting load test"
+        );
+
+        let started = Instant::now();
+        let results: Vec<_> = stream::iter(0..requests)
+            .map(|_| self.load_test_request(client, provider_name, model))
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
+        let total_duration = started.elapsed();
+
+        let mut latencies = Vec::with_capacity(results.len());
+        let mut errors = BTreeMap::new();
+        for (latency, error) in results {
+            latencies.push(latency);
+            if let Some(error) = error {
+                *errors.entry(error).or_insert(0) += 1;
+            }
+        }
+        latencies.sort();
+        let failed: usize = errors.values().sum();
+        let avg_latency = if latencies.is_empty() {
+            Duration::ZERO
+        } else {
+            latencies.iter().sum::<Duration>() / latencies.len() as u32
+        };
+        let p95_latency = percentile(&latencies, 95.0);
+
+        let report = LoadTestReport {
+            provider_name: provider_name.to_string(),
+            model: model.as_str().to_string(),
+            requests,
+            concurrency,
+            succeeded: requests - failed,
+            failed,
+            errors,
+            avg_latency,
+            p95_latency,
+            max_latency: latencies.last().copied().unwrap_or_default(),
+            total_duration,
+        };
+        info!(
+            provider = %provider_name,
+            succeeded = report.succeeded,
+            failed = report.failed,
+            avg_latency_ms = report.avg_latency.as_millis() as u64,
+            "Load test finished"
+        );
+        report
+    }
+
+    /// Send one load test request and record it, returning its latency and
+    /// error, if any
+    async fn load_test_request(
+        &self,
+        client: &Client,
+        provider_name: &str,
+        model: &ModelId,
+    ) -> (Duration, Option<String>) {
+        let context = Context::default()
+            .add_message(ContextMessage::user(LOAD_TEST_PROMPT, model.clone().into()));
+        let mut measurement =
+            PerformanceMeasurement::new(provider_name.to_string(), RequestType::Inference);
+        measurement.model_name = Some(model.as_str().to_string());
+
+        let mut response_size = 0;
+        let mut error = None;
+        let mut chunks = Box::pin(client.chat_stream(model, context));
+        while let Some(chunk) = chunks.next().await {
+            match chunk {
+                Ok(chunk) => response_size += chunk.delta.len(),
+                Err(chunk_error) => error = Some(chunk_error.to_string()),
+            }
+        }
+
+        let mut measurement = match &error {
+            Some(error) => {
+                warn!(provider = %provider_name, error = %error, "Load test request failed");
+                measurement
+                    .metadata
+                    .insert("error".to_string(), error.clone());
+                measurement.complete_failure()
+            }
+            None => measurement.complete_success(),
+        };
+        measurement.response_size_bytes = Some(response_size);
+        let latency = measurement.duration();
+        self.record_measurement(measurement).await;
+        (latency, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use forge_app::domain::{HttpConfig, Provider, RetryConfig};
+    use pretty_assertions::assert_eq;
+    use reqwest::Url;
+
+    use super::*;
+    use crate::mock_server::{MockOllamaServer, ScriptedResponse};
+    use crate::performance::PerformanceConfig;
+
+    #[tokio::test]
+    async fn test_load_test_counts_errors() {
+        let server = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/api/chat",
+                ScriptedResponse::chat_stream("llama3.2", &["Hi"]),
+            )
+            .on(
+                "POST",
+                "/api/chat",
+                ScriptedResponse::json(400, serde_json::json!({ "error": "bad request" })),
+            )
+            .start()
+            .await;
+        let client = Client::new(
+            Provider::Ollama { url: Url::parse(&format!("{}/", server.url())).unwrap() },
+            Arc::new(RetryConfig::default()),
+            "dev",
+            &HttpConfig::default(),
+        )
+        .unwrap();
+        let fixture = PerformanceMonitor::new(PerformanceConfig::default());
+        fixture.start().await.unwrap();
+
+        let actual = fixture
+            .run_load_test(&client, "ollama", &ModelId::new("llama3.2"), 4, 1)
+            .await;
+        fixture.stop().await;
+
+        assert_eq!((actual.succeeded, actual.failed), (1, 3));
+        assert_eq!(actual.errors.values().sum::<usize>(), 3);
+        let metrics = fixture.get_provider_metrics("ollama").await.unwrap();
+        assert_eq!(
+            (metrics.successful_requests, metrics.failed_requests),
+            (1, 3)
+        );
+    }
+}
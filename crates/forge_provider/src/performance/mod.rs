@@ -1,21 +1,51 @@
 //! Performance monitoring and optimization for local AI providers
 
+mod admission;
+mod alerts;
 mod cli;
+mod compare;
+mod deprecation;
+mod eviction;
+mod export;
+mod load_test;
 mod optimization;
-
-use std::collections::HashMap;
+mod persistence;
+mod quality;
+mod system_metrics;
+mod throughput;
+mod warm_standby;
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+pub use admission::*;
+pub use alerts::*;
+use chrono::{DateTime, Utc};
 pub use cli::*;
+pub use compare::*;
+pub use deprecation::*;
 use derive_setters::Setters;
+pub use eviction::*;
+pub use export::*;
+pub use load_test::*;
 pub use optimization::*;
+pub use quality::*;
 use serde::{Deserialize, Serialize};
+pub use system_metrics::*;
+pub use throughput::*;
 use tokio::sync::RwLock;
+use tokio::task::{JoinHandle, JoinSet};
 use tracing::{debug, info};
+pub use warm_standby::*;
+
+use crate::redaction::{HashRedactor, Redactor};
+use crate::timing::RequestTiming;
 
 /// Performance metrics for a provider
-#[derive(Debug, Clone, Serialize, Setters)]
+#[derive(Debug, Clone, Serialize, Deserialize, Setters)]
 #[setters(strip_option, into)]
 pub struct ProviderMetrics {
     /// Provider name
@@ -38,16 +68,24 @@ pub struct ProviderMetrics {
     pub p99_response_time: Duration,
     /// Throughput (requests per second)
     pub throughput: f64,
+    /// Response throughput (bytes per second) over the same window as
+    /// `throughput`
+    #[serde(default)]
+    pub bytes_per_second: f64,
+    /// Total response bytes across requests that reported a size
+    #[serde(default)]
+    pub total_bytes: u64,
     /// Model loading time (for local providers)
     pub model_loading_time: Option<Duration>,
     /// Memory usage (MB)
     pub memory_usage_mb: Option<u64>,
     /// CPU usage percentage
     pub cpu_usage_percent: Option<f64>,
+    /// Network timing breakdown across requests that reported one
+    pub network_timing: NetworkTimingMetrics,
     /// Last updated timestamp
-    #[serde(skip)]
     #[setters(skip)]
-    pub last_updated: Instant,
+    pub last_updated: DateTime<Utc>,
 }
 
 impl Default for ProviderMetrics {
@@ -63,10 +101,52 @@ impl Default for ProviderMetrics {
             p95_response_time: Duration::from_millis(0),
             p99_response_time: Duration::from_millis(0),
             throughput: 0.0,
+            bytes_per_second: 0.0,
+            total_bytes: 0,
             model_loading_time: None,
             memory_usage_mb: None,
             cpu_usage_percent: None,
-            last_updated: Instant::now(),
+            network_timing: NetworkTimingMetrics::default(),
+            last_updated: Utc::now(),
+        }
+    }
+}
+
+/// Aggregated network timing for a provider, separating time spent on the
+/// network from time spent waiting on the model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkTimingMetrics {
+    /// Requests that reported a timing breakdown
+    pub samples: u64,
+    /// Requests that opened a new connection
+    pub new_connections: u64,
+    /// Average DNS resolution time for new connections
+    pub avg_dns_time: Duration,
+    /// Average TCP connect time for new connections
+    pub avg_connect_time: Duration,
+    /// Average TLS handshake time for new connections, counting plain HTTP
+    /// connections as none
+    #[serde(default)]
+    pub avg_tls_time: Duration,
+    /// Average time to first byte
+    pub avg_ttfb: Duration,
+}
+
+impl NetworkTimingMetrics {
+    /// Fold a request's timing into the running averages
+    pub fn record(&mut self, timing: &RequestTiming) {
+        let running =
+            |avg: Duration, n: u64, value: Duration| (avg * (n - 1) as u32 + value) / n as u32;
+
+        self.samples += 1;
+        self.avg_ttfb = running(self.avg_ttfb, self.samples, timing.ttfb);
+
+        if let Some(connect) = timing.connect {
+            self.new_connections += 1;
+            let n = self.new_connections;
+            self.avg_connect_time = running(self.avg_connect_time, n, connect);
+            self.avg_dns_time = running(self.avg_dns_time, n, timing.dns.unwrap_or_default());
+            self.avg_tls_time = running(self.avg_tls_time, n, timing.tls.unwrap_or_default());
         }
     }
 }
@@ -93,7 +173,7 @@ pub struct PerformanceMeasurement {
 }
 
 /// Type of request being measured
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum RequestType {
     /// Model inference request
     Inference,
@@ -119,6 +199,17 @@ pub struct PerformanceConfig {
     pub benchmark_targets: BenchmarkTargets,
     /// Metrics collection interval
     pub collection_interval: Duration,
+    /// Recent response times kept per provider for percentile calculation
+    pub percentile_window: usize,
+    /// Trailing window over which throughput is computed; older
+    /// measurements are pruned by the collection task
+    pub metrics_window: Duration,
+    /// Minimum time between repeated alerts for a threshold that stays
+    /// breached
+    pub alert_debounce: Duration,
+    /// File metrics are loaded from by [`PerformanceMonitor::start`] and
+    /// flushed to by [`PerformanceMonitor::shutdown`]
+    pub persistence_path: Option<PathBuf>,
 }
 
 /// Alert thresholds for performance monitoring
@@ -147,6 +238,8 @@ pub struct BenchmarkTargets {
     pub target_success_rate: f64,
     /// Target throughput
     pub target_throughput: f64,
+    /// Minimum quality score (0.0 to 1.0) for providers with a quality result
+    pub min_quality_score: f64,
     /// Cloud provider baseline metrics for comparison
     pub cloud_baseline: Option<ProviderMetrics>,
 }
@@ -154,12 +247,41 @@ pub struct BenchmarkTargets {
 /// Performance monitoring service
 pub struct PerformanceMonitor {
     config: PerformanceConfig,
-    metrics: Arc<RwLock<HashMap<String, ProviderMetrics>>>,
+    metrics: Arc<RwLock<BTreeMap<String, ProviderMetrics>>>,
     measurements: Arc<RwLock<Vec<PerformanceMeasurement>>>,
+    redactor: Arc<dyn Redactor>,
+    quality_scores: Arc<RwLock<HashMap<String, f64>>>,
+    model_eol: Option<ModelEolConfig>,
+    response_samples: Arc<RwLock<HashMap<String, VecDeque<Duration>>>>,
+    throughput_windows: Arc<RwLock<HashMap<String, ThroughputWindow>>>,
+    /// Metrics broken down by provider and model
+    model_metrics: Arc<RwLock<HashMap<(String, String), ModelMetrics>>>,
+    collection_task: std::sync::Mutex<Option<JoinHandle<()>>>,
+    /// Cleared by [`PerformanceMonitor::stop`]; measurements are dropped and
+    /// the collection task exits while unset
+    running: Arc<AtomicBool>,
+    /// Set once metrics saved at the persistence path have been loaded, so
+    /// they are folded in only once per monitor. Held while loading so
+    /// concurrent restores do not fold them in twice.
+    restored: tokio::sync::Mutex<bool>,
+    /// Recordings started by [`PerformanceMonitor::record_in_background`],
+    /// which [`PerformanceMonitor::shutdown`] waits for
+    pending_recordings: std::sync::Mutex<JoinSet<()>>,
+    system_sampler: Option<Arc<SystemSampler>>,
+    alerts: Arc<AlertDispatcher>,
 }
 
-/// Performance optimization recommendations
+/// Metrics for one model on one provider, with the sample windows they are
+/// computed from
 #[derive(Debug, Clone)]
+struct ModelMetrics {
+    metrics: ProviderMetrics,
+    samples: VecDeque<Duration>,
+    throughput: ThroughputWindow,
+}
+
+/// Performance optimization recommendations
+#[derive(Debug, Clone, Serialize)]
 pub struct OptimizationRecommendation {
     /// Provider name
     pub provider_name: String,
@@ -176,7 +298,7 @@ pub struct OptimizationRecommendation {
 }
 
 /// Type of optimization recommendation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum RecommendationType {
     /// Model loading optimization
     ModelLoading,
@@ -190,10 +312,12 @@ pub enum RecommendationType {
     Configuration,
     /// Provider selection optimization
     ProviderSelection,
+    /// Model deprecation and migration
+    Deprecation,
 }
 
 /// Priority level for recommendations
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub enum Priority {
     Low,
     Medium,
@@ -206,19 +330,53 @@ impl PerformanceMonitor {
     pub fn new(config: PerformanceConfig) -> Self {
         Self {
             config,
-            metrics: Arc::new(RwLock::new(HashMap::new())),
+            metrics: Arc::new(RwLock::new(BTreeMap::new())),
             measurements: Arc::new(RwLock::new(Vec::new())),
+            redactor: Arc::new(HashRedactor::default()),
+            quality_scores: Arc::new(RwLock::new(HashMap::new())),
+            model_eol: None,
+            response_samples: Arc::new(RwLock::new(HashMap::new())),
+            throughput_windows: Arc::new(RwLock::new(HashMap::new())),
+            model_metrics: Arc::new(RwLock::new(HashMap::new())),
+            collection_task: std::sync::Mutex::new(None),
+            running: Arc::new(AtomicBool::new(true)),
+            restored: tokio::sync::Mutex::new(false),
+            pending_recordings: std::sync::Mutex::new(JoinSet::new()),
+            system_sampler: None,
+            alerts: Arc::new(AlertDispatcher::default()),
         }
     }
 
+    /// Raise migration recommendations for active models nearing or past
+    /// their end-of-life date
+    pub fn with_model_eol(mut self, model_eol: ModelEolConfig) -> Self {
+        self.model_eol = Some(model_eol);
+        self
+    }
+
+    /// Sample provider process memory and CPU usage on every measurement
+    pub fn with_system_sampler(mut self, sampler: Arc<SystemSampler>) -> Self {
+        self.system_sampler = Some(sampler);
+        self
+    }
+
+    /// Redact measurement metadata before it is recorded. Metadata is hashed
+    /// by default.
+    pub fn with_redactor(mut self, redactor: Arc<dyn Redactor>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
     /// Start performance monitoring
     pub async fn start(&self) -> anyhow::Result<()> {
+        self.restore().await?;
         if !self.config.enabled {
             info!("Performance monitoring is disabled");
             return Ok(());
         }
 
         info!("Starting performance monitoring");
+        self.running.store(true, Ordering::SeqCst);
 
         // Start metrics collection task
         self.start_metrics_collection().await;
@@ -226,24 +384,100 @@ impl PerformanceMonitor {
         Ok(())
     }
 
-    /// Start metrics collection background task
+    /// Start metrics collection background task, replacing any task that is
+    /// already running
     async fn start_metrics_collection(&self) {
         let interval = self.config.collection_interval;
-        let _metrics = Arc::clone(&self.metrics);
-        let _measurements = Arc::clone(&self.measurements);
+        let window = self.config.metrics_window;
+        let metrics = Arc::clone(&self.metrics);
+        let measurements = Arc::clone(&self.measurements);
+        let throughput_windows = Arc::clone(&self.throughput_windows);
+        let running = Arc::clone(&self.running);
+        let alerts = Arc::clone(&self.alerts);
+        let alert_thresholds = self.config.alert_thresholds.clone();
+        let alert_debounce = self.config.alert_debounce;
+
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if !running.load(Ordering::SeqCst) {
+                    debug!("Metrics collection task exiting");
+                    break;
+                }
+                let now = tokio::time::Instant::now().into_std();
+                collect_metrics(&metrics, &measurements, &throughput_windows, window, now).await;
+
+                // Re-notify breaches that persist without new measurements
+                let snapshot = metrics.read().await.clone();
+                for provider_metrics in snapshot.values() {
+                    alerts
+                        .evaluate_at(&alert_thresholds, provider_metrics, alert_debounce, now)
+                        .await;
+                }
+            }
+        });
 
-        // Note: In a real implementation, we would spawn this as a background task
+        if let Some(previous) = self.collection_task.lock().unwrap().replace(task) {
+            previous.abort();
+        }
         info!(
-            "Would start metrics collection with interval {:?}",
-            interval
+            interval_ms = interval.as_millis() as u64,
+            "Started metrics collection"
         );
     }
 
+    /// Stop monitoring: signal the metrics collection task to exit and drop
+    /// measurements recorded until the next [`PerformanceMonitor::start`].
+    /// Returns whether a collection task was running.
+    pub async fn stop(&self) -> bool {
+        self.running.store(false, Ordering::SeqCst);
+        let task = self.collection_task.lock().unwrap().take();
+        let Some(task) = task else {
+            info!("Performance monitoring stopped, no collection task was running");
+            return false;
+        };
+
+        let was_running = !task.is_finished();
+        task.abort();
+        let _ = task.await;
+        info!(was_running, "Stopped metrics collection");
+        was_running
+    }
+
+    /// Whether the monitor is accepting measurements
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// Whether the metrics collection task is running
+    pub fn is_collecting(&self) -> bool {
+        self.collection_task
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|task| !task.is_finished())
+    }
+
     /// Record a performance measurement
-    pub async fn record_measurement(&self, measurement: PerformanceMeasurement) {
+    pub async fn record_measurement(&self, mut measurement: PerformanceMeasurement) {
         if !self.config.enabled {
             return;
         }
+        if !self.is_running() {
+            debug!(
+                provider = %measurement.provider_name,
+                "Performance monitoring stopped, dropping measurement"
+            );
+            return;
+        }
+
+        for (key, value) in measurement.metadata.iter_mut() {
+            if !RequestTiming::METADATA_KEYS.contains(&key.as_str()) {
+                *value = self.redactor.redact(value);
+            }
+        }
 
         debug!(
             "Recording measurement for {}: {:?} - {}ms",
@@ -265,63 +499,127 @@ impl PerformanceMonitor {
 
         // Update provider metrics
         self.update_provider_metrics(&measurement).await;
+        self.check_alerts(&measurement.provider_name).await;
+    }
+
+    /// Record `measurement` on a background task, for callers that cannot
+    /// wait for it. [`PerformanceMonitor::shutdown`] waits for these
+    /// recordings before stopping.
+    pub fn record_in_background(self: &Arc<Self>, measurement: PerformanceMeasurement) {
+        let monitor = Arc::clone(self);
+        let mut pending = self.pending_recordings.lock().unwrap();
+        // Reap finished recordings so the set does not grow
+        while pending.try_join_next().is_some() {}
+        pending.spawn(async move { monitor.record_measurement(measurement).await });
+    }
+
+    /// Wait for the background recordings started so far
+    async fn drain_recordings(&self) {
+        let mut pending = std::mem::take(&mut *self.pending_recordings.lock().unwrap());
+        while pending.join_next().await.is_some() {}
     }
 
     /// Update provider metrics based on a new measurement
     async fn update_provider_metrics(&self, measurement: &PerformanceMeasurement) {
+        let usage = self
+            .system_sampler
+            .as_ref()
+            .and_then(|sampler| sampler.sample_process(&measurement.provider_name));
+
         let mut metrics = self.metrics.write().await;
 
         let provider_metrics = metrics
             .entry(measurement.provider_name.clone())
             .or_insert_with(|| ProviderMetrics::new(&measurement.provider_name));
 
-        // Update counters
-        provider_metrics.total_requests += 1;
-        if measurement.success {
-            provider_metrics.successful_requests += 1;
-        } else {
-            provider_metrics.failed_requests += 1;
+        if let Some(usage) = usage {
+            provider_metrics.memory_usage_mb = Some(usage.memory_usage_mb);
+            provider_metrics.cpu_usage_percent = Some(usage.cpu_usage_percent);
         }
 
-        let response_time = measurement.duration();
+        provider_metrics.record_request(measurement);
 
-        // Update response time metrics
-        if provider_metrics.total_requests == 1 {
-            // First measurement
-            provider_metrics.avg_response_time = response_time;
-            provider_metrics.min_response_time = response_time;
-            provider_metrics.max_response_time = response_time;
-            provider_metrics.p95_response_time = response_time;
-            provider_metrics.p99_response_time = response_time;
-        } else {
-            // Update running averages and extremes
-            let total = provider_metrics.total_requests;
-            let prev_avg = provider_metrics.avg_response_time;
-            provider_metrics.avg_response_time = Duration::from_nanos(
-                ((prev_avg.as_nanos() * (total - 1) as u128 + response_time.as_nanos())
-                    / total as u128)
-                    .try_into()
-                    .unwrap_or(u64::MAX),
+        // Recompute percentiles over the recent response time window
+        {
+            let mut samples = self.response_samples.write().await;
+            let window = samples
+                .entry(measurement.provider_name.clone())
+                .or_default();
+            provider_metrics.record_percentiles(
+                window,
+                measurement.duration(),
+                self.config.percentile_window,
             );
+        }
 
-            if response_time < provider_metrics.min_response_time {
-                provider_metrics.min_response_time = response_time;
-            }
-            if response_time > provider_metrics.max_response_time {
-                provider_metrics.max_response_time = response_time;
-            }
+        // Recompute throughput over the trailing window
+        {
+            let mut windows = self.throughput_windows.write().await;
+            let window = windows
+                .entry(measurement.provider_name.clone())
+                .or_insert_with(|| ThroughputWindow::new(self.config.metrics_window));
+            window.record_bytes_at(measurement.end_time, response_bytes(measurement));
+            provider_metrics.throughput = window.rate_at(measurement.end_time);
+            provider_metrics.bytes_per_second = window.bytes_rate_at(measurement.end_time);
         }
 
-        // Calculate throughput (simplified)
-        let time_window = Duration::from_secs(60); // 1 minute window
-        provider_metrics.throughput =
-            provider_metrics.total_requests as f64 / time_window.as_secs() as f64;
+        // The same aggregates per model, when the measurement names one
+        if let Some(model_name) = &measurement.model_name {
+            let mut model_metrics = self.model_metrics.write().await;
+            let model = model_metrics
+                .entry((measurement.provider_name.clone(), model_name.clone()))
+                .or_insert_with(|| ModelMetrics {
+                    metrics: ProviderMetrics::new(&measurement.provider_name),
+                    samples: VecDeque::new(),
+                    throughput: ThroughputWindow::new(self.config.metrics_window),
+                });
+            model.metrics.record_request(measurement);
+            model.metrics.record_percentiles(
+                &mut model.samples,
+                measurement.duration(),
+                self.config.percentile_window,
+            );
+            model
+                .throughput
+                .record_bytes_at(measurement.end_time, response_bytes(measurement));
+            model.metrics.throughput = model.throughput.rate_at(measurement.end_time);
+            model.metrics.bytes_per_second = model.throughput.bytes_rate_at(measurement.end_time);
+        }
+    }
+
+    /// Record the quality score (0.0 to 1.0) of a provider's answers against
+    /// reference cases
+    pub async fn record_quality_score(&self, provider_name: &str, score: f64) {
+        debug!(provider = provider_name, score, "Recording quality score");
+        self.quality_scores
+            .write()
+            .await
+            .insert(provider_name.to_string(), score.clamp(0.0, 1.0));
+    }
 
-        provider_metrics.last_updated = Instant::now();
+    /// Run a quality benchmark for a provider and record its score
+    pub async fn run_quality_benchmark<F, Fut>(
+        &self,
+        provider_name: &str,
+        benchmark: &QualityBenchmark,
+        answer: F,
+    ) -> QualityReport
+    where
+        F: FnMut(String) -> Fut,
+        Fut: std::future::Future<Output = anyhow::Result<String>>,
+    {
+        let report = benchmark.evaluate(answer).await;
+        self.record_quality_score(provider_name, report.score).await;
+        report
+    }
+
+    /// Get the measurements currently held in memory, oldest first
+    pub async fn get_measurements(&self) -> Vec<PerformanceMeasurement> {
+        self.measurements.read().await.clone()
     }
 
-    /// Get metrics for all providers
-    pub async fn get_all_metrics(&self) -> HashMap<String, ProviderMetrics> {
+    /// Get metrics for all providers, ordered by provider name
+    pub async fn get_all_metrics(&self) -> BTreeMap<String, ProviderMetrics> {
         let metrics = self.metrics.read().await;
         metrics.clone()
     }
@@ -332,6 +630,54 @@ impl PerformanceMonitor {
         metrics.get(provider_name).cloned()
     }
 
+    /// Get metrics for `model_name` served by `provider_name`. Only
+    /// measurements that name their model are counted.
+    pub async fn get_model_metrics(
+        &self,
+        provider_name: &str,
+        model_name: &str,
+    ) -> Option<ProviderMetrics> {
+        self.model_metrics
+            .read()
+            .await
+            .get(&(provider_name.to_string(), model_name.to_string()))
+            .map(|model| model.metrics.clone())
+    }
+
+    /// Clear all accumulated metrics and measurements. Quality scores and
+    /// the collection task are left alone.
+    pub async fn reset(&self) {
+        // Locks are taken one at a time so a concurrent collection tick or
+        // recording cannot deadlock against the reset
+        self.measurements.write().await.clear();
+        self.metrics.write().await.clear();
+        self.response_samples.write().await.clear();
+        self.throughput_windows.write().await.clear();
+        self.model_metrics.write().await.clear();
+        info!("Reset performance metrics");
+    }
+
+    /// Clear the metrics and measurements of one provider, returning whether
+    /// it had any metrics
+    pub async fn reset_provider(&self, provider_name: &str) -> bool {
+        self.measurements
+            .write()
+            .await
+            .retain(|measurement| measurement.provider_name != provider_name);
+        let existed = self.metrics.write().await.remove(provider_name).is_some();
+        self.response_samples.write().await.remove(provider_name);
+        self.throughput_windows.write().await.remove(provider_name);
+        self.model_metrics
+            .write()
+            .await
+            .retain(|(provider, _), _| provider != provider_name);
+        info!(
+            provider = provider_name,
+            "Reset provider performance metrics"
+        );
+        existed
+    }
+
     /// Get performance summary across all providers
     pub async fn get_performance_summary(&self) -> PerformanceSummary {
         let metrics = self.metrics.read().await;
@@ -452,17 +798,42 @@ impl PerformanceMonitor {
             }
         }
 
+        drop(metrics);
+        if let Some(model_eol) = &self.model_eol {
+            let active_models = self.active_models().await;
+            let today = chrono::Utc::now().date_naive();
+            recommendations.extend(model_eol.recommendations(&active_models, today));
+        }
+
         // Sort recommendations by priority
         recommendations.sort_by(|a, b| b.priority.cmp(&a.priority));
         recommendations
     }
 
+    /// Distinct `(provider, model)` pairs seen in recorded measurements
+    async fn active_models(&self) -> Vec<(String, String)> {
+        let measurements = self.measurements.read().await;
+        let mut models: Vec<(String, String)> = measurements
+            .iter()
+            .filter_map(|m| {
+                m.model_name
+                    .as_ref()
+                    .map(|model| (m.provider_name.clone(), model.clone()))
+            })
+            .collect();
+        models.sort();
+        models.dedup();
+        models
+    }
+
     /// Compare performance against benchmark targets
     pub async fn benchmark_against_targets(&self) -> BenchmarkReport {
         let metrics = self.metrics.read().await;
-        let mut provider_comparisons = HashMap::new();
+        let quality_scores = self.quality_scores.read().await;
+        let mut provider_comparisons = BTreeMap::new();
 
         for (provider_name, provider_metrics) in metrics.iter() {
+            let quality_score = quality_scores.get(provider_name).copied();
             let comparison = ProviderBenchmarkComparison {
                 provider_name: provider_name.clone(),
                 response_time_vs_target: self.compare_duration(
@@ -477,7 +848,8 @@ impl PerformanceMonitor {
                     provider_metrics.throughput,
                     self.config.benchmark_targets.target_throughput,
                 ),
-                meets_targets: self.meets_all_targets(provider_metrics),
+                quality_score,
+                meets_targets: self.meets_all_targets(provider_metrics, quality_score),
             };
             provider_comparisons.insert(provider_name.clone(), comparison);
         }
@@ -518,7 +890,7 @@ impl PerformanceMonitor {
     }
 
     /// Check if provider meets all benchmark targets
-    fn meets_all_targets(&self, metrics: &ProviderMetrics) -> bool {
+    fn meets_all_targets(&self, metrics: &ProviderMetrics, quality_score: Option<f64>) -> bool {
         let response_time_ok =
             metrics.avg_response_time <= self.config.benchmark_targets.target_response_time;
         let success_rate = if metrics.total_requests > 0 {
@@ -528,14 +900,16 @@ impl PerformanceMonitor {
         };
         let success_rate_ok = success_rate >= self.config.benchmark_targets.target_success_rate;
         let throughput_ok = metrics.throughput >= self.config.benchmark_targets.target_throughput;
+        let quality_ok = quality_score
+            .is_none_or(|score| score >= self.config.benchmark_targets.min_quality_score);
 
-        response_time_ok && success_rate_ok && throughput_ok
+        response_time_ok && success_rate_ok && throughput_ok && quality_ok
     }
 
     /// Calculate overall performance score
     async fn calculate_overall_score(
         &self,
-        comparisons: &HashMap<String, ProviderBenchmarkComparison>,
+        comparisons: &BTreeMap<String, ProviderBenchmarkComparison>,
     ) -> f64 {
         if comparisons.is_empty() {
             return 0.0;
@@ -543,12 +917,7 @@ impl PerformanceMonitor {
 
         let total_score: f64 = comparisons
             .values()
-            .map(|comp| {
-                // Weighted average of different metrics
-                (comp.response_time_vs_target * 0.4)
-                    + (comp.success_rate_vs_target * 0.4)
-                    + (comp.throughput_vs_target * 0.2)
-            })
+            .map(ProviderBenchmarkComparison::weighted_score)
             .sum();
 
         total_score / comparisons.len() as f64
@@ -567,23 +936,40 @@ pub struct PerformanceSummary {
 }
 
 /// Benchmark comparison report
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct BenchmarkReport {
-    pub provider_comparisons: HashMap<String, ProviderBenchmarkComparison>,
+    pub provider_comparisons: BTreeMap<String, ProviderBenchmarkComparison>,
     pub overall_performance_score: f64,
+    #[serde(skip)]
     pub benchmark_timestamp: Instant,
 }
 
 /// Benchmark comparison for a single provider
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ProviderBenchmarkComparison {
     pub provider_name: String,
     pub response_time_vs_target: f64, // Ratio: target/actual (>1 is better)
     pub success_rate_vs_target: f64,  // Ratio: actual/target (>1 is better)
     pub throughput_vs_target: f64,    // Ratio: actual/target (>1 is better)
+    pub quality_score: Option<f64>,   // Reference-case score (0.0 to 1.0)
     pub meets_targets: bool,
 }
 
+impl ProviderBenchmarkComparison {
+    /// Weighted average of the comparison ratios. When a quality score is
+    /// available it carries the same weight as latency so that fast but
+    /// inaccurate providers do not score well.
+    pub fn weighted_score(&self) -> f64 {
+        let performance = (self.response_time_vs_target * 0.4)
+            + (self.success_rate_vs_target * 0.4)
+            + (self.throughput_vs_target * 0.2);
+        match self.quality_score {
+            Some(quality) => performance * 0.6 + quality * 0.4,
+            None => performance,
+        }
+    }
+}
+
 impl PerformanceMeasurement {
     /// Create a new performance measurement
     pub fn new(provider_name: String, request_type: RequestType) -> Self {
@@ -624,6 +1010,12 @@ impl PerformanceMeasurement {
         self
     }
 
+    /// Record the request's network timing breakdown in the metadata
+    pub fn with_timing(mut self, timing: &RequestTiming) -> Self {
+        self.metadata.extend(timing.to_metadata());
+        self
+    }
+
     /// Set the response size
     pub fn with_response_size(mut self, size_bytes: usize) -> Self {
         self.response_size_bytes = Some(size_bytes);
@@ -651,10 +1043,13 @@ impl ProviderMetrics {
             p95_response_time: Duration::from_millis(0),
             p99_response_time: Duration::from_millis(0),
             throughput: 0.0,
+            bytes_per_second: 0.0,
+            total_bytes: 0,
             model_loading_time: None,
             memory_usage_mb: None,
             cpu_usage_percent: None,
-            last_updated: Instant::now(),
+            network_timing: NetworkTimingMetrics::default(),
+            last_updated: Utc::now(),
         }
     }
 
@@ -670,6 +1065,126 @@ impl ProviderMetrics {
     pub fn failure_rate(&self) -> f64 {
         100.0 - self.success_rate()
     }
+
+    /// Fold a measurement into the request counters, response time
+    /// aggregates and network timings
+    fn record_request(&mut self, measurement: &PerformanceMeasurement) {
+        self.total_requests += 1;
+        if measurement.success {
+            self.successful_requests += 1;
+        } else {
+            self.failed_requests += 1;
+        }
+
+        let response_time = measurement.duration();
+        if self.total_requests == 1 {
+            // First measurement
+            self.avg_response_time = response_time;
+            self.min_response_time = response_time;
+            self.max_response_time = response_time;
+        } else {
+            // Update running averages and extremes
+            let total = self.total_requests;
+            let prev_avg = self.avg_response_time;
+            self.avg_response_time = Duration::from_nanos(
+                ((prev_avg.as_nanos() * (total - 1) as u128 + response_time.as_nanos())
+                    / total as u128)
+                    .try_into()
+                    .unwrap_or(u64::MAX),
+            );
+
+            if response_time < self.min_response_time {
+                self.min_response_time = response_time;
+            }
+            if response_time > self.max_response_time {
+                self.max_response_time = response_time;
+            }
+        }
+
+        if let Some(timing) = RequestTiming::from_metadata(&measurement.metadata) {
+            self.network_timing.record(&timing);
+        }
+        self.total_bytes += response_bytes(measurement);
+
+        self.last_updated = Utc::now();
+    }
+
+    /// Add `response_time` to the recent sample `window`, keeping at most
+    /// `capacity` samples, and recompute the percentiles from it
+    fn record_percentiles(
+        &mut self,
+        window: &mut VecDeque<Duration>,
+        response_time: Duration,
+        capacity: usize,
+    ) {
+        window.push_back(response_time);
+        while window.len() > capacity.max(1) {
+            window.pop_front();
+        }
+
+        let mut sorted: Vec<Duration> = window.iter().copied().collect();
+        sorted.sort();
+        self.p95_response_time = percentile(&sorted, 95.0);
+        self.p99_response_time = percentile(&sorted, 99.0);
+    }
+}
+
+/// One collection tick at `now`: prune measurements older than `window` and
+/// recompute each provider's throughput so that idle providers decay
+async fn collect_metrics(
+    metrics: &RwLock<BTreeMap<String, ProviderMetrics>>,
+    measurements: &RwLock<Vec<PerformanceMeasurement>>,
+    throughput_windows: &RwLock<HashMap<String, ThroughputWindow>>,
+    window: Duration,
+    now: Instant,
+) {
+    let mut measurements = measurements.write().await;
+    let before = measurements.len();
+    measurements
+        .retain(|measurement| now.saturating_duration_since(measurement.end_time) <= window);
+
+    // Same order as `update_provider_metrics`: metrics, then throughput
+    let mut metrics = metrics.write().await;
+    let mut throughput_windows = throughput_windows.write().await;
+    for (provider_name, provider_metrics) in metrics.iter_mut() {
+        let (throughput, bytes_per_second) = throughput_windows
+            .get_mut(provider_name)
+            .map_or((0.0, 0.0), |window| {
+                (window.rate_at(now), window.bytes_rate_at(now))
+            });
+        provider_metrics.throughput = throughput;
+        provider_metrics.bytes_per_second = bytes_per_second;
+        provider_metrics.last_updated = Utc::now();
+    }
+
+    debug!(
+        pruned = before - measurements.len(),
+        providers = metrics.len(),
+        "Collected performance metrics"
+    );
+}
+
+impl Drop for PerformanceMonitor {
+    fn drop(&mut self) {
+        if let Some(task) = self.collection_task.get_mut().unwrap().take() {
+            task.abort();
+        }
+    }
+}
+
+/// Response size of `measurement`, or zero when it did not report one
+fn response_bytes(measurement: &PerformanceMeasurement) -> u64 {
+    measurement.response_size_bytes.unwrap_or(0) as u64
+}
+
+/// Nearest-rank percentile of `sorted`, which must be in ascending order.
+/// Small windows resolve to their upper samples rather than interpolating.
+pub(crate) fn percentile(sorted: &[Duration], percentile: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((percentile / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
 }
 
 impl Default for PerformanceConfig {
@@ -680,6 +1195,10 @@ impl Default for PerformanceConfig {
             alert_thresholds: AlertThresholds::default(),
             benchmark_targets: BenchmarkTargets::default(),
             collection_interval: Duration::from_secs(60),
+            percentile_window: 1000,
+            metrics_window: Duration::from_secs(60),
+            alert_debounce: Duration::from_secs(300),
+            persistence_path: None,
         }
     }
 }
@@ -702,6 +1221,7 @@ impl Default for BenchmarkTargets {
             target_response_time: Duration::from_millis(500),
             target_success_rate: 0.99,
             target_throughput: 10.0,
+            min_quality_score: 0.8,
             cloud_baseline: None,
         }
     }
@@ -744,6 +1264,413 @@ mod tests {
         assert_eq!(metrics.failed_requests, 0);
     }
 
+    fn measurement_taking(provider_name: &str, duration: Duration) -> PerformanceMeasurement {
+        let mut measurement =
+            PerformanceMeasurement::new(provider_name.to_string(), RequestType::Inference);
+        measurement.end_time = measurement.start_time + duration;
+        measurement.success = true;
+        measurement
+    }
+
+    #[tokio::test]
+    async fn test_models_aggregate_independently() {
+        let fixture = PerformanceMonitor::new(PerformanceConfig::default());
+        for (model, millis, success) in [
+            ("llama3.2", 100, true),
+            ("qwen2.5", 2000, true),
+            ("llama3.2", 300, true),
+            ("qwen2.5", 4000, false),
+        ] {
+            let mut measurement = measurement_taking("ollama", Duration::from_millis(millis))
+                .with_model(model.to_string());
+            measurement.success = success;
+            fixture.record_measurement(measurement).await;
+        }
+        fixture
+            .record_measurement(measurement_taking("ollama", Duration::from_millis(50)))
+            .await;
+
+        let llama = fixture
+            .get_model_metrics("ollama", "llama3.2")
+            .await
+            .unwrap();
+        let qwen = fixture
+            .get_model_metrics("ollama", "qwen2.5")
+            .await
+            .unwrap();
+        let provider = fixture.get_provider_metrics("ollama").await.unwrap();
+
+        let summarize = |metrics: &ProviderMetrics| {
+            (
+                metrics.total_requests,
+                metrics.failed_requests,
+                metrics.avg_response_time.as_millis(),
+                metrics.max_response_time.as_millis(),
+            )
+        };
+        let actual = (summarize(&llama), summarize(&qwen), provider.total_requests);
+        let expected = ((2, 0, 200, 300), (2, 1, 3000, 4000), 5);
+        assert_eq!(actual, expected);
+        assert!(fixture
+            .get_model_metrics("lmstudio", "llama3.2")
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_percentiles_over_known_durations() {
+        let fixture = PerformanceMonitor::new(PerformanceConfig::default());
+        // Record out of order so the percentiles cannot rely on arrival order
+        for millis in (1..=100).rev() {
+            fixture
+                .record_measurement(measurement_taking("ollama", Duration::from_millis(millis)))
+                .await;
+        }
+
+        let metrics = fixture.get_provider_metrics("ollama").await.unwrap();
+
+        let actual = (metrics.p95_response_time, metrics.p99_response_time);
+        let expected = (Duration::from_millis(95), Duration::from_millis(99));
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_percentiles_with_small_and_bounded_windows() {
+        let fixture =
+            PerformanceMonitor::new(PerformanceConfig::default().percentile_window(10usize));
+        for millis in [30, 10, 20] {
+            fixture
+                .record_measurement(measurement_taking("ollama", Duration::from_millis(millis)))
+                .await;
+        }
+        let metrics = fixture.get_provider_metrics("ollama").await.unwrap();
+        assert_eq!(metrics.p95_response_time, Duration::from_millis(30));
+
+        // Only the ten most recent samples count once the window is full
+        for _ in 0..10 {
+            fixture
+                .record_measurement(measurement_taking("ollama", Duration::from_millis(5)))
+                .await;
+        }
+        let metrics = fixture.get_provider_metrics("ollama").await.unwrap();
+
+        let actual = (metrics.p95_response_time, metrics.p99_response_time);
+        let expected = (Duration::from_millis(5), Duration::from_millis(5));
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_collection_task_ticks_and_prunes() {
+        let fixture = PerformanceMonitor::new(
+            PerformanceConfig::default()
+                .collection_interval(Duration::from_secs(10))
+                .metrics_window(Duration::from_secs(60)),
+        );
+        // Complete the request on the paused clock the collection task ticks on
+        let mut measurement = measurement_taking("ollama", Duration::from_millis(20));
+        measurement.end_time = tokio::time::Instant::now().into_std();
+        fixture.record_measurement(measurement).await;
+        let before = fixture.get_provider_metrics("ollama").await.unwrap();
+
+        fixture.start().await.unwrap();
+        tokio::time::sleep(Duration::from_secs(15)).await;
+
+        let after = fixture.get_provider_metrics("ollama").await.unwrap();
+        assert!(after.last_updated > before.last_updated);
+        assert_eq!(after.throughput, 0.1);
+        assert_eq!(fixture.get_measurements().await.len(), 1);
+
+        // Once the measurement leaves the window it is pruned
+        tokio::time::sleep(Duration::from_secs(70)).await;
+        let actual = fixture.get_measurements().await.len();
+        assert_eq!(actual, 0);
+        assert_eq!(
+            fixture
+                .get_provider_metrics("ollama")
+                .await
+                .unwrap()
+                .throughput,
+            0.0
+        );
+
+        fixture.stop().await;
+        assert!(!fixture.is_collecting());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_recording_with_ticking_collector() {
+        let fixture = Arc::new(PerformanceMonitor::new(
+            PerformanceConfig::default().collection_interval(Duration::from_millis(1)),
+        ));
+        fixture.start().await.unwrap();
+
+        let recordings = (0..200).map(|_| {
+            let monitor = Arc::clone(&fixture);
+            tokio::spawn(async move {
+                monitor
+                    .record_measurement(measurement_taking("ollama", Duration::from_millis(20)))
+                    .await;
+            })
+        });
+        let all = futures::future::join_all(recordings);
+        for recording in tokio::time::timeout(Duration::from_secs(10), all)
+            .await
+            .expect("recording deadlocked against the collection task")
+        {
+            recording.unwrap();
+        }
+        tokio::time::timeout(Duration::from_secs(10), fixture.stop())
+            .await
+            .expect("collection task deadlocked");
+
+        let actual = fixture
+            .get_provider_metrics("ollama")
+            .await
+            .unwrap()
+            .total_requests;
+        assert_eq!(actual, 200);
+    }
+
+    #[tokio::test]
+    async fn test_throughput_follows_trailing_window() {
+        let fixture = PerformanceMonitor::new(
+            PerformanceConfig::default().metrics_window(Duration::from_secs(60)),
+        );
+        let start = Instant::now();
+        let metrics = || async { fixture.get_provider_metrics("ollama").await.unwrap() };
+
+        // One request per second for 30 seconds, then ten per second
+        for secs in 0..30 {
+            let mut measurement = measurement_taking("ollama", Duration::from_millis(20));
+            measurement.end_time = start + Duration::from_secs(secs);
+            fixture.record_measurement(measurement).await;
+        }
+        let steady = metrics().await.throughput;
+        for tick in 0..100 {
+            let mut measurement = measurement_taking("ollama", Duration::from_millis(20));
+            measurement.end_time =
+                start + Duration::from_secs(30) + Duration::from_millis(tick * 100);
+            fixture.record_measurement(measurement).await;
+        }
+        let busy = metrics().await.throughput;
+
+        // Idle providers decay as collection ticks move the window on
+        collect_metrics(
+            &fixture.metrics,
+            &fixture.measurements,
+            &fixture.throughput_windows,
+            Duration::from_secs(60),
+            start + Duration::from_secs(100),
+        )
+        .await;
+        let idle = metrics().await.throughput;
+
+        assert!(busy > steady, "{busy} should exceed {steady}");
+        assert_eq!(idle, 0.0);
+        assert_eq!(metrics().await.total_requests, 130);
+    }
+
+    #[tokio::test]
+    async fn test_bytes_per_second_over_window() {
+        let fixture = PerformanceMonitor::new(
+            PerformanceConfig::default().metrics_window(Duration::from_secs(60)),
+        );
+        let start = Instant::now();
+
+        // 2 KB per second for 10 seconds, plus one request without a size
+        for secs in 1..=10 {
+            let mut measurement =
+                measurement_taking("ollama", Duration::from_millis(20)).with_response_size(2048);
+            measurement.end_time = start + Duration::from_secs(secs);
+            fixture.record_measurement(measurement).await;
+        }
+        let mut measurement = measurement_taking("ollama", Duration::from_millis(20));
+        measurement.end_time = start + Duration::from_secs(10);
+        fixture.record_measurement(measurement).await;
+
+        let metrics = fixture.get_provider_metrics("ollama").await.unwrap();
+        let actual = (metrics.total_bytes, metrics.bytes_per_second);
+        let expected = (20480, 2048.0 * 10.0 / 9.0);
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stop_halts_collection_and_recording() {
+        let fixture = PerformanceMonitor::new(
+            PerformanceConfig::default().collection_interval(Duration::from_secs(10)),
+        );
+        fixture
+            .record_measurement(measurement_taking("ollama", Duration::from_millis(20)))
+            .await;
+        fixture.start().await.unwrap();
+        tokio::time::sleep(Duration::from_secs(15)).await;
+
+        let actual = fixture.stop().await;
+        assert_eq!(actual, true);
+        assert!(!fixture.is_collecting());
+
+        // No tick updates metrics after stop, and new measurements are dropped
+        let stopped = fixture.get_provider_metrics("ollama").await.unwrap();
+        fixture
+            .record_measurement(measurement_taking("ollama", Duration::from_millis(20)))
+            .await;
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        let actual = fixture.get_provider_metrics("ollama").await.unwrap();
+        assert_eq!(actual.last_updated, stopped.last_updated);
+        assert_eq!(actual.total_requests, 1);
+
+        // A second stop reports that nothing was running
+        assert_eq!(fixture.stop().await, false);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_reset_clears_metrics_while_collecting() {
+        let fixture = PerformanceMonitor::new(
+            PerformanceConfig::default().collection_interval(Duration::from_secs(10)),
+        );
+        fixture.start().await.unwrap();
+        for provider_name in ["ollama", "lmstudio"] {
+            fixture
+                .record_measurement(
+                    measurement_taking(provider_name, Duration::from_millis(20))
+                        .with_model("llama3.2".to_string()),
+                )
+                .await;
+        }
+        tokio::time::sleep(Duration::from_secs(15)).await;
+
+        fixture.reset().await;
+        tokio::time::sleep(Duration::from_secs(15)).await;
+
+        assert!(fixture.get_all_metrics().await.is_empty());
+        assert!(fixture.get_measurements().await.is_empty());
+        assert!(fixture
+            .get_model_metrics("ollama", "llama3.2")
+            .await
+            .is_none());
+        assert!(fixture.is_collecting());
+
+        // Recording resumes from zero
+        fixture
+            .record_measurement(measurement_taking("ollama", Duration::from_millis(20)))
+            .await;
+        let actual = fixture.get_provider_metrics("ollama").await.unwrap();
+        assert_eq!(actual.total_requests, 1);
+        fixture.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_reset_provider_keeps_other_providers() {
+        let fixture = PerformanceMonitor::new(PerformanceConfig::default());
+        for provider_name in ["ollama", "lmstudio", "ollama"] {
+            fixture
+                .record_measurement(
+                    measurement_taking(provider_name, Duration::from_millis(20))
+                        .with_model("llama3.2".to_string()),
+                )
+                .await;
+        }
+
+        let removed = fixture.reset_provider("ollama").await;
+        let missing = fixture.reset_provider("ollama").await;
+
+        let actual: Vec<_> = fixture.get_all_metrics().await.into_keys().collect();
+        assert_eq!(actual, vec!["lmstudio".to_string()]);
+        assert_eq!((removed, missing), (true, false));
+        assert_eq!(fixture.get_measurements().await.len(), 1);
+        assert!(fixture
+            .get_model_metrics("ollama", "llama3.2")
+            .await
+            .is_none());
+        assert!(fixture
+            .get_model_metrics("lmstudio", "llama3.2")
+            .await
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_measurement_populates_process_usage_when_sampled() {
+        let sampler =
+            SystemSampler::new().with_process("ollama", ProcessMatcher::Pid(std::process::id()));
+        let fixture = PerformanceMonitor::new(PerformanceConfig::default())
+            .with_system_sampler(Arc::new(sampler));
+
+        fixture
+            .record_measurement(measurement_taking("ollama", Duration::from_millis(20)))
+            .await;
+
+        let actual = fixture.get_provider_metrics("ollama").await.unwrap();
+        if cfg!(feature = "system-metrics") {
+            assert!(actual.memory_usage_mb.unwrap() > 0);
+            assert!(actual.cpu_usage_percent.is_some());
+        } else {
+            assert_eq!(actual.memory_usage_mb, None);
+            assert_eq!(actual.cpu_usage_percent, None);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_measurement_redacts_metadata() {
+        let fixture = PerformanceMonitor::new(PerformanceConfig::default())
+            .with_redactor(Arc::new(crate::redaction::HashRedactor::default()));
+        let measurement =
+            PerformanceMeasurement::new("test-provider".to_string(), RequestType::Inference)
+                .with_metadata(
+                    "prompt".to_string(),
+                    "my account number is 4421".to_string(),
+                )
+                .complete_success();
+
+        fixture.record_measurement(measurement).await;
+
+        let actual = fixture.get_measurements().await;
+        assert_eq!(actual.len(), 1);
+        let prompt = &actual[0].metadata["prompt"];
+        assert!(!prompt.contains("account number"));
+        assert!(prompt.starts_with("[redacted 25 chars #"));
+    }
+
+    #[tokio::test]
+    async fn test_network_timing_aggregated_per_provider() {
+        let fixture = PerformanceMonitor::new(PerformanceConfig::default());
+        let timings = [
+            RequestTiming {
+                dns: Some(Duration::from_millis(4)),
+                connect: Some(Duration::from_millis(20)),
+                tls: Some(Duration::from_millis(30)),
+                ttfb: Duration::from_millis(300),
+                connection_reused: false,
+            },
+            RequestTiming {
+                dns: None,
+                connect: None,
+                tls: None,
+                ttfb: Duration::from_millis(100),
+                connection_reused: true,
+            },
+        ];
+
+        for timing in &timings {
+            let measurement =
+                PerformanceMeasurement::new("openai".to_string(), RequestType::Inference)
+                    .with_timing(timing)
+                    .complete_success();
+            fixture.record_measurement(measurement).await;
+        }
+
+        let actual = fixture
+            .get_provider_metrics("openai")
+            .await
+            .unwrap()
+            .network_timing;
+        assert_eq!(actual.samples, 2);
+        assert_eq!(actual.new_connections, 1);
+        assert_eq!(actual.avg_dns_time, Duration::from_millis(4));
+        assert_eq!(actual.avg_connect_time, Duration::from_millis(20));
+        assert_eq!(actual.avg_tls_time, Duration::from_millis(30));
+        assert_eq!(actual.avg_ttfb, Duration::from_millis(200));
+    }
+
     #[tokio::test]
     async fn test_performance_summary() {
         let config = PerformanceConfig::default();
@@ -829,6 +1756,84 @@ mod tests {
         assert!(has_provider_rec);
     }
 
+    #[tokio::test]
+    async fn test_metrics_serialize_in_stable_provider_order() {
+        let mut config = PerformanceConfig::default();
+        config.alert_thresholds.min_success_rate = 0.9;
+        let monitor = PerformanceMonitor::new(config);
+        for provider in ["zeta", "alpha", "mike"] {
+            let start = Instant::now();
+            monitor
+                .record_measurement(PerformanceMeasurement {
+                    provider_name: provider.to_string(),
+                    start_time: start,
+                    end_time: start + Duration::from_millis(100),
+                    success: false,
+                    response_size_bytes: None,
+                    model_name: None,
+                    request_type: RequestType::Inference,
+                    metadata: HashMap::new(),
+                })
+                .await;
+        }
+
+        let serialize = || async {
+            serde_json::to_string(&(
+                monitor.get_all_metrics().await,
+                monitor.benchmark_against_targets().await,
+                monitor.generate_recommendations().await,
+            ))
+            .unwrap()
+        };
+        let first = serialize().await;
+        let second = serialize().await;
+
+        assert_eq!(first, second);
+        let metrics = serde_json::to_string(&monitor.get_all_metrics().await).unwrap();
+        let positions: Vec<_> = ["alpha", "mike", "zeta"]
+            .iter()
+            .map(|name| metrics.find(&format!("\"{name}\":")).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        let actual: Vec<_> = monitor
+            .generate_recommendations()
+            .await
+            .into_iter()
+            .map(|r| r.provider_name)
+            .collect();
+        let expected = vec!["alpha", "mike", "zeta"];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_recommendations_include_retired_active_model() {
+        let eol = chrono::NaiveDate::from_ymd_opt(2020, 1, 1).unwrap();
+        let monitor = PerformanceMonitor::new(PerformanceConfig::default())
+            .with_model_eol(ModelEolConfig::default().with_eol("gpt-3.5-turbo-0301", eol));
+
+        let measurement = PerformanceMeasurement {
+            provider_name: "openai".to_string(),
+            start_time: Instant::now(),
+            end_time: Instant::now() + Duration::from_millis(50),
+            success: true,
+            response_size_bytes: None,
+            model_name: Some("gpt-3.5-turbo-0301".to_string()),
+            request_type: RequestType::Inference,
+            metadata: HashMap::new(),
+        };
+        monitor.record_measurement(measurement).await;
+
+        let actual: Vec<_> = monitor
+            .generate_recommendations()
+            .await
+            .into_iter()
+            .filter(|r| matches!(r.recommendation_type, RecommendationType::Deprecation))
+            .collect();
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].priority, Priority::High);
+    }
+
     #[tokio::test]
     async fn test_benchmark_comparison() {
         let mut config = PerformanceConfig::default();
@@ -859,4 +1864,54 @@ mod tests {
         assert!(comparison.response_time_vs_target > 1.0); // Faster than target
         assert!(comparison.success_rate_vs_target > 0.0);
     }
+
+    #[tokio::test]
+    async fn test_benchmark_ranks_providers_by_quality() {
+        let mut config = PerformanceConfig::default();
+        config.benchmark_targets.target_response_time = Duration::from_millis(100);
+        config.benchmark_targets.target_success_rate = 0.95;
+        config.benchmark_targets.target_throughput = 0.0;
+        let fixture = PerformanceMonitor::new(config);
+        let benchmark = QualityBenchmark::new(Arc::new(ExactMatchScorer))
+            .with_case("Capital of France?", "Paris")
+            .with_case("2 + 2?", "4");
+
+        for provider in ["accurate", "sloppy"] {
+            let start = Instant::now();
+            let measurement = PerformanceMeasurement {
+                end_time: start + Duration::from_millis(50),
+                start_time: start,
+                ..PerformanceMeasurement::new(provider.to_string(), RequestType::Inference)
+            }
+            .with_model("llama3.2".to_string());
+            fixture
+                .record_measurement(PerformanceMeasurement { success: true, ..measurement })
+                .await;
+        }
+        fixture
+            .run_quality_benchmark("accurate", &benchmark, |prompt| async move {
+                Ok(if prompt.contains("France") {
+                    "Paris"
+                } else {
+                    "4"
+                }
+                .to_string())
+            })
+            .await;
+        fixture
+            .run_quality_benchmark("sloppy", &benchmark, |_| async move {
+                Ok("I think it is 5".to_string())
+            })
+            .await;
+
+        let actual = fixture.benchmark_against_targets().await;
+
+        let accurate = &actual.provider_comparisons["accurate"];
+        let sloppy = &actual.provider_comparisons["sloppy"];
+        assert_eq!(accurate.quality_score, Some(1.0));
+        assert_eq!(sloppy.quality_score, Some(0.0));
+        assert!(accurate.weighted_score() > sloppy.weighted_score());
+        assert!(accurate.meets_targets);
+        assert!(!sloppy.meets_targets);
+    }
 }
@@ -4,15 +4,21 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use derive_setters::Setters;
+use forge_app::domain::ModelId;
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use tracing::{debug, info, warn};
 
+use super::SystemSampler;
+use crate::ollama::Ollama;
+
 /// Model loading optimizer for local providers
 pub struct ModelLoadingOptimizer {
     config: OptimizationConfig,
     cache: Arc<RwLock<ModelCache>>,
     preloader: ModelPreloader,
+    purge_task: std::sync::Mutex<Option<JoinHandle<()>>>,
 }
 
 /// Configuration for performance optimizations
@@ -27,8 +33,15 @@ pub struct OptimizationConfig {
     pub max_cache_size_mb: u64,
     /// Model cache TTL
     pub cache_ttl: Duration,
+    /// How often the background task drops cache entries past their TTL
+    #[serde(default = "default_cache_purge_interval")]
+    pub cache_purge_interval: Duration,
     /// Preload popular models
     pub preload_popular_models: bool,
+    /// Number of most used models per provider loaded by
+    /// [`ModelLoadingOptimizer::preload`]
+    #[serde(default = "default_preload_top_models")]
+    pub preload_top_models: usize,
     /// Memory optimization settings
     pub memory_optimization: MemoryOptimizationConfig,
     /// CPU optimization settings
@@ -93,7 +106,6 @@ struct CachedModel {
 
 /// Model preloader for anticipating usage
 pub struct ModelPreloader {
-    config: OptimizationConfig,
     usage_patterns: Arc<RwLock<UsagePatterns>>,
 }
 
@@ -155,9 +167,61 @@ impl ModelLoadingOptimizer {
     /// Create a new model loading optimizer
     pub fn new(config: OptimizationConfig) -> Self {
         let cache = ModelCache::new(config.max_cache_size_mb * 1024 * 1024);
-        let preloader = ModelPreloader::new(config.clone());
+        let preloader = ModelPreloader::new();
 
-        Self { config, cache: Arc::new(RwLock::new(cache)), preloader }
+        Self {
+            config,
+            cache: Arc::new(RwLock::new(cache)),
+            preloader,
+            purge_task: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Start a background task that purges expired cache entries every
+    /// `cache_purge_interval`, replacing any task that is already running. A
+    /// zero interval falls back to the default.
+    pub fn start_cache_purge(&self) {
+        let interval = match self.config.cache_purge_interval {
+            Duration::ZERO => {
+                warn!("Cache purge interval is zero, using the default");
+                default_cache_purge_interval()
+            }
+            interval => interval,
+        };
+        let cache = Arc::clone(&self.cache);
+
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let now = tokio::time::Instant::now().into_std();
+                cache.write().await.purge_expired_at(now);
+            }
+        });
+
+        if let Some(previous) = self.purge_task.lock().unwrap().replace(task) {
+            previous.abort();
+        }
+        info!(
+            interval_ms = interval.as_millis() as u64,
+            "Started model cache purge"
+        );
+    }
+
+    /// Stop the background cache purge task, returning whether it was running
+    pub fn stop_cache_purge(&self) -> bool {
+        let task = self.purge_task.lock().unwrap().take();
+        task.is_some_and(|task| {
+            task.abort();
+            true
+        })
+    }
+
+    /// Drop cached models whose TTL has elapsed, returning how many were
+    /// removed
+    pub async fn purge_expired_cache(&self) -> usize {
+        self.cache.write().await.purge_expired()
     }
 
     /// Optimize model loading for a provider
@@ -178,7 +242,10 @@ impl ModelLoadingOptimizer {
 
         // Try model caching optimization
         if self.config.enable_model_caching {
-            match self.apply_model_caching(provider_name, model_name).await {
+            match self
+                .apply_model_caching_at(provider_name, model_name, Instant::now())
+                .await
+            {
                 Ok(improvement) => {
                     improvements.merge(improvement);
                     optimization_types.push(OptimizationType::ModelCaching);
@@ -213,20 +280,31 @@ impl ModelLoadingOptimizer {
         })
     }
 
-    /// Apply model caching optimization
-    async fn apply_model_caching(
+    /// Apply model caching optimization at `now`. Entries past their TTL
+    /// are dropped and treated as misses.
+    async fn apply_model_caching_at(
         &self,
         provider_name: &str,
         model_name: &str,
+        now: Instant,
     ) -> anyhow::Result<PerformanceImprovement> {
         let cache_key = format!("{provider_name}:{model_name}");
 
         let mut cache = self.cache.write().await;
 
+        if cache
+            .models
+            .get(&cache_key)
+            .is_some_and(|cached_model| cached_model.is_expired_at(now))
+        {
+            debug!("Cached model expired: {}", cache_key);
+            cache.remove(&cache_key);
+        }
+
         // Check if model is already cached
         if let Some(cached_model) = cache.models.get_mut(&cache_key) {
             // Update access information
-            cached_model.last_accessed = Instant::now();
+            cached_model.last_accessed = now;
             cached_model.access_count += 1;
 
             debug!("Model cache hit for: {}", cache_key);
@@ -256,8 +334,8 @@ impl ModelLoadingOptimizer {
         let cached_model = CachedModel {
             model_id: cache_key.clone(),
             size_bytes: model_size,
-            cached_at: Instant::now(),
-            last_accessed: Instant::now(),
+            cached_at: now,
+            last_accessed: now,
             access_count: 1,
             ttl: self.config.cache_ttl,
         };
@@ -273,6 +351,42 @@ impl ModelLoadingOptimizer {
         })
     }
 
+    /// Record a request to `model_name` on `provider_name`, making the model
+    /// a candidate for [`ModelLoadingOptimizer::preload`]
+    pub async fn record_model_usage(&self, provider_name: &str, model_name: &str) {
+        self.preloader.record_usage(provider_name, model_name).await;
+    }
+
+    /// Load the most used models of `provider_name` into `provider` so the
+    /// first real requests do not wait for them, returning the models that
+    /// loaded. Does nothing unless preloading popular models is enabled.
+    pub async fn preload(&self, provider_name: &str, provider: &Ollama) -> Vec<ModelId> {
+        if !self.config.enable_model_preloading || !self.config.preload_popular_models {
+            return Vec::new();
+        }
+
+        let models = self
+            .preloader
+            .popular_models(provider_name, self.config.preload_top_models)
+            .await;
+        let mut loaded = Vec::with_capacity(models.len());
+        for model in models {
+            let model = ModelId::new(model);
+            match provider.load_model(&model).await {
+                Ok(()) => loaded.push(model),
+                Err(e) => warn!(
+                    provider = %provider_name,
+                    model = %model,
+                    error = %e,
+                    "Failed to preload model"
+                ),
+            }
+        }
+
+        info!(provider = %provider_name, models = loaded.len(), "Preloaded popular models");
+        loaded
+    }
+
     /// Apply model preloading optimization
     async fn apply_model_preloading(
         &self,
@@ -340,6 +454,13 @@ pub struct CacheStatistics {
     pub hit_rate: f64,
 }
 
+impl CachedModel {
+    /// Whether the entry has outlived its TTL at `now`
+    fn is_expired_at(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.cached_at) >= self.ttl
+    }
+}
+
 impl ModelCache {
     fn new(max_size_bytes: u64) -> Self {
         Self {
@@ -349,7 +470,40 @@ impl ModelCache {
         }
     }
 
-    fn evict_lru_models(&mut self, space_needed: u64) -> anyhow::Result<()> {
+    /// Remove a model, releasing its size from the cache total
+    fn remove(&mut self, model_id: &str) -> Option<CachedModel> {
+        let model = self.models.remove(model_id)?;
+        self.total_size_bytes = self.total_size_bytes.saturating_sub(model.size_bytes);
+        Some(model)
+    }
+
+    /// Drop models whose TTL has elapsed, returning how many were removed
+    fn purge_expired(&mut self) -> usize {
+        self.purge_expired_at(Instant::now())
+    }
+
+    fn purge_expired_at(&mut self, now: Instant) -> usize {
+        let expired: Vec<String> = self
+            .models
+            .values()
+            .filter(|model| model.is_expired_at(now))
+            .map(|model| model.model_id.clone())
+            .collect();
+        for model_id in &expired {
+            self.remove(model_id);
+        }
+        if !expired.is_empty() {
+            debug!(purged = expired.len(), "Purged expired models from cache");
+        }
+        expired.len()
+    }
+
+    /// Evict least recently used models until a model of `incoming_bytes`
+    /// fits within the cache limit
+    fn evict_lru_models(&mut self, incoming_bytes: u64) -> anyhow::Result<()> {
+        let space_needed =
+            (self.total_size_bytes + incoming_bytes).saturating_sub(self.max_size_bytes);
+
         // Sort models by last accessed time
         let mut models_by_access: Vec<_> = self.models.iter().collect();
         models_by_access.sort_by_key(|(_, model)| model.last_accessed);
@@ -368,8 +522,7 @@ impl ModelCache {
 
         // Remove selected models
         for model_id in models_to_remove {
-            if let Some(model) = self.models.remove(&model_id) {
-                self.total_size_bytes -= model.size_bytes;
+            if self.remove(&model_id).is_some() {
                 debug!("Evicted model from cache: {}", model_id);
             }
         }
@@ -387,9 +540,8 @@ impl ModelCache {
 }
 
 impl ModelPreloader {
-    fn new(config: OptimizationConfig) -> Self {
+    fn new() -> Self {
         Self {
-            config,
             usage_patterns: Arc::new(RwLock::new(UsagePatterns::default())),
         }
     }
@@ -418,6 +570,27 @@ impl ModelPreloader {
             .or_insert(0.0) += 1.0;
     }
 
+    /// The `limit` most used models of `provider_name`, most used first
+    async fn popular_models(&self, provider_name: &str, limit: usize) -> Vec<String> {
+        let patterns = self.usage_patterns.read().await;
+        let prefix = format!("{provider_name}:");
+
+        let mut models: Vec<_> = patterns
+            .model_frequency
+            .iter()
+            .filter_map(|(key, frequency)| Some((key.strip_prefix(&prefix)?, *frequency)))
+            .collect();
+        models.sort_by(|(a, a_frequency), (b, b_frequency)| {
+            b_frequency.cmp(a_frequency).then_with(|| a.cmp(b))
+        });
+
+        models
+            .into_iter()
+            .take(limit)
+            .map(|(model, _)| model.to_string())
+            .collect()
+    }
+
     async fn get_related_models(&self, provider_name: &str, model_name: &str) -> Vec<String> {
         let patterns = self.usage_patterns.read().await;
 
@@ -463,6 +636,22 @@ impl Default for PerformanceImprovement {
     }
 }
 
+fn default_cache_purge_interval() -> Duration {
+    Duration::from_secs(300)
+}
+
+fn default_preload_top_models() -> usize {
+    3
+}
+
+impl Drop for ModelLoadingOptimizer {
+    fn drop(&mut self) {
+        if let Some(task) = self.purge_task.get_mut().unwrap().take() {
+            task.abort();
+        }
+    }
+}
+
 impl Default for OptimizationConfig {
     fn default() -> Self {
         Self {
@@ -470,7 +659,9 @@ impl Default for OptimizationConfig {
             enable_model_preloading: true,
             max_cache_size_mb: 1024,              // 1GB
             cache_ttl: Duration::from_secs(3600), // 1 hour
+            cache_purge_interval: default_cache_purge_interval(),
             preload_popular_models: true,
+            preload_top_models: default_preload_top_models(),
             memory_optimization: MemoryOptimizationConfig::default(),
             cpu_optimization: CpuOptimizationConfig::default(),
         }
@@ -502,24 +693,24 @@ impl Default for CpuOptimizationConfig {
 /// System resource monitor for optimization decisions
 pub struct ResourceMonitor {
     config: OptimizationConfig,
+    sampler: SystemSampler,
 }
 
 impl ResourceMonitor {
     pub fn new(config: OptimizationConfig) -> Self {
-        Self { config }
+        Self { config, sampler: SystemSampler::new() }
     }
 
-    /// Get current system resource usage
+    /// Get current system resource usage. Memory and CPU are sampled from the
+    /// host with the `system-metrics` feature; other values are estimates.
     pub async fn get_resource_usage(&self) -> ResourceUsage {
-        // In a real implementation, this would use system APIs to get actual resource
-        // usage
-        ResourceUsage {
-            memory_usage_percent: 45.0,
-            cpu_usage_percent: 30.0,
-            available_memory_mb: 8192,
-            disk_usage_percent: 60.0,
-            network_bandwidth_mbps: 100.0,
+        let mut usage = ResourceUsage::default();
+        if let Some(host) = self.sampler.sample_host() {
+            usage.memory_usage_percent = host.memory_usage_percent;
+            usage.cpu_usage_percent = host.cpu_usage_percent;
+            usage.available_memory_mb = host.available_memory_mb;
         }
+        usage
     }
 
     /// Check if system is under resource pressure
@@ -579,6 +770,19 @@ pub struct ResourceUsage {
     pub network_bandwidth_mbps: f64,
 }
 
+impl Default for ResourceUsage {
+    /// Estimates used when system metrics are unavailable
+    fn default() -> Self {
+        Self {
+            memory_usage_percent: 45.0,
+            cpu_usage_percent: 30.0,
+            available_memory_mb: 8192,
+            disk_usage_percent: 60.0,
+            network_bandwidth_mbps: 100.0,
+        }
+    }
+}
+
 /// Resource optimization recommendation
 #[derive(Debug, Clone)]
 pub struct ResourceRecommendation {
@@ -613,6 +817,58 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::mock_server::MockServer;
+    use crate::ollama::OllamaConfig;
+
+    #[tokio::test]
+    async fn test_preload_loads_most_used_models() {
+        let mut server = MockServer::new().await;
+        let mut mocks = Vec::new();
+        for model in ["llama3.2", "qwen2.5"] {
+            mocks.push(
+                server
+                    .mock_ollama_generate(serde_json::json!({ "model": model }), 200)
+                    .await,
+            );
+        }
+        let provider = OllamaConfig::new()
+            .with_base_url(server.url())
+            .create_provider()
+            .unwrap();
+        let fixture =
+            ModelLoadingOptimizer::new(OptimizationConfig::default().preload_top_models(2usize));
+        let usage = [
+            ("ollama", "qwen2.5", 2),
+            ("ollama", "llama3.2", 3),
+            ("ollama", "phi3", 1),
+            ("lmstudio", "mistral", 5),
+        ];
+        for (provider_name, model, requests) in usage {
+            for _ in 0..requests {
+                fixture.record_model_usage(provider_name, model).await;
+            }
+        }
+
+        let actual = fixture.preload("ollama", &provider).await;
+
+        let expected = vec![ModelId::new("llama3.2"), ModelId::new("qwen2.5")];
+        assert_eq!(actual, expected);
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_preload_disabled_loads_nothing() {
+        let provider = OllamaConfig::new().create_provider().unwrap();
+        let fixture =
+            ModelLoadingOptimizer::new(OptimizationConfig::default().preload_popular_models(false));
+        fixture.record_model_usage("ollama", "llama3.2").await;
+
+        let actual = fixture.preload("ollama", &provider).await;
+
+        assert!(actual.is_empty());
+    }
 
     #[tokio::test]
     async fn test_model_loading_optimizer_creation() {
@@ -665,9 +921,13 @@ mod tests {
         assert!(usage.cpu_usage_percent >= 0.0);
         assert!(usage.available_memory_mb > 0);
 
-        let is_under_pressure = monitor.is_under_pressure().await;
-        assert!(!is_under_pressure); // Should not be under pressure with
-                                     // default values
+        // Real host usage may legitimately be under pressure
+        #[cfg(not(feature = "system-metrics"))]
+        {
+            let is_under_pressure = monitor.is_under_pressure().await;
+            assert!(!is_under_pressure); // Should not be under pressure with
+                                         // default values
+        }
     }
 
     #[tokio::test]
@@ -729,11 +989,119 @@ mod tests {
         cache.total_size_bytes = 1024 * 1024 * 100;
 
         // Try to add another model that would exceed cache size
-        let space_needed = 1024 * 1024 * 150; // 150MB
-        let result = cache.evict_lru_models(space_needed);
+        let incoming_bytes = 1024 * 1024 * 150; // 150MB
+        let result = cache.evict_lru_models(incoming_bytes);
 
         assert!(result.is_ok());
         assert_eq!(cache.models.len(), 0); // Should have evicted the model
         assert_eq!(cache.total_size_bytes, 0);
     }
+
+    #[tokio::test]
+    async fn test_expired_cache_entry_is_a_miss() {
+        let fixture = ModelLoadingOptimizer::new(
+            OptimizationConfig::default().cache_ttl(Duration::from_secs(60)),
+        );
+        let start = Instant::now();
+
+        fixture
+            .apply_model_caching_at("ollama", "llama3.2", start)
+            .await
+            .unwrap();
+        let hit = fixture
+            .apply_model_caching_at("ollama", "llama3.2", start + Duration::from_secs(30))
+            .await
+            .unwrap();
+        let expired = fixture
+            .apply_model_caching_at("ollama", "llama3.2", start + Duration::from_secs(61))
+            .await
+            .unwrap();
+
+        // A hit reports the cached load time, a miss the caching overhead
+        let actual = (
+            hit.response_time_improvement,
+            expired.response_time_improvement,
+        );
+        let expected = (Duration::from_millis(500), Duration::from_millis(200));
+        assert_eq!(actual, expected);
+        let cache = fixture.cache.read().await;
+        assert_eq!(
+            cache.models["ollama:llama3.2"].cached_at,
+            start + Duration::from_secs(61)
+        );
+        assert_eq!(cache.total_size_bytes, 1024 * 1024 * 100);
+    }
+
+    #[tokio::test]
+    async fn test_purge_expired_removes_entries_and_size() {
+        let fixture = ModelLoadingOptimizer::new(
+            OptimizationConfig::default().cache_ttl(Duration::from_secs(60)),
+        );
+        let start = Instant::now();
+        fixture
+            .apply_model_caching_at("ollama", "llama3.2", start)
+            .await
+            .unwrap();
+        fixture
+            .apply_model_caching_at("ollama", "qwen2.5", start + Duration::from_secs(45))
+            .await
+            .unwrap();
+
+        let mut cache = fixture.cache.write().await;
+        let actual = (
+            cache.purge_expired_at(start + Duration::from_secs(59)),
+            cache.purge_expired_at(start + Duration::from_secs(90)),
+        );
+
+        assert_eq!(actual, (0, 1));
+        assert_eq!(
+            cache.models.keys().collect::<Vec<_>>(),
+            vec!["ollama:qwen2.5"]
+        );
+        assert_eq!(cache.total_size_bytes, 1024 * 1024 * 100);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_background_purge_drops_expired_entries() {
+        let fixture = ModelLoadingOptimizer::new(
+            OptimizationConfig::default()
+                .cache_ttl(Duration::from_secs(60))
+                .cache_purge_interval(Duration::from_secs(10)),
+        );
+        fixture
+            .apply_model_caching_at("ollama", "llama3.2", tokio::time::Instant::now().into_std())
+            .await
+            .unwrap();
+        fixture.start_cache_purge();
+
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        let before = fixture.get_cache_stats().await.total_models;
+        tokio::time::sleep(Duration::from_secs(40)).await;
+        let after = fixture.get_cache_stats().await;
+
+        assert_eq!((before, after.total_models, after.total_size_mb), (1, 0, 0));
+        assert!(fixture.stop_cache_purge());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_zero_purge_interval_uses_default() {
+        let fixture = ModelLoadingOptimizer::new(
+            OptimizationConfig::default()
+                .cache_ttl(Duration::from_secs(60))
+                .cache_purge_interval(Duration::ZERO),
+        );
+        fixture
+            .apply_model_caching_at("ollama", "llama3.2", tokio::time::Instant::now().into_std())
+            .await
+            .unwrap();
+        fixture.start_cache_purge();
+
+        tokio::time::sleep(Duration::from_secs(120)).await;
+        let before = fixture.get_cache_stats().await.total_models;
+        tokio::time::sleep(Duration::from_secs(200)).await;
+        let after = fixture.get_cache_stats().await.total_models;
+
+        assert_eq!((before, after), (1, 0));
+        assert!(fixture.stop_cache_purge());
+    }
 }
@@ -0,0 +1,353 @@
+//! Persisting performance metrics across process restarts
+//!
+//! The CLI runs as many short-lived processes, so metrics are saved to a JSON
+//! file on exit and folded back in on the next start. Loading accumulates into
+//! the in-memory metrics rather than replacing them.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Context;
+use tracing::{debug, info};
+
+use super::{collect_metrics, NetworkTimingMetrics, PerformanceMonitor, ProviderMetrics};
+
+impl PerformanceMonitor {
+    /// Wait for measurements still being recorded, stop monitoring and, when
+    /// [`PerformanceConfig::persistence_path`](super::PerformanceConfig::persistence_path)
+    /// is set, fold buffered measurements into the metrics and flush them to
+    /// it. Metrics saved there by earlier processes are kept; if they cannot
+    /// be loaded, nothing is written so they are not overwritten. Calling it
+    /// again flushes the same state.
+    pub async fn shutdown(&self) -> anyhow::Result<()> {
+        self.drain_recordings().await;
+        self.stop().await;
+        self.restore().await?;
+        collect_metrics(
+            &self.metrics,
+            &self.measurements,
+            &self.throughput_windows,
+            self.config.metrics_window,
+            tokio::time::Instant::now().into_std(),
+        )
+        .await;
+
+        match &self.config.persistence_path {
+            Some(path) => self.save_to_path(path).await,
+            None => {
+                debug!("No persistence path configured, performance metrics not saved");
+                Ok(())
+            }
+        }
+    }
+
+    /// Merge the metrics saved at the persistence path into the current
+    /// metrics, unless this monitor already has. A failed load is retried by
+    /// the next call.
+    pub(super) async fn restore(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.config.persistence_path else {
+            return Ok(());
+        };
+        let mut restored = self.restored.lock().await;
+        if !*restored {
+            self.load_from_path(path).await?;
+            *restored = true;
+        }
+        Ok(())
+    }
+
+    /// Write all per-provider metrics to `path` as JSON
+    pub async fn save_to_path(&self, path: &Path) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(&*self.metrics.read().await)?;
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, json)
+            .await
+            .with_context(|| format!("Failed to save performance metrics to {}", path.display()))?;
+
+        debug!(path = %path.display(), "Saved performance metrics");
+        Ok(())
+    }
+
+    /// Merge metrics saved at `path` into the current metrics, returning the
+    /// number of providers loaded. A missing file loads nothing.
+    pub async fn load_from_path(&self, path: &Path) -> anyhow::Result<usize> {
+        let json = match tokio::fs::read_to_string(path).await {
+            Ok(json) => json,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                debug!(path = %path.display(), "No saved performance metrics");
+                return Ok(0);
+            }
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("Failed to read performance metrics from {}", path.display())
+                })
+            }
+        };
+        let saved: BTreeMap<String, ProviderMetrics> = serde_json::from_str(&json)
+            .with_context(|| format!("Invalid performance metrics in {}", path.display()))?;
+
+        let loaded = saved.len();
+        let mut metrics = self.metrics.write().await;
+        for (provider_name, saved) in saved {
+            match metrics.get_mut(&provider_name) {
+                Some(current) => current.merge(&saved),
+                None => {
+                    metrics.insert(provider_name, saved);
+                }
+            }
+        }
+
+        info!(path = %path.display(), providers = loaded, "Loaded performance metrics");
+        Ok(loaded)
+    }
+}
+
+impl ProviderMetrics {
+    /// Fold `other` into these metrics. Counters add up and averages are
+    /// weighted by request count; percentiles and throughput describe the
+    /// current sample window, so `other` only fills them in when this side
+    /// has no requests yet.
+    pub fn merge(&mut self, other: &ProviderMetrics) {
+        if other.total_requests == 0 {
+            return;
+        }
+        if self.total_requests == 0 {
+            let provider_name = std::mem::take(&mut self.provider_name);
+            *self = ProviderMetrics { provider_name, ..other.clone() };
+            return;
+        }
+
+        self.avg_response_time = weighted(
+            self.avg_response_time,
+            self.total_requests,
+            other.avg_response_time,
+            other.total_requests,
+        );
+        self.total_requests += other.total_requests;
+        self.successful_requests += other.successful_requests;
+        self.failed_requests += other.failed_requests;
+        self.total_bytes += other.total_bytes;
+        self.min_response_time = self.min_response_time.min(other.min_response_time);
+        self.max_response_time = self.max_response_time.max(other.max_response_time);
+        self.model_loading_time = self.model_loading_time.or(other.model_loading_time);
+        self.memory_usage_mb = self.memory_usage_mb.or(other.memory_usage_mb);
+        self.cpu_usage_percent = self.cpu_usage_percent.or(other.cpu_usage_percent);
+        self.network_timing.merge(&other.network_timing);
+        self.last_updated = self.last_updated.max(other.last_updated);
+    }
+}
+
+impl NetworkTimingMetrics {
+    /// Fold `other` into these timings, weighting averages by sample count
+    pub fn merge(&mut self, other: &NetworkTimingMetrics) {
+        self.avg_ttfb = weighted(self.avg_ttfb, self.samples, other.avg_ttfb, other.samples);
+        self.avg_connect_time = weighted(
+            self.avg_connect_time,
+            self.new_connections,
+            other.avg_connect_time,
+            other.new_connections,
+        );
+        self.avg_dns_time = weighted(
+            self.avg_dns_time,
+            self.new_connections,
+            other.avg_dns_time,
+            other.new_connections,
+        );
+        self.avg_tls_time = weighted(
+            self.avg_tls_time,
+            self.new_connections,
+            other.avg_tls_time,
+            other.new_connections,
+        );
+        self.samples += other.samples;
+        self.new_connections += other.new_connections;
+    }
+}
+
+fn weighted(a: Duration, a_count: u64, b: Duration, b_count: u64) -> Duration {
+    let total = a_count + b_count;
+    if total == 0 {
+        return a;
+    }
+    let nanos = (a.as_nanos() * a_count as u128 + b.as_nanos() * b_count as u128) / total as u128;
+    Duration::from_nanos(nanos.try_into().unwrap_or(u64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::performance::{PerformanceConfig, PerformanceMeasurement, RequestType};
+
+    fn measurement(provider_name: &str, millis: u64, success: bool) -> PerformanceMeasurement {
+        let mut measurement =
+            PerformanceMeasurement::new(provider_name.to_string(), RequestType::Inference);
+        measurement.end_time = measurement.start_time + Duration::from_millis(millis);
+        measurement.success = success;
+        measurement
+    }
+
+    async fn fixture(measurements: &[(&str, u64, bool)]) -> PerformanceMonitor {
+        let monitor = PerformanceMonitor::new(PerformanceConfig::default());
+        for (provider_name, millis, success) in measurements {
+            monitor
+                .record_measurement(measurement(provider_name, *millis, *success))
+                .await;
+        }
+        monitor
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_preserves_metrics() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("metrics.json");
+        let fixture = fixture(&[("ollama", 200, true), ("ollama", 400, false)]).await;
+        fixture.save_to_path(&path).await.unwrap();
+
+        let restarted = PerformanceMonitor::new(PerformanceConfig::default());
+        let loaded = restarted.load_from_path(&path).await.unwrap();
+
+        let expected = fixture.get_provider_metrics("ollama").await.unwrap();
+        let actual = restarted.get_provider_metrics("ollama").await.unwrap();
+        assert_eq!(loaded, 1);
+        assert_eq!(
+            serde_json::to_value(&actual).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+        assert_eq!(actual.last_updated, expected.last_updated);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_flushes_metrics_to_persistence_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("metrics.json");
+        let fixture =
+            PerformanceMonitor::new(PerformanceConfig::default().persistence_path(path.clone()));
+        fixture.start().await.unwrap();
+        for millis in [200, 400] {
+            fixture
+                .record_measurement(measurement("ollama", millis, true))
+                .await;
+        }
+
+        fixture.shutdown().await.unwrap();
+        fixture.shutdown().await.unwrap();
+
+        let restarted = PerformanceMonitor::new(PerformanceConfig::default());
+        restarted.load_from_path(&path).await.unwrap();
+        let actual = restarted.get_provider_metrics("ollama").await.unwrap();
+        assert_eq!((actual.total_requests, actual.successful_requests), (2, 2));
+        assert!(!fixture.is_collecting());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_keeps_metrics_of_earlier_lifetimes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("metrics.json");
+        let config = PerformanceConfig::default().persistence_path(path.clone());
+        for (millis, success) in [(200, true), (400, false)] {
+            let fixture = PerformanceMonitor::new(config.clone());
+            fixture.start().await.unwrap();
+            fixture
+                .record_measurement(measurement("ollama", millis, success))
+                .await;
+            fixture.shutdown().await.unwrap();
+        }
+
+        let restarted = PerformanceMonitor::new(PerformanceConfig::default());
+        restarted.load_from_path(&path).await.unwrap();
+        let actual = restarted.get_provider_metrics("ollama").await.unwrap();
+        assert_eq!(
+            (
+                actual.total_requests,
+                actual.successful_requests,
+                actual.failed_requests
+            ),
+            (2, 1, 1)
+        );
+        assert_eq!(actual.avg_response_time, Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_keeps_unreadable_metrics_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("metrics.json");
+        tokio::fs::write(&path, "not json").await.unwrap();
+        let fixture =
+            PerformanceMonitor::new(PerformanceConfig::default().persistence_path(path.clone()));
+        let started = fixture.start().await;
+        fixture
+            .record_measurement(measurement("ollama", 200, true))
+            .await;
+
+        let actual = fixture.shutdown().await;
+
+        assert!(started.is_err());
+        assert!(actual.is_err());
+        assert_eq!(tokio::fs::read_to_string(&path).await.unwrap(), "not json");
+    }
+
+    #[tokio::test]
+    async fn test_load_accumulates_counters() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("metrics.json");
+        fixture(&[("ollama", 200, true), ("ollama", 400, false)])
+            .await
+            .save_to_path(&path)
+            .await
+            .unwrap();
+
+        let fixture = fixture(&[("ollama", 600, true), ("lmstudio", 50, true)]).await;
+        fixture.load_from_path(&path).await.unwrap();
+
+        let actual = fixture.get_provider_metrics("ollama").await.unwrap();
+        assert_eq!(
+            (
+                actual.total_requests,
+                actual.successful_requests,
+                actual.failed_requests
+            ),
+            (3, 2, 1)
+        );
+        assert_eq!(actual.avg_response_time, Duration::from_millis(400));
+        assert_eq!(
+            (actual.min_response_time, actual.max_response_time),
+            (Duration::from_millis(200), Duration::from_millis(600))
+        );
+        assert_eq!(fixture.get_all_metrics().await.len(), 2);
+    }
+
+    #[test]
+    fn test_last_updated_survives_serialization() {
+        let mut fixture = ProviderMetrics::new("ollama");
+        fixture.last_updated = Utc.with_ymd_and_hms(2020, 2, 29, 23, 59, 59).unwrap()
+            + chrono::Duration::nanoseconds(123_456_789);
+
+        let json = serde_json::to_string(&fixture).unwrap();
+        let actual: ProviderMetrics = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(actual.last_updated, fixture.last_updated);
+    }
+
+    #[tokio::test]
+    async fn test_missing_file_loads_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = PerformanceMonitor::new(PerformanceConfig::default());
+
+        let actual = fixture
+            .load_from_path(&dir.path().join("missing.json"))
+            .await
+            .unwrap();
+
+        assert_eq!(actual, 0);
+    }
+}
@@ -0,0 +1,288 @@
+//! Output quality scoring against reference answers
+//!
+//! Latency and cost alone favour providers that are cheap and fast but wrong.
+//! A [`QualityBenchmark`] replays reference prompts against a provider and
+//! scores each answer with a [`QualityScorer`], producing a quality score that
+//! feeds into the benchmark report.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::redaction::{HashRedactor, Redactor};
+
+/// A reference prompt and the answer it is expected to produce
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferenceCase {
+    /// Prompt sent to the provider
+    pub prompt: String,
+    /// Expected answer
+    pub expected: String,
+}
+
+/// Scores an answer against the expected answer, from 0.0 (wrong) to 1.0
+#[async_trait::async_trait]
+pub trait QualityScorer: Send + Sync {
+    async fn score(&self, expected: &str, actual: &str) -> anyhow::Result<f64>;
+}
+
+/// Scores 1.0 when the answers match ignoring case and surrounding or repeated
+/// whitespace, 0.0 otherwise
+#[derive(Debug, Clone, Default)]
+pub struct ExactMatchScorer;
+
+#[async_trait::async_trait]
+impl QualityScorer for ExactMatchScorer {
+    async fn score(&self, expected: &str, actual: &str) -> anyhow::Result<f64> {
+        let normalize = |text: &str| {
+            text.split_whitespace()
+                .map(str::to_lowercase)
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        Ok(if normalize(expected) == normalize(actual) {
+            1.0
+        } else {
+            0.0
+        })
+    }
+}
+
+/// Produces embedding vectors for text
+#[async_trait::async_trait]
+pub trait Embedder: Send + Sync {
+    async fn embed(&self, text: &str) -> anyhow::Result<Vec<f32>>;
+}
+
+/// Scores answers by the cosine similarity of their embeddings, clamped to
+/// 0.0-1.0
+pub struct EmbeddingSimilarityScorer<E> {
+    embedder: E,
+}
+
+impl<E: Embedder> EmbeddingSimilarityScorer<E> {
+    pub fn new(embedder: E) -> Self {
+        Self { embedder }
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: Embedder> QualityScorer for EmbeddingSimilarityScorer<E> {
+    async fn score(&self, expected: &str, actual: &str) -> anyhow::Result<f64> {
+        let expected = self.embedder.embed(expected).await?;
+        let actual = self.embedder.embed(actual).await?;
+        anyhow::ensure!(
+            expected.len() == actual.len(),
+            "Embedding dimensions differ: {} vs {}",
+            expected.len(),
+            actual.len()
+        );
+
+        let dot: f64 = expected
+            .iter()
+            .zip(&actual)
+            .map(|(a, b)| *a as f64 * *b as f64)
+            .sum();
+        let norm = |v: &[f32]| v.iter().map(|x| (*x as f64).powi(2)).sum::<f64>().sqrt();
+        let denominator = norm(&expected) * norm(&actual);
+        if denominator == 0.0 {
+            return Ok(0.0);
+        }
+        Ok((dot / denominator).clamp(0.0, 1.0))
+    }
+}
+
+/// Score for a single reference case
+#[derive(Debug, Clone)]
+pub struct CaseScore {
+    pub prompt: String,
+    pub score: f64,
+    pub error: Option<String>,
+}
+
+/// Quality of one provider across all reference cases
+#[derive(Debug, Clone)]
+pub struct QualityReport {
+    /// Mean score across cases (0.0-1.0)
+    pub score: f64,
+    pub cases: Vec<CaseScore>,
+}
+
+/// Set of reference cases and the scorer used to grade answers
+#[derive(Clone)]
+pub struct QualityBenchmark {
+    cases: Vec<ReferenceCase>,
+    scorer: Arc<dyn QualityScorer>,
+    redactor: Arc<dyn Redactor>,
+}
+
+impl QualityBenchmark {
+    pub fn new(scorer: Arc<dyn QualityScorer>) -> Self {
+        Self {
+            cases: Vec::new(),
+            scorer,
+            redactor: Arc::new(HashRedactor::default()),
+        }
+    }
+
+    /// Redact prompts and errors before they are logged. They are hashed by
+    /// default.
+    pub fn with_redactor(mut self, redactor: Arc<dyn Redactor>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Add a reference prompt and its expected answer
+    pub fn with_case(mut self, prompt: impl Into<String>, expected: impl Into<String>) -> Self {
+        self.cases
+            .push(ReferenceCase { prompt: prompt.into(), expected: expected.into() });
+        self
+    }
+
+    /// Send every reference prompt through `answer` and score the results.
+    /// Failed requests and scoring errors count as a score of 0.0.
+    pub async fn evaluate<F, Fut>(&self, mut answer: F) -> QualityReport
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = anyhow::Result<String>>,
+    {
+        let mut cases = Vec::with_capacity(self.cases.len());
+
+        for case in &self.cases {
+            let result = match answer(case.prompt.clone()).await {
+                Ok(actual) => self.scorer.score(&case.expected, &actual).await,
+                Err(e) => Err(e),
+            };
+
+            let (score, error) = match result {
+                Ok(score) => (score.clamp(0.0, 1.0), None),
+                Err(e) => {
+                    warn!(
+                        prompt = %self.redactor.redact(&case.prompt),
+                        error = %self.redactor.redact(&e.to_string()),
+                        "Reference case failed"
+                    );
+                    (0.0, Some(e.to_string()))
+                }
+            };
+            debug!(prompt = %self.redactor.redact(&case.prompt), score, "Scored reference case");
+            cases.push(CaseScore { prompt: case.prompt.clone(), score, error });
+        }
+
+        let score = if cases.is_empty() {
+            0.0
+        } else {
+            cases.iter().map(|case| case.score).sum::<f64>() / cases.len() as f64
+        };
+
+        QualityReport { score, cases }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    struct LetterCountEmbedder;
+
+    #[async_trait::async_trait]
+    impl Embedder for LetterCountEmbedder {
+        async fn embed(&self, text: &str) -> anyhow::Result<Vec<f32>> {
+            let mut counts = vec![0.0; 26];
+            for c in text.to_lowercase().chars().filter(char::is_ascii_lowercase) {
+                counts[(c as u8 - b'a') as usize] += 1.0;
+            }
+            Ok(counts)
+        }
+    }
+
+    fn fixture(scorer: Arc<dyn QualityScorer>) -> QualityBenchmark {
+        QualityBenchmark::new(scorer)
+            .with_case("Capital of France?", "Paris")
+            .with_case("2 + 2?", "4")
+    }
+
+    #[tokio::test]
+    async fn test_exact_match_scores_answers() {
+        let fixture = fixture(Arc::new(ExactMatchScorer));
+
+        let actual = fixture
+            .evaluate(|prompt| async move {
+                Ok(if prompt.contains("France") {
+                    " paris "
+                } else {
+                    "5"
+                }
+                .to_string())
+            })
+            .await;
+
+        assert_eq!(actual.score, 0.5);
+        assert_eq!(actual.cases[0].score, 1.0);
+        assert_eq!(actual.cases[1].score, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_request_scores_zero() {
+        let fixture = fixture(Arc::new(ExactMatchScorer));
+
+        let actual = fixture
+            .evaluate(|_| async move { anyhow::bail!("connection refused") })
+            .await;
+
+        assert_eq!(actual.score, 0.0);
+        assert_eq!(actual.cases[0].error.as_deref(), Some("connection refused"));
+    }
+
+    /// Formatted log output written while installed
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_logs_redact_prompts_by_default() {
+        let fixture = fixture(Arc::new(ExactMatchScorer));
+        let captured = CapturedLogs::default();
+        let writer = captured.clone();
+        let _guard = tracing::subscriber::set_default(
+            tracing_subscriber::fmt()
+                .with_max_level(tracing::Level::DEBUG)
+                .with_writer(move || writer.clone())
+                .finish(),
+        );
+
+        fixture
+            .evaluate(|prompt| async move { anyhow::bail!("timed out answering {prompt}") })
+            .await;
+
+        let actual = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(actual.contains("Reference case failed"));
+        assert!(actual.contains("[redacted 18 chars #"));
+        assert!(!actual.contains("France"));
+    }
+
+    #[tokio::test]
+    async fn test_embedding_similarity_ranks_closer_answers_higher() {
+        let fixture = EmbeddingSimilarityScorer::new(LetterCountEmbedder);
+
+        let close = fixture.score("Paris", "paris, france").await.unwrap();
+        let far = fixture.score("Paris", "xyz").await.unwrap();
+
+        assert!(close > far, "close={close} far={far}");
+        assert!(fixture.score("Paris", "PARIS").await.unwrap() > 0.999);
+    }
+}
@@ -0,0 +1,211 @@
+//! Process and host resource sampling
+//!
+//! With the `system-metrics` feature enabled, a [`SystemSampler`] reads memory
+//! and CPU usage of local provider processes (e.g. the Ollama server) and of
+//! the host through `sysinfo`. Without the feature, or when a process cannot
+//! be found, sampling returns `None` and callers keep their defaults.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Identifies the process serving a local provider
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessMatcher {
+    /// First process whose name contains this string
+    Name(String),
+    /// Process with this pid
+    Pid(u32),
+}
+
+/// Resource usage of a provider process
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProcessUsage {
+    pub memory_usage_mb: u64,
+    /// CPU usage since the previous sample, where 100% is one full core
+    pub cpu_usage_percent: f64,
+}
+
+/// Resource usage of the host
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HostUsage {
+    pub memory_usage_percent: f64,
+    pub cpu_usage_percent: f64,
+    pub available_memory_mb: u64,
+}
+
+/// Samples provider process and host resource usage
+pub struct SystemSampler {
+    processes: HashMap<String, ProcessMatcher>,
+    #[cfg(feature = "system-metrics")]
+    system: std::sync::Mutex<sysinfo::System>,
+}
+
+impl Default for SystemSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SystemSampler {
+    pub fn new() -> Self {
+        Self {
+            processes: HashMap::new(),
+            #[cfg(feature = "system-metrics")]
+            system: std::sync::Mutex::new(sysinfo::System::new()),
+        }
+    }
+
+    /// Sample `provider_name` from the process matched by `matcher`
+    pub fn with_process(
+        mut self,
+        provider_name: impl Into<String>,
+        matcher: ProcessMatcher,
+    ) -> Self {
+        self.processes.insert(provider_name.into(), matcher);
+        self
+    }
+
+    /// Whether resource sampling is compiled in
+    pub fn is_enabled(&self) -> bool {
+        cfg!(feature = "system-metrics")
+    }
+
+    /// Current usage of the process serving `provider_name`, or `None` if
+    /// the provider has no process configured or the process is not running
+    pub fn sample_process(&self, provider_name: &str) -> Option<ProcessUsage> {
+        let matcher = self.processes.get(provider_name)?;
+        self.sample_matched(matcher)
+    }
+
+    #[cfg(feature = "system-metrics")]
+    fn sample_matched(&self, matcher: &ProcessMatcher) -> Option<ProcessUsage> {
+        use sysinfo::{Pid, ProcessesToUpdate};
+
+        let mut system = self.system.lock().unwrap();
+        let pid = match matcher {
+            ProcessMatcher::Pid(pid) => Pid::from_u32(*pid),
+            ProcessMatcher::Name(name) => {
+                let known = system
+                    .processes_by_name(name.as_ref())
+                    .next()
+                    .map(|process| process.pid());
+                match known {
+                    Some(pid) => pid,
+                    None => {
+                        // The process may have started since the last refresh
+                        system.refresh_processes(ProcessesToUpdate::All, true);
+                        system.processes_by_name(name.as_ref()).next()?.pid()
+                    }
+                }
+            }
+        };
+
+        system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+        let Some(process) = system.process(pid) else {
+            tracing::debug!(?matcher, "Provider process not found");
+            return None;
+        };
+        Some(ProcessUsage {
+            memory_usage_mb: process.memory() / (1024 * 1024),
+            cpu_usage_percent: process.cpu_usage() as f64,
+        })
+    }
+
+    #[cfg(not(feature = "system-metrics"))]
+    fn sample_matched(&self, _matcher: &ProcessMatcher) -> Option<ProcessUsage> {
+        None
+    }
+
+    /// Current host memory and CPU usage. CPU usage is measured since the
+    /// previous sample, so the first sample reports 0%.
+    #[cfg(feature = "system-metrics")]
+    pub fn sample_host(&self) -> Option<HostUsage> {
+        let mut system = self.system.lock().unwrap();
+        system.refresh_memory();
+        system.refresh_cpu_usage();
+
+        let total = system.total_memory();
+        if total == 0 {
+            return None;
+        }
+        Some(HostUsage {
+            memory_usage_percent: system.used_memory() as f64 / total as f64 * 100.0,
+            cpu_usage_percent: system.global_cpu_usage() as f64,
+            available_memory_mb: system.available_memory() / (1024 * 1024),
+        })
+    }
+
+    /// Current host memory and CPU usage; unavailable without the
+    /// `system-metrics` feature
+    #[cfg(not(feature = "system-metrics"))]
+    pub fn sample_host(&self) -> Option<HostUsage> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fixture() -> SystemSampler {
+        SystemSampler::new().with_process("ollama", ProcessMatcher::Pid(std::process::id()))
+    }
+
+    /// Keep a core busy for longer than sysinfo's minimum update interval
+    #[cfg(feature = "system-metrics")]
+    fn spin() {
+        let deadline = std::time::Instant::now()
+            + sysinfo::MINIMUM_CPU_UPDATE_INTERVAL
+            + std::time::Duration::from_millis(100);
+        let mut spins = 0u64;
+        while std::time::Instant::now() < deadline {
+            spins = std::hint::black_box(spins.wrapping_add(1));
+        }
+    }
+
+    #[cfg(feature = "system-metrics")]
+    #[test]
+    fn test_samples_running_process_and_host() {
+        let fixture = fixture();
+
+        // sysinfo reports 0% until a previous sample has seen some CPU time,
+        // so burn CPU before every sample and only trust the last one
+        let mut samples = Vec::new();
+        for _ in 0..3 {
+            spin();
+            samples.push(fixture.sample_process("ollama").unwrap());
+        }
+
+        let actual = samples.last().unwrap();
+        assert!(actual.memory_usage_mb > 0);
+        assert!(actual.cpu_usage_percent > 0.0, "{samples:?}");
+
+        let host = fixture.sample_host().unwrap();
+        assert!(host.memory_usage_percent > 0.0);
+        assert!(host.available_memory_mb > 0);
+    }
+
+    #[cfg(not(feature = "system-metrics"))]
+    #[test]
+    fn test_sampling_disabled_without_feature() {
+        let fixture = fixture();
+
+        let actual = (fixture.sample_process("ollama"), fixture.sample_host());
+
+        assert_eq!(actual, (None, None));
+        assert_eq!(fixture.is_enabled(), false);
+    }
+
+    #[test]
+    fn test_unconfigured_provider_is_not_sampled() {
+        let fixture = fixture();
+
+        let actual = fixture.sample_process("llama-cpp");
+
+        assert_eq!(actual, None);
+    }
+}
@@ -0,0 +1,122 @@
+//! Sliding-window request and byte throughput
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Timestamps and response sizes of a provider's recent requests, used to
+/// compute requests and bytes per second over a trailing window
+#[derive(Debug, Clone)]
+pub struct ThroughputWindow {
+    window: Duration,
+    requests: VecDeque<(Instant, u64)>,
+    first_request: Option<Instant>,
+}
+
+impl ThroughputWindow {
+    pub fn new(window: Duration) -> Self {
+        Self { window, requests: VecDeque::new(), first_request: None }
+    }
+
+    /// Record a request completed at `now`
+    pub fn record_at(&mut self, now: Instant) {
+        self.record_bytes_at(now, 0);
+    }
+
+    /// Record a request completed at `now` whose response was `bytes` long
+    pub fn record_bytes_at(&mut self, now: Instant, bytes: u64) {
+        self.first_request.get_or_insert(now);
+        self.requests.push_back((now, bytes));
+        self.evict(now);
+    }
+
+    /// Requests per second over the window ending at `now`. Until the
+    /// provider has been seen for a full window, the rate is taken over the
+    /// time since its first request.
+    pub fn rate_at(&mut self, now: Instant) -> f64 {
+        self.evict(now);
+        self.span_at(now)
+            .map_or(0.0, |span| self.requests.len() as f64 / span.as_secs_f64())
+    }
+
+    /// Response bytes per second over the window ending at `now`, measured
+    /// over the same span as [`ThroughputWindow::rate_at`]
+    pub fn bytes_rate_at(&mut self, now: Instant) -> f64 {
+        self.evict(now);
+        let bytes: u64 = self.requests.iter().map(|(_, bytes)| bytes).sum();
+        self.span_at(now)
+            .map_or(0.0, |span| bytes as f64 / span.as_secs_f64())
+    }
+
+    /// Requests currently inside the window
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    fn span_at(&self, now: Instant) -> Option<Duration> {
+        let first_request = self.first_request?;
+        Some(
+            self.window
+                .min(now.saturating_duration_since(first_request))
+                .max(Duration::from_secs(1)),
+        )
+    }
+
+    fn evict(&mut self, now: Instant) {
+        while self
+            .requests
+            .front()
+            .is_some_and(|(at, _)| now.saturating_duration_since(*at) > self.window)
+        {
+            self.requests.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_rate_rises_and_decays_over_simulated_time() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut fixture = ThroughputWindow::new(Duration::from_secs(60));
+
+        // 2 requests per second for the first 30 seconds
+        for tick in 0..60 {
+            fixture.record_at(start + Duration::from_millis(tick * 500));
+        }
+        let warming = fixture.rate_at(at(30));
+
+        // 5 requests per second from 60s to 120s
+        for tick in 0..300 {
+            fixture.record_at(at(60) + Duration::from_millis(tick * 200));
+        }
+        let busy = fixture.rate_at(at(120));
+
+        let idle = fixture.rate_at(at(200));
+
+        let actual = (warming, busy, idle, fixture.len());
+        let expected = (2.0, 5.0, 0.0, 0);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_old_requests_are_evicted_on_record() {
+        let start = Instant::now();
+        let mut fixture = ThroughputWindow::new(Duration::from_secs(10));
+
+        for secs in 0..100 {
+            fixture.record_at(start + Duration::from_secs(secs));
+        }
+
+        assert_eq!(fixture.len(), 11);
+        assert_eq!(fixture.rate_at(start + Duration::from_secs(99)), 1.1);
+    }
+}
@@ -0,0 +1,212 @@
+//! Warm-standby keepalive for cloud provider connections
+//!
+//! Fallback to a cloud provider normally pays for a fresh TCP and TLS
+//! handshake because the pooled connection has long since gone idle. A warm
+//! standby sends a small periodic request through the same HTTP client so the
+//! pooled connection stays open and a fallback can reuse it immediately.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use derive_setters::Setters;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Configuration for keeping cloud connections warm
+#[derive(Debug, Clone, Serialize, Deserialize, Setters)]
+#[setters(strip_option, into)]
+pub struct WarmStandbyConfig {
+    /// Enable periodic keepalive requests
+    pub enabled: bool,
+    /// Time between keepalives; keep this below the pool idle timeout
+    pub keepalive_interval: Duration,
+    /// Upper bound on keepalive requests per target per hour
+    pub max_keepalives_per_hour: u32,
+}
+
+impl Default for WarmStandbyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            keepalive_interval: Duration::from_secs(30),
+            max_keepalives_per_hour: 120,
+        }
+    }
+}
+
+/// Keepalive requests sent in the current hourly window
+#[derive(Debug)]
+struct KeepaliveBudget {
+    window_start: Instant,
+    sent: u32,
+}
+
+/// Keeps pooled connections to cloud providers alive with cheap periodic
+/// requests, bounded by an hourly budget
+#[derive(Debug, Clone)]
+pub struct WarmStandby {
+    client: reqwest::Client,
+    targets: Vec<Url>,
+    config: WarmStandbyConfig,
+    budget: Arc<RwLock<KeepaliveBudget>>,
+}
+
+impl WarmStandby {
+    /// Create a warm standby that sends keepalives through `client`. The
+    /// client must be the one used for real requests so they share its pool.
+    pub fn new(client: reqwest::Client, config: WarmStandbyConfig) -> Self {
+        Self {
+            client,
+            targets: Vec::new(),
+            config,
+            budget: Arc::new(RwLock::new(KeepaliveBudget {
+                window_start: Instant::now(),
+                sent: 0,
+            })),
+        }
+    }
+
+    /// Add an endpoint to keep warm
+    pub fn with_target(mut self, url: Url) -> Self {
+        self.targets.push(url);
+        self
+    }
+
+    /// Keepalive requests sent in the current hourly window
+    pub async fn keepalives_sent(&self) -> u32 {
+        self.budget.read().await.sent
+    }
+
+    /// Send one keepalive to every target that still has budget left in the
+    /// current window. Returns the number of keepalives that succeeded.
+    pub async fn ping_once(&self) -> usize {
+        if !self.config.enabled {
+            return 0;
+        }
+
+        let mut succeeded = 0;
+        for target in &self.targets {
+            if !self.try_reserve().await {
+                debug!(target = %target, "Keepalive budget exhausted for this hour");
+                break;
+            }
+
+            // Drain the body so the connection is returned to the pool
+            let result = match self.client.get(target.clone()).send().await {
+                Ok(response) => response.bytes().await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(()) => {
+                    debug!(target = %target, "Keepalive sent");
+                    succeeded += 1;
+                }
+                Err(e) => warn!(target = %target, error = %e, "Keepalive failed"),
+            }
+        }
+        succeeded
+    }
+
+    /// Spawn a background task that sends keepalives on the configured
+    /// interval until the returned handle is aborted
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.keepalive_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                self.ping_once().await;
+            }
+        })
+    }
+
+    /// Reserve one keepalive from the hourly budget, resetting the window
+    /// once an hour has passed
+    async fn try_reserve(&self) -> bool {
+        let mut budget = self.budget.write().await;
+        if budget.window_start.elapsed() >= Duration::from_secs(3600) {
+            budget.window_start = Instant::now();
+            budget.sent = 0;
+        }
+
+        let limit = self.config.max_keepalives_per_hour as usize * self.targets.len();
+        if budget.sent as usize >= limit {
+            return false;
+        }
+        budget.sent += 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::mock_server::spawn_connection_counting_server;
+
+    #[tokio::test]
+    async fn test_keepalive_reuses_connection_for_real_request() {
+        let (url, connections) = spawn_connection_counting_server().await;
+        let client = reqwest::Client::new();
+        let fixture = WarmStandby::new(client.clone(), WarmStandbyConfig::default())
+            .with_target(Url::parse(&url).unwrap());
+
+        for _ in 0..3 {
+            assert_eq!(fixture.ping_once().await, 1);
+        }
+        client
+            .get(format!("{url}/chat/completions"))
+            .send()
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap();
+
+        let actual = connections.load(Ordering::SeqCst);
+        let expected = 1;
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_respects_hourly_budget() {
+        let (url, _connections) = spawn_connection_counting_server().await;
+        let fixture = WarmStandby::new(
+            reqwest::Client::new(),
+            WarmStandbyConfig::default().max_keepalives_per_hour(2u32),
+        )
+        .with_target(Url::parse(&url).unwrap());
+
+        let actual: Vec<usize> = vec![
+            fixture.ping_once().await,
+            fixture.ping_once().await,
+            fixture.ping_once().await,
+        ];
+
+        let expected = vec![1, 1, 0];
+        assert_eq!(actual, expected);
+        assert_eq!(fixture.keepalives_sent().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_standby_sends_nothing() {
+        let (url, connections) = spawn_connection_counting_server().await;
+        let fixture = WarmStandby::new(
+            reqwest::Client::new(),
+            WarmStandbyConfig::default().enabled(false),
+        )
+        .with_target(Url::parse(&url).unwrap());
+
+        let actual = fixture.ping_once().await;
+
+        assert_eq!(actual, 0);
+        assert_eq!(connections.load(Ordering::SeqCst), 0);
+    }
+}
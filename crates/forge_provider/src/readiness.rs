@@ -0,0 +1,162 @@
+//! Readiness gate for provider startup
+//!
+//! Requests sent before initial health checks and model discovery finish see
+//! an empty provider state and fail spuriously. A [`ReadinessGate`] is marked
+//! ready once startup completes, or failed if it does not, and callers await
+//! it before their first request.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tracing::{debug, warn};
+
+/// Outcome of provider startup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Readiness {
+    /// Initial checks are still running
+    Pending,
+    /// Initial checks completed
+    Ready,
+    /// Startup ended without completing the initial checks
+    Failed,
+}
+
+/// Signals when initial provider checks have completed
+#[derive(Debug, Clone)]
+pub struct ReadinessGate {
+    state: Arc<watch::Sender<Readiness>>,
+}
+
+impl Default for ReadinessGate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReadinessGate {
+    pub fn new() -> Self {
+        let (state, _) = watch::channel(Readiness::Pending);
+        Self { state: Arc::new(state) }
+    }
+
+    /// Open the gate, releasing all current and future waiters
+    pub fn mark_ready(&self) {
+        self.state.send_replace(Readiness::Ready);
+        debug!("Provider readiness gate opened");
+    }
+
+    /// Release all current and future waiters without the initial checks
+    /// having completed
+    pub fn mark_failed(&self, reason: &str) {
+        self.state.send_replace(Readiness::Failed);
+        warn!(reason = %reason, "Provider startup failed, releasing readiness waiters");
+    }
+
+    /// Guard that marks the gate failed when dropped while still pending, so
+    /// that startup returning an error, panicking or being cancelled never
+    /// leaves waiters until the timeout
+    pub fn guard(&self) -> ReadinessGuard {
+        ReadinessGuard { gate: self.clone() }
+    }
+
+    /// Current state of the gate
+    pub fn state(&self) -> Readiness {
+        *self.state.borrow()
+    }
+
+    /// Whether the gate has been opened
+    pub fn is_ready(&self) -> bool {
+        self.state() == Readiness::Ready
+    }
+
+    /// Future that resolves once the gate is opened or failed, or after
+    /// `timeout` so that startup never blocks indefinitely
+    pub fn wait(&self, timeout: Duration) -> impl Future<Output = ()> + Send + 'static {
+        let mut receiver = self.state.subscribe();
+        async move {
+            let settled = tokio::time::timeout(
+                timeout,
+                receiver.wait_for(|state| *state != Readiness::Pending),
+            )
+            .await;
+            if settled.is_err() {
+                warn!(
+                    timeout_ms = timeout.as_millis() as u64,
+                    "Timed out waiting for providers to become ready"
+                );
+            }
+        }
+    }
+}
+
+/// Marks its [`ReadinessGate`] failed if dropped before startup settled it
+#[derive(Debug)]
+pub struct ReadinessGuard {
+    gate: ReadinessGate,
+}
+
+impl Drop for ReadinessGuard {
+    fn drop(&mut self) {
+        if self.gate.state() == Readiness::Pending {
+            self.gate
+                .mark_failed("startup ended before initial checks completed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_resolves_when_marked_ready() {
+        let fixture = ReadinessGate::new();
+        let waiter = tokio::spawn(fixture.wait(Duration::from_secs(5)));
+
+        fixture.mark_ready();
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(fixture.is_ready(), true);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_guard_fails_pending_gate() {
+        let fixture = ReadinessGate::new();
+        let waiter = tokio::spawn(fixture.wait(Duration::from_secs(5)));
+
+        drop(fixture.guard());
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(fixture.state(), Readiness::Failed);
+    }
+
+    #[test]
+    fn test_dropped_guard_keeps_ready_gate() {
+        let fixture = ReadinessGate::new();
+        let guard = fixture.guard();
+
+        fixture.mark_ready();
+        drop(guard);
+
+        assert_eq!(fixture.state(), Readiness::Ready);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wait_resolves_after_timeout() {
+        let fixture = ReadinessGate::new();
+
+        fixture.wait(Duration::from_secs(30)).await;
+
+        assert_eq!(fixture.is_ready(), false);
+    }
+}
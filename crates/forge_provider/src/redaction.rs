@@ -0,0 +1,149 @@
+//! Redaction of prompt and response content before it is logged or recorded
+//!
+//! Anything that may contain user content (measurement metadata, error
+//! messages, audit records) should pass through a [`Redactor`] before it is
+//! written anywhere.
+
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use regex::Regex;
+
+/// Transforms text so it can be logged without exposing raw content
+pub trait Redactor: fmt::Debug + Send + Sync {
+    /// Return a redacted form of `text`
+    fn redact(&self, text: &str) -> String;
+}
+
+/// Replaces content with its length and a stable hash, optionally keeping a
+/// short prefix so records remain recognisable
+#[derive(Debug, Clone, Default)]
+pub struct HashRedactor {
+    preview_chars: usize,
+}
+
+impl HashRedactor {
+    /// Keep the first `chars` characters of the content in clear text
+    pub fn with_preview(mut self, chars: usize) -> Self {
+        self.preview_chars = chars;
+        self
+    }
+}
+
+impl Redactor for HashRedactor {
+    fn redact(&self, text: &str) -> String {
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        let hash = hasher.finish();
+        let length = text.chars().count();
+        let preview: String = text.chars().take(self.preview_chars).collect();
+
+        if preview.is_empty() {
+            format!("[redacted {length} chars #{hash:016x}]")
+        } else {
+            format!("{preview}… [redacted {length} chars #{hash:016x}]")
+        }
+    }
+}
+
+/// Replaces substrings matching configured patterns, leaving the rest of the
+/// text readable
+#[derive(Debug, Clone, Default)]
+pub struct PatternRedactor {
+    rules: Vec<(Regex, String)>,
+}
+
+impl PatternRedactor {
+    /// Redactor for common PII: email addresses, API keys, card numbers,
+    /// US social security numbers and phone numbers
+    pub fn with_default_pii() -> Self {
+        let rules = [
+            (r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}", "[EMAIL]"),
+            (r"\b(?:sk|pk|api|key)-[A-Za-z0-9_-]{16,}\b", "[API_KEY]"),
+            (r"\b(?:\d[ -]?){13,16}\b", "[CARD]"),
+            (r"\b\d{3}-\d{2}-\d{4}\b", "[SSN]"),
+            (
+                r"\+?\b\d{1,3}[ .-]?\(?\d{3}\)?[ .-]?\d{3}[ .-]?\d{4}\b",
+                "[PHONE]",
+            ),
+        ];
+
+        Self {
+            rules: rules
+                .into_iter()
+                .map(|(pattern, replacement)| {
+                    (Regex::new(pattern).unwrap(), replacement.to_string())
+                })
+                .collect(),
+        }
+    }
+
+    /// Add a pattern whose matches are replaced with `replacement`
+    pub fn with_pattern(mut self, pattern: &str, replacement: &str) -> anyhow::Result<Self> {
+        let regex = Regex::new(pattern)
+            .map_err(|e| anyhow::anyhow!("Invalid redaction pattern '{pattern}': {e}"))?;
+        self.rules.push((regex, replacement.to_string()));
+        Ok(self)
+    }
+}
+
+impl Redactor for PatternRedactor {
+    fn redact(&self, text: &str) -> String {
+        self.rules
+            .iter()
+            .fold(text.to_string(), |text, (regex, replacement)| {
+                regex.replace_all(&text, replacement.as_str()).into_owned()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::{assert_eq, assert_ne};
+
+    use super::*;
+
+    #[test]
+    fn test_hash_redactor_hides_content() {
+        let fixture = HashRedactor::default();
+
+        let actual = fixture.redact("summarise my medical history");
+
+        assert!(actual.starts_with("[redacted 28 chars #"));
+        assert!(!actual.contains("medical"));
+        assert_eq!(actual, fixture.redact("summarise my medical history"));
+        assert_ne!(actual, fixture.redact("summarise my tax history"));
+    }
+
+    #[test]
+    fn test_hash_redactor_preview() {
+        let fixture = HashRedactor::default().with_preview(9);
+
+        let actual = fixture.redact("summarise my medical history");
+
+        assert!(actual.starts_with("summarise… [redacted 28 chars #"));
+    }
+
+    #[test]
+    fn test_pattern_redactor_default_pii() {
+        let fixture = PatternRedactor::with_default_pii();
+
+        let actual = fixture
+            .redact("mail jane.doe@example.com, ssn 123-45-6789, key sk-abcdefghijklmnopqrstuv");
+
+        let expected = "mail [EMAIL], ssn [SSN], key [API_KEY]";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_pattern_redactor_custom_pattern() {
+        let fixture = PatternRedactor::default()
+            .with_pattern(r"PRJ-\d+", "[PROJECT]")
+            .unwrap();
+
+        let actual = fixture.redact("status of PRJ-4521?");
+
+        assert_eq!(actual, "status of [PROJECT]?");
+        assert!(PatternRedactor::default().with_pattern("(", "x").is_err());
+    }
+}
@@ -1,9 +1,246 @@
+use std::future::Future;
+use std::time::Duration;
+
+use derive_setters::Setters;
 use forge_app::domain::{Error as DomainError, RetryConfig};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
 
 use crate::error::{Error, ErrorResponse};
+use crate::ollama::OllamaError;
 
 const TRANSPORT_ERROR_CODES: [&str; 3] = ["ERR_STREAM_PREMATURE_CLOSE", "ECONNRESET", "ETIMEDOUT"];
 
+/// Why a provider request failed, deciding whether retrying can help
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    /// Temporary: the server is down, overloaded or the request timed out
+    Transient,
+    /// The provider asked us to slow down
+    RateLimited,
+    /// Credentials are missing or rejected
+    AuthFailure,
+    /// The provider does not serve the requested model
+    ModelNotFound,
+    /// The request itself is wrong and will fail again unchanged
+    Fatal,
+    /// The caller cancelled the request, which says nothing about the
+    /// provider
+    Cancelled,
+}
+
+impl FailureKind {
+    /// Whether retrying the same request against the same provider can
+    /// succeed
+    pub fn is_retryable(self) -> bool {
+        matches!(self, FailureKind::Transient | FailureKind::RateLimited)
+    }
+
+    /// Whether the failure counts against the provider's health. Cancelled
+    /// requests do not.
+    pub fn is_provider_failure(self) -> bool {
+        self != FailureKind::Cancelled
+    }
+
+    /// Classify an HTTP error response from its status and body
+    pub fn from_status(status: u16, body: &str) -> Self {
+        let body = body.to_lowercase();
+        if body.contains("rate limit") || body.contains("rate_limit") {
+            return FailureKind::RateLimited;
+        }
+        if body.contains("model_not_found")
+            || (body.contains("model") && body.contains("not found"))
+        {
+            return FailureKind::ModelNotFound;
+        }
+        match status {
+            401 | 403 => FailureKind::AuthFailure,
+            404 => FailureKind::ModelNotFound,
+            429 => FailureKind::RateLimited,
+            408 | 409 | 425 | 500 | 502..=504 | 529 => FailureKind::Transient,
+            _ => FailureKind::Fatal,
+        }
+    }
+
+    /// Classify a failed provider call. Errors that carry no status or
+    /// recognizable type are treated as transient, so callers keep retrying
+    /// what they cannot explain.
+    pub fn classify(error: &anyhow::Error) -> Self {
+        if let Some(DomainError::Retryable(inner)) = error.downcast_ref::<DomainError>() {
+            return Self::classify(inner);
+        }
+
+        for cause in error.chain() {
+            if let Some(error) = cause.downcast_ref::<OllamaError>() {
+                return error.failure_kind();
+            }
+            if let Some(error) = cause.downcast_ref::<Error>() {
+                return match error {
+                    Error::Response(response) => {
+                        let body = serde_json::to_string(response).unwrap_or_default();
+                        match response
+                            .get_code_deep()
+                            .as_ref()
+                            .and_then(|code| code.as_number())
+                        {
+                            Some(status) => Self::from_status(status, &body),
+                            None if has_transport_error_code(response) => FailureKind::Transient,
+                            None => Self::from_status(0, &body),
+                        }
+                    }
+                    Error::Anthropic(response) => Self::from_status(0, &response.to_string()),
+                    Error::InvalidStatusCode(status) => Self::from_status(*status, ""),
+                    Error::ToolCallMissingName
+                    | Error::ToolCallMissingId
+                    | Error::UnsupportedRole(_)
+                    | Error::ProtocolMismatch { .. } => FailureKind::Fatal,
+                    Error::Cancelled => FailureKind::Cancelled,
+                };
+            }
+            if let Some(error) = cause.downcast_ref::<reqwest::Error>() {
+                if error.is_timeout() {
+                    return FailureKind::Transient;
+                }
+                if let Some(status) = error.status() {
+                    return Self::from_status(status.as_u16(), "");
+                }
+            }
+            if let Some(reqwest_eventsource::Error::InvalidStatusCode(status, _)) =
+                cause.downcast_ref::<reqwest_eventsource::Error>()
+            {
+                return Self::from_status(status.as_u16(), "");
+            }
+        }
+
+        FailureKind::Transient
+    }
+}
+
+/// Capped, jittered exponential backoff for retrying a single operation
+#[derive(Debug, Clone, PartialEq, Setters)]
+#[setters(into)]
+pub struct RetryPolicy {
+    /// Attempts including the first, so 1 never retries
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub base_delay: Duration,
+    /// Factor the delay grows by with each retry
+    pub multiplier: f64,
+    /// Upper bound on any single delay
+    pub max_delay: Duration,
+    /// Fraction of each delay (0.0 to 1.0) that is randomized, so callers
+    /// retrying together spread out
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            multiplier: 2.0,
+            max_delay: Duration::from_secs(10),
+            jitter: 0.5,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `retry` (starting at 0) before jitter
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.min(i32::MAX as u32) as i32;
+        let delay = self.base_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        Duration::try_from_secs_f64(delay).map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+
+    /// Randomize the last `jitter` fraction of `delay` using `random`
+    fn jittered(&self, delay: Duration, random: u64) -> Duration {
+        let spread = delay.mul_f64(self.jitter.clamp(0.0, 1.0));
+        // The top 53 bits give a uniform fraction in [0, 1)
+        let fraction = (random >> 11) as f64 / (1u64 << 53) as f64;
+        delay - spread + spread.mul_f64(fraction)
+    }
+}
+
+/// Run `operation` until it succeeds, fails with an error `is_retryable`
+/// rejects, or `policy.max_attempts` attempts are used up, sleeping with
+/// jittered exponential backoff between attempts. Returns the last error on
+/// failure.
+pub async fn retry_with<T, E, F, Fut>(
+    policy: &RetryPolicy,
+    is_retryable: impl Fn(&E) -> bool,
+    operation: F,
+) -> Result<T, E>
+where
+    E: std::fmt::Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry_scaled(policy, |error| is_retryable(error).then_some(1), operation).await
+}
+
+/// Like [`retry_with`], but `delay_factor` decides per error: `None` stops
+/// retrying, `Some(n)` waits `n` times the regular backoff before the next
+/// attempt
+pub async fn retry_scaled<T, E, F, Fut>(
+    policy: &RetryPolicy,
+    delay_factor: impl Fn(&E) -> Option<u32>,
+    mut operation: F,
+) -> Result<T, E>
+where
+    E: std::fmt::Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut random = random_seed();
+    let mut attempt = 1;
+    loop {
+        let error = match operation().await {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+        let factor = match delay_factor(&error) {
+            Some(factor) if attempt < policy.max_attempts => factor,
+            _ => return Err(error),
+        };
+
+        random = splitmix64(random);
+        let backoff = policy.backoff(attempt - 1).saturating_mul(factor);
+        let delay = policy.jittered(backoff, random);
+        warn!(
+            attempt,
+            delay_ms = delay.as_millis() as u64,
+            error = %error,
+            "Request failed, retrying"
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Whether `error` was classified as retryable by [`into_retry`]
+pub fn is_retryable(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<DomainError>()
+        .is_some_and(|error| matches!(error, DomainError::Retryable(_)))
+}
+
+/// Seed for jitter sequences, taken from the clock
+pub(crate) fn random_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
+
+/// Next value of a splitmix64 sequence
+pub(crate) fn splitmix64(state: u64) -> u64 {
+    let mut z = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
 pub fn into_retry(error: anyhow::Error, retry_config: &RetryConfig) -> anyhow::Error {
     if let Some(code) = get_req_status_code(&error)
         .or(get_event_req_status_code(&error))
@@ -109,7 +346,10 @@ fn is_event_transport_error(error: &anyhow::Error) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
     use anyhow::anyhow;
+    use pretty_assertions::assert_eq;
 
     use super::*;
     use crate::error::{Error, ErrorCode, ErrorResponse};
@@ -123,6 +363,159 @@ mod tests {
         }
     }
 
+    fn policy() -> RetryPolicy {
+        RetryPolicy::default()
+            .max_attempts(4u32)
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(250))
+            .jitter(0.0)
+    }
+
+    /// Run `retry_with` over scripted results, returning the outcome, the
+    /// number of attempts and the time spent
+    async fn run(
+        policy: &RetryPolicy,
+        results: Vec<Result<&'static str, &'static str>>,
+    ) -> (Result<&'static str, &'static str>, u32, Duration) {
+        let attempts = AtomicU32::new(0);
+        let start = tokio::time::Instant::now();
+        let actual = retry_with(
+            policy,
+            |error: &&str| *error != "fatal",
+            || {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst) as usize;
+                let result = results[attempt.min(results.len() - 1)];
+                async move { result }
+            },
+        )
+        .await;
+        (actual, attempts.load(Ordering::SeqCst), start.elapsed())
+    }
+
+    #[test]
+    fn test_failure_kind_from_status() {
+        let fixture = [
+            (503, "Service Unavailable"),
+            (502, ""),
+            (500, r#"{"error":"internal error"}"#),
+            (429, "Too Many Requests"),
+            (400, r#"{"error":{"type":"rate_limit_error"}}"#),
+            (401, r#"{"error":{"code":"invalid_api_key"}}"#),
+            (403, "forbidden"),
+            (
+                404,
+                r#"{"error":"model 'llama9' not found, try pulling it first"}"#,
+            ),
+            (400, r#"{"error":{"code":"model_not_found"}}"#),
+            (404, "Not Found"),
+            (400, r#"{"error":"invalid request"}"#),
+            (422, ""),
+        ];
+
+        let actual: Vec<_> = fixture
+            .iter()
+            .map(|(status, body)| FailureKind::from_status(*status, body))
+            .collect();
+
+        let expected = vec![
+            FailureKind::Transient,
+            FailureKind::Transient,
+            FailureKind::Transient,
+            FailureKind::RateLimited,
+            FailureKind::RateLimited,
+            FailureKind::AuthFailure,
+            FailureKind::AuthFailure,
+            FailureKind::ModelNotFound,
+            FailureKind::ModelNotFound,
+            FailureKind::ModelNotFound,
+            FailureKind::Fatal,
+            FailureKind::Fatal,
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_failure_kind_classifies_provider_errors() {
+        let fixture: Vec<anyhow::Error> = vec![
+            OllamaError::service_unavailable("http://localhost:11434".to_string()).into(),
+            anyhow::Error::from(OllamaError::http_error(401, "unauthorized".to_string()))
+                .context("Failed to fetch the models"),
+            OllamaError::model_not_found("llama9".to_string()).into(),
+            OllamaError::protocol_mismatch("ollama", "missing field `models`").into(),
+            Error::Response(ErrorResponse::default().code(ErrorCode::Number(429))).into(),
+            Error::InvalidStatusCode(403).into(),
+            into_retry(
+                Error::Response(ErrorResponse::default().code(ErrorCode::Number(503))).into(),
+                &RetryConfig::default(),
+            ),
+            Error::ToolCallMissingName.into(),
+            Error::ProtocolMismatch {
+                provider: "https://api.openai.com/v1/".to_string(),
+                detail: "invalid type: map, expected a sequence".to_string(),
+            }
+            .into(),
+            anyhow!("connection reset"),
+        ];
+
+        let actual: Vec<_> = fixture.iter().map(FailureKind::classify).collect();
+
+        let expected = vec![
+            FailureKind::Transient,
+            FailureKind::AuthFailure,
+            FailureKind::ModelNotFound,
+            FailureKind::Fatal,
+            FailureKind::RateLimited,
+            FailureKind::AuthFailure,
+            FailureKind::Transient,
+            FailureKind::Fatal,
+            FailureKind::Fatal,
+            FailureKind::Transient,
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_with_immediate_success() {
+        let actual = run(&policy(), vec![Ok("done")]).await;
+
+        assert_eq!(actual, (Ok("done"), 1, Duration::ZERO));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_with_succeeds_after_transient_failures() {
+        let actual = run(&policy(), vec![Err("busy"), Err("busy"), Ok("done")]).await;
+
+        // 100ms then 200ms of backoff
+        assert_eq!(actual, (Ok("done"), 3, Duration::from_millis(300)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_with_fatal_error_short_circuits() {
+        let actual = run(&policy(), vec![Err("busy"), Err("fatal"), Ok("done")]).await;
+
+        assert_eq!(actual, (Err("fatal"), 2, Duration::from_millis(100)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_with_returns_last_error_when_exhausted() {
+        let actual = run(&policy(), vec![Err("busy"), Err("still busy")]).await;
+
+        // Backoff is capped at 250ms: 100 + 200 + 250
+        assert_eq!(actual, (Err("still busy"), 4, Duration::from_millis(550)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_with_jitter_stays_within_delay() {
+        let fixture = policy().max_attempts(2u32).jitter(0.5);
+
+        let (_, _, actual) = run(&fixture, vec![Err("busy")]).await;
+
+        assert!(
+            (Duration::from_millis(50)..=Duration::from_millis(100)).contains(&actual),
+            "{actual:?}"
+        );
+    }
+
     #[test]
     fn test_into_retry_with_matching_api_status_code() {
         // Setup
@@ -0,0 +1,169 @@
+//! Load balancing across equally healthy local providers
+//!
+//! The fallback engine takes the first suitable provider in health order, so
+//! without balancing a second local instance never sees any load. Within each
+//! health tier, providers are ordered by requests in flight and then by
+//! average response time; providers that tie on both take turns.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tracing::debug;
+
+use super::warm::health_rank;
+use super::ProviderSelector;
+use crate::config::local_ai::ProviderHealthStatus;
+
+/// In-flight request counts and the round-robin cursor used to break ties
+#[derive(Debug, Clone, Default)]
+pub struct LoadBalancer {
+    in_flight: HashMap<String, u32>,
+    cursor: usize,
+}
+
+impl LoadBalancer {
+    /// Requests currently in flight on `provider_name`
+    pub fn in_flight(&self, provider_name: &str) -> u32 {
+        self.in_flight.get(provider_name).copied().unwrap_or(0)
+    }
+}
+
+impl ProviderSelector {
+    /// Mark a request to `provider_name` as started
+    pub fn begin_request(&mut self, provider_name: &str) {
+        *self
+            .load_balancer
+            .in_flight
+            .entry(provider_name.to_string())
+            .or_insert(0) += 1;
+    }
+
+    /// Mark a request to `provider_name` as finished
+    pub fn end_request(&mut self, provider_name: &str) {
+        if let Some(count) = self.load_balancer.in_flight.get_mut(provider_name) {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    /// In-flight request counts used for load balancing
+    pub fn load_balancer(&self) -> &LoadBalancer {
+        &self.load_balancer
+    }
+
+    /// Reorder `local_health` so that, within each health tier, the least
+    /// loaded and fastest providers come first, rotating providers that tie
+    pub(super) fn balance_local_providers(
+        &self,
+        local_health: &mut [(String, ProviderHealthStatus)],
+    ) {
+        let key = |name: &str, status: &ProviderHealthStatus| {
+            let avg_response_time = self
+                .provider_metrics
+                .get(name)
+                .map_or(Duration::ZERO, |metrics| metrics.avg_response_time);
+            (
+                health_rank(status),
+                self.load_balancer.in_flight(name),
+                avg_response_time,
+            )
+        };
+        local_health.sort_by(|(a, a_status), (b, b_status)| {
+            key(a, a_status)
+                .cmp(&key(b, b_status))
+                .then_with(|| a.cmp(b))
+        });
+
+        // Rotate each run of tied providers by the round-robin cursor
+        let cursor = self.load_balancer.cursor;
+        let mut start = 0;
+        while start < local_health.len() {
+            let tied = key(&local_health[start].0, &local_health[start].1);
+            let len = local_health[start..]
+                .iter()
+                .take_while(|(name, status)| key(name, status) == tied)
+                .count();
+            local_health[start..start + len].rotate_left(cursor % len);
+            start += len;
+        }
+
+        debug!(
+            order = ?local_health.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            "Balanced local providers"
+        );
+    }
+
+    /// Move the round-robin cursor on, so the next selection rotates tied
+    /// providers
+    pub(super) fn advance_balance_cursor(&mut self) {
+        self.load_balancer.cursor = self.load_balancer.cursor.wrapping_add(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::selection::{ProviderMetrics, ProviderType, SelectionContext};
+    use crate::test_utils::{create_healthy_status, selector_with_health};
+
+    async fn fixture(avg_response_ms: [u64; 2]) -> ProviderSelector {
+        let mut selector = selector_with_health(&[
+            ("gpu-a", create_healthy_status()),
+            ("gpu-b", create_healthy_status()),
+        ])
+        .await;
+        for (name, millis) in ["gpu-a", "gpu-b"].into_iter().zip(avg_response_ms) {
+            let mut metrics = ProviderMetrics::new(ProviderType::Local);
+            metrics.avg_response_time = Duration::from_millis(millis);
+            selector.provider_metrics.insert(name.to_string(), metrics);
+        }
+        selector
+    }
+
+    async fn select(selector: &mut ProviderSelector) -> String {
+        selector
+            .select_provider(SelectionContext::new("llama3.2:latest".to_string()))
+            .await
+            .unwrap()
+            .provider_name
+    }
+
+    #[tokio::test]
+    async fn test_faster_provider_is_chosen() {
+        for (avg_response_ms, expected) in [([500, 100], "gpu-b"), ([100, 500], "gpu-a")] {
+            let mut fixture = fixture(avg_response_ms).await;
+
+            let actual = vec![select(&mut fixture).await, select(&mut fixture).await];
+
+            assert_eq!(actual, vec![expected, expected]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tied_providers_are_round_robined() {
+        let mut fixture = fixture([200, 200]).await;
+
+        let mut actual = Vec::new();
+        for _ in 0..4 {
+            actual.push(select(&mut fixture).await);
+        }
+
+        let expected = vec!["gpu-a", "gpu-b", "gpu-a", "gpu-b"];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_least_loaded_provider_is_chosen() {
+        let mut fixture = fixture([100, 500]).await;
+        fixture.begin_request("gpu-a");
+
+        let loaded = select(&mut fixture).await;
+        fixture.end_request("gpu-a");
+        let idle = select(&mut fixture).await;
+
+        let actual = (loaded, idle, fixture.load_balancer().in_flight("gpu-a"));
+        let expected = ("gpu-b".to_string(), "gpu-a".to_string(), 0);
+        assert_eq!(actual, expected);
+    }
+}
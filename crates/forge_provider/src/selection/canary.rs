@@ -0,0 +1,341 @@
+//! Canary deployments that ramp traffic to a new local model
+
+use std::time::{Duration, Instant};
+
+use derive_setters::Setters;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::config::aliases::ModelAliasResolver;
+
+/// Service levels a canary must hold to be promoted
+#[derive(Debug, Clone, Serialize, Deserialize, Setters)]
+#[setters(strip_option, into)]
+pub struct CanarySla {
+    /// Maximum acceptable failure rate (0.0-1.0)
+    pub max_error_rate: f64,
+    /// Maximum acceptable average response time
+    pub max_avg_response_time: Duration,
+    /// Requests required before the SLA is evaluated
+    pub min_requests: u64,
+}
+
+impl Default for CanarySla {
+    fn default() -> Self {
+        Self {
+            max_error_rate: 0.05,
+            max_avg_response_time: Duration::from_secs(10),
+            min_requests: 20,
+        }
+    }
+}
+
+/// Configuration for ramping traffic to a canary model
+#[derive(Debug, Clone, Serialize, Deserialize, Setters)]
+#[setters(strip_option, into)]
+pub struct CanaryConfig {
+    /// Model requests must ask for to be eligible for the canary
+    pub model: String,
+    /// Provider serving the canary model
+    pub provider: String,
+    /// Initial share of matching traffic, in percent
+    pub start_percent: f64,
+    /// Percentage points added after every interval
+    pub step: f64,
+    /// Time between ramp steps
+    pub interval: Duration,
+    /// SLA the canary must hold to be promoted
+    pub promote_sla: CanarySla,
+}
+
+impl Default for CanaryConfig {
+    fn default() -> Self {
+        Self {
+            model: String::new(),
+            provider: String::new(),
+            start_percent: 5.0,
+            step: 10.0,
+            interval: Duration::from_secs(300), // 5 minutes
+            promote_sla: CanarySla::default(),
+        }
+    }
+}
+
+/// Lifecycle state of a canary deployment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanaryState {
+    /// Traffic share is increasing on schedule
+    Ramping,
+    /// SLA held through the ramp; the canary receives all matching traffic
+    Promoted,
+    /// SLA was breached; the canary receives no traffic
+    RolledBack,
+}
+
+/// Routes a ramping share of matching requests to a canary provider,
+/// promoting or rolling back based on observed outcomes
+#[derive(Debug, Clone)]
+pub struct CanaryDeployment {
+    config: CanaryConfig,
+    aliases: ModelAliasResolver,
+    started_at: Instant,
+    state: CanaryState,
+    /// Ramp step the routed and considered counts belong to
+    counted_step: u64,
+    routed: u64,
+    considered: u64,
+    requests: u64,
+    failures: u64,
+    total_response_time: Duration,
+}
+
+impl CanaryDeployment {
+    /// Start a canary deployment now
+    pub fn new(config: CanaryConfig) -> Self {
+        Self::started_at(config, Instant::now())
+    }
+
+    /// Start a canary deployment at the given instant
+    pub fn started_at(config: CanaryConfig, started_at: Instant) -> Self {
+        info!(
+            model = %config.model,
+            provider = %config.provider,
+            start_percent = config.start_percent,
+            "Starting canary deployment"
+        );
+        Self {
+            config,
+            aliases: ModelAliasResolver::default(),
+            started_at,
+            state: CanaryState::Ramping,
+            counted_step: 0,
+            routed: 0,
+            considered: 0,
+            requests: 0,
+            failures: 0,
+            total_response_time: Duration::ZERO,
+        }
+    }
+
+    /// Compare requested models with the canary model through `aliases`
+    pub fn with_aliases(mut self, aliases: ModelAliasResolver) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// Current lifecycle state
+    pub fn state(&self) -> CanaryState {
+        self.state
+    }
+
+    /// Provider serving the canary
+    pub fn provider(&self) -> &str {
+        &self.config.provider
+    }
+
+    /// Share of matching traffic (0-100) sent to the canary as of `now`
+    pub fn traffic_share_at(&self, now: Instant) -> f64 {
+        match self.state {
+            CanaryState::Promoted => 100.0,
+            CanaryState::RolledBack => 0.0,
+            CanaryState::Ramping => {
+                let steps = self.ramp_step_at(now) as f64;
+                (self.config.start_percent + self.config.step * steps).clamp(0.0, 100.0)
+            }
+        }
+    }
+
+    /// Number of whole ramp intervals elapsed as of `now`
+    fn ramp_step_at(&self, now: Instant) -> u64 {
+        let interval = self.config.interval.as_secs_f64();
+        if interval > 0.0 {
+            (now.saturating_duration_since(self.started_at).as_secs_f64() / interval) as u64
+        } else {
+            0
+        }
+    }
+
+    /// Decide whether a request for `model` should go to the canary now
+    pub fn should_route(&mut self, model: &str) -> bool {
+        self.should_route_at(model, Instant::now())
+    }
+
+    /// Decide whether a request for `model` should go to the canary. `model`
+    /// is eligible when it resolves to the canary model through the aliases.
+    /// Routing is deterministic so the routed fraction tracks the current
+    /// share.
+    /// Counts restart at every ramp step, so requests from earlier steps do
+    /// not skew the fraction routed at the new share.
+    pub fn should_route_at(&mut self, model: &str, now: Instant) -> bool {
+        if self.aliases.normalize(model) != self.aliases.normalize(&self.config.model) {
+            return false;
+        }
+
+        let step = self.ramp_step_at(now);
+        if step != self.counted_step {
+            self.counted_step = step;
+            self.routed = 0;
+            self.considered = 0;
+        }
+
+        let share = self.traffic_share_at(now) / 100.0;
+        self.considered += 1;
+        if (self.routed as f64) < (self.considered as f64 * share).floor() {
+            self.routed += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Record the outcome of a request served by the canary just now
+    pub fn record_outcome(&mut self, success: bool, response_time: Duration) {
+        self.record_outcome_at(success, response_time, Instant::now());
+    }
+
+    /// Record the outcome of a request served by the canary and update its
+    /// state, rolling back on SLA breach or promoting once fully ramped
+    pub fn record_outcome_at(&mut self, success: bool, response_time: Duration, now: Instant) {
+        if self.state != CanaryState::Ramping {
+            return;
+        }
+
+        self.requests += 1;
+        if !success {
+            self.failures += 1;
+        }
+        self.total_response_time += response_time;
+
+        let sla = &self.config.promote_sla;
+        if self.requests < sla.min_requests {
+            return;
+        }
+
+        let error_rate = self.failures as f64 / self.requests as f64;
+        let avg_response_time = self.total_response_time / self.requests as u32;
+
+        if error_rate > sla.max_error_rate || avg_response_time > sla.max_avg_response_time {
+            warn!(
+                model = %self.config.model,
+                provider = %self.config.provider,
+                error_rate,
+                avg_response_time_ms = avg_response_time.as_millis(),
+                "Canary breached SLA, rolling back"
+            );
+            self.state = CanaryState::RolledBack;
+        } else if self.traffic_share_at(now) >= 100.0 {
+            info!(
+                model = %self.config.model,
+                provider = %self.config.provider,
+                "Canary held SLA through ramp, promoting"
+            );
+            self.state = CanaryState::Promoted;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn fixture() -> CanaryConfig {
+        CanaryConfig::default()
+            .model("qwen2.5-coder")
+            .provider("ollama")
+            .start_percent(10.0)
+            .step(30.0)
+            .interval(Duration::from_secs(60))
+            .promote_sla(CanarySla::default().max_error_rate(0.1).min_requests(5u64))
+    }
+
+    #[test]
+    fn test_traffic_share_ramps_per_schedule() {
+        let start = Instant::now();
+        let fixture = CanaryDeployment::started_at(fixture(), start);
+
+        let actual: Vec<f64> = [0, 59, 60, 120, 180, 600]
+            .iter()
+            .map(|secs| fixture.traffic_share_at(start + Duration::from_secs(*secs)))
+            .collect();
+
+        let expected = vec![10.0, 10.0, 40.0, 70.0, 100.0, 100.0];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_routed_fraction_tracks_share() {
+        let start = Instant::now();
+        let mut fixture = CanaryDeployment::started_at(fixture(), start);
+
+        let actual = (0..100)
+            .filter(|_| fixture.should_route_at("qwen2.5-coder", start))
+            .count();
+
+        assert_eq!(actual, 10);
+        assert!(!fixture.should_route_at("llama3.2", start));
+    }
+
+    #[test]
+    fn test_aliased_model_ids_are_eligible() {
+        let start = Instant::now();
+        let mut fixture = CanaryDeployment::started_at(fixture().start_percent(100.0), start)
+            .with_aliases(ModelAliasResolver::new(HashMap::from([(
+                "coder".to_string(),
+                "qwen2.5-coder".to_string(),
+            )])));
+
+        let actual = ["coder", "qwen2.5-coder:latest", "qwen2.5"]
+            .map(|model| fixture.should_route_at(model, start));
+
+        let expected = [true, true, false];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_routed_fraction_restarts_each_step() {
+        let start = Instant::now();
+        let mut fixture = CanaryDeployment::started_at(fixture(), start);
+        for _ in 0..100 {
+            fixture.should_route_at("qwen2.5-coder", start);
+        }
+        let next_step = start + Duration::from_secs(60);
+
+        let actual = (0..10)
+            .filter(|_| fixture.should_route_at("qwen2.5-coder", next_step))
+            .count();
+
+        assert_eq!(actual, 4);
+    }
+
+    #[test]
+    fn test_sla_breach_rolls_back() {
+        let start = Instant::now();
+        let mut fixture = CanaryDeployment::started_at(fixture(), start);
+
+        for _ in 0..5 {
+            fixture.record_outcome_at(false, Duration::from_millis(200), start);
+        }
+
+        assert_eq!(fixture.state(), CanaryState::RolledBack);
+        assert_eq!(fixture.traffic_share_at(start), 0.0);
+        assert!(!fixture.should_route_at("qwen2.5-coder", start));
+    }
+
+    #[test]
+    fn test_promotes_when_sla_holds_through_ramp() {
+        let start = Instant::now();
+        let mut fixture = CanaryDeployment::started_at(fixture(), start);
+        let end = start + Duration::from_secs(180);
+
+        for _ in 0..5 {
+            fixture.record_outcome_at(true, Duration::from_millis(200), end);
+        }
+
+        assert_eq!(fixture.state(), CanaryState::Promoted);
+        assert_eq!(fixture.traffic_share_at(end), 100.0);
+    }
+}
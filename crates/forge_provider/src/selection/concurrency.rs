@@ -0,0 +1,212 @@
+//! Caps on requests in flight per local provider
+//!
+//! A local model can only serve a few generations at once before latency
+//! balloons. Every enabled local provider gets a semaphore sized by its
+//! `max_concurrent_requests`; a request holds a [`RequestSlot`] while it runs.
+//! Once a provider is saturated, further requests either wait a bounded time
+//! for a slot or move on to the next provider, depending on its
+//! [`SaturationPolicy`]. The limits are shared, so the selector and every
+//! [`Client`](crate::Client) built with them count against the same slots.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use super::ProviderSelector;
+use crate::config::local_ai::{LocalAiConfig, SaturationPolicy};
+
+/// Semaphores enforcing each local provider's concurrency limit
+#[derive(Debug, Clone, Default)]
+pub struct ConcurrencyLimits {
+    limits: HashMap<String, ProviderLimit>,
+}
+
+#[derive(Debug, Clone)]
+struct ProviderLimit {
+    semaphore: Arc<Semaphore>,
+    capacity: usize,
+    saturation_policy: SaturationPolicy,
+    queue_timeout: Duration,
+}
+
+/// Permission to run one request against a provider, released on drop
+#[derive(Debug)]
+pub struct RequestSlot {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+/// Rejection returned when a provider has no free request slot
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Provider '{provider}' is at its limit of {capacity} concurrent requests")]
+pub struct Saturated {
+    /// Provider that was saturated
+    pub provider: String,
+    /// Requests the provider accepts at once
+    pub capacity: usize,
+}
+
+impl ConcurrencyLimits {
+    /// Build limits for every enabled provider in `local_config`. Providers
+    /// without a limit are still tracked so their in-flight count is known.
+    pub fn new(local_config: &LocalAiConfig) -> Self {
+        let limits = local_config
+            .enabled_providers()
+            .map(|(name, config)| {
+                let capacity = match config.max_concurrent_requests {
+                    0 => Semaphore::MAX_PERMITS,
+                    limit => limit,
+                };
+                let limit = ProviderLimit {
+                    semaphore: Arc::new(Semaphore::new(capacity)),
+                    capacity,
+                    saturation_policy: config.saturation_policy,
+                    queue_timeout: Duration::from_millis(config.queue_timeout_ms),
+                };
+                (name.clone(), limit)
+            })
+            .collect();
+        Self { limits }
+    }
+
+    /// Requests currently holding a slot for `provider_name`
+    pub fn in_flight(&self, provider_name: &str) -> usize {
+        self.limits.get(provider_name).map_or(0, |limit| {
+            limit.capacity - limit.semaphore.available_permits()
+        })
+    }
+
+    /// Take a slot for `provider_name`. A saturated provider that queues
+    /// requests is waited on for up to its `queue_timeout_ms`; one that falls
+    /// back is rejected at once. Providers without an entry, such as cloud
+    /// providers, are never limited.
+    pub async fn acquire(&self, provider_name: &str) -> Result<RequestSlot, Saturated> {
+        let Some(limit) = self.limits.get(provider_name) else {
+            return Ok(RequestSlot { _permit: None });
+        };
+        let semaphore = Arc::clone(&limit.semaphore);
+        let permit = match limit.saturation_policy {
+            SaturationPolicy::Queue => {
+                tokio::time::timeout(limit.queue_timeout, semaphore.acquire_owned())
+                    .await
+                    .ok()
+                    .and_then(Result::ok)
+            }
+            SaturationPolicy::Fallback => semaphore.try_acquire_owned().ok(),
+        };
+        match permit {
+            Some(permit) => Ok(RequestSlot { _permit: Some(permit) }),
+            None => Err(Saturated {
+                provider: provider_name.to_string(),
+                capacity: limit.capacity,
+            }),
+        }
+    }
+}
+
+impl ProviderSelector {
+    /// Take a slot to run a request against `provider_name`; see
+    /// [`ConcurrencyLimits::acquire`]
+    pub async fn acquire_request_slot(
+        &self,
+        provider_name: &str,
+    ) -> Result<RequestSlot, Saturated> {
+        self.concurrency.acquire(provider_name).await
+    }
+
+    /// The selector's concurrency limits, for handing to the
+    /// [`Client`](crate::Client) that dispatches requests so both share the
+    /// same slots
+    pub fn concurrency_limits(&self) -> ConcurrencyLimits {
+        self.concurrency.clone()
+    }
+
+    /// Requests currently in flight on `provider_name`
+    pub fn requests_in_flight(&self, provider_name: &str) -> usize {
+        self.concurrency.in_flight(provider_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::config::fallback::FallbackConfig;
+    use crate::config::local_ai::LocalProviderConfig;
+    use crate::selection::SelectionContext;
+    use crate::test_utils::{create_healthy_status, selector_with_config};
+
+    async fn fixture(saturation_policy: SaturationPolicy) -> ProviderSelector {
+        let mut local_config = LocalAiConfig::new();
+        local_config.providers.insert(
+            "ollama".to_string(),
+            LocalProviderConfig::default()
+                .max_concurrent_requests(1usize)
+                .saturation_policy(saturation_policy),
+        );
+        selector_with_config(
+            local_config,
+            FallbackConfig::default(),
+            &[("ollama", create_healthy_status())],
+        )
+        .await
+    }
+
+    async fn serve(selector: &mut ProviderSelector) -> String {
+        selector
+            .execute_with_fallback(
+                SelectionContext::new("llama3.2".to_string()),
+                |selection| async move { Ok(selection.provider_name) },
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_saturated_provider_queues_request() {
+        let mut fixture = fixture(SaturationPolicy::Queue).await;
+        let held = fixture.acquire_request_slot("ollama").await.unwrap();
+        assert_eq!(fixture.requests_in_flight("ollama"), 1);
+
+        let started = tokio::time::Instant::now();
+        let release = async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            drop(held);
+        };
+        let (actual, ()) = tokio::join!(serve(&mut fixture), release);
+
+        assert_eq!(actual, "ollama");
+        assert_eq!(started.elapsed(), Duration::from_secs(1));
+        assert_eq!(fixture.requests_in_flight("ollama"), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_queued_request_falls_back_after_queue_timeout() {
+        let mut fixture = fixture(SaturationPolicy::Queue).await;
+        let _held = fixture.acquire_request_slot("ollama").await.unwrap();
+
+        let started = tokio::time::Instant::now();
+        let actual = serve(&mut fixture).await;
+
+        assert_eq!(actual, "cloud:openai");
+        assert_eq!(started.elapsed(), Duration::from_secs(30));
+        assert_eq!(fixture.requests_in_flight("ollama"), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_saturated_provider_falls_back() {
+        let mut fixture = fixture(SaturationPolicy::Fallback).await;
+        let _held = fixture.acquire_request_slot("ollama").await.unwrap();
+
+        let started = tokio::time::Instant::now();
+        let actual = serve(&mut fixture).await;
+
+        assert_eq!(actual, "cloud:openai");
+        assert_eq!(started.elapsed(), Duration::ZERO);
+        assert_eq!(fixture.requests_in_flight("ollama"), 1);
+    }
+}
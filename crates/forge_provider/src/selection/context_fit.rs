@@ -0,0 +1,274 @@
+//! Keeping long prompts away from models that cannot hold them
+//!
+//! When [`SelectionContext::prompt_tokens`](super::SelectionContext) is set,
+//! local providers whose copy of the requested model has a context window
+//! smaller than the prompt are treated as unavailable, and providers known to
+//! fit are tried before those whose context length was never reported. Only
+//! when no local model fits does the fallback engine move to the cloud.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use forge_app::domain::Model;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use super::warm::health_rank;
+use super::ProviderSelector;
+use crate::config::aliases::ModelAliasResolver;
+use crate::config::local_ai::ProviderHealthStatus;
+
+/// Context window of each model served by each local provider, in tokens.
+/// Shared with
+/// [`ModelDiscoveryService::with_context_lengths`](crate::discovery::ModelDiscoveryService::with_context_lengths)
+/// to keep it in step with discovery.
+#[derive(Debug, Clone, Default)]
+pub struct ContextLengths {
+    lengths: Arc<RwLock<HashMap<String, HashMap<String, u64>>>>,
+}
+
+impl ContextLengths {
+    /// Record the context window of `model_id` on `provider_name`
+    pub async fn set_context_length(&self, provider_name: &str, model_id: &str, tokens: u64) {
+        self.lengths
+            .write()
+            .await
+            .entry(provider_name.to_string())
+            .or_default()
+            .insert(model_id.to_string(), tokens);
+    }
+
+    /// Record the context windows reported for `models` as everything
+    /// `provider_name` serves, forgetting models it no longer lists
+    pub async fn set_models(&self, provider_name: &str, models: &[Model]) {
+        let provider = models
+            .iter()
+            .filter_map(|model| Some((model.id.as_str().to_string(), model.context_length?)))
+            .collect();
+        self.lengths
+            .write()
+            .await
+            .insert(provider_name.to_string(), provider);
+    }
+
+    /// Context window of `model_id` on `provider_name`, if any was reported.
+    /// A model equal to `model_id` once normalized decides it; otherwise the
+    /// largest window in the model's family does.
+    async fn context_length(
+        &self,
+        provider_name: &str,
+        model_id: &str,
+        aliases: &ModelAliasResolver,
+    ) -> Option<u64> {
+        let lengths = self.lengths.read().await;
+        let models = lengths.get(provider_name)?;
+        let requested = aliases.normalize(model_id);
+        models
+            .iter()
+            .find(|(model, _)| aliases.normalize(model) == requested)
+            .map(|(_, tokens)| *tokens)
+            .or_else(|| {
+                models
+                    .iter()
+                    .filter(|(model, _)| aliases.matches(model_id, model))
+                    .map(|(_, tokens)| *tokens)
+                    .max()
+            })
+    }
+}
+
+impl ProviderSelector {
+    /// Context windows of the models served by each local provider
+    pub fn context_lengths(&self) -> &ContextLengths {
+        &self.context_lengths
+    }
+
+    /// Judge context fit against `context_lengths`, such as those a
+    /// [`ModelDiscoveryService`](crate::discovery::ModelDiscoveryService)
+    /// reports to
+    pub fn with_context_lengths(mut self, context_lengths: ContextLengths) -> Self {
+        self.context_lengths = context_lengths;
+        self
+    }
+
+    /// Mark the usable providers in `local_health` whose copy of `model_id`
+    /// cannot hold `prompt_tokens` as unhealthy, then move providers known
+    /// to fit ahead of those with an unknown context length within each
+    /// health tier
+    pub(super) async fn apply_context_fit(
+        &self,
+        model_id: &str,
+        prompt_tokens: Option<usize>,
+        local_health: &mut [(String, ProviderHealthStatus)],
+    ) {
+        let Some(prompt_tokens) = prompt_tokens else {
+            return;
+        };
+
+        let mut known = HashMap::new();
+        for (name, status) in local_health.iter_mut() {
+            let context_length = self
+                .context_lengths
+                .context_length(name, model_id, &self.aliases)
+                .await;
+            known.insert(name.clone(), context_length.is_some());
+            let Some(context_length) = context_length else {
+                continue;
+            };
+            if status.is_usable() && context_length < prompt_tokens as u64 {
+                let reason = format!(
+                    "Context length {context_length} is below the {prompt_tokens}-token prompt"
+                );
+                debug!(provider = %name, reason = %reason, "Provider excluded by context length");
+                *status = ProviderHealthStatus::Unhealthy {
+                    reason,
+                    response_time: status.response_time(),
+                };
+            }
+        }
+
+        local_health.sort_by_key(|(name, status)| (health_rank(status), !known[name]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::config::fallback::FallbackConfig;
+    use crate::config::local_ai::{LocalAiConfig, LocalProviderConfig};
+    use crate::discovery::ModelDiscoveryService;
+    use crate::mock_server::{MockOllamaServer, ScriptedResponse};
+    use crate::selection::SelectionContext;
+    use crate::test_utils::{create_healthy_status, selector_with_health};
+
+    async fn fixture(providers: &[(&str, u64)]) -> ProviderSelector {
+        let health: Vec<_> = providers
+            .iter()
+            .map(|(name, _)| (*name, create_healthy_status()))
+            .collect();
+        let selector = selector_with_health(&health).await;
+        for (name, context_length) in providers {
+            selector
+                .context_lengths
+                .set_context_length(name, "llama3.2:latest", *context_length)
+                .await;
+        }
+        selector
+    }
+
+    async fn select_names(fixture: &mut ProviderSelector, prompt_tokens: usize) -> Vec<String> {
+        let mut names = Vec::new();
+        for _ in 0..4 {
+            let context = SelectionContext::new("llama3.2:latest".to_string())
+                .with_prompt_tokens(prompt_tokens);
+            names.push(
+                fixture
+                    .select_provider(context)
+                    .await
+                    .unwrap()
+                    .provider_name,
+            );
+        }
+        names
+    }
+
+    #[tokio::test]
+    async fn test_long_prompt_skips_small_context_model() {
+        let mut fixture = fixture(&[("small", 4_096), ("large", 131_072)]).await;
+
+        let actual = select_names(&mut fixture, 32_000).await;
+
+        let expected = vec!["large".to_string(); 4];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_long_prompt_goes_to_cloud_when_no_local_model_fits() {
+        let mut fixture = fixture(&[("small", 4_096)]).await;
+
+        let actual = select_names(&mut fixture, 32_000).await;
+
+        assert!(
+            actual.iter().all(|name| name.starts_with("cloud:")),
+            "{actual:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_exact_model_decides_over_larger_family_member() {
+        let mut fixture = fixture(&[("small", 4_096)]).await;
+        fixture
+            .context_lengths
+            .set_context_length("small", "llama3.2:1b", 131_072)
+            .await;
+
+        let actual = select_names(&mut fixture, 32_000).await;
+
+        assert!(
+            actual.iter().all(|name| name.starts_with("cloud:")),
+            "{actual:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_short_prompt_may_use_small_context_model() {
+        let mut fixture = fixture(&[("small", 4_096)]).await;
+
+        let actual = select_names(&mut fixture, 1_000).await;
+
+        let expected = vec!["small".to_string(); 4];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_context_lengths_come_from_discovery() {
+        let server = MockOllamaServer::builder()
+            .tags(&["llama3.2:latest"])
+            .on(
+                "POST",
+                "/api/show",
+                ScriptedResponse::json(
+                    200,
+                    serde_json::json!({ "model_info": { "llama.context_length": 4096 } }),
+                ),
+            )
+            .start()
+            .await;
+        let local_config = LocalAiConfig::new().add_provider(
+            "small".to_string(),
+            LocalProviderConfig::default().endpoint(server.url()),
+        );
+        let mut fixture = ProviderSelector::new(local_config.clone(), FallbackConfig::default())
+            .await
+            .unwrap();
+        fixture
+            .health_monitor
+            .set_provider_status(
+                "small",
+                ProviderHealthStatus::Healthy {
+                    response_time: Duration::from_millis(50),
+                    models_available: 1,
+                    additional_info: None,
+                },
+            )
+            .await;
+        let mut discovery = ModelDiscoveryService::new(local_config)
+            .await
+            .unwrap()
+            .with_context_lengths(fixture.context_lengths().clone());
+        discovery.refresh_discovery().await.unwrap();
+
+        let long = select_names(&mut fixture, 32_000).await;
+        let short = select_names(&mut fixture, 1_000).await;
+
+        assert!(
+            long.iter().all(|name| name.starts_with("cloud:")),
+            "{long:?}"
+        );
+        assert_eq!(short, vec!["small".to_string(); 4]);
+    }
+}
@@ -0,0 +1,182 @@
+//! Request ids correlating everything logged for one request
+//!
+//! Each top-level selection opens a `provider_request` span carrying a fresh
+//! request id and the model id. Health reads, fallback decisions and, under
+//! [`ProviderSelector::execute_with_fallback`], the provider call itself are
+//! all logged inside that span, so filtering on the id shows the request's
+//! whole journey.
+//!
+//! [`ProviderSelector::execute_with_fallback`]: super::ProviderSelector::execute_with_fallback
+
+use tracing::Span;
+use uuid::Uuid;
+
+/// Generate the id of a new request
+pub(crate) fn new_request_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// Span grouping everything logged while serving request `request_id`
+pub(crate) fn request_span(request_id: &str, model_id: &str) -> Span {
+    tracing::info_span!("provider_request", request_id, model = model_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use pretty_assertions::assert_eq;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id};
+    use tracing::{Event, Subscriber};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::registry::LookupSpan;
+    use tracing_subscriber::Layer;
+
+    use crate::config::enhanced::EnhancedFallbackConfig;
+    use crate::config::fallback::FallbackConfig;
+    use crate::config::local_ai::LocalAiConfig;
+    use crate::selection::{EnhancedProviderSelector, ProviderSelector, SelectionContext};
+    use crate::test_utils::{create_healthy_status, selector_with_config};
+
+    /// Target and enclosing request id of an event
+    type CapturedEvent = (String, Option<String>);
+
+    /// Every event logged while installed
+    #[derive(Clone, Default)]
+    struct CapturedEvents(Arc<Mutex<Vec<CapturedEvent>>>);
+
+    impl CapturedEvents {
+        /// Request ids of the events logged by modules under `target`
+        fn request_ids(&self, target: &str) -> Vec<Option<String>> {
+            self.0
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(event_target, _)| event_target.starts_with(target))
+                .map(|(_, request_id)| request_id.clone())
+                .collect()
+        }
+    }
+
+    struct RequestId(String);
+
+    #[derive(Default)]
+    struct RequestIdVisitor(Option<String>);
+
+    impl Visit for RequestIdVisitor {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if field.name() == "request_id" {
+                self.0 = Some(value.to_string());
+            }
+        }
+
+        fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+    }
+
+    impl<S> Layer<S> for CapturedEvents
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+            let mut visitor = RequestIdVisitor::default();
+            attrs.record(&mut visitor);
+            if let (Some(request_id), Some(span)) = (visitor.0, ctx.span(id)) {
+                span.extensions_mut().insert(RequestId(request_id));
+            }
+        }
+
+        fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+            let request_id = ctx.event_scope(event).and_then(|scope| {
+                scope
+                    .from_root()
+                    .find_map(|span| span.extensions().get::<RequestId>().map(|id| id.0.clone()))
+            });
+            self.0
+                .lock()
+                .unwrap()
+                .push((event.metadata().target().to_string(), request_id));
+        }
+    }
+
+    async fn fixture() -> ProviderSelector {
+        selector_with_config(
+            LocalAiConfig::with_default_ollama(),
+            FallbackConfig::default(),
+            &[("ollama", create_healthy_status())],
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_selection_and_fallback_events_share_request_id() {
+        let mut fixture = fixture().await;
+        let captured = CapturedEvents::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(captured.clone()));
+
+        let actual = fixture
+            .select_provider(SelectionContext::new("llama3.2".to_string()))
+            .await
+            .unwrap();
+
+        let expected = actual.request_id.clone();
+        assert!(expected.is_some());
+        let selection = captured.request_ids("forge_provider::selection");
+        let fallback = captured.request_ids("forge_provider::config::fallback");
+        assert!(!selection.is_empty());
+        assert!(!fallback.is_empty());
+        assert!(selection.iter().all(|request_id| request_id == &expected));
+        assert!(fallback.iter().all(|request_id| request_id == &expected));
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_events_share_request_id() {
+        let mut fixture = fixture().await;
+        let captured = CapturedEvents::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(captured.clone()));
+
+        let actual = fixture
+            .execute_with_fallback(
+                SelectionContext::new("llama3.2".to_string()),
+                |selection| async move {
+                    tracing::info!(provider = %selection.provider_name, "Dispatching request");
+                    Ok(selection.request_id)
+                },
+            )
+            .await
+            .unwrap();
+
+        let dispatch = captured.request_ids(module_path!());
+        assert_eq!(dispatch, vec![actual.clone()]);
+        assert!(captured
+            .request_ids("forge_provider::config::fallback")
+            .iter()
+            .all(|request_id| request_id == &actual));
+    }
+
+    #[tokio::test]
+    async fn test_enhanced_selection_events_share_request_id() {
+        let mut fixture = EnhancedProviderSelector::new(
+            LocalAiConfig::with_default_ollama(),
+            EnhancedFallbackConfig::default(),
+        )
+        .await
+        .unwrap();
+        let captured = CapturedEvents::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(captured.clone()));
+
+        let actual = fixture
+            .select_provider_enhanced(SelectionContext::new("llama3.2".to_string()))
+            .await
+            .unwrap();
+
+        let expected = actual.selection.request_id.clone();
+        assert!(expected.is_some());
+        let decisions = captured.request_ids("forge_provider::config");
+        assert!(!decisions.is_empty());
+        assert!(decisions.iter().all(|request_id| request_id == &expected));
+    }
+}
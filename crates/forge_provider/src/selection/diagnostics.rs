@@ -0,0 +1,530 @@
+//! Diagnostic trail attached to failures across all providers
+
+use std::fmt;
+use std::future::Future;
+use std::time::Instant;
+
+use tracing::{debug, info, warn, Instrument};
+
+use crate::config::local_ai::ProviderHealthStatus;
+use crate::retry::FailureKind;
+use crate::selection::correlation::{new_request_id, request_span};
+use crate::selection::{
+    ProviderSelection, ProviderSelector, SelectionContext, SelectionError, SelectionResult,
+};
+
+/// A single attempt made while serving a request
+#[derive(Debug, Clone)]
+pub struct ProviderAttempt {
+    /// Provider that was tried
+    pub provider_name: String,
+    /// Health of the provider when it was tried (local providers only)
+    pub health: Option<ProviderHealthStatus>,
+    /// Error returned by the provider
+    pub error: String,
+}
+
+/// Every provider attempted for a request and why each one failed
+#[derive(Debug, Clone)]
+pub struct SelectionDiagnostics {
+    /// Model being requested
+    pub model_id: String,
+    /// Attempts in the order they were made
+    pub attempts: Vec<ProviderAttempt>,
+}
+
+impl fmt::Display for SelectionDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "All {} provider attempts failed for model {}",
+            self.attempts.len(),
+            self.model_id
+        )?;
+        for attempt in &self.attempts {
+            let health = attempt
+                .health
+                .as_ref()
+                .map(ProviderHealthStatus::label)
+                .unwrap_or("n/a");
+            write!(
+                f,
+                "\n  - {} (health: {health}): {}",
+                attempt.provider_name, attempt.error
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SelectionDiagnostics {}
+
+impl ProviderSelector {
+    /// Run `request` against the selected provider, falling back to the
+    /// remaining recommended providers on failure. A provider at its
+    /// concurrency limit is waited on or skipped according to its
+    /// saturation policy. When every provider fails and `explain_on_error`
+    /// is enabled, the returned error wraps a [`SelectionDiagnostics`]
+    /// listing each attempt. A cancelled request is returned as is, without
+    /// counting against the provider or falling back, and so is the failure
+    /// of a provider forced by `context.force_provider`.
+    ///
+    /// Selection and every call to `request` run in one request span, so
+    /// whatever `request` logs shares the selection's request id.
+    pub async fn execute_with_fallback<T, F, Fut>(
+        &mut self,
+        context: SelectionContext,
+        request: F,
+    ) -> anyhow::Result<T>
+    where
+        F: FnMut(ProviderSelection) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let request_id = new_request_id();
+        let span = request_span(&request_id, &context.model_id);
+        self.execute_request(context, &request_id, request)
+            .instrument(span)
+            .await
+    }
+
+    async fn execute_request<T, F, Fut>(
+        &mut self,
+        context: SelectionContext,
+        request_id: &str,
+        mut request: F,
+    ) -> anyhow::Result<T>
+    where
+        F: FnMut(ProviderSelection) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let model_id = context.model_id.clone();
+        let mut attempts: Vec<ProviderAttempt> = Vec::new();
+        let mut last_error = None;
+        let mut next = Some(
+            self.select_provider_for(context.clone(), request_id)
+                .await?,
+        );
+
+        while let Some(mut selection) = next.take() {
+            selection.request_id = Some(request_id.to_string());
+            let provider_name = selection.provider_name.clone();
+            let health = self
+                .health_monitor
+                .get_provider_health(&provider_name)
+                .await;
+
+            let Ok(_slot) = self.concurrency.acquire(&provider_name).await else {
+                if context.force_provider.is_some() {
+                    return Err(SelectionError::ForcedProviderUnavailable {
+                        provider: provider_name,
+                        reason: "At its concurrency limit".to_string(),
+                    }
+                    .into());
+                }
+                info!(provider = %provider_name, "Provider at its concurrency limit, falling back");
+                attempts.push(ProviderAttempt {
+                    provider_name: provider_name.clone(),
+                    health,
+                    error: "At its concurrency limit".to_string(),
+                });
+                next = self.next_fallback_selection(&context, &attempts).await;
+                continue;
+            };
+            let started = Instant::now();
+
+            match request(selection).await {
+                Ok(value) => {
+                    self.record_success(&provider_name, started.elapsed());
+                    return Ok(value);
+                }
+                Err(error) if !FailureKind::classify(&error).is_provider_failure() => {
+                    info!(provider = %provider_name, "Request cancelled");
+                    return Err(error);
+                }
+                Err(error) => {
+                    self.record_failure(&provider_name, &error.to_string());
+                    if context.force_provider.is_some() {
+                        return Err(error);
+                    }
+                    attempts.push(ProviderAttempt {
+                        provider_name: provider_name.clone(),
+                        health,
+                        error: self.redact(&format!("{error:#}")),
+                    });
+                    last_error = Some(error);
+                }
+            }
+
+            next = self.next_fallback_selection(&context, &attempts).await;
+        }
+
+        let diagnostics = SelectionDiagnostics { model_id, attempts };
+        warn!(
+            model = %diagnostics.model_id,
+            attempts = diagnostics.attempts.len(),
+            "All providers failed"
+        );
+
+        match last_error {
+            Some(error) if !self.fallback_config.explain_on_error => {
+                Err(error.context("All providers failed"))
+            }
+            _ => Err(diagnostics.into()),
+        }
+    }
+
+    /// Select the next provider for `context` with every attempted
+    /// provider excluded, applying the same routing rules, tag, context fit,
+    /// latency and circuit breaker filters as the first selection
+    async fn next_fallback_selection(
+        &mut self,
+        context: &SelectionContext,
+        attempts: &[ProviderAttempt],
+    ) -> Option<ProviderSelection> {
+        let context = context
+            .clone()
+            .with_excluded_providers(attempts.iter().map(|a| a.provider_name.clone()));
+        let selection = match self
+            .plan_selection(&context, false, &self.fallback_engine)
+            .await
+        {
+            Ok(SelectionResult::Selected(selection)) => selection,
+            Ok(SelectionResult::Degraded(_)) => return None,
+            Err(error) => {
+                debug!(error = %error, "No provider left to fall back to");
+                return None;
+            }
+        };
+
+        let previous = attempts
+            .last()
+            .map(|a| a.provider_name.as_str())
+            .unwrap_or("");
+        let selection = ProviderSelection {
+            reason: format!("Falling back after failure of {previous}"),
+            is_fallback: true,
+            ..selection
+        };
+
+        info!(provider = %selection.provider_name, previous = previous, "Trying next provider");
+        self.record_selection(&selection, Instant::now());
+        Some(selection)
+    }
+
+    /// Mark the providers in `local_health` that must not be selected as
+    /// unhealthy
+    pub(super) fn exclude_providers(
+        &self,
+        excluded: &[String],
+        local_health: &mut [(String, ProviderHealthStatus)],
+    ) {
+        for (name, status) in local_health.iter_mut() {
+            if excluded.contains(name) {
+                *status = ProviderHealthStatus::Unhealthy {
+                    reason: "Already attempted for this request".to_string(),
+                    response_time: status.response_time(),
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use forge_app::domain::{Context, ContextMessage, HttpConfig, ModelId, Provider, RetryConfig};
+    use futures::StreamExt;
+    use pretty_assertions::assert_eq;
+    use reqwest::Url;
+    use tokio_util::sync::CancellationToken;
+
+    use super::*;
+    use crate::circuit_breaker::CircuitState;
+    use crate::client::Client;
+    use crate::config::fallback::FallbackConfig;
+    use crate::config::local_ai::{LocalAiConfig, LocalProviderConfig};
+    use crate::mock_server::{MockOllamaServer, ScriptedResponse};
+    use crate::redaction::PatternRedactor;
+    use crate::selection::ProviderType;
+    use crate::test_utils::{create_healthy_status, selector_with_config};
+
+    async fn fixture(fallback_config: FallbackConfig) -> ProviderSelector {
+        selector_with_config(
+            LocalAiConfig::with_default_ollama(),
+            fallback_config,
+            &[("ollama", create_healthy_status())],
+        )
+        .await
+    }
+
+    async fn fail_everywhere(selector: &mut ProviderSelector) -> anyhow::Error {
+        selector
+            .execute_with_fallback(
+                SelectionContext::new("llama3.2".to_string()),
+                |selection| async move {
+                    Err::<(), _>(anyhow::anyhow!("{} refused", selection.provider_name))
+                },
+            )
+            .await
+            .unwrap_err()
+    }
+
+    #[tokio::test]
+    async fn test_failure_diagnostics_list_every_attempt() {
+        let mut fixture = fixture(FallbackConfig::default()).await;
+
+        let actual = fail_everywhere(&mut fixture).await;
+
+        let diagnostics = actual.downcast_ref::<SelectionDiagnostics>().unwrap();
+        let providers: Vec<_> = diagnostics
+            .attempts
+            .iter()
+            .map(|attempt| attempt.provider_name.as_str())
+            .collect();
+        assert_eq!(providers, vec!["ollama", "cloud:openai", "cloud:anthropic"]);
+        let errors: Vec<_> = diagnostics
+            .attempts
+            .iter()
+            .map(|attempt| attempt.error.as_str())
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "ollama refused",
+                "cloud:openai refused",
+                "cloud:anthropic refused"
+            ]
+        );
+        assert!(matches!(
+            diagnostics.attempts[0].health,
+            Some(ProviderHealthStatus::Healthy { .. })
+        ));
+        assert!(diagnostics.attempts[1].health.is_none());
+        assert!(actual.to_string().contains("cloud:anthropic (health: n/a)"));
+    }
+
+    #[tokio::test]
+    async fn test_failure_without_explain_returns_last_error() {
+        let mut fixture = fixture(FallbackConfig::default().explain_on_error(false)).await;
+
+        let actual = fail_everywhere(&mut fixture).await;
+
+        assert!(actual.downcast_ref::<SelectionDiagnostics>().is_none());
+        assert_eq!(actual.root_cause().to_string(), "cloud:anthropic refused");
+    }
+
+    #[tokio::test]
+    async fn test_failure_diagnostics_are_redacted() {
+        let mut fixture = fixture(FallbackConfig::default())
+            .await
+            .with_redactor(Arc::new(PatternRedactor::with_default_pii()));
+
+        let actual = fixture
+            .execute_with_fallback(
+                SelectionContext::new("llama3.2".to_string()),
+                |_| async move {
+                    Err::<(), _>(anyhow::anyhow!("rejected prompt from jane@example.com"))
+                },
+            )
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(!actual.contains("jane@example.com"));
+        assert!(actual.contains("rejected prompt from [EMAIL]"));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_request_ends_promptly_without_fallback() {
+        let server = MockOllamaServer::builder()
+            .on(
+                "POST",
+                "/api/chat",
+                ScriptedResponse::chat_stream("llama3.2", &["token"; 50])
+                    .with_chunk_interval(Duration::from_millis(100)),
+            )
+            .start()
+            .await;
+        let client = Client::new(
+            Provider::Ollama { url: Url::parse(&format!("{}/", server.url())).unwrap() },
+            Arc::new(RetryConfig::default()),
+            "dev",
+            &HttpConfig::default(),
+        )
+        .unwrap();
+        let mut fixture = fixture(FallbackConfig::default()).await;
+        let attempted = Arc::new(Mutex::new(Vec::new()));
+        let before = provider_state(&fixture);
+        let started = Instant::now();
+
+        let actual = fixture
+            .execute_with_fallback(SelectionContext::new("llama3.2".to_string()), |selection| {
+                let client = client.clone();
+                let attempted = Arc::clone(&attempted);
+                async move {
+                    attempted.lock().unwrap().push(selection.provider_name);
+                    let token = CancellationToken::new();
+                    let context = Context::default().add_message(ContextMessage::user(
+                        "Hello",
+                        ModelId::new("llama3.2").into(),
+                    ));
+                    let mut chunks = Box::pin(client.chat_cancellable(
+                        &ModelId::new("llama3.2"),
+                        context,
+                        token.clone(),
+                    ));
+                    while let Some(chunk) = chunks.next().await {
+                        chunk?;
+                        // Cancel as soon as generation has started
+                        token.cancel();
+                    }
+                    Ok(())
+                }
+            })
+            .await
+            .unwrap_err();
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(FailureKind::classify(&actual), FailureKind::Cancelled);
+        assert!(actual.downcast_ref::<SelectionDiagnostics>().is_none());
+        assert_eq!(*attempted.lock().unwrap(), vec!["ollama".to_string()]);
+        assert_eq!(provider_state(&fixture), before);
+    }
+
+    /// Request counts and breaker state of every provider, to check that a
+    /// request left no trace on them
+    fn provider_state(selector: &ProviderSelector) -> Vec<(String, u64, u64, CircuitState)> {
+        let now = Instant::now();
+        ["ollama", "cloud:openai", "cloud:anthropic"]
+            .into_iter()
+            .map(|provider_name| {
+                let (total, successful) = selector
+                    .get_provider_metrics()
+                    .get(provider_name)
+                    .map_or((0, 0), |metrics| {
+                        (metrics.total_requests, metrics.successful_requests)
+                    });
+                let breaker = match provider_name.strip_prefix("cloud:") {
+                    Some(cloud) => selector.fallback_engine.cloud_breaker_state_at(cloud, now),
+                    None => selector.latency_slo.breaker_state_at(provider_name, now),
+                };
+                (provider_name.to_string(), total, successful, breaker)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_success_after_fallback() {
+        let mut fixture = fixture(FallbackConfig::default()).await;
+
+        let actual = fixture
+            .execute_with_fallback(
+                SelectionContext::new("llama3.2".to_string()),
+                |selection| async move {
+                    if selection.provider_type == ProviderType::Local {
+                        anyhow::bail!("model not loaded")
+                    }
+                    Ok(selection.provider_name)
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(actual, "cloud:openai");
+    }
+
+    fn attempted_providers(error: &anyhow::Error) -> Vec<String> {
+        error
+            .downcast_ref::<SelectionDiagnostics>()
+            .unwrap()
+            .attempts
+            .iter()
+            .map(|attempt| attempt.provider_name.clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_fallback_skips_cloud_provider_with_open_circuit() {
+        let mut fixture = fixture(FallbackConfig::default()).await;
+        for _ in 0..3 {
+            fixture.fallback_engine.record_cloud_failure("openai");
+        }
+
+        let actual = attempted_providers(&fail_everywhere(&mut fixture).await);
+
+        let expected = vec!["ollama".to_string(), "cloud:anthropic".to_string()];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_skips_local_model_too_small_for_prompt() {
+        let mut local_config = LocalAiConfig::new();
+        for name in ["large", "small"] {
+            local_config
+                .providers
+                .insert(name.to_string(), LocalProviderConfig::default());
+        }
+        let mut fixture = ProviderSelector::new(local_config, FallbackConfig::default())
+            .await
+            .unwrap();
+        for (name, context_length) in [("large", 131_072), ("small", 4_096)] {
+            fixture
+                .health_monitor
+                .set_provider_status(
+                    name,
+                    ProviderHealthStatus::Healthy {
+                        response_time: Duration::from_millis(20),
+                        models_available: 1,
+                        additional_info: None,
+                    },
+                )
+                .await;
+            fixture
+                .context_lengths
+                .set_context_length(name, "llama3.2:latest", context_length)
+                .await;
+        }
+
+        let actual = fixture
+            .execute_with_fallback(
+                SelectionContext::new("llama3.2:latest".to_string()).with_prompt_tokens(32_000),
+                |selection| async move {
+                    Err::<(), _>(anyhow::anyhow!("{} refused", selection.provider_name))
+                },
+            )
+            .await
+            .unwrap_err();
+
+        let expected = vec![
+            "large".to_string(),
+            "cloud:openai".to_string(),
+            "cloud:anthropic".to_string(),
+        ];
+        assert_eq!(attempted_providers(&actual), expected);
+    }
+
+    #[tokio::test]
+    async fn test_failed_forced_provider_is_not_replaced() {
+        let mut fixture = fixture(FallbackConfig::default()).await;
+        let attempted = Arc::new(Mutex::new(Vec::new()));
+
+        let actual = fixture
+            .execute_with_fallback(
+                SelectionContext::new("llama3.2".to_string()).with_force_provider("ollama"),
+                |selection| {
+                    let attempted = Arc::clone(&attempted);
+                    async move {
+                        attempted.lock().unwrap().push(selection.provider_name);
+                        Err::<(), _>(anyhow::anyhow!("ollama refused"))
+                    }
+                },
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(actual.to_string(), "ollama refused");
+        assert_eq!(*attempted.lock().unwrap(), vec!["ollama".to_string()]);
+    }
+}
@@ -4,19 +4,48 @@
 //! enhancements, providing adaptive decision-making, pattern learning, and
 //! improved user experience.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use tracing::{debug, info, warn};
+use tracing::{debug, info, warn, Instrument};
 
 use crate::config::enhanced::{
-    EnhancedFallbackConfig, EnhancedFallbackDecision, EnhancedFallbackEngine,
+    EnhancedFallbackConfig, EnhancedFallbackDecision, EnhancedFallbackEngine, RequestOutcome,
+    TokenUsage,
 };
 use crate::config::fallback::{FallbackContext, FallbackDecision};
 use crate::config::local_ai::{LocalAiConfig, ProviderHealthStatus};
-use crate::health::HealthMonitor;
-use crate::selection::{ProviderMetrics, ProviderSelection, ProviderType, SelectionContext};
+use crate::health::{HealthCheckerFactory, HealthMonitor};
+use crate::selection::correlation::{new_request_id, request_span};
+use crate::selection::{
+    CandidateExplanation, DecisionStage, ProviderMetrics, ProviderSelection, ProviderType,
+    SelectionContext, SelectionError, SelectionExplanation,
+};
+
+/// Most recent outcomes for the same provider and model considered when
+/// calculating recommendation strength
+const OUTCOME_WINDOW: usize = 20;
+
+/// Outcomes needed before history carries its full weight in the
+/// recommendation strength
+const OUTCOME_FULL_WEIGHT_SAMPLES: usize = 5;
+
+/// Lowest success rate at which seamless switching keeps the current provider
+const SEAMLESS_MIN_SUCCESS_RATE: f64 = 0.8;
+
+/// Slowest average response time at which seamless switching keeps the
+/// current provider
+const SEAMLESS_MAX_RESPONSE_TIME: Duration = Duration::from_secs(5);
+
+/// Feedback entries kept per selector
+const MAX_USER_FEEDBACK: usize = 100;
+
+/// Age at which a feedback rating counts half as much as a fresh one
+const FEEDBACK_HALF_LIFE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// How far feedback can move a provider's recommendation score either way
+const FEEDBACK_WEIGHT: f64 = 0.2;
 
 /// Enhanced provider selector with intelligent features
 pub struct EnhancedProviderSelector {
@@ -26,9 +55,11 @@ pub struct EnhancedProviderSelector {
     health_monitor: HealthMonitor,
     provider_metrics: HashMap<String, ProviderMetrics>,
     current_provider: Option<String>,
-    last_fallback_time: Option<Instant>,
     selection_history: Vec<SelectionHistoryEntry>,
-    user_feedback: HashMap<String, UserFeedback>,
+    /// Feedback keyed by arrival order, oldest first
+    user_feedback: BTreeMap<u64, UserFeedback>,
+    next_feedback_id: u64,
+    last_explanation: Option<SelectionExplanation>,
 }
 
 /// Selection history entry for learning
@@ -38,6 +69,8 @@ pub struct SelectionHistoryEntry {
     pub timestamp: Instant,
     /// Context of the selection
     pub context: SelectionContext,
+    /// Request the selection was made for
+    pub request_id: Option<String>,
     /// Decision made
     pub decision: EnhancedFallbackDecision,
     /// Actual outcome
@@ -55,6 +88,8 @@ pub struct SelectionOutcome {
     pub user_satisfaction: Option<f64>,
     /// Quality assessment
     pub quality_score: Option<f64>,
+    /// Tokens the provider reported for the request
+    pub token_usage: Option<TokenUsage>,
     /// Error message if failed
     pub error_message: Option<String>,
 }
@@ -89,6 +124,17 @@ pub enum FeedbackType {
     Reliability,
 }
 
+impl FeedbackType {
+    /// How much a rating of this type says about the provider's responses
+    fn weight(&self) -> f64 {
+        match self {
+            FeedbackType::Satisfaction | FeedbackType::Quality | FeedbackType::Reliability => 1.0,
+            FeedbackType::Performance => 0.75,
+            FeedbackType::Cost => 0.5,
+        }
+    }
+}
+
 /// Enhanced provider selection result
 #[derive(Debug, Clone)]
 pub struct EnhancedProviderSelection {
@@ -126,10 +172,25 @@ impl EnhancedProviderSelector {
     pub async fn new(
         local_config: LocalAiConfig,
         enhanced_config: EnhancedFallbackConfig,
+    ) -> Result<Self> {
+        Self::new_with_factory(
+            local_config,
+            enhanced_config,
+            &HealthCheckerFactory::default(),
+        )
+        .await
+    }
+
+    /// Create an enhanced provider selector whose health checkers are built
+    /// by `factory`
+    pub async fn new_with_factory(
+        local_config: LocalAiConfig,
+        enhanced_config: EnhancedFallbackConfig,
+        factory: &HealthCheckerFactory,
     ) -> Result<Self> {
         let enhanced_engine =
             EnhancedFallbackEngine::new(enhanced_config.clone(), local_config.clone());
-        let health_monitor = HealthMonitor::new(local_config.clone()).await?;
+        let health_monitor = HealthMonitor::new_with_factory(local_config.clone(), factory).await?;
 
         Ok(Self {
             local_config,
@@ -138,9 +199,10 @@ impl EnhancedProviderSelector {
             health_monitor,
             provider_metrics: HashMap::new(),
             current_provider: None,
-            last_fallback_time: None,
             selection_history: Vec::new(),
-            user_feedback: HashMap::new(),
+            user_feedback: BTreeMap::new(),
+            next_feedback_id: 0,
+            last_explanation: None,
         })
     }
 
@@ -178,6 +240,18 @@ impl EnhancedProviderSelector {
     pub async fn select_provider_enhanced(
         &mut self,
         context: SelectionContext,
+    ) -> Result<EnhancedProviderSelection> {
+        let request_id = new_request_id();
+        let span = request_span(&request_id, &context.model_id);
+        self.select_enhanced(context, &request_id)
+            .instrument(span)
+            .await
+    }
+
+    async fn select_enhanced(
+        &mut self,
+        context: SelectionContext,
+        request_id: &str,
     ) -> Result<EnhancedProviderSelection> {
         info!(
             model = %context.model_id,
@@ -188,7 +262,10 @@ impl EnhancedProviderSelector {
 
         // Check for seamless switching opportunities
         if self.enhanced_config.ux_optimizations.seamless_switching {
-            if let Some(seamless_switch) = self.check_seamless_switching(&context).await {
+            if let Some(mut seamless_switch) = self.check_seamless_switching(&context).await {
+                seamless_switch.selection.request_id = Some(request_id.to_string());
+                self.record_selection_history(&context, &seamless_switch)
+                    .await;
                 return Ok(seamless_switch);
             }
         }
@@ -197,11 +274,12 @@ impl EnhancedProviderSelector {
         let local_health: Vec<_> = self.health_monitor.get_providers_by_health().await;
 
         // Create fallback context
-        let fallback_context = FallbackContext::new(context.model_id.clone())
+        let mut fallback_context = FallbackContext::new(context.model_id.clone())
             .with_streaming(context.requires_streaming)
             .with_tools(context.requires_tools)
             .with_previous_provider(context.previous_provider.clone().unwrap_or_default())
             .with_consecutive_failures(context.consecutive_failures);
+        fallback_context.prompt_chars = context.prompt_chars;
 
         // Make enhanced fallback decision
         let enhanced_decision = self
@@ -210,13 +288,15 @@ impl EnhancedProviderSelector {
             .await;
 
         // Convert to enhanced selection
-        let enhanced_selection = self
+        let mut enhanced_selection = self
             .convert_to_enhanced_selection(enhanced_decision, &local_health, &context)
             .await?;
+        enhanced_selection.selection.request_id = Some(request_id.to_string());
 
         // Record selection in history
         self.record_selection_history(&context, &enhanced_selection)
             .await;
+        self.last_explanation = Some(self.explain(&context, &local_health, &enhanced_selection));
 
         // Update current provider
         self.current_provider = Some(enhanced_selection.selection.provider_name.clone());
@@ -241,28 +321,204 @@ impl EnhancedProviderSelector {
         })
     }
 
-    /// Check for seamless switching opportunities
+    /// Explanation of the most recent [`Self::select_provider_enhanced`]
+    /// decision: every configured provider with its health, score and why it
+    /// lost, and the reasoning behind the winner
+    pub fn explain_last_selection(&self) -> Option<&SelectionExplanation> {
+        self.last_explanation.as_ref()
+    }
+
+    /// Build the explanation for a completed selection
+    fn explain(
+        &self,
+        context: &SelectionContext,
+        local_health: &[(String, ProviderHealthStatus)],
+        selection: &EnhancedProviderSelection,
+    ) -> SelectionExplanation {
+        let base_config = &self.enhanced_config.base_config;
+        let decision = &selection.enhanced_decision;
+        let chosen_provider = selection.selection.provider_name.as_str();
+
+        let mut providers: Vec<_> = local_health
+            .iter()
+            .map(|(name, status)| (name.clone(), Some(status.clone())))
+            .collect();
+        let mut unchecked: Vec<_> = self
+            .local_config
+            .providers
+            .keys()
+            .filter(|name| !local_health.iter().any(|(checked, _)| checked == *name))
+            .map(|name| (name.clone(), None))
+            .collect();
+        unchecked.sort_by(|(a, _), (b, _)| a.cmp(b));
+        providers.extend(unchecked);
+        providers.extend(
+            base_config
+                .cloud_providers
+                .iter()
+                .map(|provider| (format!("cloud:{provider}"), None)),
+        );
+
+        let candidates: Vec<_> = providers
+            .into_iter()
+            .map(|(name, status)| {
+                let score = self.enhanced_engine.provider_score(&name, status.as_ref());
+                let rejection = decision
+                    .alternatives
+                    .iter()
+                    .find(|alternative| alternative.provider_name == name)
+                    .map(|alternative| alternative.rejection_reason.clone());
+                CandidateExplanation::new(
+                    name,
+                    status,
+                    Some(score),
+                    Some(chosen_provider),
+                    rejection,
+                )
+            })
+            .collect();
+
+        let stages = vec![
+            DecisionStage {
+                id: "strategy".to_string(),
+                name: "Strategy".to_string(),
+                details: vec![
+                    format!("{:?}", base_config.strategy),
+                    format!("consecutive failures: {}", context.consecutive_failures),
+                ],
+                passed: true,
+            },
+            DecisionStage {
+                id: "health".to_string(),
+                name: "Health checks".to_string(),
+                details: local_health
+                    .iter()
+                    .map(|(name, status)| format!("{name}: {}", status.label()))
+                    .collect(),
+                passed: local_health.iter().any(|(_, status)| status.is_usable()),
+            },
+            DecisionStage {
+                id: "scores".to_string(),
+                name: "Performance scores".to_string(),
+                details: candidates
+                    .iter()
+                    .map(|candidate| {
+                        format!(
+                            "{}: {:.2}",
+                            candidate.provider_name,
+                            candidate.score.unwrap_or_default()
+                        )
+                    })
+                    .collect(),
+                passed: true,
+            },
+            DecisionStage {
+                id: "reasoning".to_string(),
+                name: "Reasoning".to_string(),
+                details: decision.reasoning.clone(),
+                passed: true,
+            },
+        ];
+
+        SelectionExplanation {
+            model_id: context.model_id.clone(),
+            strategy: base_config.strategy.clone(),
+            stages,
+            candidates,
+            chosen_provider: Some(chosen_provider.to_string()),
+            reason: selection.selection.reason.clone(),
+        }
+    }
+
+    /// Whether a local provider lists `model_id` among its preferred models,
+    /// or lists none at all
+    fn provider_supports_model(&self, provider_name: &str, model_id: &str) -> bool {
+        let Some(provider_config) = self.local_config.providers.get(provider_name) else {
+            return true;
+        };
+        let aliases = self.local_config.alias_resolver();
+        provider_config.preferred_models.is_empty()
+            || provider_config
+                .preferred_models
+                .iter()
+                .any(|preferred| aliases.matches(model_id, preferred))
+    }
+
+    /// Keep using the current provider while its metrics still meet the
+    /// seamless switching thresholds, so the user sees no switch at all.
+    /// A local provider must also still be usable and serve the requested
+    /// model. Returns `None` to run normal selection otherwise.
     async fn check_seamless_switching(
         &self,
-        _context: &SelectionContext,
+        context: &SelectionContext,
     ) -> Option<EnhancedProviderSelection> {
-        if let Some(ref current) = self.current_provider {
-            // Check if current provider is still optimal
-            if let Some(metrics) = self.provider_metrics.get(current) {
-                if metrics.is_performing_well(0.8, Duration::from_secs(5)) {
-                    // Current provider is still good, continue using it
-                    debug!(
-                        "Seamless switching: continuing with current provider {}",
-                        current
-                    );
-
-                    // Create a simple selection to continue with current provider
-                    // This would be implemented based on the actual selection logic
-                    return None; // Simplified for now
-                }
+        let current = self.current_provider.as_ref()?;
+        let metrics = self.provider_metrics.get(current)?;
+        if !metrics.is_performing_well(SEAMLESS_MIN_SUCCESS_RATE, SEAMLESS_MAX_RESPONSE_TIME) {
+            return None;
+        }
+        if !current.starts_with("cloud:") {
+            let status = self.health_monitor.get_provider_health(current).await?;
+            if !status.is_usable() || !self.provider_supports_model(current, &context.model_id) {
+                return None;
             }
         }
-        None
+
+        debug!(
+            provider = %current,
+            success_rate = metrics.success_rate(),
+            avg_response_time_ms = metrics.avg_response_time.as_millis(),
+            "Seamless switching: continuing with current provider"
+        );
+
+        let reason = "Current provider still performing well".to_string();
+        let (decision, is_fallback) = match current.strip_prefix("cloud:") {
+            Some(provider_name) => (
+                FallbackDecision::UseCloud {
+                    provider_name: provider_name.to_string(),
+                    reason: reason.clone(),
+                    local_status: None,
+                },
+                true,
+            ),
+            None => (
+                FallbackDecision::UseLocal {
+                    provider_name: current.clone(),
+                    reason: reason.clone(),
+                    model_override: None,
+                },
+                false,
+            ),
+        };
+        let enhanced_decision = EnhancedFallbackDecision {
+            decision,
+            confidence: metrics.success_rate(),
+            reasoning: vec![reason.clone()],
+            alternatives: Vec::new(),
+            cost_impact: None,
+            performance_prediction: None,
+            model_override: None,
+        };
+        let recommendation_strength = self
+            .calculate_recommendation_strength(&enhanced_decision, context)
+            .await;
+
+        Some(EnhancedProviderSelection {
+            selection: ProviderSelection {
+                provider_name: current.clone(),
+                provider_type: metrics.provider_type.clone(),
+                reason,
+                is_fallback,
+                local_health: None,
+                model_override: None,
+                request_id: None,
+            },
+            enhanced_decision,
+            recommendation_strength,
+            user_notification: None,
+            suggested_alternatives: Vec::new(),
+            learning_insights: Vec::new(),
+        })
     }
 
     /// Convert enhanced decision to enhanced selection
@@ -274,33 +530,42 @@ impl EnhancedProviderSelector {
     ) -> Result<EnhancedProviderSelection> {
         // Convert base decision to provider selection
         let selection = match &enhanced_decision.decision {
-            FallbackDecision::UseLocal { provider_name, reason } => ProviderSelection {
-                provider_name: provider_name.clone(),
-                provider_type: ProviderType::Local,
-                reason: reason.clone(),
-                is_fallback: false,
-                local_health: Some(local_health.iter().cloned().collect()),
-            },
+            FallbackDecision::UseLocal { provider_name, reason, model_override } => {
+                ProviderSelection {
+                    provider_name: provider_name.clone(),
+                    provider_type: ProviderType::Local,
+                    reason: reason.clone(),
+                    is_fallback: false,
+                    local_health: Some(local_health.iter().cloned().collect()),
+                    model_override: enhanced_decision
+                        .model_override
+                        .clone()
+                        .or_else(|| model_override.clone()),
+                    request_id: None,
+                }
+            }
             FallbackDecision::UseCloud { provider_name, reason, .. } => ProviderSelection {
                 provider_name: format!("cloud:{provider_name}"),
                 provider_type: ProviderType::Cloud,
                 reason: reason.clone(),
                 is_fallback: true,
                 local_health: Some(local_health.iter().cloned().collect()),
+                model_override: enhanced_decision.model_override.clone(),
+                request_id: None,
             },
             FallbackDecision::RequireManual { reason, available_options } => {
-                return Err(anyhow::anyhow!(
-                    "Manual provider selection required: {}. Available options: {:?}",
-                    reason,
-                    available_options
-                ));
+                return Err(SelectionError::ManualRequired {
+                    reason: reason.clone(),
+                    options: available_options.clone(),
+                }
+                .into());
             }
             FallbackDecision::NoProvider { reason, attempted_providers } => {
-                return Err(anyhow::anyhow!(
-                    "No suitable provider available: {}. Attempted: {:?}",
-                    reason,
-                    attempted_providers
-                ));
+                return Err(SelectionError::NoProvider {
+                    reason: reason.clone(),
+                    attempted: attempted_providers.clone(),
+                }
+                .into());
             }
         };
 
@@ -334,7 +599,7 @@ impl EnhancedProviderSelector {
     async fn calculate_recommendation_strength(
         &self,
         enhanced_decision: &EnhancedFallbackDecision,
-        _context: &SelectionContext,
+        context: &SelectionContext,
     ) -> f64 {
         let mut strength = enhanced_decision.confidence;
 
@@ -355,8 +620,55 @@ impl EnhancedProviderSelector {
             }
         }
 
-        // Cap at 1.0
-        strength.min(1.0)
+        // Adjust by how this provider actually did for the model recently
+        if let Some(history) = enhanced_decision
+            .decision
+            .provider_name()
+            .and_then(|provider_name| self.outcome_history(provider_name, &context.model_id))
+        {
+            strength += history.weight * (history.success_rate - 0.5) * 0.4;
+            if history.median_response_time < Duration::from_secs(2) {
+                strength += history.weight * 0.05;
+            } else if history.median_response_time > Duration::from_secs(10) {
+                strength -= history.weight * 0.1;
+            }
+        }
+
+        strength.clamp(0.0, 1.0)
+    }
+
+    /// Realized success rate and median response time of the most recent
+    /// recorded outcomes for `provider_name` serving `model_id`, or `None`
+    /// without any
+    fn outcome_history(&self, provider_name: &str, model_id: &str) -> Option<OutcomeHistory> {
+        let outcomes: Vec<_> = self
+            .selection_history
+            .iter()
+            .rev()
+            .filter(|entry| {
+                entry.context.model_id == model_id
+                    && entry.decision.decision.provider_name() == Some(provider_name)
+            })
+            .filter_map(|entry| entry.outcome.as_ref())
+            .take(OUTCOME_WINDOW)
+            .collect();
+        if outcomes.is_empty() {
+            return None;
+        }
+
+        let successes = outcomes.iter().filter(|outcome| outcome.success).count();
+        let mut response_times: Vec<_> = outcomes
+            .iter()
+            .map(|outcome| outcome.response_time)
+            .collect();
+        response_times.sort();
+
+        Some(OutcomeHistory {
+            success_rate: successes as f64 / outcomes.len() as f64,
+            median_response_time: response_times[response_times.len() / 2],
+            weight: outcomes.len().min(OUTCOME_FULL_WEIGHT_SAMPLES) as f64
+                / OUTCOME_FULL_WEIGHT_SAMPLES as f64,
+        })
     }
 
     /// Generate learning insights
@@ -405,6 +717,7 @@ impl EnhancedProviderSelector {
         let entry = SelectionHistoryEntry {
             timestamp: Instant::now(),
             context: context.clone(),
+            request_id: selection.selection.request_id.clone(),
             decision: selection.enhanced_decision.clone(),
             outcome: None, // Will be updated when outcome is known
         };
@@ -476,6 +789,7 @@ impl EnhancedProviderSelector {
         context: &SelectionContext,
         response_time: Duration,
         quality_score: Option<f64>,
+        token_usage: Option<TokenUsage>,
     ) {
         // Record in base metrics
         if let Some(metrics) = self.provider_metrics.get_mut(provider_name) {
@@ -493,28 +807,35 @@ impl EnhancedProviderSelector {
         }
 
         // Record in enhanced engine for pattern learning
-        let fallback_context = FallbackContext::new(context.model_id.clone())
-            .with_streaming(context.requires_streaming)
-            .with_tools(context.requires_tools)
-            .with_previous_provider(context.previous_provider.clone().unwrap_or_default())
-            .with_consecutive_failures(context.consecutive_failures);
+        let fallback_context = outcome_context(context);
 
         self.enhanced_engine
-            .record_usage(provider_name, &fallback_context, true, response_time)
-            .await;
-
-        // Update selection history outcome
-        if let Some(last_entry) = self.selection_history.last_mut() {
-            if last_entry.outcome.is_none() {
-                last_entry.outcome = Some(SelectionOutcome {
+            .record_outcome(
+                provider_name,
+                &fallback_context,
+                RequestOutcome {
                     success: true,
                     response_time,
-                    user_satisfaction: None,
+                    token_usage,
                     quality_score,
-                    error_message: None,
-                });
-            }
-        }
+                    user_satisfaction: None,
+                },
+            )
+            .await;
+
+        // Update selection history outcome
+        self.attach_outcome(
+            provider_name,
+            None,
+            SelectionOutcome {
+                success: true,
+                response_time,
+                user_satisfaction: None,
+                quality_score,
+                token_usage,
+                error_message: None,
+            },
+        );
 
         debug!(
             provider = provider_name,
@@ -531,6 +852,7 @@ impl EnhancedProviderSelector {
         context: &SelectionContext,
         error: &str,
         response_time: Option<Duration>,
+        token_usage: Option<TokenUsage>,
     ) {
         // Record in base metrics
         if let Some(metrics) = self.provider_metrics.get_mut(provider_name) {
@@ -539,42 +861,121 @@ impl EnhancedProviderSelector {
         }
 
         // Record in enhanced engine for pattern learning
-        let fallback_context = FallbackContext::new(context.model_id.clone())
-            .with_streaming(context.requires_streaming)
-            .with_tools(context.requires_tools)
-            .with_previous_provider(context.previous_provider.clone().unwrap_or_default())
-            .with_consecutive_failures(context.consecutive_failures);
+        let fallback_context = outcome_context(context);
+        let response_time = response_time.unwrap_or(Duration::from_secs(30));
 
         self.enhanced_engine
-            .record_usage(
+            .record_outcome(
                 provider_name,
                 &fallback_context,
-                false,
-                response_time.unwrap_or(Duration::from_secs(30)),
+                RequestOutcome {
+                    success: false,
+                    response_time,
+                    token_usage,
+                    quality_score: None,
+                    user_satisfaction: None,
+                },
             )
             .await;
 
         // Update selection history outcome
-        if let Some(last_entry) = self.selection_history.last_mut() {
-            if last_entry.outcome.is_none() {
-                last_entry.outcome = Some(SelectionOutcome {
-                    success: false,
-                    response_time: response_time.unwrap_or(Duration::from_secs(30)),
-                    user_satisfaction: None,
-                    quality_score: None,
-                    error_message: Some(error.to_string()),
-                });
-            }
-        }
+        self.attach_outcome(
+            provider_name,
+            None,
+            SelectionOutcome {
+                success: false,
+                response_time,
+                user_satisfaction: None,
+                quality_score: None,
+                token_usage,
+                error_message: Some(error.to_string()),
+            },
+        );
 
         warn!(
             provider = provider_name,
             error = error,
-            response_time_ms = response_time.map(|d| d.as_millis()),
+            response_time_ms = response_time.as_millis(),
             "Enhanced failure recording completed"
         );
     }
 
+    /// Record the full outcome of a selection, feeding latency, quality and
+    /// user satisfaction back into the engine's performance scores. The
+    /// outcome is kept with the selection of `provider_name` made for
+    /// `request_id`, or its latest one without an outcome when no request id
+    /// is given.
+    pub async fn record_outcome(
+        &mut self,
+        provider_name: &str,
+        request_id: Option<&str>,
+        context: &SelectionContext,
+        outcome: SelectionOutcome,
+    ) {
+        if let Some(metrics) = self.provider_metrics.get_mut(provider_name) {
+            metrics.total_requests += 1;
+            if outcome.success {
+                metrics.successful_requests += 1;
+            }
+            metrics.last_request_time = Some(Instant::now());
+        }
+
+        let fallback_context = outcome_context(context);
+
+        self.enhanced_engine
+            .record_outcome(
+                provider_name,
+                &fallback_context,
+                RequestOutcome {
+                    success: outcome.success,
+                    response_time: outcome.response_time,
+                    token_usage: outcome.token_usage,
+                    quality_score: outcome.quality_score,
+                    user_satisfaction: outcome.user_satisfaction,
+                },
+            )
+            .await;
+
+        debug!(
+            provider = provider_name,
+            success = outcome.success,
+            quality_score = ?outcome.quality_score,
+            user_satisfaction = ?outcome.user_satisfaction,
+            "Selection outcome recorded"
+        );
+
+        self.attach_outcome(provider_name, request_id, outcome);
+    }
+
+    /// Attach `outcome` to the latest selection of `provider_name`, made for
+    /// `request_id` when given, that has no outcome yet. Selections of other
+    /// providers or requests in between keep theirs.
+    fn attach_outcome(
+        &mut self,
+        provider_name: &str,
+        request_id: Option<&str>,
+        outcome: SelectionOutcome,
+    ) {
+        // Decisions name cloud providers without the `cloud:` prefix
+        let decided_name = provider_name
+            .strip_prefix("cloud:")
+            .unwrap_or(provider_name);
+        let entry = self.selection_history.iter_mut().rev().find(|entry| {
+            entry.outcome.is_none()
+                && entry.decision.decision.provider_name() == Some(decided_name)
+                && request_id.is_none_or(|id| entry.request_id.as_deref() == Some(id))
+        });
+        if let Some(entry) = entry {
+            entry.outcome = Some(outcome);
+        }
+    }
+
+    /// Rank providers using current health and learned outcomes, best first
+    pub async fn get_provider_ranking(&self) -> Vec<(String, f64)> {
+        let local_health = self.health_monitor.get_providers_by_health().await;
+        self.enhanced_engine.rank_providers(&local_health).await
+    }
+
     /// Record user feedback for learning
     pub async fn record_user_feedback(&mut self, feedback: UserFeedback) {
         info!(
@@ -584,24 +985,41 @@ impl EnhancedProviderSelector {
             "Recording user feedback"
         );
 
-        self.user_feedback.insert(
-            format!(
-                "{}_{}",
-                feedback.provider_name,
-                feedback.timestamp.elapsed().as_secs()
-            ),
-            feedback,
-        );
+        self.user_feedback.insert(self.next_feedback_id, feedback);
+        self.next_feedback_id += 1;
 
-        // Keep only recent feedback (last 100 entries)
-        if self.user_feedback.len() > 100 {
-            // Remove oldest entry
-            if let Some(oldest_key) = self.user_feedback.keys().next().cloned() {
-                self.user_feedback.remove(&oldest_key);
-            }
+        // Keep only recent feedback
+        if self.user_feedback.len() > MAX_USER_FEEDBACK {
+            self.user_feedback.pop_first();
         }
     }
 
+    /// Average user rating for `provider_name` scaled to 0.0-1.0, or `None`
+    /// without any feedback. Recent ratings and those about the response
+    /// itself count for more.
+    pub fn feedback_score(&self, provider_name: &str) -> Option<f64> {
+        self.feedback_score_at(provider_name, Instant::now())
+    }
+
+    fn feedback_score_at(&self, provider_name: &str, now: Instant) -> Option<f64> {
+        let (weighted_sum, total_weight) = self
+            .user_feedback
+            .values()
+            .filter(|feedback| feedback.provider_name == provider_name)
+            .map(|feedback| {
+                let age = now.saturating_duration_since(feedback.timestamp);
+                let recency = 0.5_f64.powf(age.as_secs_f64() / FEEDBACK_HALF_LIFE.as_secs_f64());
+                let weight = recency * feedback.feedback_type.weight();
+                let score = (feedback.rating.clamp(1, 5) - 1) as f64 / 4.0;
+                (score * weight, weight)
+            })
+            .fold((0.0, 0.0), |(sum, total), (score, weight)| {
+                (sum + score, total + weight)
+            });
+
+        (total_weight > 0.0).then(|| weighted_sum / total_weight)
+    }
+
     /// Get smart retry configuration
     pub fn get_smart_retry_config(&self) -> SmartRetryConfig {
         SmartRetryConfig {
@@ -628,18 +1046,18 @@ impl EnhancedProviderSelector {
             }
         }
 
-        // Sort by success rate and response time
+        // Sort by success rate, response time and user feedback
+        let score = |provider_name: &str| {
+            let metrics = &self.provider_metrics[provider_name];
+            let feedback = self
+                .feedback_score(provider_name)
+                .map_or(0.0, |feedback| (feedback - 0.5) * FEEDBACK_WEIGHT);
+            metrics.success_rate() - (metrics.avg_response_time.as_millis() as f64 / 10000.0)
+                + feedback
+        };
         recommendations.sort_by(|a, b| {
-            let metrics_a = self.provider_metrics.get(a).unwrap();
-            let metrics_b = self.provider_metrics.get(b).unwrap();
-
-            let score_a = metrics_a.success_rate()
-                - (metrics_a.avg_response_time.as_millis() as f64 / 10000.0);
-            let score_b = metrics_b.success_rate()
-                - (metrics_b.avg_response_time.as_millis() as f64 / 10000.0);
-
-            score_b
-                .partial_cmp(&score_a)
+            score(b)
+                .partial_cmp(&score(a))
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
 
@@ -701,6 +1119,15 @@ impl EnhancedProviderSelector {
     }
 }
 
+/// Summary of a provider's recent outcomes for one model
+struct OutcomeHistory {
+    success_rate: f64,
+    median_response_time: Duration,
+    /// How much the history counts, growing from 0.0 to 1.0 with the number
+    /// of outcomes
+    weight: f64,
+}
+
 impl Default for SmartRetryConfig {
     fn default() -> Self {
         Self {
@@ -713,12 +1140,27 @@ impl Default for SmartRetryConfig {
     }
 }
 
+/// Fallback context for recording the outcome of a request made for
+/// `context`, carrying its idempotency key so retries are charged once
+fn outcome_context(context: &SelectionContext) -> FallbackContext {
+    let fallback_context = FallbackContext::new(context.model_id.clone())
+        .with_streaming(context.requires_streaming)
+        .with_tools(context.requires_tools)
+        .with_previous_provider(context.previous_provider.clone().unwrap_or_default())
+        .with_consecutive_failures(context.consecutive_failures);
+    match &context.idempotency_key {
+        Some(key) => fallback_context.with_idempotency_key(key.clone()),
+        None => fallback_context,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
-    use crate::config::local_ai::LocalAiConfig;
+    use crate::config::local_ai::{LocalAiConfig, LocalProviderConfig};
+    use crate::idempotency::IdempotencyKey;
 
     #[tokio::test]
     async fn test_enhanced_provider_selector_creation() {
@@ -745,6 +1187,7 @@ mod tests {
             response_time: Duration::from_millis(500),
             user_satisfaction: Some(0.9),
             quality_score: Some(0.85),
+            token_usage: None,
             error_message: None,
         };
 
@@ -752,4 +1195,533 @@ mod tests {
         assert_eq!(outcome.response_time, Duration::from_millis(500));
         assert_eq!(outcome.user_satisfaction, Some(0.9));
     }
+
+    #[tokio::test]
+    async fn test_recorded_outcomes_drive_ranking() {
+        let mut fixture =
+            EnhancedProviderSelector::new(LocalAiConfig::new(), EnhancedFallbackConfig::default())
+                .await
+                .unwrap();
+        let context = SelectionContext::new("llama3.2".to_string());
+        let local_health = vec![
+            (
+                "good".to_string(),
+                ProviderHealthStatus::Healthy {
+                    response_time: Duration::from_millis(100),
+                    models_available: 1,
+                    additional_info: None,
+                },
+            ),
+            (
+                "poor".to_string(),
+                ProviderHealthStatus::Healthy {
+                    response_time: Duration::from_millis(100),
+                    models_available: 1,
+                    additional_info: None,
+                },
+            ),
+        ];
+
+        for _ in 0..5 {
+            fixture
+                .record_outcome(
+                    "good",
+                    None,
+                    &context,
+                    SelectionOutcome {
+                        success: true,
+                        response_time: Duration::from_millis(400),
+                        user_satisfaction: Some(0.9),
+                        quality_score: Some(0.9),
+                        token_usage: None,
+                        error_message: None,
+                    },
+                )
+                .await;
+            fixture
+                .record_outcome(
+                    "poor",
+                    None,
+                    &context,
+                    SelectionOutcome {
+                        success: false,
+                        response_time: Duration::from_secs(20),
+                        user_satisfaction: Some(0.1),
+                        quality_score: Some(0.2),
+                        token_usage: None,
+                        error_message: Some("timeout".to_string()),
+                    },
+                )
+                .await;
+        }
+
+        let actual = fixture.enhanced_engine.rank_providers(&local_health).await;
+
+        let expected = vec!["good".to_string(), "poor".to_string()];
+        assert_eq!(
+            actual
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_explain_last_selection_lists_all_providers() {
+        let mut local_config = LocalAiConfig::new();
+        for name in ["gpu-a", "gpu-b"] {
+            local_config.providers.insert(
+                name.to_string(),
+                LocalProviderConfig::default().preferred_models(Vec::<String>::new()),
+            );
+        }
+        let mut fixture =
+            EnhancedProviderSelector::new(local_config, EnhancedFallbackConfig::default())
+                .await
+                .unwrap();
+        fixture
+            .health_monitor
+            .set_provider_status(
+                "gpu-a",
+                ProviderHealthStatus::Unhealthy {
+                    reason: "Connection refused".to_string(),
+                    response_time: Duration::from_millis(0),
+                },
+            )
+            .await;
+        fixture
+            .health_monitor
+            .set_provider_status(
+                "gpu-b",
+                ProviderHealthStatus::Healthy {
+                    response_time: Duration::from_millis(100),
+                    models_available: 1,
+                    additional_info: None,
+                },
+            )
+            .await;
+
+        let selection = fixture
+            .select_provider_enhanced(SelectionContext::new("llama3.2".to_string()))
+            .await
+            .unwrap();
+        let actual = fixture.explain_last_selection().unwrap();
+
+        let candidates: Vec<_> = actual
+            .candidates
+            .iter()
+            .map(|candidate| {
+                (
+                    candidate.provider_name.as_str(),
+                    candidate.elimination_reason.is_none(),
+                )
+            })
+            .collect();
+        let expected = vec![
+            ("gpu-b", true),
+            ("gpu-a", false),
+            ("cloud:openai", false),
+            ("cloud:anthropic", false),
+        ];
+        assert_eq!(candidates, expected);
+        assert!(actual
+            .candidates
+            .iter()
+            .all(|candidate| candidate.score.is_some()));
+        assert_eq!(
+            actual.chosen_provider.as_deref(),
+            Some(selection.selection.provider_name.as_str())
+        );
+        assert_eq!(
+            actual.candidates[1].elimination_reason.as_deref(),
+            Some("Lower performance score: 0.60")
+        );
+    }
+
+    fn decision_fixture(provider_name: &str) -> EnhancedFallbackDecision {
+        EnhancedFallbackDecision {
+            decision: FallbackDecision::UseLocal {
+                provider_name: provider_name.to_string(),
+                reason: "Local provider available and healthy".to_string(),
+                model_override: None,
+            },
+            confidence: 0.7,
+            reasoning: Vec::new(),
+            alternatives: Vec::new(),
+            cost_impact: None,
+            performance_prediction: None,
+            model_override: None,
+        }
+    }
+
+    fn seed_history(
+        fixture: &mut EnhancedProviderSelector,
+        provider_name: &str,
+        model_id: &str,
+        success: bool,
+        response_time: Duration,
+    ) {
+        for _ in 0..5 {
+            fixture.selection_history.push(SelectionHistoryEntry {
+                timestamp: Instant::now(),
+                context: SelectionContext::new(model_id.to_string()),
+                request_id: None,
+                decision: decision_fixture(provider_name),
+                outcome: Some(SelectionOutcome {
+                    success,
+                    response_time,
+                    user_satisfaction: None,
+                    quality_score: None,
+                    token_usage: None,
+                    error_message: (!success).then(|| "timeout".to_string()),
+                }),
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recommendation_strength_follows_outcome_history() {
+        let mut fixture =
+            EnhancedProviderSelector::new(LocalAiConfig::new(), EnhancedFallbackConfig::default())
+                .await
+                .unwrap();
+        seed_history(
+            &mut fixture,
+            "good",
+            "llama3.2",
+            true,
+            Duration::from_millis(400),
+        );
+        seed_history(
+            &mut fixture,
+            "poor",
+            "llama3.2",
+            false,
+            Duration::from_secs(20),
+        );
+        let context = SelectionContext::new("llama3.2".to_string());
+
+        let good = fixture
+            .calculate_recommendation_strength(&decision_fixture("good"), &context)
+            .await;
+        let unknown = fixture
+            .calculate_recommendation_strength(&decision_fixture("unknown"), &context)
+            .await;
+        let poor = fixture
+            .calculate_recommendation_strength(&decision_fixture("poor"), &context)
+            .await;
+
+        assert!(good > unknown, "{good} should exceed {unknown}");
+        assert!(unknown > poor, "{unknown} should exceed {poor}");
+        assert_eq!(unknown, 0.7);
+    }
+
+    #[tokio::test]
+    async fn test_outcome_attaches_to_matching_selection() {
+        let mut fixture =
+            EnhancedProviderSelector::new(LocalAiConfig::new(), EnhancedFallbackConfig::default())
+                .await
+                .unwrap();
+        for (provider_name, request_id) in [
+            ("ollama", "req-1"),
+            ("ollama", "req-2"),
+            ("lmstudio", "req-3"),
+        ] {
+            fixture.selection_history.push(SelectionHistoryEntry {
+                timestamp: Instant::now(),
+                context: SelectionContext::new("llama3.2".to_string()),
+                request_id: Some(request_id.to_string()),
+                decision: decision_fixture(provider_name),
+                outcome: None,
+            });
+        }
+        let context = SelectionContext::new("llama3.2".to_string());
+        let outcome = |success| SelectionOutcome {
+            success,
+            response_time: Duration::from_millis(400),
+            user_satisfaction: None,
+            quality_score: None,
+            token_usage: None,
+            error_message: None,
+        };
+
+        fixture
+            .record_outcome("ollama", Some("req-1"), &context, outcome(false))
+            .await;
+        fixture
+            .record_outcome("ollama", None, &context, outcome(true))
+            .await;
+
+        let actual: Vec<_> = fixture
+            .selection_history
+            .iter()
+            .map(|entry| entry.outcome.as_ref().map(|outcome| outcome.success))
+            .collect();
+
+        let expected = vec![Some(false), Some(true), None];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_retried_outcomes_are_charged_once() {
+        let mut fixture =
+            EnhancedProviderSelector::new(LocalAiConfig::new(), EnhancedFallbackConfig::default())
+                .await
+                .unwrap();
+        let context = SelectionContext::new("gpt-4o".to_string())
+            .with_idempotency_key(IdempotencyKey::new("forge-retry"));
+
+        for _ in 0..2 {
+            fixture
+                .record_success_enhanced(
+                    "cloud:openai",
+                    &context,
+                    Duration::from_secs(1),
+                    None,
+                    None,
+                )
+                .await;
+        }
+
+        let actual = fixture.enhanced_engine.daily_cost();
+        let expected = 0.002;
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_recommendation_strength_ignores_other_model_history() {
+        let mut fixture =
+            EnhancedProviderSelector::new(LocalAiConfig::new(), EnhancedFallbackConfig::default())
+                .await
+                .unwrap();
+        seed_history(
+            &mut fixture,
+            "poor",
+            "codellama",
+            false,
+            Duration::from_secs(20),
+        );
+        let context = SelectionContext::new("llama3.2".to_string());
+
+        let actual = fixture
+            .calculate_recommendation_strength(&decision_fixture("poor"), &context)
+            .await;
+
+        assert_eq!(actual, 0.7);
+    }
+
+    fn feedback_fixture(
+        provider_name: &str,
+        feedback_type: FeedbackType,
+        rating: u8,
+        timestamp: Instant,
+    ) -> UserFeedback {
+        UserFeedback {
+            provider_name: provider_name.to_string(),
+            feedback_type,
+            rating,
+            comments: None,
+            timestamp,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_feedback_score_averages_weighted_ratings() {
+        let mut fixture =
+            EnhancedProviderSelector::new(LocalAiConfig::new(), EnhancedFallbackConfig::default())
+                .await
+                .unwrap();
+        let start = Instant::now();
+        for (feedback_type, rating) in [
+            (FeedbackType::Quality, 5),
+            (FeedbackType::Satisfaction, 3),
+            (FeedbackType::Cost, 1),
+        ] {
+            fixture
+                .record_user_feedback(feedback_fixture("ollama", feedback_type, rating, start))
+                .await;
+        }
+        fixture
+            .record_user_feedback(feedback_fixture("other", FeedbackType::Quality, 1, start))
+            .await;
+
+        let actual = fixture.feedback_score_at("ollama", start);
+        let aged = fixture.feedback_score_at("ollama", start + FEEDBACK_HALF_LIFE);
+
+        // (1.0 + 0.5 + 0.0 * 0.5) / 2.5; ageing every rating equally changes nothing
+        assert_eq!(actual, Some(0.6));
+        assert_eq!(aged, Some(0.6));
+        assert_eq!(fixture.feedback_score_at("missing", start), None);
+        assert_eq!(fixture.user_feedback.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_recent_feedback_outweighs_old_feedback() {
+        let mut fixture =
+            EnhancedProviderSelector::new(LocalAiConfig::new(), EnhancedFallbackConfig::default())
+                .await
+                .unwrap();
+        let start = Instant::now();
+        fixture
+            .record_user_feedback(feedback_fixture("ollama", FeedbackType::Quality, 1, start))
+            .await;
+        fixture
+            .record_user_feedback(feedback_fixture(
+                "ollama",
+                FeedbackType::Quality,
+                5,
+                start + FEEDBACK_HALF_LIFE,
+            ))
+            .await;
+
+        let actual = fixture.feedback_score_at("ollama", start + FEEDBACK_HALF_LIFE);
+
+        // Weights 0.5 and 1.0
+        assert_eq!(actual, Some(1.0 / 1.5));
+    }
+
+    #[tokio::test]
+    async fn test_feedback_reorders_recommendations() {
+        let mut fixture =
+            EnhancedProviderSelector::new(LocalAiConfig::new(), EnhancedFallbackConfig::default())
+                .await
+                .unwrap();
+        for (name, millis) in [("fast", 100), ("slow", 300)] {
+            let mut metrics = ProviderMetrics::new(ProviderType::Local);
+            metrics.total_requests = 20;
+            metrics.successful_requests = 20;
+            metrics.avg_response_time = Duration::from_millis(millis);
+            fixture.provider_metrics.insert(name.to_string(), metrics);
+        }
+        let context = SelectionContext::new("llama3.2".to_string());
+        let before = fixture.get_provider_recommendations(&context).await;
+
+        let now = Instant::now();
+        fixture
+            .record_user_feedback(feedback_fixture("fast", FeedbackType::Quality, 1, now))
+            .await;
+        fixture
+            .record_user_feedback(feedback_fixture("slow", FeedbackType::Quality, 5, now))
+            .await;
+        let actual = fixture.get_provider_recommendations(&context).await;
+
+        assert_eq!(before, vec!["fast".to_string(), "slow".to_string()]);
+        assert_eq!(actual, vec!["slow".to_string(), "fast".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_feedback_evicts_oldest_beyond_capacity() {
+        let mut fixture =
+            EnhancedProviderSelector::new(LocalAiConfig::new(), EnhancedFallbackConfig::default())
+                .await
+                .unwrap();
+        let now = Instant::now();
+        for index in 0..150 {
+            let feedback = UserFeedback {
+                comments: Some(index.to_string()),
+                ..feedback_fixture("ollama", FeedbackType::Quality, 5, now)
+            };
+            fixture.record_user_feedback(feedback).await;
+        }
+
+        let actual: Vec<_> = fixture
+            .user_feedback
+            .values()
+            .filter_map(|feedback| feedback.comments.clone())
+            .collect();
+
+        let expected: Vec<_> = (50..150).map(|index: u32| index.to_string()).collect();
+        assert_eq!(actual, expected);
+    }
+
+    async fn seamless_fixture(successful_requests: u64) -> EnhancedProviderSelector {
+        let mut fixture =
+            EnhancedProviderSelector::new(LocalAiConfig::new(), EnhancedFallbackConfig::default())
+                .await
+                .unwrap();
+        let mut metrics = ProviderMetrics::new(ProviderType::Local);
+        metrics.total_requests = 20;
+        metrics.successful_requests = successful_requests;
+        metrics.avg_response_time = Duration::from_millis(200);
+        fixture
+            .provider_metrics
+            .insert("ollama".to_string(), metrics);
+        fixture.current_provider = Some("ollama".to_string());
+        fixture
+            .health_monitor
+            .set_provider_status(
+                "ollama",
+                ProviderHealthStatus::Healthy {
+                    response_time: Duration::from_millis(100),
+                    models_available: 1,
+                    additional_info: None,
+                },
+            )
+            .await;
+        fixture
+    }
+
+    #[tokio::test]
+    async fn test_seamless_switching_keeps_healthy_current_provider() {
+        let mut fixture = seamless_fixture(20).await;
+
+        let actual = fixture
+            .select_provider_enhanced(SelectionContext::new("llama3.2".to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(actual.selection.provider_name, "ollama");
+        assert_eq!(actual.selection.provider_type, ProviderType::Local);
+        assert_eq!(
+            actual.enhanced_decision.decision.provider_name(),
+            Some("ollama")
+        );
+        assert_eq!(actual.user_notification, None);
+        assert_eq!(fixture.selection_history.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_seamless_switching_yields_when_current_provider_degrades() {
+        let fixture = seamless_fixture(10).await;
+        let context = SelectionContext::new("llama3.2".to_string());
+
+        let actual = fixture.check_seamless_switching(&context).await;
+
+        assert!(actual.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_seamless_switching_yields_when_current_provider_is_unhealthy() {
+        let fixture = seamless_fixture(20).await;
+        fixture
+            .health_monitor
+            .set_provider_status(
+                "ollama",
+                ProviderHealthStatus::Unhealthy {
+                    reason: "Connection refused".to_string(),
+                    response_time: Duration::from_millis(0),
+                },
+            )
+            .await;
+        let context = SelectionContext::new("llama3.2".to_string());
+
+        let actual = fixture.check_seamless_switching(&context).await;
+
+        assert!(actual.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_seamless_switching_yields_when_model_is_not_served() {
+        let mut fixture = seamless_fixture(20).await;
+        fixture.local_config.providers.insert(
+            "ollama".to_string(),
+            LocalProviderConfig::default().preferred_models(vec!["qwen2.5".to_string()]),
+        );
+        let context = SelectionContext::new("llama3.2".to_string());
+
+        let actual = fixture.check_seamless_switching(&context).await;
+
+        assert!(actual.is_none());
+    }
 }
@@ -0,0 +1,559 @@
+//! Explanation of provider selection decisions, renderable as diagrams
+
+use std::fmt::Write as _;
+use std::time::Instant;
+
+use crate::config::fallback::{
+    FallbackContext, FallbackDecision, FallbackEngine, FallbackStrategy,
+};
+use crate::config::local_ai::ProviderHealthStatus;
+use crate::selection::{ProviderSelection, ProviderSelector, SelectionContext};
+
+/// A single stage evaluated while selecting a provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionStage {
+    /// Stable identifier for the stage, used as the diagram node id
+    pub id: String,
+    /// Human-readable stage name
+    pub name: String,
+    /// What was evaluated at this stage
+    pub details: Vec<String>,
+    /// Whether the stage left at least one viable candidate
+    pub passed: bool,
+}
+
+/// A provider considered during selection
+#[derive(Debug, Clone, PartialEq)]
+pub struct CandidateExplanation {
+    /// Provider name, with a `cloud:` prefix for cloud providers
+    pub provider_name: String,
+    /// Health status, for local providers that have been checked
+    pub health: Option<ProviderHealthStatus>,
+    /// Performance score, when the selector keeps one
+    pub score: Option<f64>,
+    /// Why the provider was not chosen; `None` for the chosen provider
+    pub elimination_reason: Option<String>,
+}
+
+impl CandidateExplanation {
+    /// Describe `provider_name` relative to the chosen provider. `rejection`
+    /// takes precedence over reasons derived from health.
+    pub(crate) fn new(
+        provider_name: String,
+        health: Option<ProviderHealthStatus>,
+        score: Option<f64>,
+        chosen_provider: Option<&str>,
+        rejection: Option<String>,
+    ) -> Self {
+        let elimination_reason = if chosen_provider == Some(provider_name.as_str()) {
+            None
+        } else {
+            let reason = rejection
+                .or_else(|| {
+                    health
+                        .as_ref()
+                        .filter(|status| !status.is_usable())
+                        .map(|status| format!("Provider is {}", status.label()))
+                })
+                .unwrap_or_else(|| match chosen_provider {
+                    Some(chosen) => format!("Ranked below {chosen}"),
+                    None => "No provider selected".to_string(),
+                });
+            Some(reason)
+        };
+        Self { provider_name, health, score, elimination_reason }
+    }
+}
+
+/// Full explanation of how a provider would be selected for a context
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectionExplanation {
+    /// Model being requested
+    pub model_id: String,
+    /// Fallback strategy in effect
+    pub strategy: FallbackStrategy,
+    /// Evaluated stages in order
+    pub stages: Vec<DecisionStage>,
+    /// Every provider considered, local providers first
+    pub candidates: Vec<CandidateExplanation>,
+    /// Provider that would be chosen, if any
+    pub chosen_provider: Option<String>,
+    /// Reason reported for the final decision
+    pub reason: String,
+}
+
+impl SelectionExplanation {
+    /// Point the explanation at the provider actually selected, which can
+    /// differ from the fallback decision when a forced provider, routing
+    /// rule or return to local picked it first
+    pub(crate) fn adopt(mut self, selection: &ProviderSelection) -> Self {
+        let chosen = selection.provider_name.clone();
+        if self.chosen_provider.as_deref() == Some(chosen.as_str()) {
+            return self;
+        }
+
+        for candidate in &mut self.candidates {
+            if candidate.provider_name == chosen {
+                candidate.elimination_reason = None;
+            } else if matches!(
+                candidate.elimination_reason.as_deref(),
+                None | Some("No provider selected")
+            ) {
+                candidate.elimination_reason = Some(format!("Ranked below {chosen}"));
+            }
+        }
+        self.chosen_provider = Some(chosen);
+        self.reason = selection.reason.clone();
+        self
+    }
+
+    /// Render the decision as a Graphviz DOT digraph
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph selection {\n    rankdir=LR;\n    node [shape=box];\n");
+        let _ = writeln!(
+            dot,
+            "    request [label=\"{}\", shape=ellipse];",
+            dot_label(["Request", self.model_id.as_str()])
+        );
+
+        let mut previous = "request".to_string();
+        for stage in &self.stages {
+            let label = dot_label(
+                std::iter::once(stage.name.as_str())
+                    .chain(stage.details.iter().map(String::as_str)),
+            );
+            let color = if stage.passed { "black" } else { "red" };
+            let _ = writeln!(dot, "    {} [label=\"{label}\", color={color}];", stage.id);
+            let _ = writeln!(dot, "    {previous} -> {};", stage.id);
+            previous = stage.id.clone();
+        }
+
+        match &self.chosen_provider {
+            Some(provider) => {
+                let _ = writeln!(
+                    dot,
+                    "    \"{0}\" [label=\"{0}\", shape=doubleoctagon, color=green];",
+                    escape_dot(provider)
+                );
+                let _ = writeln!(
+                    dot,
+                    "    {previous} -> \"{}\" [label=\"selected\"];",
+                    escape_dot(provider)
+                );
+            }
+            None => {
+                let _ = writeln!(
+                    dot,
+                    "    none [label=\"{}\", shape=octagon, color=red];",
+                    dot_label(["No provider", self.reason.as_str()])
+                );
+                let _ = writeln!(dot, "    {previous} -> none;");
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Render the decision as a Mermaid flowchart
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("flowchart LR\n");
+        let _ = writeln!(
+            mermaid,
+            "    request([\"Request: {}\"])",
+            escape_mermaid(&self.model_id)
+        );
+
+        let mut previous = "request".to_string();
+        for stage in &self.stages {
+            let mut label = stage.name.clone();
+            for detail in &stage.details {
+                label.push_str("<br/>");
+                label.push_str(detail);
+            }
+            let _ = writeln!(mermaid, "    {} [\"{}\"]", stage.id, escape_mermaid(&label));
+            let _ = writeln!(mermaid, "    {previous} --> {}", stage.id);
+            previous = stage.id.clone();
+        }
+
+        match &self.chosen_provider {
+            Some(provider) => {
+                let _ = writeln!(
+                    mermaid,
+                    "    chosen{{{{\"{}\"}}}}",
+                    escape_mermaid(provider)
+                );
+                let _ = writeln!(mermaid, "    {previous} -->|selected| chosen");
+            }
+            None => {
+                let _ = writeln!(
+                    mermaid,
+                    "    none[\"No provider: {}\"]",
+                    escape_mermaid(&self.reason)
+                );
+                let _ = writeln!(mermaid, "    {previous} --> none");
+            }
+        }
+
+        mermaid
+    }
+}
+
+/// Escape backslashes and double quotes so labels stay valid DOT strings
+fn escape_dot(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Escape each line and join them with DOT line breaks
+fn dot_label<'a>(lines: impl IntoIterator<Item = &'a str>) -> String {
+    lines
+        .into_iter()
+        .map(escape_dot)
+        .collect::<Vec<_>>()
+        .join("\\n")
+}
+
+/// Replace double quotes with the Mermaid entity so labels stay valid
+fn escape_mermaid(value: &str) -> String {
+    value.replace('"', "#quot;")
+}
+
+impl ProviderSelector {
+    /// Explain how a provider would be selected for `context` without changing
+    /// selector state. The result can be rendered with
+    /// [`SelectionExplanation::to_dot`] or
+    /// [`SelectionExplanation::to_mermaid`].
+    pub async fn explain_selection(&self, context: &SelectionContext) -> SelectionExplanation {
+        self.explain_with(context, &self.fallback_engine.snapshot())
+            .await
+    }
+
+    /// Explanation of the most recent [`Self::select_provider`] decision:
+    /// every configured provider with its health and why it lost, and the
+    /// reasoning behind the winner
+    pub fn explain_last_selection(&self) -> Option<&SelectionExplanation> {
+        self.last_explanation.as_ref()
+    }
+
+    /// Explain the selection for `context` using `engine` for the fallback
+    /// decision
+    pub(super) async fn explain_with(
+        &self,
+        context: &SelectionContext,
+        engine: &FallbackEngine,
+    ) -> SelectionExplanation {
+        let strategy = self.fallback_config.strategy.clone();
+        let mut local_health = self.health_monitor.get_providers_by_health().await;
+        self.prefer_warm_providers(&context.model_id, &mut local_health)
+            .await;
+        self.apply_latency_slo(&mut local_health, Instant::now());
+
+        let mut fallback_context = FallbackContext::new(context.model_id.clone())
+            .with_streaming(context.requires_streaming)
+            .with_tools(context.requires_tools)
+            .with_previous_provider(context.previous_provider.clone().unwrap_or_default())
+            .with_consecutive_failures(context.consecutive_failures);
+        fallback_context.prompt_chars = context.prompt_chars;
+
+        let decision = engine
+            .decide_provider(&fallback_context, &local_health)
+            .await;
+
+        let mut stages = vec![DecisionStage {
+            id: "strategy".to_string(),
+            name: "Strategy".to_string(),
+            details: vec![
+                format!("{strategy:?}"),
+                format!("consecutive failures: {}", context.consecutive_failures),
+            ],
+            passed: true,
+        }];
+
+        stages.push(DecisionStage {
+            id: "health".to_string(),
+            name: "Health checks".to_string(),
+            details: local_health
+                .iter()
+                .map(|(name, status)| format!("{name}: {}", status.label()))
+                .collect(),
+            passed: local_health.iter().any(|(_, status)| status.is_usable()),
+        });
+
+        let mut capable: Vec<String> = local_health
+            .iter()
+            .filter(|(name, _)| self.provider_supports_model(name, &context.model_id))
+            .map(|(name, _)| name.clone())
+            .collect();
+        if strategy != FallbackStrategy::None {
+            capable.extend(
+                self.fallback_config
+                    .cloud_providers
+                    .iter()
+                    .filter(|provider| {
+                        engine.cloud_provider_supports_features(provider, &fallback_context)
+                    })
+                    .map(|provider| format!("cloud:{provider}")),
+            );
+        }
+        stages.push(DecisionStage {
+            id: "capabilities".to_string(),
+            name: "Capability gates".to_string(),
+            details: vec![
+                format!(
+                    "streaming: {}, tools: {}",
+                    context.requires_streaming, context.requires_tools
+                ),
+                format!("capable: {}", capable.join(", ")),
+            ],
+            passed: !capable.is_empty(),
+        });
+
+        let cost_detail = match &decision {
+            FallbackDecision::UseCloud { .. } => "cloud provider, billed per request".to_string(),
+            FallbackDecision::UseLocal { .. } => "local provider, no per-request cost".to_string(),
+            _ => "no billable provider selected".to_string(),
+        };
+        stages.push(DecisionStage {
+            id: "cost".to_string(),
+            name: "Cost / budget".to_string(),
+            details: vec![cost_detail],
+            // This selector has no budget, so cost only fails when no
+            // provider is left to bill or run locally
+            passed: decision.provider_name().is_some(),
+        });
+
+        let chosen_provider = match &decision {
+            FallbackDecision::UseLocal { provider_name, .. } => Some(provider_name.clone()),
+            FallbackDecision::UseCloud { provider_name, .. } => {
+                Some(format!("cloud:{provider_name}"))
+            }
+            _ => None,
+        };
+
+        let health = local_health
+            .into_iter()
+            .map(|(name, status)| (name, Some(status)));
+        let cloud = self
+            .fallback_config
+            .cloud_providers
+            .iter()
+            .map(|provider| (format!("cloud:{provider}"), None));
+        let candidates = health
+            .chain(cloud)
+            .map(|(name, status)| {
+                let rejection = (!capable.contains(&name))
+                    .then(|| "Does not support the requested model or features".to_string());
+                CandidateExplanation::new(name, status, None, chosen_provider.as_deref(), rejection)
+            })
+            .collect();
+
+        SelectionExplanation {
+            model_id: context.model_id.clone(),
+            strategy,
+            stages,
+            candidates,
+            chosen_provider,
+            reason: decision.reason().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::config::fallback::FallbackConfig;
+    use crate::config::local_ai::{LocalAiConfig, ProviderHealthStatus};
+    use crate::test_utils::selector_with_config;
+
+    async fn fixture() -> ProviderSelector {
+        selector_with_config(
+            LocalAiConfig::with_default_ollama(),
+            FallbackConfig::default(),
+            &[(
+                "ollama",
+                ProviderHealthStatus::Unhealthy {
+                    reason: "Connection refused".to_string(),
+                    response_time: Duration::from_millis(0),
+                },
+            )],
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_explain_selection_stages() {
+        let fixture = fixture().await;
+
+        let actual = fixture
+            .explain_selection(&SelectionContext::new("llama3.2".to_string()))
+            .await;
+
+        let stage_ids: Vec<_> = actual
+            .stages
+            .iter()
+            .map(|stage| stage.id.as_str())
+            .collect();
+        assert_eq!(
+            stage_ids,
+            vec!["strategy", "health", "capabilities", "cost"]
+        );
+        assert_eq!(actual.chosen_provider, Some("cloud:openai".to_string()));
+        assert!(!actual.stages[1].passed);
+        let candidates: Vec<_> = actual
+            .candidates
+            .iter()
+            .map(|candidate| {
+                (
+                    candidate.provider_name.as_str(),
+                    candidate.elimination_reason.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            candidates,
+            vec![
+                ("ollama", Some("Provider is unhealthy")),
+                ("cloud:openai", None),
+                ("cloud:anthropic", Some("Ranked below cloud:openai")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_explain_selection_to_dot() {
+        let fixture = fixture().await;
+
+        let actual = fixture
+            .explain_selection(&SelectionContext::new("llama3.2".to_string()))
+            .await
+            .to_dot();
+
+        assert!(actual.starts_with("digraph selection {"));
+        for node in [
+            "request [",
+            "strategy [",
+            "health [",
+            "capabilities [",
+            "cost [",
+        ] {
+            assert!(actual.contains(node), "missing node {node} in:\n{actual}");
+        }
+        assert!(actual.contains("cost -> \"cloud:openai\" [label=\"selected\"];"));
+        assert!(actual.contains("ollama: unhealthy"));
+    }
+
+    #[tokio::test]
+    async fn test_explain_last_selection_matches_selection() {
+        let mut fixture = fixture().await;
+        let before = fixture.explain_last_selection().cloned();
+
+        let selection = fixture
+            .select_provider(SelectionContext::new("llama3.2".to_string()))
+            .await
+            .unwrap();
+
+        let actual = fixture.explain_last_selection().unwrap();
+        let candidates: Vec<_> = actual
+            .candidates
+            .iter()
+            .map(|candidate| candidate.provider_name.as_str())
+            .collect();
+        assert_eq!(before, None);
+        assert_eq!(actual.chosen_provider, Some(selection.provider_name));
+        assert_eq!(
+            candidates,
+            vec!["ollama", "cloud:openai", "cloud:anthropic"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_explain_last_selection_follows_forced_provider() {
+        let mut fixture = fixture().await;
+
+        fixture
+            .select_provider(
+                SelectionContext::new("llama3.2".to_string()).with_force_provider("anthropic"),
+            )
+            .await
+            .unwrap();
+
+        let actual = fixture.explain_last_selection().unwrap();
+        let candidates: Vec<_> = actual
+            .candidates
+            .iter()
+            .map(|candidate| {
+                (
+                    candidate.provider_name.as_str(),
+                    candidate.elimination_reason.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(actual.chosen_provider, Some("cloud:anthropic".to_string()));
+        assert_eq!(actual.reason, "Forced by request");
+        assert_eq!(
+            candidates,
+            vec![
+                ("ollama", Some("Provider is unhealthy")),
+                ("cloud:openai", Some("Ranked below cloud:anthropic")),
+                ("cloud:anthropic", None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cost_stage_fails_without_provider() {
+        let fixture = ProviderSelector::new(
+            LocalAiConfig::with_default_ollama(),
+            FallbackConfig::default().strategy(FallbackStrategy::None),
+        )
+        .await
+        .unwrap();
+
+        let actual = fixture
+            .explain_selection(&SelectionContext::new("llama3.2".to_string()))
+            .await;
+
+        let cost = actual
+            .stages
+            .iter()
+            .find(|stage| stage.id == "cost")
+            .unwrap();
+        assert_eq!(actual.chosen_provider, None);
+        assert!(!cost.passed);
+    }
+
+    #[test]
+    fn test_dot_labels_escape_backslashes() {
+        let fixture = SelectionExplanation {
+            model_id: r#"C:\models\"llama""#.to_string(),
+            strategy: FallbackStrategy::Graceful,
+            stages: vec![],
+            candidates: vec![],
+            chosen_provider: None,
+            reason: r"no\provider".to_string(),
+        };
+
+        let actual = fixture.to_dot();
+
+        assert!(actual.contains(r#"label="Request\nC:\\models\\\"llama\"""#));
+        assert!(actual.contains(r"No provider\nno\\provider"));
+    }
+
+    #[tokio::test]
+    async fn test_explain_selection_to_mermaid() {
+        let fixture = fixture().await;
+
+        let actual = fixture
+            .explain_selection(&SelectionContext::new("llama3.2".to_string()))
+            .await
+            .to_mermaid();
+
+        assert!(actual.starts_with("flowchart LR"));
+        assert!(actual.contains("chosen{{\"cloud:openai\"}}"));
+        assert!(actual.contains("cost -->|selected| chosen"));
+    }
+}
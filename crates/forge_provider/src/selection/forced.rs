@@ -0,0 +1,188 @@
+//! Request-scoped provider overrides
+//!
+//! A caller can pin a single request to a named provider through
+//! [`SelectionContext::force_provider`]. The override bypasses the fallback
+//! decision entirely, so a forced provider that cannot serve the request is
+//! reported as an error rather than silently replaced by another provider.
+
+use thiserror::Error;
+use tracing::info;
+
+use super::{ProviderSelection, ProviderSelector, ProviderType, SelectionContext};
+use crate::config::local_ai::ProviderHealthStatus;
+
+/// Errors raised while selecting a provider
+#[derive(Debug, Error)]
+pub enum SelectionError {
+    #[error("Forced provider '{provider}' is unavailable: {reason}")]
+    ForcedProviderUnavailable { provider: String, reason: String },
+
+    #[error("Forced provider '{provider}' is not configured")]
+    UnknownForcedProvider { provider: String },
+
+    #[error("No suitable provider available: {reason}. Attempted: {attempted:?}")]
+    NoProvider {
+        reason: String,
+        attempted: Vec<String>,
+    },
+
+    #[error("Manual provider selection required: {reason}. Available options: {options:?}")]
+    ManualRequired {
+        reason: String,
+        options: Vec<String>,
+    },
+}
+
+impl ProviderSelector {
+    /// Select the provider named by `context.force_provider`, or fail with a
+    /// [`SelectionError`] when it is unknown or unusable
+    pub(super) fn select_forced(
+        &self,
+        provider: &str,
+        local_health: &[(String, ProviderHealthStatus)],
+        context: &SelectionContext,
+    ) -> Result<ProviderSelection, SelectionError> {
+        let selection =
+            if let Some((_, status)) = local_health.iter().find(|(name, _)| name == provider) {
+                match status {
+                    ProviderHealthStatus::Unhealthy { reason, .. } => {
+                        return Err(SelectionError::ForcedProviderUnavailable {
+                            provider: provider.to_string(),
+                            reason: reason.clone(),
+                        });
+                    }
+                    _ => ProviderSelection {
+                        provider_name: provider.to_string(),
+                        provider_type: ProviderType::Local,
+                        reason: "Forced by request".to_string(),
+                        is_fallback: false,
+                        local_health: Some(local_health.iter().cloned().collect()),
+                        model_override: None,
+                        request_id: None,
+                    },
+                }
+            } else {
+                let cloud_name = provider.strip_prefix("cloud:").unwrap_or(provider);
+                if !self
+                    .fallback_config
+                    .cloud_providers
+                    .iter()
+                    .any(|name| name == cloud_name)
+                {
+                    return Err(SelectionError::UnknownForcedProvider {
+                        provider: provider.to_string(),
+                    });
+                }
+                ProviderSelection {
+                    provider_name: format!("cloud:{cloud_name}"),
+                    provider_type: ProviderType::Cloud,
+                    reason: "Forced by request".to_string(),
+                    is_fallback: false,
+                    local_health: Some(local_health.iter().cloned().collect()),
+                    model_override: None,
+                    request_id: None,
+                }
+            };
+
+        info!(
+            provider = %selection.provider_name,
+            model = %context.model_id,
+            "Provider forced by request"
+        );
+
+        Ok(selection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::selection::ProviderMetrics;
+    use crate::test_utils::{create_healthy_status, selector_with_health};
+
+    /// `gpu-a` is fast, `gpu-b` is slow
+    async fn fixture() -> ProviderSelector {
+        selector_with_health(&[
+            ("gpu-a", create_healthy_status()),
+            (
+                "gpu-b",
+                ProviderHealthStatus::Healthy {
+                    response_time: Duration::from_millis(500),
+                    models_available: 3,
+                    additional_info: None,
+                },
+            ),
+        ])
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_forced_healthy_provider_is_always_chosen() {
+        for forced in ["gpu-a", "gpu-b", "cloud:openai"] {
+            let mut fixture = fixture().await;
+            let context = SelectionContext::new("llama3.2".to_string()).with_force_provider(forced);
+
+            let actual = fixture.select_provider(context).await.unwrap();
+
+            assert_eq!(actual.provider_name, forced);
+            assert_eq!(actual.reason, "Forced by request");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_forced_provider_records_metrics() {
+        let mut fixture = fixture().await;
+        fixture.provider_metrics.insert(
+            "gpu-b".to_string(),
+            ProviderMetrics::new(ProviderType::Local),
+        );
+        let context = SelectionContext::new("llama3.2".to_string()).with_force_provider("gpu-b");
+
+        fixture.select_provider(context).await.unwrap();
+
+        let actual = fixture.get_provider_metrics()["gpu-b"].total_requests;
+        let expected = 1;
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_forced_unhealthy_provider_returns_error() {
+        let mut fixture = fixture().await;
+        fixture
+            .health_monitor
+            .set_provider_status(
+                "gpu-b",
+                ProviderHealthStatus::Unhealthy {
+                    reason: "connection refused".to_string(),
+                    response_time: Duration::from_millis(0),
+                },
+            )
+            .await;
+        let context = SelectionContext::new("llama3.2".to_string()).with_force_provider("gpu-b");
+
+        let actual = fixture.select_provider(context).await.unwrap_err();
+
+        assert!(matches!(
+            actual,
+            SelectionError::ForcedProviderUnavailable { provider, reason }
+                if provider == "gpu-b" && reason == "connection refused"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_forced_unknown_provider_returns_error() {
+        let mut fixture = fixture().await;
+        let context = SelectionContext::new("llama3.2".to_string()).with_force_provider("nowhere");
+
+        let actual = fixture.select_provider(context).await.unwrap_err();
+
+        assert!(matches!(
+            actual,
+            SelectionError::UnknownForcedProvider { .. }
+        ));
+    }
+}
@@ -0,0 +1,187 @@
+//! Selection through per-model routing rules
+//!
+//! Rules from [`FallbackConfig::routing_rules`](crate::config::FallbackConfig)
+//! are evaluated in order before the default selection logic. A matching rule
+//! whose target cannot serve the request falls through to default selection
+//! rather than failing the request.
+
+use tracing::{debug, info, warn};
+
+use super::{ProviderSelection, ProviderSelector, ProviderType, SelectionContext};
+use crate::config::local_ai::ProviderHealthStatus;
+use crate::config::routing::RoutingTable;
+
+impl ProviderSelector {
+    /// Compiled routing rules
+    pub fn routing_table(&self) -> &RoutingTable {
+        &self.routing
+    }
+
+    /// Select the target of the first routing rule matching the requested
+    /// model, if it is usable
+    pub(super) fn route_by_rules(
+        &self,
+        context: &SelectionContext,
+        local_health: &[(String, ProviderHealthStatus)],
+    ) -> Option<ProviderSelection> {
+        let rule = self.routing.route(&context.model_id)?.clone();
+        let reason = format!("Matched routing rule {} -> {}", rule.pattern, rule.target);
+        if context.excluded_providers.contains(&rule.target) {
+            debug!(
+                model = %context.model_id,
+                target = %rule.target,
+                "Routing rule target excluded, using default selection"
+            );
+            return None;
+        }
+
+        let selection = match local_health.iter().find(|(name, _)| *name == rule.target) {
+            Some((_, status)) if !status.is_usable() => {
+                warn!(
+                    model = %context.model_id,
+                    target = %rule.target,
+                    status = status.label(),
+                    "Routing rule target unavailable, using default selection"
+                );
+                return None;
+            }
+            Some(_) => ProviderSelection {
+                provider_name: rule.target.clone(),
+                provider_type: ProviderType::Local,
+                reason,
+                is_fallback: false,
+                local_health: Some(local_health.iter().cloned().collect()),
+                model_override: None,
+                request_id: None,
+            },
+            None => {
+                let cloud_name = rule.target.strip_prefix("cloud:").filter(|name| {
+                    self.fallback_config
+                        .cloud_providers
+                        .iter()
+                        .any(|configured| configured == name)
+                });
+                let Some(cloud_name) = cloud_name else {
+                    warn!(
+                        model = %context.model_id,
+                        target = %rule.target,
+                        "Routing rule target is not configured, using default selection"
+                    );
+                    return None;
+                };
+                ProviderSelection {
+                    provider_name: format!("cloud:{cloud_name}"),
+                    provider_type: ProviderType::Cloud,
+                    reason,
+                    is_fallback: false,
+                    local_health: Some(local_health.iter().cloned().collect()),
+                    model_override: None,
+                    request_id: None,
+                }
+            }
+        };
+
+        info!(
+            model = %context.model_id,
+            provider = %selection.provider_name,
+            pattern = %rule.pattern,
+            "Provider selected by routing rule"
+        );
+        Some(selection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::{assert_eq, assert_ne};
+
+    use super::*;
+    use crate::config::fallback::FallbackConfig;
+    use crate::config::local_ai::{LocalAiConfig, LocalProviderConfig};
+    use crate::config::routing::RoutingRule;
+    use crate::test_utils::{create_healthy_status, selector_with_config};
+
+    async fn fixture() -> ProviderSelector {
+        let mut local_config = LocalAiConfig::new();
+        for name in ["gpu-pool", "embeddings", "laptop"] {
+            local_config.providers.insert(
+                name.to_string(),
+                LocalProviderConfig::default().preferred_models(Vec::<String>::new()),
+            );
+        }
+        let fallback_config = FallbackConfig::default().routing_rules(vec![
+            RoutingRule::glob("*-70b", "gpu-pool"),
+            RoutingRule::glob("*-embed*", "embeddings"),
+            RoutingRule::regex("gpt-.*", "cloud:openai"),
+        ]);
+        selector_with_config(
+            local_config,
+            fallback_config,
+            &[
+                ("gpu-pool", create_healthy_status()),
+                ("embeddings", create_healthy_status()),
+                ("laptop", create_healthy_status()),
+            ],
+        )
+        .await
+    }
+
+    async fn select(fixture: &mut ProviderSelector, model_id: &str) -> String {
+        fixture
+            .select_provider(SelectionContext::new(model_id.to_string()))
+            .await
+            .unwrap()
+            .provider_name
+    }
+
+    #[tokio::test]
+    async fn test_matching_models_route_to_rule_target() {
+        let mut fixture = fixture().await;
+
+        let actual = vec![
+            select(&mut fixture, "llama3.1-70b").await,
+            select(&mut fixture, "nomic-embed-text").await,
+            select(&mut fixture, "gpt-4o").await,
+        ];
+
+        let expected = vec!["gpu-pool", "embeddings", "cloud:openai"];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_non_matching_model_uses_default_selection() {
+        let mut fixture = fixture().await;
+
+        let actual = fixture
+            .select_provider(SelectionContext::new("llama3.2:3b".to_string()))
+            .await
+            .unwrap();
+
+        assert!(!actual.reason.starts_with("Matched routing rule"));
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_target_falls_through() {
+        let mut fixture = fixture().await;
+        fixture
+            .health_monitor
+            .set_provider_status(
+                "gpu-pool",
+                ProviderHealthStatus::Unhealthy {
+                    reason: "out of memory".to_string(),
+                    response_time: Duration::from_millis(0),
+                },
+            )
+            .await;
+
+        let actual = fixture
+            .select_provider(SelectionContext::new("llama3.1-70b".to_string()))
+            .await
+            .unwrap();
+
+        assert_ne!(actual.provider_name, "gpu-pool");
+        assert!(!actual.reason.starts_with("Matched routing rule"));
+    }
+}
@@ -0,0 +1,234 @@
+//! Response time SLA from user preferences
+//!
+//! [`UserPreferences::max_response_time`](super::UserPreferences) means "use
+//! local unless it would be too slow". A local provider whose recent latency
+//! exceeds it is treated as unavailable for the request, so the fallback
+//! engine moves to a cloud provider even though the local one is healthy.
+//! Recent latency is the p95 over the latency SLO window once enough requests
+//! were sampled, and the last health check's response time before that; a
+//! lifetime average would keep penalizing a provider long after it recovered.
+//! The SLA is opt-in, with a 10s cap from `UserPreferences::prefer_local`,
+//! and only enforced when the preferences allow fallback and a cloud provider
+//! would accept the request right now; otherwise a slow local answer beats
+//! none.
+
+use std::time::Instant;
+
+use tracing::debug;
+
+use super::{ProviderSelector, UserPreferences};
+use crate::config::fallback::{FallbackContext, FallbackEngine, FallbackStrategy};
+use crate::config::local_ai::ProviderHealthStatus;
+
+impl ProviderSelector {
+    /// Mark the usable providers in `local_health` whose recent latency
+    /// exceeds `preferences.max_response_time` as unhealthy, when `engine`
+    /// has a cloud provider to take the request
+    pub(super) fn apply_response_time_sla(
+        &self,
+        preferences: Option<&UserPreferences>,
+        engine: &FallbackEngine,
+        fallback_context: &FallbackContext,
+        local_health: &mut [(String, ProviderHealthStatus)],
+    ) {
+        let Some(max_response_time) = preferences
+            .filter(|p| p.allow_fallback)
+            .and_then(|p| p.max_response_time)
+        else {
+            return;
+        };
+        if self.fallback_config.strategy == FallbackStrategy::None
+            || !engine.has_available_cloud_provider_at(fallback_context, Instant::now())
+        {
+            return;
+        }
+
+        let now = Instant::now();
+        for (name, status) in local_health.iter_mut() {
+            if !status.is_usable() {
+                continue;
+            }
+            let latency = self
+                .latency_slo
+                .p95_at(name, now)
+                .unwrap_or_else(|| status.response_time());
+            if latency <= max_response_time {
+                continue;
+            }
+            let reason = format!(
+                "Recent latency {}ms exceeds the {}ms SLA",
+                latency.as_millis(),
+                max_response_time.as_millis()
+            );
+            debug!(provider = %name, reason = %reason, "Provider excluded by response time SLA");
+            *status =
+                ProviderHealthStatus::Unhealthy { reason, response_time: status.response_time() };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::circuit_breaker::CircuitBreakerConfig;
+    use crate::config::fallback::FallbackConfig;
+    use crate::config::local_ai::{LocalAiConfig, LocalProviderConfig};
+    use crate::selection::{LatencySloConfig, ProviderMetrics, ProviderType, SelectionContext};
+    use crate::test_utils::selector_with_config;
+
+    /// A selector whose only local provider is healthy and answered its last
+    /// health check in `response_time`
+    async fn healthy_fixture(
+        fallback_config: FallbackConfig,
+        response_time: Duration,
+    ) -> ProviderSelector {
+        let mut local_config = LocalAiConfig::new();
+        local_config
+            .providers
+            .insert("gpu".to_string(), LocalProviderConfig::default());
+        let status = ProviderHealthStatus::Healthy {
+            response_time,
+            models_available: 3,
+            additional_info: None,
+        };
+        selector_with_config(local_config, fallback_config, &[("gpu", status)]).await
+    }
+
+    /// A healthy local provider taking 3s to answer health checks
+    async fn fixture(fallback_config: FallbackConfig) -> ProviderSelector {
+        healthy_fixture(fallback_config, Duration::from_secs(3)).await
+    }
+
+    async fn select(fixture: &mut ProviderSelector, max_response_time: Duration) -> String {
+        let preferences = UserPreferences {
+            max_response_time: Some(max_response_time),
+            ..UserPreferences::prefer_local()
+        };
+        select_with(fixture, preferences).await
+    }
+
+    async fn select_with(fixture: &mut ProviderSelector, preferences: UserPreferences) -> String {
+        fixture
+            .select_provider(
+                SelectionContext::new("llama3.2:latest".to_string()).with_preferences(preferences),
+            )
+            .await
+            .unwrap()
+            .provider_name
+    }
+
+    #[tokio::test]
+    async fn test_slow_local_provider_falls_back_to_cloud_under_tight_sla() {
+        let mut fixture = fixture(FallbackConfig::default()).await;
+
+        let actual = select(&mut fixture, Duration::from_secs(1)).await;
+
+        assert!(actual.starts_with("cloud:"), "{actual}");
+    }
+
+    #[tokio::test]
+    async fn test_slow_local_provider_stays_local_under_loose_sla() {
+        let mut fixture = fixture(FallbackConfig::default()).await;
+
+        let actual = select(&mut fixture, Duration::from_secs(10)).await;
+
+        assert_eq!(actual, "gpu");
+    }
+
+    #[tokio::test]
+    async fn test_sla_is_not_enforced_without_cloud_fallback() {
+        let mut fixture = fixture(FallbackConfig::default().strategy(FallbackStrategy::None)).await;
+
+        let actual = select(&mut fixture, Duration::from_secs(1)).await;
+
+        assert_eq!(actual, "gpu");
+    }
+
+    #[tokio::test]
+    async fn test_sla_is_not_enforced_when_every_cloud_circuit_is_open() {
+        let mut fixture = fixture(
+            FallbackConfig::default()
+                .cloud_breaker(CircuitBreakerConfig::default().failure_threshold(1u32)),
+        )
+        .await;
+        fixture.record_failure("cloud:openai", "rate limited");
+        fixture.record_failure("cloud:anthropic", "rate limited");
+
+        let actual = select(&mut fixture, Duration::from_secs(1)).await;
+
+        assert_eq!(actual, "gpu");
+    }
+
+    #[tokio::test]
+    async fn test_sla_is_not_enforced_when_fallback_is_disallowed() {
+        let mut fixture = fixture(FallbackConfig::default()).await;
+        let preferences = UserPreferences {
+            allow_fallback: false,
+            max_response_time: Some(Duration::from_secs(1)),
+            ..UserPreferences::prefer_local()
+        };
+
+        let actual = select_with(&mut fixture, preferences).await;
+
+        assert_eq!(actual, "gpu");
+    }
+
+    #[tokio::test]
+    async fn test_sla_uses_windowed_request_latency() {
+        let mut fixture = healthy_fixture(FallbackConfig::default(), Duration::from_millis(50))
+            .await
+            .with_latency_slo(
+                LatencySloConfig::default()
+                    .enabled(true)
+                    .p95_target(Duration::from_secs(60)),
+            );
+        let now = Instant::now();
+        for _ in 0..5 {
+            fixture
+                .latency_slo
+                .record_latency_at("gpu", Duration::from_secs(3), now);
+        }
+
+        let actual = select(&mut fixture, Duration::from_secs(1)).await;
+
+        assert!(actual.starts_with("cloud:"), "{actual}");
+    }
+
+    #[tokio::test]
+    async fn test_sla_ignores_stale_lifetime_average() {
+        let mut fixture =
+            healthy_fixture(FallbackConfig::default(), Duration::from_millis(50)).await;
+        let mut metrics = ProviderMetrics::new(ProviderType::Local);
+        metrics.avg_response_time = Duration::from_secs(3);
+        fixture.provider_metrics.insert("gpu".to_string(), metrics);
+
+        let actual = select(&mut fixture, Duration::from_secs(1)).await;
+
+        assert_eq!(actual, "gpu");
+    }
+
+    #[tokio::test]
+    async fn test_sla_is_opt_in() {
+        let mut fixture = healthy_fixture(FallbackConfig::default(), Duration::from_secs(12)).await;
+
+        let actual = select_with(&mut fixture, UserPreferences::default()).await;
+
+        assert_eq!(actual, "gpu");
+    }
+
+    #[tokio::test]
+    async fn test_prefer_local_caps_response_time_at_ten_seconds() {
+        let mut actual = Vec::new();
+        for response_time in [Duration::from_secs(3), Duration::from_secs(12)] {
+            let mut fixture = healthy_fixture(FallbackConfig::default(), response_time).await;
+            actual.push(select_with(&mut fixture, UserPreferences::prefer_local()).await);
+        }
+
+        assert_eq!(actual[0], "gpu");
+        assert!(actual[1].starts_with("cloud:"), "{}", actual[1]);
+    }
+}
@@ -0,0 +1,348 @@
+//! Latency SLO enforcement for local providers
+//!
+//! A provider can pass its health checks while serving requests far too
+//! slowly. The SLO tracker keeps a rolling window of observed latencies per
+//! provider and takes a provider out of rotation while its p95 exceeds the
+//! target. Sustained breaches open a circuit breaker, so the provider is only
+//! probed again after the breaker's cooldown.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use derive_setters::Setters;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use super::ProviderSelector;
+use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
+use crate::config::local_ai::ProviderHealthStatus;
+use crate::performance::percentile;
+
+/// Latency service level objective for local providers
+#[derive(Debug, Clone, Serialize, Deserialize, Setters)]
+#[setters(strip_option, into)]
+pub struct LatencySloConfig {
+    /// Route away from providers breaching the SLO
+    pub enabled: bool,
+    /// Maximum acceptable p95 latency
+    pub p95_target: Duration,
+    /// Rolling window the p95 is computed over
+    pub window: Duration,
+    /// Samples required in the window before the SLO is evaluated
+    pub min_samples: usize,
+    /// Breaker opened after this many consecutive breached evaluations
+    pub breaker: CircuitBreakerConfig,
+}
+
+impl Default for LatencySloConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            p95_target: Duration::from_secs(10),
+            window: Duration::from_secs(300), // 5 minutes
+            min_samples: 5,
+            breaker: CircuitBreakerConfig::default(),
+        }
+    }
+}
+
+/// Rolling latency samples and breaker for one provider
+#[derive(Debug, Clone)]
+struct ProviderSlo {
+    samples: VecDeque<(Instant, Duration)>,
+    breaker: CircuitBreaker,
+}
+
+/// Tracks provider latency against the configured SLO
+#[derive(Debug, Clone)]
+pub struct LatencySlo {
+    config: LatencySloConfig,
+    providers: HashMap<String, ProviderSlo>,
+}
+
+impl LatencySlo {
+    pub fn new(config: LatencySloConfig) -> Self {
+        Self { config, providers: HashMap::new() }
+    }
+
+    /// Record a request latency for `provider_name` observed at `now`
+    pub fn record_latency_at(&mut self, provider_name: &str, latency: Duration, now: Instant) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let config = &self.config;
+        let entry = self
+            .providers
+            .entry(provider_name.to_string())
+            .or_insert_with(|| ProviderSlo {
+                samples: VecDeque::new(),
+                breaker: CircuitBreaker::new(config.breaker.clone()),
+            });
+
+        // A half-open probe is judged on its own latency
+        if entry.breaker.state_at(now) == CircuitState::HalfOpen {
+            if latency <= config.p95_target {
+                entry.breaker.record_success();
+            } else {
+                entry.breaker.record_failure_at(now);
+            }
+            entry.samples.clear();
+            return;
+        }
+
+        entry.samples.push_back((now, latency));
+        prune(&mut entry.samples, config.window, now);
+
+        let Some(p95) = p95(&entry.samples, config.min_samples) else {
+            return;
+        };
+        if p95 > config.p95_target {
+            entry.breaker.record_failure_at(now);
+            if entry.breaker.state_at(now) == CircuitState::Open {
+                warn!(
+                    provider = provider_name,
+                    p95_ms = p95.as_millis() as u64,
+                    target_ms = config.p95_target.as_millis() as u64,
+                    "Sustained latency SLO breach, opening circuit breaker"
+                );
+                // Judge the provider afresh once the cooldown has elapsed
+                entry.samples.clear();
+            }
+        } else {
+            entry.breaker.record_success();
+        }
+    }
+
+    /// Current p95 latency for `provider_name` over the window ending at
+    /// `now`, if enough samples were recorded
+    pub fn p95_at(&self, provider_name: &str, now: Instant) -> Option<Duration> {
+        let entry = self.providers.get(provider_name)?;
+        let samples: VecDeque<_> = entry
+            .samples
+            .iter()
+            .filter(|(at, _)| now.saturating_duration_since(*at) <= self.config.window)
+            .copied()
+            .collect();
+        p95(&samples, self.config.min_samples)
+    }
+
+    /// Breaker state for `provider_name` at `now`
+    pub fn breaker_state_at(&self, provider_name: &str, now: Instant) -> CircuitState {
+        self.providers
+            .get(provider_name)
+            .map(|entry| entry.breaker.state_at(now))
+            .unwrap_or(CircuitState::Closed)
+    }
+
+    /// Why `provider_name` should not receive requests at `now`, if it
+    /// should not
+    pub fn violation_at(&self, provider_name: &str, now: Instant) -> Option<String> {
+        if !self.config.enabled {
+            return None;
+        }
+        let entry = self.providers.get(provider_name)?;
+
+        if !entry.breaker.is_available_at(now) {
+            return Some("circuit open after sustained latency SLO breach".to_string());
+        }
+        match self.p95_at(provider_name, now) {
+            Some(p95) if p95 > self.config.p95_target => Some(format!(
+                "p95 latency {p95:?} exceeds SLO {:?}",
+                self.config.p95_target
+            )),
+            _ => None,
+        }
+    }
+
+    /// Reserve the half-open probe for `provider_name` once it is selected
+    pub fn acquire_at(&mut self, provider_name: &str, now: Instant) {
+        if let Some(entry) = self.providers.get_mut(provider_name) {
+            entry.breaker.try_acquire_at(now);
+        }
+    }
+
+    /// Record a failed request for `provider_name` at `now`. A failed
+    /// half-open probe reopens the breaker, releasing the probe so the
+    /// provider is tried again after the next cooldown.
+    pub fn record_failure_at(&mut self, provider_name: &str, now: Instant) {
+        if let Some(entry) = self.providers.get_mut(provider_name) {
+            if entry.breaker.state_at(now) == CircuitState::HalfOpen {
+                entry.breaker.record_failure_at(now);
+            }
+        }
+    }
+}
+
+/// Drop samples older than `window`
+fn prune(samples: &mut VecDeque<(Instant, Duration)>, window: Duration, now: Instant) {
+    while samples
+        .front()
+        .is_some_and(|(at, _)| now.saturating_duration_since(*at) > window)
+    {
+        samples.pop_front();
+    }
+}
+
+/// 95th percentile latency, if there are at least `min_samples`
+fn p95(samples: &VecDeque<(Instant, Duration)>, min_samples: usize) -> Option<Duration> {
+    if samples.is_empty() || samples.len() < min_samples {
+        return None;
+    }
+    let mut latencies: Vec<Duration> = samples.iter().map(|(_, latency)| *latency).collect();
+    latencies.sort();
+    Some(percentile(&latencies, 95.0))
+}
+
+impl ProviderSelector {
+    /// Route away from local providers whose live p95 latency breaches `config`
+    pub fn with_latency_slo(mut self, config: LatencySloConfig) -> Self {
+        self.latency_slo = LatencySlo::new(config);
+        self
+    }
+
+    /// Latency SLO tracker fed by [`ProviderSelector::record_success`]
+    pub fn latency_slo(&self) -> &LatencySlo {
+        &self.latency_slo
+    }
+
+    /// Mark providers breaching the latency SLO as unhealthy so the fallback
+    /// engine routes around them
+    pub(super) fn apply_latency_slo(
+        &self,
+        local_health: &mut [(String, ProviderHealthStatus)],
+        now: Instant,
+    ) {
+        for (name, status) in local_health.iter_mut() {
+            if !status.is_usable() {
+                continue;
+            }
+            if let Some(reason) = self.latency_slo.violation_at(name, now) {
+                debug!(provider = %name, reason = %reason, "Provider excluded by latency SLO");
+                *status = ProviderHealthStatus::Unhealthy {
+                    reason,
+                    response_time: status.response_time(),
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::{assert_eq, assert_ne};
+
+    use super::*;
+    use crate::selection::SelectionContext;
+    use crate::test_utils::{create_healthy_status, selector_with_health};
+
+    fn slo_config() -> LatencySloConfig {
+        LatencySloConfig::default()
+            .enabled(true)
+            .p95_target(Duration::from_secs(2))
+            .min_samples(3usize)
+            .breaker(
+                CircuitBreakerConfig::default()
+                    .failure_threshold(3u32)
+                    .cooldown(Duration::from_secs(30)),
+            )
+    }
+
+    async fn fixture() -> ProviderSelector {
+        selector_with_health(&[
+            ("gpu-a", create_healthy_status()),
+            ("gpu-b", create_healthy_status()),
+        ])
+        .await
+        .with_latency_slo(slo_config())
+    }
+
+    #[tokio::test]
+    async fn test_slow_healthy_provider_is_routed_around() {
+        for slow in ["gpu-a", "gpu-b"] {
+            let mut fixture = fixture().await;
+            for _ in 0..3 {
+                fixture.record_success(slow, Duration::from_secs(5));
+            }
+
+            let actual = fixture
+                .select_provider(SelectionContext::new("llama3.2:latest".to_string()))
+                .await
+                .unwrap();
+
+            assert_ne!(actual.provider_name, slow);
+        }
+    }
+
+    #[test]
+    fn test_sustained_breach_trips_breaker_until_cooldown() {
+        let mut fixture = LatencySlo::new(slo_config());
+        let start = Instant::now();
+
+        for i in 0..3 {
+            fixture.record_latency_at("gpu-a", Duration::from_secs(5), start);
+            assert_eq!(
+                fixture.breaker_state_at("gpu-a", start),
+                CircuitState::Closed,
+                "sample {i}"
+            );
+        }
+        for _ in 0..2 {
+            fixture.record_latency_at("gpu-a", Duration::from_secs(5), start);
+        }
+
+        assert_eq!(fixture.breaker_state_at("gpu-a", start), CircuitState::Open);
+        assert!(fixture.violation_at("gpu-a", start).is_some());
+
+        let later = start + Duration::from_secs(30);
+        assert_eq!(
+            fixture.breaker_state_at("gpu-a", later),
+            CircuitState::HalfOpen
+        );
+        assert_eq!(fixture.violation_at("gpu-a", later), None);
+
+        fixture.acquire_at("gpu-a", later);
+        fixture.record_latency_at("gpu-a", Duration::from_millis(500), later);
+        assert_eq!(
+            fixture.breaker_state_at("gpu-a", later),
+            CircuitState::Closed
+        );
+    }
+
+    #[test]
+    fn test_failed_probe_reopens_breaker() {
+        let mut fixture = LatencySlo::new(slo_config());
+        let start = Instant::now();
+        for _ in 0..5 {
+            fixture.record_latency_at("gpu-a", Duration::from_secs(5), start);
+        }
+        let probe_at = start + Duration::from_secs(30);
+        fixture.acquire_at("gpu-a", probe_at);
+
+        fixture.record_failure_at("gpu-a", probe_at);
+
+        assert_eq!(
+            fixture.breaker_state_at("gpu-a", probe_at),
+            CircuitState::Open
+        );
+        let next_probe = probe_at + Duration::from_secs(30);
+        assert_eq!(
+            fixture.breaker_state_at("gpu-a", next_probe),
+            CircuitState::HalfOpen
+        );
+        assert_eq!(fixture.violation_at("gpu-a", next_probe), None);
+    }
+
+    #[test]
+    fn test_samples_outside_window_are_ignored() {
+        let mut fixture = LatencySlo::new(slo_config().window(Duration::from_secs(60)));
+        let start = Instant::now();
+        for _ in 0..3 {
+            fixture.record_latency_at("gpu-a", Duration::from_secs(5), start);
+        }
+
+        let actual = fixture.violation_at("gpu-a", start + Duration::from_secs(61));
+
+        assert_eq!(actual, None);
+    }
+}
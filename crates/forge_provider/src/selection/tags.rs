@@ -0,0 +1,143 @@
+//! Restricting selection to tagged local providers
+//!
+//! Operators running a separate local stack per team tag each provider in
+//! [`LocalProviderConfig::tags`](crate::config::local_ai::LocalProviderConfig)
+//! and set [`SelectionContext::tags`](super::SelectionContext) on requests.
+//! Local providers carrying none of the required tags are never considered,
+//! however healthy they are. Cloud providers carry no tags and stay available
+//! as fallbacks.
+
+use tracing::debug;
+
+use super::ProviderSelector;
+use crate::config::local_ai::ProviderHealthStatus;
+
+impl ProviderSelector {
+    /// Drop the local providers in `local_health` that carry none of the
+    /// required `tags`
+    pub(super) fn restrict_to_tags(
+        &self,
+        tags: &[String],
+        local_health: &mut Vec<(String, ProviderHealthStatus)>,
+    ) {
+        if tags.is_empty() {
+            return;
+        }
+        let before = local_health.len();
+        local_health.retain(|(name, _)| self.provider_matches_tags(name, tags));
+        debug!(
+            ?tags,
+            excluded = before - local_health.len(),
+            "Restricted local providers to required tags"
+        );
+    }
+
+    /// Whether `provider_name` may serve a request requiring `tags`
+    pub(super) fn provider_matches_tags(&self, provider_name: &str, tags: &[String]) -> bool {
+        if provider_name.starts_with("cloud:") {
+            return true;
+        }
+        self.local_config
+            .providers
+            .get(provider_name)
+            .map_or(tags.is_empty(), |config| config.matches_tags(tags))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::config::fallback::FallbackConfig;
+    use crate::config::local_ai::{LocalAiConfig, LocalProviderConfig};
+    use crate::selection::SelectionContext;
+    use crate::test_utils::selector_with_config;
+
+    /// `team-a` is slow, `team-b` is fast
+    async fn fixture() -> ProviderSelector {
+        let mut local_config = LocalAiConfig::new();
+        for name in ["team-a", "team-b"] {
+            local_config.providers.insert(
+                name.to_string(),
+                LocalProviderConfig::default()
+                    .preferred_models(Vec::<String>::new())
+                    .tags(vec![name.to_string()]),
+            );
+        }
+        let health = [("team-a", 900), ("team-b", 20)].map(|(name, millis)| {
+            (
+                name,
+                ProviderHealthStatus::Healthy {
+                    response_time: Duration::from_millis(millis),
+                    models_available: 3,
+                    additional_info: None,
+                },
+            )
+        });
+        selector_with_config(local_config, FallbackConfig::default(), &health).await
+    }
+
+    async fn select_names(
+        fixture: &mut ProviderSelector,
+        context: SelectionContext,
+    ) -> Vec<String> {
+        let mut names = Vec::new();
+        for _ in 0..4 {
+            let selection = fixture.select_provider(context.clone()).await.unwrap();
+            names.push(selection.provider_name);
+        }
+        names
+    }
+
+    #[tokio::test]
+    async fn test_tagged_request_only_selects_matching_providers() {
+        let mut fixture = fixture().await;
+        let context = SelectionContext::new("llama3.2".to_string()).with_tags(["team-a"]);
+
+        let actual = select_names(&mut fixture, context).await;
+
+        let expected = vec!["team-a".to_string(); 4];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_untagged_request_considers_every_provider() {
+        let mut fixture = fixture().await;
+        let context = SelectionContext::new("llama3.2".to_string());
+
+        let actual = select_names(&mut fixture, context).await;
+
+        assert!(actual.contains(&"team-b".to_string()), "{actual:?}");
+    }
+
+    #[tokio::test]
+    async fn test_tagged_request_never_falls_back_to_other_team() {
+        let mut fixture = fixture().await;
+        fixture
+            .health_monitor
+            .set_provider_status(
+                "team-a",
+                ProviderHealthStatus::Unhealthy {
+                    reason: "connection refused".to_string(),
+                    response_time: Duration::from_millis(0),
+                },
+            )
+            .await;
+        let context = SelectionContext::new("llama3.2".to_string()).with_tags(["team-a"]);
+
+        let mut attempted = Vec::new();
+        fixture
+            .execute_with_fallback(context, |selection| {
+                attempted.push(selection.provider_name);
+                async { Err::<(), _>(anyhow::anyhow!("refused")) }
+            })
+            .await
+            .unwrap_err();
+
+        assert!(!attempted.contains(&"team-b".to_string()), "{attempted:?}");
+        assert!(!attempted.is_empty());
+    }
+}
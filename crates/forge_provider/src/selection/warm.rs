@@ -0,0 +1,210 @@
+//! Selection bias toward providers that already have a related model loaded
+//!
+//! Switching a single-GPU host between large models forces an unload and a
+//! cold reload. When several local providers are equally suitable, preferring
+//! the one whose loaded model shares the requested model's family lets the
+//! request reuse warm memory instead. Loaded models are taken from the health
+//! checks of providers that report them, such as Ollama's `/api/ps`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use super::ProviderSelector;
+use crate::config::local_ai::ProviderHealthStatus;
+
+/// Models currently loaded on each local provider
+#[derive(Debug, Clone, Default)]
+pub struct WarmModels {
+    loaded: Arc<RwLock<HashMap<String, Vec<String>>>>,
+}
+
+impl WarmModels {
+    /// Replace the set of models loaded on `provider_name`
+    pub async fn set_loaded_models(&self, provider_name: &str, models: Vec<String>) {
+        self.loaded
+            .write()
+            .await
+            .insert(provider_name.to_string(), models);
+    }
+
+    /// Models loaded on `provider_name`
+    pub async fn loaded_models(&self, provider_name: &str) -> Vec<String> {
+        self.loaded
+            .read()
+            .await
+            .get(provider_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Whether `provider_name` has a model loaded that is related to
+    /// `model_id`
+    pub async fn is_warm_for(&self, provider_name: &str, model_id: &str) -> bool {
+        self.loaded
+            .read()
+            .await
+            .get(provider_name)
+            .is_some_and(|models| {
+                models
+                    .iter()
+                    .any(|loaded| is_related_model(loaded, model_id))
+            })
+    }
+}
+
+/// Two models are related when they belong to the same family, i.e. they
+/// differ at most in their tag (`llama3.2:1b` and `llama3.2:3b`)
+pub fn is_related_model(a: &str, b: &str) -> bool {
+    let family = |model: &str| model.split(':').next().unwrap_or(model).to_string();
+    family(a) == family(b)
+}
+
+/// Rank used to keep the health ordering when applying the warm bias
+pub(super) fn health_rank(status: &ProviderHealthStatus) -> u8 {
+    match status {
+        ProviderHealthStatus::Healthy { .. } => 0,
+        ProviderHealthStatus::Degraded { .. } => 1,
+        ProviderHealthStatus::Unhealthy { .. } => 2,
+    }
+}
+
+impl ProviderSelector {
+    /// Models loaded on each local provider, used to bias selection toward
+    /// warm GPU memory
+    pub fn warm_models(&self) -> &WarmModels {
+        &self.warm_models
+    }
+
+    /// Reorder `local_health` so that, within each health tier, providers with
+    /// a related model loaded come first, refreshing the loaded models from
+    /// the latest health checks
+    pub(super) async fn prefer_warm_providers(
+        &self,
+        model_id: &str,
+        local_health: &mut [(String, ProviderHealthStatus)],
+    ) {
+        let mut warm = HashMap::new();
+        for (name, _) in local_health.iter() {
+            if let Some(models) = self.health_monitor.get_loaded_models(name).await {
+                self.warm_models.set_loaded_models(name, models).await;
+            }
+            warm.insert(
+                name.clone(),
+                self.warm_models.is_warm_for(name, model_id).await,
+            );
+        }
+
+        local_health.sort_by_key(|(name, status)| (health_rank(status), !warm[name]));
+        debug!(
+            model = %model_id,
+            order = ?local_health.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            "Applied warm model bias"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::config::fallback::FallbackConfig;
+    use crate::config::local_ai::{LocalAiConfig, LocalProviderConfig};
+    use crate::mock_server::MockOllamaServer;
+    use crate::selection::SelectionContext;
+    use crate::test_utils::{create_healthy_status, selector_with_config};
+
+    async fn fixture() -> ProviderSelector {
+        let mut local_config = LocalAiConfig::new();
+        for name in ["gpu-a", "gpu-b"] {
+            local_config.providers.insert(
+                name.to_string(),
+                LocalProviderConfig::default().preferred_models(Vec::<String>::new()),
+            );
+        }
+        selector_with_config(
+            local_config,
+            FallbackConfig::default(),
+            &[
+                ("gpu-a", create_healthy_status()),
+                ("gpu-b", create_healthy_status()),
+            ],
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_prefers_host_with_related_model_warm() {
+        for warm_host in ["gpu-a", "gpu-b"] {
+            let mut fixture = fixture().await;
+            fixture
+                .warm_models()
+                .set_loaded_models(warm_host, vec!["llama3.2:1b".to_string()])
+                .await;
+
+            let actual = fixture
+                .select_provider(SelectionContext::new("llama3.2:3b".to_string()))
+                .await
+                .unwrap();
+
+            assert_eq!(actual.provider_name, warm_host);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_loaded_models_are_read_from_health_checks() {
+        for warm_host in ["gpu-a", "gpu-b"] {
+            let mut servers = Vec::new();
+            let mut local_config = LocalAiConfig::new();
+            for name in ["gpu-a", "gpu-b"] {
+                let loaded: &[&str] = if name == warm_host {
+                    &["llama3.2:1b"]
+                } else {
+                    &[]
+                };
+                let server = MockOllamaServer::builder()
+                    .tags(&["llama3.2:1b", "llama3.2:3b"])
+                    .ps(loaded)
+                    .start()
+                    .await;
+                local_config.providers.insert(
+                    name.to_string(),
+                    LocalProviderConfig::default()
+                        .endpoint(server.url())
+                        .preferred_models(Vec::<String>::new()),
+                );
+                servers.push(server);
+            }
+            let mut fixture = ProviderSelector::new(local_config, FallbackConfig::default())
+                .await
+                .unwrap();
+            fixture.refresh_health().await.unwrap();
+
+            let actual = fixture
+                .select_provider(SelectionContext::new("llama3.2:3b".to_string()))
+                .await
+                .unwrap();
+
+            assert_eq!(actual.provider_name, warm_host);
+            assert_eq!(
+                fixture.warm_models().loaded_models(warm_host).await,
+                vec!["llama3.2:1b".to_string()]
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_related_model() {
+        let actual = vec![
+            is_related_model("llama3.2:1b", "llama3.2:3b"),
+            is_related_model("llama3.2", "llama3.2:latest"),
+            is_related_model("llama3.2:1b", "qwen2.5:1b"),
+        ];
+
+        let expected = vec![true, true, false];
+        assert_eq!(actual, expected);
+    }
+}
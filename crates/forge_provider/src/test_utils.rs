@@ -7,8 +7,11 @@ use std::time::{Duration, Instant};
 use forge_app::domain::{Model, ModelId};
 use tokio::sync::RwLock;
 
-use crate::config::local_ai::{LocalAiConfig, ProviderHealthChecker, ProviderHealthStatus};
-use crate::discovery::{DiscoveredModel, DiscoveryStats, ModelDiscoveryResult};
+use crate::config::local_ai::{
+    HealthCheckConfig, LocalAiConfig, LocalProviderConfig, ProviderHealthChecker,
+    ProviderHealthStatus,
+};
+use crate::discovery::{DiscoveredModel, ModelDiscoveryResult};
 use crate::health::{HealthCheckResult, ProviderHealthInfo};
 use crate::selection::{ProviderMetrics, ProviderType};
 
@@ -40,7 +43,6 @@ impl MockHealthChecker {
                 reason: "Mock degraded provider".to_string(),
                 response_time: Duration::from_millis(2000),
                 models_available: 2,
-                additional_info: None,
             },
             should_fail: false,
             response_delay: Duration::from_millis(2000),
@@ -90,6 +92,10 @@ impl ProviderHealthChecker for MockHealthChecker {
 
         Ok(self.status.clone())
     }
+
+    fn provider_type(&self) -> &str {
+        "mock"
+    }
 }
 
 /// Mock Ollama service for testing
@@ -100,6 +106,12 @@ pub struct MockOllamaService {
     should_fail: bool,
 }
 
+impl Default for MockOllamaService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MockOllamaService {
     /// Create a new mock Ollama service
     pub fn new() -> Self {
@@ -177,15 +189,14 @@ impl TestFixtures {
 
     /// Create a test local AI configuration with multiple providers
     pub fn multi_provider_config() -> LocalAiConfig {
-        let mut config = LocalAiConfig::new();
-
-        // Add primary Ollama provider
-        config.add_ollama_provider("ollama-primary", "http://localhost:11434");
-
-        // Add backup Ollama provider
-        config.add_ollama_provider("ollama-backup", "http://localhost:11435");
-
-        config
+        LocalAiConfig::new()
+            // Primary Ollama provider
+            .add_provider("ollama-primary".to_string(), LocalProviderConfig::default())
+            // Backup Ollama provider
+            .add_provider(
+                "ollama-backup".to_string(),
+                LocalProviderConfig::default().endpoint("http://localhost:11435"),
+            )
     }
 
     /// Create test models
@@ -222,7 +233,6 @@ impl TestFixtures {
                     reason: "High response time".to_string(),
                     response_time: Duration::from_millis(2000),
                     models_available: 2,
-                    additional_info: None,
                 },
                 available: true,
                 last_checked: Instant::now(),
@@ -246,17 +256,18 @@ impl TestFixtures {
     pub fn discovery_result() -> ModelDiscoveryResult {
         let discovered_models = Self::discovered_models();
         let available_count = discovered_models.iter().filter(|m| m.available).count();
-        let providers: std::collections::HashSet<_> =
-            discovered_models.iter().map(|m| &m.provider).collect();
+        let healthy_providers: std::collections::HashSet<_> = discovered_models
+            .iter()
+            .filter(|m| m.provider_health.is_usable())
+            .map(|m| &m.provider)
+            .collect();
 
         ModelDiscoveryResult {
-            discovered_models,
-            stats: DiscoveryStats {
-                total_models: 3,
-                available_models: available_count,
-                total_providers: providers.len(),
-                last_discovery: Some(Instant::now()),
-            },
+            total_models: discovered_models.len(),
+            healthy_providers: healthy_providers.len(),
+            available_models: available_count,
+            discovery_duration: Duration::from_millis(50),
+            warnings: vec![],
         }
     }
 
@@ -280,6 +291,11 @@ impl TestFixtures {
                     Some("Test error".to_string())
                 },
             }],
+            server_load: None,
+            loaded_models: None,
+            current_interval: Duration::from_secs(30),
+            recovering: false,
+            health_config: HealthCheckConfig::default(),
         }
     }
 
@@ -322,7 +338,6 @@ pub fn create_degraded_status() -> ProviderHealthStatus {
         reason: "High response time".to_string(),
         response_time: Duration::from_millis(2000),
         models_available: 2,
-        additional_info: None,
     }
 }
 
@@ -334,11 +349,49 @@ pub fn create_unhealthy_status() -> ProviderHealthStatus {
     }
 }
 
+/// Provider selector over a default-configured local provider for each
+/// entry of `health`, reported with that status
+#[cfg(test)]
+pub async fn selector_with_health(
+    health: &[(&str, ProviderHealthStatus)],
+) -> crate::selection::ProviderSelector {
+    let mut local_config = LocalAiConfig::new();
+    for (name, _) in health {
+        local_config
+            .providers
+            .insert(name.to_string(), LocalProviderConfig::default());
+    }
+    selector_with_config(local_config, Default::default(), health).await
+}
+
+/// Provider selector built from `local_config` and `fallback_config`, with
+/// each provider in `health` reported with its status
+#[cfg(test)]
+pub async fn selector_with_config(
+    local_config: LocalAiConfig,
+    fallback_config: crate::config::fallback::FallbackConfig,
+    health: &[(&str, ProviderHealthStatus)],
+) -> crate::selection::ProviderSelector {
+    let selector = crate::selection::ProviderSelector::new(local_config, fallback_config)
+        .await
+        .unwrap();
+    for (name, status) in health {
+        selector.set_provider_status(name, status.clone()).await;
+    }
+    selector
+}
+
 /// Mock health monitor for testing
 pub struct MockHealthMonitor {
     health_status: Arc<RwLock<HashMap<String, ProviderHealthInfo>>>,
 }
 
+impl Default for MockHealthMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MockHealthMonitor {
     /// Create a new mock health monitor
     pub fn new() -> Self {
@@ -519,10 +572,10 @@ mod tests {
     fn test_test_fixtures_discovery_result() {
         let fixture = TestFixtures::discovery_result();
 
-        assert_eq!(fixture.stats.total_models, 3);
-        assert!(fixture.stats.available_models <= fixture.stats.total_models);
-        assert!(fixture.stats.total_providers > 0);
-        assert!(fixture.stats.last_discovery.is_some());
+        assert_eq!(fixture.total_models, 3);
+        assert!(fixture.available_models <= fixture.total_models);
+        assert!(fixture.healthy_providers > 0);
+        assert!(fixture.warnings.is_empty());
     }
 
     #[test]
@@ -0,0 +1,498 @@
+//! Network timing breakdown for provider requests
+//!
+//! A [`ConnectionTimer`] hooks into a provider's HTTP client through reqwest's
+//! DNS resolver, connector layer and the TLS session store, so each request
+//! can report how long was spent resolving, connecting, negotiating TLS and
+//! waiting for the first byte. This separates a slow network from a slow
+//! model. The hooks record into the [`TimedRequest`] whose future or stream
+//! they run under, so concurrent requests on one client never see each
+//! other's timings.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use futures::{Stream, StreamExt};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use rustls::client::{
+    ClientSessionMemoryCache, ClientSessionStore, Resumption, Tls12ClientSessionValue,
+    Tls13ClientSessionValue,
+};
+use rustls::pki_types::ServerName;
+use rustls::NamedGroup;
+use tower_layer::Layer;
+use tower_service::Service;
+
+tokio::task_local! {
+    /// Timing state of the request being sent by the current task
+    static CURRENT: Arc<Mutex<PendingTiming>>;
+}
+
+/// Timing breakdown for a single request
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestTiming {
+    /// DNS resolution, when a new connection needed one
+    pub dns: Option<Duration>,
+    /// TCP connect, excluding DNS, when a new connection was opened
+    pub connect: Option<Duration>,
+    /// TLS handshake, when a new connection to an HTTPS endpoint was opened
+    pub tls: Option<Duration>,
+    /// Time from sending the request until response headers arrived
+    pub ttfb: Duration,
+    /// Whether a pooled connection was reused
+    pub connection_reused: bool,
+}
+
+impl RequestTiming {
+    pub const DNS_KEY: &'static str = "dns_ms";
+    pub const CONNECT_KEY: &'static str = "connect_ms";
+    pub const TLS_KEY: &'static str = "tls_ms";
+    pub const TTFB_KEY: &'static str = "ttfb_ms";
+    pub const REUSED_KEY: &'static str = "connection_reused";
+
+    /// Metadata keys written by [`RequestTiming::to_metadata`]
+    pub const METADATA_KEYS: [&'static str; 5] = [
+        Self::DNS_KEY,
+        Self::CONNECT_KEY,
+        Self::TLS_KEY,
+        Self::TTFB_KEY,
+        Self::REUSED_KEY,
+    ];
+
+    /// Encode the timing as measurement metadata
+    pub fn to_metadata(&self) -> Vec<(String, String)> {
+        let millis = |d: Duration| format!("{:.3}", d.as_secs_f64() * 1000.0);
+        let mut metadata = vec![
+            (Self::TTFB_KEY.to_string(), millis(self.ttfb)),
+            (
+                Self::REUSED_KEY.to_string(),
+                self.connection_reused.to_string(),
+            ),
+        ];
+        if let Some(dns) = self.dns {
+            metadata.push((Self::DNS_KEY.to_string(), millis(dns)));
+        }
+        if let Some(connect) = self.connect {
+            metadata.push((Self::CONNECT_KEY.to_string(), millis(connect)));
+        }
+        if let Some(tls) = self.tls {
+            metadata.push((Self::TLS_KEY.to_string(), millis(tls)));
+        }
+        metadata
+    }
+
+    /// Decode timing from measurement metadata, if present
+    pub fn from_metadata(metadata: &HashMap<String, String>) -> Option<Self> {
+        let duration = |key: &str| {
+            metadata
+                .get(key)
+                .and_then(|value| value.parse::<f64>().ok())
+                .map(|ms| Duration::from_micros((ms * 1000.0).round() as u64))
+        };
+        Some(Self {
+            dns: duration(Self::DNS_KEY),
+            connect: duration(Self::CONNECT_KEY),
+            tls: duration(Self::TLS_KEY),
+            ttfb: duration(Self::TTFB_KEY)?,
+            connection_reused: metadata
+                .get(Self::REUSED_KEY)
+                .is_some_and(|value| value == "true"),
+        })
+    }
+}
+
+/// Timings recorded by the client hooks for one request
+#[derive(Debug, Default)]
+struct PendingTiming {
+    dns: Option<Duration>,
+    connect: Option<Duration>,
+    tls: Option<Duration>,
+    /// When the TLS handshake on the connection being opened began
+    handshake_started: Option<Instant>,
+}
+
+/// Timing state for a single request. Hooks installed by
+/// [`ConnectionTimer::instrument`] record into the request whose future or
+/// stream is being polled, through [`TimedRequest::scope`] or
+/// [`TimedRequest::stream`].
+#[derive(Debug, Clone)]
+pub struct TimedRequest {
+    pending: Arc<Mutex<PendingTiming>>,
+    started_at: Instant,
+}
+
+impl TimedRequest {
+    /// Start timing a request now
+    pub fn start() -> Self {
+        Self {
+            pending: Arc::new(Mutex::new(PendingTiming::default())),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// When the request started
+    pub fn started_at(&self) -> Instant {
+        self.started_at
+    }
+
+    /// Run `future` with this request's timing state
+    pub async fn scope<F: Future>(&self, future: F) -> F::Output {
+        CURRENT.scope(self.pending.clone(), future).await
+    }
+
+    /// Poll `stream` with this request's timing state, for clients that only
+    /// send the request once the response stream is first polled
+    pub fn stream<S: Stream + Unpin>(&self, mut stream: S) -> impl Stream<Item = S::Item> {
+        let pending = self.pending.clone();
+        futures::stream::poll_fn(move |cx| {
+            CURRENT.sync_scope(pending.clone(), || stream.poll_next_unpin(cx))
+        })
+    }
+
+    /// Timing recorded so far, with the time to first byte measured until
+    /// now
+    pub fn timing(&self) -> RequestTiming {
+        let pending = self.pending.lock().unwrap();
+        RequestTiming {
+            dns: pending.dns,
+            connect: pending.connect,
+            tls: pending.tls,
+            ttfb: self.started_at.elapsed(),
+            connection_reused: pending.connect.is_none(),
+        }
+    }
+}
+
+/// Installs the hooks that capture DNS, connect, TLS and time-to-first-byte
+/// timings for requests sent through a client
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionTimer;
+
+impl ConnectionTimer {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Install the timing hooks on a client builder. HTTPS connections use
+    /// rustls with the webpki roots, as reqwest's own rustls backend does.
+    pub fn instrument(&self, builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        builder
+            .dns_resolver(Arc::new(TimedResolver))
+            .connector_layer(ConnectTimingLayer)
+            .use_preconfigured_tls(tls_config())
+    }
+
+    /// Send a request and report its timing breakdown. The response body is
+    /// not read, so total time should be measured by the caller.
+    pub async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> reqwest::Result<(reqwest::Response, RequestTiming)> {
+        let timed = TimedRequest::start();
+        let response = timed.scope(request.send()).await?;
+        Ok((response, timed.timing()))
+    }
+}
+
+/// Record into the timing state of the request sent by the current task, if
+/// any. Connections finished in the background after their request was
+/// served by a pooled connection have none.
+fn with_current(record: impl FnOnce(&mut PendingTiming)) {
+    let _ = CURRENT.try_with(|pending| record(&mut pending.lock().unwrap()));
+}
+
+fn tls_config() -> rustls::ClientConfig {
+    let provider = rustls::crypto::CryptoProvider::get_default()
+        .cloned()
+        .unwrap_or_else(|| Arc::new(rustls::crypto::ring::default_provider()));
+    let roots = rustls::RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.to_vec() };
+    let mut config = rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .expect("crypto provider supports the default TLS versions")
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    // reqwest only speaks HTTP/1.1 without its http2 feature
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    config.resumption = Resumption::store(Arc::new(TimedSessionStore::default()));
+    config
+}
+
+/// DNS resolver that records how long each lookup took
+struct TimedResolver;
+
+impl Resolve for TimedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let start = Instant::now();
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            let elapsed = start.elapsed();
+            with_current(|pending| pending.dns = Some(elapsed));
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// TLS session store that marks when a handshake begins. rustls looks up a
+/// session to resume as its first step, right after the TCP connection is
+/// established, which splits connecting from the handshake.
+#[derive(Debug)]
+struct TimedSessionStore {
+    inner: ClientSessionMemoryCache,
+}
+
+impl Default for TimedSessionStore {
+    fn default() -> Self {
+        Self { inner: ClientSessionMemoryCache::new(256) }
+    }
+}
+
+impl ClientSessionStore for TimedSessionStore {
+    fn set_kx_hint(&self, server_name: ServerName<'static>, group: NamedGroup) {
+        self.inner.set_kx_hint(server_name, group)
+    }
+
+    fn kx_hint(&self, server_name: &ServerName<'_>) -> Option<NamedGroup> {
+        self.inner.kx_hint(server_name)
+    }
+
+    fn set_tls12_session(&self, server_name: ServerName<'static>, value: Tls12ClientSessionValue) {
+        self.inner.set_tls12_session(server_name, value)
+    }
+
+    fn tls12_session(&self, server_name: &ServerName<'_>) -> Option<Tls12ClientSessionValue> {
+        self.inner.tls12_session(server_name)
+    }
+
+    fn remove_tls12_session(&self, server_name: &ServerName<'static>) {
+        self.inner.remove_tls12_session(server_name)
+    }
+
+    fn insert_tls13_ticket(
+        &self,
+        server_name: ServerName<'static>,
+        value: Tls13ClientSessionValue,
+    ) {
+        self.inner.insert_tls13_ticket(server_name, value)
+    }
+
+    fn take_tls13_ticket(
+        &self,
+        server_name: &ServerName<'static>,
+    ) -> Option<Tls13ClientSessionValue> {
+        let now = Instant::now();
+        with_current(|pending| {
+            pending.handshake_started.get_or_insert(now);
+        });
+        self.inner.take_tls13_ticket(server_name)
+    }
+}
+
+/// Connector layer that records how long establishing a connection took
+#[derive(Clone)]
+struct ConnectTimingLayer;
+
+impl<S> Layer<S> for ConnectTimingLayer {
+    type Service = ConnectTimingService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConnectTimingService { inner }
+    }
+}
+
+#[derive(Clone)]
+struct ConnectTimingService<S> {
+    inner: S,
+}
+
+impl<S, R> Service<R> for ConnectTimingService<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        // Capture the request now: the connection may finish in the
+        // background once the request is served by a pooled one
+        let pending = CURRENT.try_with(Arc::clone).ok();
+        let start = Instant::now();
+        let connecting = self.inner.call(request);
+        Box::pin(async move {
+            let connection = connecting.await;
+            if let (Ok(_), Some(pending)) = (&connection, pending) {
+                let mut pending = pending.lock().unwrap();
+                // The connector resolves DNS itself, so remove it from connect
+                let dns = pending.dns.unwrap_or_default();
+                let total = start.elapsed();
+                match pending.handshake_started.take() {
+                    Some(started) => {
+                        let tcp = started.saturating_duration_since(start);
+                        pending.connect = Some(tcp.saturating_sub(dns));
+                        pending.tls = Some(total.saturating_sub(tcp));
+                    }
+                    None => pending.connect = Some(total.saturating_sub(dns)),
+                }
+            }
+            connection
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::mock_server::spawn_connection_counting_server;
+
+    /// Connector that spends `tcp` connecting, then `tls` on a handshake
+    /// that starts by looking up a session in `store`
+    struct FakeTlsConnector {
+        store: Arc<TimedSessionStore>,
+        tcp: Duration,
+        tls: Duration,
+    }
+
+    impl Service<()> for FakeTlsConnector {
+        type Response = ();
+        type Error = std::io::Error;
+        type Future = Pin<Box<dyn Future<Output = std::io::Result<()>> + Send>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: ()) -> Self::Future {
+            let (store, tcp, tls) = (self.store.clone(), self.tcp, self.tls);
+            Box::pin(async move {
+                tokio::time::sleep(tcp).await;
+                store.take_tls13_ticket(&ServerName::try_from("api.example.com").unwrap());
+                tokio::time::sleep(tls).await;
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_new_connection_reports_breakdown() {
+        let (url, _connections) = spawn_connection_counting_server().await;
+        let url = url.replace("127.0.0.1", "localhost");
+        let fixture = ConnectionTimer::new();
+        let client = fixture
+            .instrument(reqwest::Client::builder())
+            .build()
+            .unwrap();
+
+        let (response, first) = fixture
+            .send(client.get(format!("{url}/api/tags")))
+            .await
+            .unwrap();
+        // Reading the body returns the connection to the pool
+        response.bytes().await.unwrap();
+        let (_, second) = fixture
+            .send(client.get(format!("{url}/api/tags")))
+            .await
+            .unwrap();
+
+        assert!(first.dns.is_some());
+        assert!(first.connect.is_some());
+        assert_eq!(first.tls, None);
+        assert!(!first.connection_reused);
+        assert!(first.ttfb > Duration::ZERO);
+        assert_eq!(second.dns, None);
+        assert_eq!(second.connect, None);
+        assert!(second.connection_reused);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_keep_their_own_timing() {
+        let (url, _connections) = spawn_connection_counting_server().await;
+        let fixture = ConnectionTimer::new();
+        let client = fixture
+            .instrument(reqwest::Client::builder())
+            .build()
+            .unwrap();
+        let (response, _) = fixture
+            .send(client.get(format!("{url}/api/tags")))
+            .await
+            .unwrap();
+        response.bytes().await.unwrap();
+
+        // A different host name needs its own connection, while the original
+        // one reuses the pooled connection at the same time
+        let (fresh, pooled) = tokio::join!(
+            fixture.send(client.get(format!(
+                "{}/api/tags",
+                url.replace("127.0.0.1", "localhost")
+            ))),
+            fixture.send(client.get(format!("{url}/api/tags"))),
+        );
+        let (_, fresh) = fresh.unwrap();
+        let (_, pooled) = pooled.unwrap();
+
+        assert!(fresh.dns.is_some());
+        assert!(fresh.connect.is_some());
+        assert!(!fresh.connection_reused);
+        assert_eq!(pooled.dns, None);
+        assert_eq!(pooled.connect, None);
+        assert!(pooled.connection_reused);
+    }
+
+    #[tokio::test]
+    async fn test_tls_handshake_timed_separately_from_connect() {
+        let mut connector = ConnectTimingLayer.layer(FakeTlsConnector {
+            store: Arc::new(TimedSessionStore::default()),
+            tcp: Duration::from_millis(10),
+            tls: Duration::from_millis(100),
+        });
+        let fixture = TimedRequest::start();
+
+        fixture
+            .scope(async { connector.call(()).await })
+            .await
+            .unwrap();
+
+        let actual = fixture.timing();
+        let connect = actual.connect.unwrap();
+        let tls = actual.tls.unwrap();
+        assert!(connect >= Duration::from_millis(10));
+        assert!(connect < Duration::from_millis(100));
+        assert!(tls >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_instrumented_client_builds() {
+        let actual = ConnectionTimer::new()
+            .instrument(reqwest::Client::builder())
+            .build();
+
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn test_timing_metadata_round_trip() {
+        let fixture = RequestTiming {
+            dns: Some(Duration::from_millis(3)),
+            connect: Some(Duration::from_millis(12)),
+            tls: Some(Duration::from_millis(20)),
+            ttfb: Duration::from_millis(250),
+            connection_reused: false,
+        };
+
+        let metadata: HashMap<String, String> = fixture.to_metadata().into_iter().collect();
+        let actual = RequestTiming::from_metadata(&metadata).unwrap();
+
+        assert_eq!(actual, fixture);
+    }
+}
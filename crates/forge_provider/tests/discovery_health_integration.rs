@@ -3,7 +3,7 @@
 use std::time::Duration;
 
 use forge_provider::config::local_ai::LocalAiConfig;
-use forge_provider::discovery::{DiscoveredModel, ModelDiscoveryService};
+use forge_provider::discovery::ModelDiscoveryService;
 use forge_provider::health::HealthMonitor;
 use forge_provider::test_utils::{
     create_degraded_status, create_healthy_status, create_unhealthy_status, MockHealthMonitor,
@@ -17,25 +17,25 @@ async fn test_discovery_with_health_integration() {
     let config = TestFixtures::local_config();
 
     // Create discovery service
-    let discovery_service = ModelDiscoveryService::new(config.clone()).await.unwrap();
+    let mut discovery_service = ModelDiscoveryService::new(config.clone()).await.unwrap();
 
     // Create health monitor
     let health_monitor = HealthMonitor::new(config).await.unwrap();
 
     // Test that both services can be created and work together
-    let discovery_result = discovery_service.discover_models().await;
+    let discovery_result = discovery_service.discover_all_models().await;
     let health_status = health_monitor.get_health_status().await;
 
-    // Both should work without errors
+    // Both should work without errors; health may be empty before the first
+    // check completes
     assert!(discovery_result.is_ok());
-    assert!(health_status.len() >= 0); // May be empty if no providers
-                                       // configured
+    assert!(health_status.len() <= 1);
 }
 
 #[tokio::test]
 async fn test_discovery_with_mock_health_monitor() {
     // Create mock health monitor
-    let mock_health = MockHealthMonitor::new().await;
+    let mock_health = MockHealthMonitor::new();
 
     // Add providers with different health statuses
     mock_health
@@ -70,24 +70,17 @@ async fn test_discovery_with_mock_health_monitor() {
 #[tokio::test]
 async fn test_discovery_result_with_health_aware_filtering() {
     // Create test discovery result
+    let discovered_models = TestFixtures::discovered_models();
     let discovery_result = TestFixtures::discovery_result();
 
     // Verify the result contains models with different health statuses
-    assert_eq!(discovery_result.discovered_models.len(), 3);
-    assert_eq!(discovery_result.stats.total_models, 3);
+    assert_eq!(discovered_models.len(), 3);
+    assert_eq!(discovery_result.total_models, 3);
 
     // Filter by availability (should exclude unhealthy providers)
-    let available_models: Vec<_> = discovery_result
-        .discovered_models
-        .iter()
-        .filter(|m| m.available)
-        .collect();
+    let available_models: Vec<_> = discovered_models.iter().filter(|m| m.available).collect();
 
-    let unavailable_models: Vec<_> = discovery_result
-        .discovered_models
-        .iter()
-        .filter(|m| !m.available)
-        .collect();
+    let unavailable_models: Vec<_> = discovered_models.iter().filter(|m| !m.available).collect();
 
     assert_eq!(available_models.len(), 2); // Healthy and degraded
     assert_eq!(unavailable_models.len(), 1); // Unhealthy
@@ -104,12 +97,11 @@ async fn test_discovery_result_with_health_aware_filtering() {
 
 #[tokio::test]
 async fn test_discovery_performance_metrics() {
-    // Create test discovery result
-    let discovery_result = TestFixtures::discovery_result();
+    // Create test discovered models
+    let discovered_models = TestFixtures::discovered_models();
 
     // Verify response time metrics
-    let response_times: Vec<_> = discovery_result
-        .discovered_models
+    let response_times: Vec<_> = discovered_models
         .iter()
         .filter_map(|m| m.response_time)
         .collect();
@@ -135,26 +127,21 @@ async fn test_discovery_performance_metrics() {
 #[tokio::test]
 async fn test_discovery_provider_distribution() {
     // Create test discovery result
+    let discovered_models = TestFixtures::discovered_models();
     let discovery_result = TestFixtures::discovery_result();
 
     // Analyze provider distribution
     let mut provider_counts = std::collections::HashMap::new();
-    for model in &discovery_result.discovered_models {
+    for model in &discovered_models {
         *provider_counts.entry(&model.provider).or_insert(0) += 1;
     }
 
     // Verify we have multiple providers
-    assert!(provider_counts.len() >= 1);
-
-    // Verify stats match actual counts
-    assert_eq!(
-        discovery_result.stats.total_providers,
-        provider_counts.len()
-    );
+    assert_eq!(provider_counts.len(), 2);
 
     // Verify model counts
     let total_models: usize = provider_counts.values().sum();
-    assert_eq!(discovery_result.stats.total_models, total_models);
+    assert_eq!(discovery_result.total_models, total_models);
 }
 
 #[tokio::test]
@@ -173,8 +160,8 @@ async fn test_health_monitor_initialization() {
         let monitor = health_monitor.unwrap();
         let health_status = monitor.get_health_status().await;
 
-        // Should not crash and should return a valid map
-        assert!(health_status.len() >= 0);
+        // Should not crash and should only report configured providers
+        assert!(health_status.len() <= 2);
     }
 }
 
@@ -191,15 +178,14 @@ async fn test_discovery_service_initialization() {
         let discovery_service = ModelDiscoveryService::new(config).await;
         assert!(discovery_service.is_ok());
 
-        let service = discovery_service.unwrap();
-        let result = service.discover_models().await;
+        let mut service = discovery_service.unwrap();
+        let result = service.discover_all_models().await;
 
         // Should not crash and should return a valid result
         assert!(result.is_ok());
 
         let discovery_result = result.unwrap();
-        assert!(discovery_result.stats.total_models >= 0);
-        assert!(discovery_result.stats.available_models <= discovery_result.stats.total_models);
+        assert!(discovery_result.available_models <= discovery_result.total_models);
     }
 }
 
@@ -209,21 +195,26 @@ async fn test_end_to_end_discovery_workflow() {
     let config = TestFixtures::local_config();
 
     // Initialize services
-    let discovery_service = ModelDiscoveryService::new(config.clone()).await.unwrap();
+    let mut discovery_service = ModelDiscoveryService::new(config.clone()).await.unwrap();
     let health_monitor = HealthMonitor::new(config).await.unwrap();
 
     // Perform discovery
-    let discovery_result = discovery_service.discover_models().await.unwrap();
+    let discovery_result = discovery_service.discover_all_models().await.unwrap();
+    let discovered_models = discovery_service.get_discovered_models();
+    let stats = discovery_service.get_discovery_stats();
 
     // Check health status
     let health_status = health_monitor.get_health_status().await;
 
     // Verify integration
-    assert!(discovery_result.stats.last_discovery.is_some());
-    assert!(health_status.len() >= 0);
+    assert_eq!(
+        stats.last_discovery.is_some(),
+        !discovered_models.is_empty()
+    );
+    assert!(health_status.len() <= 1);
 
     // If we have discovered models, verify they have health information
-    for model in &discovery_result.discovered_models {
+    for model in &discovered_models {
         // Each model should have provider health information
         assert!(!model.provider.is_empty());
 
@@ -234,20 +225,15 @@ async fn test_end_to_end_discovery_workflow() {
     }
 
     // Verify statistics consistency
-    let available_count = discovery_result
-        .discovered_models
-        .iter()
-        .filter(|m| m.available)
-        .count();
-    assert_eq!(discovery_result.stats.available_models, available_count);
+    let available_count = discovered_models.iter().filter(|m| m.available).count();
+    assert_eq!(discovery_result.available_models, available_count);
 
-    let provider_count = discovery_result
-        .discovered_models
+    let provider_count = discovered_models
         .iter()
         .map(|m| &m.provider)
         .collect::<std::collections::HashSet<_>>()
         .len();
-    assert_eq!(discovery_result.stats.total_providers, provider_count);
+    assert_eq!(stats.total_providers, provider_count);
 }
 
 #[tokio::test]
@@ -256,12 +242,12 @@ async fn test_concurrent_discovery_and_health_checks() {
     let config = TestFixtures::local_config();
 
     // Create services
-    let discovery_service = ModelDiscoveryService::new(config.clone()).await.unwrap();
+    let mut discovery_service = ModelDiscoveryService::new(config.clone()).await.unwrap();
     let health_monitor = HealthMonitor::new(config).await.unwrap();
 
     // Run discovery and health checks concurrently
     let (discovery_result, health_status) = tokio::join!(
-        discovery_service.discover_models(),
+        discovery_service.discover_all_models(),
         health_monitor.get_health_status()
     );
 
@@ -270,11 +256,8 @@ async fn test_concurrent_discovery_and_health_checks() {
     let discovery = discovery_result.unwrap();
 
     // Verify results
-    assert!(discovery.stats.total_models >= 0);
-    assert!(health_status.len() >= 0);
-
-    // No race conditions should occur
-    assert!(discovery.stats.last_discovery.is_some());
+    assert!(discovery.available_models <= discovery.total_models);
+    assert!(health_status.len() <= 1);
 }
 
 #[tokio::test]
@@ -283,15 +266,15 @@ async fn test_discovery_error_handling() {
 
     // Empty configuration should not crash
     let empty_config = LocalAiConfig::new();
-    let discovery_service = ModelDiscoveryService::new(empty_config).await.unwrap();
-    let result = discovery_service.discover_models().await;
+    let mut discovery_service = ModelDiscoveryService::new(empty_config).await.unwrap();
+    let result = discovery_service.discover_all_models().await;
 
     // Should succeed but return empty results
     assert!(result.is_ok());
     let discovery_result = result.unwrap();
-    assert_eq!(discovery_result.stats.total_models, 0);
-    assert_eq!(discovery_result.stats.available_models, 0);
-    assert_eq!(discovery_result.stats.total_providers, 0);
+    assert_eq!(discovery_result.total_models, 0);
+    assert_eq!(discovery_result.available_models, 0);
+    assert_eq!(discovery_service.get_discovery_stats().total_providers, 0);
 }
 
 #[tokio::test]
@@ -4,7 +4,7 @@ use std::time::Duration;
 
 use forge_provider::config::fallback::FallbackConfig;
 use forge_provider::config::local_ai::LocalAiConfig;
-use forge_provider::discovery::{ModelDiscoveryResult, ModelDiscoveryService};
+use forge_provider::discovery::ModelDiscoveryService;
 use forge_provider::health::HealthMonitor;
 use forge_provider::selection::{
     ProviderSelector, ProviderType, SelectionContext, UserPreferences,
@@ -23,7 +23,7 @@ async fn test_complete_local_provider_workflow() {
     let fallback_config = FallbackConfig::default();
 
     // Step 2: Initialize services
-    let discovery_service = ModelDiscoveryService::new(local_config.clone())
+    let mut discovery_service = ModelDiscoveryService::new(local_config.clone())
         .await
         .unwrap();
     let health_monitor = HealthMonitor::new(local_config.clone()).await.unwrap();
@@ -35,7 +35,7 @@ async fn test_complete_local_provider_workflow() {
     provider_selector.initialize().await.unwrap();
 
     // Step 4: Discover available models
-    let discovery_result = discovery_service.discover_models().await.unwrap();
+    let discovery_result = discovery_service.discover_all_models().await.unwrap();
 
     // Step 5: Check health status
     let health_status = health_monitor.get_health_status().await;
@@ -48,8 +48,8 @@ async fn test_complete_local_provider_workflow() {
     let selection_result = provider_selector.select_provider(selection_context).await;
 
     // Verify the complete workflow
-    assert!(discovery_result.stats.total_models >= 0);
-    assert!(health_status.len() >= 0);
+    assert!(discovery_result.available_models <= discovery_result.total_models);
+    assert!(health_status.len() <= 1);
 
     // Selection may fail if no real providers are available, but shouldn't crash
     match selection_result {
@@ -61,9 +61,7 @@ async fn test_complete_local_provider_workflow() {
             provider_selector.record_success(&selection.provider_name, Duration::from_millis(200));
 
             // Verify metrics were updated
-            let metrics = provider_selector.get_provider_metric(&selection.provider_name);
-            if metrics.is_some() {
-                let metrics = metrics.unwrap();
+            if let Some(metrics) = provider_selector.get_provider_metric(&selection.provider_name) {
                 assert!(metrics.successful_requests > 0);
             }
         }
@@ -81,7 +79,7 @@ async fn test_multi_provider_workflow_with_health_states() {
     let fallback_config = FallbackConfig::default();
 
     // Initialize services
-    let discovery_service = ModelDiscoveryService::new(local_config.clone())
+    let mut discovery_service = ModelDiscoveryService::new(local_config.clone())
         .await
         .unwrap();
     let mut provider_selector = ProviderSelector::new(local_config, fallback_config)
@@ -90,7 +88,7 @@ async fn test_multi_provider_workflow_with_health_states() {
     provider_selector.initialize().await.unwrap();
 
     // Discover models
-    let discovery_result = discovery_service.discover_models().await.unwrap();
+    let discovery_result = discovery_service.discover_all_models().await.unwrap();
 
     // Test selection with different scenarios
     let scenarios = vec![
@@ -126,7 +124,7 @@ async fn test_multi_provider_workflow_with_health_states() {
     }
 
     // Verify discovery worked
-    assert!(discovery_result.stats.total_models >= 0);
+    assert!(discovery_result.available_models <= discovery_result.total_models);
 
     // Verify provider metrics were updated
     let metrics = provider_selector.get_provider_metrics();
@@ -242,7 +240,7 @@ async fn test_workflow_performance() {
     let local_config = TestFixtures::local_config();
     let fallback_config = FallbackConfig::default();
 
-    let discovery_service = ModelDiscoveryService::new(local_config.clone())
+    let mut discovery_service = ModelDiscoveryService::new(local_config.clone())
         .await
         .unwrap();
     let mut provider_selector = ProviderSelector::new(local_config, fallback_config)
@@ -252,40 +250,25 @@ async fn test_workflow_performance() {
 
     // Measure discovery performance
     let start_time = std::time::Instant::now();
-    let discovery_result = discovery_service.discover_models().await.unwrap();
+    let discovery_result = discovery_service.discover_all_models().await.unwrap();
     let discovery_time = start_time.elapsed();
 
     println!("Discovery completed in {:?}", discovery_time);
     assert!(discovery_time < Duration::from_secs(10)); // Should be reasonably fast
 
     // Measure selection performance
-    let selection_times = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
-    let mut handles = Vec::new();
+    let mut times = Vec::new();
 
     for i in 0..10 {
-        let mut selector = provider_selector.clone();
-        let times = selection_times.clone();
+        let start = std::time::Instant::now();
 
-        let handle = tokio::spawn(async move {
-            let start = std::time::Instant::now();
+        let context = SelectionContext::new(format!("model-{}", i));
+        let _result = provider_selector.select_provider(context).await;
 
-            let context = SelectionContext::new(format!("model-{}", i));
-            let _result = selector.select_provider(context).await;
-
-            let elapsed = start.elapsed();
-            times.lock().unwrap().push(elapsed);
-        });
-
-        handles.push(handle);
-    }
-
-    // Wait for all selections to complete
-    for handle in handles {
-        handle.await.unwrap();
+        times.push(start.elapsed());
     }
 
     // Analyze performance
-    let times = selection_times.lock().unwrap();
     if !times.is_empty() {
         let total_time: Duration = times.iter().sum();
         let avg_time = total_time / times.len() as u32;
@@ -303,17 +286,17 @@ async fn test_workflow_performance() {
     }
 
     // Verify discovery results
-    assert!(discovery_result.stats.total_models >= 0);
+    assert!(discovery_result.available_models <= discovery_result.total_models);
 }
 
 /// Test workflow with configuration changes
 #[tokio::test]
 async fn test_workflow_with_configuration_changes() {
     // Start with basic configuration
-    let mut local_config = TestFixtures::local_config();
+    let local_config = TestFixtures::local_config();
     let fallback_config = FallbackConfig::default();
 
-    let discovery_service = ModelDiscoveryService::new(local_config.clone())
+    let mut discovery_service = ModelDiscoveryService::new(local_config.clone())
         .await
         .unwrap();
     let mut provider_selector =
@@ -323,17 +306,17 @@ async fn test_workflow_with_configuration_changes() {
     provider_selector.initialize().await.unwrap();
 
     // Initial discovery
-    let initial_result = discovery_service.discover_models().await.unwrap();
+    let initial_result = discovery_service.discover_all_models().await.unwrap();
     let initial_metrics_count = provider_selector.get_provider_metrics().len();
 
     println!(
         "Initial state: {} models, {} providers",
-        initial_result.stats.total_models, initial_metrics_count
+        initial_result.total_models, initial_metrics_count
     );
 
     // Simulate configuration change by creating new services
     let multi_config = TestFixtures::multi_provider_config();
-    let new_discovery = ModelDiscoveryService::new(multi_config.clone())
+    let mut new_discovery = ModelDiscoveryService::new(multi_config.clone())
         .await
         .unwrap();
     let mut new_selector = ProviderSelector::new(multi_config, fallback_config)
@@ -342,12 +325,12 @@ async fn test_workflow_with_configuration_changes() {
     new_selector.initialize().await.unwrap();
 
     // New discovery with updated configuration
-    let updated_result = new_discovery.discover_models().await.unwrap();
+    let updated_result = new_discovery.discover_all_models().await.unwrap();
     let updated_metrics_count = new_selector.get_provider_metrics().len();
 
     println!(
         "Updated state: {} models, {} providers",
-        updated_result.stats.total_models, updated_metrics_count
+        updated_result.total_models, updated_metrics_count
     );
 
     // Verify configuration changes were applied
@@ -376,7 +359,7 @@ async fn test_workflow_error_handling() {
     let fallback_config = FallbackConfig::default();
 
     // Services should initialize without crashing
-    let discovery_service = ModelDiscoveryService::new(empty_config.clone())
+    let mut discovery_service = ModelDiscoveryService::new(empty_config.clone())
         .await
         .unwrap();
     let health_monitor = HealthMonitor::new(empty_config.clone()).await.unwrap();
@@ -389,9 +372,9 @@ async fn test_workflow_error_handling() {
     assert!(init_result.is_ok());
 
     // Discovery should work but return empty results
-    let discovery_result = discovery_service.discover_models().await.unwrap();
-    assert_eq!(discovery_result.stats.total_models, 0);
-    assert_eq!(discovery_result.stats.available_models, 0);
+    let discovery_result = discovery_service.discover_all_models().await.unwrap();
+    assert_eq!(discovery_result.total_models, 0);
+    assert_eq!(discovery_result.available_models, 0);
 
     // Health monitoring should work but return empty status
     let health_status = health_monitor.get_health_status().await;
@@ -419,7 +402,7 @@ async fn test_workflow_error_handling() {
 #[tokio::test]
 async fn test_workflow_with_mock_health_monitor() {
     // Create mock health monitor with controlled states
-    let mock_health = MockHealthMonitor::new().await;
+    let mock_health = MockHealthMonitor::new();
 
     // Add providers with different health states
     mock_health
@@ -450,20 +433,12 @@ async fn test_workflow_with_mock_health_monitor() {
     assert_eq!(sorted_providers[2].0, "ollama-unhealthy");
 
     // Test discovery with mock health data
-    let discovery_result = TestFixtures::discovery_result();
+    let discovered_models = TestFixtures::discovered_models();
 
     // Verify health-aware filtering
-    let available_models: Vec<_> = discovery_result
-        .discovered_models
-        .iter()
-        .filter(|m| m.available)
-        .collect();
-
-    let unavailable_models: Vec<_> = discovery_result
-        .discovered_models
-        .iter()
-        .filter(|m| !m.available)
-        .collect();
+    let available_models: Vec<_> = discovered_models.iter().filter(|m| m.available).collect();
+
+    let unavailable_models: Vec<_> = discovered_models.iter().filter(|m| !m.available).collect();
 
     // Should have both available and unavailable models based on health
     assert!(!available_models.is_empty() || !unavailable_models.is_empty());
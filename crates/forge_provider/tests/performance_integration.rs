@@ -117,10 +117,7 @@ async fn test_resource_monitoring_integration() {
     // With default mock values, should not have critical recommendations
     let critical_count = recommendations
         .iter()
-        .filter(|r| {
-            r.severity
-                == forge_provider::performance::optimization::RecommendationSeverity::Critical
-        })
+        .filter(|r| r.severity == forge_provider::performance::RecommendationSeverity::Critical)
         .count();
     assert_eq!(critical_count, 0);
 }
@@ -197,13 +194,13 @@ async fn test_optimization_recommendations_integration() {
     let has_network_rec = recommendations.iter().any(|r| {
         matches!(
             r.recommendation_type,
-            forge_provider::performance::optimization::RecommendationType::Network
+            forge_provider::performance::RecommendationType::Network
         )
     });
     let has_provider_rec = recommendations.iter().any(|r| {
         matches!(
             r.recommendation_type,
-            forge_provider::performance::optimization::RecommendationType::ProviderSelection
+            forge_provider::performance::RecommendationType::ProviderSelection
         )
     });
 
@@ -213,7 +210,7 @@ async fn test_optimization_recommendations_integration() {
     // Check recommendation priorities
     let critical_recommendations: Vec<_> = recommendations
         .iter()
-        .filter(|r| r.priority == forge_provider::performance::optimization::Priority::Critical)
+        .filter(|r| r.priority == forge_provider::performance::Priority::Critical)
         .collect();
     assert!(!critical_recommendations.is_empty());
 }
@@ -234,7 +231,7 @@ async fn test_cli_integration() {
 
     // Test metrics command
     let metrics_result = cli
-        .execute_command(PerformanceCommand::Metrics { provider_name: None })
+        .execute_command(PerformanceCommand::Metrics { provider_name: None, model_name: None })
         .await;
     assert!(metrics_result.is_ok());
     let metrics_output = metrics_result.unwrap();
@@ -280,7 +277,7 @@ fn test_cli_command_parsing() {
 
     let metrics_cmd = parse_performance_command("metrics");
     assert!(metrics_cmd.is_ok());
-    if let PerformanceCommand::Metrics { provider_name } = metrics_cmd.unwrap() {
+    if let PerformanceCommand::Metrics { provider_name, .. } = metrics_cmd.unwrap() {
         assert_eq!(provider_name, None);
     } else {
         panic!("Expected Metrics command");
@@ -288,7 +285,7 @@ fn test_cli_command_parsing() {
 
     let metrics_provider_cmd = parse_performance_command("metrics ollama");
     assert!(metrics_provider_cmd.is_ok());
-    if let PerformanceCommand::Metrics { provider_name } = metrics_provider_cmd.unwrap() {
+    if let PerformanceCommand::Metrics { provider_name, .. } = metrics_provider_cmd.unwrap() {
         assert_eq!(provider_name, Some("ollama".to_string()));
     } else {
         panic!("Expected Metrics command with provider");
@@ -370,7 +367,7 @@ async fn test_end_to_end_performance_workflow() {
     }
 
     // Simulate some failed Anthropic requests
-    for i in 0..3 {
+    for _ in 0..3 {
         let measurement =
             PerformanceMeasurement::new("anthropic".to_string(), RequestType::Inference)
                 .complete_failure()
@@ -389,7 +386,7 @@ async fn test_end_to_end_performance_workflow() {
 
     // 4. Get detailed metrics
     let metrics_result = cli
-        .execute_command(PerformanceCommand::Metrics { provider_name: None })
+        .execute_command(PerformanceCommand::Metrics { provider_name: None, model_name: None })
         .await
         .unwrap();
     assert!(metrics_result.success);
@@ -1,7 +1,8 @@
 use std::time::Duration;
 
 use forge_provider::config::local_ai::{
-    HealthCheckConfig, LocalAiConfig, LocalProviderConfig, ProviderSpecificConfig,
+    HealthCheckConfig, HealthCheckDepth, LocalAiConfig, LocalProviderConfig,
+    ProviderSpecificConfig, SaturationPolicy,
 };
 use forge_provider::discovery::ModelDiscoveryService;
 use pretty_assertions::assert_eq;
@@ -48,7 +49,15 @@ async fn test_local_model_discovery_with_custom_ollama_config() {
             timeout_seconds: 5,
             failure_threshold: 3,
             success_threshold: 2,
+            max_backoff_seconds: 300,
+            jitter_fraction: None,
+            depth: HealthCheckDepth::Shallow,
+            degraded_response_time_threshold_ms: None,
         },
+        max_concurrent_requests: 0,
+        saturation_policy: SaturationPolicy::Queue,
+        queue_timeout_ms: 30_000,
+        tags: Vec::new(),
     };
 
     let fixture = LocalAiConfig::new()
@@ -84,9 +93,7 @@ async fn test_local_model_discovery_all_models() {
     let result = discovery_result.unwrap().unwrap();
 
     // Basic validation of the discovery result structure
-    assert!(result.total_models >= 0);
-    assert!(result.healthy_providers >= 0);
-    assert!(result.available_models >= 0);
+    assert!(result.healthy_providers <= 1);
     assert!(result.discovery_duration > Duration::from_millis(0));
 
     // Available models should not exceed total models
@@ -100,7 +107,7 @@ async fn test_local_model_discovery_get_discovered_models() {
 
     // Test getting discovered models (should return empty list if no discovery ran)
     let discovered_models = service.get_discovered_models();
-    assert!(discovered_models.len() >= 0); // Should be 0 or more
+    assert!(discovered_models.is_empty());
 }
 
 #[tokio::test]
@@ -110,7 +117,7 @@ async fn test_local_model_discovery_get_available_models() {
 
     // Test getting available models
     let available_models = service.get_available_models();
-    assert!(available_models.len() >= 0); // Should be 0 or more
+    assert!(available_models.is_empty());
 }
 
 #[tokio::test]
@@ -120,7 +127,7 @@ async fn test_local_model_discovery_get_provider_models() {
 
     // Test getting models from a specific provider
     let provider_models = service.get_provider_models("ollama");
-    assert!(provider_models.len() >= 0); // Should be 0 or more
+    assert!(provider_models.is_empty());
 }
 
 #[tokio::test]
@@ -135,7 +142,7 @@ async fn test_local_model_discovery_is_model_available() {
     let is_available = service.is_model_available(&model_id);
 
     // Should return false since no discovery has been performed
-    assert_eq!(is_available, false);
+    assert!(!is_available);
 }
 
 #[tokio::test]
@@ -145,7 +152,7 @@ async fn test_local_model_discovery_get_provider_health_status() {
 
     // Test getting provider health status
     let health_status = service.get_provider_health_status().await;
-    assert!(health_status.len() >= 0); // Should be 0 or more providers
+    assert!(health_status.len() <= 1); // At most the one configured provider
 }
 
 #[tokio::test]
@@ -160,9 +167,8 @@ async fn test_local_model_discovery_refresh_discovery() {
     let result = refresh_result.unwrap().unwrap();
 
     // Basic validation of the refresh result
-    assert!(result.total_models >= 0);
-    assert!(result.healthy_providers >= 0);
-    assert!(result.available_models >= 0);
+    assert!(result.healthy_providers <= 1);
+    assert!(result.available_models <= result.total_models);
 }
 
 #[tokio::test]
@@ -173,11 +179,10 @@ async fn test_local_model_discovery_get_discovery_stats() {
     // Test getting discovery statistics
     let stats = service.get_discovery_stats();
 
-    // Basic validation of stats structure
-    assert!(stats.total_models >= 0);
-    assert!(stats.available_models >= 0);
-    assert!(stats.total_providers >= 0);
-    assert!(stats.available_models <= stats.total_models);
+    // Nothing has been discovered yet
+    assert_eq!(stats.total_models, 0);
+    assert_eq!(stats.available_models, 0);
+    assert_eq!(stats.total_providers, 0);
 }
 
 #[tokio::test]
@@ -195,6 +200,10 @@ async fn test_local_model_discovery_multiple_providers() {
             user_agent: Some("test-agent-1".to_string()),
         },
         health_check: HealthCheckConfig::default(),
+        max_concurrent_requests: 0,
+        saturation_policy: SaturationPolicy::Queue,
+        queue_timeout_ms: 30_000,
+        tags: Vec::new(),
     };
 
     let ollama_config_2 = LocalProviderConfig {
@@ -210,6 +219,10 @@ async fn test_local_model_discovery_multiple_providers() {
             user_agent: Some("test-agent-2".to_string()),
         },
         health_check: HealthCheckConfig::default(),
+        max_concurrent_requests: 0,
+        saturation_policy: SaturationPolicy::Queue,
+        queue_timeout_ms: 30_000,
+        tags: Vec::new(),
     };
 
     let fixture = LocalAiConfig::new()
@@ -226,9 +239,8 @@ async fn test_local_model_discovery_multiple_providers() {
     let result = discovery_result.unwrap().unwrap();
 
     // Basic validation
-    assert!(result.total_models >= 0);
-    assert!(result.healthy_providers >= 0);
-    assert!(result.available_models >= 0);
+    assert!(result.healthy_providers <= 2);
+    assert!(result.available_models <= result.total_models);
 }
 
 #[tokio::test]
@@ -247,6 +259,10 @@ async fn test_local_model_discovery_validation() {
             user_agent: None,
         },
         health_check: HealthCheckConfig::default(),
+        max_concurrent_requests: 0,
+        saturation_policy: SaturationPolicy::Queue,
+        queue_timeout_ms: 30_000,
+        tags: Vec::new(),
     };
 
     let fixture = LocalAiConfig::new()
@@ -1,7 +1,8 @@
 use std::time::Duration;
 
 use forge_provider::config::local_ai::{
-    HealthCheckConfig, LocalAiConfig, LocalProviderConfig, ProviderSpecificConfig,
+    HealthCheckConfig, HealthCheckDepth, LocalAiConfig, LocalProviderConfig,
+    ProviderSpecificConfig, SaturationPolicy,
 };
 use forge_provider::discovery::ModelDiscoveryService;
 use pretty_assertions::assert_eq;
@@ -32,7 +33,15 @@ async fn test_local_model_discovery_with_real_ollama() {
             timeout_seconds: 3, // Short timeout for testing
             failure_threshold: 1,
             success_threshold: 1,
+            max_backoff_seconds: 300,
+            jitter_fraction: None,
+            depth: HealthCheckDepth::Shallow,
+            degraded_response_time_threshold_ms: None,
         },
+        max_concurrent_requests: 0,
+        saturation_policy: SaturationPolicy::Queue,
+        queue_timeout_ms: 30_000,
+        tags: Vec::new(),
     };
 
     let config = LocalAiConfig::new()
@@ -160,7 +169,15 @@ async fn test_discovery_multiple_ollama_instances() {
             timeout_seconds: 2,
             failure_threshold: 1,
             success_threshold: 1,
+            max_backoff_seconds: 300,
+            jitter_fraction: None,
+            depth: HealthCheckDepth::Shallow,
+            degraded_response_time_threshold_ms: None,
         },
+        max_concurrent_requests: 0,
+        saturation_policy: SaturationPolicy::Queue,
+        queue_timeout_ms: 30_000,
+        tags: Vec::new(),
     };
 
     let ollama_config_2 = LocalProviderConfig {
@@ -180,7 +197,15 @@ async fn test_discovery_multiple_ollama_instances() {
             timeout_seconds: 2,
             failure_threshold: 1,
             success_threshold: 1,
+            max_backoff_seconds: 300,
+            jitter_fraction: None,
+            depth: HealthCheckDepth::Shallow,
+            degraded_response_time_threshold_ms: None,
         },
+        max_concurrent_requests: 0,
+        saturation_policy: SaturationPolicy::Queue,
+        queue_timeout_ms: 30_000,
+        tags: Vec::new(),
     };
 
     let config = LocalAiConfig::new()
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use forge_app::Services;
+use forge_provider::selection::ContextLengths;
 
 use crate::app_config::ForgeConfigService;
 use crate::attachment::ForgeChatRequest;
@@ -78,7 +79,12 @@ impl<
         let conversation_service = Arc::new(ForgeConversationService::new(mcp_service.clone()));
         let config_service = Arc::new(ForgeConfigService::new(infra.clone()));
         let auth_service = Arc::new(ForgeAuthService::new(infra.clone()));
-        let chat_service = Arc::new(ForgeProviderService::new(infra.clone()));
+        // Discovery reports model context windows that provider selection
+        // routes long prompts by
+        let context_lengths = ContextLengths::default();
+        let chat_service = Arc::new(
+            ForgeProviderService::new(infra.clone()).with_context_lengths(context_lengths.clone()),
+        );
         let file_create_service = Arc::new(ForgeFsCreate::new(infra.clone()));
         let file_read_service = Arc::new(ForgeFsRead::new(infra.clone()));
         let file_search_service = Arc::new(ForgeFsSearch::new(infra.clone()));
@@ -88,7 +94,9 @@ impl<
         let shell_service = Arc::new(ForgeShell::new(infra.clone()));
         let fetch_service = Arc::new(ForgeFetch::new());
         let followup_service = Arc::new(ForgeFollowup::new(infra.clone()));
-        let provider_service = Arc::new(ForgeProviderRegistry::new(infra.clone()));
+        let provider_service = Arc::new(
+            ForgeProviderRegistry::new(infra.clone()).with_context_lengths(context_lengths),
+        );
         let env_service = Arc::new(ForgeEnvironmentService::new(infra));
         Self {
             conversation_service,
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
@@ -7,7 +8,12 @@ use forge_app::domain::{
 };
 use forge_app::{AppConfig, ProviderService};
 use forge_provider::config::local_ai::LocalAiConfig;
-use forge_provider::discovery::ModelDiscoveryService;
+use forge_provider::discovery::{render_model_summary, ModelDiscoveryService};
+use forge_provider::performance::{
+    AdmissionController, IdleEvictionPolicy, ModelLoadingOptimizer, PerformanceConfig,
+    PerformanceMonitor,
+};
+use forge_provider::selection::{ConcurrencyLimits, ContextLengths};
 use forge_provider::Client;
 use tokio::sync::Mutex;
 use tracing::{debug, error, info, warn};
@@ -17,10 +23,21 @@ use crate::EnvironmentInfra;
 #[derive(Clone)]
 pub struct ForgeProviderService {
     retry_config: Arc<RetryConfig>,
-    cached_client: Arc<Mutex<Option<Client>>>,
+    /// One client per provider, so requests routed to different providers
+    /// are sent to the right server
+    cached_clients: Arc<Mutex<HashMap<Provider, Client>>>,
     cached_models: Arc<Mutex<Option<Vec<Model>>>>,
     cached_local_models: Arc<Mutex<Option<Vec<Model>>>>,
     local_discovery: Arc<Mutex<Option<ModelDiscoveryService>>>,
+    /// Local providers requests may be routed to, naming the provider each
+    /// client's requests count against
+    local_config: Arc<LocalAiConfig>,
+    admission: Arc<AdmissionController>,
+    concurrency: ConcurrencyLimits,
+    optimizer: Arc<ModelLoadingOptimizer>,
+    idle_eviction: Arc<IdleEvictionPolicy>,
+    performance: Arc<PerformanceMonitor>,
+    context_lengths: ContextLengths,
     version: String,
     timeout_config: HttpConfig,
 }
@@ -30,36 +47,75 @@ impl ForgeProviderService {
         let env = infra.get_environment();
         let version = env.version();
         let retry_config = Arc::new(env.retry_config);
+        // Metrics outlive the process so they build up across CLI runs
+        let performance = PerformanceMonitor::new(
+            PerformanceConfig::default()
+                .persistence_path(env.base_path.join("performance_metrics.json")),
+        );
+        let local_config = LocalAiConfig::with_default_ollama();
+        let admission = AdmissionController::default();
+        admission.register_local_providers(&local_config);
         Self {
             retry_config,
-            cached_client: Arc::new(Mutex::new(None)),
+            cached_clients: Arc::new(Mutex::new(HashMap::new())),
             cached_models: Arc::new(Mutex::new(None)),
             cached_local_models: Arc::new(Mutex::new(None)),
             local_discovery: Arc::new(Mutex::new(None)),
+            admission: Arc::new(admission),
+            concurrency: ConcurrencyLimits::new(&local_config),
+            optimizer: Arc::new(ModelLoadingOptimizer::new(Default::default())),
+            idle_eviction: Arc::new(IdleEvictionPolicy::new(
+                local_config.settings.idle_eviction.clone(),
+            )),
+            local_config: Arc::new(local_config),
+            performance: Arc::new(performance),
+            context_lengths: ContextLengths::default(),
             version,
             timeout_config: env.http,
         }
     }
 
+    /// Report the context windows of discovered local models to
+    /// `context_lengths`, such as those provider selection routes by
+    pub fn with_context_lengths(mut self, context_lengths: ContextLengths) -> Self {
+        self.context_lengths = context_lengths;
+        self
+    }
+
     async fn client(&self, provider: Provider) -> Result<Client> {
-        let mut client_guard = self.cached_client.lock().await;
-
-        match client_guard.as_ref() {
-            Some(client) => Ok(client.clone()),
-            None => {
-                // Client doesn't exist, create new one
-                let client = Client::new(
-                    provider,
-                    self.retry_config.clone(),
-                    &self.version,
-                    &self.timeout_config,
-                )?;
-
-                // Cache the new client
-                *client_guard = Some(client.clone());
-                Ok(client)
+        let mut clients_guard = self.cached_clients.lock().await;
+        if let Some(client) = clients_guard.get(&provider) {
+            return Ok(client.clone());
+        }
+
+        // Client doesn't exist for this provider, create new one
+        let local_name = self
+            .local_config
+            .provider_name_for(&provider)
+            .map(str::to_string);
+        let mut client = Client::new(
+            provider.clone(),
+            self.retry_config.clone(),
+            &self.version,
+            &self.timeout_config,
+        )?;
+        // Only local servers queue requests behind a small number of
+        // generation slots, so only they are admission controlled
+        if let Some(name) = local_name {
+            if let Err(e) = self.performance.start().await {
+                warn!("Failed to start performance monitoring: {}", e);
             }
+            client = client
+                .with_admission(self.admission.clone(), name.as_str())
+                .with_concurrency_limits(self.concurrency.clone(), name.as_str())
+                .with_model_optimizer(self.optimizer.clone(), name.as_str())
+                .with_idle_eviction(self.idle_eviction.clone(), name.as_str())
+                .with_performance_monitor(self.performance.clone(), name);
         }
+
+        // Cache the new client
+        clients_guard.insert(provider, client.clone());
+        Ok(client)
     }
 
     async fn get_local_ai_config(app_config: &AppConfig) -> LocalAiConfig {
@@ -90,6 +146,16 @@ impl ForgeProviderService {
             match ModelDiscoveryService::new(local_config).await {
                 Ok(discovery) => {
                     info!("Local AI model discovery service initialized successfully");
+                    let mut discovery = discovery
+                        .with_model_optimizer(self.optimizer.clone())
+                        .with_idle_eviction(self.idle_eviction.clone())
+                        .with_admission(self.admission.clone())
+                        .with_context_lengths(self.context_lengths.clone());
+                    // Periodic health checks keep admission capacities
+                    // following server load; starting also runs idle eviction
+                    if let Err(e) = discovery.start().await {
+                        warn!("Failed to start local AI discovery service: {}", e);
+                    }
                     *discovery_guard = Some(discovery);
                 }
                 Err(e) => {
@@ -134,7 +200,7 @@ impl ForgeProviderService {
             match discovery.discover_all_models().await {
                 Ok(_discovery_result) => {
                     // Get the discovered models from the service
-                    let discovered_models = discovery.get_available_models();
+                    let discovered_models = discovery.get_servable_models();
 
                     let local_models: Vec<Model> = discovered_models
                         .into_iter()
@@ -230,4 +296,96 @@ impl ProviderService for ForgeProviderService {
         );
         Ok(all_models)
     }
+
+    async fn model_summary(&self, app_config: AppConfig) -> Result<Option<String>> {
+        self.discover_local_models(&app_config).await?;
+
+        let discovery_guard = self.local_discovery.lock().await;
+        let rows = discovery_guard
+            .as_ref()
+            .map(ModelDiscoveryService::summarize)
+            .unwrap_or_default();
+        Ok((!rows.is_empty()).then(|| render_model_summary(&rows)))
+    }
+
+    async fn flush_performance_metrics(&self) -> Result<()> {
+        self.performance.shutdown().await
+    }
+
+    async fn health_dump(&self, app_config: AppConfig) -> Result<Option<String>> {
+        self.discover_local_models(&app_config).await?;
+
+        let discovery_guard = self.local_discovery.lock().await;
+        match discovery_guard.as_ref() {
+            Some(discovery) => Ok(Some(discovery.health_snapshot().await.to_json()?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use forge_app::domain::Environment;
+    use pretty_assertions::assert_eq;
+    use url::Url;
+
+    use super::*;
+
+    struct MockEnvironmentInfra;
+
+    impl EnvironmentInfra for MockEnvironmentInfra {
+        fn get_environment(&self) -> Environment {
+            Environment {
+                os: "linux".to_string(),
+                pid: 12345,
+                cwd: PathBuf::from("/home/user/project"),
+                home: Some(PathBuf::from("/home/user")),
+                shell: "/bin/bash".to_string(),
+                base_path: PathBuf::from("/home/user/.forge"),
+                forge_api_url: Url::parse("http://forgecode.dev/api").unwrap(),
+                retry_config: Default::default(),
+                max_search_lines: 25,
+                fetch_truncation_limit: 55,
+                stdout_max_prefix_length: 10,
+                stdout_max_suffix_length: 10,
+                max_read_size: 10,
+                http: Default::default(),
+                max_file_size: 256 << 10,
+            }
+        }
+
+        fn get_env_var(&self, _key: &str) -> Option<String> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_is_cached_per_provider() {
+        let fixture = ForgeProviderService::new(Arc::new(MockEnvironmentInfra));
+        let local = Provider::ollama("http://localhost:11434");
+        let cloud = Provider::anthropic("anthropic-key");
+
+        fixture.client(local.clone()).await.unwrap();
+        fixture.client(cloud.clone()).await.unwrap();
+        fixture.client(local.clone()).await.unwrap();
+
+        let clients = fixture.cached_clients.lock().await;
+        let actual = (
+            clients.len(),
+            clients.contains_key(&local),
+            clients.contains_key(&cloud),
+        );
+        assert_eq!(actual, (2, true, true));
+    }
+
+    #[test]
+    fn test_admission_tracks_configured_local_providers() {
+        let fixture = ForgeProviderService::new(Arc::new(MockEnvironmentInfra));
+
+        let actual = ["ollama", "lmstudio"].map(|name| fixture.admission.capacity(name));
+
+        assert_eq!(actual, [Some(4), None]);
+    }
 }
@@ -1,16 +1,31 @@
 use std::sync::Arc;
 
 use anyhow::Context;
-use forge_app::domain::{Provider, ProviderUrl};
+use forge_app::domain::{ModelId, Provider, ProviderUrl};
 use forge_app::{AppConfig, ProviderRegistry};
 use forge_provider::config::fallback::FallbackConfig;
 use forge_provider::config::local_ai::LocalAiConfig;
-use forge_provider::selection::{ProviderSelector, ProviderType, SelectionContext};
+use forge_provider::selection::{
+    ContextLengths, ProviderSelection, ProviderSelector, ProviderType, SelectionContext,
+};
 use tokio::sync::RwLock;
+use tracing::warn;
 
 use crate::EnvironmentInfra;
 
-type ProviderSearch = (&'static str, Box<dyn FnOnce(&str) -> Provider>);
+/// Environment variable holding a cloud API key, the name provider selection
+/// knows the provider by, and how to build the provider from the key
+type ProviderSearch = (&'static str, &'static str, fn(&str) -> Provider);
+
+/// Cloud providers configured through the environment, in order of preference
+const ENV_PROVIDERS: [ProviderSearch; 6] = [
+    ("FORGE_KEY", "forge", Provider::forge),
+    ("OPENROUTER_API_KEY", "openrouter", Provider::open_router),
+    ("REQUESTY_API_KEY", "requesty", Provider::requesty),
+    ("XAI_API_KEY", "xai", Provider::xai),
+    ("OPENAI_API_KEY", "openai", Provider::openai),
+    ("ANTHROPIC_API_KEY", "anthropic", Provider::anthropic),
+];
 
 pub struct ForgeProviderRegistry<F> {
     infra: Arc<F>,
@@ -18,6 +33,7 @@ pub struct ForgeProviderRegistry<F> {
     // session. This helps to keep the user logged in for current session.
     cache: Arc<RwLock<Option<Provider>>>,
     provider_selector: Arc<RwLock<Option<ProviderSelector>>>,
+    context_lengths: ContextLengths,
 }
 
 impl<F: EnvironmentInfra> ForgeProviderRegistry<F> {
@@ -26,9 +42,17 @@ impl<F: EnvironmentInfra> ForgeProviderRegistry<F> {
             infra,
             cache: Arc::new(Default::default()),
             provider_selector: Arc::new(Default::default()),
+            context_lengths: ContextLengths::default(),
         }
     }
 
+    /// Keep long prompts away from local models whose context windows, as
+    /// reported to `context_lengths` by model discovery, cannot hold them
+    pub fn with_context_lengths(mut self, context_lengths: ContextLengths) -> Self {
+        self.context_lengths = context_lengths;
+        self
+    }
+
     fn provider_url(&self) -> Option<ProviderUrl> {
         if let Some(url) = self.infra.get_env_var("OPENAI_URL") {
             return Some(ProviderUrl::OpenAI(url));
@@ -48,8 +72,13 @@ impl<F: EnvironmentInfra> ForgeProviderRegistry<F> {
         resolve_env_provider(self.provider_url(), self.infra.as_ref())
     }
     async fn ensure_provider_selector(&self, app_config: &AppConfig) -> anyhow::Result<()> {
-        let mut selector_guard = self.provider_selector.write().await;
+        if self.provider_selector.read().await.is_some() {
+            return Ok(());
+        }
 
+        // Checked again under the write lock so only one selector, and one
+        // health monitor, is ever started
+        let mut selector_guard = self.provider_selector.write().await;
         if selector_guard.is_none() {
             // Create local AI config
             let local_config = if let Some(local_ai_config) = &app_config.local_ai {
@@ -62,27 +91,24 @@ impl<F: EnvironmentInfra> ForgeProviderRegistry<F> {
                 LocalAiConfig::with_default_ollama()
             };
 
-            // Create fallback config with environment-based cloud providers
-            let mut cloud_providers = Vec::new();
-
-            // Add cloud providers based on environment variables
-            if let Some(forge_key) = &app_config.key_info {
-                let provider = Provider::forge(forge_key.api_key.as_str());
-                let provider = override_url(provider, self.provider_url());
-                cloud_providers.push("forge".to_string());
-            } else if let Some(_provider) =
-                resolve_env_provider(self.provider_url(), self.infra.as_ref())
-            {
-                cloud_providers.push("openai".to_string()); // Default cloud
-                                                            // provider
-            }
+            // Create fallback config with every cloud provider that has a key
+            let cloud_providers = self
+                .cloud_credentials(app_config)
+                .into_iter()
+                .map(|(name, _)| name.to_string())
+                .collect::<Vec<_>>();
 
             let fallback_config = FallbackConfig::default().cloud_providers(cloud_providers);
 
-            // Create the enhanced provider selector
-            let selector = ProviderSelector::new(local_config, fallback_config)
+            // Create the enhanced provider selector and start its health monitor
+            let mut selector = ProviderSelector::new(local_config, fallback_config)
                 .await
-                .context("Failed to create provider selector")?;
+                .context("Failed to create provider selector")?
+                .with_context_lengths(self.context_lengths.clone());
+            selector
+                .initialize()
+                .await
+                .context("Failed to initialize provider selector")?;
 
             *selector_guard = Some(selector);
         }
@@ -90,49 +116,102 @@ impl<F: EnvironmentInfra> ForgeProviderRegistry<F> {
         Ok(())
     }
 
+    /// Provider selection chose for `context`, with the model it should be
+    /// asked for instead of the requested one, if any
     async fn get_provider_enhanced(
         &self,
         app_config: AppConfig,
-    ) -> anyhow::Result<Option<Provider>> {
+        context: SelectionContext,
+    ) -> anyhow::Result<Option<(Provider, Option<ModelId>)>> {
         // Ensure provider selector is initialized
         self.ensure_provider_selector(&app_config).await?;
 
-        let mut selector_guard = self.provider_selector.write().await;
-        if let Some(ref mut selector) = *selector_guard {
-            // Create a default selection context
-            let context = SelectionContext {
-                model_id: "default".to_string(),
-                requires_streaming: false,
-                requires_tools: false,
-                user_preferences: None,
-                previous_provider: None,
-                consecutive_failures: 0,
-            };
+        // Select under the read lock so concurrent requests do not wait on
+        // each other; the write lock is only taken to record the selection
+        let selector_guard = self.provider_selector.read().await;
+        let Some(selector) = selector_guard.as_ref() else {
+            return Ok(self.fallback_without_override(app_config));
+        };
+        let selection = match selector.select_provider_shared(context).await {
+            Ok(selection) => selection,
+            // Fall back to environment-based selection
+            Err(_) => return Ok(self.fallback_without_override(app_config)),
+        };
+        let selected =
+            self.selected_provider(selection.clone(), selector.local_config(), &app_config);
+        drop(selector_guard);
 
-            // Use enhanced provider selection
-            match selector.select_provider(context).await {
-                Ok(selection) => {
-                    // Convert the selection to a Provider
-                    match selection.provider_type {
-                        ProviderType::Local => {
-                            // For local providers, create an Ollama provider
-                            Ok(Some(Provider::Ollama {
-                                url: reqwest::Url::parse("http://localhost:11434").unwrap(),
-                            }))
-                        }
-                        ProviderType::Cloud => {
-                            // For cloud providers, use the existing logic
-                            Ok(self.get_provider_fallback(app_config))
-                        }
-                    }
-                }
-                Err(_) => {
-                    // Fall back to environment-based selection
-                    Ok(self.get_provider_fallback(app_config))
+        if let Some(selector) = self.provider_selector.write().await.as_mut() {
+            selector.commit_selection(&selection);
+        }
+
+        match selected {
+            Some(selected) => Ok(Some(selected)),
+            None => Ok(self.fallback_without_override(app_config)),
+        }
+    }
+
+    /// Convert `selection` to a Provider, paired with the model the
+    /// selection substitutes for the requested one
+    fn selected_provider(
+        &self,
+        selection: ProviderSelection,
+        local_config: &LocalAiConfig,
+        app_config: &AppConfig,
+    ) -> Option<(Provider, Option<ModelId>)> {
+        let provider = match selection.provider_type {
+            ProviderType::Local => match selection.local_provider(local_config) {
+                Ok(provider) => provider,
+                Err(error) => {
+                    warn!(provider = %selection.provider_name, error = %error, "Cannot build selected local provider");
+                    return None;
                 }
+            },
+            ProviderType::Cloud => self.cloud_provider(&selection, app_config)?,
+        };
+        Some((provider, selection.model_override.map(ModelId::new)))
+    }
+
+    /// The environment provider, which keeps the requested model: a
+    /// selection's override names a model of the provider it selected
+    fn fallback_without_override(
+        &self,
+        app_config: AppConfig,
+    ) -> Option<(Provider, Option<ModelId>)> {
+        self.get_provider_fallback(app_config)
+            .map(|provider| (provider, None))
+    }
+
+    /// Cloud providers selection may choose from, with the key each one
+    /// authenticates with. A logged-in user always goes through Forge.
+    fn cloud_credentials(&self, app_config: &AppConfig) -> Vec<(&'static str, String)> {
+        if let Some(forge_key) = &app_config.key_info {
+            return vec![("forge", forge_key.api_key.clone())];
+        }
+        ENV_PROVIDERS
+            .iter()
+            .filter_map(|(var, name, _)| self.infra.get_env_var(var).map(|key| (*name, key)))
+            .collect()
+    }
+
+    /// Build the cloud provider `selection` chose, authenticated with its key
+    /// and honouring any URL override
+    fn cloud_provider(
+        &self,
+        selection: &ProviderSelection,
+        app_config: &AppConfig,
+    ) -> Option<Provider> {
+        let name = selection.provider_name.strip_prefix("cloud:")?;
+        let (_, key) = self
+            .cloud_credentials(app_config)
+            .into_iter()
+            .find(|(candidate, _)| *candidate == name)?;
+        match selection.cloud_provider(&key) {
+            Ok(provider) => Some(override_url(provider, self.provider_url())),
+            Err(error) => {
+                warn!(provider = %selection.provider_name, error = %error, "Cannot build selected cloud provider");
+                None
             }
-        } else {
-            Ok(self.get_provider_fallback(app_config))
         }
     }
 
@@ -153,8 +232,9 @@ impl<F: EnvironmentInfra> ProviderRegistry for ForgeProviderRegistry<F> {
         }
 
         // Try enhanced provider selection first
-        let provider = match self.get_provider_enhanced(config.clone()).await? {
-            Some(provider) => provider,
+        let context = SelectionContext::new("default".to_string());
+        let provider = match self.get_provider_enhanced(config.clone(), context).await? {
+            Some((provider, _)) => provider,
             None => {
                 // Fall back to the old logic if enhanced selection fails
                 self.get_provider_fallback(config)
@@ -165,22 +245,31 @@ impl<F: EnvironmentInfra> ProviderRegistry for ForgeProviderRegistry<F> {
         self.cache.write().await.replace(provider.clone());
         Ok(provider)
     }
+
+    async fn get_provider_for(
+        &self,
+        config: AppConfig,
+        model: &ModelId,
+        prompt_tokens: usize,
+    ) -> anyhow::Result<(Provider, ModelId)> {
+        // Selected per request, so a prompt too long for the local model's
+        // context window goes elsewhere
+        let context =
+            SelectionContext::new(model.as_str().to_string()).with_prompt_tokens(prompt_tokens);
+        match self.get_provider_enhanced(config.clone(), context).await? {
+            Some((provider, model_override)) => {
+                Ok((provider, model_override.unwrap_or_else(|| model.clone())))
+            }
+            None => Ok((self.get_provider(config).await?, model.clone())),
+        }
+    }
 }
 
 fn resolve_env_provider<F: EnvironmentInfra>(
     url: Option<ProviderUrl>,
     env: &F,
 ) -> Option<Provider> {
-    let keys: [ProviderSearch; 6] = [
-        ("FORGE_KEY", Box::new(Provider::forge)),
-        ("OPENROUTER_API_KEY", Box::new(Provider::open_router)),
-        ("REQUESTY_API_KEY", Box::new(Provider::requesty)),
-        ("XAI_API_KEY", Box::new(Provider::xai)),
-        ("OPENAI_API_KEY", Box::new(Provider::openai)),
-        ("ANTHROPIC_API_KEY", Box::new(Provider::anthropic)),
-    ];
-
-    keys.into_iter().find_map(|(key, fun)| {
+    ENV_PROVIDERS.into_iter().find_map(|(key, _, fun)| {
         env.get_env_var(key).map(|key| {
             let provider = fun(&key);
             override_url(provider, url.clone())
@@ -194,3 +283,131 @@ fn override_url(mut provider: Provider, url: Option<ProviderUrl>) -> Provider {
     }
     provider
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use forge_app::domain::Environment;
+    use pretty_assertions::assert_eq;
+    use url::Url;
+
+    use super::*;
+
+    struct MockEnvironmentInfra {
+        env: HashMap<String, String>,
+    }
+
+    impl EnvironmentInfra for MockEnvironmentInfra {
+        fn get_environment(&self) -> Environment {
+            Environment {
+                os: "linux".to_string(),
+                pid: 12345,
+                cwd: PathBuf::from("/home/user/project"),
+                home: Some(PathBuf::from("/home/user")),
+                shell: "/bin/bash".to_string(),
+                base_path: PathBuf::from("/home/user/.forge"),
+                forge_api_url: Url::parse("http://forgecode.dev/api").unwrap(),
+                retry_config: Default::default(),
+                max_search_lines: 25,
+                fetch_truncation_limit: 55,
+                stdout_max_prefix_length: 10,
+                stdout_max_suffix_length: 10,
+                max_read_size: 10,
+                http: Default::default(),
+                max_file_size: 256 << 10,
+            }
+        }
+
+        fn get_env_var(&self, key: &str) -> Option<String> {
+            self.env.get(key).cloned()
+        }
+    }
+
+    fn fixture(env: &[(&str, &str)]) -> ForgeProviderRegistry<MockEnvironmentInfra> {
+        let env = env
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        ForgeProviderRegistry::new(Arc::new(MockEnvironmentInfra { env }))
+    }
+
+    fn cloud_selection(provider_name: &str) -> ProviderSelection {
+        ProviderSelection {
+            provider_name: provider_name.to_string(),
+            provider_type: ProviderType::Cloud,
+            reason: "Local providers unavailable".to_string(),
+            is_fallback: true,
+            local_health: None,
+            model_override: None,
+            request_id: None,
+        }
+    }
+
+    #[test]
+    fn test_cloud_selection_builds_selected_provider() {
+        let fixture = fixture(&[
+            ("OPENAI_API_KEY", "openai-key"),
+            ("ANTHROPIC_API_KEY", "anthropic-key"),
+        ]);
+
+        let actual = ["cloud:anthropic", "cloud:openai", "cloud:xai"]
+            .map(|name| fixture.cloud_provider(&cloud_selection(name), &AppConfig::default()));
+
+        let expected = [
+            Some(Provider::anthropic("anthropic-key")),
+            Some(Provider::openai("openai-key")),
+            None,
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_selected_provider_carries_model_override() {
+        let fixture = fixture(&[("ANTHROPIC_API_KEY", "anthropic-key")]);
+        let local_config = LocalAiConfig::with_default_ollama();
+        let local = ProviderSelection {
+            provider_name: "ollama".to_string(),
+            provider_type: ProviderType::Local,
+            model_override: Some("qwen2.5:0.5b".to_string()),
+            ..cloud_selection("ollama")
+        };
+        let cloud = ProviderSelection {
+            model_override: Some("claude-3-5-haiku".to_string()),
+            ..cloud_selection("cloud:anthropic")
+        };
+
+        let actual = [local, cloud, cloud_selection("cloud:anthropic")].map(|selection| {
+            fixture.selected_provider(selection, &local_config, &AppConfig::default())
+        });
+
+        let expected = [
+            Some((
+                Provider::ollama("http://localhost:11434"),
+                Some(ModelId::new("qwen2.5:0.5b")),
+            )),
+            Some((
+                Provider::anthropic("anthropic-key"),
+                Some(ModelId::new("claude-3-5-haiku")),
+            )),
+            Some((Provider::anthropic("anthropic-key"), None)),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_get_provider_follows_cloud_selection() {
+        let fixture = fixture(&[("ANTHROPIC_API_KEY", "anthropic-key")]);
+        let config = AppConfig {
+            local_ai: Some(serde_json::from_value(serde_json::json!({"enabled": false})).unwrap()),
+            ..Default::default()
+        };
+
+        let actual = ProviderRegistry::get_provider(&fixture, config)
+            .await
+            .unwrap();
+
+        assert_eq!(actual, Provider::anthropic("anthropic-key"));
+    }
+}
@@ -4,6 +4,7 @@ mod dispatch;
 mod error;
 mod event;
 mod log;
+pub mod log_rotation;
 pub use can_track::VERSION;
 pub use dispatch::Tracker;
 use error::Result;
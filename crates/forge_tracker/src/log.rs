@@ -5,6 +5,7 @@ use tracing_appender::non_blocking::{self, WorkerGuard};
 use tracing_subscriber::{self};
 
 use crate::can_track::can_track;
+use crate::log_rotation::{LogRotationConfig, RotatingFileWriter};
 use crate::Tracker;
 
 pub fn init_tracing(log_path: PathBuf, tracker: Tracker) -> anyhow::Result<Guard> {
@@ -12,7 +13,7 @@ pub fn init_tracing(log_path: PathBuf, tracker: Tracker) -> anyhow::Result<Guard
 
     // If tracking is enabled, use PostHog for logging; otherwise, use a rolling
     // file appender.
-    let (writer, guard, level) = prepare_writer(log_path, tracker);
+    let (writer, guard, level) = prepare_writer(log_path, tracker)?;
 
     tracing_subscriber::fmt()
         .json()
@@ -32,11 +33,11 @@ pub fn init_tracing(log_path: PathBuf, tracker: Tracker) -> anyhow::Result<Guard
 fn prepare_writer(
     log_path: PathBuf,
     tracker: Tracker,
-) -> (
+) -> anyhow::Result<(
     non_blocking::NonBlocking,
     WorkerGuard,
     tracing_subscriber::EnvFilter,
-) {
+)> {
     let ((non_blocking, guard), env) = if can_track() {
         let append = PostHogWriter::new(tracker);
         (
@@ -44,13 +45,15 @@ fn prepare_writer(
             tracing_subscriber::EnvFilter::new("forge=info"),
         )
     } else {
-        let append = tracing_appender::rolling::daily(log_path, "forge.log");
+        // Rotated daily or at 10 MB, keeping the last five files
+        let append =
+            RotatingFileWriter::open(log_path.join("forge.log"), LogRotationConfig::default())?;
         (
             tracing_appender::non_blocking(append),
             tracing_subscriber::EnvFilter::new("forge=debug"),
         )
     };
-    (non_blocking, guard, env)
+    Ok((non_blocking, guard, env))
 }
 
 pub struct Guard(#[allow(dead_code)] WorkerGuard);
@@ -0,0 +1,296 @@
+//! Size- and age-based rotation for log files
+//!
+//! Long-running deployments append log records indefinitely. A
+//! [`RotatingFileWriter`] rolls the active file over once it exceeds a size or
+//! age limit, optionally gzips the rotated file, and keeps only a bounded
+//! number of rotated files. Each sink owns a writer with its own
+//! [`LogRotationConfig`]; the tracing log file is one such sink.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::Context;
+use derive_setters::Setters;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+/// Rotation and retention settings for one log sink
+#[derive(Debug, Clone, Serialize, Deserialize, Setters)]
+#[setters(strip_option, into)]
+pub struct LogRotationConfig {
+    /// Rotate once the active file reaches this size
+    pub max_size_bytes: Option<u64>,
+    /// Rotate once the active file is this old
+    pub max_age: Option<Duration>,
+    /// Rotated files to keep; older ones are deleted
+    pub max_files: usize,
+    /// Gzip rotated files
+    pub compress: bool,
+}
+
+impl Default for LogRotationConfig {
+    fn default() -> Self {
+        Self {
+            max_size_bytes: Some(10 * 1024 * 1024),           // 10 MB
+            max_age: Some(Duration::from_secs(24 * 60 * 60)), // daily
+            max_files: 5,
+            compress: false,
+        }
+    }
+}
+
+/// Line-oriented log writer that rotates its file according to a
+/// [`LogRotationConfig`]. As an [`io::Write`] every write is treated as one
+/// whole record, which is how the tracing formatter writes events.
+#[derive(Debug)]
+pub struct RotatingFileWriter {
+    path: PathBuf,
+    config: LogRotationConfig,
+    file: File,
+    size: u64,
+    opened_at: SystemTime,
+}
+
+impl RotatingFileWriter {
+    /// Open `path` for appending, creating it if needed
+    pub fn open(path: impl Into<PathBuf>, config: LogRotationConfig) -> anyhow::Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create log directory {}", parent.display()))?;
+        }
+
+        let file = open_append(&path)?;
+        let metadata = file.metadata()?;
+        let opened_at = metadata
+            .created()
+            .or_else(|_| metadata.modified())
+            .unwrap_or_else(|_| SystemTime::now());
+
+        Ok(Self { path, config, file, size: metadata.len(), opened_at })
+    }
+
+    /// Path of the active log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append `record` as a line, rotating first if the file is due
+    pub fn write_record(&mut self, record: &str) -> anyhow::Result<()> {
+        self.write_record_at(record, SystemTime::now())
+    }
+
+    /// Append `record` as a line at `now`, rotating first if the file is due
+    pub fn write_record_at(&mut self, record: &str, now: SystemTime) -> anyhow::Result<()> {
+        let len = record.len() as u64 + 1;
+        if self.should_rotate(len, now) {
+            self.rotate_at(now)?;
+        }
+
+        writeln!(self.file, "{record}")
+            .with_context(|| format!("Failed to write to {}", self.path.display()))?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn should_rotate(&self, incoming: u64, now: SystemTime) -> bool {
+        if self.size == 0 {
+            return false;
+        }
+        let too_large = self
+            .config
+            .max_size_bytes
+            .is_some_and(|max| self.size + incoming > max);
+        let too_old = self.config.max_age.is_some_and(|max| {
+            now.duration_since(self.opened_at)
+                .is_ok_and(|age| age >= max)
+        });
+        too_large || too_old
+    }
+
+    /// Roll the active file over to `<path>.1`, shifting older rotations up
+    /// and deleting any beyond the retention count
+    pub fn rotate_at(&mut self, now: SystemTime) -> anyhow::Result<()> {
+        self.file.flush()?;
+
+        if self.config.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let oldest = self.rotated_path(self.config.max_files);
+            remove_if_exists(&oldest)?;
+            for index in (1..self.config.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+
+            let rotated = self.rotated_path(1);
+            if self.config.compress {
+                compress(&self.path, &rotated)?;
+                fs::remove_file(&self.path)?;
+            } else {
+                fs::rename(&self.path, &rotated)?;
+            }
+            debug!(from = %self.path.display(), to = %rotated.display(), "Rotated log file");
+        }
+
+        self.file = open_append(&self.path)?;
+        self.size = 0;
+        self.opened_at = now;
+        info!(
+            path = %self.path.display(),
+            max_files = self.config.max_files,
+            "Log file rotated"
+        );
+        Ok(())
+    }
+
+    /// Path of the `index`th most recent rotated file
+    pub fn rotated_path(&self, index: usize) -> PathBuf {
+        let extension = if self.config.compress { ".gz" } else { "" };
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{index}{extension}"));
+        PathBuf::from(name)
+    }
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len() as u64;
+        if self.should_rotate(len, SystemTime::now()) {
+            self.rotate_at(SystemTime::now())
+                .map_err(io::Error::other)?;
+        }
+
+        self.file.write_all(buf)?;
+        self.size += len;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn open_append(path: &Path) -> anyhow::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open log file {}", path.display()))
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn compress(source: &Path, destination: &Path) -> anyhow::Result<()> {
+    let mut input = File::open(source)?;
+    let mut encoder = GzEncoder::new(File::create(destination)?, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn record(i: usize) -> String {
+        format!(r#"{{"event":"provider_selected","seq":{i:04}}}"#)
+    }
+
+    #[test]
+    fn test_size_rotation_enforces_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = LogRotationConfig::default()
+            .max_size_bytes(100u64)
+            .max_files(2usize);
+        let mut fixture = RotatingFileWriter::open(dir.path().join("audit.log"), config).unwrap();
+
+        // Each record is 41 bytes, so every file holds two records
+        for i in 0..8 {
+            fixture.write_record(&record(i)).unwrap();
+        }
+
+        let actual = vec![
+            fs::read_to_string(fixture.path()).unwrap(),
+            fs::read_to_string(fixture.rotated_path(1)).unwrap(),
+            fs::read_to_string(fixture.rotated_path(2)).unwrap(),
+        ];
+        let expected = vec![
+            format!("{}\n{}\n", record(6), record(7)),
+            format!("{}\n{}\n", record(4), record(5)),
+            format!("{}\n{}\n", record(2), record(3)),
+        ];
+        assert_eq!(actual, expected);
+        assert!(!fixture.rotated_path(3).exists());
+    }
+
+    #[test]
+    fn test_io_writes_rotate_as_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = LogRotationConfig::default()
+            .max_size_bytes(100u64)
+            .max_files(1usize);
+        let mut fixture = RotatingFileWriter::open(dir.path().join("forge.log"), config).unwrap();
+
+        for i in 0..3 {
+            fixture
+                .write_all(format!("{}\n", record(i)).as_bytes())
+                .unwrap();
+        }
+
+        let actual = vec![
+            fs::read_to_string(fixture.path()).unwrap(),
+            fs::read_to_string(fixture.rotated_path(1)).unwrap(),
+        ];
+        let expected = vec![
+            format!("{}\n", record(2)),
+            format!("{}\n{}\n", record(0), record(1)),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_age_rotation_compresses_rotated_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = LogRotationConfig::default()
+            .max_age(Duration::from_secs(60))
+            .compress(true);
+        let mut fixture = RotatingFileWriter::open(dir.path().join("events.log"), config).unwrap();
+        let start = SystemTime::now();
+
+        fixture.write_record_at(&record(0), start).unwrap();
+        fixture
+            .write_record_at(&record(1), start + Duration::from_secs(120))
+            .unwrap();
+
+        let mut actual = String::new();
+        GzDecoder::new(File::open(fixture.rotated_path(1)).unwrap())
+            .read_to_string(&mut actual)
+            .unwrap();
+        assert_eq!(actual, format!("{}\n", record(0)));
+        assert!(fixture.rotated_path(1).ends_with("events.log.1.gz"));
+        assert_eq!(
+            fs::read_to_string(fixture.path()).unwrap(),
+            format!("{}\n", record(1))
+        );
+    }
+}